@@ -2,9 +2,10 @@
 
 use clap::Args;
 use reth_primitives::{TxHash, B256};
+use reth_provider::DEFAULT_MAX_BAD_BLOCKS;
 
 /// Parameters for debugging purposes
-#[derive(Debug, Args, PartialEq, Default)]
+#[derive(Debug, Args, PartialEq)]
 #[clap(next_help_heading = "Debug")]
 pub struct DebugArgs {
     /// Prompt the downloader to download blocks one at a time.
@@ -57,6 +58,31 @@ pub struct DebugArgs {
         conflicts_with = "hook_transaction"
     )]
     pub hook_all: bool,
+
+    /// The maximum number of rejected blocks kept in memory and served by
+    /// `debug_getBadBlocks`.
+    #[arg(
+        long = "debug.max-bad-blocks",
+        help_heading = "Debug",
+        default_value_t = DEFAULT_MAX_BAD_BLOCKS
+    )]
+    pub max_bad_blocks: usize,
+}
+
+impl Default for DebugArgs {
+    fn default() -> Self {
+        Self {
+            continuous: false,
+            terminate: false,
+            tip: None,
+            max_block: None,
+            print_inspector: false,
+            hook_block: None,
+            hook_transaction: None,
+            hook_all: false,
+            max_bad_blocks: DEFAULT_MAX_BAD_BLOCKS,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -8,6 +8,10 @@ pub use network_args::{DiscoveryArgs, NetworkArgs};
 mod rpc_server_args;
 pub use rpc_server_args::RpcServerArgs;
 
+/// RpcRateLimitArgs struct for configuring the RPC rate limit
+mod rpc_rate_limit_args;
+pub use rpc_rate_limit_args::RpcRateLimitArgs;
+
 /// RpcStateCacheArgs struct for configuring RPC state cache
 mod rpc_state_cache_args;
 pub use rpc_state_cache_args::RpcStateCacheArgs;
@@ -4,11 +4,16 @@ use crate::version::P2P_CLIENT_VERSION;
 use clap::Args;
 use reth_config::Config;
 use reth_discv4::{DEFAULT_DISCOVERY_ADDR, DEFAULT_DISCOVERY_PORT};
+use reth_discv5::{Discv5Config, DEFAULT_DISCOVERY_V5_PORT};
 use reth_net_nat::NatResolver;
-use reth_network::{HelloMessageWithProtocols, NetworkConfigBuilder};
+use reth_network::{HelloMessageWithProtocols, NetworkConfigBuilder, NetworkPermissions};
 use reth_primitives::{mainnet_nodes, ChainSpec, NodeRecord};
 use secp256k1::SecretKey;
-use std::{net::Ipv4Addr, path::PathBuf, sync::Arc};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
 
 /// Parameters for configuring the network more granularity via CLI
 #[derive(Debug, Args, PartialEq, Eq)]
@@ -28,6 +33,25 @@ pub struct NetworkArgs {
     #[arg(long)]
     pub trusted_only: bool,
 
+    /// The path to a JSON file with the enode URLs of the nodes that are allowed to connect.
+    ///
+    /// Enables the permissioned network mode: sessions with nodes that are not allow-listed are
+    /// rejected. The allowlist can be changed at runtime with `admin_addAllowedPeer` and
+    /// `admin_removeAllowedPeer`.
+    #[arg(long = "permissions.allowlist", value_name = "FILE", verbatim_doc_comment)]
+    pub permissions_allowlist: Option<PathBuf>,
+
+    /// Rejects incoming connections from addresses of nodes that are not allow-listed before the
+    /// handshake.
+    ///
+    /// Only use this if all allow-listed nodes connect from the address of their enode URL.
+    #[arg(
+        long = "permissions.ip-filter",
+        requires = "permissions_allowlist",
+        verbatim_doc_comment
+    )]
+    pub permissions_ip_filter: bool,
+
     /// Comma separated enode URLs for P2P discovery bootstrap.
     ///
     /// Will fall back to a network-specific default if not specified.
@@ -55,7 +79,7 @@ pub struct NetworkArgs {
     pub no_persist_peers: bool,
 
     #[allow(rustdoc::invalid_html_tags)]
-    /// NAT resolution method (any|none|upnp|publicip|extip:<IP>)
+    /// NAT resolution method (any|none|upnp|publicip|extip:<IP>|natpmp[:<IP>]|pcp[:<IP>])
     #[arg(long, default_value = "any")]
     pub nat: NatResolver,
 
@@ -74,6 +98,21 @@ pub struct NetworkArgs {
     /// Maximum number of inbound requests. default: 30
     #[arg(long)]
     pub max_inbound_peers: Option<usize>,
+
+    /// Propagate blocks with `NewBlock` and `NewBlockHashes` messages.
+    ///
+    /// Announced blocks are validated, relayed to peers and inserted into the blockchain tree.
+    /// This is intended for private chains that are not driven by a beacon node.
+    #[arg(long, verbatim_doc_comment)]
+    pub block_gossip: bool,
+
+    /// Serve the state to peers over the `snap/1` protocol.
+    ///
+    /// Only the state of the last fully synced block is served, so peers can only snap sync
+    /// from this node while it is at the tip. Without this flag, requests of peers are answered
+    /// with empty responses.
+    #[arg(long = "snap.serve", verbatim_doc_comment)]
+    pub snap_serve: bool,
 }
 
 impl NetworkArgs {
@@ -115,6 +154,24 @@ impl NetworkArgs {
         self.discovery.apply_to_builder(network_config_builder)
     }
 
+    /// Returns the allowlist of the permissioned network mode, loaded from the
+    /// `--permissions.allowlist` file.
+    ///
+    /// Permissioning is disabled if no file is configured.
+    pub fn permissions(&self) -> std::io::Result<NetworkPermissions> {
+        match &self.permissions_allowlist {
+            Some(path) => {
+                let permissions = NetworkPermissions::allowlist_from_file(path)?;
+                Ok(if self.permissions_ip_filter {
+                    permissions.with_ip_filter()
+                } else {
+                    permissions
+                })
+            }
+            None => Ok(NetworkPermissions::default()),
+        }
+    }
+
     /// If `no_persist_peers` is true then this returns the path to the persistent peers file path.
     pub fn persistent_peers_file(&self, peers_file: PathBuf) -> Option<PathBuf> {
         if self.no_persist_peers {
@@ -131,6 +188,8 @@ impl Default for NetworkArgs {
             discovery: DiscoveryArgs::default(),
             trusted_peers: vec![],
             trusted_only: false,
+            permissions_allowlist: None,
+            permissions_ip_filter: false,
             bootnodes: None,
             peers_file: None,
             identity: P2P_CLIENT_VERSION.to_string(),
@@ -141,6 +200,8 @@ impl Default for NetworkArgs {
             port: DEFAULT_DISCOVERY_PORT,
             max_outbound_peers: None,
             max_inbound_peers: None,
+            block_gossip: false,
+            snap_serve: false,
         }
     }
 }
@@ -160,6 +221,12 @@ pub struct DiscoveryArgs {
     #[arg(long, conflicts_with = "disable_discovery")]
     pub disable_discv4_discovery: bool,
 
+    /// Enable Discv5 discovery.
+    ///
+    /// Discv5 runs alongside discv4 on its own UDP port.
+    #[arg(long, conflicts_with = "disable_discovery")]
+    pub enable_discv5_discovery: bool,
+
     /// The UDP address to use for P2P discovery/networking
     #[arg(long = "discovery.addr", name = "discovery.addr", value_name = "DISCOVERY_ADDR", default_value_t = DEFAULT_DISCOVERY_ADDR)]
     pub addr: Ipv4Addr,
@@ -167,6 +234,15 @@ pub struct DiscoveryArgs {
     /// The UDP port to use for P2P discovery/networking
     #[arg(long = "discovery.port", name = "discovery.port", value_name = "DISCOVERY_PORT", default_value_t = DEFAULT_DISCOVERY_PORT)]
     pub port: u16,
+
+    /// The UDP port to use for discv5, if enabled with `--enable-discv5-discovery`
+    #[arg(
+        long = "discovery.v5.port",
+        name = "discovery.v5.port",
+        value_name = "DISCOVERY_V5_PORT",
+        default_value_t = DEFAULT_DISCOVERY_V5_PORT
+    )]
+    pub discv5_port: u16,
 }
 
 impl DiscoveryArgs {
@@ -182,6 +258,12 @@ impl DiscoveryArgs {
         if self.disable_discovery || self.disable_discv4_discovery {
             network_config_builder = network_config_builder.disable_discv4_discovery();
         }
+
+        if self.enable_discv5_discovery && !self.disable_discovery {
+            let mut discv5 = Discv5Config::builder();
+            discv5.listen_addr(SocketAddr::from((self.addr, self.discv5_port)));
+            network_config_builder = network_config_builder.discovery_v5(discv5);
+        }
         network_config_builder
     }
 }
@@ -192,8 +274,10 @@ impl Default for DiscoveryArgs {
             disable_discovery: false,
             disable_dns_discovery: false,
             disable_discv4_discovery: false,
+            enable_discv5_discovery: false,
             addr: DEFAULT_DISCOVERY_ADDR,
             port: DEFAULT_DISCOVERY_PORT,
+            discv5_port: DEFAULT_DISCOVERY_V5_PORT,
         }
     }
 }
@@ -257,6 +341,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_discv5_args() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert!(!args.discovery.enable_discv5_discovery);
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--enable-discv5-discovery",
+            "--discovery.v5.port",
+            "9200",
+        ])
+        .args;
+        assert!(args.discovery.enable_discv5_discovery);
+        assert_eq!(args.discovery.discv5_port, 9200);
+
+        assert!(CommandParser::<NetworkArgs>::try_parse_from([
+            "reth",
+            "--enable-discv5-discovery",
+            "--disable-discovery",
+        ])
+        .is_err());
+    }
+
     #[test]
     fn network_args_default_sanity_test() {
         let default_args = NetworkArgs::default();
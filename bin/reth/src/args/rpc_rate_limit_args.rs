@@ -0,0 +1,153 @@
+use clap::{builder::RangedU64ValueParser, Args};
+use hyper::header::HeaderName;
+use reth_rpc::RateLimitConfig;
+
+/// The default number of tokens a client can spend at once.
+const DEFAULT_BURST: u32 = 1_000;
+
+/// The default number of tokens a client is credited per second.
+const DEFAULT_PER_SECOND: u32 = 100;
+
+/// Parameters to configure the rate limit of the http and ws RPC servers.
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "RPC Rate Limit")]
+pub struct RpcRateLimitArgs {
+    /// Enable rate limiting of the http and ws servers.
+    ///
+    /// Every client has a budget of tokens that is refilled continuously, each call costs the
+    /// weight of its method. Requests that exceed the budget are rejected with a JSON-RPC error
+    /// and a `Retry-After` header.
+    #[arg(long = "rpc.ratelimit")]
+    pub enabled: bool,
+
+    /// Number of tokens a client can spend at once.
+    #[arg(
+        long = "rpc.ratelimit.burst",
+        value_name = "TOKENS",
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..),
+        default_value_t = DEFAULT_BURST,
+    )]
+    pub burst: u32,
+
+    /// Number of tokens a client is credited per second.
+    #[arg(
+        long = "rpc.ratelimit.per-second",
+        value_name = "TOKENS",
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..),
+        default_value_t = DEFAULT_PER_SECOND,
+    )]
+    pub per_second: u32,
+
+    /// Comma separated method weights that override the defaults, e.g.
+    /// `eth_getLogs=50,debug_*=100`.
+    ///
+    /// Methods without weight cost 1 token.
+    #[arg(
+        long = "rpc.ratelimit.weights",
+        value_name = "METHOD=WEIGHT",
+        value_delimiter = ',',
+        value_parser = parse_method_weight
+    )]
+    pub weights: Vec<(String, u32)>,
+
+    /// Header that identifies clients by API key, e.g. `x-api-key`.
+    #[arg(long = "rpc.ratelimit.api-key-header", value_name = "HEADER")]
+    pub api_key_header: Option<HeaderName>,
+
+    /// Identify clients by the `X-Forwarded-For` or `X-Real-IP` header.
+    ///
+    /// Only enable this behind a reverse proxy that sets these headers. Otherwise clients are
+    /// identified by the IP address of their connection.
+    #[arg(long = "rpc.ratelimit.trust-forwarded")]
+    pub trust_forwarded: bool,
+}
+
+impl RpcRateLimitArgs {
+    /// Returns the rate limit config if rate limiting is enabled.
+    pub fn rate_limit_config(&self, max_request_body_size: u32) -> Option<RateLimitConfig> {
+        if !self.enabled {
+            return None
+        }
+        let mut config = RateLimitConfig {
+            burst: self.burst,
+            per_second: self.per_second,
+            api_key_header: self.api_key_header.clone(),
+            trust_forwarded_headers: self.trust_forwarded,
+            max_request_body_size,
+            ..Default::default()
+        };
+        config.method_weights.extend(self.weights.iter().cloned());
+        Some(config)
+    }
+}
+
+impl Default for RpcRateLimitArgs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            burst: DEFAULT_BURST,
+            per_second: DEFAULT_PER_SECOND,
+            weights: Vec::new(),
+            api_key_header: None,
+            trust_forwarded: false,
+        }
+    }
+}
+
+/// Parses a `METHOD=WEIGHT` pair.
+fn parse_method_weight(value: &str) -> eyre::Result<(String, u32)> {
+    let (method, weight) =
+        value.split_once('=').ok_or_else(|| eyre::eyre!("expected METHOD=WEIGHT: {value}"))?;
+    Ok((method.trim().to_string(), weight.trim().parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_rate_limit_args() {
+        let args = CommandParser::<RpcRateLimitArgs>::parse_from([
+            "reth",
+            "--rpc.ratelimit",
+            "--rpc.ratelimit.per-second",
+            "10",
+            "--rpc.ratelimit.weights",
+            "eth_getLogs=50,debug_*=100",
+            "--rpc.ratelimit.api-key-header",
+            "x-api-key",
+        ])
+        .args;
+
+        let config = args.rate_limit_config(1024).unwrap();
+        assert_eq!(config.burst, DEFAULT_BURST);
+        assert_eq!(config.per_second, 10);
+        assert_eq!(config.weight("eth_getLogs"), 50);
+        assert_eq!(config.weight("debug_traceCall"), 100);
+        assert_eq!(config.weight("eth_call"), 5);
+        assert_eq!(config.api_key_header, Some(HeaderName::from_static("x-api-key")));
+        assert_eq!(config.max_request_body_size, 1024);
+
+        assert!(CommandParser::<RpcRateLimitArgs>::try_parse_from([
+            "reth",
+            "--rpc.ratelimit.weights",
+            "eth_getLogs",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn rate_limit_args_default_sanity_test() {
+        let args = CommandParser::<RpcRateLimitArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, RpcRateLimitArgs::default());
+        assert!(args.rate_limit_config(1024).is_none());
+    }
+}
@@ -3,7 +3,7 @@
 use crate::{
     args::{
         types::{MaxU32, ZeroAsNoneU64},
-        GasPriceOracleArgs, RpcStateCacheArgs,
+        GasPriceOracleArgs, RpcRateLimitArgs, RpcStateCacheArgs,
     },
     cli::{
         components::{RethNodeComponents, RethRpcComponents, RethRpcServerHandles},
@@ -18,13 +18,18 @@ use clap::{
 };
 use futures::TryFutureExt;
 use reth_network_api::{NetworkInfo, Peers};
+use reth_primitives::Address;
 use reth_provider::{
-    AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, HeaderProvider, StateProviderFactory,
+    AccountReader, AddressAppearanceReader, BlockReaderIdExt, CanonStateSubscriptions,
+    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, LogIndexReader,
+    StateProviderFactory, StateRangeReader,
 };
 use reth_rpc::{
-    eth::{cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, RPC_DEFAULT_GAS_CAP},
-    JwtError, JwtSecret,
+    eth::{
+        cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, KeystoreSigner,
+        DEFAULT_ETH_PROOF_WINDOW, MAX_ETH_PROOF_WINDOW, RPC_DEFAULT_GAS_CAP,
+    },
+    JwtError, JwtSecret, ValidationApiConfig,
 };
 use reth_rpc_builder::{
     auth::{AuthServerConfig, AuthServerHandle},
@@ -169,10 +174,51 @@ pub struct RpcServerArgs {
     )]
     pub rpc_gas_cap: u64,
 
+    /// Maximum number of blocks into the past for which `eth_getProof` is served.
+    ///
+    /// Historical proofs are generated by reverting the latest state, so their cost grows with the
+    /// distance to the target block. (0 = latest block only)
+    #[arg(
+        long = "rpc.eth-proof-window",
+        value_name = "BLOCKS",
+        value_parser = RangedU64ValueParser::<u64>::new().range(..=MAX_ETH_PROOF_WINDOW),
+        default_value_t = DEFAULT_ETH_PROOF_WINDOW
+    )]
+    pub rpc_eth_proof_window: u64,
+
+    /// Comma separated addresses that blocks validated via `flashbots_validateBuilderSubmission`
+    /// must not touch.
+    #[arg(long = "rpc.builder-blacklist", value_name = "ADDRESSES", value_delimiter = ',')]
+    pub rpc_builder_blacklist: Vec<Address>,
+
+    /// Directory of the encrypted keystore whose accounts can sign via `eth_sign`,
+    /// `eth_signTransaction` and `eth_sendTransaction`.
+    ///
+    /// Accounts are locked until they are unlocked with `--keystore.password` or via
+    /// `personal_unlockAccount`.
+    #[arg(long, value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
+
+    /// Path to a file whose first line is the password that unlocks all keystore accounts at
+    /// startup.
+    #[arg(long = "keystore.password", value_name = "PATH", requires = "keystore")]
+    pub keystore_password: Option<PathBuf>,
+
+    /// Allow unlocking accounts via `personal_unlockAccount` over http and ws.
+    ///
+    /// Unlocked accounts can be used by anyone who can reach these servers, so by default
+    /// accounts can only be unlocked over ipc.
+    #[arg(long = "rpc.allow-insecure-unlock")]
+    pub rpc_allow_insecure_unlock: bool,
+
     /// State cache configuration.
     #[clap(flatten)]
     pub rpc_state_cache: RpcStateCacheArgs,
 
+    /// Rate limit configuration.
+    #[clap(flatten)]
+    pub rpc_rate_limit: RpcRateLimitArgs,
+
     /// Gas price oracle configuration.
     #[clap(flatten)]
     pub gas_price_oracle: GasPriceOracleArgs,
@@ -208,13 +254,17 @@ impl RpcServerArgs {
             .with_network(components.network())
             .with_events(components.events())
             .with_executor(components.task_executor())
+            .with_bundle_pool(components.bundle_pool())
+            .with_bad_blocks(components.bad_blocks())
+            .with_keystore(self.keystore_signer()?)
             .build_with_auth_server(module_config, engine_api);
 
         let rpc_components = RethRpcComponents { registry: &mut registry, modules: &mut modules };
         // apply configured customization
         conf.extend_rpc_modules(self, components, rpc_components)?;
 
-        let server_config = self.rpc_server_config();
+        let server_config =
+            self.rpc_server_config().with_tracing_guard(registry.blocking_task_guard().clone());
         let launch_rpc = modules.clone().start_server(server_config).map_ok(|handle| {
             if let Some(url) = handle.ipc_endpoint() {
                 info!(target: "reth::cli", url=%url, "RPC IPC server started");
@@ -245,6 +295,20 @@ impl RpcServerArgs {
         Ok(handles)
     }
 
+    /// Loads the configured keystore, unlocking all accounts if a password file is configured.
+    ///
+    /// Returns an empty keystore if none is configured.
+    fn keystore_signer(&self) -> eyre::Result<KeystoreSigner> {
+        let Some(dir) = &self.keystore else { return Ok(KeystoreSigner::default()) };
+
+        let keystore = KeystoreSigner::load(dir)?;
+        if let Some(password_file) = &self.keystore_password {
+            keystore.unlock_all_with_password_file(password_file)?;
+        }
+        info!(target: "reth::cli", path=?dir, accounts=keystore.accounts().len(), unlocked=self.keystore_password.is_some(), "Loaded keystore");
+        Ok(keystore)
+    }
+
     /// Convenience function for starting a rpc server with configs which extracted from cli args.
     pub async fn start_rpc_server<Provider, Pool, Network, Tasks, Events>(
         &self,
@@ -262,6 +326,9 @@ impl RpcServerArgs {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + AddressAppearanceReader
+            + LogIndexReader
+            + StateRangeReader
             + Clone
             + Unpin
             + 'static,
@@ -297,6 +364,7 @@ impl RpcServerArgs {
             + ChainSpecProvider
             + EvmEnvProvider
             + HeaderProvider
+            + LogIndexReader
             + StateProviderFactory
             + Clone
             + Unpin
@@ -336,6 +404,7 @@ impl RethRpcConfig for RpcServerArgs {
             .max_blocks_per_filter(self.rpc_max_blocks_per_filter.unwrap_or_max())
             .max_logs_per_response(self.rpc_max_logs_per_response.unwrap_or_max() as usize)
             .rpc_gas_cap(self.rpc_gas_cap)
+            .eth_proof_window(self.rpc_eth_proof_window)
             .state_cache(self.state_cache_config())
             .gpo_config(self.gas_price_oracle_config())
     }
@@ -362,8 +431,15 @@ impl RethRpcConfig for RpcServerArgs {
     }
 
     fn transport_rpc_module_config(&self) -> TransportRpcModuleConfig {
-        let mut config = TransportRpcModuleConfig::default()
-            .with_config(RpcModuleConfig::new(self.eth_config()));
+        let mut config = TransportRpcModuleConfig::default().with_config(
+            RpcModuleConfig::builder()
+                .eth(self.eth_config())
+                .flashbots(ValidationApiConfig {
+                    blacklist: self.rpc_builder_blacklist.iter().copied().collect(),
+                })
+                .allow_insecure_unlock(self.rpc_allow_insecure_unlock)
+                .build(),
+        );
 
         if self.http {
             config = config.with_http(
@@ -405,7 +481,10 @@ impl RethRpcConfig for RpcServerArgs {
     }
 
     fn rpc_server_config(&self) -> RpcServerConfig {
-        let mut config = RpcServerConfig::default().with_jwt_secret(self.rpc_secret_key());
+        let mut config =
+            RpcServerConfig::default().with_jwt_secret(self.rpc_secret_key()).with_rate_limit(
+                self.rpc_rate_limit.rate_limit_config(self.rpc_max_request_size_bytes()),
+            );
 
         if self.http {
             let socket_address = SocketAddr::new(self.http_addr, self.http_port);
@@ -477,8 +556,14 @@ impl Default for RpcServerArgs {
             rpc_max_blocks_per_filter: constants::DEFAULT_MAX_BLOCKS_PER_FILTER.into(),
             rpc_max_logs_per_response: (constants::DEFAULT_MAX_LOGS_PER_RESPONSE as u64).into(),
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
+            rpc_eth_proof_window: DEFAULT_ETH_PROOF_WINDOW,
+            rpc_builder_blacklist: Vec::new(),
+            keystore: None,
+            keystore_password: None,
+            rpc_allow_insecure_unlock: false,
             gas_price_oracle: GasPriceOracleArgs::default(),
             rpc_state_cache: RpcStateCacheArgs::default(),
+            rpc_rate_limit: RpcRateLimitArgs::default(),
         }
     }
 }
@@ -544,6 +629,27 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn test_rpc_eth_proof_window() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        let config = args.eth_config();
+        assert_eq!(config.eth_proof_window, DEFAULT_ETH_PROOF_WINDOW);
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--rpc.eth-proof-window", "128"])
+                .args;
+        let config = args.eth_config();
+        assert_eq!(config.eth_proof_window, 128);
+
+        let window = (MAX_ETH_PROOF_WINDOW + 1).to_string();
+        let args = CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--rpc.eth-proof-window",
+            window.as_str(),
+        ]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn test_rpc_server_args_parser() {
         let args =
@@ -699,6 +805,30 @@ mod tests {
         assert_eq!(config.max_logs_per_response, Some(200));
     }
 
+    #[test]
+    fn test_keystore_args() {
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--keystore",
+            "/tmp/keystore",
+            "--keystore.password",
+            "/tmp/password.txt",
+            "--rpc.allow-insecure-unlock",
+        ])
+        .args;
+        assert_eq!(args.keystore, Some(PathBuf::from("/tmp/keystore")));
+        assert_eq!(args.keystore_password, Some(PathBuf::from("/tmp/password.txt")));
+        assert!(args.rpc_allow_insecure_unlock);
+
+        // the password file requires a keystore
+        assert!(CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--keystore.password",
+            "/tmp/password.txt",
+        ])
+        .is_err());
+    }
+
     #[test]
     fn rpc_server_args_default_sanity_test() {
         let default_args = RpcServerArgs::default();
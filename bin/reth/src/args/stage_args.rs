@@ -15,5 +15,7 @@ pub enum StageEnum {
     TxLookup,
     AccountHistory,
     StorageHistory,
+    AddressAppearances,
+    LogIndex,
     TotalDifficulty,
 }
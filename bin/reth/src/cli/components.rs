@@ -2,10 +2,12 @@
 
 use reth_network::{NetworkEvents, NetworkProtocols};
 use reth_network_api::{NetworkInfo, Peers};
+use reth_payload_builder::BundlePool;
 use reth_primitives::ChainSpec;
 use reth_provider::{
-    AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, StateProviderFactory,
+    AccountReader, AddressAppearanceReader, BadBlockStore, BlockReaderIdExt,
+    CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, EvmEnvProvider, LogIndexReader,
+    StateProviderFactory, StateRangeReader,
 };
 use reth_rpc_builder::{
     auth::AuthServerHandle, RethModuleRegistry, RpcServerHandle, TransportRpcModules,
@@ -22,6 +24,9 @@ pub trait FullProvider:
     + EvmEnvProvider
     + ChainSpecProvider
     + ChangeSetReader
+    + AddressAppearanceReader
+    + LogIndexReader
+    + StateRangeReader
     + Clone
     + Unpin
     + 'static
@@ -35,6 +40,9 @@ impl<T> FullProvider for T where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AddressAppearanceReader
+        + LogIndexReader
+        + StateRangeReader
         + Clone
         + Unpin
         + 'static
@@ -69,6 +77,12 @@ pub trait RethNodeComponents: Clone + Send + Sync + 'static {
     /// Returns the instance of the events subscription handler.
     fn events(&self) -> Self::Events;
 
+    /// Returns the pool of bundles shared by the RPC handlers and the payload builder.
+    fn bundle_pool(&self) -> BundlePool;
+
+    /// Returns the store of recently rejected blocks.
+    fn bad_blocks(&self) -> BadBlockStore;
+
     /// Helper function to return the chain spec.
     fn chain_spec(&self) -> Arc<ChainSpec> {
         self.provider().chain_spec()
@@ -109,6 +123,8 @@ pub struct RethNodeComponentsImpl<Provider, Pool, Network, Events, Tasks> {
     pub network: Network,
     pub task_executor: Tasks,
     pub events: Events,
+    pub bundle_pool: BundlePool,
+    pub bad_blocks: BadBlockStore,
 }
 
 impl<Provider, Pool, Network, Events, Tasks> RethNodeComponents
@@ -145,6 +161,14 @@ where
     fn events(&self) -> Self::Events {
         self.events.clone()
     }
+
+    fn bundle_pool(&self) -> BundlePool {
+        self.bundle_pool.clone()
+    }
+
+    fn bad_blocks(&self) -> BadBlockStore {
+        self.bad_blocks.clone()
+    }
 }
 
 /// Contains the handles to the spawned RPC servers.
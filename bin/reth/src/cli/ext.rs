@@ -160,7 +160,8 @@ pub trait RethNodeCommandConfig: fmt::Debug {
             payload_job_config,
             components.chain_spec(),
             payload_builder,
-        );
+        )
+        .with_bundle_pool(components.bundle_pool());
         let (payload_service, payload_builder) = PayloadBuilderService::new(payload_generator);
 
         components
@@ -16,11 +16,12 @@ use clap::Parser;
 
 use reth_db::{
     cursor::DbCursorRO, database::Database, open_db_read_only, table::Table, transaction::DbTx,
-    AccountChangeSet, AccountHistory, AccountsTrie, BlockBodyIndices, BlockOmmers,
-    BlockWithdrawals, Bytecodes, CanonicalHeaders, ConsensusContent, ConsensusNumber, DatabaseEnv,
-    HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers, PlainAccountState,
-    PlainStorageState, PruneCheckpoints, Receipts, StorageChangeSet, StorageHistory, StoragesTrie,
-    SyncStage, SyncStageProgress, Tables, TransactionBlock, Transactions, TxHashNumber, TxSenders,
+    AccountChangeSet, AccountHistory, AccountsTrie, AddressAppearances, BlockBodyIndices,
+    BlockOmmers, BlockWithdrawals, Bytecodes, CanonicalHeaders, ConsensusContent, ConsensusNumber,
+    DatabaseEnv, DatabaseMigrations, HashedAccount, HashedStorage, HeaderNumbers, HeaderTD,
+    Headers, LogAddressIndex, LogTopicIndex, PlainAccountState, PlainStorageState,
+    PruneCheckpoints, Receipts, StorageChangeSet, StorageHistory, StoragesTrie, SyncStage,
+    SyncStageProgress, Tables, TransactionBlock, Transactions, TxHashNumber, TxSenders,
 };
 use tracing::info;
 
@@ -146,6 +147,18 @@ impl Command {
                 Tables::ConsensusContent => {
                     find_diffs::<ConsensusContent>(primary_tx, secondary_tx, output_dir)?
                 }
+                Tables::AddressAppearances => {
+                    find_diffs::<AddressAppearances>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::LogAddressIndex => {
+                    find_diffs::<LogAddressIndex>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::LogTopicIndex => {
+                    find_diffs::<LogTopicIndex>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::DatabaseMigrations => {
+                    find_diffs::<DatabaseMigrations>(primary_tx, secondary_tx, output_dir)?
+                }
             };
         }
 
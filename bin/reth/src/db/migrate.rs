@@ -0,0 +1,51 @@
+use clap::Parser;
+use reth_db::{
+    init_db,
+    migration::Migrator,
+    version::{get_db_version, DatabaseVersionError},
+};
+use reth_interfaces::db::LogLevel;
+use std::path::Path;
+
+/// The arguments for the `reth db migrate` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Lists the pending migrations without applying them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Command {
+    /// Execute `db migrate` command
+    pub fn execute(self, db_path: &Path, log_level: Option<LogLevel>) -> eyre::Result<()> {
+        let version = match get_db_version(db_path) {
+            Ok(version) => version,
+            Err(DatabaseVersionError::MissingFile) => {
+                println!("Local database is uninitialized, nothing to migrate");
+                return Ok(())
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let migrator = Migrator::default();
+        let pending = migrator.pending(version)?;
+        if pending.is_empty() {
+            println!("Local database is at the latest version (v{version}), nothing to migrate");
+            return Ok(())
+        }
+
+        println!("Pending migrations from v{version} to v{}:", migrator.target());
+        for migration in pending {
+            println!("  v{}: {}", migration.version(), migration.description());
+        }
+
+        if self.dry_run {
+            return Ok(())
+        }
+
+        init_db(db_path, log_level)?;
+        println!("Database migrated to v{}", migrator.target());
+
+        Ok(())
+    }
+}
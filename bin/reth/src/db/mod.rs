@@ -27,6 +27,7 @@ mod clear;
 mod diff;
 mod get;
 mod list;
+mod migrate;
 mod snapshots;
 /// DB List TUI
 mod tui;
@@ -87,6 +88,8 @@ pub enum Subcommands {
     Snapshot(snapshots::Command),
     /// Lists current and local database versions
     Version,
+    /// Applies pending database migrations
+    Migrate(migrate::Command),
     /// Returns the full database path
     Path,
 }
@@ -241,6 +244,9 @@ impl Command {
                     println!("Local database is uninitialized");
                 }
             }
+            Subcommands::Migrate(command) => {
+                command.execute(&db_path, self.db.log_level)?;
+            }
             Subcommands::Path => {
                 println!("{}", db_path.display());
             }
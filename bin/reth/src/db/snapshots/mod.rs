@@ -113,6 +113,16 @@ impl Command {
                             factory.clone(),
                             snap_segments::Receipts::new(*compression, filters),
                         )?,
+                        SnapshotSegment::AccountChangeSets => self
+                            .generate_snapshot::<DatabaseEnv>(
+                                factory.clone(),
+                                snap_segments::AccountChangeSets::new(*compression, filters),
+                            )?,
+                        SnapshotSegment::StorageChangeSets => self
+                            .generate_snapshot::<DatabaseEnv>(
+                                factory.clone(),
+                                snap_segments::StorageChangeSets::new(*compression, filters),
+                            )?,
                     }
                 }
             }
@@ -145,6 +155,9 @@ impl Command {
                         InclusionFilter::Cuckoo,
                         phf,
                     )?,
+                    // Change set snapshots are looked up by range, so there's no random access
+                    // benchmark for them.
+                    SnapshotSegment::AccountChangeSets | SnapshotSegment::StorageChangeSets => {}
                 }
             }
         }
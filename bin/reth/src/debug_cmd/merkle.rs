@@ -95,6 +95,7 @@ impl Command {
         let network = self
             .network
             .network_config(config, self.chain.clone(), secret_key, default_peers_path)
+            .permissions(self.network.permissions()?)
             .with_task_executor(Box::new(task_executor))
             .listener_addr(SocketAddr::V4(SocketAddrV4::new(self.network.addr, self.network.port)))
             .discovery_addr(SocketAddr::V4(SocketAddrV4::new(
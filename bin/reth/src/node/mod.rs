@@ -53,8 +53,14 @@ use reth_interfaces::{
     },
     RethResult,
 };
-use reth_network::{NetworkBuilder, NetworkConfig, NetworkEvents, NetworkHandle, NetworkManager};
+use reth_network::{
+    config::NetworkMode,
+    import::{BlockImport, ConsensusBlockImport},
+    snap::{SnapFetchClient, SnapProtocolHandler, SnapRequestHandler},
+    NetworkBuilder, NetworkConfig, NetworkEvents, NetworkHandle, NetworkManager,
+};
 use reth_network_api::{NetworkInfo, PeersInfo};
+use reth_payload_builder::BundlePool;
 use reth_primitives::{
     constants::eip4844::{LoadKzgSettingsError, MAINNET_KZG_TRUSTED_SETUP},
     fs,
@@ -63,8 +69,8 @@ use reth_primitives::{
     BlockHashOrNumber, BlockNumber, ChainSpec, DisplayHardforks, Head, SealedHeader, B256,
 };
 use reth_provider::{
-    providers::BlockchainProvider, providers::ConsensusProvider, BlockHashReader, BlockReader,
-    CanonStateSubscriptions, HeaderProvider, HeaderSyncMode, ProviderFactory,
+    providers::BlockchainProvider, providers::ConsensusProvider, BadBlockStore, BlockHashReader,
+    BlockReader, CanonStateSubscriptions, HeaderProvider, HeaderSyncMode, ProviderFactory,
     StageCheckpointReader,
 };
 use reth_prune::PrunerBuilder;
@@ -75,8 +81,9 @@ use reth_stages::{
     prelude::*,
     stages::{
         AccountHashingStage, ExecutionStage, ExecutionStageThresholds, IndexAccountHistoryStage,
-        IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage, StorageHashingStage,
-        TotalDifficultyStage, TransactionLookupStage,
+        IndexAddressAppearancesStage, IndexLogsStage, IndexStorageHistoryStage, MerkleStage,
+        SenderRecoveryStage, SnapSyncStage, StorageHashingStage, TotalDifficultyStage,
+        TransactionLookupStage,
     },
 };
 use reth_tasks::TaskExecutor;
@@ -301,12 +308,14 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             EvmProcessorFactory::new(self.chain.clone()),
         );
         let tree_config = BlockchainTreeConfig::default();
+        let bad_blocks = BadBlockStore::new(self.debug.max_bad_blocks);
         let tree = BlockchainTree::new(
             tree_externals,
             tree_config,
             prune_config.clone().map(|config| config.segments),
         )?
-        .with_sync_metrics_tx(sync_metrics_tx.clone());
+        .with_sync_metrics_tx(sync_metrics_tx.clone())
+        .with_bad_blocks(bad_blocks.clone());
         let canon_state_notification_sender = tree.canon_state_notification_sender();
         let blockchain_tree = ShareableBlockchainTree::new(tree);
         debug!(target: "reth::cli", "configured blockchain tree");
@@ -353,6 +362,14 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let secret_key = get_secret_key(&network_secret_path)?;
         info!(target: "reth::cli", network_secret_path = ?network_secret_path, secret_key = %reth_primitives::hex::encode(secret_key.as_ref()), "P2P network");
         let default_peers_path = data_dir.known_peers_path();
+        let block_import = self.network.block_gossip.then(|| {
+            Box::new(ConsensusBlockImport::new(
+                Arc::clone(&consensus),
+                blockchain_db.clone(),
+                blockchain_tree.clone(),
+                Box::new(ctx.task_executor.clone()),
+            )) as Box<dyn BlockImport>
+        });
         let network_config = self.load_network_config(
             &config,
             provider_factory.clone(),
@@ -360,17 +377,33 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             head,
             secret_key,
             default_peers_path.clone(),
-        );
+            block_import,
+        )?;
 
         let network_client = network_config.client.clone();
         let mut network_builder = NetworkManager::builder(network_config).await?;
 
+        // the snap protocol is also used by the snap sync stage to request state from peers, the
+        // state is only served from the database if enabled
+        let snap_protocol = if self.network.snap_serve {
+            let (snap_request_handler, snap_protocol) =
+                SnapRequestHandler::with_protocol(provider_factory.clone());
+            ctx.task_executor.spawn_critical("p2p snap request handler", snap_request_handler);
+            snap_protocol
+        } else {
+            SnapProtocolHandler::client_only()
+        };
+        let snap_client = snap_protocol.client();
+        network_builder.network_mut().add_rlpx_sub_protocol(snap_protocol);
+
         let components = RethNodeComponentsImpl {
             provider: blockchain_db.clone(),
             pool: transaction_pool.clone(),
             network: network_builder.handle(),
             task_executor: ctx.task_executor.clone(),
             events: blockchain_db.clone(),
+            bundle_pool: BundlePool::new(),
+            bad_blocks: bad_blocks.clone(),
         };
 
         // allow network modifications
@@ -389,6 +422,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         info!(target: "reth::cli", peer_id = %network.peer_id(), local_addr = %network.local_addr(), enode = %network.local_node_record(), "Connected to P2P network");
         let network_client = network.fetch_client().await?;
+        let snap_client = snap_client.with_peers_handle(network.peers_handle().clone());
 
         self.ext.on_components_initialized(&components)?;
 
@@ -434,6 +468,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 .build_networked_pipeline(
                     &config.stages,
                     client.clone(),
+                    snap_client,
                     Arc::clone(&consensus),
                     provider_factory.clone(),
                     &ctx.task_executor,
@@ -454,6 +489,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 .build_networked_pipeline(
                     &config.stages,
                     network_client.clone(),
+                    snap_client,
                     Arc::clone(&consensus),
                     provider_factory.clone(),
                     &ctx.task_executor,
@@ -480,6 +516,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 consensus_db,
                 auth_config,
             )
+            .with_bad_blocks(bad_blocks.clone())
             .build();
             let pipeline_events = pipeline.events();
             task.set_pipeline_events(pipeline_events);
@@ -537,6 +574,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             consensus_engine_rx,
             hooks,
         )?;
+        let beacon_consensus_engine = beacon_consensus_engine.with_bad_blocks(bad_blocks);
         info!(target: "reth::cli", "Consensus engine initialized");
 
         let events = stream_select!(
@@ -640,6 +678,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         &self,
         config: &StageConfig,
         client: Client,
+        snap_client: SnapFetchClient,
         consensus: Arc<dyn Consensus>,
         provider_factory: ProviderFactory<DB>,
         task_executor: &TaskExecutor,
@@ -666,6 +705,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 config,
                 header_downloader,
                 body_downloader,
+                snap_client,
                 consensus,
                 max_block,
                 self.debug.continuous,
@@ -864,6 +904,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn load_network_config<DB: Database>(
         &self,
         config: &Config,
@@ -872,10 +913,19 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         head: Head,
         secret_key: SecretKey,
         default_peers_path: PathBuf,
-    ) -> NetworkConfig<ProviderFactory<DB>> {
+        block_import: Option<Box<dyn BlockImport>>,
+    ) -> eyre::Result<NetworkConfig<ProviderFactory<DB>>> {
+        let permissions = self.network.permissions().wrap_err_with(|| {
+            format!("Could not load allowlist {:?}", self.network.permissions_allowlist)
+        })?;
+        if permissions.is_enabled() {
+            info!(target: "reth::cli", nodes = permissions.nodes().len(), "Permissioned network mode enabled");
+        }
+
         let cfg_builder = self
             .network
             .network_config(config, self.chain.clone(), secret_key, default_peers_path)
+            .permissions(permissions)
             .with_task_executor(Box::new(executor))
             .set_head(head)
             .listener_addr(SocketAddr::V4(SocketAddrV4::new(
@@ -889,6 +939,15 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 self.network.port + self.instance - 1,
             )));
 
+        // Propagate blocks over devp2p instead of relying on a beacon node
+        let cfg_builder = match block_import {
+            Some(block_import) => {
+                info!(target: "reth::cli", "Block gossip enabled");
+                cfg_builder.network_mode(NetworkMode::Work).block_import(block_import)
+            }
+            None => cfg_builder,
+        };
+
         // When `sequencer_endpoint` is configured, the node will forward all transactions to a
         // Sequencer node for execution and inclusion on L1, and disable its own txpool
         // gossip to prevent other parties in the network from learning about them.
@@ -897,7 +956,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             .sequencer_endpoint(self.rollup.sequencer_http.clone())
             .disable_tx_gossip(self.rollup.disable_txpool_gossip);
 
-        cfg_builder.build(provider_factory)
+        Ok(cfg_builder.build(provider_factory))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -907,6 +966,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         config: &StageConfig,
         header_downloader: H,
         body_downloader: B,
+        snap_client: SnapFetchClient,
         consensus: Arc<dyn Consensus>,
         max_block: Option<u64>,
         continuous: bool,
@@ -969,7 +1029,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 })
                 .set(
                     ExecutionStage::new(
-                        factory,
+                        factory.clone(),
                         ExecutionStageThresholds {
                             max_blocks: config.execution.max_blocks,
                             max_changes: config.execution.max_changes,
@@ -1004,7 +1064,32 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 .set(IndexStorageHistoryStage::new(
                     config.index_storage_history.commit_threshold,
                     prune_modes.storage_history,
-                )),
+                ))
+                .add_after(
+                    IndexAddressAppearancesStage::new(
+                        factory,
+                        config.index_address_appearances.commit_threshold,
+                        prune_modes.address_appearances,
+                    ),
+                    StageId::IndexAccountHistory,
+                )
+                .disable_if(StageId::IndexAddressAppearances, || {
+                    !config.index_address_appearances.enabled
+                })
+                .add_after(
+                    IndexLogsStage::new(config.index_logs.commit_threshold, prune_modes.log_index),
+                    StageId::IndexAddressAppearances,
+                )
+                .disable_if(StageId::IndexLogs, || !config.index_logs.enabled)
+                .add_after(
+                    SnapSyncStage::new(
+                        snap_client,
+                        config.snap_sync.pivot_distance,
+                        config.snap_sync.response_bytes,
+                    ),
+                    StageId::TotalDifficulty,
+                )
+                .disable_if(StageId::SnapSync, || !config.snap_sync.enabled),
             )
             .build(provider_factory);
 
@@ -1023,7 +1108,8 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 }
 
 /// Drives the [NetworkManager] future until a [Shutdown](reth_tasks::shutdown::Shutdown) signal is
-/// received. If configured, this writes known peers to `persistent_peers_file` afterwards.
+/// received. If configured, this writes the state of the peer set to `persistent_peers_file`
+/// afterwards.
 async fn run_network_until_shutdown<C>(
     shutdown: reth_tasks::shutdown::GracefulShutdown,
     network: NetworkManager<C>,
@@ -1042,9 +1128,10 @@ async fn run_network_until_shutdown<C>(
     }
 
     if let Some(file_path) = persistent_peers_file {
-        let known_peers = network.all_peers().collect::<Vec<_>>();
-        if let Ok(known_peers) = serde_json::to_string_pretty(&known_peers) {
-            trace!(target: "reth::cli", peers_file =?file_path, num_peers=%known_peers.len(), "Saving current peers");
+        let snapshot = network.peers_snapshot();
+        let num_peers = snapshot.peers.len();
+        if let Ok(known_peers) = serde_json::to_string_pretty(&snapshot) {
+            trace!(target: "reth::cli", peers_file =?file_path, %num_peers, "Saving current peers");
             let parent_dir = file_path.parent().map(fs::create_dir_all).transpose();
             match parent_dir.and_then(|_| fs::write(&file_path, known_peers)) {
                 Ok(_) => {
@@ -10,7 +10,7 @@ use crate::{
 };
 use clap::Parser;
 use reth_db::{database::Database, open_db, tables, transaction::DbTxMut, DatabaseEnv};
-use reth_primitives::{fs, stage::StageId, ChainSpec};
+use reth_primitives::{fs, stage::StageId, ChainSpec, PruneSegment};
 use std::sync::Arc;
 use tracing::info;
 
@@ -145,6 +145,22 @@ impl Command {
                         Default::default(),
                     )?;
                 }
+                StageEnum::AddressAppearances => {
+                    tx.clear::<tables::AddressAppearances>()?;
+                    tx.delete::<tables::PruneCheckpoints>(PruneSegment::AddressAppearances, None)?;
+                    tx.put::<tables::SyncStage>(
+                        StageId::IndexAddressAppearances.to_string(),
+                        Default::default(),
+                    )?;
+                }
+                StageEnum::LogIndex => {
+                    tx.clear::<tables::LogAddressIndex>()?;
+                    tx.clear::<tables::LogTopicIndex>()?;
+                    tx.put::<tables::SyncStage>(
+                        StageId::IndexLogs.to_string(),
+                        Default::default(),
+                    )?;
+                }
                 StageEnum::TotalDifficulty => {
                     tx.clear::<tables::HeaderTD>()?;
                     tx.put::<tables::SyncStage>(
@@ -21,8 +21,9 @@ use reth_provider::{ProviderFactory, StageCheckpointReader};
 use reth_stages::{
     stages::{
         AccountHashingStage, BodyStage, ExecutionStage, ExecutionStageThresholds,
-        IndexAccountHistoryStage, IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage,
-        StorageHashingStage, TransactionLookupStage,
+        IndexAccountHistoryStage, IndexAddressAppearancesStage, IndexLogsStage,
+        IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage, StorageHashingStage,
+        TransactionLookupStage,
     },
     ExecInput, Stage, StageExt, UnwindInput,
 };
@@ -173,6 +174,7 @@ impl Command {
                             p2p_secret_key,
                             default_peers_path,
                         )
+                        .permissions(self.network.permissions()?)
                         .build(provider_factory.clone())
                         .start_network()
                         .await?;
@@ -225,6 +227,11 @@ impl Command {
                 ),
                 StageEnum::AccountHistory => (Box::<IndexAccountHistoryStage>::default(), None),
                 StageEnum::StorageHistory => (Box::<IndexStorageHistoryStage>::default(), None),
+                StageEnum::AddressAppearances => {
+                    let factory = reth_revm::EvmProcessorFactory::new(self.chain.clone());
+                    (Box::new(IndexAddressAppearancesStage::new_with_factory(factory)), None)
+                }
+                StageEnum::LogIndex => (Box::<IndexLogsStage>::default(), None),
                 _ => return Ok(()),
             };
         if let Some(unwind_stage) = &unwind_stage {
@@ -23,7 +23,7 @@ use reth_primitives::{
 };
 use reth_provider::{
     chain::{ChainSplit, ChainSplitTarget},
-    BlockExecutionWriter, BlockNumReader, BlockWriter, BundleStateWithReceipts,
+    BadBlockStore, BlockExecutionWriter, BlockNumReader, BlockWriter, BundleStateWithReceipts,
     CanonStateNotification, CanonStateNotificationSender, CanonStateNotifications, Chain,
     ChainSpecProvider, DisplayBlocksChain, ExecutorFactory, HeaderProvider, ProviderError,
 };
@@ -74,6 +74,8 @@ pub struct BlockchainTree<DB: Database, EF: ExecutorFactory> {
     /// Metrics for sync stages.
     sync_metrics_tx: Option<MetricEventsSender>,
     prune_modes: Option<PruneModes>,
+    /// Store of the most recent blocks that failed validation.
+    bad_blocks: BadBlockStore,
 }
 
 impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
@@ -117,6 +119,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             metrics: Default::default(),
             sync_metrics_tx: None,
             prune_modes,
+            bad_blocks: BadBlockStore::default(),
         })
     }
 
@@ -126,6 +129,20 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         self
     }
 
+    /// Set the store that blocks failing validation are recorded in.
+    pub fn with_bad_blocks(mut self, bad_blocks: BadBlockStore) -> Self {
+        self.bad_blocks = bad_blocks;
+        self
+    }
+
+    /// Records the block of the error in the bad block store if it was rejected as invalid.
+    fn record_bad_block(&self, err: InsertBlockError) -> InsertBlockError {
+        if err.kind().is_invalid_block() {
+            self.bad_blocks.insert(err.block().clone(), err.kind());
+        }
+        err
+    }
+
     /// Check if the block is known to blockchain tree or database and return its status.
     ///
     /// Function will check:
@@ -682,7 +699,9 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
     ) -> Result<InsertPayloadOk, InsertBlockError> {
         match block.try_seal_with_senders() {
             Ok(block) => self.insert_block(block, BlockValidationKind::Exhaustive),
-            Err(block) => Err(InsertBlockError::sender_recovery_error(block)),
+            Err(block) => {
+                Err(self.record_bad_block(InsertBlockError::sender_recovery_error(block)))
+            }
         }
     }
 
@@ -692,7 +711,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
     pub fn buffer_block(&mut self, block: SealedBlockWithSenders) -> Result<(), InsertBlockError> {
         // validate block consensus rules
         if let Err(err) = self.validate_block(&block) {
-            return Err(InsertBlockError::consensus_error(err, block.block));
+            return Err(self.record_bad_block(InsertBlockError::consensus_error(err, block.block)));
         }
 
         self.state.buffered_blocks.insert_block(block);
@@ -781,12 +800,13 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
 
         // validate block consensus rules
         if let Err(err) = self.validate_block(&block) {
-            return Err(InsertBlockError::consensus_error(err, block.block));
+            return Err(self.record_bad_block(InsertBlockError::consensus_error(err, block.block)));
         }
 
-        Ok(InsertPayloadOk::Inserted(
-            self.try_insert_validated_block(block, block_validation_kind)?,
-        ))
+        let status = self
+            .try_insert_validated_block(block, block_validation_kind)
+            .map_err(|err| self.record_bad_block(err))?;
+        Ok(InsertPayloadOk::Inserted(status))
     }
 
     /// Finalize blocks up until and including `finalized_block`, and remove them from the tree.
@@ -1612,6 +1632,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn records_bad_blocks() {
+        let data = BlockChainTestData::default_with_numbers(11, 12);
+        let (block1, _) = data.blocks[0].clone();
+
+        let TreeExternals { provider_factory, executor_factory, .. } = setup_externals(vec![]);
+        let consensus = Arc::new(TestConsensus::default());
+        consensus.set_fail_validation(true);
+        let externals = TreeExternals::new(provider_factory, consensus, executor_factory);
+
+        let bad_blocks = BadBlockStore::default();
+        let config = BlockchainTreeConfig::new(1, 2, 3, 2);
+        let mut tree = BlockchainTree::new(externals, config, None)
+            .expect("failed to create tree")
+            .with_bad_blocks(bad_blocks.clone());
+
+        let err = tree.insert_block(block1.clone(), BlockValidationKind::Exhaustive).unwrap_err();
+        assert!(err.kind().is_consensus_error());
+
+        let bad_block = bad_blocks.get(&block1.hash()).expect("block is recorded");
+        assert_eq!(bad_block.block, block1.block);
+        assert_eq!(bad_block.error, err.kind().to_string());
+    }
+
     #[tokio::test]
     async fn sanity_path() {
         let data = BlockChainTestData::default_with_numbers(11, 12);
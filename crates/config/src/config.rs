@@ -71,6 +71,12 @@ pub struct StageConfig {
     pub index_account_history: IndexHistoryConfig,
     /// Index Storage History stage configuration.
     pub index_storage_history: IndexHistoryConfig,
+    /// Index Address Appearances stage configuration.
+    pub index_address_appearances: IndexAddressAppearancesConfig,
+    /// Index Logs stage configuration.
+    pub index_logs: IndexLogsConfig,
+    /// Snap Sync stage configuration.
+    pub snap_sync: SnapSyncConfig,
 }
 
 /// Header stage configuration.
@@ -257,6 +263,61 @@ impl Default for IndexHistoryConfig {
     }
 }
 
+/// Index Address Appearances stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct IndexAddressAppearancesConfig {
+    /// Whether the stage is enabled. The stage is optional and disabled by default.
+    pub enabled: bool,
+    /// The maximum number of blocks to process before committing progress to the database.
+    ///
+    /// Blocks are re-executed by the stage, so this is lower than for the other index stages.
+    pub commit_threshold: u64,
+}
+
+impl Default for IndexAddressAppearancesConfig {
+    fn default() -> Self {
+        Self { enabled: false, commit_threshold: 10_000 }
+    }
+}
+
+/// Index Logs stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct IndexLogsConfig {
+    /// Whether the stage is enabled. The stage is optional and disabled by default.
+    pub enabled: bool,
+    /// The maximum number of blocks to process before committing progress to the database.
+    pub commit_threshold: u64,
+}
+
+impl Default for IndexLogsConfig {
+    fn default() -> Self {
+        Self { enabled: false, commit_threshold: 100_000 }
+    }
+}
+
+/// Snap Sync stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SnapSyncConfig {
+    /// Whether the stage is enabled. The stage is optional and disabled by default.
+    pub enabled: bool,
+    /// The distance of the pivot block to the synced header tip.
+    ///
+    /// Peers only serve the state of recent blocks; reth peers only serve the state of their
+    /// latest block.
+    pub pivot_distance: u64,
+    /// The soft limit of the response size of a single request, in bytes.
+    pub response_bytes: u64,
+}
+
+impl Default for SnapSyncConfig {
+    fn default() -> Self {
+        Self { enabled: false, pivot_distance: 0, response_bytes: 512 * 1024 }
+    }
+}
+
 /// Pruning configuration.
 #[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
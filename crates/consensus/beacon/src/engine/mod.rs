@@ -26,8 +26,8 @@ use reth_primitives::{
     SealedHeader, B256, U256,
 };
 use reth_provider::{
-    BlockIdReader, BlockReader, BlockSource, CanonChainTracker, ChainSpecProvider, ProviderError,
-    StageCheckpointReader,
+    BadBlockStore, BlockIdReader, BlockReader, BlockSource, CanonChainTracker, ChainSpecProvider,
+    ProviderError, StageCheckpointReader,
 };
 use reth_rpc_types::engine::{
     CancunPayloadFields, ExecutionPayload, PayloadAttributes, PayloadStatus, PayloadStatusEnum,
@@ -74,6 +74,7 @@ use crate::hooks::{EngineHookEvent, EngineHooks, PolledHook};
 pub use forkchoice::ForkchoiceStatus;
 use reth_interfaces::blockchain_tree::BlockValidationKind;
 use reth_payload_validator::ExecutionPayloadValidator;
+use reth_rpc_types_compat::engine::payload::try_into_block;
 
 mod metrics;
 
@@ -195,6 +196,8 @@ where
     /// Tracks the header of invalid payloads that were rejected by the engine because they're
     /// invalid.
     invalid_headers: InvalidHeaderCache,
+    /// Store of the most recent blocks that were rejected before they reached the tree.
+    bad_blocks: BadBlockStore,
     /// Consensus engine metrics.
     metrics: EngineMetrics,
     /// After downloading a block corresponding to a recent forkchoice update, the engine will
@@ -305,6 +308,7 @@ where
             payload_builder,
             listeners: EventListeners::default(),
             invalid_headers: InvalidHeaderCache::new(MAX_INVALID_HEADERS),
+            bad_blocks: BadBlockStore::default(),
             metrics: EngineMetrics::default(),
             pipeline_run_threshold,
             hooks: EngineHooksController::new(hooks),
@@ -323,6 +327,14 @@ where
         Ok((this, handle))
     }
 
+    /// Set the store that payloads failing validation are recorded in.
+    ///
+    /// This should be the same store the blockchain tree records its invalid blocks in.
+    pub fn with_bad_blocks(mut self, bad_blocks: BadBlockStore) -> Self {
+        self.bad_blocks = bad_blocks;
+        self
+    }
+
     /// Check if the pipeline is consistent (all stages have the checkpoint block numbers no less
     /// than the checkpoint of the first stage).
     ///
@@ -1134,6 +1146,9 @@ where
         if let Some(status) =
             self.check_invalid_ancestor_with_head(lowest_buffered_ancestor, block.hash)
         {
+            if let PayloadStatusEnum::Invalid { validation_error } = &status.status {
+                self.bad_blocks.insert(block, validation_error);
+            }
             return Ok(status);
         }
 
@@ -1201,11 +1216,22 @@ where
         cancun_fields: Option<CancunPayloadFields>,
     ) -> Result<SealedBlock, PayloadStatus> {
         let parent_hash = payload.parent_hash();
+        let parent_beacon_block_root = cancun_fields.as_ref().map(|f| f.parent_beacon_block_root);
 
-        match self.payload_validator.ensure_well_formed_payload(payload, cancun_fields.into()) {
+        match self
+            .payload_validator
+            .ensure_well_formed_payload(payload.clone(), cancun_fields.into())
+        {
             Ok(block) => Ok(block),
             Err(error) => {
                 error!(target: "consensus::engine", ?error, "Invalid payload");
+
+                // record the rejected block, if the payload can be decoded into one. If the block
+                // hash of the payload is wrong, the block is recorded under its actual hash.
+                if let Ok(block) = try_into_block(payload, parent_beacon_block_root) {
+                    self.bad_blocks.insert(block.seal_slow(), &error);
+                }
+
                 // we need to convert the error to a payload status (response to the CL)
 
                 let latest_valid_hash =
@@ -2546,6 +2572,42 @@ mod tests {
             assert_matches!(engine_rx.try_recv(), Err(TryRecvError::Empty));
         }
 
+        #[tokio::test]
+        async fn records_rejected_payloads() {
+            let mut rng = generators::rng();
+            let chain_spec = Arc::new(
+                ChainSpecBuilder::default()
+                    .chain(MAINNET.chain)
+                    .genesis(MAINNET.genesis.clone())
+                    .paris_activated()
+                    .build(),
+            );
+
+            let (consensus_engine, env) = TestConsensusEngineBuilder::new(chain_spec.clone())
+                .with_pipeline_exec_outputs(VecDeque::from([Ok(ExecOutput {
+                    checkpoint: StageCheckpoint::new(0),
+                    done: true,
+                })]))
+                .build();
+            let bad_blocks = BadBlockStore::default();
+            let consensus_engine = consensus_engine.with_bad_blocks(bad_blocks.clone());
+
+            let mut engine_rx = spawn_consensus_engine(consensus_engine);
+
+            // Send new payload with a block hash that does not match its content
+            let block = random_block(&mut rng, 1, None, None, Some(0));
+            let mut payload = try_block_to_payload_v1(block.clone());
+            payload.block_hash = rng.gen();
+            let res = env.send_new_payload(payload, None).await;
+            assert_matches!(res, Ok(result) => assert_matches!(result.status, PayloadStatusEnum::Invalid { .. }));
+
+            let bad_block = bad_blocks.get(&block.hash).expect("block is recorded");
+            assert_eq!(bad_block.block, block);
+            assert!(bad_block.error.starts_with("block hash mismatch"));
+
+            assert_matches!(engine_rx.try_recv(), Err(TryRecvError::Empty));
+        }
+
         #[tokio::test]
         async fn payload_pre_merge() {
             let data = BlockChainTestData::default();
@@ -23,7 +23,7 @@ use reth_interfaces::{
     test_utils::{NoopFullBlockClient, TestConsensus},
 };
 use reth_payload_builder::test_utils::spawn_test_payload_service;
-use reth_primitives::{BlockNumber, ChainSpec, PruneModes, Receipt, B256, U256};
+use reth_primitives::{Address, BlockNumber, ChainSpec, PruneModes, Receipt, B256, U256};
 use reth_provider::{
     providers::BlockchainProvider, test_utils::TestExecutorFactory, BlockExecutor,
     BundleStateWithReceipts, ExecutorFactory, HeaderSyncMode, ProviderFactory,
@@ -36,7 +36,10 @@ use reth_rpc_types::engine::{
 };
 use reth_stages::{sets::DefaultStages, test_utils::TestStages, ExecOutput, Pipeline, StageError};
 use reth_tasks::TokioTaskExecutor;
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
 use tokio::sync::{oneshot, watch};
 
 type TestBeaconConsensusEngine<Client> = BeaconConsensusEngine<
@@ -233,6 +236,20 @@ where
             EitherBlockExecutor::Right(b) => b.size_hint(),
         }
     }
+
+    fn set_record_accessed_accounts(&mut self, record: bool) {
+        match self {
+            EitherBlockExecutor::Left(a) => a.set_record_accessed_accounts(record),
+            EitherBlockExecutor::Right(b) => b.set_record_accessed_accounts(record),
+        }
+    }
+
+    fn take_accessed_accounts(&mut self) -> BTreeMap<Address, Vec<BlockNumber>> {
+        match self {
+            EitherBlockExecutor::Left(a) => a.take_accessed_accounts(),
+            EitherBlockExecutor::Right(b) => b.take_accessed_accounts(),
+        }
+    }
 }
 
 impl<A, B> PrunableBlockExecutor for EitherBlockExecutor<A, B>
@@ -3,6 +3,7 @@ use alloy_primitives::{B256, U256};
 
 use reqwest::StatusCode;
 
+use reth_provider::BadBlockStore;
 use reth_rpc_types::{
     engine::{
         ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadId,
@@ -10,6 +11,7 @@ use reth_rpc_types::{
     },
     ExecutionPayloadV2,
 };
+use reth_rpc_types_compat::engine::payload::try_payload_v2_to_block;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
@@ -269,15 +271,28 @@ pub struct ApiService {
     next_payload_id_pairs: HashMap<B256, PayloadId>,
     /// key proposing block_id, value:ExecutionPayloadWrapperV2
     proposing_payload_pairs: HashMap<B256, (PayloadId, ExecutionPayloadWrapperV2)>,
+    /// blocks that failed validation
+    bad_blocks: BadBlockStore,
 }
 
 impl ApiService {
-    pub fn new(api: Arc<HttpJsonRpcSync>) -> Self {
+    pub fn new(api: Arc<HttpJsonRpcSync>, bad_blocks: BadBlockStore) -> Self {
         Self {
             api,
             latest_committed_id: None,
             next_payload_id_pairs: HashMap::new(),
             proposing_payload_pairs: HashMap::new(),
+            bad_blocks,
+        }
+    }
+
+    /// Record a block that failed validation, so it can be inspected via `debug_getBadBlocks`
+    fn record_bad_block(&self, playload: &ExecutionPayloadWrapperV2, error: String) {
+        match try_payload_v2_to_block(playload.execution_payload.clone()) {
+            Ok(block) => self.bad_blocks.insert(block.seal_slow(), error),
+            Err(e) => {
+                tracing::warn!(target:"consensus::cl","ApiService::record_bad_block::try_payload_v2_to_block return(error: {:?})", e);
+            }
         }
     }
 
@@ -449,6 +464,17 @@ impl ApiService {
 
         if !forkchoice_updated.payload_status.status.is_valid() {
             tracing::error!(target:"consensus::cl","ApiService::check_blocks::forkchoice_updated return(not valid)");
+            if forkchoice_updated.payload_status.status.is_invalid() {
+                // the parent is the block that failed validation, not this one. If the parent was
+                // not proposed through this service, the execution layer already recorded it when
+                // it rejected it.
+                if let Some((_, parent)) = self.proposing_payload_pairs.get(&previous_id) {
+                    self.record_bad_block(
+                        parent,
+                        format!("{:?}", forkchoice_updated.payload_status.status),
+                    );
+                }
+            }
             return Err(ApiServiceError::BlockNotReady);
         } else {
             self.proposing_payload_pairs.insert(block_id, (payload_id, playload.clone()));
@@ -486,6 +512,9 @@ impl ApiService {
             }
         } else {
             tracing::error!(target:"consensus::cl","ApiService::commit_block::new_payload return(not valid)");
+            if payload_status.status.is_invalid() {
+                self.record_bad_block(&execution_payload, format!("{:?}", payload_status.status));
+            }
             return Err(ApiServiceError::BlockNotReady);
         }
     }
@@ -506,13 +535,15 @@ impl ApiService {
             Ok(x) => x,
             Err(e) => {
                 // return Err(ApiServiceError::ApiError(format!("forkchoice_updated: {:?}", e)));
-                //tracing::error!(target:"consensus::cl","ApiService::sync_block::forkchoice_updated return(error: {:?})", e);
+                //tracing::error!(target:"consensus::cl","
+                // ApiService::sync_block::forkchoice_updated return(error: {:?})", e);
                 return Err(ApiServiceError::ApiError(format!("forkchoice_updated: {:?}", e)));
             }
         };
         if !forkchoice_updated_result.payload_status.status.is_valid() {
             // return Err(ApiServiceError::BlockNotReady);
-            //tracing::error!(target:"consensus::cl","ApiService::sync_block::forkchoice_updated return(not valid)");
+            //tracing::error!(target:"consensus::cl","ApiService::sync_block::forkchoice_updated
+            // return(not valid)");
             return Err(ApiServiceError::BlockNotReady);
         }
 
@@ -14,7 +14,9 @@ pub use engine_api::AuthHttpConfig;
 
 use reth_network::NetworkHandle;
 use reth_primitives::{ChainSpec, SealedHeader};
-use reth_provider::{BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter};
+use reth_provider::{
+    BadBlockStore, BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter,
+};
 
 use secp256k1::SecretKey;
 use std::sync::Arc;
@@ -66,6 +68,7 @@ pub struct ConsensusBuilder<Client, CDB> {
     storages: CDB,
     latest_header: SealedHeader,
     auth_config: AuthHttpConfig,
+    bad_blocks: BadBlockStore,
 }
 
 impl<Client, CDB> ConsensusBuilder<Client, CDB>
@@ -97,8 +100,16 @@ where
             storages,
             latest_header,
             auth_config,
+            bad_blocks: BadBlockStore::default(),
         }
     }
+
+    /// Sets the store that records the blocks which failed validation.
+    pub fn with_bad_blocks(mut self, bad_blocks: BadBlockStore) -> Self {
+        self.bad_blocks = bad_blocks;
+        self
+    }
+
     /// Consumes the type and returns all components
     #[track_caller]
     pub fn build(self) -> ClTask<Client, CDB>
@@ -115,6 +126,7 @@ where
             storages,
             latest_header,
             auth_config,
+            bad_blocks,
         } = self;
         let task = ClTask::new(
             secret,
@@ -125,6 +137,7 @@ where
             consensus_agent,
             storages,
             latest_header,
+            bad_blocks,
         );
         task
     }
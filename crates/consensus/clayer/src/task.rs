@@ -15,8 +15,8 @@ use reth_interfaces::clayer::{ClayerConsensusEvent, ClayerConsensusMessageAgentT
 use reth_network::NetworkHandle;
 use reth_primitives::{ChainSpec, SealedHeader};
 use reth_provider::{
-    BlockReaderIdExt, CanonChainTracker, ConsensusNumberReader, ConsensusNumberWriter,
-    StateProviderFactory,
+    BadBlockStore, BlockReaderIdExt, CanonChainTracker, ConsensusNumberReader,
+    ConsensusNumberWriter, StateProviderFactory,
 };
 use reth_stages::PipelineEvent;
 use secp256k1::SecretKey;
@@ -58,6 +58,7 @@ pub struct ClTask<Client, CDB> {
     consensus_engine_task_handle: Option<std::thread::JoinHandle<()>>,
     auth_config: AuthHttpConfig,
     secret: SecretKey,
+    bad_blocks: BadBlockStore,
 }
 
 impl<Client, CDB> ClTask<Client, CDB>
@@ -75,6 +76,7 @@ where
         consensus_agent: ClayerConsensusMessagingAgent,
         storages: CDB,
         startup_latest_header: SealedHeader,
+        bad_blocks: BadBlockStore,
     ) -> Self {
         Self {
             secret,
@@ -91,6 +93,7 @@ where
             pbft_running_state: Arc::new(AtomicBool::new(false)),
             startup_latest_header,
             consensus_engine_task_handle: None,
+            bad_blocks,
         }
     }
 
@@ -106,6 +109,7 @@ where
         let cdb = self.storages.clone();
         let client = self.client.clone();
         let secret = self.secret.clone();
+        let bad_blocks = self.bad_blocks.clone();
 
         let startup_latest_header = self.startup_latest_header.clone();
         let thread_join_handle = std::thread::spawn(move || {
@@ -129,7 +133,7 @@ where
             let state = &mut pbft_state;
             let mut consensus_engine = ClayerConsensusEngine::new(
                 consensus_agent.clone(),
-                ApiService::new(Arc::new(api)),
+                ApiService::new(Arc::new(api), bad_blocks),
                 cdb,
                 client,
             );
@@ -0,0 +1,29 @@
+use crate::p2p::{download::DownloadClient, error::PeerRequestResult};
+use futures::Future;
+use reth_eth_wire::{
+    AccountRangeMessage, ByteCodesMessage, GetAccountRangeMessage, GetByteCodesMessage,
+    GetStorageRangesMessage, GetTrieNodesMessage, StorageRangesMessage, TrieNodesMessage,
+};
+use std::pin::Pin;
+
+/// The future type of a `snap` request.
+pub type SnapFut<T> = Pin<Box<dyn Future<Output = PeerRequestResult<T>> + Send + Sync>>;
+
+/// A client capable of requesting state from peers that support the `snap/1` protocol.
+///
+/// The request ids of the given requests are assigned by the client.
+#[auto_impl::auto_impl(&, Arc, Box)]
+pub trait SnapClient: DownloadClient {
+    /// Requests a range of accounts of the state trie with the given root.
+    fn get_account_range(&self, request: GetAccountRangeMessage) -> SnapFut<AccountRangeMessage>;
+
+    /// Requests the storage slots of the given accounts.
+    fn get_storage_ranges(&self, request: GetStorageRangesMessage)
+        -> SnapFut<StorageRangesMessage>;
+
+    /// Requests contract bytecodes by their hash.
+    fn get_byte_codes(&self, request: GetByteCodesMessage) -> SnapFut<ByteCodesMessage>;
+
+    /// Requests trie nodes by their path.
+    fn get_trie_nodes(&self, request: GetTrieNodesMessage) -> SnapFut<TrieNodesMessage>;
+}
@@ -84,9 +84,9 @@ pub use reth_primitives::NodeRecord;
 pub mod test_utils;
 
 use crate::table::PongTable;
-use reth_net_nat::ResolveNatInterval;
 /// reexport to get public ip.
 pub use reth_net_nat::{external_ip, NatResolver};
+use reth_net_nat::{MappedPort, PortMapping, ResolveNatInterval, TransportProtocol};
 
 /// The default address for discv4 via UDP
 ///
@@ -556,7 +556,13 @@ impl Discv4Service {
             ping_interval,
             evict_expired_requests_interval,
             lookup_rotator,
-            resolve_external_ip_interval: config.resolve_external_ip_interval(),
+            // map the RLPx and discovery ports if the resolver supports it
+            resolve_external_ip_interval: config.resolve_external_ip_interval().map(|interval| {
+                interval.with_port_mappings([
+                    PortMapping::tcp(local_node_record.tcp_port),
+                    PortMapping::udp(local_node_record.udp_port),
+                ])
+            }),
             config,
             queued_events: Default::default(),
             received_pongs: Default::default(),
@@ -596,6 +602,44 @@ impl Discv4Service {
         }
     }
 
+    /// Announces the external ports the gateway mapped the RLPx and discovery ports to.
+    ///
+    /// This updates the ports of the local [NodeRecord] and the EIP-868 [`Enr`].
+    pub fn set_mapped_ports(&mut self, mapped_ports: &[MappedPort]) {
+        let mut updated = false;
+        for mapped in mapped_ports {
+            let port = mapped.external_port;
+            match mapped.mapping.protocol {
+                TransportProtocol::Tcp if self.local_node_record.tcp_port != port => {
+                    debug!(target: "discv4", %port, "Updating external tcp port");
+                    self.local_node_record.tcp_port = port;
+                    if self.local_node_record.address.is_ipv4() {
+                        let _ = self.local_eip_868_enr.set_tcp4(port, &self.secret_key);
+                    } else {
+                        let _ = self.local_eip_868_enr.set_tcp6(port, &self.secret_key);
+                    }
+                    updated = true;
+                }
+                TransportProtocol::Udp if self.local_node_record.udp_port != port => {
+                    debug!(target: "discv4", %port, "Updating external udp port");
+                    self.local_node_record.udp_port = port;
+                    if self.local_node_record.address.is_ipv4() {
+                        let _ = self.local_eip_868_enr.set_udp4(port, &self.secret_key);
+                    } else {
+                        let _ = self.local_eip_868_enr.set_udp6(port, &self.secret_key);
+                    }
+                    updated = true;
+                }
+                _ => {}
+            }
+        }
+        if updated {
+            let mut lock = self.shared_node_record.lock();
+            *lock = self.local_node_record;
+            debug!(target: "discv4", enr=?self.local_eip_868_enr, "Updated local ENR");
+        }
+    }
+
     /// Returns the [PeerId] that identifies this node
     pub fn local_peer_id(&self) -> &PeerId {
         &self.local_node_record.id
@@ -1548,10 +1592,12 @@ impl Discv4Service {
                 self.re_ping_oldest();
             }
 
-            if let Some(Poll::Ready(Some(ip))) =
-                self.resolve_external_ip_interval.as_mut().map(|r| r.poll_tick(cx))
-            {
-                self.set_external_ip_addr(ip);
+            if let Some(interval) = self.resolve_external_ip_interval.as_mut() {
+                if let Poll::Ready(Some(ip)) = interval.poll_tick(cx) {
+                    let mapped_ports = interval.mapped_ports().to_vec();
+                    self.set_external_ip_addr(ip);
+                    self.set_mapped_ports(&mapped_ports);
+                }
             }
 
             // process all incoming commands, this channel can never close
@@ -2274,6 +2320,40 @@ mod tests {
         };
     }
 
+    #[tokio::test]
+    async fn test_set_mapped_ports() {
+        reth_tracing::init_test_tracing();
+        let config = Discv4Config::builder().external_ip_resolver(None).build();
+        let (_discv4, mut service) = create_discv4_with_config(config).await;
+
+        let external_ip: IpAddr = Ipv4Addr::new(203, 0, 113, 7).into();
+        let local_record = service.local_enr();
+        let mapped_ports = [
+            MappedPort {
+                mapping: PortMapping::tcp(local_record.tcp_port),
+                external_addr: external_ip,
+                external_port: 40303,
+                lifetime: Duration::from_secs(600),
+            },
+            MappedPort {
+                mapping: PortMapping::udp(local_record.udp_port),
+                external_addr: external_ip,
+                external_port: 40304,
+                lifetime: Duration::from_secs(600),
+            },
+        ];
+        service.set_external_ip_addr(external_ip);
+        service.set_mapped_ports(&mapped_ports);
+
+        let record = service.local_enr();
+        assert_eq!(record.address, external_ip);
+        assert_eq!(record.tcp_port, 40303);
+        assert_eq!(record.udp_port, 40304);
+        assert_eq!(*service.shared_node_record.lock(), record);
+        assert_eq!(service.local_eip_868_enr.tcp4(), Some(40303));
+        assert_eq!(service.local_eip_868_enr.udp4(), Some(40304));
+    }
+
     #[tokio::test]
     async fn test_respect_ping_expiration() {
         reth_tracing::init_test_tracing();
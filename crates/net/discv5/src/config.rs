@@ -0,0 +1,190 @@
+//! A set of configuration parameters to tune the discovery v5 protocol.
+
+use crate::{enr::ClayerEnrEntry, filter::EnrFilter};
+use discv5::{Enr, ListenConfig};
+use reth_primitives::{bytes::Bytes, ForkId};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+/// The default UDP port of discv5, which is shared with consensus layer clients.
+pub const DEFAULT_DISCOVERY_V5_PORT: u16 = 9000;
+
+/// The default address of discv5: `0.0.0.0:9000`
+pub const DEFAULT_DISCOVERY_V5_ADDR: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_DISCOVERY_V5_PORT));
+
+/// The default interval between two lookups of new peers.
+pub const DEFAULT_LOOKUP_INTERVAL: Duration = Duration::from_secs(20);
+
+/// The default number of peers a single lookup tries to find.
+pub const DEFAULT_LOOKUP_TARGET_PEERS: usize = 16;
+
+/// Configuration of the discv5 service.
+#[derive(Debug, Clone)]
+pub struct Discv5Config {
+    /// The configuration of the underlying discv5 protocol.
+    pub discv5_config: discv5::Config,
+    /// The UDP address discv5 listens on.
+    pub listen_addr: SocketAddr,
+    /// The external IP advertised in the local ENR.
+    pub external_ip: Option<IpAddr>,
+    /// The RLPx TCP port advertised in the local ENR.
+    pub tcp_port: u16,
+    /// Nodes to bootstrap the routing table with.
+    pub bootstrap_nodes: Vec<Enr>,
+    /// The fork id advertised in the `eth` entry of the local ENR.
+    pub fork_id: Option<ForkId>,
+    /// The clayer capability advertised in the `clayer` entry of the local ENR.
+    pub clayer: Option<ClayerEnrEntry>,
+    /// Additional RLP encoded entries of the local ENR.
+    pub additional_enr_pairs: Vec<(Vec<u8>, Bytes)>,
+    /// The interval between two lookups of new peers.
+    pub lookup_interval: Duration,
+    /// The number of peers a single lookup tries to find.
+    pub lookup_target_peers: usize,
+    /// Only discovered nodes whose ENR match the filter are reported.
+    pub filter: EnrFilter,
+}
+
+impl Discv5Config {
+    /// Returns a new default builder instance
+    pub fn builder() -> Discv5ConfigBuilder {
+        Default::default()
+    }
+
+    /// Sets the fork id advertised in the `eth` entry.
+    pub fn set_fork_id(&mut self, fork_id: ForkId) {
+        self.fork_id = Some(fork_id);
+    }
+
+    /// Sets the RLPx TCP port advertised in the local ENR.
+    pub fn set_tcp_port(&mut self, tcp_port: u16) {
+        self.tcp_port = tcp_port;
+    }
+}
+
+impl Default for Discv5Config {
+    fn default() -> Self {
+        Discv5ConfigBuilder::default().build()
+    }
+}
+
+/// Builder type for [`Discv5Config`]
+#[derive(Debug, Clone)]
+pub struct Discv5ConfigBuilder {
+    listen_addr: SocketAddr,
+    external_ip: Option<IpAddr>,
+    tcp_port: u16,
+    bootstrap_nodes: Vec<Enr>,
+    fork_id: Option<ForkId>,
+    clayer: Option<ClayerEnrEntry>,
+    additional_enr_pairs: Vec<(Vec<u8>, Bytes)>,
+    lookup_interval: Duration,
+    lookup_target_peers: usize,
+    filter: EnrFilter,
+}
+
+impl Default for Discv5ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            listen_addr: DEFAULT_DISCOVERY_V5_ADDR,
+            external_ip: None,
+            tcp_port: 30303,
+            bootstrap_nodes: Vec::new(),
+            fork_id: None,
+            clayer: None,
+            additional_enr_pairs: Vec::new(),
+            lookup_interval: DEFAULT_LOOKUP_INTERVAL,
+            lookup_target_peers: DEFAULT_LOOKUP_TARGET_PEERS,
+            filter: EnrFilter::Any,
+        }
+    }
+}
+
+impl Discv5ConfigBuilder {
+    /// Sets the UDP address discv5 listens on.
+    pub fn listen_addr(&mut self, listen_addr: SocketAddr) -> &mut Self {
+        self.listen_addr = listen_addr;
+        self
+    }
+
+    /// Sets the external IP advertised in the local ENR.
+    pub fn external_ip(&mut self, external_ip: IpAddr) -> &mut Self {
+        self.external_ip = Some(external_ip);
+        self
+    }
+
+    /// Sets the RLPx TCP port advertised in the local ENR.
+    pub fn tcp_port(&mut self, tcp_port: u16) -> &mut Self {
+        self.tcp_port = tcp_port;
+        self
+    }
+
+    /// Adds a node to bootstrap the routing table with.
+    pub fn add_bootstrap_node(&mut self, enr: Enr) -> &mut Self {
+        self.bootstrap_nodes.push(enr);
+        self
+    }
+
+    /// Adds multiple nodes to bootstrap the routing table with.
+    pub fn add_bootstrap_nodes(&mut self, enrs: impl IntoIterator<Item = Enr>) -> &mut Self {
+        self.bootstrap_nodes.extend(enrs);
+        self
+    }
+
+    /// Sets the fork id advertised in the `eth` entry.
+    pub fn fork_id(&mut self, fork_id: ForkId) -> &mut Self {
+        self.fork_id = Some(fork_id);
+        self
+    }
+
+    /// Sets the clayer capability advertised in the `clayer` entry.
+    pub fn clayer(&mut self, clayer: ClayerEnrEntry) -> &mut Self {
+        self.clayer = Some(clayer);
+        self
+    }
+
+    /// Adds an RLP encoded entry to the local ENR.
+    pub fn add_enr_pair(&mut self, key: impl Into<Vec<u8>>, rlp: Bytes) -> &mut Self {
+        self.additional_enr_pairs.push((key.into(), rlp));
+        self
+    }
+
+    /// Sets the interval between two lookups of new peers.
+    pub fn lookup_interval(&mut self, interval: Duration) -> &mut Self {
+        self.lookup_interval = interval;
+        self
+    }
+
+    /// Sets the number of peers a single lookup tries to find.
+    pub fn lookup_target_peers(&mut self, target: usize) -> &mut Self {
+        self.lookup_target_peers = target;
+        self
+    }
+
+    /// Sets the filter discovered nodes must match to be reported.
+    pub fn filter(&mut self, filter: EnrFilter) -> &mut Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the configured [`Discv5Config`]
+    pub fn build(&self) -> Discv5Config {
+        let listen_config = ListenConfig::from_ip(self.listen_addr.ip(), self.listen_addr.port());
+        Discv5Config {
+            discv5_config: discv5::ConfigBuilder::new(listen_config).build(),
+            listen_addr: self.listen_addr,
+            external_ip: self.external_ip,
+            tcp_port: self.tcp_port,
+            bootstrap_nodes: self.bootstrap_nodes.clone(),
+            fork_id: self.fork_id,
+            clayer: self.clayer,
+            additional_enr_pairs: self.additional_enr_pairs.clone(),
+            lookup_interval: self.lookup_interval,
+            lookup_target_peers: self.lookup_target_peers,
+            filter: self.filter.clone(),
+        }
+    }
+}
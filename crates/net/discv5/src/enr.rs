@@ -0,0 +1,147 @@
+//! ENR entries advertised over discv5 and conversions of discovered records.
+
+use crate::{config::Discv5Config, error::Discv5Error};
+use alloy_rlp::{Decodable, RlpDecodable, RlpEncodable};
+use discv5::{
+    enr::{CombinedKey, EnrBuilder, NodeId},
+    Enr,
+};
+use reth_discv4::EnrForkIdEntry;
+use reth_primitives::{keccak256, ForkId, NodeRecord, PeerId};
+use std::net::IpAddr;
+
+/// The ENR key of the `eth` entry that advertises the fork id.
+///
+/// See also <https://github.com/ethereum/devp2p/blob/master/enr-entries/eth.md>
+pub const ETH_ENR_KEY: &[u8] = b"eth";
+
+/// The ENR key of the entry that advertises the clayer consensus capability.
+pub const CLAYER_ENR_KEY: &[u8] = b"clayer";
+
+/// The clayer consensus capability advertised in the ENR of a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ClayerEnrEntry {
+    /// Version of the clayer consensus protocol.
+    pub version: u64,
+    /// Whether the node participates in consensus as a validator.
+    pub validator: bool,
+}
+
+/// A node discovered via discv5 that can be dialed over RLPx.
+#[derive(Debug, Clone)]
+pub struct DiscoveredPeer {
+    /// The RLPx endpoint of the node.
+    pub node_record: NodeRecord,
+    /// The fork id advertised in the `eth` entry.
+    pub fork_id: Option<ForkId>,
+    /// The clayer capability advertised in the `clayer` entry.
+    pub clayer: Option<ClayerEnrEntry>,
+    /// The ENR of the node.
+    pub enr: Enr,
+}
+
+impl DiscoveredPeer {
+    /// Converts the ENR, returns `None` if the node doesn't advertise an RLPx endpoint.
+    pub fn from_enr(enr: Enr) -> Option<Self> {
+        Some(Self {
+            node_record: node_record(&enr)?,
+            fork_id: fork_id(&enr),
+            clayer: clayer(&enr),
+            enr,
+        })
+    }
+}
+
+/// Returns the [PeerId] of the node if it is identified by a secp256k1 key.
+pub fn peer_id(enr: &Enr) -> Option<PeerId> {
+    let pk = secp256k1::PublicKey::from_slice(&enr.public_key().encode()).ok()?;
+    Some(PeerId::from_slice(&pk.serialize_uncompressed()[1..]))
+}
+
+/// Returns the discv5 [NodeId] of the peer.
+pub fn node_id(peer_id: PeerId) -> NodeId {
+    NodeId::new(&keccak256(peer_id).0)
+}
+
+/// Returns the RLPx endpoint of the node as [NodeRecord], if the ENR advertises one.
+pub fn node_record(enr: &Enr) -> Option<NodeRecord> {
+    let id = peer_id(enr)?;
+    let (address, tcp_port, udp_port) = if let (Some(ip), Some(tcp)) = (enr.ip4(), enr.tcp4()) {
+        (IpAddr::V4(ip), tcp, enr.udp4().unwrap_or(tcp))
+    } else if let (Some(ip), Some(tcp)) = (enr.ip6(), enr.tcp6()) {
+        (IpAddr::V6(ip), tcp, enr.udp6().unwrap_or(tcp))
+    } else {
+        return None
+    };
+    Some(NodeRecord { address, tcp_port, udp_port, id })
+}
+
+/// Returns the fork id of the `eth` entry.
+pub fn fork_id(enr: &Enr) -> Option<ForkId> {
+    let rlp = entry_rlp(enr, ETH_ENR_KEY)?;
+    EnrForkIdEntry::decode(&mut &rlp[..]).ok().map(|entry| entry.fork_id)
+}
+
+/// Returns the clayer capability of the `clayer` entry.
+pub fn clayer(enr: &Enr) -> Option<ClayerEnrEntry> {
+    let rlp = entry_rlp(enr, CLAYER_ENR_KEY)?;
+    ClayerEnrEntry::decode(&mut &rlp[..]).ok()
+}
+
+/// Returns the RLP of the entry.
+///
+/// discv5 only inserts byte strings into the ENR of a running node, so entries that were updated
+/// after the start, see [`Discv5::set_fork_id`](crate::Discv5::set_fork_id), are wrapped in an
+/// RLP string.
+fn entry_rlp<'a>(enr: &'a Enr, key: &[u8]) -> Option<&'a [u8]> {
+    let mut rlp = enr.get_raw_rlp(key)?;
+    if rlp.first().is_some_and(|byte| *byte < alloy_rlp::EMPTY_LIST_CODE) {
+        rlp = alloy_rlp::Header::decode_bytes(&mut rlp, false).ok()?;
+    }
+    Some(rlp)
+}
+
+/// Builds the local ENR that advertises the discovery and RLPx endpoints and the configured
+/// entries.
+pub(crate) fn build_local_enr(
+    key: &CombinedKey,
+    config: &Discv5Config,
+) -> Result<Enr, Discv5Error> {
+    let mut builder = EnrBuilder::new("v4");
+
+    // an unspecified address is updated by discv5 once peers report the external address
+    let ip = config.external_ip.unwrap_or(config.listen_addr.ip());
+    let udp_port = config.listen_addr.port();
+    match ip {
+        IpAddr::V4(ip) => {
+            if !ip.is_unspecified() {
+                builder.ip4(ip);
+            }
+            builder.udp4(udp_port).tcp4(config.tcp_port);
+        }
+        IpAddr::V6(ip) => {
+            if !ip.is_unspecified() {
+                builder.ip6(ip);
+            }
+            builder.udp6(udp_port).tcp6(config.tcp_port);
+        }
+    }
+
+    if let Some(fork_id) = config.fork_id {
+        builder.add_value_rlp(ETH_ENR_KEY, alloy_rlp::encode(EnrForkIdEntry::from(fork_id)).into());
+    }
+    if let Some(clayer) = config.clayer {
+        builder.add_value_rlp(CLAYER_ENR_KEY, alloy_rlp::encode(clayer).into());
+    }
+    for (key, value) in &config.additional_enr_pairs {
+        builder.add_value_rlp(key, value.clone());
+    }
+
+    builder.build(key).map_err(|err| Discv5Error::Enr(format!("{err:?}")))
+}
+
+/// Converts the secret key into a discv5 key.
+pub(crate) fn combined_key(sk: &secp256k1::SecretKey) -> Result<CombinedKey, Discv5Error> {
+    CombinedKey::secp256k1_from_bytes(&mut sk.secret_bytes())
+        .map_err(|err| Discv5Error::InvalidSecretKey(format!("{err:?}")))
+}
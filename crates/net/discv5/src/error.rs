@@ -0,0 +1,21 @@
+//! Error types that can occur in this crate.
+
+/// Errors that can occur when starting or using discv5.
+#[derive(Debug, thiserror::Error)]
+pub enum Discv5Error {
+    /// The secret key can't be used as discv5 key.
+    #[error("invalid secret key: {0}")]
+    InvalidSecretKey(String),
+    /// Failed to build the local ENR.
+    #[error("failed to build local ENR: {0}")]
+    Enr(String),
+    /// Failed to start the discv5 service.
+    #[error("failed to start discv5: {0}")]
+    Start(String),
+    /// The ENR was rejected by the routing table.
+    #[error("failed to add ENR: {0}")]
+    AddEnr(&'static str),
+    /// A lookup query failed.
+    #[error("lookup failed: {0}")]
+    Lookup(String),
+}
@@ -0,0 +1,70 @@
+//! Filters to select discovered nodes by the entries of their ENR.
+
+use crate::enr::{clayer, fork_id};
+use discv5::Enr;
+use reth_primitives::ForkId;
+use std::{fmt, sync::Arc};
+
+/// A filter on the ENR of discovered nodes.
+#[derive(Clone, Default)]
+pub enum EnrFilter {
+    /// Matches any node.
+    #[default]
+    Any,
+    /// Matches nodes that advertise an entry with the given key, e.g. `eth` or `clayer`.
+    Topic(Vec<u8>),
+    /// Matches nodes that advertise an `eth` entry with the same fork hash.
+    ForkId(ForkId),
+    /// Matches nodes that advertise the clayer capability as validator.
+    ClayerValidator,
+    /// Matches nodes for which the predicate returns `true`.
+    Predicate(Arc<dyn Fn(&Enr) -> bool + Send + Sync>),
+    /// Matches nodes that match all filters.
+    All(Vec<EnrFilter>),
+}
+
+impl EnrFilter {
+    /// Creates a filter from the predicate.
+    pub fn predicate(f: impl Fn(&Enr) -> bool + Send + Sync + 'static) -> Self {
+        Self::Predicate(Arc::new(f))
+    }
+
+    /// Returns a filter that matches nodes which match both filters.
+    pub fn and(self, other: EnrFilter) -> Self {
+        match (self, other) {
+            (Self::Any, other) | (other, Self::Any) => other,
+            (Self::All(mut filters), other) => {
+                filters.push(other);
+                Self::All(filters)
+            }
+            (this, other) => Self::All(vec![this, other]),
+        }
+    }
+
+    /// Returns `true` if the ENR matches the filter.
+    pub fn matches(&self, enr: &Enr) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Topic(key) => enr.get_raw_rlp(key).is_some(),
+            Self::ForkId(expected) => fork_id(enr).map_or(false, |id| id.hash == expected.hash),
+            Self::ClayerValidator => clayer(enr).map_or(false, |entry| entry.validator),
+            Self::Predicate(f) => f(enr),
+            Self::All(filters) => filters.iter().all(|filter| filter.matches(enr)),
+        }
+    }
+}
+
+impl fmt::Debug for EnrFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => f.write_str("Any"),
+            Self::Topic(key) => {
+                f.debug_tuple("Topic").field(&String::from_utf8_lossy(key)).finish()
+            }
+            Self::ForkId(fork_id) => f.debug_tuple("ForkId").field(fork_id).finish(),
+            Self::ClayerValidator => f.write_str("ClayerValidator"),
+            Self::Predicate(_) => f.write_str("Predicate(..)"),
+            Self::All(filters) => f.debug_tuple("All").field(filters).finish(),
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Discovery v5 implementation: <https://github.com/ethereum/devp2p/blob/master/discv5/discv5.md>
+//!
+//! Discv5 runs alongside discv4 and finds nodes by the entries of their ENR. The local ENR
+//! advertises the `eth` fork id and, optionally, the clayer consensus capability, so that nodes
+//! can select peers of the same chain and consensus without connecting to them first.
+//!
+//! This implementation consists of a [`Discv5`] handle and a spawned service. The service
+//! regularly looks up nodes that match the configured [`EnrFilter`] and yields every discovered
+//! node that can be dialed over RLPx as [`DiscoveredPeer`].
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![warn(missing_debug_implementations, missing_docs, rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms, unreachable_pub, unused_crate_dependencies)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+use discv5::{enr::NodeId, Enr, Event, QueryError};
+use futures::{future::BoxFuture, FutureExt};
+use reth_discv4::EnrForkIdEntry;
+use reth_primitives::{ForkId, PeerId};
+use secp256k1::SecretKey;
+use std::{fmt, net::IpAddr, sync::Arc};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Interval, MissedTickBehavior},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+pub mod config;
+pub mod enr;
+pub mod error;
+pub mod filter;
+
+pub use crate::enr::{ClayerEnrEntry, DiscoveredPeer, CLAYER_ENR_KEY, ETH_ENR_KEY};
+pub use config::{Discv5Config, Discv5ConfigBuilder, DEFAULT_DISCOVERY_V5_PORT};
+pub use discv5::Enr as Discv5Enr;
+pub use error::Discv5Error;
+pub use filter::EnrFilter;
+
+/// The maximum number of discovered peers that are buffered until they are consumed.
+const DISCOVERED_PEERS_BUFFER: usize = 1024;
+
+/// A pending lookup of the service.
+type LookupFuture = BoxFuture<'static, Result<Vec<Enr>, QueryError>>;
+
+/// The handle to a running discv5 service.
+#[derive(Clone)]
+pub struct Discv5 {
+    /// The running discv5 protocol.
+    discv5: Arc<discv5::Discv5>,
+    /// The number of peers a single lookup tries to find.
+    lookup_target_peers: usize,
+}
+
+impl Discv5 {
+    /// Starts discv5 on the configured address and spawns the service that looks up new nodes.
+    ///
+    /// Returns the handle, the stream of discovered peers that match the configured filter and
+    /// the handle of the spawned service.
+    pub async fn start(
+        sk: &SecretKey,
+        config: Discv5Config,
+    ) -> Result<(Self, ReceiverStream<DiscoveredPeer>, JoinHandle<()>), Discv5Error> {
+        let key = enr::combined_key(sk)?;
+        let local_enr = enr::build_local_enr(&key, &config)?;
+        let Discv5Config {
+            discv5_config,
+            bootstrap_nodes,
+            lookup_interval,
+            lookup_target_peers,
+            filter,
+            ..
+        } = config;
+
+        let mut discv5 = discv5::Discv5::new(local_enr, key, discv5_config)
+            .map_err(|err| Discv5Error::Start(err.to_string()))?;
+        discv5.start().await.map_err(|err| Discv5Error::Start(format!("{err:?}")))?;
+
+        for enr in bootstrap_nodes {
+            if let Err(err) = discv5.add_enr(enr) {
+                debug!(target: "net::discv5", %err, "Failed to add bootstrap node");
+            }
+        }
+
+        let events =
+            discv5.event_stream().await.map_err(|err| Discv5Error::Start(format!("{err:?}")))?;
+        let discv5 = Arc::new(discv5);
+
+        let mut lookup_interval = tokio::time::interval(lookup_interval);
+        lookup_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let (updates_tx, updates_rx) = mpsc::channel(DISCOVERED_PEERS_BUFFER);
+        let service = Discv5Service {
+            discv5: discv5.clone(),
+            events,
+            updates: updates_tx,
+            filter,
+            lookup_interval,
+            lookup_target_peers,
+        };
+        let service = tokio::spawn(service.run());
+
+        Ok((Self { discv5, lookup_target_peers }, ReceiverStream::new(updates_rx), service))
+    }
+
+    /// Returns the current local ENR.
+    pub fn local_enr(&self) -> Enr {
+        self.discv5.local_enr()
+    }
+
+    /// Updates the fork id advertised in the `eth` entry of the local ENR.
+    pub fn set_fork_id(&self, fork_id: ForkId) -> Result<(), Discv5Error> {
+        let key = std::str::from_utf8(ETH_ENR_KEY).expect("ENR keys are ASCII");
+        let rlp = alloy_rlp::encode(EnrForkIdEntry::from(fork_id));
+        self.discv5.enr_insert(key, &rlp).map_err(|err| Discv5Error::Enr(format!("{err:?}")))?;
+        Ok(())
+    }
+
+    /// Adds the node to the routing table.
+    pub fn add_enr(&self, enr: Enr) -> Result<(), Discv5Error> {
+        self.discv5.add_enr(enr).map_err(Discv5Error::AddEnr)
+    }
+
+    /// Bans the node and its IP permanently.
+    pub fn ban(&self, peer_id: PeerId, ip: IpAddr) {
+        self.discv5.ban_node(&enr::node_id(peer_id), None);
+        self.discv5.ban_ip(ip, None);
+    }
+
+    /// Bans the IP permanently.
+    pub fn ban_ip(&self, ip: IpAddr) {
+        self.discv5.ban_ip(ip, None);
+    }
+
+    /// Looks up nodes close to a random target whose ENR matches the filter.
+    pub async fn lookup(&self, filter: EnrFilter) -> Result<Vec<Enr>, Discv5Error> {
+        lookup(&self.discv5, filter, self.lookup_target_peers)
+            .await
+            .map_err(|err| Discv5Error::Lookup(format!("{err:?}")))
+    }
+
+    /// Looks up nodes that advertise an ENR entry with the given key, e.g. `clayer`.
+    pub async fn lookup_topic(&self, key: impl Into<Vec<u8>>) -> Result<Vec<Enr>, Discv5Error> {
+        self.lookup(EnrFilter::Topic(key.into())).await
+    }
+}
+
+impl fmt::Debug for Discv5 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Discv5")
+            .field("local_enr", &self.discv5.local_enr())
+            .field("lookup_target_peers", &self.lookup_target_peers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs a lookup for nodes close to a random target that match the filter.
+fn lookup(discv5: &Arc<discv5::Discv5>, filter: EnrFilter, target_peers: usize) -> LookupFuture {
+    let discv5 = discv5.clone();
+    async move {
+        let predicate = Box::new(move |enr: &Enr| filter.matches(enr));
+        discv5.find_node_predicate(NodeId::random(), predicate, target_peers).await
+    }
+    .boxed()
+}
+
+/// The service that drives lookups and forwards discovered nodes.
+struct Discv5Service {
+    discv5: Arc<discv5::Discv5>,
+    /// Events of the discv5 protocol.
+    events: mpsc::Receiver<Event>,
+    /// Sender half of the discovered peers stream.
+    updates: mpsc::Sender<DiscoveredPeer>,
+    /// Discovered nodes that don't match the filter are ignored.
+    filter: EnrFilter,
+    /// Triggers a new lookup.
+    lookup_interval: Interval,
+    /// The number of peers a single lookup tries to find.
+    lookup_target_peers: usize,
+}
+
+impl Discv5Service {
+    /// Runs the service until discv5 shuts down or the discovered peers stream is dropped.
+    async fn run(mut self) {
+        let mut pending_lookup: Option<LookupFuture> = None;
+
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Some(Event::Discovered(enr)) | Some(Event::SessionEstablished(enr, _)) => {
+                        self.on_enr(enr)
+                    }
+                    Some(_) => {}
+                    None => break,
+                },
+                _ = self.lookup_interval.tick(), if pending_lookup.is_none() => {
+                    pending_lookup =
+                        Some(lookup(&self.discv5, self.filter.clone(), self.lookup_target_peers));
+                }
+                res = async { pending_lookup.as_mut().expect("checked").await },
+                    if pending_lookup.is_some() =>
+                {
+                    pending_lookup = None;
+                    match res {
+                        Ok(enrs) => enrs.into_iter().for_each(|enr| self.on_enr(enr)),
+                        Err(err) => debug!(target: "net::discv5", ?err, "Lookup failed"),
+                    }
+                }
+            }
+
+            if self.updates.is_closed() {
+                break
+            }
+        }
+
+        trace!(target: "net::discv5", "Service stopped");
+    }
+
+    /// Forwards the node if it matches the filter and can be dialed.
+    fn on_enr(&self, enr: Enr) {
+        if !self.filter.matches(&enr) {
+            return
+        }
+        let Some(peer) = DiscoveredPeer::from_enr(enr) else { return };
+        if self.updates.try_send(peer).is_err() {
+            trace!(target: "net::discv5", "Discovered peers buffer full");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enr::{build_local_enr, clayer, combined_key, fork_id, node_record, peer_id};
+    use reth_primitives::{ForkHash, ForkId};
+    use secp256k1::SECP256K1;
+    use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+
+    /// Returns a UDP address on localhost that was unused when the function was called.
+    fn unused_udp_addr() -> SocketAddr {
+        UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap()
+    }
+
+    fn local_enr(sk: &SecretKey, config: &Discv5Config) -> Enr {
+        build_local_enr(&combined_key(sk).unwrap(), config).unwrap()
+    }
+
+    #[test]
+    fn local_enr_advertises_entries() {
+        let sk = SecretKey::new(&mut rand::thread_rng());
+        let fork = ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 };
+        let entry = ClayerEnrEntry { version: 1, validator: true };
+        let config = Discv5Config::builder()
+            .listen_addr(SocketAddr::from((Ipv4Addr::LOCALHOST, 9001)))
+            .tcp_port(30304)
+            .fork_id(fork)
+            .clayer(entry)
+            .build();
+
+        let enr = local_enr(&sk, &config);
+        let id = PeerId::from_slice(&sk.public_key(SECP256K1).serialize_uncompressed()[1..]);
+        assert_eq!(peer_id(&enr), Some(id));
+        assert_eq!(fork_id(&enr), Some(fork));
+        assert_eq!(clayer(&enr), Some(entry));
+
+        let record = node_record(&enr).unwrap();
+        assert_eq!(record.id, id);
+        assert_eq!(record.address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(record.tcp_port, 30304);
+        assert_eq!(record.udp_port, 9001);
+        assert_eq!(enr.node_id(), enr::node_id(id));
+    }
+
+    #[test]
+    fn filter_matches_entries() {
+        let sk = SecretKey::new(&mut rand::thread_rng());
+        let fork = ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 };
+        let other_fork = ForkId { hash: ForkHash([0xfe, 0x33, 0x66, 0xe7]), next: 0 };
+
+        let plain = local_enr(&sk, &Discv5Config::builder().fork_id(fork).build());
+        let validator = local_enr(
+            &sk,
+            &Discv5Config::builder()
+                .fork_id(fork)
+                .clayer(ClayerEnrEntry { version: 1, validator: true })
+                .build(),
+        );
+
+        assert!(EnrFilter::Any.matches(&plain));
+        assert!(EnrFilter::Topic(ETH_ENR_KEY.to_vec()).matches(&plain));
+        assert!(!EnrFilter::Topic(CLAYER_ENR_KEY.to_vec()).matches(&plain));
+        assert!(EnrFilter::Topic(CLAYER_ENR_KEY.to_vec()).matches(&validator));
+        assert!(EnrFilter::ForkId(fork).matches(&plain));
+        assert!(!EnrFilter::ForkId(other_fork).matches(&plain));
+        assert!(!EnrFilter::ClayerValidator.matches(&plain));
+        assert!(EnrFilter::ClayerValidator.matches(&validator));
+
+        let filter = EnrFilter::ForkId(fork).and(EnrFilter::ClayerValidator);
+        assert!(filter.matches(&validator));
+        assert!(!filter.matches(&plain));
+        assert!(!EnrFilter::predicate(|enr| enr.tcp4() == Some(1)).matches(&plain));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn discovers_bootstrap_node() {
+        let fork = ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 };
+        let config =
+            || Discv5Config::builder().listen_addr(unused_udp_addr()).fork_id(fork).build();
+
+        let sk_boot = SecretKey::new(&mut rand::thread_rng());
+        let (boot, _boot_updates, _boot_service) = Discv5::start(&sk_boot, config()).await.unwrap();
+
+        let sk = SecretKey::new(&mut rand::thread_rng());
+        let mut config = config();
+        config.bootstrap_nodes.push(boot.local_enr());
+        let (discv5, _updates, _service) = Discv5::start(&sk, config).await.unwrap();
+
+        let found = discv5.lookup(EnrFilter::ForkId(fork)).await.unwrap();
+        assert!(found.iter().any(|enr| enr.node_id() == boot.local_enr().node_id()));
+    }
+
+    #[tokio::test]
+    async fn updates_fork_id() {
+        let fork = ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 1_681_338_455 };
+        let next_fork = ForkId { hash: ForkHash([0xf0, 0xaf, 0xd0, 0xe3]), next: 0 };
+        let config = Discv5Config::builder().listen_addr(unused_udp_addr()).fork_id(fork).build();
+
+        let sk = SecretKey::new(&mut rand::thread_rng());
+        let (discv5, _updates, _service) = Discv5::start(&sk, config).await.unwrap();
+        assert_eq!(fork_id(&discv5.local_enr()), Some(fork));
+
+        let seq = discv5.local_enr().seq();
+        discv5.set_fork_id(next_fork).unwrap();
+        let enr = discv5.local_enr();
+        assert_eq!(fork_id(&enr), Some(next_fork));
+        assert!(EnrFilter::ForkId(next_fork).matches(&enr));
+        assert!(enr.seq() > seq);
+    }
+}
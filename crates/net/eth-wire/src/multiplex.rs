@@ -13,6 +13,7 @@ use std::{
     future::Future,
     io,
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
 
@@ -27,6 +28,18 @@ use reth_primitives::ForkFilter;
 use tokio::sync::{mpsc, mpsc::UnboundedSender};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
+/// Observes the messages of an additional subprotocol that are exchanged over the wire, for
+/// example to account their size.
+///
+/// Messages are passed with their message ID relative to the subprotocol.
+pub trait ProtocolMeter: Send + Sync + fmt::Debug {
+    /// Called with every message received for the subprotocol.
+    fn on_ingress(&self, msg: &[u8]);
+
+    /// Called with every message the subprotocol sends.
+    fn on_egress(&self, msg: &[u8]);
+}
+
 /// A Stream and Sink type that wraps a raw rlpx stream [P2PStream] and handles message ID
 /// multiplexing.
 #[derive(Debug)]
@@ -59,7 +72,7 @@ impl<St> RlpxProtocolMultiplexer<St> {
         F: FnOnce(ProtocolConnection) -> Proto,
         Proto: Stream<Item = BytesMut> + Send + 'static,
     {
-        self.inner.install_protocol(cap, f)
+        self.inner.install_protocol(cap, None, f)
     }
 
     /// Returns the [SharedCapabilities] of the underlying raw p2p stream
@@ -248,6 +261,7 @@ impl<St> MultiplexInner<St> {
     fn install_protocol<F, Proto>(
         &mut self,
         cap: &Capability,
+        meter: Option<Arc<dyn ProtocolMeter>>,
         f: F,
     ) -> Result<(), UnsupportedCapabilityError>
     where
@@ -259,7 +273,7 @@ impl<St> MultiplexInner<St> {
         let (to_satellite, rx) = mpsc::unbounded_channel();
         let proto_conn = ProtocolConnection { from_wire: UnboundedReceiverStream::new(rx) };
         let st = f(proto_conn);
-        let st = ProtocolStream { shared_cap, to_satellite, satellite_st: Box::pin(st) };
+        let st = ProtocolStream { shared_cap, to_satellite, satellite_st: Box::pin(st), meter };
         self.protocols.push(st);
         Ok(())
     }
@@ -403,7 +417,23 @@ impl<St, Primary> RlpxSatelliteStream<St, Primary> {
         F: FnOnce(ProtocolConnection) -> Proto,
         Proto: Stream<Item = BytesMut> + Send + 'static,
     {
-        self.inner.install_protocol(cap, f)
+        self.inner.install_protocol(cap, None, f)
+    }
+
+    /// Installs a new protocol on top of the raw p2p stream, like
+    /// [`install_protocol`](Self::install_protocol), and passes all messages of the protocol to the
+    /// given [ProtocolMeter].
+    pub fn install_metered_protocol<F, Proto>(
+        &mut self,
+        cap: &Capability,
+        meter: Arc<dyn ProtocolMeter>,
+        f: F,
+    ) -> Result<(), UnsupportedCapabilityError>
+    where
+        F: FnOnce(ProtocolConnection) -> Proto,
+        Proto: Stream<Item = BytesMut> + Send + 'static,
+    {
+        self.inner.install_protocol(cap, Some(meter), f)
     }
 
     /// Returns the primary protocol.
@@ -588,6 +618,8 @@ struct ProtocolStream {
     /// the channel shared with the satellite stream
     to_satellite: UnboundedSender<BytesMut>,
     satellite_st: Pin<Box<dyn Stream<Item = BytesMut> + Send>>,
+    /// Observes the messages of the protocol, if any.
+    meter: Option<Arc<dyn ProtocolMeter>>,
 }
 
 impl ProtocolStream {
@@ -615,7 +647,11 @@ impl ProtocolStream {
 
     /// Sends the message to the satellite stream.
     fn send_raw(&self, msg: BytesMut) {
-        let _ = self.to_satellite.send(self.unmask_id(msg));
+        let msg = self.unmask_id(msg);
+        if let Some(meter) = &self.meter {
+            meter.on_ingress(&msg);
+        }
+        let _ = self.to_satellite.send(msg);
     }
 }
 
@@ -625,7 +661,12 @@ impl Stream for ProtocolStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
         let msg = ready!(this.satellite_st.as_mut().poll_next(cx));
-        Poll::Ready(msg.filter(|msg| !msg.is_empty()).map(|msg| this.mask_msg_id(msg)))
+        Poll::Ready(msg.filter(|msg| !msg.is_empty()).map(|msg| {
+            if let Some(meter) = &this.meter {
+                meter.on_egress(&msg);
+            }
+            this.mask_msg_id(msg)
+        }))
     }
 }
 
@@ -645,9 +686,27 @@ mod tests {
         },
         UnauthedEthStream, UnauthedP2PStream,
     };
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio::{net::TcpListener, sync::oneshot};
     use tokio_util::codec::Decoder;
 
+    /// Counts the bytes of the messages of a protocol.
+    #[derive(Debug, Default)]
+    struct CountingMeter {
+        ingress: AtomicUsize,
+        egress: AtomicUsize,
+    }
+
+    impl ProtocolMeter for CountingMeter {
+        fn on_ingress(&self, msg: &[u8]) {
+            self.ingress.fetch_add(msg.len(), Ordering::Relaxed);
+        }
+
+        fn on_egress(&self, msg: &[u8]) {
+            self.egress.fetch_add(msg.len(), Ordering::Relaxed);
+        }
+    }
+
     #[tokio::test]
     async fn eth_satellite() {
         reth_tracing::init_test_tracing();
@@ -738,8 +797,9 @@ mod tests {
             .unwrap();
 
         let (tx, mut rx) = oneshot::channel();
+        let meter = Arc::new(CountingMeter::default());
 
-        st.install_protocol(&TestProtoMessage::capability(), |mut conn| {
+        st.install_metered_protocol(&TestProtoMessage::capability(), meter.clone(), |mut conn| {
             async_stream::stream! {
                 let msg = conn.next().await.unwrap();
                 let msg = TestProtoMessage::decode_message(&mut &msg[..]).unwrap();
@@ -774,5 +834,13 @@ mod tests {
                 }
             }
         }
+
+        let ingress = TestProtoMessage::ping().encoded().len() +
+            TestProtoMessage::message("hello").encoded().len() +
+            TestProtoMessage::message("good bye!").encoded().len();
+        let egress = TestProtoMessage::pong().encoded().len() +
+            TestProtoMessage::message("good bye!").encoded().len();
+        assert_eq!(meter.ingress.load(Ordering::Relaxed), ingress);
+        assert_eq!(meter.egress.load(Ordering::Relaxed), egress);
     }
 }
@@ -24,5 +24,8 @@ pub use state::*;
 pub mod receipts;
 pub use receipts::*;
 
+pub mod snap;
+pub use snap::*;
+
 pub mod clayer;
 pub use clayer::*;
@@ -0,0 +1,490 @@
+//! Implements the `snap/1` protocol messages.
+//!
+//! See also <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+
+use crate::{capability::Capability, protocol::Protocol};
+use alloy_rlp::{length_of_length, Decodable, Encodable, Header, RlpDecodable, RlpEncodable};
+use reth_primitives::{
+    bytes::{BufMut, BytesMut},
+    constants::EMPTY_ROOT_HASH,
+    Account, Bytes, B256, KECCAK_EMPTY, U256,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Requests an unknown number of accounts from a given account trie, starting at the specified
+/// account hash and capped by the maximum allowed response size in bytes.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetAccountRangeMessage {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// Account hash of the first to retrieve.
+    pub starting_hash: B256,
+    /// Account hash after which to stop serving data.
+    pub limit_hash: B256,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// An account in the snap protocol, keyed by its hash.
+///
+/// The body is the RLP encoded account in the "slim" format: empty storage roots and code hashes
+/// are encoded as empty strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountData {
+    /// Hash of the account address (trie path).
+    pub hash: B256,
+    /// RLP encoded slim account.
+    pub body: Bytes,
+}
+
+/// The slim representation of an account.
+#[derive(RlpEncodable, RlpDecodable)]
+struct SlimAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: Bytes,
+    code_hash: Bytes,
+}
+
+impl AccountData {
+    /// Creates the account data for the given account and its storage root.
+    pub fn new(hash: B256, account: Account, storage_root: B256) -> Self {
+        let slim = SlimAccount {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root: if storage_root == EMPTY_ROOT_HASH {
+                Bytes::new()
+            } else {
+                storage_root.to_vec().into()
+            },
+            code_hash: match account.bytecode_hash {
+                Some(code_hash) if code_hash != KECCAK_EMPTY => code_hash.to_vec().into(),
+                _ => Bytes::new(),
+            },
+        };
+        Self { hash, body: alloy_rlp::encode(slim).into() }
+    }
+
+    /// Decodes the slim account body, returns the account and its storage root.
+    pub fn account(&self) -> alloy_rlp::Result<(Account, B256)> {
+        let slim = SlimAccount::decode(&mut &self.body[..])?;
+        let storage_root = match slim.storage_root.len() {
+            0 => EMPTY_ROOT_HASH,
+            32 => B256::from_slice(&slim.storage_root),
+            _ => return Err(alloy_rlp::Error::UnexpectedLength),
+        };
+        let bytecode_hash = match slim.code_hash.len() {
+            0 => None,
+            32 => Some(B256::from_slice(&slim.code_hash)),
+            _ => return Err(alloy_rlp::Error::UnexpectedLength),
+        };
+        Ok((Account { nonce: slim.nonce, balance: slim.balance, bytecode_hash }, storage_root))
+    }
+}
+
+// The body is embedded as raw RLP list, not as string.
+impl Encodable for AccountData {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.hash.length() + self.body.len() }.encode(out);
+        self.hash.encode(out);
+        out.put_slice(&self.body);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.hash.length() + self.body.len();
+        payload_length + length_of_length(payload_length)
+    }
+}
+
+impl Decodable for AccountData {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let hash = B256::decode(buf)?;
+        let body_len = header
+            .payload_length
+            .checked_sub(started_len - buf.len())
+            .ok_or(alloy_rlp::Error::UnexpectedLength)?;
+        if buf.len() < body_len {
+            return Err(alloy_rlp::Error::InputTooShort)
+        }
+        let body = Bytes::copy_from_slice(&buf[..body_len]);
+        *buf = &buf[body_len..];
+        Ok(Self { hash, body })
+    }
+}
+
+/// The response to [`GetAccountRangeMessage`], containing the consecutive accounts and the merkle
+/// proofs for the starting hash and the last returned account.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountRangeMessage {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// List of consecutive accounts from the trie.
+    pub accounts: Vec<AccountData>,
+    /// List of trie nodes proving the account range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests the storage slots of multiple accounts' storage tries.
+///
+/// The starting and limit hashes only apply to the first and last account, empty values
+/// request the full range.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetStorageRangesMessage {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// Account hashes of the storage tries to serve.
+    pub account_hashes: Vec<B256>,
+    /// Storage slot hash of the first to retrieve.
+    pub starting_hash: Bytes,
+    /// Storage slot hash after which to stop serving.
+    pub limit_hash: Bytes,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// A storage slot in the snap protocol, keyed by its hash.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageData {
+    /// Hash of the storage slot key (trie path).
+    pub hash: B256,
+    /// RLP encoded storage slot value.
+    pub data: Bytes,
+}
+
+/// The response to [`GetStorageRangesMessage`], containing the consecutive slots of the requested
+/// accounts.
+///
+/// The proof is only attached if the storage of the last account was not served in full.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageRangesMessage {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// List of list of consecutive slots from the trie (one list per account).
+    pub slots: Vec<Vec<StorageData>>,
+    /// List of trie nodes proving the slot range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests a number of contract byte-codes by hash.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetByteCodesMessage {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Code hashes to retrieve the code for.
+    pub hashes: Vec<B256>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetByteCodesMessage`], containing the requested byte-codes in request order.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteCodesMessage {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// The requested bytecodes in order.
+    pub codes: Vec<Bytes>,
+}
+
+/// Path in the trie for an account and its storage.
+///
+/// The first element is the compact encoded path in the account trie. If there are more elements,
+/// they are compact encoded paths in the storage trie of that account.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriePathSet {
+    /// The paths of the set.
+    pub paths: Vec<Bytes>,
+}
+
+/// Requests a number of state (either account or storage) Merkle trie nodes by path.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetTrieNodesMessage {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// Trie paths to retrieve the nodes for, grouped by account.
+    pub paths: Vec<TriePathSet>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetTrieNodesMessage`], containing the requested trie nodes in request
+/// order.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrieNodesMessage {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// The requested trie nodes in order.
+    pub nodes: Vec<Bytes>,
+}
+
+/// Represents message IDs for `snap` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapMessageId {
+    /// Requests of an unknown number of accounts from a given account trie.
+    GetAccountRange = 0x00,
+    /// Response with the number of consecutive accounts and the Merkle proofs for the entire
+    /// range.
+    AccountRange = 0x01,
+    /// Requests the storage slots of multiple accounts' storage tries.
+    GetStorageRanges = 0x02,
+    /// Response with a number of consecutive storage slots for the requested account.
+    StorageRanges = 0x03,
+    /// Requests a number of contract byte-codes by hash.
+    GetByteCodes = 0x04,
+    /// Response with a number of requested contract codes.
+    ByteCodes = 0x05,
+    /// Requests a number of state (either account or storage) Merkle trie nodes by path.
+    GetTrieNodes = 0x06,
+    /// Response with a number of requested state trie nodes.
+    TrieNodes = 0x07,
+}
+
+impl SnapMessageId {
+    /// The number of message IDs used by `snap/1`.
+    pub const COUNT: u8 = 8;
+}
+
+impl TryFrom<u8> for SnapMessageId {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Ok(match id {
+            0x00 => Self::GetAccountRange,
+            0x01 => Self::AccountRange,
+            0x02 => Self::GetStorageRanges,
+            0x03 => Self::StorageRanges,
+            0x04 => Self::GetByteCodes,
+            0x05 => Self::ByteCodes,
+            0x06 => Self::GetTrieNodes,
+            0x07 => Self::TrieNodes,
+            _ => return Err(alloy_rlp::Error::Custom("invalid snap message id")),
+        })
+    }
+}
+
+/// A `snap` protocol message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapProtocolMessage {
+    /// Represents a `GetAccountRange` request.
+    GetAccountRange(GetAccountRangeMessage),
+    /// Represents an `AccountRange` response.
+    AccountRange(AccountRangeMessage),
+    /// Represents a `GetStorageRanges` request.
+    GetStorageRanges(GetStorageRangesMessage),
+    /// Represents a `StorageRanges` response.
+    StorageRanges(StorageRangesMessage),
+    /// Represents a `GetByteCodes` request.
+    GetByteCodes(GetByteCodesMessage),
+    /// Represents a `ByteCodes` response.
+    ByteCodes(ByteCodesMessage),
+    /// Represents a `GetTrieNodes` request.
+    GetTrieNodes(GetTrieNodesMessage),
+    /// Represents a `TrieNodes` response.
+    TrieNodes(TrieNodesMessage),
+}
+
+impl SnapProtocolMessage {
+    /// Returns the `snap/1` capability.
+    pub const fn capability() -> Capability {
+        Capability::new_static("snap", 1)
+    }
+
+    /// Returns the `snap/1` protocol.
+    pub const fn protocol() -> Protocol {
+        Protocol::new(Self::capability(), SnapMessageId::COUNT)
+    }
+
+    /// Returns the message id of the message.
+    pub fn message_id(&self) -> SnapMessageId {
+        match self {
+            Self::GetAccountRange(_) => SnapMessageId::GetAccountRange,
+            Self::AccountRange(_) => SnapMessageId::AccountRange,
+            Self::GetStorageRanges(_) => SnapMessageId::GetStorageRanges,
+            Self::StorageRanges(_) => SnapMessageId::StorageRanges,
+            Self::GetByteCodes(_) => SnapMessageId::GetByteCodes,
+            Self::ByteCodes(_) => SnapMessageId::ByteCodes,
+            Self::GetTrieNodes(_) => SnapMessageId::GetTrieNodes,
+            Self::TrieNodes(_) => SnapMessageId::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            Self::GetAccountRange(msg) => msg.request_id,
+            Self::AccountRange(msg) => msg.request_id,
+            Self::GetStorageRanges(msg) => msg.request_id,
+            Self::StorageRanges(msg) => msg.request_id,
+            Self::GetByteCodes(msg) => msg.request_id,
+            Self::ByteCodes(msg) => msg.request_id,
+            Self::GetTrieNodes(msg) => msg.request_id,
+            Self::TrieNodes(msg) => msg.request_id,
+        }
+    }
+
+    /// Sets the request id of the message.
+    pub fn set_request_id(&mut self, request_id: u64) {
+        match self {
+            Self::GetAccountRange(msg) => msg.request_id = request_id,
+            Self::AccountRange(msg) => msg.request_id = request_id,
+            Self::GetStorageRanges(msg) => msg.request_id = request_id,
+            Self::StorageRanges(msg) => msg.request_id = request_id,
+            Self::GetByteCodes(msg) => msg.request_id = request_id,
+            Self::ByteCodes(msg) => msg.request_id = request_id,
+            Self::GetTrieNodes(msg) => msg.request_id = request_id,
+            Self::TrieNodes(msg) => msg.request_id = request_id,
+        }
+    }
+
+    /// Returns `true` if the message is a request.
+    pub fn is_request(&self) -> bool {
+        (self.message_id() as u8) % 2 == 0
+    }
+
+    /// Encodes the message ID followed by the RLP encoded message.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.message_id() as u8);
+        match self {
+            Self::GetAccountRange(msg) => msg.encode(&mut buf),
+            Self::AccountRange(msg) => msg.encode(&mut buf),
+            Self::GetStorageRanges(msg) => msg.encode(&mut buf),
+            Self::StorageRanges(msg) => msg.encode(&mut buf),
+            Self::GetByteCodes(msg) => msg.encode(&mut buf),
+            Self::ByteCodes(msg) => msg.encode(&mut buf),
+            Self::GetTrieNodes(msg) => msg.encode(&mut buf),
+            Self::TrieNodes(msg) => msg.encode(&mut buf),
+        }
+        buf
+    }
+
+    /// Decodes a message from the message ID and the RLP encoded message.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (&id, rest) = buf.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        *buf = rest;
+        Ok(match SnapMessageId::try_from(id)? {
+            SnapMessageId::GetAccountRange => {
+                Self::GetAccountRange(GetAccountRangeMessage::decode(buf)?)
+            }
+            SnapMessageId::AccountRange => Self::AccountRange(AccountRangeMessage::decode(buf)?),
+            SnapMessageId::GetStorageRanges => {
+                Self::GetStorageRanges(GetStorageRangesMessage::decode(buf)?)
+            }
+            SnapMessageId::StorageRanges => Self::StorageRanges(StorageRangesMessage::decode(buf)?),
+            SnapMessageId::GetByteCodes => Self::GetByteCodes(GetByteCodesMessage::decode(buf)?),
+            SnapMessageId::ByteCodes => Self::ByteCodes(ByteCodesMessage::decode(buf)?),
+            SnapMessageId::GetTrieNodes => Self::GetTrieNodes(GetTrieNodesMessage::decode(buf)?),
+            SnapMessageId::TrieNodes => Self::TrieNodes(TrieNodesMessage::decode(buf)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(msg: SnapProtocolMessage) {
+        let encoded = msg.encoded();
+        let decoded = SnapProtocolMessage::decode_message(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn snap_messages_roundtrip() {
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        roundtrip(SnapProtocolMessage::GetAccountRange(GetAccountRangeMessage {
+            request_id: 1,
+            root_hash: B256::random(),
+            starting_hash: B256::ZERO,
+            limit_hash: B256::repeat_byte(0xff),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapProtocolMessage::AccountRange(AccountRangeMessage {
+            request_id: 1,
+            accounts: vec![AccountData::new(B256::random(), account, EMPTY_ROOT_HASH)],
+            proof: vec![Bytes::from_static(&[0xc0])],
+        }));
+        roundtrip(SnapProtocolMessage::GetStorageRanges(GetStorageRangesMessage {
+            request_id: 2,
+            root_hash: B256::random(),
+            account_hashes: vec![B256::random()],
+            starting_hash: Bytes::new(),
+            limit_hash: Bytes::new(),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapProtocolMessage::StorageRanges(StorageRangesMessage {
+            request_id: 2,
+            slots: vec![vec![StorageData { hash: B256::random(), data: Bytes::from_static(&[1]) }]],
+            proof: vec![],
+        }));
+        roundtrip(SnapProtocolMessage::GetByteCodes(GetByteCodesMessage {
+            request_id: 3,
+            hashes: vec![B256::random()],
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapProtocolMessage::ByteCodes(ByteCodesMessage {
+            request_id: 3,
+            codes: vec![Bytes::from_static(&[0x60, 0x00])],
+        }));
+        roundtrip(SnapProtocolMessage::GetTrieNodes(GetTrieNodesMessage {
+            request_id: 4,
+            root_hash: B256::random(),
+            paths: vec![TriePathSet { paths: vec![Bytes::from_static(&[0x00])] }],
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapProtocolMessage::TrieNodes(TrieNodesMessage {
+            request_id: 4,
+            nodes: vec![Bytes::from_static(&[0xc0])],
+        }));
+    }
+
+    #[test]
+    fn slim_account_roundtrip() {
+        let storage_root = B256::random();
+        let code_hash = B256::random();
+        let account =
+            Account { nonce: 7, balance: U256::from(100), bytecode_hash: Some(code_hash) };
+        let data = AccountData::new(B256::random(), account, storage_root);
+        assert_eq!(data.account().unwrap(), (account, storage_root));
+
+        // empty storage root and code hash are encoded as empty strings
+        let empty = Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(KECCAK_EMPTY) };
+        let data = AccountData::new(B256::random(), empty, EMPTY_ROOT_HASH);
+        assert_eq!(&data.body[..], &[0xc4, 0x80, 0x80, 0x80, 0x80]);
+        assert_eq!(
+            data.account().unwrap(),
+            (Account { bytecode_hash: None, ..empty }, EMPTY_ROOT_HASH)
+        );
+
+        let mut encoded = Vec::new();
+        data.encode(&mut encoded);
+        assert_eq!(encoded.len(), data.length());
+        assert_eq!(AccountData::decode(&mut &encoded[..]).unwrap(), data);
+    }
+}
@@ -1,4 +1,4 @@
-//! Helpers for resolving the external IP.
+//! Helpers for resolving the external IP and mapping ports on the gateway.
 //!
 //! ## Feature Flags
 //!
@@ -13,6 +13,12 @@
 #![deny(unused_must_use, rust_2018_idioms)]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod port_mapping;
+pub use port_mapping::{
+    default_gateway, GatewayProtocol, MappedPort, PortMapping, PortMappingClient, PortMappingError,
+    TransportProtocol, GATEWAY_PORT,
+};
+
 use igd::aio::search_gateway;
 use pin_project_lite::pin_project;
 use std::{
@@ -42,6 +48,13 @@ pub enum NatResolver {
     PublicIp,
     /// Use the given [IpAddr]
     ExternalIp(IpAddr),
+    /// Resolve via NAT-PMP with the given gateway, or the default gateway
+    NatPmp(Option<IpAddr>),
+    /// Resolve via PCP with the given gateway, or the default gateway.
+    ///
+    /// PCP resolves the external IP only when mapping ports, see
+    /// [ResolveNatInterval::with_port_mappings].
+    Pcp(Option<IpAddr>),
     /// Resolve nothing
     None,
 }
@@ -53,6 +66,22 @@ impl NatResolver {
     pub async fn external_addr(self) -> Option<IpAddr> {
         external_addr_with(self).await
     }
+
+    /// Returns a client for the port mapping protocol of the gateway, if the resolver uses one.
+    ///
+    /// Returns `None` if no gateway was configured and the default gateway can't be determined.
+    pub fn port_mapping_client(self) -> Option<PortMappingClient> {
+        let (protocol, gateway) = match self {
+            NatResolver::NatPmp(gateway) => (GatewayProtocol::NatPmp, gateway),
+            NatResolver::Pcp(gateway) => (GatewayProtocol::Pcp, gateway),
+            _ => return None,
+        };
+        let Some(gateway) = gateway.or_else(|| default_gateway().map(IpAddr::V4)) else {
+            debug!(target: "net::nat", ?protocol, "Failed to find default gateway");
+            return None
+        };
+        Some(PortMappingClient::new(protocol, gateway))
+    }
 }
 
 impl fmt::Display for NatResolver {
@@ -62,6 +91,10 @@ impl fmt::Display for NatResolver {
             NatResolver::Upnp => f.write_str("upnp"),
             NatResolver::PublicIp => f.write_str("publicip"),
             NatResolver::ExternalIp(ip) => write!(f, "extip:{ip}"),
+            NatResolver::NatPmp(None) => f.write_str("natpmp"),
+            NatResolver::NatPmp(Some(gateway)) => write!(f, "natpmp:{gateway}"),
+            NatResolver::Pcp(None) => f.write_str("pcp"),
+            NatResolver::Pcp(Some(gateway)) => write!(f, "pcp:{gateway}"),
             NatResolver::None => f.write_str("none"),
         }
     }
@@ -87,7 +120,15 @@ impl FromStr for NatResolver {
             "upnp" => NatResolver::Upnp,
             "none" => NatResolver::None,
             "publicip" | "public-ip" => NatResolver::PublicIp,
+            "natpmp" => NatResolver::NatPmp(None),
+            "pcp" => NatResolver::Pcp(None),
             s => {
+                if let Some(gateway) = s.strip_prefix("natpmp:") {
+                    return Ok(NatResolver::NatPmp(Some(gateway.parse::<IpAddr>()?)))
+                }
+                if let Some(gateway) = s.strip_prefix("pcp:") {
+                    return Ok(NatResolver::Pcp(Some(gateway.parse::<IpAddr>()?)))
+                }
                 let Some(ip) = s.strip_prefix("extip:") else {
                     return Err(ParseNatResolverError::UnknownVariant(format!(
                         "Unknown Nat Resolver: {s}"
@@ -104,8 +145,13 @@ impl FromStr for NatResolver {
 #[must_use = "Does nothing unless polled"]
 pub struct ResolveNatInterval {
     resolver: NatResolver,
-    future: Option<ResolveFut>,
+    future: Option<MapPortsFut>,
     interval: tokio::time::Interval,
+    port_mappings: Vec<PortMapping>,
+    /// The ports that were mapped on the last tick.
+    mapped_ports: Vec<MappedPort>,
+    /// Renews the mapped ports before the leases the gateway granted expire.
+    renewal: Option<Pin<Box<tokio::time::Sleep>>>,
 }
 
 // === impl ResolveNatInterval ===
@@ -116,13 +162,41 @@ impl fmt::Debug for ResolveNatInterval {
             .field("resolver", &self.resolver)
             .field("future", &self.future.as_ref().map(drop))
             .field("interval", &self.interval)
+            .field("port_mappings", &self.port_mappings)
+            .field("mapped_ports", &self.mapped_ports)
+            .field("renewal", &self.renewal.as_ref().map(|renewal| renewal.deadline()))
             .finish()
     }
 }
 
 impl ResolveNatInterval {
     fn with_interval(resolver: NatResolver, interval: tokio::time::Interval) -> Self {
-        Self { resolver, future: None, interval }
+        Self {
+            resolver,
+            future: None,
+            interval,
+            port_mappings: Vec::new(),
+            mapped_ports: Vec::new(),
+            renewal: None,
+        }
+    }
+
+    /// Maps the given ports on the gateway on every tick, if the resolver supports port mapping.
+    ///
+    /// The external IP is then resolved from the mappings. Leases are requested for twice the
+    /// period of the interval. If the gateway grants a shorter lifetime, the ports are mapped again
+    /// halfway through it, so that they're renewed before they expire.
+    pub fn with_port_mappings(mut self, mappings: impl IntoIterator<Item = PortMapping>) -> Self {
+        self.port_mappings.extend(mappings);
+        self
+    }
+
+    /// Returns the ports that were mapped on the last tick.
+    ///
+    /// The gateway may have assigned external ports that differ from the local ones, these are the
+    /// ports that must be announced to peers.
+    pub fn mapped_ports(&self) -> &[MappedPort] {
+        &self.mapped_ports
     }
 
     /// Creates a new [ResolveNatInterval] that attempts to resolve the public IP with interval of
@@ -159,13 +233,45 @@ impl ResolveNatInterval {
     ///  * `Poll::Ready(Option<IpAddr>)` if the next [IpAddr] has been resolved. This returns `None`
     ///    if the attempt was unsuccessful.
     pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Option<IpAddr>> {
-        if self.interval.poll_tick(cx).is_ready() {
-            self.future = Some(Box::pin(self.resolver.external_addr()));
+        let renew =
+            self.renewal.as_mut().map_or(false, |renewal| renewal.as_mut().poll(cx).is_ready());
+        if self.interval.poll_tick(cx).is_ready() || renew {
+            self.renewal = None;
+            let client = (!self.port_mappings.is_empty())
+                .then(|| self.resolver.port_mapping_client())
+                .flatten();
+            let future: MapPortsFut = match client {
+                Some(client) => {
+                    let mapping = port_mapping::map_ports(
+                        client,
+                        self.port_mappings.clone(),
+                        self.interval.period() * 2,
+                    );
+                    Box::pin(async move {
+                        let mapped_ports = mapping.await;
+                        (mapped_ports.last().map(|mapped| mapped.external_addr), mapped_ports)
+                    })
+                }
+                None => {
+                    let resolve = self.resolver.external_addr();
+                    Box::pin(async move { (resolve.await, Vec::new()) })
+                }
+            };
+            self.future = Some(future);
         }
 
         if let Some(mut fut) = self.future.take() {
             match fut.as_mut().poll(cx) {
-                Poll::Ready(ip) => return Poll::Ready(ip),
+                Poll::Ready((ip, mapped_ports)) => {
+                    if let Some(delay) = port_mapping::renewal_delay(&mapped_ports) {
+                        let mut renewal = Box::pin(tokio::time::sleep(delay));
+                        // register the waker of the timer
+                        let _ = renewal.as_mut().poll(cx);
+                        self.renewal = Some(renewal);
+                    }
+                    self.mapped_ports = mapped_ports;
+                    return Poll::Ready(ip)
+                }
                 Poll::Pending => {
                     self.future = Some(fut);
                 }
@@ -194,12 +300,20 @@ pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
         NatResolver::Upnp => resolve_external_ip_upnp().await,
         NatResolver::PublicIp => resolve_external_ip().await,
         NatResolver::ExternalIp(ip) => Some(ip),
+        NatResolver::NatPmp(_) => resolve_external_ip_nat_pmp(resolver).await,
+        NatResolver::Pcp(_) => {
+            debug!(target: "net::nat", "PCP resolves the external IP only when mapping ports");
+            None
+        }
         NatResolver::None => None,
     }
 }
 
 type ResolveFut = Pin<Box<dyn Future<Output = Option<IpAddr>> + Send>>;
 
+/// Resolves the external IP and the ports that were mapped on the gateway.
+type MapPortsFut = Pin<Box<dyn Future<Output = (Option<IpAddr>, Vec<MappedPort>)> + Send>>;
+
 pin_project! {
     /// A future that resolves the first ip via all configured resolvers
     struct ResolveAny {
@@ -259,6 +373,18 @@ async fn resolve_external_ip_upnp() -> Option<IpAddr> {
         .ok()
 }
 
+async fn resolve_external_ip_nat_pmp(resolver: NatResolver) -> Option<IpAddr> {
+    resolver
+        .port_mapping_client()?
+        .external_addr()
+        .await
+        .map_err(|err| {
+            debug!(target: "net::nat", ?err, "Failed to resolve external IP via NAT-PMP");
+            err
+        })
+        .ok()
+}
+
 async fn resolve_external_ip() -> Option<IpAddr> {
     public_ip::addr().await
 }
@@ -297,5 +423,16 @@ mod tests {
         let s = "extip:0.0.0.0";
         assert_eq!(ip, s.parse().unwrap());
         assert_eq!(ip.to_string().as_str(), s);
+
+        assert_eq!(NatResolver::NatPmp(None), "natpmp".parse().unwrap());
+        assert_eq!(NatResolver::Pcp(None), "pcp".parse().unwrap());
+        let gateway = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1));
+        for (resolver, s) in [
+            (NatResolver::NatPmp(Some(gateway)), "natpmp:192.168.1.1"),
+            (NatResolver::Pcp(Some(gateway)), "pcp:192.168.1.1"),
+        ] {
+            assert_eq!(resolver, s.parse().unwrap());
+            assert_eq!(resolver.to_string().as_str(), s);
+        }
     }
 }
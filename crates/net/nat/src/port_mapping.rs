@@ -0,0 +1,583 @@
+//! Port mapping via NAT-PMP ([RFC 6886](https://www.rfc-editor.org/rfc/rfc6886)) and PCP
+//! ([RFC 6887](https://www.rfc-editor.org/rfc/rfc6887)).
+//!
+//! Both protocols are spoken with the gateway over UDP. Mappings are leases that expire after their
+//! lifetime, so they need to be renewed periodically, see
+//! [`ResolveNatInterval::with_port_mappings`](crate::ResolveNatInterval::with_port_mappings).
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use tracing::{debug, trace, warn};
+
+/// The port gateways listen on for NAT-PMP and PCP requests.
+pub const GATEWAY_PORT: u16 = 5351;
+
+/// How long to wait for the first response of the gateway, doubled for every retry.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// How often a request is sent before giving up.
+const MAX_ATTEMPTS: u32 = 4;
+
+const NAT_PMP_VERSION: u8 = 0;
+const PCP_VERSION: u8 = 2;
+/// Opcode of the NAT-PMP external address request.
+const NAT_PMP_OP_EXTERNAL_ADDR: u8 = 0;
+/// Opcode of the PCP MAP request.
+const PCP_OP_MAP: u8 = 1;
+/// Set in the opcode of all responses.
+const RESPONSE_BIT: u8 = 0x80;
+/// Size of a PCP MAP request and response.
+const PCP_MAP_SIZE: usize = 60;
+
+/// The protocol spoken with the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GatewayProtocol {
+    /// NAT Port Mapping Protocol
+    NatPmp,
+    /// Port Control Protocol, the successor of NAT-PMP
+    Pcp,
+}
+
+/// The transport protocol of a mapped port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportProtocol {
+    /// TCP
+    Tcp,
+    /// UDP
+    Udp,
+}
+
+impl TransportProtocol {
+    /// The opcode of a NAT-PMP mapping request for this protocol.
+    fn nat_pmp_opcode(self) -> u8 {
+        match self {
+            TransportProtocol::Udp => 1,
+            TransportProtocol::Tcp => 2,
+        }
+    }
+
+    /// The IANA protocol number used by PCP.
+    fn protocol_number(self) -> u8 {
+        match self {
+            TransportProtocol::Tcp => 6,
+            TransportProtocol::Udp => 17,
+        }
+    }
+}
+
+/// A local port to map on the gateway.
+///
+/// The gateway is asked to map the same external port, but may assign a different one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortMapping {
+    /// The transport protocol of the port.
+    pub protocol: TransportProtocol,
+    /// The local port.
+    pub port: u16,
+}
+
+impl PortMapping {
+    /// A mapping for the given TCP port.
+    pub const fn tcp(port: u16) -> Self {
+        Self { protocol: TransportProtocol::Tcp, port }
+    }
+
+    /// A mapping for the given UDP port.
+    pub const fn udp(port: u16) -> Self {
+        Self { protocol: TransportProtocol::Udp, port }
+    }
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappedPort {
+    /// The requested mapping.
+    pub mapping: PortMapping,
+    /// The external address of the gateway.
+    pub external_addr: IpAddr,
+    /// The external port assigned by the gateway.
+    pub external_port: u16,
+    /// How long the gateway keeps the mapping.
+    pub lifetime: Duration,
+}
+
+/// Errors when talking to the gateway.
+#[derive(Debug, thiserror::Error)]
+pub enum PortMappingError {
+    /// Failed to send or receive a request.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The gateway did not respond.
+    #[error("gateway did not respond")]
+    Timeout,
+    /// The gateway rejected the request.
+    #[error("gateway rejected the request with result code {0}")]
+    Rejected(u16),
+    /// The protocol can't resolve the external address without mapping a port.
+    #[error("PCP resolves the external address only when mapping ports")]
+    Unsupported,
+}
+
+/// A client for the NAT-PMP or PCP server of a gateway.
+#[derive(Debug, Clone)]
+pub struct PortMappingClient {
+    /// The protocol spoken with the gateway.
+    protocol: GatewayProtocol,
+    /// The address of the gateway's server.
+    gateway: SocketAddr,
+    /// Identifies the PCP mappings of this client, so that they can be renewed.
+    nonce: [u8; 12],
+}
+
+impl PortMappingClient {
+    /// Creates a new client for the given gateway.
+    pub fn new(protocol: GatewayProtocol, gateway: IpAddr) -> Self {
+        Self::with_gateway_addr(protocol, SocketAddr::new(gateway, GATEWAY_PORT))
+    }
+
+    /// Creates a new client for a gateway that listens on a custom address.
+    pub fn with_gateway_addr(protocol: GatewayProtocol, gateway: SocketAddr) -> Self {
+        Self { protocol, gateway, nonce: rand::random() }
+    }
+
+    /// Returns the protocol spoken with the gateway.
+    pub fn protocol(&self) -> GatewayProtocol {
+        self.protocol
+    }
+
+    /// Requests the external address of the gateway.
+    ///
+    /// This is only supported by NAT-PMP. With PCP, the external address is part of every
+    /// [`MappedPort`].
+    pub async fn external_addr(&self) -> Result<IpAddr, PortMappingError> {
+        if self.protocol == GatewayProtocol::Pcp {
+            return Err(PortMappingError::Unsupported)
+        }
+        let socket = self.connect().await?;
+        let request = [NAT_PMP_VERSION, NAT_PMP_OP_EXTERNAL_ADDR];
+        send_request(&socket, &request, |response| {
+            let response = parse_nat_pmp_response(response, NAT_PMP_OP_EXTERNAL_ADDR, 12)?;
+            Some(response.map(|response| {
+                IpAddr::V4(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+            }))
+        })
+        .await
+    }
+
+    /// Maps the port on the gateway for the given lifetime.
+    ///
+    /// Requesting an existing mapping again renews its lease.
+    pub async fn map(
+        &self,
+        mapping: PortMapping,
+        lifetime: Duration,
+    ) -> Result<MappedPort, PortMappingError> {
+        let lifetime = lifetime.as_secs().min(u32::MAX as u64) as u32;
+        match self.protocol {
+            GatewayProtocol::NatPmp => self.map_nat_pmp(mapping, lifetime).await,
+            GatewayProtocol::Pcp => self.map_pcp(mapping, lifetime).await,
+        }
+    }
+
+    async fn map_nat_pmp(
+        &self,
+        mapping: PortMapping,
+        lifetime: u32,
+    ) -> Result<MappedPort, PortMappingError> {
+        let external_addr = self.external_addr().await?;
+
+        let socket = self.connect().await?;
+        let opcode = mapping.protocol.nat_pmp_opcode();
+        let mut request = [0u8; 12];
+        request[0] = NAT_PMP_VERSION;
+        request[1] = opcode;
+        request[4..6].copy_from_slice(&mapping.port.to_be_bytes());
+        request[6..8].copy_from_slice(&mapping.port.to_be_bytes());
+        request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+        send_request(&socket, &request, |response| {
+            let response = match parse_nat_pmp_response(response, opcode, 16)? {
+                Ok(response) => response,
+                Err(err) => return Some(Err(err)),
+            };
+            if read_u16(response, 8) != mapping.port {
+                return None
+            }
+            Some(Ok(MappedPort {
+                mapping,
+                external_addr,
+                external_port: read_u16(response, 10),
+                lifetime: Duration::from_secs(read_u32(response, 12) as u64),
+            }))
+        })
+        .await
+    }
+
+    async fn map_pcp(
+        &self,
+        mapping: PortMapping,
+        lifetime: u32,
+    ) -> Result<MappedPort, PortMappingError> {
+        let socket = self.connect().await?;
+        let client_addr = socket.local_addr()?.ip();
+
+        let mut request = [0u8; PCP_MAP_SIZE];
+        request[0] = PCP_VERSION;
+        request[1] = PCP_OP_MAP;
+        request[4..8].copy_from_slice(&lifetime.to_be_bytes());
+        request[8..24].copy_from_slice(&to_pcp_addr(client_addr).octets());
+        request[24..36].copy_from_slice(&self.nonce);
+        request[36] = mapping.protocol.protocol_number();
+        request[40..42].copy_from_slice(&mapping.port.to_be_bytes());
+        request[42..44].copy_from_slice(&mapping.port.to_be_bytes());
+        // no preference for the external address, in the address family of the client
+        let any_addr = match client_addr {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        request[44..60].copy_from_slice(&to_pcp_addr(any_addr).octets());
+
+        send_request(&socket, &request, |response| {
+            if response.len() >= 4 && response[0] == NAT_PMP_VERSION {
+                // the gateway only speaks NAT-PMP and rejected the unsupported version
+                return Some(Err(PortMappingError::Rejected(read_u16(response, 2))))
+            }
+            if response.len() < PCP_MAP_SIZE ||
+                response[0] != PCP_VERSION ||
+                response[1] != PCP_OP_MAP | RESPONSE_BIT ||
+                response[24..36] != self.nonce
+            {
+                return None
+            }
+            if response[3] != 0 {
+                return Some(Err(PortMappingError::Rejected(response[3] as u16)))
+            }
+            let external_addr = from_pcp_addr(&response[44..60]);
+            Some(Ok(MappedPort {
+                mapping,
+                external_addr,
+                external_port: read_u16(response, 42),
+                lifetime: Duration::from_secs(read_u32(response, 4) as u64),
+            }))
+        })
+        .await
+    }
+
+    /// Returns a socket that is connected to the gateway.
+    async fn connect(&self) -> Result<UdpSocket, PortMappingError> {
+        let local_addr: SocketAddr = match self.gateway {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local_addr).await?;
+        socket.connect(self.gateway).await?;
+        Ok(socket)
+    }
+}
+
+/// The shortest delay after which leases are renewed.
+const MIN_RENEWAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Maps all ports with the client and returns the ports that were mapped.
+///
+/// The gateway may assign a different external port and a shorter lifetime than requested.
+pub(crate) async fn map_ports(
+    client: PortMappingClient,
+    mappings: Vec<PortMapping>,
+    lifetime: Duration,
+) -> Vec<MappedPort> {
+    let mut mapped_ports = Vec::with_capacity(mappings.len());
+    for mapping in mappings {
+        match client.map(mapping, lifetime).await {
+            Ok(mapped) => {
+                trace!(target: "net::nat", ?mapped, "Mapped port");
+                if mapped.external_port != mapping.port {
+                    warn!(target: "net::nat", ?mapping, external_port = mapped.external_port, "Gateway mapped a different external port");
+                }
+                mapped_ports.push(mapped);
+            }
+            Err(err) => {
+                debug!(target: "net::nat", ?err, ?mapping, protocol = ?client.protocol(), "Failed to map port");
+            }
+        }
+    }
+    mapped_ports
+}
+
+/// Returns the delay after which the mapped ports must be renewed.
+///
+/// Leases are renewed halfway through the shortest lifetime the gateway granted.
+pub(crate) fn renewal_delay(mapped_ports: &[MappedPort]) -> Option<Duration> {
+    mapped_ports.iter().map(|mapped| (mapped.lifetime / 2).max(MIN_RENEWAL_DELAY)).min()
+}
+
+/// Returns the default IPv4 gateway of the host, if it can be determined.
+///
+/// This is only supported on Linux.
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+        parse_default_gateway(&routes)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Parses the default gateway from the content of `/proc/net/route`.
+#[cfg_attr(not(any(test, target_os = "linux")), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|route| {
+        let mut columns = route.split_whitespace().skip(1);
+        let destination = columns.next()?;
+        let gateway = columns.next()?;
+        if destination != "00000000" {
+            return None
+        }
+        // the kernel prints addresses in host byte order
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_ne_bytes())).filter(|gateway| !gateway.is_unspecified())
+    })
+}
+
+/// Sends the request until a matching response is received, retrying with an exponential backoff.
+///
+/// `parse` returns `None` for responses that don't belong to the request.
+async fn send_request<T>(
+    socket: &UdpSocket,
+    request: &[u8],
+    mut parse: impl FnMut(&[u8]) -> Option<Result<T, PortMappingError>>,
+) -> Result<T, PortMappingError> {
+    let mut timeout = INITIAL_TIMEOUT;
+    for _ in 0..MAX_ATTEMPTS {
+        socket.send(request).await?;
+        match tokio::time::timeout(timeout, recv_response(socket, &mut parse)).await {
+            Ok(response) => return response,
+            Err(_) => timeout *= 2,
+        }
+    }
+    Err(PortMappingError::Timeout)
+}
+
+/// Receives from the socket until `parse` accepts a response.
+async fn recv_response<T>(
+    socket: &UdpSocket,
+    parse: &mut impl FnMut(&[u8]) -> Option<Result<T, PortMappingError>>,
+) -> Result<T, PortMappingError> {
+    let mut buf = [0u8; 1100];
+    loop {
+        let len = socket.recv(&mut buf).await?;
+        if let Some(response) = parse(&buf[..len]) {
+            return response
+        }
+    }
+}
+
+/// Validates the header of a NAT-PMP response.
+///
+/// Returns `None` if the response does not belong to a request with the given opcode.
+fn parse_nat_pmp_response(
+    response: &[u8],
+    opcode: u8,
+    size: usize,
+) -> Option<Result<&[u8], PortMappingError>> {
+    if response.len() < 4 || response[0] != NAT_PMP_VERSION || response[1] != opcode | RESPONSE_BIT
+    {
+        return None
+    }
+    let result = read_u16(response, 2);
+    if result != 0 {
+        return Some(Err(PortMappingError::Rejected(result)))
+    }
+    (response.len() >= size).then_some(Ok(response))
+}
+
+/// PCP encodes IPv4 addresses as IPv4-mapped IPv6 addresses.
+fn to_pcp_addr(addr: IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+    }
+}
+
+fn from_pcp_addr(octets: &[u8]) -> IpAddr {
+    let mut addr = [0u8; 16];
+    addr.copy_from_slice(octets);
+    let addr = Ipv6Addr::from(addr);
+    addr.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(IpAddr::V6(addr))
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([buf[offset], buf[offset + 1], buf[offset + 2], buf[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTERNAL_ADDR: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 7);
+
+    /// A gateway that answers NAT-PMP and PCP requests and assigns the next higher external port.
+    ///
+    /// The gateway rejects mappings of port `0` and ignores the first request it receives.
+    async fn spawn_mock_gateway() -> SocketAddr {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1100];
+            let (_, _) = socket.recv_from(&mut buf).await.unwrap();
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                let request = &buf[..len];
+                let response = match (request[0], request[1]) {
+                    (NAT_PMP_VERSION, NAT_PMP_OP_EXTERNAL_ADDR) => {
+                        let mut response = vec![NAT_PMP_VERSION, RESPONSE_BIT, 0, 0, 0, 0, 0, 1];
+                        response.extend_from_slice(&EXTERNAL_ADDR.octets());
+                        response
+                    }
+                    (NAT_PMP_VERSION, opcode) => {
+                        let port = read_u16(request, 4);
+                        let result: u16 = if port == 0 { 2 } else { 0 };
+                        let mut response = vec![NAT_PMP_VERSION, opcode | RESPONSE_BIT];
+                        response.extend_from_slice(&result.to_be_bytes());
+                        response.extend_from_slice(&1u32.to_be_bytes());
+                        response.extend_from_slice(&port.to_be_bytes());
+                        response.extend_from_slice(&(port + 1).to_be_bytes());
+                        response.extend_from_slice(&request[8..12]);
+                        response
+                    }
+                    (PCP_VERSION, PCP_OP_MAP) => {
+                        let port = read_u16(request, 40);
+                        let mut response = request.to_vec();
+                        response[1] = PCP_OP_MAP | RESPONSE_BIT;
+                        response[3] = if port == 0 { 2 } else { 0 };
+                        response[8..24].fill(0);
+                        response[42..44].copy_from_slice(&(port + 1).to_be_bytes());
+                        response[44..60].copy_from_slice(&EXTERNAL_ADDR.to_ipv6_mapped().octets());
+                        response
+                    }
+                    _ => continue,
+                };
+                socket.send_to(&response, from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn nat_pmp() {
+        let gateway = spawn_mock_gateway().await;
+        let client = PortMappingClient::with_gateway_addr(GatewayProtocol::NatPmp, gateway);
+
+        assert_eq!(client.external_addr().await.unwrap(), IpAddr::V4(EXTERNAL_ADDR));
+
+        let lifetime = Duration::from_secs(600);
+        let mapped = client.map(PortMapping::tcp(30303), lifetime).await.unwrap();
+        assert_eq!(
+            mapped,
+            MappedPort {
+                mapping: PortMapping::tcp(30303),
+                external_addr: IpAddr::V4(EXTERNAL_ADDR),
+                external_port: 30304,
+                lifetime,
+            }
+        );
+
+        let err = client.map(PortMapping::udp(0), lifetime).await.unwrap_err();
+        assert!(matches!(err, PortMappingError::Rejected(2)));
+    }
+
+    #[tokio::test]
+    async fn pcp() {
+        let gateway = spawn_mock_gateway().await;
+        let client = PortMappingClient::with_gateway_addr(GatewayProtocol::Pcp, gateway);
+
+        assert!(matches!(client.external_addr().await, Err(PortMappingError::Unsupported)));
+
+        let lifetime = Duration::from_secs(600);
+        let mapped = client.map(PortMapping::udp(30303), lifetime).await.unwrap();
+        assert_eq!(mapped.external_addr, IpAddr::V4(EXTERNAL_ADDR));
+        assert_eq!(mapped.external_port, 30304);
+        assert_eq!(mapped.lifetime, lifetime);
+
+        let err = client.map(PortMapping::tcp(0), lifetime).await.unwrap_err();
+        assert!(matches!(err, PortMappingError::Rejected(2)));
+    }
+
+    #[tokio::test]
+    async fn map_all_ports() {
+        let gateway = spawn_mock_gateway().await;
+        let client = PortMappingClient::with_gateway_addr(GatewayProtocol::NatPmp, gateway);
+        let mappings = vec![PortMapping::tcp(30303), PortMapping::udp(30303)];
+
+        let mapped = map_ports(client, mappings, Duration::from_secs(600)).await;
+        assert_eq!(mapped.len(), 2);
+        for (mapped, mapping) in
+            mapped.iter().zip([PortMapping::tcp(30303), PortMapping::udp(30303)])
+        {
+            assert_eq!(mapped.mapping, mapping);
+            assert_eq!(mapped.external_addr, IpAddr::V4(EXTERNAL_ADDR));
+            assert_eq!(mapped.external_port, 30304);
+        }
+        assert_eq!(renewal_delay(&mapped), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn renew_before_shortest_lease_expires() {
+        let mapped = |mapping, secs| MappedPort {
+            mapping,
+            external_addr: IpAddr::V4(EXTERNAL_ADDR),
+            external_port: 30303,
+            lifetime: Duration::from_secs(secs),
+        };
+
+        assert_eq!(renewal_delay(&[]), None);
+        assert_eq!(
+            renewal_delay(&[
+                mapped(PortMapping::tcp(30303), 7200),
+                mapped(PortMapping::udp(30303), 120)
+            ]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(renewal_delay(&[mapped(PortMapping::udp(30303), 0)]), Some(MIN_RENEWAL_DELAY));
+    }
+
+    #[tokio::test]
+    async fn gateway_timeout() {
+        // nothing listens on this socket
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let client = PortMappingClient::with_gateway_addr(
+            GatewayProtocol::NatPmp,
+            socket.local_addr().unwrap(),
+        );
+        drop(socket);
+
+        assert!(client.external_addr().await.is_err());
+    }
+
+    #[test]
+    fn parse_proc_net_route() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+            eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        let gateway = parse_default_gateway(routes).unwrap();
+        assert_eq!(
+            gateway,
+            Ipv4Addr::from(u32::from_str_radix("0101A8C0", 16).unwrap().to_ne_bytes())
+        );
+        if cfg!(target_endian = "little") {
+            assert_eq!(gateway, Ipv4Addr::new(192, 168, 1, 1));
+        }
+
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+}
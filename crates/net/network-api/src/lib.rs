@@ -18,8 +18,12 @@
 use async_trait::async_trait;
 use reth_eth_wire::{DisconnectReason, EthVersion, Status};
 use reth_primitives::{NodeRecord, PeerId};
-use reth_rpc_types::NetworkStatus;
-use std::{net::SocketAddr, sync::Arc, time::Instant};
+use reth_rpc_types::{Bans, NetworkStatus, PeerBandwidthInfo};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 pub use error::NetworkError;
 pub use reputation::{Reputation, ReputationChangeKind};
@@ -124,6 +128,34 @@ pub trait Peers: PeersInfo {
 
     /// Get the reputation of a peer.
     async fn reputation_by_id(&self, peer_id: PeerId) -> Result<Option<Reputation>, NetworkError>;
+
+    /// Adds a node to the allowlist of the permissioned network mode.
+    ///
+    /// Returns `false` if the network is not permissioned.
+    fn add_allowed_peer(&self, peer: NodeRecord) -> bool;
+
+    /// Removes a node from the allowlist of the permissioned network mode and disconnects it.
+    ///
+    /// Returns `true` if the node was allow-listed.
+    fn remove_allowed_peer(&self, peer: PeerId) -> bool;
+
+    /// Bans the peer for the given duration, or indefinitely, and disconnects it.
+    fn ban_peer(&self, peer: PeerId, duration: Option<Duration>);
+
+    /// Lifts the ban of the peer.
+    fn unban_peer(&self, peer: PeerId);
+
+    /// Bans the IP address for the given duration, or indefinitely, and disconnects all peers
+    /// using it.
+    ///
+    /// Note: non-global IP addresses can not be banned.
+    fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>);
+
+    /// Lifts the ban of the IP address.
+    fn unban_ip(&self, ip: IpAddr);
+
+    /// Returns all banned peers and IP addresses.
+    async fn get_bans(&self) -> Result<Bans, NetworkError>;
 }
 
 /// Represents the kind of peer
@@ -157,6 +189,8 @@ pub struct PeerInfo {
     pub status: Arc<Status>,
     /// The timestamp when the session to that peer has been established.
     pub session_established: Instant,
+    /// Bytes exchanged with the peer during the session.
+    pub bandwidth: PeerBandwidthInfo,
 }
 
 /// The direction of the connection.
@@ -11,8 +11,11 @@ use async_trait::async_trait;
 use reth_discv4::DEFAULT_DISCOVERY_PORT;
 use reth_eth_wire::{DisconnectReason, ProtocolVersion};
 use reth_primitives::{Chain, NodeRecord, PeerId};
-use reth_rpc_types::{EthProtocolInfo, NetworkStatus};
-use std::net::{IpAddr, SocketAddr};
+use reth_rpc_types::{Bans, EthProtocolInfo, NetworkStatus};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 /// A type that implements all network trait that does nothing.
 ///
@@ -99,4 +102,24 @@ impl Peers for NoopNetwork {
     async fn reputation_by_id(&self, _peer_id: PeerId) -> Result<Option<Reputation>, NetworkError> {
         Ok(None)
     }
+
+    fn add_allowed_peer(&self, _peer: NodeRecord) -> bool {
+        false
+    }
+
+    fn remove_allowed_peer(&self, _peer: PeerId) -> bool {
+        false
+    }
+
+    fn ban_peer(&self, _peer: PeerId, _duration: Option<Duration>) {}
+
+    fn unban_peer(&self, _peer: PeerId) {}
+
+    fn ban_ip(&self, _ip: IpAddr, _duration: Option<Duration>) {}
+
+    fn unban_ip(&self, _ip: IpAddr) {}
+
+    async fn get_bans(&self) -> Result<Bans, NetworkError> {
+        Ok(Bans::default())
+    }
 }
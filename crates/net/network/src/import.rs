@@ -1,8 +1,45 @@
 //! This module provides an abstraction over block import in the form of the `BlockImport` trait.
 
-use crate::message::NewBlockMessage;
-use reth_primitives::PeerId;
-use std::task::{Context, Poll};
+use crate::{cache::LruCache, message::NewBlockMessage, FetchClient};
+use reth_eth_wire::{BlockHashNumber, NewBlock, NewBlockHashes};
+use reth_interfaces::{
+    blockchain_tree::{
+        error::InsertBlockErrorKind, BlockStatus, BlockValidationKind, BlockchainTreeEngine,
+        BlockchainTreeViewer, InsertPayloadOk,
+    },
+    consensus::Consensus,
+    p2p::full_block::FullBlockClient,
+};
+use reth_primitives::{PeerId, SealedBlock, SealedHeader, B256, U128};
+use reth_provider::{BlockNumReader, HeaderProvider};
+use reth_tasks::TaskSpawner;
+use std::{
+    collections::HashSet,
+    num::NonZeroUsize,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+
+/// The number of recently announced blocks [`ConsensusBlockImport`] remembers, to import every
+/// block only once.
+const RECENTLY_ANNOUNCED_BLOCKS: usize = 128;
+
+/// The maximum number of blocks announced via `NewBlockHashes` that are fetched at the same time.
+const MAX_INFLIGHT_FETCHES: usize = 64;
+
+/// The time after which the fetch of an announced block is abandoned.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Limits: <https://github.com/ethereum/go-ethereum/blob/v1.13.5/eth/fetcher/block_fetcher.go#L36-L38>
+
+/// Announced blocks that are more than this number of blocks behind the head are not fetched.
+const MAX_UNCLE_DISTANCE: u64 = 7;
+
+/// Announced blocks that are more than this number of blocks ahead of the head are not fetched.
+const MAX_QUEUE_DISTANCE: u64 = 32;
 
 /// Abstraction over block import.
 pub trait BlockImport: std::fmt::Debug + Send + Sync {
@@ -15,6 +52,19 @@ pub trait BlockImport: std::fmt::Debug + Send + Sync {
     /// [`BlockImport::poll`].
     fn on_new_block(&mut self, peer_id: PeerId, incoming_block: NewBlockMessage);
 
+    /// Invoked for a received `NewBlockHashes` announcement from the peer.
+    ///
+    /// The announced blocks can be fetched with the given [`FetchClient`], and are then expected to
+    /// be imported like blocks received via [`BlockImport::on_new_block`]. Announcements are
+    /// ignored by default.
+    fn on_new_block_hashes(
+        &mut self,
+        _peer_id: PeerId,
+        _hashes: NewBlockHashes,
+        _client: &FetchClient,
+    ) {
+    }
+
     /// Returns the results of a [`BlockImport::on_new_block`]
     fn poll(&mut self, cx: &mut Context<'_>) -> Poll<BlockImportOutcome>;
 }
@@ -51,6 +101,9 @@ pub enum BlockImportError {
     /// Consensus error
     #[error(transparent)]
     Consensus(#[from] reth_interfaces::consensus::ConsensusError),
+    /// The block could not be inserted into the blockchain tree because it is invalid.
+    #[error(transparent)]
+    Insert(#[from] InsertBlockErrorKind),
 }
 
 /// An implementation of `BlockImport` used in Proof-of-Stake consensus that does nothing.
@@ -67,3 +120,582 @@ impl BlockImport for ProofOfStakeBlockImport {
         Poll::Pending
     }
 }
+
+/// A [`BlockImport`] for chains that propagate blocks over devp2p, like private chains that are
+/// not driven by a beacon node.
+///
+/// Like geth's block fetcher, announced blocks are checked with the configured [`Consensus`],
+/// first on their own and then against their parent. Blocks that pass are relayed to a subset of
+/// peers via `NewBlock` right away, and then executed and inserted into the blockchain tree that is
+/// shared with the engine. Blocks that extend the canonical chain are announced to the remaining
+/// peers via `NewBlockHashes`. Making them canonical is up to the consensus engine.
+///
+/// Blocks whose parent is unknown are not relayed, the tree buffers them until the parent is
+/// inserted. Blocks announced via `NewBlockHashes` are fetched from the network if they are close
+/// to the canonical head, and imported the same way.
+///
+/// Blocks are inserted into the tree directly instead of being sent to the engine as new payloads,
+/// because an execution payload can't represent pre-merge blocks: it has no difficulty, nonce or
+/// ommers and limits the extra data to 32 bytes, so a clique block for example would not keep its
+/// hash.
+///
+/// Every block is imported only once, no matter how many peers announce it.
+#[derive(Debug)]
+pub struct ConsensusBlockImport<Provider, Tree> {
+    /// Validates announced blocks.
+    consensus: Arc<dyn Consensus>,
+    /// Provides the canonical headers.
+    provider: Provider,
+    /// The blockchain tree to insert validated blocks into.
+    tree: Tree,
+    /// Spawns the tasks that fetch and insert blocks.
+    executor: Box<dyn TaskSpawner>,
+    /// Hashes of the recently announced blocks.
+    recently_announced: LruCache<B256>,
+    /// Hashes of the announced blocks that are being fetched.
+    fetching: HashSet<B256>,
+    /// Sender half for fetched blocks, `None` if the fetch failed.
+    fetched_tx: mpsc::UnboundedSender<(PeerId, B256, Option<SealedBlock>)>,
+    /// Receiver half for fetched blocks.
+    fetched_rx: mpsc::UnboundedReceiver<(PeerId, B256, Option<SealedBlock>)>,
+    /// Sender half for the outcomes of block imports.
+    outcome_tx: mpsc::UnboundedSender<BlockImportOutcome>,
+    /// Receiver half for the outcomes of block imports.
+    outcome_rx: mpsc::UnboundedReceiver<BlockImportOutcome>,
+}
+
+// === impl ConsensusBlockImport ===
+
+impl<Provider, Tree> ConsensusBlockImport<Provider, Tree> {
+    /// Creates a new block import that validates blocks with the given consensus against the
+    /// headers of the provider and the tree, and inserts them into the given tree.
+    pub fn new(
+        consensus: Arc<dyn Consensus>,
+        provider: Provider,
+        tree: Tree,
+        executor: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let (fetched_tx, fetched_rx) = mpsc::unbounded_channel();
+        let (outcome_tx, outcome_rx) = mpsc::unbounded_channel();
+        Self {
+            consensus,
+            provider,
+            tree,
+            executor,
+            recently_announced: LruCache::new(
+                NonZeroUsize::new(RECENTLY_ANNOUNCED_BLOCKS).expect("not zero"),
+            ),
+            fetching: HashSet::new(),
+            fetched_tx,
+            fetched_rx,
+            outcome_tx,
+            outcome_rx,
+        }
+    }
+}
+
+impl<Provider, Tree> ConsensusBlockImport<Provider, Tree>
+where
+    Provider: HeaderProvider + BlockNumReader + Clone + std::fmt::Debug + 'static,
+    Tree: BlockchainTreeEngine + Clone + std::fmt::Debug + 'static,
+{
+    /// Validates the block on its own and spawns its import.
+    ///
+    /// The total difficulty is the one announced with the block, if it was received via `NewBlock`.
+    fn import(&mut self, peer_id: PeerId, block: SealedBlock, td: Option<U128>) {
+        if self.recently_announced.contains(&block.hash) {
+            trace!(target: "net::import", hash=?block.hash, ?peer_id, "Ignoring block that is already imported");
+            return
+        }
+
+        if let Err(err) = self
+            .consensus
+            .validate_header(&block.header)
+            .and_then(|_| self.consensus.validate_block(&block))
+        {
+            debug!(target: "net::import", hash=?block.hash, number=block.number, ?peer_id, %err, "Received invalid block");
+            let _ =
+                self.outcome_tx.send(BlockImportOutcome { peer: peer_id, result: Err(err.into()) });
+            return
+        }
+        // The hash only commits to the header, so the block is only remembered once its body is
+        // known to match it. Otherwise a peer could announce the hash with an invalid body first,
+        // and make the valid block be ignored.
+        self.recently_announced.insert(block.hash);
+
+        let consensus = Arc::clone(&self.consensus);
+        let provider = self.provider.clone();
+        let tree = self.tree.clone();
+        let outcome_tx = self.outcome_tx.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            import_block(&*consensus, &provider, &tree, block, td, |result| {
+                let _ = outcome_tx.send(BlockImportOutcome { peer: peer_id, result });
+            });
+        }));
+    }
+}
+
+impl<Provider, Tree> BlockImport for ConsensusBlockImport<Provider, Tree>
+where
+    Provider: HeaderProvider + BlockNumReader + Clone + std::fmt::Debug + 'static,
+    Tree: BlockchainTreeEngine + Clone + std::fmt::Debug + 'static,
+{
+    fn on_new_block(&mut self, peer_id: PeerId, incoming_block: NewBlockMessage) {
+        let td = incoming_block.block.td;
+        let block = incoming_block.block.block.clone().seal(incoming_block.hash);
+        self.import(peer_id, block, Some(td));
+    }
+
+    fn on_new_block_hashes(
+        &mut self,
+        peer_id: PeerId,
+        hashes: NewBlockHashes,
+        client: &FetchClient,
+    ) {
+        let head = self.provider.best_block_number().unwrap_or_default();
+        for BlockHashNumber { hash, number } in hashes.0 {
+            if self.recently_announced.contains(&hash) ||
+                self.fetching.contains(&hash) ||
+                self.tree.block_by_hash(hash).is_some()
+            {
+                continue
+            }
+            if number > head + MAX_QUEUE_DISTANCE || number + MAX_UNCLE_DISTANCE < head {
+                trace!(target: "net::import", ?hash, number, head, ?peer_id, "Ignoring announced block that is too far from the head");
+                continue
+            }
+            if self.fetching.len() >= MAX_INFLIGHT_FETCHES {
+                debug!(target: "net::import", ?hash, number, ?peer_id, "Too many announced blocks are being fetched");
+                break
+            }
+
+            self.fetching.insert(hash);
+            let client = FullBlockClient::new(client.clone(), Arc::clone(&self.consensus));
+            let fetched_tx = self.fetched_tx.clone();
+            self.executor.spawn(Box::pin(async move {
+                let block = tokio::time::timeout(FETCH_TIMEOUT, client.get_full_block(hash)).await;
+                let _ = fetched_tx.send((peer_id, hash, block.ok()));
+            }));
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<BlockImportOutcome> {
+        // the import holds a sender of both channels itself, so they are never closed
+        while let Poll::Ready(Some((peer_id, hash, block))) = self.fetched_rx.poll_recv(cx) {
+            self.fetching.remove(&hash);
+            match block {
+                Some(block) => self.import(peer_id, block, None),
+                None => {
+                    debug!(target: "net::import", ?hash, ?peer_id, "Failed to fetch announced block")
+                }
+            }
+        }
+
+        match self.outcome_rx.poll_recv(cx) {
+            Poll::Ready(Some(outcome)) => Poll::Ready(outcome),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+/// Validates the block against its parent, and executes and inserts it into the tree.
+///
+/// Reports the outcomes as they happen: the block is relayed before it is executed, like pre-merge
+/// clients do, if its parent is known and its total difficulty is either announced or can be
+/// derived from the parent. It is announced if it extends the canonical chain.
+fn import_block<Provider, Tree>(
+    consensus: &dyn Consensus,
+    provider: &Provider,
+    tree: &Tree,
+    block: SealedBlock,
+    td: Option<U128>,
+    mut report: impl FnMut(Result<BlockValidation, BlockImportError>),
+) where
+    Provider: HeaderProvider,
+    Tree: BlockchainTreeEngine,
+{
+    // the tree holds the blocks that are not persisted yet
+    let parent_hash = block.parent_hash;
+    let parent = tree.header_by_hash(parent_hash).or_else(|| {
+        provider.header(&parent_hash).ok().flatten().map(|header| header.seal(parent_hash))
+    });
+    match parent {
+        Some(parent) => {
+            if let Err(err) = consensus.validate_header_against_parent(&block.header, &parent) {
+                debug!(target: "net::import", hash=?block.hash, number=block.number, %err, "Received block that is invalid on its parent");
+                report(Err(err.into()));
+                return
+            }
+            match td.or_else(|| total_difficulty(provider, &parent, &block)) {
+                Some(td) => report(Ok(BlockValidation::ValidHeader {
+                    block: new_block_message(&block, td),
+                })),
+                None => {
+                    trace!(target: "net::import", hash=?block.hash, "Not relaying block with unknown total difficulty")
+                }
+            }
+        }
+        None => {
+            trace!(target: "net::import", hash=?block.hash, ?parent_hash, "Not relaying block with unknown parent")
+        }
+    }
+
+    let hash = block.hash;
+    match tree.insert_block_without_senders(block.clone(), BlockValidationKind::Exhaustive) {
+        Ok(InsertPayloadOk::Inserted(BlockStatus::Valid)) => {
+            let td = td.unwrap_or_default();
+            report(Ok(BlockValidation::ValidBlock { block: new_block_message(&block, td) }))
+        }
+        Ok(status) => {
+            trace!(target: "net::import", ?hash, ?status, "Inserted block that does not extend the canonical chain");
+        }
+        Err(err) => {
+            let (block, kind) = err.split();
+            if !kind.is_invalid_block() {
+                debug!(target: "net::import", hash=?block.hash, number=block.number, %kind, "Failed to insert block");
+                return
+            }
+            debug!(target: "net::import", hash=?block.hash, number=block.number, %kind, "Received block that failed execution");
+            report(Err(kind.into()));
+        }
+    }
+}
+
+/// Returns the total difficulty of the block if the total difficulty of its parent is known.
+fn total_difficulty<Provider: HeaderProvider>(
+    provider: &Provider,
+    parent: &SealedHeader,
+    block: &SealedBlock,
+) -> Option<U128> {
+    let parent_td = provider.header_td(&parent.hash).ok().flatten()?;
+    u128::try_from(parent_td + block.difficulty).ok().map(U128::from)
+}
+
+/// Creates the message to relay or announce the block with.
+fn new_block_message(block: &SealedBlock, td: U128) -> NewBlockMessage {
+    NewBlockMessage {
+        hash: block.hash,
+        block: Arc::new(NewBlock { block: block.clone().unseal(), td }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetch::{BlockResponseOutcome, FetchAction, StateFetcher},
+        message::BlockRequest,
+        peers::PeersManager,
+        PeersConfig,
+    };
+    use reth_blockchain_tree::noop::NoopBlockchainTree;
+    use reth_interfaces::{
+        blockchain_tree::{
+            error::{InsertBlockError, InsertBlockErrorKind},
+            BlockchainTreeViewer, CanonicalOutcome,
+        },
+        executor::{BlockExecutionError, BlockValidationError},
+        test_utils::{generators, generators::random_block, TestConsensus},
+        RethResult,
+    };
+    use reth_primitives::{
+        BlockBody, BlockHash, BlockNumHash, BlockNumber, Header, Receipt, SealedBlockWithSenders,
+        U256,
+    };
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_tasks::TokioTaskExecutor;
+    use std::{collections::BTreeMap, future::poll_fn, sync::atomic::AtomicU64};
+
+    /// A tree that executes every inserted block with the same result, and is empty otherwise.
+    #[derive(Clone, Debug)]
+    struct TestTree {
+        /// Whether inserted blocks pass execution.
+        valid: bool,
+    }
+
+    impl BlockchainTreeEngine for TestTree {
+        fn buffer_block(&self, _block: SealedBlockWithSenders) -> Result<(), InsertBlockError> {
+            Ok(())
+        }
+
+        fn insert_block(
+            &self,
+            block: SealedBlockWithSenders,
+            _validation_kind: BlockValidationKind,
+        ) -> Result<InsertPayloadOk, InsertBlockError> {
+            if self.valid {
+                return Ok(InsertPayloadOk::Inserted(BlockStatus::Valid))
+            }
+            Err(InsertBlockError::execution_error(
+                BlockExecutionError::Validation(BlockValidationError::IncrementBalanceFailed),
+                block.block,
+            ))
+        }
+
+        fn finalize_block(&self, _finalized_block: BlockNumber) {}
+
+        fn connect_buffered_blocks_to_canonical_hashes_and_finalize(
+            &self,
+            _last_finalized_block: BlockNumber,
+        ) -> RethResult<()> {
+            Ok(())
+        }
+
+        fn connect_buffered_blocks_to_canonical_hashes(&self) -> RethResult<()> {
+            Ok(())
+        }
+
+        fn make_canonical(&self, block_hash: &BlockHash) -> RethResult<CanonicalOutcome> {
+            NoopBlockchainTree::default().make_canonical(block_hash)
+        }
+
+        fn unwind(&self, _unwind_to: BlockNumber) -> RethResult<()> {
+            Ok(())
+        }
+    }
+
+    impl BlockchainTreeViewer for TestTree {
+        fn blocks(&self) -> BTreeMap<BlockNumber, HashSet<BlockHash>> {
+            Default::default()
+        }
+
+        fn header_by_hash(&self, _hash: BlockHash) -> Option<SealedHeader> {
+            None
+        }
+
+        fn block_by_hash(&self, _hash: BlockHash) -> Option<SealedBlock> {
+            None
+        }
+
+        fn block_with_senders_by_hash(&self, _hash: BlockHash) -> Option<SealedBlockWithSenders> {
+            None
+        }
+
+        fn buffered_block_by_hash(&self, _block_hash: BlockHash) -> Option<SealedBlock> {
+            None
+        }
+
+        fn buffered_header_by_hash(&self, _block_hash: BlockHash) -> Option<SealedHeader> {
+            None
+        }
+
+        fn canonical_blocks(&self) -> BTreeMap<BlockNumber, BlockHash> {
+            Default::default()
+        }
+
+        fn find_canonical_ancestor(&self, _parent_hash: BlockHash) -> Option<BlockHash> {
+            None
+        }
+
+        fn is_canonical(&self, block_hash: BlockHash) -> RethResult<bool> {
+            NoopBlockchainTree::default().is_canonical(block_hash)
+        }
+
+        fn lowest_buffered_ancestor(&self, _hash: BlockHash) -> Option<SealedBlockWithSenders> {
+            None
+        }
+
+        fn canonical_tip(&self) -> BlockNumHash {
+            Default::default()
+        }
+
+        fn pending_blocks(&self) -> (BlockNumber, Vec<BlockHash>) {
+            (0, vec![])
+        }
+
+        fn pending_block_num_hash(&self) -> Option<BlockNumHash> {
+            None
+        }
+
+        fn pending_block_and_receipts(&self) -> Option<(SealedBlock, Vec<Receipt>)> {
+            None
+        }
+
+        fn receipts_by_block_hash(&self, _block_hash: BlockHash) -> Option<Vec<Receipt>> {
+            None
+        }
+    }
+
+    /// The canonical head, which is the parent of the blocks of [`announced_block`].
+    fn parent() -> SealedHeader {
+        Header { number: 0, difficulty: U256::from(10), ..Default::default() }.seal_slow()
+    }
+
+    fn announced_block(parent_hash: B256) -> NewBlockMessage {
+        let block = random_block(&mut generators::rng(), 1, Some(parent_hash), Some(0), Some(0));
+        NewBlockMessage {
+            hash: block.hash,
+            block: Arc::new(NewBlock { block: block.unseal(), td: Default::default() }),
+        }
+    }
+
+    fn block_import<Tree>(
+        consensus: Arc<TestConsensus>,
+        tree: Tree,
+    ) -> ConsensusBlockImport<MockEthProvider, Tree> {
+        let provider = MockEthProvider::default();
+        let parent = parent();
+        provider.add_header(parent.hash, parent.unseal());
+        ConsensusBlockImport::new(consensus, provider, tree, Box::<TokioTaskExecutor>::default())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn relays_valid_block_once() {
+        let mut import =
+            block_import(Arc::new(TestConsensus::default()), NoopBlockchainTree::default());
+        let peer = PeerId::random();
+        let block = announced_block(parent().hash);
+
+        import.on_new_block(peer, block.clone());
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidHeader { block: relayed }) if relayed.hash == block.hash
+        ));
+
+        // the same block announced by another peer is ignored
+        import.on_new_block(PeerId::random(), block);
+        assert!(import.outcome_rx.try_recv().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejects_invalid_block() {
+        let consensus = Arc::new(TestConsensus::default());
+        consensus.set_fail_validation(true);
+        let mut import = block_import(consensus.clone(), NoopBlockchainTree::default());
+        let peer = PeerId::random();
+        let block = announced_block(parent().hash);
+
+        import.on_new_block(peer, block.clone());
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(outcome.result, Err(BlockImportError::Consensus(_))));
+
+        // an invalid announcement doesn't prevent the block from being imported from another peer
+        consensus.set_fail_validation(false);
+        import.on_new_block(PeerId::random(), block.clone());
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidHeader { block: relayed }) if relayed.hash == block.hash
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn announces_executed_block() {
+        let mut import = block_import(Arc::new(TestConsensus::default()), TestTree { valid: true });
+        let peer = PeerId::random();
+        let block = announced_block(parent().hash);
+
+        import.on_new_block(peer, block.clone());
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(outcome.result, Ok(BlockValidation::ValidHeader { .. })));
+
+        // the block is announced to the remaining peers once it was executed
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidBlock { block: announced }) if announced.hash == block.hash
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejects_block_that_fails_execution() {
+        let mut import =
+            block_import(Arc::new(TestConsensus::default()), TestTree { valid: false });
+        let peer = PeerId::random();
+
+        import.on_new_block(peer, announced_block(parent().hash));
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(outcome.result, Ok(BlockValidation::ValidHeader { .. })));
+
+        // the peer is penalized for the bad block by the network manager
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(
+            outcome.result,
+            Err(BlockImportError::Insert(InsertBlockErrorKind::Execution(_)))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn does_not_relay_block_with_unknown_parent() {
+        let mut import =
+            block_import(Arc::new(TestConsensus::default()), TestTree { valid: false });
+        let peer = PeerId::random();
+
+        // the block is still inserted into the tree, but not relayed
+        import.on_new_block(peer, announced_block(B256::random()));
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Err(BlockImportError::Insert(InsertBlockErrorKind::Execution(_)))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fetches_announced_block() {
+        let mut import = block_import(Arc::new(TestConsensus::default()), TestTree { valid: true });
+        let block = announced_block(parent().hash);
+        let sealed = block.block.block.clone().seal(block.hash);
+
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let peer = PeerId::random();
+        fetcher.new_active_peer(peer, block.hash, 1, Arc::new(AtomicU64::new(1)));
+
+        // blocks too far from the head are not fetched
+        let hashes = NewBlockHashes(vec![
+            BlockHashNumber { hash: block.hash, number: 1 },
+            BlockHashNumber { hash: B256::random(), number: MAX_QUEUE_DISTANCE + 1 },
+        ]);
+        import.on_new_block_hashes(peer, hashes, &fetcher.client());
+        assert_eq!(import.fetching, HashSet::from([block.hash]));
+
+        // serve the header and the body of the announced block
+        let FetchAction::BlockRequest { mut peer_id, mut request } =
+            poll_fn(|cx| fetcher.poll(cx)).await;
+        for served in 1..=2 {
+            let outcome = match request {
+                BlockRequest::GetBlockHeaders(_) => fetcher
+                    .on_block_headers_response(peer_id, Ok(vec![sealed.header.clone().unseal()])),
+                BlockRequest::GetBlockBodies(_) => {
+                    let body = BlockBody {
+                        transactions: sealed.body.clone(),
+                        ommers: sealed.ommers.clone(),
+                        withdrawals: sealed.withdrawals.clone(),
+                    };
+                    fetcher.on_block_bodies_response(peer_id, Ok(vec![body]))
+                }
+            };
+            if served == 2 {
+                break
+            }
+            (peer_id, request) = match outcome {
+                Some(BlockResponseOutcome::Request(peer_id, request)) => (peer_id, request),
+                _ => {
+                    let FetchAction::BlockRequest { peer_id, request } =
+                        poll_fn(|cx| fetcher.poll(cx)).await;
+                    (peer_id, request)
+                }
+            };
+        }
+
+        // the fetched block is relayed with the total difficulty derived from its parent
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidHeader { block: relayed })
+                if relayed.hash == block.hash && relayed.block.td == U128::from(10)
+        ));
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidBlock { block: announced }) if announced.hash == block.hash
+        ));
+        assert!(import.fetching.is_empty());
+    }
+}
@@ -130,8 +130,10 @@ pub mod message;
 mod metrics;
 mod network;
 pub mod peers;
+pub mod permissions;
 pub mod protocol;
 mod session;
+pub mod snap;
 mod state;
 mod swarm;
 pub mod transactions;
@@ -143,11 +145,12 @@ pub use fetch::FetchClient;
 pub use manager::{NetworkEvent, NetworkManager};
 pub use message::PeerRequest;
 pub use network::{NetworkEvents, NetworkHandle, NetworkProtocols};
-pub use peers::PeersConfig;
+pub use peers::{PeersConfig, PeersSnapshot};
+pub use permissions::{NetworkPermissions, PermissionsSource};
 pub use session::{
-    ActiveSessionHandle, ActiveSessionMessage, Direction, PeerInfo, PendingSessionEvent,
-    PendingSessionHandle, PendingSessionHandshakeError, SessionCommand, SessionEvent, SessionId,
-    SessionLimits, SessionManager, SessionsConfig,
+    ActiveSessionHandle, ActiveSessionMessage, BandwidthLimit, Direction, PeerInfo,
+    PendingSessionEvent, PendingSessionHandle, PendingSessionHandshakeError, SessionCommand,
+    SessionEvent, SessionId, SessionLimits, SessionManager, SessionsConfig,
 };
 
 pub use reth_eth_wire::{DisconnectReason, HelloMessageWithProtocols};
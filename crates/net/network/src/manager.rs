@@ -26,7 +26,7 @@ use crate::{
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerRequestSender},
     metrics::{DisconnectMetrics, NetworkMetrics, NETWORK_POOL_TRANSACTIONS_SCOPE},
     network::{NetworkHandle, NetworkHandleMessage},
-    peers::{PeersHandle, PeersManager},
+    peers::{PeersHandle, PeersManager, PeersSnapshot},
     protocol::IntoRlpxSubProtocol,
     session::SessionManager,
     state::NetworkState,
@@ -169,6 +169,7 @@ where
             client,
             secret_key,
             mut discovery_v4_config,
+            mut discovery_v5_config,
             discovery_addr,
             listener_addr,
             peers_config,
@@ -184,6 +185,7 @@ where
             dns_discovery_config,
             extra_protocols,
             tx_gossip_disabled,
+            permissions,
             #[cfg(feature = "optimism")]
                 optimism_network_config: crate::config::OptimismNetworkConfig { sequencer_endpoint },
         } = config;
@@ -203,9 +205,20 @@ where
             disc_config
         });
 
-        let discovery =
-            Discovery::new(discovery_addr, secret_key, discovery_v4_config, dns_discovery_config)
-                .await?;
+        if let Some(disc_config) = discovery_v5_config.as_mut() {
+            // advertise the fork id and the actual RLPx port in the ENR
+            disc_config.set_fork_id(status.forkid);
+            disc_config.set_tcp_port(listener_address.lock().port());
+        }
+
+        let discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            discovery_v4_config,
+            discovery_v5_config,
+            dns_discovery_config,
+        )
+        .await?;
         // need to retrieve the addr here since provided port could be `0`
         let local_peer_id = discovery.local_id();
 
@@ -221,6 +234,7 @@ where
             fork_filter,
             extra_protocols,
             bandwidth_meter.clone(),
+            permissions.clone(),
         );
 
         let state = NetworkState::new(
@@ -246,6 +260,7 @@ where
             bandwidth_meter,
             Arc::new(AtomicU64::new(chain_spec.chain.id())),
             tx_gossip_disabled,
+            permissions,
             #[cfg(feature = "optimism")]
             sequencer_endpoint,
         );
@@ -334,6 +349,14 @@ where
         self.swarm.state().peers().iter_peers()
     }
 
+    /// Returns a snapshot of the peer set, including reputations, backoffs and bans, that can be
+    /// persisted and restored on the next start.
+    ///
+    /// See also [`PeersConfig::with_snapshot`](crate::PeersConfig::with_snapshot).
+    pub fn peers_snapshot(&self) -> PeersSnapshot {
+        self.swarm.state().peers().snapshot()
+    }
+
     /// Returns a new [`PeersHandle`] that can be cloned and shared.
     ///
     /// The [`PeersHandle`] can be used to interact with the network's peer set.
@@ -497,7 +520,10 @@ where
             PeerMessage::NewBlockHashes(hashes) => {
                 self.within_pow_or_disconnect(peer_id, |this| {
                     // update peer's state, to track what blocks this peer has seen
-                    this.swarm.state_mut().on_new_block_hashes(peer_id, hashes.0)
+                    this.swarm.state_mut().on_new_block_hashes(peer_id, hashes.0.clone());
+                    // fetch the announced blocks
+                    let client = this.fetch_client();
+                    this.block_import.on_new_block_hashes(peer_id, hashes, &client);
                 })
             }
             PeerMessage::NewBlock(block) => {
@@ -911,6 +937,13 @@ where
                                 ReputationChangeKind::BadProtocol,
                             );
                         }
+                        SwarmEvent::BandwidthExceeded { peer_id } => {
+                            this.swarm.state_mut().peers_mut().apply_reputation_change(
+                                &peer_id,
+                                ReputationChangeKind::ExceededBandwidth,
+                            );
+                            this.metrics.bandwidth_limit_violations.increment(1);
+                        }
                     }
                 }
             }
@@ -125,6 +125,17 @@ impl PeerRequest {
         };
     }
 
+    /// Returns the maximum number of items a valid response to this request contains.
+    pub fn max_response_items(&self) -> usize {
+        match self {
+            PeerRequest::GetBlockHeaders { request, .. } => request.limit as usize,
+            PeerRequest::GetBlockBodies { request, .. } => request.0.len(),
+            PeerRequest::GetPooledTransactions { request, .. } => request.0.len(),
+            PeerRequest::GetNodeData { request, .. } => request.0.len(),
+            PeerRequest::GetReceipts { request, .. } => request.0.len(),
+        }
+    }
+
     /// Returns the [`EthMessage`] for this type
     pub fn create_request_message(&self, request_id: u64) -> EthMessage {
         match self {
@@ -43,6 +43,9 @@ pub struct NetworkMetrics {
 
     /// Number of Eth Requests dropped due to channel being at full capacity
     pub(crate) total_dropped_eth_requests_at_full_capacity: Counter,
+
+    /// Number of times a peer exceeded its bandwidth limit
+    pub(crate) bandwidth_limit_violations: Counter,
 }
 
 /// Metrics for SessionManager
@@ -53,6 +56,26 @@ pub struct SessionManagerMetrics {
     pub(crate) total_dial_successes: Counter,
 }
 
+/// Metrics for the bytes exchanged with peers, labeled by capability and message
+#[derive(Metrics)]
+#[metrics(scope = "network.bandwidth")]
+pub struct MessageBandwidthMetrics {
+    /// Number of bytes received from peers
+    pub(crate) ingress_bytes: Counter,
+    /// Number of bytes sent to peers
+    pub(crate) egress_bytes: Counter,
+}
+
+/// Metrics for the permissioned network mode
+#[derive(Metrics)]
+#[metrics(scope = "network.permissions")]
+pub struct PermissionsMetrics {
+    /// Number of incoming connections denied because of their address
+    pub(crate) denied_incoming_connections: Counter,
+    /// Number of sessions denied because the node is not allow-listed
+    pub(crate) denied_sessions: Counter,
+}
+
 /// Metrics for the TransactionsManager
 #[derive(Metrics)]
 #[metrics(scope = "network")]
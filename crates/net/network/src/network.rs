@@ -1,6 +1,6 @@
 use crate::{
     config::NetworkMode, discovery::DiscoveryEvent, manager::NetworkEvent, message::PeerRequest,
-    peers::PeersHandle, protocol::RlpxSubProtocol, FetchClient,
+    peers::PeersHandle, permissions::NetworkPermissions, protocol::RlpxSubProtocol, FetchClient,
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -14,14 +14,15 @@ use reth_network_api::{
     ReputationChangeKind,
 };
 use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, B256};
-use reth_rpc_types::NetworkStatus;
+use reth_rpc_types::{Bans, NetworkStatus};
 use secp256k1::SecretKey;
 use std::{
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -51,6 +52,7 @@ impl NetworkHandle {
         bandwidth_meter: BandwidthMeter,
         chain_id: Arc<AtomicU64>,
         tx_gossip_disabled: bool,
+        permissions: NetworkPermissions,
         #[cfg(feature = "optimism")] sequencer_endpoint: Option<String>,
     ) -> Self {
         let inner = NetworkInner {
@@ -66,6 +68,7 @@ impl NetworkHandle {
             initial_sync_done: Arc::new(AtomicBool::new(false)),
             chain_id,
             tx_gossip_disabled,
+            permissions,
             #[cfg(feature = "optimism")]
             sequencer_endpoint,
         };
@@ -84,6 +87,11 @@ impl NetworkHandle {
         &self.inner.peers
     }
 
+    /// Returns the allowlist of the permissioned network mode.
+    pub fn permissions(&self) -> &NetworkPermissions {
+        &self.inner.permissions
+    }
+
     fn manager(&self) -> &UnboundedSender<NetworkHandleMessage> {
         &self.inner.to_manager_tx
     }
@@ -277,6 +285,40 @@ impl Peers for NetworkHandle {
         let _ = self.manager().send(NetworkHandleMessage::GetReputationById(peer_id, tx));
         Ok(rx.await?)
     }
+
+    fn add_allowed_peer(&self, peer: NodeRecord) -> bool {
+        self.inner.permissions.add_node(peer)
+    }
+
+    /// Removes the node from the allowlist and sends a message to the
+    /// [`NetworkManager`](crate::NetworkManager) to disconnect an existing connection to it.
+    fn remove_allowed_peer(&self, peer: PeerId) -> bool {
+        let removed = self.inner.permissions.remove_node(&peer);
+        if removed && !self.inner.permissions.is_allowed(&peer) {
+            self.disconnect_peer(peer);
+        }
+        removed
+    }
+
+    fn ban_peer(&self, peer: PeerId, duration: Option<Duration>) {
+        self.inner.peers.ban_peer(peer, duration);
+    }
+
+    fn unban_peer(&self, peer: PeerId) {
+        self.inner.peers.unban_peer(peer);
+    }
+
+    fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>) {
+        self.inner.peers.ban_ip(ip, duration);
+    }
+
+    fn unban_ip(&self, ip: IpAddr) {
+        self.inner.peers.unban_ip(ip);
+    }
+
+    async fn get_bans(&self) -> Result<Bans, NetworkError> {
+        Ok(self.inner.peers.bans().await)
+    }
 }
 
 #[async_trait]
@@ -364,6 +406,8 @@ struct NetworkInner {
     chain_id: Arc<AtomicU64>,
     /// Whether to disable transaction gossip
     tx_gossip_disabled: bool,
+    /// The allowlist of the permissioned network mode
+    permissions: NetworkPermissions,
     /// The sequencer HTTP Endpoint
     #[cfg(feature = "optimism")]
     sequencer_endpoint: Option<String>,
@@ -2,6 +2,10 @@ use crate::{
     error::{BackoffKind, SessionError},
     peers::{
         reputation::{is_banned_reputation, DEFAULT_REPUTATION},
+        snapshot::{
+            deadline_after, decay_reputation, from_unix_timestamp, to_unix_timestamp, PeersFile,
+            PeersSnapshot, PersistedPeer, MAX_DEADLINE_DURATION,
+        },
         ReputationChangeWeights, DEFAULT_MAX_CONCURRENT_DIALS, DEFAULT_MAX_PEERS_INBOUND,
         DEFAULT_MAX_PEERS_OUTBOUND,
     },
@@ -12,6 +16,7 @@ use reth_eth_wire::{errors::EthStreamError, DisconnectReason};
 use reth_net_common::ban_list::BanList;
 use reth_network_api::{PeerKind, ReputationChangeKind};
 use reth_primitives::{ForkId, NodeRecord, PeerId};
+use reth_rpc_types::{BannedIp, BannedPeer, Bans};
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     fmt::Display,
@@ -73,6 +78,35 @@ impl PeersHandle {
 
         rx.await.unwrap_or_default()
     }
+
+    /// Bans the peer for the given duration, or indefinitely, and disconnects it.
+    pub fn ban_peer(&self, peer_id: PeerId, duration: Option<Duration>) {
+        self.send(PeerCommand::BanPeer(peer_id, duration));
+    }
+
+    /// Lifts the ban of the peer.
+    pub fn unban_peer(&self, peer_id: PeerId) {
+        self.send(PeerCommand::UnbanPeer(peer_id));
+    }
+
+    /// Bans the IP address for the given duration, or indefinitely, and disconnects all peers
+    /// using it.
+    pub fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>) {
+        self.send(PeerCommand::BanIp(ip, duration));
+    }
+
+    /// Lifts the ban of the IP address.
+    pub fn unban_ip(&self, ip: IpAddr) {
+        self.send(PeerCommand::UnbanIp(ip));
+    }
+
+    /// Returns all banned peers and IP addresses.
+    pub async fn bans(&self) -> Bans {
+        let (tx, rx) = oneshot::channel();
+        self.send(PeerCommand::GetBans(tx));
+
+        rx.await.unwrap_or_default()
+    }
 }
 
 /// Maintains the state of _all_ the peers known to the network.
@@ -130,6 +164,7 @@ impl PeersManager {
             connect_trusted_nodes_only,
             basic_nodes,
             max_backoff_count,
+            snapshot,
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
         let now = Instant::now();
@@ -147,7 +182,7 @@ impl PeersManager {
             peers.entry(id).or_insert_with(|| Peer::new(SocketAddr::from((address, tcp_port))));
         }
 
-        Self {
+        let mut manager = Self {
             peers,
             manager_tx,
             handle_rx: UnboundedReceiverStream::new(handle_rx),
@@ -166,6 +201,97 @@ impl PeersManager {
             connect_trusted_nodes_only,
             last_tick: Instant::now(),
             max_backoff_count,
+        };
+
+        if let Some(snapshot) = snapshot {
+            manager.restore(snapshot);
+        }
+
+        manager
+    }
+
+    /// Restores the state of a previous run from the given snapshot.
+    ///
+    /// Peers that are not yet in the peer set are added as basic peers. The reputation of peers
+    /// recovers for the time that passed since the snapshot was taken, see
+    /// [`decay_reputation`]. Bans and backoffs that expired in the meantime are dropped.
+    fn restore(&mut self, snapshot: PeersSnapshot) {
+        let downtime = snapshot.downtime();
+        let PeersSnapshot { peers, bans, .. } = snapshot;
+        info!(target: "net::peers", peers = peers.len(), banned_peers = bans.peers.len(), banned_ips = bans.ips.len(), ?downtime, "Restoring peers");
+
+        for BannedPeer { id, until } in bans.peers {
+            match until {
+                Some(until) => {
+                    if let Some(until) = from_unix_timestamp(until) {
+                        self.ban_list.ban_peer_until(id, until);
+                    }
+                }
+                None => self.ban_list.ban_peer(id),
+            }
+        }
+
+        for BannedIp { ip, until } in bans.ips {
+            match until {
+                Some(until) => {
+                    if let Some(until) = from_unix_timestamp(until) {
+                        self.ban_list.ban_ip_until(ip, until);
+                    }
+                }
+                None => self.ban_list.ban_ip(ip),
+            }
+        }
+
+        for PersistedPeer { record, reputation, backoff_until, severe_backoff_counter } in peers {
+            let peer = self.peers.entry(record.id).or_insert_with(|| Peer::new(record.tcp_addr()));
+            peer.reputation = decay_reputation(reputation, downtime);
+            peer.severe_backoff_counter = severe_backoff_counter;
+
+            // the ban of a peer with banned reputation expired while offline
+            if peer.is_banned() && !self.ban_list.is_banned_peer(&record.id) {
+                peer.unban();
+            }
+
+            if let Some(until) = backoff_until.and_then(from_unix_timestamp) {
+                peer.backed_off = true;
+                self.backed_off_peers.insert(record.id, until);
+            }
+        }
+    }
+
+    /// Returns a snapshot of the peer set that can be persisted and restored on the next start.
+    pub fn snapshot(&self) -> PeersSnapshot {
+        let peers = self
+            .peers
+            .iter()
+            .map(|(peer_id, peer)| PersistedPeer {
+                record: NodeRecord::new(peer.addr, *peer_id),
+                reputation: peer.reputation,
+                backoff_until: self.backed_off_peers.get(peer_id).copied().map(to_unix_timestamp),
+                severe_backoff_counter: peer.severe_backoff_counter,
+            })
+            .collect();
+
+        PeersSnapshot {
+            saved_at: to_unix_timestamp(std::time::Instant::now()),
+            peers,
+            bans: self.bans(),
+        }
+    }
+
+    /// Returns all banned peers and IP addresses.
+    pub(crate) fn bans(&self) -> Bans {
+        Bans {
+            peers: self
+                .ban_list
+                .banned_peers()
+                .map(|(id, until)| BannedPeer { id, until: until.map(to_unix_timestamp) })
+                .collect(),
+            ips: self
+                .ban_list
+                .banned_ips()
+                .map(|(ip, until)| BannedIp { ip, until: until.map(to_unix_timestamp) })
+                .collect(),
         }
     }
 
@@ -320,6 +446,65 @@ impl PeersManager {
         self.queued_actions.push_back(PeerAction::UnBanPeer { peer_id });
     }
 
+    /// Bans the peer for the given duration, or indefinitely, and disconnects it if connected.
+    ///
+    /// Durations longer than [`MAX_DEADLINE_DURATION`] are clamped.
+    pub(crate) fn ban_peer_for(&mut self, peer_id: PeerId, duration: Option<Duration>) {
+        info!(target: "net::peers", ?peer_id, ?duration, "Banning peer");
+        let until = duration.and_then(deadline_after);
+        self.ban_list.ban_peer_with(peer_id, until);
+        self.queued_actions.push_back(PeerAction::BanPeer { peer_id });
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            if peer.state.is_connected() {
+                peer.state.disconnect();
+                self.queued_actions.push_back(PeerAction::Disconnect {
+                    peer_id,
+                    reason: Some(DisconnectReason::DisconnectRequested),
+                });
+            }
+        }
+    }
+
+    /// Lifts the ban of the peer and resets its reputation if it was banned.
+    pub(crate) fn unban_peer_manually(&mut self, peer_id: PeerId) {
+        info!(target: "net::peers", ?peer_id, "Unbanning peer");
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            if peer.is_banned() {
+                peer.unban();
+            }
+        }
+        self.unban_peer(peer_id);
+    }
+
+    /// Bans the IP address for the given duration, or indefinitely, and disconnects all peers
+    /// using it.
+    ///
+    /// Non-global IP addresses are not banned. Durations longer than [`MAX_DEADLINE_DURATION`] are
+    /// clamped.
+    pub(crate) fn ban_ip_for(&mut self, ip: IpAddr, duration: Option<Duration>) {
+        info!(target: "net::peers", %ip, ?duration, "Banning ip");
+        let until = duration.and_then(deadline_after);
+        self.ban_list.ban_ip_with(ip, until);
+        if !self.ban_list.is_banned_ip(&ip) {
+            return
+        }
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if peer.addr.ip() == ip && peer.state.is_connected() {
+                peer.state.disconnect();
+                self.queued_actions.push_back(PeerAction::Disconnect {
+                    peer_id: *peer_id,
+                    reason: Some(DisconnectReason::DisconnectRequested),
+                });
+            }
+        }
+    }
+
+    /// Lifts the ban of the IP address.
+    pub(crate) fn unban_ip(&mut self, ip: IpAddr) {
+        info!(target: "net::peers", %ip, "Unbanning ip");
+        self.ban_list.unban_ip(&ip);
+    }
+
     /// Tick function to update reputation of all connected peers.
     /// Peers are rewarded with reputation increases for the time they are connected since the last
     /// tick. This is to prevent peers from being disconnected eventually due to slashed
@@ -652,9 +837,10 @@ impl PeersManager {
     ///
     /// Returns `None` if no peer is available.
     fn best_unconnected(&mut self) -> Option<(PeerId, &mut Peer)> {
-        let mut unconnected = self.peers.iter_mut().filter(|(_, peer)| {
+        let mut unconnected = self.peers.iter_mut().filter(|(peer_id, peer)| {
             !peer.is_backed_off() &&
                 !peer.is_banned() &&
+                !self.ban_list.is_banned(peer_id, &peer.addr.ip()) &&
                 peer.state.is_unconnected() &&
                 (!self.connect_trusted_nodes_only || peer.is_trusted())
         });
@@ -745,6 +931,13 @@ impl PeersManager {
                     PeerCommand::GetPeers(tx) => {
                         let _ = tx.send(self.iter_peers().collect());
                     }
+                    PeerCommand::BanPeer(peer_id, duration) => self.ban_peer_for(peer_id, duration),
+                    PeerCommand::UnbanPeer(peer_id) => self.unban_peer_manually(peer_id),
+                    PeerCommand::BanIp(ip, duration) => self.ban_ip_for(ip, duration),
+                    PeerCommand::UnbanIp(ip) => self.unban_ip(ip),
+                    PeerCommand::GetBans(tx) => {
+                        let _ = tx.send(self.bans());
+                    }
                 }
             }
 
@@ -1040,6 +1233,16 @@ pub(crate) enum PeerCommand {
     GetPeer(PeerId, oneshot::Sender<Option<Peer>>),
     /// Get node information on all peers
     GetPeers(oneshot::Sender<Vec<NodeRecord>>),
+    /// Ban a peer for the given duration, or indefinitely.
+    BanPeer(PeerId, Option<Duration>),
+    /// Lift the ban of a peer.
+    UnbanPeer(PeerId),
+    /// Ban an IP address for the given duration, or indefinitely.
+    BanIp(IpAddr, Option<Duration>),
+    /// Lift the ban of an IP address.
+    UnbanIp(IpAddr),
+    /// Get all banned peers and IP addresses.
+    GetBans(oneshot::Sender<Bans>),
 }
 
 /// Actions the peer manager can trigger.
@@ -1133,6 +1336,9 @@ pub struct PeersConfig {
     ///
     /// The backoff duration increases with number of backoff attempts.
     pub backoff_durations: PeerBackoffDurations,
+    /// State of the peer set of a previous run to restore.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub snapshot: Option<PeersSnapshot>,
 }
 
 impl Default for PeersConfig {
@@ -1149,6 +1355,7 @@ impl Default for PeersConfig {
             connect_trusted_nodes_only: false,
             basic_nodes: Default::default(),
             max_backoff_count: 5,
+            snapshot: None,
         }
     }
 }
@@ -1248,7 +1455,15 @@ impl PeersConfig {
         self
     }
 
+    /// State of the peer set of a previous run to restore at launch.
+    pub fn with_snapshot(mut self, snapshot: PeersSnapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
     /// Read from file nodes available at launch. Ignored if None.
+    ///
+    /// The file either contains a [`PeersSnapshot`] or a list of node records.
     pub fn with_basic_nodes_from_file(
         self,
         optional_file: Option<impl AsRef<Path>>,
@@ -1260,8 +1475,10 @@ impl PeersConfig {
             Err(e) => Err(e)?,
         };
         info!(target: "net::peers", file = %file_path.as_ref().display(), "Loading saved peers");
-        let nodes: HashSet<NodeRecord> = serde_json::from_reader(reader)?;
-        Ok(self.with_basic_nodes(nodes))
+        match serde_json::from_reader(reader)? {
+            PeersFile::Snapshot(snapshot) => Ok(self.with_snapshot(snapshot)),
+            PeersFile::Nodes(nodes) => Ok(self.with_basic_nodes(nodes)),
+        }
     }
 }
 
@@ -1341,8 +1558,9 @@ mod test {
         error::BackoffKind,
         peers::{
             manager::{ConnectionInfo, PeerBackoffDurations, PeerConnectionState},
-            reputation::DEFAULT_REPUTATION,
-            PeerAction,
+            reputation::{BANNED_REPUTATION, DEFAULT_REPUTATION},
+            snapshot::MAX_DEADLINE_DURATION,
+            PeerAction, PeersSnapshot, PersistedPeer,
         },
         session::PendingSessionHandshakeError,
         PeersConfig,
@@ -1355,6 +1573,7 @@ mod test {
     use reth_net_common::ban_list::BanList;
     use reth_network_api::ReputationChangeKind;
     use reth_primitives::{PeerId, B512};
+    use reth_rpc_types::{BannedIp, BannedPeer, Bans};
     use std::{
         collections::HashSet,
         future::{poll_fn, Future},
@@ -1362,7 +1581,7 @@ mod test {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         pin::Pin,
         task::{Context, Poll},
-        time::Duration,
+        time::{Duration, SystemTime, UNIX_EPOCH},
     };
 
     struct PeerActionFuture<'a> {
@@ -2254,4 +2473,138 @@ mod test {
             .count();
         assert_eq!(dials, peer_manager.connection_info.max_concurrent_outbound_dials);
     }
+
+    #[tokio::test]
+    async fn test_restore_snapshot() {
+        let banned = PeerId::random();
+        let backed_off = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let banned_ip = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
+
+        let mut peers = PeersManager::default();
+        peers.add_peer(banned, socket_addr, None);
+        peers.add_peer(backed_off, socket_addr, None);
+        peers.apply_reputation_change(&banned, ReputationChangeKind::BadProtocol);
+        peers.backoff_peer_until(backed_off, std::time::Instant::now() + Duration::from_secs(600));
+        peers.ban_ip_for(banned_ip, None);
+
+        let snapshot = peers.snapshot();
+        assert_eq!(snapshot.peers.len(), 2);
+        assert_eq!(snapshot.bans.peers.len(), 1);
+        assert_eq!(snapshot.bans.ips, vec![BannedIp { ip: banned_ip, until: None }]);
+
+        let mut restored = PeersManager::new(PeersConfig::default().with_snapshot(snapshot));
+        assert_eq!(restored.num_known_peers(), 2);
+        assert!(restored.ban_list.is_banned_peer(&banned));
+        assert!(restored.peers[&banned].is_banned());
+        assert!(restored.ban_list.is_banned_ip(&banned_ip));
+        assert!(restored.peers[&backed_off].is_backed_off());
+        assert_eq!(restored.num_backed_off_peers(), 1);
+
+        // neither of the peers is dialed right away
+        assert!(restored.best_unconnected().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_after_downtime() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let downtime = 13 * 60 * 60;
+        let saved_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - downtime;
+
+        let snapshot = PeersSnapshot {
+            saved_at,
+            peers: vec![PersistedPeer {
+                record: NodeRecord::new(socket_addr, peer),
+                reputation: BANNED_REPUTATION - 1,
+                backoff_until: Some(saved_at + 600),
+                severe_backoff_counter: 2,
+            }],
+            bans: Bans {
+                peers: vec![BannedPeer { id: peer, until: Some(saved_at + 12 * 60 * 60) }],
+                ips: vec![],
+            },
+        };
+
+        let mut peers = PeersManager::new(PeersConfig::default().with_snapshot(snapshot));
+        let restored = &peers.peers[&peer];
+        assert!(!restored.is_banned());
+        assert!(!restored.is_backed_off());
+        assert_eq!(restored.severe_backoff_counter, 2);
+        assert_eq!(restored.reputation, BANNED_REPUTATION - 1 + downtime as i32);
+        assert!(!peers.ban_list.is_banned_peer(&peer));
+        assert_eq!(peers.best_unconnected().map(|(peer_id, _)| peer_id), Some(peer));
+    }
+
+    #[tokio::test]
+    async fn test_ban_connected_peer() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+        peers.add_peer(peer, socket_addr, None);
+
+        match event!(peers) {
+            PeerAction::PeerAdded(peer_id) => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+
+        peers.ban_peer_for(peer, Some(Duration::from_secs(60)));
+        match event!(peers) {
+            PeerAction::BanPeer { peer_id } => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+        match event!(peers) {
+            PeerAction::Disconnect { peer_id, reason } => {
+                assert_eq!(peer_id, peer);
+                assert_eq!(reason, Some(DisconnectReason::DisconnectRequested));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(peers.bans().peers.len(), 1);
+
+        peers.unban_peer_manually(peer);
+        match event!(peers) {
+            PeerAction::UnBanPeer { peer_id } => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+        assert!(peers.bans().peers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ban_for_max_duration() {
+        let peer = PeerId::random();
+        let ip = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
+        let mut peers = PeersManager::default();
+
+        peers.ban_peer_for(peer, Some(Duration::MAX));
+        peers.ban_ip_for(ip, Some(Duration::MAX));
+        assert!(peers.ban_list.is_banned_peer(&peer));
+        assert!(peers.ban_list.is_banned_ip(&ip));
+
+        let deadline = std::time::Instant::now() + MAX_DEADLINE_DURATION;
+        let (_, until) = peers.ban_list.banned_peers().next().unwrap();
+        assert!(until.unwrap() <= deadline);
+        let (_, until) = peers.ban_list.banned_ips().next().unwrap();
+        assert!(until.unwrap() <= deadline);
+
+        // the clamped bans survive a restart
+        let mut restored =
+            PeersManager::new(PeersConfig::default().with_snapshot(peers.snapshot()));
+        assert!(restored.ban_list.is_banned_peer(&peer));
+        assert!(restored.ban_list.is_banned_ip(&ip));
+        restored.unban_peer_manually(peer);
+        assert!(!restored.ban_list.is_banned_peer(&peer));
+    }
 }
@@ -2,11 +2,13 @@
 
 mod manager;
 mod reputation;
+mod snapshot;
 
 pub(crate) use manager::InboundConnectionError;
 pub use manager::{ConnectionInfo, Peer, PeerAction, PeersConfig, PeersHandle, PeersManager};
 pub use reputation::ReputationChangeWeights;
 pub use reth_network_api::PeerKind;
+pub use snapshot::{PeersSnapshot, PersistedPeer};
 
 /// Maximum number of available slots for outbound sessions.
 pub(crate) const DEFAULT_MAX_PEERS_OUTBOUND: usize = 100;
@@ -0,0 +1,169 @@
+//! Persistence of the peer set across restarts.
+//!
+//! The [`PeersManager`](super::PeersManager) state is written as a [`PeersSnapshot`] on shutdown
+//! and restored on startup. All timestamps are stored as UNIX timestamps in seconds, so that time
+//! spent offline counts towards bans and backoffs.
+
+use crate::peers::reputation::DEFAULT_REPUTATION;
+use reth_network_api::Reputation;
+use reth_primitives::NodeRecord;
+use reth_rpc_types::Bans;
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// The state of the peer set that is persisted across restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeersSnapshot {
+    /// UNIX timestamp in seconds when the snapshot was taken.
+    pub saved_at: u64,
+    /// All peers in the peer set.
+    pub peers: Vec<PersistedPeer>,
+    /// Banned peers and IP addresses.
+    pub bans: Bans,
+}
+
+impl PeersSnapshot {
+    /// Returns the time that passed since the snapshot was taken.
+    pub fn downtime(&self) -> Duration {
+        unix_now().saturating_sub(Duration::from_secs(self.saved_at))
+    }
+}
+
+/// The persisted state of a single peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistedPeer {
+    /// Where to reach the peer.
+    pub record: NodeRecord,
+    /// Reputation of the peer.
+    pub reputation: Reputation,
+    /// UNIX timestamp in seconds until which the peer is backed off, if it is.
+    pub backoff_until: Option<u64>,
+    /// Number of times the peer was backed off due to a severe backoff kind.
+    pub severe_backoff_counter: u32,
+}
+
+/// The content of a persisted peers file.
+///
+/// Older versions only stored the node records of the peer set.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(untagged))]
+pub(crate) enum PeersFile {
+    /// A full snapshot of the peer set.
+    Snapshot(PeersSnapshot),
+    /// Node records only.
+    Nodes(HashSet<NodeRecord>),
+}
+
+/// Returns the reputation of a peer after it was not connected to for the given duration.
+///
+/// The reputation of peers below the default reputation recovers at the same rate as for connected
+/// peers, by one point per second, until it reaches the default reputation.
+pub(crate) fn decay_reputation(reputation: Reputation, downtime: Duration) -> Reputation {
+    if reputation >= DEFAULT_REPUTATION {
+        return reputation
+    }
+    let recovered = downtime.as_secs().min(Reputation::MAX as u64) as Reputation;
+    reputation.saturating_add(recovered).min(DEFAULT_REPUTATION)
+}
+
+/// The longest duration a ban or backoff can last, longer durations are clamped to this.
+///
+/// This keeps deadlines derived from untrusted input, like RPC calls or the peers file, from
+/// overflowing [`Instant`].
+pub(crate) const MAX_DEADLINE_DURATION: Duration = Duration::from_secs(10 * 365 * 24 * 60 * 60);
+
+/// Returns the instant after the given duration, clamped to [`MAX_DEADLINE_DURATION`].
+///
+/// Returns `None` if the deadline is not representable on this platform.
+pub(crate) fn deadline_after(duration: Duration) -> Option<Instant> {
+    Instant::now().checked_add(duration.min(MAX_DEADLINE_DURATION))
+}
+
+/// Converts the given instant to a UNIX timestamp in seconds.
+pub(crate) fn to_unix_timestamp(instant: Instant) -> u64 {
+    let remaining = instant.saturating_duration_since(Instant::now());
+    unix_now().saturating_add(remaining).as_secs()
+}
+
+/// Converts the given UNIX timestamp in seconds to an instant.
+///
+/// Returns `None` if the timestamp is in the past.
+pub(crate) fn from_unix_timestamp(timestamp: u64) -> Option<Instant> {
+    let remaining = Duration::from_secs(timestamp).checked_sub(unix_now())?;
+    if remaining.is_zero() {
+        return None
+    }
+    deadline_after(remaining)
+}
+
+fn unix_now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::reputation::BANNED_REPUTATION;
+
+    #[test]
+    fn reputation_recovers_during_downtime() {
+        assert_eq!(decay_reputation(-5_000, Duration::from_secs(1_000)), -4_000);
+        assert_eq!(decay_reputation(-5_000, Duration::from_secs(10_000)), DEFAULT_REPUTATION);
+        assert_eq!(decay_reputation(i32::MIN, Duration::from_secs(u64::MAX)), DEFAULT_REPUTATION);
+        assert_eq!(decay_reputation(BANNED_REPUTATION - 1, Duration::ZERO), BANNED_REPUTATION - 1);
+        assert_eq!(decay_reputation(100, Duration::from_secs(1_000)), 100);
+    }
+
+    #[test]
+    fn timestamp_conversion() {
+        let until = Instant::now() + Duration::from_secs(600);
+        let timestamp = to_unix_timestamp(until);
+        let restored = from_unix_timestamp(timestamp).unwrap();
+        let diff = if restored > until { restored - until } else { until - restored };
+        assert!(diff <= Duration::from_secs(1));
+
+        let expired = to_unix_timestamp(Instant::now()) - 10;
+        assert!(from_unix_timestamp(expired).is_none());
+    }
+
+    #[test]
+    fn timestamp_conversion_clamps_far_future() {
+        let now = Instant::now();
+        let restored = from_unix_timestamp(u64::MAX).unwrap();
+        assert!(restored <= Instant::now() + MAX_DEADLINE_DURATION);
+        assert!(restored >= now + MAX_DEADLINE_DURATION - Duration::from_secs(1));
+
+        let until = deadline_after(Duration::MAX).unwrap();
+        assert!(until <= Instant::now() + MAX_DEADLINE_DURATION);
+        assert!(to_unix_timestamp(until) > to_unix_timestamp(now));
+    }
+
+    #[test]
+    fn parse_legacy_peers_file() {
+        let nodes = r#"["enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@10.3.58.6:30303?discport=30301"]"#;
+        let PeersFile::Nodes(nodes) = serde_json::from_str(nodes).unwrap() else {
+            panic!("expected node records")
+        };
+        assert_eq!(nodes.len(), 1);
+
+        let snapshot = PeersSnapshot {
+            saved_at: 1_700_000_000,
+            peers: vec![PersistedPeer {
+                record: *nodes.iter().next().unwrap(),
+                reputation: -1024,
+                backoff_until: Some(1_700_000_030),
+                severe_backoff_counter: 1,
+            }],
+            bans: Default::default(),
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let PeersFile::Snapshot(restored) = serde_json::from_str(&json).unwrap() else {
+            panic!("expected snapshot")
+        };
+        assert_eq!(restored, snapshot);
+    }
+}
@@ -0,0 +1,261 @@
+//! Permissioned network mode.
+//!
+//! A permissioned network only establishes sessions with allow-listed nodes. The allowlist is
+//! made up of a static set of nodes, loaded from a file or added at runtime, and optional dynamic
+//! [`PermissionsSource`]s, for example the validator set of a consortium chain or an on-chain
+//! registry contract.
+//!
+//! Every session, incoming and outgoing, is checked against the allowlist by the node ID the remote
+//! proved in the ECIES handshake. Optionally, incoming connections can also be filtered by their
+//! address before the handshake, see [`NetworkPermissions::with_ip_filter`].
+
+use crate::metrics::PermissionsMetrics;
+use parking_lot::RwLock;
+use reth_primitives::{NodeRecord, PeerId};
+use std::{
+    collections::HashMap,
+    fmt,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tracing::{info, warn};
+
+/// A dynamic source of nodes that are allowed to connect.
+#[auto_impl::auto_impl(&, Arc, Box)]
+pub trait PermissionsSource: Send + Sync + fmt::Debug {
+    /// Returns `true` if the node with the given ID is allowed to connect.
+    fn is_allowed(&self, peer_id: &PeerId) -> bool;
+}
+
+/// The allowlist of a permissioned network.
+///
+/// This type is cheap to clone and shared by all sessions, updates are visible to new sessions
+/// right away. Existing sessions are not re-checked.
+///
+/// Permissioning is disabled by default, in which case all nodes are allowed.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkPermissions {
+    inner: Option<Arc<PermissionsInner>>,
+}
+
+#[derive(Debug, Default)]
+struct PermissionsInner {
+    /// The allow-listed nodes and their address, if known.
+    nodes: RwLock<HashMap<PeerId, Option<IpAddr>>>,
+    /// The dynamic sources of allowed nodes.
+    sources: RwLock<Vec<Arc<dyn PermissionsSource>>>,
+    /// Whether incoming connections are filtered by their address before the handshake.
+    ip_filter: AtomicBool,
+    /// Metrics for denied connection attempts.
+    metrics: PermissionsMetrics,
+}
+
+// === impl NetworkPermissions ===
+
+impl NetworkPermissions {
+    /// Enables permissioning with the given nodes as the allowlist.
+    pub fn allowlist(nodes: impl IntoIterator<Item = NodeRecord>) -> Self {
+        let permissions = Self { inner: Some(Default::default()) };
+        for node in nodes {
+            permissions.add_node(node);
+        }
+        permissions
+    }
+
+    /// Enables permissioning with the nodes of the given file as the allowlist.
+    ///
+    /// The file is a JSON array of enode URLs.
+    #[cfg(feature = "serde")]
+    pub fn allowlist_from_file(path: impl AsRef<std::path::Path>) -> Result<Self, std::io::Error> {
+        let reader = std::io::BufReader::new(std::fs::File::open(path.as_ref())?);
+        let nodes: Vec<NodeRecord> = serde_json::from_reader(reader)?;
+        tracing::info!(target: "net::permissions", file = %path.as_ref().display(), nodes = nodes.len(), "Loaded node allowlist");
+        Ok(Self::allowlist(nodes))
+    }
+
+    /// Adds a dynamic source of allowed nodes. This enables permissioning if it was disabled.
+    pub fn with_source(self, source: impl PermissionsSource + 'static) -> Self {
+        let permissions = if self.is_enabled() { self } else { Self::allowlist([]) };
+        if let Some(inner) = &permissions.inner {
+            inner.sources.write().push(Arc::new(source));
+        }
+        permissions
+    }
+
+    /// Rejects incoming connections from addresses that do not belong to an allow-listed node
+    /// before the handshake.
+    ///
+    /// This only saves the cost of the handshake, sessions are always checked by their node ID.
+    /// Allow-listed nodes that connect from an address other than the one in their node record
+    /// are rejected, so this should only be enabled if the addresses of all nodes are static.
+    pub fn with_ip_filter(self) -> Self {
+        if let Some(inner) = &self.inner {
+            inner.ip_filter.store(true, Ordering::Relaxed);
+        }
+        self
+    }
+
+    /// Returns `true` if incoming connections are filtered by their address.
+    pub fn has_ip_filter(&self) -> bool {
+        self.inner.as_ref().map_or(false, |inner| inner.ip_filter.load(Ordering::Relaxed))
+    }
+
+    /// Returns `true` if the network is permissioned.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Adds the node to the allowlist.
+    ///
+    /// Returns `false` if permissioning is disabled.
+    pub fn add_node(&self, node: NodeRecord) -> bool {
+        let Some(inner) = &self.inner else { return false };
+        let addr = (!node.address.is_unspecified()).then_some(node.address);
+        inner.nodes.write().insert(node.id, addr);
+        true
+    }
+
+    /// Removes the node from the allowlist.
+    ///
+    /// Returns `true` if the node was allow-listed.
+    pub fn remove_node(&self, peer_id: &PeerId) -> bool {
+        self.inner.as_ref().map_or(false, |inner| inner.nodes.write().remove(peer_id).is_some())
+    }
+
+    /// Returns the IDs of all nodes of the static allowlist.
+    pub fn nodes(&self) -> Vec<PeerId> {
+        self.inner
+            .as_ref()
+            .map(|inner| inner.nodes.read().keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the node with the given ID is allowed to connect.
+    pub fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        let Some(inner) = &self.inner else { return true };
+        inner.nodes.read().contains_key(peer_id) ||
+            inner.sources.read().iter().any(|source| source.is_allowed(peer_id))
+    }
+
+    /// Returns `true` if a connection from the given address may belong to an allowed node.
+    ///
+    /// This is always the case if there are dynamic sources, or an allowed node with an unknown
+    /// address.
+    pub fn is_ip_allowed(&self, ip: IpAddr) -> bool {
+        let Some(inner) = &self.inner else { return true };
+        !inner.sources.read().is_empty() ||
+            inner.nodes.read().values().any(|addr| addr.map_or(true, |addr| addr == ip))
+    }
+
+    /// Checks whether an incoming connection from the given address is allowed, if the
+    /// [ip filter](Self::with_ip_filter) is enabled.
+    ///
+    /// Denied connections are logged and recorded in the metrics.
+    pub(crate) fn check_incoming(&self, ip: IpAddr) -> bool {
+        let Some(inner) = &self.inner else { return true };
+        if !inner.ip_filter.load(Ordering::Relaxed) || self.is_ip_allowed(ip) {
+            return true
+        }
+        info!(target: "net::permissions", %ip, "Denied incoming connection from unknown address");
+        inner.metrics.denied_incoming_connections.increment(1);
+        false
+    }
+
+    /// Checks whether a session with the given node is allowed.
+    ///
+    /// Denied sessions are logged and recorded in the metrics.
+    pub(crate) fn check_session(&self, peer_id: &PeerId, remote_addr: IpAddr) -> bool {
+        if self.is_allowed(peer_id) {
+            return true
+        }
+        warn!(target: "net::permissions", ?peer_id, %remote_addr, "Denied session with node that is not allow-listed");
+        if let Some(inner) = &self.inner {
+            inner.metrics.denied_sessions.increment(1);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[derive(Debug)]
+    struct Validators(Vec<PeerId>);
+
+    impl PermissionsSource for Validators {
+        fn is_allowed(&self, peer_id: &PeerId) -> bool {
+            self.0.contains(peer_id)
+        }
+    }
+
+    fn node(ip: Ipv4Addr) -> NodeRecord {
+        NodeRecord::new((ip, 30303).into(), PeerId::random())
+    }
+
+    #[test]
+    fn disabled_allows_all() {
+        let permissions = NetworkPermissions::default();
+        assert!(!permissions.is_enabled());
+        assert!(permissions.is_allowed(&PeerId::random()));
+        assert!(permissions.is_ip_allowed(Ipv4Addr::LOCALHOST.into()));
+        assert!(!permissions.add_node(node(Ipv4Addr::LOCALHOST)));
+    }
+
+    #[test]
+    fn static_allowlist() {
+        let allowed = node(Ipv4Addr::new(10, 0, 0, 1));
+        let permissions = NetworkPermissions::allowlist([allowed]);
+
+        assert!(permissions.is_allowed(&allowed.id));
+        assert!(!permissions.is_allowed(&PeerId::random()));
+        assert!(permissions.is_ip_allowed(allowed.address));
+        assert!(!permissions.is_ip_allowed(Ipv4Addr::new(10, 0, 0, 2).into()));
+
+        // nodes added at runtime are visible to all clones
+        let added = node(Ipv4Addr::UNSPECIFIED);
+        assert!(permissions.clone().add_node(added));
+        assert!(permissions.is_allowed(&added.id));
+        assert!(permissions.is_ip_allowed(Ipv4Addr::new(10, 0, 0, 2).into()));
+
+        assert!(permissions.remove_node(&added.id));
+        assert!(!permissions.is_allowed(&added.id));
+        assert!(!permissions.remove_node(&added.id));
+    }
+
+    #[test]
+    fn dynamic_source() {
+        let validator = PeerId::random();
+        let permissions = NetworkPermissions::default().with_source(Validators(vec![validator]));
+
+        assert!(permissions.is_enabled());
+        assert!(permissions.is_allowed(&validator));
+        assert!(!permissions.is_allowed(&PeerId::random()));
+        assert!(permissions.is_ip_allowed(Ipv4Addr::new(10, 0, 0, 2).into()));
+    }
+
+    #[test]
+    fn ip_filter_is_opt_in() {
+        let allowed = node(Ipv4Addr::new(10, 0, 0, 1));
+        let unknown = Ipv4Addr::new(10, 0, 0, 2).into();
+
+        let permissions = NetworkPermissions::allowlist([allowed]);
+        assert!(!permissions.has_ip_filter());
+        assert!(permissions.check_incoming(unknown));
+        assert!(!permissions.check_session(&PeerId::random(), unknown));
+
+        let permissions = permissions.with_ip_filter();
+        assert!(permissions.has_ip_filter());
+        assert!(permissions.check_incoming(allowed.address));
+        assert!(!permissions.check_incoming(unknown));
+
+        // the filter has no effect if permissioning is disabled
+        let permissions = NetworkPermissions::default().with_ip_filter();
+        assert!(!permissions.has_ip_filter());
+        assert!(permissions.check_incoming(unknown));
+    }
+}
@@ -3,12 +3,14 @@
 use crate::{
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerResponse, PeerResponseResult},
     session::{
+        bandwidth::{IngressLimiter, SessionBandwidth},
         config::INITIAL_REQUEST_TIMEOUT,
         conn::EthRlpxConnection,
         handle::{ActiveSessionMessage, SessionCommand},
         SessionId,
     },
 };
+use alloy_rlp::Encodable;
 use core::sync::atomic::Ordering;
 use fnv::FnvHashMap;
 use futures::{stream::Fuse, SinkExt, StreamExt};
@@ -17,7 +19,7 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectP2P, DisconnectReason, EthMessage,
+    DisconnectP2P, DisconnectReason, EthMessage, EthMessageID,
 };
 use reth_interfaces::p2p::error::RequestError;
 use reth_metrics::common::mpsc::MeteredPollSender;
@@ -34,7 +36,7 @@ use std::{
 };
 use tokio::{
     sync::{mpsc::error::TrySendError, oneshot},
-    time::Interval,
+    time::{Interval, Sleep},
 };
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::PollSender;
@@ -97,6 +99,12 @@ pub(crate) struct ActiveSession {
     pub(crate) protocol_breach_request_timeout: Duration,
     /// Used to reserve a slot to guarantee that the termination message is delivered
     pub(crate) terminate_message: Option<(PollSender<ActiveSessionMessage>, ActiveSessionMessage)>,
+    /// Bytes exchanged with the peer, shared with the session's handle.
+    pub(crate) bandwidth: Arc<SessionBandwidth>,
+    /// Enforces the bandwidth limit on the messages the peer sends, if configured.
+    pub(crate) ingress_limiter: Option<IngressLimiter>,
+    /// Pauses reading from the connection while the peer exceeds its bandwidth limit.
+    pub(crate) ingress_throttle: Option<Pin<Box<Sleep>>>,
 }
 
 impl ActiveSession {
@@ -118,6 +126,44 @@ impl ActiveSession {
         self.queued_outgoing.shrink_to_fit();
     }
 
+    /// Accounts a message of the given size that was read from the connection.
+    ///
+    /// All messages count towards the bandwidth limit of the peer, including responses to our own
+    /// requests.
+    fn on_incoming_bytes(&mut self, id: EthMessageID, size: usize) {
+        self.bandwidth.on_ingress(id, size);
+        self.consume_ingress_budget(size as u64);
+    }
+
+    /// Counts the bytes the peer sent over additional RLPx sub-protocols since the last call
+    /// towards its bandwidth limit.
+    ///
+    /// These messages are delegated to their protocol by the connection, so they are only
+    /// accounted after the connection was polled.
+    fn on_incoming_protocol_bytes(&mut self) {
+        let size = self.bandwidth.take_protocol_ingress();
+        if size > 0 {
+            self.consume_ingress_budget(size);
+        }
+    }
+
+    /// Consumes the given number of received bytes from the bandwidth limit of the peer.
+    ///
+    /// If the peer exceeds its limit, it is reported to the manager and reading from the
+    /// connection is paused until the peer is back within its limit.
+    fn consume_ingress_budget(&mut self, size: u64) {
+        let Some(limiter) = &mut self.ingress_limiter else { return };
+        if let Some(delay) = limiter.consume(size as usize, Instant::now()) {
+            debug!(target: "net::session", ?delay, remote_peer_id=?self.remote_peer_id, "peer exceeded bandwidth limit");
+            if let Some(sender) = self.to_session_manager.inner().get_ref() {
+                let _ = sender.try_send(ActiveSessionMessage::BandwidthExceeded {
+                    peer_id: self.remote_peer_id,
+                });
+            }
+            self.ingress_throttle = Some(Box::pin(tokio::time::sleep(delay)));
+        }
+    }
+
     /// Handle a message read from the connection.
     ///
     /// Returns an error if the message is considered to be in violation of the protocol.
@@ -150,6 +196,13 @@ impl ActiveSession {
                 #[allow(clippy::collapsible_match)]
                 if let Some(req) = self.inflight_requests.remove(&request_id) {
                     match req.request {
+                        RequestState::Waiting(request @ PeerRequest::$item { .. })
+                            if message.0.len() > request.max_response_items() =>
+                        {
+                            // the peer sent more items than we asked for
+                            request.send_bad_response();
+                            self.on_bad_message();
+                        }
                         RequestState::Waiting(PeerRequest::$item { response, .. }) => {
                             let _ = response.send(Ok(message));
                             self.update_request_timeout(req.timestamp, Instant::now());
@@ -287,8 +340,8 @@ impl ActiveSession {
 
     /// Returns the deadline timestamp at which the request times out
     fn request_deadline(&self) -> Instant {
-        Instant::now()
-            + Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed))
+        Instant::now() +
+            Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed))
     }
 
     /// Handle a Response to the peer
@@ -559,6 +612,7 @@ impl Future for ActiveSession {
             while this.conn.poll_ready_unpin(cx).is_ready() {
                 if let Some(msg) = this.queued_outgoing.pop_front() {
                     progress = true;
+                    this.bandwidth.on_egress(msg.message_id(), msg.size());
                     let res = match msg {
                         OutgoingMessage::Eth(msg) => this.conn.start_send_unpin(msg),
                         OutgoingMessage::Broadcast(msg) => this.conn.start_send_broadcast(msg),
@@ -600,7 +654,17 @@ impl Future for ActiveSession {
                     };
                 }
 
-                match this.conn.poll_next_unpin(cx) {
+                // wait until the peer is back within its bandwidth limit
+                if let Some(throttle) = this.ingress_throttle.as_mut() {
+                    if throttle.as_mut().poll(cx).is_pending() {
+                        break 'receive;
+                    }
+                    this.ingress_throttle = None;
+                }
+
+                let res = this.conn.poll_next_unpin(cx);
+                this.on_incoming_protocol_bytes();
+                match res {
                     Poll::Pending => break,
                     Poll::Ready(None) => {
                         if this.is_disconnecting() {
@@ -614,6 +678,7 @@ impl Future for ActiveSession {
                         match res {
                             Ok(msg) => {
                                 trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "received eth message");
+                                this.on_incoming_bytes(msg.message_id(), message_size(&msg));
                                 // decode and handle message
                                 match this.on_incoming_message(msg) {
                                     OnIncomingMessageOutcome::Ok => {
@@ -742,6 +807,24 @@ pub(crate) enum OutgoingMessage {
     Broadcast(EthBroadcastMessage),
 }
 
+impl OutgoingMessage {
+    /// Returns the message's ID.
+    fn message_id(&self) -> EthMessageID {
+        match self {
+            OutgoingMessage::Eth(msg) => msg.message_id(),
+            OutgoingMessage::Broadcast(msg) => msg.message_id(),
+        }
+    }
+
+    /// Returns the encoded size of the message, including its ID.
+    fn size(&self) -> usize {
+        match self {
+            OutgoingMessage::Eth(msg) => message_size(msg),
+            OutgoingMessage::Broadcast(msg) => msg.message_id().length() + msg.length(),
+        }
+    }
+}
+
 impl From<EthMessage> for OutgoingMessage {
     fn from(value: EthMessage) -> Self {
         OutgoingMessage::Eth(value)
@@ -754,6 +837,12 @@ impl From<EthBroadcastMessage> for OutgoingMessage {
     }
 }
 
+/// Returns the encoded size of the message, including its ID.
+#[inline]
+fn message_size(msg: &EthMessage) -> usize {
+    msg.message_id().length() + msg.length()
+}
+
 /// Calculates a new timeout using an updated estimation of the RTT
 #[inline]
 fn calculate_new_timeout(current_timeout: Duration, estimated_rtt: Duration) -> Duration {
@@ -770,15 +859,15 @@ mod tests {
     use crate::session::{
         config::{INITIAL_REQUEST_TIMEOUT, PROTOCOL_BREACH_REQUEST_TIMEOUT},
         handle::PendingSessionEvent,
-        start_pending_incoming_session,
+        start_pending_incoming_session, BandwidthLimit,
     };
     use reth_ecies::{stream::ECIESStream, util::pk2id};
     use reth_eth_wire::{
-        EthStream, GetBlockBodies, HelloMessageWithProtocols, P2PStream, Status, StatusBuilder,
-        UnauthedEthStream, UnauthedP2PStream,
+        BlockBodies, EthStream, GetBlockBodies, HelloMessageWithProtocols, P2PStream, Status,
+        StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
     };
     use reth_net_common::bandwidth_meter::{BandwidthMeter, MeteredStream};
-    use reth_primitives::{ForkFilter, Hardfork, MAINNET};
+    use reth_primitives::{BlockBody, ForkFilter, Hardfork, B256, MAINNET};
     use secp256k1::{SecretKey, SECP256K1};
     use std::time::Duration;
     use tokio::{
@@ -861,6 +950,8 @@ mod tests {
                 self.status,
                 self.fork_filter.clone(),
                 Default::default(),
+                Arc::new(SessionBandwidth::new(Default::default())),
+                Default::default(),
             ));
 
             let mut stream = ReceiverStream::new(pending_sessions_rx);
@@ -905,6 +996,9 @@ mod tests {
                         )),
                         protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
                         terminate_message: None,
+                        bandwidth: Arc::new(SessionBandwidth::new(Default::default())),
+                        ingress_limiter: None,
+                        ingress_throttle: None,
                     }
                 }
                 ev => {
@@ -1067,6 +1161,92 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bandwidth_limit() {
+        reth_tracing::init_test_tracing();
+
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let msg = EthMessage::NewPooledTransactionHashes66(vec![B256::ZERO; 10].into());
+        let size = message_size(&msg);
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            for _ in 0..2 {
+                client_stream.send(msg.clone()).await.unwrap();
+            }
+            let _ = tokio::time::timeout(Duration::from_secs(100), client_stream.next()).await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let mut session = builder.connect_incoming(incoming).await;
+        // the first announcement fits into the burst, the second one exceeds the limit
+        session.ingress_limiter =
+            Some(IngressLimiter::new(BandwidthLimit::new(1, size as u64 + size as u64 / 2)));
+        let bandwidth = Arc::clone(&session.bandwidth);
+        tokio::spawn(session);
+
+        loop {
+            match builder.active_session_rx.next().await.unwrap() {
+                ActiveSessionMessage::ValidMessage { .. } => {}
+                ActiveSessionMessage::BandwidthExceeded { .. } => break,
+                ev => unreachable!("{ev:?}"),
+            }
+        }
+
+        let info = bandwidth.info();
+        assert_eq!(info.ingress, 2 * size as u64);
+        assert_eq!(info.egress, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_oversized_response() {
+        reth_tracing::init_test_tracing();
+
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            // answer the request with more bodies than requested
+            let Some(Ok(EthMessage::GetBlockBodies(req))) = client_stream.next().await else {
+                unreachable!("expected a GetBlockBodies request")
+            };
+            let bodies = BlockBodies(vec![BlockBody::default(); 2]);
+            client_stream
+                .send(EthMessage::BlockBodies(RequestPair {
+                    request_id: req.request_id,
+                    message: bodies,
+                }))
+                .await
+                .unwrap();
+            let _ = tokio::time::timeout(Duration::from_secs(100), client_stream.next()).await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let mut session = builder.connect_incoming(incoming).await;
+        let (tx, rx) = oneshot::channel();
+        let req =
+            PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![B256::ZERO]), response: tx };
+        session.on_internal_peer_request(req, Instant::now() + Duration::from_secs(100));
+        tokio::spawn(session);
+
+        let err = rx.await.unwrap().unwrap_err();
+        assert_eq!(err, RequestError::BadResponse);
+
+        loop {
+            match builder.active_session_rx.next().await.unwrap() {
+                ActiveSessionMessage::BadMessage { .. } => break,
+                ev => unreachable!("{ev:?}"),
+            }
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_keep_alive() {
         let mut builder = SessionBuilder::default();
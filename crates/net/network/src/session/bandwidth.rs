@@ -0,0 +1,342 @@
+//! Per-peer bandwidth accounting and limits.
+
+use crate::metrics::MessageBandwidthMetrics;
+use parking_lot::Mutex;
+use reth_eth_wire::{multiplex::ProtocolMeter, EthMessageID};
+use reth_rpc_types::{MessageBandwidthInfo, PeerBandwidthInfo};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The capability of the messages handled by an active session.
+const ETH_CAPABILITY: &str = "eth";
+
+/// All `eth` message IDs.
+const ETH_MESSAGE_IDS: [EthMessageID; 16] = [
+    EthMessageID::Status,
+    EthMessageID::NewBlockHashes,
+    EthMessageID::Transactions,
+    EthMessageID::GetBlockHeaders,
+    EthMessageID::BlockHeaders,
+    EthMessageID::GetBlockBodies,
+    EthMessageID::BlockBodies,
+    EthMessageID::NewBlock,
+    EthMessageID::NewPooledTransactionHashes,
+    EthMessageID::GetPooledTransactions,
+    EthMessageID::PooledTransactions,
+    EthMessageID::GetNodeData,
+    EthMessageID::NodeData,
+    EthMessageID::GetReceipts,
+    EthMessageID::Receipts,
+    EthMessageID::Consensus,
+];
+
+/// Number of slots needed to account `eth` messages by their ID.
+const ETH_MESSAGE_SLOTS: usize = EthMessageID::Consensus as usize + 1;
+
+/// A token bucket rate limit on the bytes a peer may send.
+///
+/// All messages count towards the limit, including responses to our own requests and the messages
+/// of additional RLPx sub-protocols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandwidthLimit {
+    /// The sustained number of bytes per second a peer may send.
+    pub bytes_per_second: u64,
+    /// The number of bytes a peer may send in a burst.
+    ///
+    /// This should not be lower than the size of the largest expected message, otherwise every
+    /// such message exceeds the limit. Note that responses to our own requests can be as large as
+    /// the soft response limit of the protocol.
+    pub burst_bytes: u64,
+}
+
+impl BandwidthLimit {
+    /// Creates a new limit with the given sustained rate and burst size.
+    pub const fn new(bytes_per_second: u64, burst_bytes: u64) -> Self {
+        Self { bytes_per_second, burst_bytes }
+    }
+}
+
+/// Enforces a [`BandwidthLimit`] on the bytes received from a peer.
+#[derive(Debug)]
+pub(crate) struct IngressLimiter {
+    /// The enforced limit.
+    limit: BandwidthLimit,
+    /// Number of bytes the peer may currently send, negative if the peer exceeded the limit.
+    tokens: f64,
+    /// The last time tokens were added to the bucket.
+    last_refill: Instant,
+}
+
+impl IngressLimiter {
+    /// Creates a new limiter with a full bucket.
+    pub(crate) fn new(limit: BandwidthLimit) -> Self {
+        Self { limit, tokens: limit.burst_bytes as f64, last_refill: Instant::now() }
+    }
+
+    /// Consumes the given number of bytes from the bucket.
+    ///
+    /// If the peer exceeded its limit, this returns how long it takes until the peer is back within
+    /// its limit.
+    pub(crate) fn consume(&mut self, bytes: usize, now: Instant) -> Option<Duration> {
+        let rate = self.limit.bytes_per_second.max(1) as f64;
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * rate).min(self.limit.burst_bytes as f64);
+        self.tokens -= bytes as f64;
+
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / rate))
+    }
+}
+
+/// Metrics of the bytes exchanged with all peers, per message.
+#[derive(Debug)]
+pub(crate) struct BandwidthMetrics {
+    messages: [Option<MessageBandwidthMetrics>; ETH_MESSAGE_SLOTS],
+}
+
+impl Default for BandwidthMetrics {
+    fn default() -> Self {
+        let mut messages: [Option<MessageBandwidthMetrics>; ETH_MESSAGE_SLOTS] = Default::default();
+        for id in ETH_MESSAGE_IDS {
+            messages[id as usize] = Some(MessageBandwidthMetrics::new_with_labels(&[
+                ("capability", ETH_CAPABILITY.to_string()),
+                ("message", format!("{id:?}")),
+            ]));
+        }
+        Self { messages }
+    }
+}
+
+/// Bytes exchanged with a single peer, per message.
+///
+/// This is shared between the active session, which updates it, and its handle.
+#[derive(Debug)]
+pub(crate) struct SessionBandwidth {
+    /// Bytes received, by message ID.
+    ingress: [AtomicU64; ETH_MESSAGE_SLOTS],
+    /// Bytes sent, by message ID.
+    egress: [AtomicU64; ETH_MESSAGE_SLOTS],
+    /// Bytes exchanged over the additional RLPx sub-protocols of the session.
+    protocols: Mutex<Vec<Arc<ProtocolBandwidth>>>,
+    /// Metrics of all peers.
+    metrics: Arc<BandwidthMetrics>,
+}
+
+impl SessionBandwidth {
+    /// Creates new empty counters that also update the given metrics.
+    pub(crate) fn new(metrics: Arc<BandwidthMetrics>) -> Self {
+        Self {
+            ingress: Default::default(),
+            egress: Default::default(),
+            protocols: Default::default(),
+            metrics,
+        }
+    }
+
+    /// Returns new counters for the additional RLPx sub-protocol with the given capability name.
+    pub(crate) fn protocol(&self, capability: &str) -> Arc<ProtocolBandwidth> {
+        let protocol = Arc::new(ProtocolBandwidth::new(capability));
+        self.protocols.lock().push(Arc::clone(&protocol));
+        protocol
+    }
+
+    /// Returns the bytes received over additional RLPx sub-protocols since the last call.
+    pub(crate) fn take_protocol_ingress(&self) -> u64 {
+        self.protocols
+            .lock()
+            .iter()
+            .map(|protocol| protocol.unlimited_ingress.swap(0, Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Records a message of the given size that was received from the peer.
+    pub(crate) fn on_ingress(&self, id: EthMessageID, size: usize) {
+        self.ingress[id as usize].fetch_add(size as u64, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics.messages[id as usize] {
+            metrics.ingress_bytes.increment(size as u64);
+        }
+    }
+
+    /// Records a message of the given size that was sent to the peer.
+    pub(crate) fn on_egress(&self, id: EthMessageID, size: usize) {
+        self.egress[id as usize].fetch_add(size as u64, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics.messages[id as usize] {
+            metrics.egress_bytes.increment(size as u64);
+        }
+    }
+
+    /// Returns the bytes exchanged with the peer so far.
+    pub(crate) fn info(&self) -> PeerBandwidthInfo {
+        let mut info = PeerBandwidthInfo::default();
+        let mut messages = std::collections::BTreeMap::new();
+        for id in ETH_MESSAGE_IDS {
+            let ingress = self.ingress[id as usize].load(Ordering::Relaxed);
+            let egress = self.egress[id as usize].load(Ordering::Relaxed);
+            if ingress == 0 && egress == 0 {
+                continue
+            }
+            info.ingress += ingress;
+            info.egress += egress;
+            messages.insert(id as u8, MessageBandwidthInfo { ingress, egress });
+        }
+        if !messages.is_empty() {
+            info.messages.insert(ETH_CAPABILITY.to_string(), messages);
+        }
+        for protocol in self.protocols.lock().iter() {
+            let messages: BTreeMap<_, _> =
+                protocol.messages.lock().iter().map(|(id, (info, _))| (*id, *info)).collect();
+            if messages.is_empty() {
+                continue
+            }
+            for message in messages.values() {
+                info.ingress += message.ingress;
+                info.egress += message.egress;
+            }
+            info.messages.entry(protocol.capability.clone()).or_default().extend(messages);
+        }
+        info
+    }
+}
+
+/// Bytes exchanged with a single peer over an additional RLPx sub-protocol, per message.
+///
+/// This is shared between the multiplexer of the connection, which updates it, and the
+/// [`SessionBandwidth`] of the session.
+#[derive(Debug)]
+pub(crate) struct ProtocolBandwidth {
+    /// The name of the protocol's capability.
+    capability: String,
+    /// Bytes exchanged and the metrics, by message ID relative to the protocol.
+    messages: Mutex<BTreeMap<u8, (MessageBandwidthInfo, MessageBandwidthMetrics)>>,
+    /// Bytes received that were not yet counted towards the bandwidth limit of the peer.
+    unlimited_ingress: AtomicU64,
+}
+
+impl ProtocolBandwidth {
+    /// Creates new empty counters for the protocol with the given capability name.
+    fn new(capability: &str) -> Self {
+        Self {
+            capability: capability.to_string(),
+            messages: Default::default(),
+            unlimited_ingress: Default::default(),
+        }
+    }
+
+    /// Applies `f` to the counters and metrics of the given message.
+    fn on_message(
+        &self,
+        msg: &[u8],
+        f: impl FnOnce(&mut MessageBandwidthInfo, &MessageBandwidthMetrics),
+    ) {
+        let Some(&id) = msg.first() else { return };
+        let mut messages = self.messages.lock();
+        let (info, metrics) = messages.entry(id).or_insert_with(|| {
+            let metrics = MessageBandwidthMetrics::new_with_labels(&[
+                ("capability", self.capability.clone()),
+                ("message", id.to_string()),
+            ]);
+            (MessageBandwidthInfo::default(), metrics)
+        });
+        f(info, metrics)
+    }
+}
+
+impl ProtocolMeter for ProtocolBandwidth {
+    fn on_ingress(&self, msg: &[u8]) {
+        let size = msg.len() as u64;
+        self.on_message(msg, |info, metrics| {
+            info.ingress += size;
+            metrics.ingress_bytes.increment(size);
+        });
+        self.unlimited_ingress.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn on_egress(&self, msg: &[u8]) {
+        let size = msg.len() as u64;
+        self.on_message(msg, |info, metrics| {
+            info.egress += size;
+            metrics.egress_bytes.increment(size);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ingress_limiter() {
+        let start = Instant::now();
+        let mut limiter = IngressLimiter::new(BandwidthLimit::new(1_000, 2_000));
+        limiter.last_refill = start;
+
+        // the burst is available right away
+        assert_eq!(limiter.consume(2_000, start), None);
+        // the peer is 500 bytes in debt, which takes half a second to pay back
+        assert_eq!(limiter.consume(500, start), Some(Duration::from_millis(500)));
+        // after a second, the peer can send another 500 bytes
+        let now = start + Duration::from_secs(1);
+        assert_eq!(limiter.consume(500, now), None);
+        // the bucket never holds more than the burst
+        let now = now + Duration::from_secs(60);
+        assert_eq!(limiter.consume(2_000, now), None);
+        assert!(limiter.consume(1, now).is_some());
+    }
+
+    #[test]
+    fn session_bandwidth_info() {
+        let bandwidth = SessionBandwidth::new(Default::default());
+        assert_eq!(bandwidth.info(), PeerBandwidthInfo::default());
+
+        bandwidth.on_ingress(EthMessageID::BlockBodies, 1_000);
+        bandwidth.on_ingress(EthMessageID::BlockBodies, 500);
+        bandwidth.on_egress(EthMessageID::GetBlockBodies, 100);
+        bandwidth.on_ingress(EthMessageID::Consensus, 50);
+
+        let info = bandwidth.info();
+        assert_eq!(info.ingress, 1_550);
+        assert_eq!(info.egress, 100);
+        let messages = &info.messages[ETH_CAPABILITY];
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[&(EthMessageID::BlockBodies as u8)],
+            MessageBandwidthInfo { ingress: 1_500, egress: 0 }
+        );
+        assert_eq!(
+            messages[&(EthMessageID::GetBlockBodies as u8)],
+            MessageBandwidthInfo { ingress: 0, egress: 100 }
+        );
+    }
+
+    #[test]
+    fn protocol_bandwidth() {
+        let bandwidth = SessionBandwidth::new(Default::default());
+        let snap = bandwidth.protocol("snap");
+        bandwidth.on_ingress(EthMessageID::BlockBodies, 1_000);
+
+        // messages are accounted by their ID relative to the protocol
+        snap.on_egress(&[0x00, 1, 2, 3]);
+        snap.on_ingress(&[0x01; 200]);
+        snap.on_ingress(&[0x01; 100]);
+        snap.on_ingress(&[]);
+
+        let info = bandwidth.info();
+        assert_eq!(info.ingress, 1_300);
+        assert_eq!(info.egress, 4);
+        let messages = &info.messages["snap"];
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[&0x00], MessageBandwidthInfo { ingress: 0, egress: 4 });
+        assert_eq!(messages[&0x01], MessageBandwidthInfo { ingress: 300, egress: 0 });
+
+        // received bytes are counted towards the bandwidth limit once
+        assert_eq!(bandwidth.take_protocol_ingress(), 300);
+        assert_eq!(bandwidth.take_protocol_ingress(), 0);
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     peers::{DEFAULT_MAX_PEERS_INBOUND, DEFAULT_MAX_PEERS_OUTBOUND},
-    session::{Direction, ExceedsSessionLimit},
+    session::{BandwidthLimit, Direction, ExceedsSessionLimit},
 };
 use std::time::Duration;
 
@@ -38,6 +38,10 @@ pub struct SessionsConfig {
     /// `PROTOCOL_BREACH_REQUEST_TIMEOUT`) this is considered a protocol violation and results in a
     /// dropped session.
     pub protocol_breach_request_timeout: Duration,
+    /// The bandwidth limit to enforce on the messages every peer sends.
+    ///
+    /// By default, peers are not limited. The bytes exchanged are accounted either way.
+    pub bandwidth_limit: Option<BandwidthLimit>,
 }
 
 impl Default for SessionsConfig {
@@ -56,6 +60,7 @@ impl Default for SessionsConfig {
             limits: Default::default(),
             initial_internal_request_timeout: INITIAL_REQUEST_TIMEOUT,
             protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
+            bandwidth_limit: None,
         }
     }
 }
@@ -70,6 +75,15 @@ impl SessionsConfig {
         self.session_event_buffer = n;
         self
     }
+
+    /// Sets the bandwidth limit to enforce on the messages every peer sends.
+    ///
+    /// Peers that exceed the limit are penalized, and reading from their connection is paused until
+    /// they are back within the limit.
+    pub fn with_bandwidth_limit(mut self, limit: BandwidthLimit) -> Self {
+        self.bandwidth_limit = Some(limit);
+        self
+    }
 }
 
 /// Limits for sessions.
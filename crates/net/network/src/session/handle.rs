@@ -1,7 +1,7 @@
 //! Session handles.
 use crate::{
     message::PeerMessage,
-    session::{conn::EthRlpxConnection, Direction, SessionId},
+    session::{bandwidth::SessionBandwidth, conn::EthRlpxConnection, Direction, SessionId},
 };
 use reth_ecies::ECIESError;
 use reth_eth_wire::{
@@ -27,6 +27,8 @@ pub struct PendingSessionHandle {
     pub(crate) disconnect_tx: Option<oneshot::Sender<()>>,
     /// The direction of the session
     pub(crate) direction: Direction,
+    /// Bytes exchanged with the peer, handed over to the session once it is established.
+    pub(crate) bandwidth: Arc<SessionBandwidth>,
 }
 
 // === impl PendingSessionHandle ===
@@ -73,6 +75,8 @@ pub struct ActiveSessionHandle {
     pub(crate) local_addr: Option<SocketAddr>,
     /// The Status message the peer sent for the `eth` handshake
     pub(crate) status: Arc<Status>,
+    /// Bytes exchanged with the peer, updated by the session.
+    pub(crate) bandwidth: Arc<SessionBandwidth>,
 }
 
 // === impl ActiveSessionHandle ===
@@ -145,6 +149,7 @@ impl ActiveSessionHandle {
             eth_version: self.version,
             status: self.status.clone(),
             session_established: self.established,
+            bandwidth: self.bandwidth.info(),
         }
     }
 }
@@ -211,6 +216,17 @@ pub enum PendingSessionEvent {
         /// The direction of the session, either `Inbound` or `Outgoing`
         direction: Direction,
     },
+    /// Closes the session because the remote node is not allow-listed in a permissioned network.
+    NotAllowed {
+        /// The remote node's socket address
+        remote_addr: SocketAddr,
+        /// The internal identifier for the disconnected session
+        session_id: SessionId,
+        /// The remote node's public key
+        peer_id: PeerId,
+        /// The direction of the session, either `Inbound` or `Outgoing`
+        direction: Direction,
+    },
 }
 
 /// Commands that can be sent to the spawned session.
@@ -272,4 +288,9 @@ pub enum ActiveSessionMessage {
         /// Identifier of the remote peer.
         peer_id: PeerId,
     },
+    /// Remote peer exceeded its bandwidth limit
+    BandwidthExceeded {
+        /// Identifier of the remote peer.
+        peer_id: PeerId,
+    },
 }
@@ -2,7 +2,12 @@
 use crate::{
     message::PeerMessage,
     metrics::SessionManagerMetrics,
-    session::{active::ActiveSession, config::SessionCounter},
+    permissions::NetworkPermissions,
+    session::{
+        active::ActiveSession,
+        bandwidth::{BandwidthMetrics, IngressLimiter, SessionBandwidth},
+        config::SessionCounter,
+    },
 };
 use fnv::FnvHashMap;
 use futures::{future::Either, io, FutureExt, StreamExt};
@@ -39,11 +44,13 @@ use tokio_util::sync::PollSender;
 use tracing::{instrument, trace};
 
 mod active;
+mod bandwidth;
 mod config;
 mod conn;
 mod handle;
 pub use crate::message::PeerRequestSender;
 use crate::protocol::{IntoRlpxSubProtocol, RlpxSubProtocolHandlers, RlpxSubProtocols};
+pub use bandwidth::BandwidthLimit;
 pub use config::{SessionLimits, SessionsConfig};
 pub use handle::{
     ActiveSessionHandle, ActiveSessionMessage, PendingSessionEvent, PendingSessionHandle,
@@ -70,6 +77,10 @@ pub struct SessionManager {
     /// If an [ActiveSession] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     protocol_breach_request_timeout: Duration,
+    /// The bandwidth limit every [ActiveSession] enforces on its peer, if any.
+    bandwidth_limit: Option<BandwidthLimit>,
+    /// Metrics for the bytes exchanged with peers, shared by all sessions.
+    bandwidth_metrics: Arc<BandwidthMetrics>,
     /// The secret key used for authenticating sessions.
     secret_key: SecretKey,
     /// The `Status` message to send to peers.
@@ -109,6 +120,8 @@ pub struct SessionManager {
     bandwidth_meter: BandwidthMeter,
     /// Metrics for the session manager.
     metrics: SessionManagerMetrics,
+    /// The allowlist of the permissioned network mode.
+    permissions: NetworkPermissions,
 }
 
 // === impl SessionManager ===
@@ -125,6 +138,7 @@ impl SessionManager {
         fork_filter: ForkFilter,
         extra_protocols: RlpxSubProtocols,
         bandwidth_meter: BandwidthMeter,
+        permissions: NetworkPermissions,
     ) -> Self {
         let (pending_sessions_tx, pending_sessions_rx) = mpsc::channel(config.session_event_buffer);
         let (active_session_tx, active_session_rx) = mpsc::channel(config.session_event_buffer);
@@ -135,6 +149,8 @@ impl SessionManager {
             counter: SessionCounter::new(config.limits),
             initial_internal_request_timeout: config.initial_internal_request_timeout,
             protocol_breach_request_timeout: config.protocol_breach_request_timeout,
+            bandwidth_limit: config.bandwidth_limit,
+            bandwidth_metrics: Default::default(),
             secret_key,
             status,
             hello_message,
@@ -150,6 +166,7 @@ impl SessionManager {
             bandwidth_meter,
             extra_protocols,
             metrics: Default::default(),
+            permissions,
         }
     }
 
@@ -181,6 +198,11 @@ impl SessionManager {
         self.hello_message.clone()
     }
 
+    /// Returns the allowlist of the permissioned network mode.
+    pub(crate) fn permissions(&self) -> &NetworkPermissions {
+        &self.permissions
+    }
+
     /// Adds an additional protocol handler to the RLPx sub-protocol list.
     pub(crate) fn add_rlpx_sub_protocol(&mut self, protocol: impl IntoRlpxSubProtocol) {
         self.extra_protocols.push(protocol)
@@ -235,6 +257,8 @@ impl SessionManager {
         let status = self.status;
         let fork_filter = self.fork_filter.clone();
         let extra_handlers = self.extra_protocols.on_incoming(remote_addr);
+        let bandwidth = Arc::new(SessionBandwidth::new(Arc::clone(&self.bandwidth_metrics)));
+        let permissions = self.permissions.clone();
         self.spawn(start_pending_incoming_session(
             disconnect_rx,
             session_id,
@@ -246,11 +270,14 @@ impl SessionManager {
             status,
             fork_filter,
             extra_handlers,
+            Arc::clone(&bandwidth),
+            permissions,
         ));
 
         let handle = PendingSessionHandle {
             disconnect_tx: Some(disconnect_tx),
             direction: Direction::Incoming,
+            bandwidth,
         };
         self.pending_sessions.insert(session_id, handle);
         self.counter.inc_pending_inbound();
@@ -270,6 +297,8 @@ impl SessionManager {
             let status = self.status;
             let band_with_meter = self.bandwidth_meter.clone();
             let extra_handlers = self.extra_protocols.on_outgoing(remote_addr, remote_peer_id);
+            let bandwidth = Arc::new(SessionBandwidth::new(Arc::clone(&self.bandwidth_metrics)));
+            let permissions = self.permissions.clone();
             self.spawn(start_pending_outbound_session(
                 disconnect_rx,
                 pending_events,
@@ -282,11 +311,14 @@ impl SessionManager {
                 fork_filter,
                 band_with_meter,
                 extra_handlers,
+                Arc::clone(&bandwidth),
+                permissions,
             ));
 
             let handle = PendingSessionHandle {
                 disconnect_tx: Some(disconnect_tx),
                 direction: Direction::Outgoing(remote_peer_id),
+                bandwidth,
             };
             self.pending_sessions.insert(session_id, handle);
             self.counter.inc_pending_outbound();
@@ -402,6 +434,9 @@ impl SessionManager {
                     ActiveSessionMessage::ProtocolBreach { peer_id } => {
                         Poll::Ready(SessionEvent::ProtocolBreach { peer_id })
                     }
+                    ActiveSessionMessage::BandwidthExceeded { peer_id } => {
+                        Poll::Ready(SessionEvent::BandwidthExceeded { peer_id })
+                    }
                 }
             }
         }
@@ -425,7 +460,10 @@ impl SessionManager {
                 client_id,
             } => {
                 // move from pending to established.
-                self.remove_pending_session(&session_id);
+                let bandwidth = match self.remove_pending_session(&session_id) {
+                    Some(pending) => pending.bandwidth,
+                    None => Arc::new(SessionBandwidth::new(Arc::clone(&self.bandwidth_metrics))),
+                };
 
                 // If there's already a session to the peer then we disconnect right away
                 if self.active_sessions.contains_key(&peer_id) {
@@ -484,6 +522,9 @@ impl SessionManager {
                     internal_request_timeout: Arc::clone(&timeout),
                     protocol_breach_request_timeout: self.protocol_breach_request_timeout,
                     terminate_message: None,
+                    bandwidth: Arc::clone(&bandwidth),
+                    ingress_limiter: self.bandwidth_limit.map(IngressLimiter::new),
+                    ingress_throttle: None,
                 };
 
                 self.spawn(session);
@@ -501,6 +542,7 @@ impl SessionManager {
                     client_version: Arc::clone(&client_version),
                     remote_addr,
                     local_addr,
+                    bandwidth,
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -590,6 +632,32 @@ impl SessionManager {
                     }
                 }
             }
+            PendingSessionEvent::NotAllowed { remote_addr, session_id, peer_id, direction } => {
+                trace!(
+                    target: "net::session",
+                    ?session_id,
+                    ?remote_addr,
+                    ?peer_id,
+                    "node is not allowed"
+                );
+                self.remove_pending_session(&session_id);
+                let error = Some(PendingSessionHandshakeError::NotAllowed(peer_id));
+                match direction {
+                    Direction::Incoming => {
+                        Poll::Ready(SessionEvent::IncomingPendingSessionClosed {
+                            remote_addr,
+                            error,
+                        })
+                    }
+                    Direction::Outgoing(peer_id) => {
+                        Poll::Ready(SessionEvent::OutgoingPendingSessionClosed {
+                            remote_addr,
+                            peer_id,
+                            error,
+                        })
+                    }
+                }
+            }
         }
     }
 
@@ -685,6 +753,11 @@ pub enum SessionEvent {
         /// Identifier of the remote peer.
         peer_id: PeerId,
     },
+    /// Remote peer exceeded its bandwidth limit
+    BandwidthExceeded {
+        /// Identifier of the remote peer.
+        peer_id: PeerId,
+    },
     /// Closed an incoming pending session during handshaking.
     IncomingPendingSessionClosed {
         /// The remote node's socket address
@@ -735,6 +808,8 @@ pub enum PendingSessionHandshakeError {
     Eth(EthStreamError),
     /// The pending session failed due to an error while establishing the ECIES stream
     Ecies(ECIESError),
+    /// The remote node is not allow-listed in a permissioned network
+    NotAllowed(PeerId),
 }
 
 impl PendingSessionHandshakeError {
@@ -768,6 +843,8 @@ pub(crate) async fn start_pending_incoming_session(
     status: Status,
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
+    bandwidth: Arc<SessionBandwidth>,
+    permissions: NetworkPermissions,
 ) {
     authenticate(
         disconnect_rx,
@@ -781,6 +858,8 @@ pub(crate) async fn start_pending_incoming_session(
         status,
         fork_filter,
         extra_handlers,
+        bandwidth,
+        permissions,
     )
     .await
 }
@@ -800,6 +879,8 @@ async fn start_pending_outbound_session(
     fork_filter: ForkFilter,
     bandwidth_meter: BandwidthMeter,
     extra_handlers: RlpxSubProtocolHandlers,
+    bandwidth: Arc<SessionBandwidth>,
+    permissions: NetworkPermissions,
 ) {
     let stream = match TcpStream::connect(remote_addr).await {
         Ok(stream) => {
@@ -832,6 +913,8 @@ async fn start_pending_outbound_session(
         status,
         fork_filter,
         extra_handlers,
+        bandwidth,
+        permissions,
     )
     .await
 }
@@ -850,6 +933,8 @@ async fn authenticate(
     status: Status,
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
+    bandwidth: Arc<SessionBandwidth>,
+    permissions: NetworkPermissions,
 ) {
     let local_addr = stream.inner().local_addr().ok();
     let stream = match get_eciess_stream(stream, secret_key, direction).await {
@@ -867,6 +952,16 @@ async fn authenticate(
         }
     };
 
+    // in a permissioned network, only allow-listed nodes are accepted
+    let peer_id = stream.remote_id();
+    if !permissions.check_session(&peer_id, remote_addr.ip()) {
+        let _ = UnauthedP2PStream::new(stream).send_disconnect(DisconnectReason::UselessPeer).await;
+        let _ = events
+            .send(PendingSessionEvent::NotAllowed { remote_addr, session_id, peer_id, direction })
+            .await;
+        return
+    }
+
     let unauthed = UnauthedP2PStream::new(stream);
 
     let auth = authenticate_stream(
@@ -879,6 +974,7 @@ async fn authenticate(
         status,
         fork_filter,
         extra_handlers,
+        bandwidth,
     )
     .boxed();
 
@@ -931,6 +1027,7 @@ async fn authenticate_stream(
     mut status: Status,
     fork_filter: ForkFilter,
     mut extra_handlers: RlpxSubProtocolHandlers,
+    bandwidth: Arc<SessionBandwidth>,
 ) -> PendingSessionEvent {
     // Add extra protocols to the hello message
     extra_handlers.retain(|handler| hello.try_add_protocol(handler.protocol()).is_ok());
@@ -986,12 +1083,13 @@ async fn authenticate_stream(
             .await
             .unwrap();
 
-        // install additional handlers
+        // install additional handlers, the bytes they exchange are accounted per capability
         for handler in extra_handlers.into_iter() {
             let cap = handler.protocol().cap;
             let remote_peer_id = their_hello.id;
+            let meter = bandwidth.protocol(&cap.name);
             multiplex_stream
-                .install_protocol(&cap, move |conn| {
+                .install_metered_protocol(&cap, meter, move |conn| {
                     handler.into_connection(direction, remote_peer_id, conn)
                 })
                 .ok();
@@ -0,0 +1,292 @@
+//! A client that requests state from `snap/1` peers.
+
+use crate::peers::PeersHandle;
+use futures::future;
+use parking_lot::Mutex;
+use reth_eth_wire::{
+    AccountRangeMessage, ByteCodesMessage, GetAccountRangeMessage, GetByteCodesMessage,
+    GetStorageRangesMessage, GetTrieNodesMessage, SnapProtocolMessage, StorageRangesMessage,
+    TrieNodesMessage,
+};
+use reth_interfaces::p2p::{
+    download::DownloadClient,
+    error::{RequestError, RequestResult},
+    snap::{SnapClient, SnapFut},
+};
+use reth_network_api::ReputationChangeKind;
+use reth_primitives::{PeerId, WithPeerId};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// The default timeout of a request to a single peer.
+pub const SNAP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum number of peers a request is sent to before it fails.
+const MAX_REQUEST_ATTEMPTS: usize = 3;
+
+/// A request of the [`SnapFetchClient`] that is sent to the peer by its
+/// [`SnapConnection`](super::SnapConnection).
+#[derive(Debug)]
+pub(crate) struct SnapPeerRequest {
+    /// The request to send. The request id is assigned by the connection.
+    pub(crate) request: SnapProtocolMessage,
+    /// The channel sender for the response.
+    pub(crate) response: oneshot::Sender<RequestResult<SnapProtocolMessage>>,
+}
+
+/// All connected peers that support `snap/1`, shared by the connections and the client.
+pub(crate) type SnapPeers = Arc<Mutex<HashMap<PeerId, mpsc::UnboundedSender<SnapPeerRequest>>>>;
+
+/// Front-end API for requesting state from `snap/1` peers.
+///
+/// Requests are distributed over the connected `snap/1` peers in round-robin fashion. A request
+/// that times out or whose connection drops is retried with the next peer, up to
+/// [`MAX_REQUEST_ATTEMPTS`] peers.
+#[derive(Debug, Clone)]
+pub struct SnapFetchClient {
+    /// The connected `snap/1` peers.
+    peers: SnapPeers,
+    /// Index of the next peer to send a request to.
+    next_peer: Arc<AtomicUsize>,
+    /// The handle to the peers, used to report bad responses.
+    peers_handle: Option<PeersHandle>,
+    /// The timeout of a request to a single peer.
+    request_timeout: Duration,
+}
+
+impl SnapFetchClient {
+    /// Creates a new client for the given peers.
+    pub(crate) fn new(peers: SnapPeers) -> Self {
+        Self {
+            peers,
+            next_peer: Default::default(),
+            peers_handle: None,
+            request_timeout: SNAP_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Sets the timeout of a request to a single peer.
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the [`PeersHandle`] that bad responses are reported to.
+    pub fn with_peers_handle(mut self, peers_handle: PeersHandle) -> Self {
+        self.peers_handle = Some(peers_handle);
+        self
+    }
+
+    /// Picks the next `snap/1` peer.
+    fn next_peer(&self) -> Option<(PeerId, mpsc::UnboundedSender<SnapPeerRequest>)> {
+        let peers = self.peers.lock();
+        if peers.is_empty() {
+            return None
+        }
+        let idx = self.next_peer.fetch_add(1, Ordering::Relaxed) % peers.len();
+        peers.iter().nth(idx).map(|(peer_id, tx)| (*peer_id, tx.clone()))
+    }
+
+    /// Sends the request to the next peer and maps the response with the given function.
+    ///
+    /// Peers that don't respond in time are penalized and the request is retried with the next
+    /// peer. Responses that can't be mapped are reported as [`RequestError::BadResponse`].
+    fn send_request<T: Send + 'static>(
+        &self,
+        request: SnapProtocolMessage,
+        map: fn(SnapProtocolMessage) -> Option<T>,
+    ) -> SnapFut<T> {
+        if self.peers.lock().is_empty() {
+            return Box::pin(future::err(RequestError::UnsupportedCapability))
+        }
+
+        let this = self.clone();
+        Box::pin(async move {
+            let mut err = RequestError::UnsupportedCapability;
+            for _ in 0..MAX_REQUEST_ATTEMPTS {
+                let Some((peer_id, tx)) = this.next_peer() else { break };
+
+                let (response, rx) = oneshot::channel();
+                if tx.send(SnapPeerRequest { request: request.clone(), response }).is_err() {
+                    err = RequestError::ConnectionDropped;
+                    continue
+                }
+
+                let response = match tokio::time::timeout(this.request_timeout, rx).await {
+                    Ok(Ok(Ok(response))) => response,
+                    Ok(Ok(Err(request_err))) => {
+                        err = request_err;
+                        continue
+                    }
+                    Ok(Err(_)) => {
+                        err = RequestError::ConnectionDropped;
+                        continue
+                    }
+                    Err(_) => {
+                        this.report(peer_id, ReputationChangeKind::Timeout);
+                        err = RequestError::Timeout;
+                        continue
+                    }
+                };
+                return map(response)
+                    .map(|data| WithPeerId::new(peer_id, data))
+                    .ok_or(RequestError::BadResponse)
+            }
+            Err(err)
+        })
+    }
+
+    /// Reports the peer to the [`PeersHandle`], if set.
+    fn report(&self, peer_id: PeerId, kind: ReputationChangeKind) {
+        if let Some(peers_handle) = &self.peers_handle {
+            peers_handle.reputation_change(peer_id, kind);
+        }
+    }
+}
+
+impl DownloadClient for SnapFetchClient {
+    fn report_bad_message(&self, peer_id: PeerId) {
+        self.report(peer_id, ReputationChangeKind::BadMessage);
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.peers.lock().len()
+    }
+}
+
+impl SnapClient for SnapFetchClient {
+    fn get_account_range(&self, request: GetAccountRangeMessage) -> SnapFut<AccountRangeMessage> {
+        self.send_request(SnapProtocolMessage::GetAccountRange(request), |msg| match msg {
+            SnapProtocolMessage::AccountRange(msg) => Some(msg),
+            _ => None,
+        })
+    }
+
+    fn get_storage_ranges(
+        &self,
+        request: GetStorageRangesMessage,
+    ) -> SnapFut<StorageRangesMessage> {
+        self.send_request(SnapProtocolMessage::GetStorageRanges(request), |msg| match msg {
+            SnapProtocolMessage::StorageRanges(msg) => Some(msg),
+            _ => None,
+        })
+    }
+
+    fn get_byte_codes(&self, request: GetByteCodesMessage) -> SnapFut<ByteCodesMessage> {
+        self.send_request(SnapProtocolMessage::GetByteCodes(request), |msg| match msg {
+            SnapProtocolMessage::ByteCodes(msg) => Some(msg),
+            _ => None,
+        })
+    }
+
+    fn get_trie_nodes(&self, request: GetTrieNodesMessage) -> SnapFut<TrieNodesMessage> {
+        self.send_request(SnapProtocolMessage::GetTrieNodes(request), |msg| match msg {
+            SnapProtocolMessage::TrieNodes(msg) => Some(msg),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Bytes, B256};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn request_without_peers() {
+        let client = SnapFetchClient::new(Default::default());
+        assert_eq!(client.num_connected_peers(), 0);
+
+        let request = GetByteCodesMessage { request_id: 0, hashes: vec![], response_bytes: 0 };
+        let err = client.get_byte_codes(request).await.unwrap_err();
+        assert_eq!(err, RequestError::UnsupportedCapability);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn request_connected_peer() {
+        let peers = SnapPeers::default();
+        let client = SnapFetchClient::new(peers.clone());
+
+        let peer_id = PeerId::random();
+        let (tx, mut rx) = mpsc::unbounded_channel::<SnapPeerRequest>();
+        peers.lock().insert(peer_id, tx);
+        assert_eq!(client.num_connected_peers(), 1);
+
+        let code = Bytes::from_static(&[0x60, 0x00]);
+        let expected = code.clone();
+        tokio::spawn(async move {
+            // answer the first request and respond with the wrong message to the second one
+            let req = rx.recv().await.unwrap();
+            assert!(matches!(req.request, SnapProtocolMessage::GetByteCodes(_)));
+            let _ = req.response.send(Ok(SnapProtocolMessage::ByteCodes(ByteCodesMessage {
+                request_id: req.request.request_id(),
+                codes: vec![code],
+            })));
+
+            let req = rx.recv().await.unwrap();
+            let _ = req.response.send(Ok(SnapProtocolMessage::TrieNodes(TrieNodesMessage {
+                request_id: req.request.request_id(),
+                nodes: vec![],
+            })));
+        });
+
+        let request = GetByteCodesMessage {
+            request_id: 0,
+            hashes: vec![B256::random()],
+            response_bytes: 1024 * 1024,
+        };
+        let response = client.get_byte_codes(request.clone()).await.unwrap();
+        assert_eq!(response.peer_id(), peer_id);
+        assert_eq!(response.into_data().codes, vec![expected]);
+
+        let err = client.get_byte_codes(request).await.unwrap_err();
+        assert_eq!(err, RequestError::BadResponse);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retry_unresponsive_peer() {
+        let peers = SnapPeers::default();
+        let client =
+            SnapFetchClient::new(peers.clone()).with_request_timeout(Duration::from_millis(50));
+
+        // the first peer never responds
+        let (tx, mut unresponsive) = mpsc::unbounded_channel::<SnapPeerRequest>();
+        peers.lock().insert(PeerId::random(), tx);
+        let request =
+            GetByteCodesMessage { request_id: 0, hashes: vec![], response_bytes: 1024 * 1024 };
+        let pending = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            while let Some(req) = unresponsive.recv().await {
+                requests.push(req);
+            }
+        });
+        let err = client.get_byte_codes(request.clone()).await.unwrap_err();
+        assert_eq!(err, RequestError::Timeout);
+
+        // the request is retried with the responsive peer
+        let peer_id = PeerId::random();
+        let (tx, mut rx) = mpsc::unbounded_channel::<SnapPeerRequest>();
+        peers.lock().insert(peer_id, tx);
+        tokio::spawn(async move {
+            while let Some(req) = rx.recv().await {
+                let _ = req.response.send(Ok(SnapProtocolMessage::ByteCodes(ByteCodesMessage {
+                    request_id: req.request.request_id(),
+                    codes: vec![],
+                })));
+            }
+        });
+
+        for _ in 0..2 {
+            let response = client.get_byte_codes(request.clone()).await.unwrap();
+            assert_eq!(response.peer_id(), peer_id);
+        }
+        pending.abort();
+    }
+}
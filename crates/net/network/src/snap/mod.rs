@@ -0,0 +1,421 @@
+//! Support for the `snap/1` protocol as RLPx sub-protocol.
+//!
+//! The [`SnapProtocolHandler`] is registered via
+//! [`add_rlpx_sub_protocol`](crate::NetworkConfigBuilder::add_rlpx_sub_protocol) and announces
+//! `snap/1` on every connection. Requests of the remote are forwarded to the
+//! [`SnapRequestHandler`], which serves them from the hashed state and trie tables. Only the state
+//! root of the last fully synced block is served, and only while no pipeline run is writing the
+//! tables, so peers can only sync from this node while it is at the tip. A handler created with
+//! [`SnapProtocolHandler::client_only`] doesn't serve any state and answers all requests with empty
+//! responses.
+//!
+//! State of other peers can be requested through the [`SnapFetchClient`] of the handler, see
+//! [`SnapProtocolHandler::client`].
+//!
+//! See also <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+
+use crate::protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler};
+use client::{SnapPeerRequest, SnapPeers};
+use futures::{
+    future::{self, BoxFuture},
+    stream::FuturesUnordered,
+    FutureExt, Stream, StreamExt,
+};
+use reth_eth_wire::{
+    capability::SharedCapabilities, multiplex::ProtocolConnection, protocol::Protocol,
+    AccountRangeMessage, ByteCodesMessage, GetAccountRangeMessage, GetByteCodesMessage,
+    GetStorageRangesMessage, GetTrieNodesMessage, SnapProtocolMessage, StorageRangesMessage,
+    TrieNodesMessage,
+};
+use reth_interfaces::p2p::error::{RequestError, RequestResult};
+use reth_network_api::Direction;
+use reth_primitives::{BytesMut, PeerId};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::trace;
+
+mod client;
+mod server;
+pub use client::SnapFetchClient;
+pub use server::SnapRequestHandler;
+
+/// The maximum number of requests that are buffered until the [`SnapRequestHandler`] serves
+/// them.
+pub const SNAP_REQUEST_CHANNEL_CAPACITY: usize = 1024;
+
+/// All `snap` requests delegated to the [`SnapRequestHandler`].
+#[derive(Debug)]
+pub enum IncomingSnapRequest {
+    /// Request a range of accounts.
+    GetAccountRange {
+        /// The ID of the peer to request accounts from.
+        peer_id: PeerId,
+        /// The specific accounts requested.
+        request: GetAccountRangeMessage,
+        /// The channel sender for the response.
+        response: oneshot::Sender<AccountRangeMessage>,
+    },
+    /// Request ranges of storage slots.
+    GetStorageRanges {
+        /// The ID of the peer to request storage slots from.
+        peer_id: PeerId,
+        /// The specific storage slots requested.
+        request: GetStorageRangesMessage,
+        /// The channel sender for the response.
+        response: oneshot::Sender<StorageRangesMessage>,
+    },
+    /// Request contract bytecodes.
+    GetByteCodes {
+        /// The ID of the peer to request bytecodes from.
+        peer_id: PeerId,
+        /// The specific bytecodes requested.
+        request: GetByteCodesMessage,
+        /// The channel sender for the response.
+        response: oneshot::Sender<ByteCodesMessage>,
+    },
+    /// Request trie nodes.
+    GetTrieNodes {
+        /// The ID of the peer to request trie nodes from.
+        peer_id: PeerId,
+        /// The specific trie nodes requested.
+        request: GetTrieNodesMessage,
+        /// The channel sender for the response.
+        response: oneshot::Sender<TrieNodesMessage>,
+    },
+}
+
+/// The [`ProtocolHandler`] of the `snap/1` protocol.
+#[derive(Debug, Clone)]
+pub struct SnapProtocolHandler {
+    /// Sender half of the channel to the [`SnapRequestHandler`], `None` if state is not served.
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+    /// The connected peers that support `snap/1`.
+    peers: SnapPeers,
+}
+
+impl SnapProtocolHandler {
+    /// Creates a new handler that delegates requests to the given channel.
+    pub fn new(to_request_handler: mpsc::Sender<IncomingSnapRequest>) -> Self {
+        Self { to_request_handler: Some(to_request_handler), peers: Default::default() }
+    }
+
+    /// Creates a new handler that only requests state from peers.
+    ///
+    /// Requests of peers are answered with empty responses, like a node that doesn't have the
+    /// requested state.
+    pub fn client_only() -> Self {
+        Self { to_request_handler: None, peers: Default::default() }
+    }
+
+    /// Returns a [`SnapFetchClient`] that sends requests to the `snap/1` peers connected through
+    /// this handler.
+    pub fn client(&self) -> SnapFetchClient {
+        SnapFetchClient::new(self.peers.clone())
+    }
+
+    fn connection_handler(&self) -> SnapConnectionHandler {
+        SnapConnectionHandler {
+            to_request_handler: self.to_request_handler.clone(),
+            peers: self.peers.clone(),
+        }
+    }
+}
+
+impl ProtocolHandler for SnapProtocolHandler {
+    type ConnectionHandler = SnapConnectionHandler;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+}
+
+/// The [`ConnectionHandler`] of the `snap/1` protocol.
+#[derive(Debug)]
+pub struct SnapConnectionHandler {
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+    peers: SnapPeers,
+}
+
+impl ConnectionHandler for SnapConnectionHandler {
+    type Connection = SnapConnection;
+
+    fn protocol(&self) -> Protocol {
+        SnapProtocolMessage::protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+        self.peers.lock().insert(peer_id, request_tx.clone());
+        SnapConnection {
+            peer_id,
+            conn,
+            to_request_handler: self.to_request_handler,
+            pending_responses: Default::default(),
+            peers: self.peers,
+            request_tx,
+            requests: UnboundedReceiverStream::new(request_rx),
+            inflight_requests: Default::default(),
+            next_request_id: 0,
+        }
+    }
+}
+
+/// A response that is being served by the [`SnapRequestHandler`].
+type PendingResponse = BoxFuture<'static, Option<SnapProtocolMessage>>;
+
+/// A `snap/1` connection to a peer.
+///
+/// Yields the encoded responses to the requests of the peer and the requests of the
+/// [`SnapFetchClient`]. The connection is closed if the peer sends an invalid message.
+#[must_use = "Streams do nothing unless polled"]
+pub struct SnapConnection {
+    /// The peer of the connection.
+    peer_id: PeerId,
+    /// Incoming messages of the peer.
+    conn: ProtocolConnection,
+    /// Sender half of the channel to the [`SnapRequestHandler`], `None` if state is not served.
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+    /// Responses that are being served.
+    pending_responses: FuturesUnordered<PendingResponse>,
+    /// The connected `snap/1` peers, this connection is removed on drop.
+    peers: SnapPeers,
+    /// The sender half of [`Self::requests`] that is registered in [`Self::peers`].
+    request_tx: mpsc::UnboundedSender<SnapPeerRequest>,
+    /// Requests of the [`SnapFetchClient`] to send to the peer.
+    requests: UnboundedReceiverStream<SnapPeerRequest>,
+    /// Requests sent to the peer that await a response, by request id.
+    inflight_requests: HashMap<u64, oneshot::Sender<RequestResult<SnapProtocolMessage>>>,
+    /// The request id of the next request.
+    next_request_id: u64,
+}
+
+impl SnapConnection {
+    /// Delegates the request to the [`SnapRequestHandler`].
+    fn on_request(&mut self, request: SnapProtocolMessage) {
+        if self.to_request_handler.is_none() {
+            if let Some(response) = empty_response(&request) {
+                self.pending_responses.push(future::ready(Some(response)).boxed());
+                return
+            }
+        }
+
+        let peer_id = self.peer_id;
+        let (request, response) = match request {
+            SnapProtocolMessage::GetAccountRange(request) => {
+                let (tx, rx) = oneshot::channel();
+                let request =
+                    IncomingSnapRequest::GetAccountRange { peer_id, request, response: tx };
+                (request, rx.map(|res| res.ok().map(SnapProtocolMessage::AccountRange)).boxed())
+            }
+            SnapProtocolMessage::GetStorageRanges(request) => {
+                let (tx, rx) = oneshot::channel();
+                let request =
+                    IncomingSnapRequest::GetStorageRanges { peer_id, request, response: tx };
+                (request, rx.map(|res| res.ok().map(SnapProtocolMessage::StorageRanges)).boxed())
+            }
+            SnapProtocolMessage::GetByteCodes(request) => {
+                let (tx, rx) = oneshot::channel();
+                let request = IncomingSnapRequest::GetByteCodes { peer_id, request, response: tx };
+                (request, rx.map(|res| res.ok().map(SnapProtocolMessage::ByteCodes)).boxed())
+            }
+            SnapProtocolMessage::GetTrieNodes(request) => {
+                let (tx, rx) = oneshot::channel();
+                let request = IncomingSnapRequest::GetTrieNodes { peer_id, request, response: tx };
+                (request, rx.map(|res| res.ok().map(SnapProtocolMessage::TrieNodes)).boxed())
+            }
+            response => return self.on_response(response),
+        };
+
+        let delegated =
+            self.to_request_handler.as_ref().map_or(false, |tx| tx.try_send(request).is_ok());
+        if delegated {
+            self.pending_responses.push(response);
+        } else {
+            trace!(target: "net::snap", ?peer_id, "Snap request handler is busy, dropping request");
+        }
+    }
+
+    /// Resolves the inflight request the response belongs to.
+    fn on_response(&mut self, response: SnapProtocolMessage) {
+        match self.inflight_requests.remove(&response.request_id()) {
+            Some(tx) => {
+                let _ = tx.send(Ok(response));
+            }
+            None => {
+                trace!(target: "net::snap", peer_id=?self.peer_id, id=?response.message_id(), "Received unsolicited snap response");
+            }
+        }
+    }
+
+    /// Assigns a request id to the request and returns the encoded message to send.
+    fn on_outgoing_request(&mut self, request: SnapPeerRequest) -> BytesMut {
+        let SnapPeerRequest { mut request, response } = request;
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        request.set_request_id(request_id);
+        self.inflight_requests.insert(request_id, response);
+        request.encoded()
+    }
+}
+
+impl Stream for SnapConnection {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            // flush served responses first
+            while let Poll::Ready(Some(response)) = this.pending_responses.poll_next_unpin(cx) {
+                if let Some(response) = response {
+                    return Poll::Ready(Some(response.encoded()))
+                }
+            }
+
+            if let Poll::Ready(Some(request)) = this.requests.poll_next_unpin(cx) {
+                return Poll::Ready(Some(this.on_outgoing_request(request)))
+            }
+
+            let Some(msg) = futures::ready!(this.conn.poll_next_unpin(cx)) else {
+                // the connection was closed
+                return Poll::Ready(None)
+            };
+
+            match SnapProtocolMessage::decode_message(&mut &msg[..]) {
+                Ok(msg) => this.on_request(msg),
+                Err(err) => {
+                    trace!(target: "net::snap", peer_id=?this.peer_id, %err, "Received invalid snap message");
+                    return Poll::Ready(None)
+                }
+            }
+        }
+    }
+}
+
+/// Returns the empty response to the request, which tells the peer that the state is not
+/// available.
+///
+/// Returns `None` if the message is not a request.
+fn empty_response(request: &SnapProtocolMessage) -> Option<SnapProtocolMessage> {
+    let request_id = request.request_id();
+    let response = match request {
+        SnapProtocolMessage::GetAccountRange(_) => {
+            SnapProtocolMessage::AccountRange(AccountRangeMessage {
+                request_id,
+                accounts: Vec::new(),
+                proof: Vec::new(),
+            })
+        }
+        SnapProtocolMessage::GetStorageRanges(_) => {
+            SnapProtocolMessage::StorageRanges(StorageRangesMessage {
+                request_id,
+                slots: Vec::new(),
+                proof: Vec::new(),
+            })
+        }
+        SnapProtocolMessage::GetByteCodes(_) => {
+            SnapProtocolMessage::ByteCodes(ByteCodesMessage { request_id, codes: Vec::new() })
+        }
+        SnapProtocolMessage::GetTrieNodes(_) => {
+            SnapProtocolMessage::TrieNodes(TrieNodesMessage { request_id, nodes: Vec::new() })
+        }
+        _ => return None,
+    };
+    Some(response)
+}
+
+impl std::fmt::Debug for SnapConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapConnection")
+            .field("peer_id", &self.peer_id)
+            .field("pending_responses", &self.pending_responses.len())
+            .field("inflight_requests", &self.inflight_requests.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SnapConnection {
+    fn drop(&mut self) {
+        let mut peers = self.peers.lock();
+        // the peer may have reconnected in the meantime
+        if peers.get(&self.peer_id).map_or(false, |tx| tx.same_channel(&self.request_tx)) {
+            peers.remove(&self.peer_id);
+        }
+        drop(peers);
+
+        for (_, tx) in self.inflight_requests.drain() {
+            let _ = tx.send(Err(RequestError::ConnectionDropped));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+
+    #[test]
+    fn empty_responses() {
+        let request = SnapProtocolMessage::GetAccountRange(GetAccountRangeMessage {
+            request_id: 7,
+            root_hash: B256::random(),
+            starting_hash: B256::ZERO,
+            limit_hash: B256::repeat_byte(0xff),
+            response_bytes: 1024,
+        });
+        assert_eq!(
+            empty_response(&request),
+            Some(SnapProtocolMessage::AccountRange(AccountRangeMessage {
+                request_id: 7,
+                accounts: Vec::new(),
+                proof: Vec::new(),
+            }))
+        );
+
+        let request = SnapProtocolMessage::GetByteCodes(GetByteCodesMessage {
+            request_id: 8,
+            hashes: vec![B256::random()],
+            response_bytes: 1024,
+        });
+        assert_eq!(
+            empty_response(&request),
+            Some(SnapProtocolMessage::ByteCodes(ByteCodesMessage {
+                request_id: 8,
+                codes: Vec::new()
+            }))
+        );
+
+        // responses are not answered
+        let response =
+            SnapProtocolMessage::TrieNodes(TrieNodesMessage { request_id: 9, nodes: Vec::new() });
+        assert_eq!(empty_response(&response), None);
+    }
+}
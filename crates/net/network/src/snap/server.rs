@@ -0,0 +1,558 @@
+//! Serves `snap` requests from the database.
+
+use super::{IncomingSnapRequest, SNAP_REQUEST_CHANNEL_CAPACITY};
+use crate::snap::SnapProtocolHandler;
+use futures::StreamExt;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_eth_wire::{
+    AccountData, AccountRangeMessage, ByteCodesMessage, GetAccountRangeMessage,
+    GetByteCodesMessage, GetStorageRangesMessage, GetTrieNodesMessage, StorageData,
+    StorageRangesMessage, TrieNodesMessage,
+};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{stage::StageId, trie::Nibbles, Bytes, PeerId, B256, KECCAK_EMPTY};
+use reth_provider::{DatabaseProviderRO, HeaderProvider, ProviderFactory, StageCheckpointReader};
+use reth_trie::{Proof, StateRootError};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::debug;
+
+// Limits: <https://github.com/ethereum/go-ethereum/blob/v1.13.5/eth/protocols/snap/handler.go#L36-L58>
+
+/// Maximum size of replies to data retrievals.
+const SOFT_RESPONSE_LIMIT: u64 = 2 * 1024 * 1024;
+
+/// Maximum number of bytecodes to serve.
+const MAX_CODE_LOOKUPS: usize = 1024;
+
+/// Maximum number of trie nodes to serve.
+const MAX_TRIE_NODE_LOOKUPS: usize = 1024;
+
+/// Serves `snap` requests from the hashed state and trie tables.
+///
+/// Only the state of the last fully synced block is served, requests for any other state root
+/// are answered with empty responses.
+///
+/// This can be spawned to another task and is supposed to be run as background service.
+#[derive(Debug)]
+#[must_use = "Manager does nothing unless polled."]
+pub struct SnapRequestHandler<DB> {
+    /// Access to the database.
+    factory: ProviderFactory<DB>,
+    /// Incoming requests of the `snap` connections.
+    incoming_requests: ReceiverStream<IncomingSnapRequest>,
+}
+
+// === impl SnapRequestHandler ===
+
+impl<DB> SnapRequestHandler<DB> {
+    /// Create a new instance
+    pub fn new(
+        factory: ProviderFactory<DB>,
+        incoming: mpsc::Receiver<IncomingSnapRequest>,
+    ) -> Self {
+        Self { factory, incoming_requests: ReceiverStream::new(incoming) }
+    }
+
+    /// Creates a new instance and the [`SnapProtocolHandler`] that delegates to it.
+    pub fn with_protocol(factory: ProviderFactory<DB>) -> (Self, SnapProtocolHandler) {
+        let (tx, rx) = mpsc::channel(SNAP_REQUEST_CHANNEL_CAPACITY);
+        (Self::new(factory, rx), SnapProtocolHandler::new(tx))
+    }
+}
+
+impl<DB: Database> SnapRequestHandler<DB> {
+    /// Returns a read-only provider if the state root is served.
+    ///
+    /// The hashed state and trie tables are only consistent with the root of the last fully synced
+    /// block if the stages writing them are not ahead of it, which they are while the pipeline is
+    /// running.
+    fn provider_for_root(&self, root: B256) -> ProviderResult<Option<DatabaseProviderRO<DB>>> {
+        let provider = self.factory.provider()?;
+        let synced = provider.get_stage_checkpoint(StageId::Finish)?.unwrap_or_default();
+        for stage in [StageId::AccountHashing, StageId::StorageHashing, StageId::MerkleExecute] {
+            let checkpoint = provider.get_stage_checkpoint(stage)?.unwrap_or_default();
+            if checkpoint.block_number != synced.block_number {
+                return Ok(None)
+            }
+        }
+        let served = provider
+            .header_by_number(synced.block_number)?
+            .map_or(false, |header| header.state_root == root);
+        Ok(served.then_some(provider))
+    }
+
+    fn account_range(
+        &self,
+        request: &GetAccountRangeMessage,
+    ) -> ProviderResult<(Vec<AccountData>, Vec<Bytes>)> {
+        let Some(provider) = self.provider_for_root(request.root_hash)? else {
+            return Ok(Default::default())
+        };
+        let tx = provider.tx_ref();
+        let proof = Proof::new(tx);
+        let limit = request.response_bytes.min(SOFT_RESPONSE_LIMIT);
+
+        let mut accounts = Vec::new();
+        let mut size = 0;
+        let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+        for entry in cursor.walk(Some(request.starting_hash))? {
+            let (hashed_address, account) = entry?;
+            let storage_root = proof.storage_root(hashed_address).map_err(into_provider_err)?;
+            let account = AccountData::new(hashed_address, account, storage_root);
+            size += (B256::len_bytes() + account.body.len()) as u64;
+            accounts.push(account);
+
+            if hashed_address >= request.limit_hash || size >= limit {
+                break
+            }
+        }
+
+        // prove the origin and the last returned account
+        let mut targets = Vec::from([Nibbles::unpack(request.starting_hash)]);
+        if let Some(last) = accounts.last() {
+            targets.push(Nibbles::unpack(last.hash));
+        }
+        let (_, nodes) = proof.account_multiproof(targets).map_err(into_provider_err)?;
+
+        Ok((accounts, nodes.into_values().collect()))
+    }
+
+    fn storage_ranges(
+        &self,
+        request: &GetStorageRangesMessage,
+    ) -> ProviderResult<(Vec<Vec<StorageData>>, Vec<Bytes>)> {
+        let Some(provider) = self.provider_for_root(request.root_hash)? else {
+            return Ok(Default::default())
+        };
+        let tx = provider.tx_ref();
+        let proof = Proof::new(tx);
+        let limit = request.response_bytes.min(SOFT_RESPONSE_LIMIT);
+
+        let mut slots = Vec::new();
+        let mut proof_nodes = Vec::new();
+        let mut size = 0;
+        let mut cursor = tx.cursor_dup_read::<tables::HashedStorage>()?;
+        for (idx, hashed_address) in request.account_hashes.iter().copied().enumerate() {
+            if size >= limit {
+                break
+            }
+
+            // the origin only applies to the first and the limit only to the last account
+            let origin = (idx == 0)
+                .then(|| hash_from_bytes(&request.starting_hash))
+                .flatten()
+                .unwrap_or_default();
+            let last_hash = (idx + 1 == request.account_hashes.len())
+                .then(|| hash_from_bytes(&request.limit_hash))
+                .flatten()
+                .unwrap_or(B256::repeat_byte(0xff));
+
+            let mut storage = Vec::new();
+            let mut aborted = false;
+            for entry in cursor.walk_dup(Some(hashed_address), Some(origin))? {
+                if size >= limit {
+                    aborted = true;
+                    break
+                }
+                let (_, entry) = entry?;
+                let data: Bytes = alloy_rlp::encode(entry.value).into();
+                size += (B256::len_bytes() + data.len()) as u64;
+                storage.push(StorageData { hash: entry.key, data });
+
+                if entry.key >= last_hash {
+                    break
+                }
+            }
+
+            // prove incomplete storage ranges, which are always the last served range
+            let last = storage.last().map(|slot| slot.hash);
+            slots.push(storage);
+            if !origin.is_zero() || (aborted && last.is_some()) {
+                let mut targets = Vec::from([Nibbles::unpack(origin)]);
+                targets.extend(last.map(Nibbles::unpack));
+                let (_, nodes) =
+                    proof.storage_multiproof(hashed_address, targets).map_err(into_provider_err)?;
+                proof_nodes.extend(nodes.into_values());
+                break
+            }
+        }
+
+        Ok((slots, proof_nodes))
+    }
+
+    fn byte_codes(&self, request: &GetByteCodesMessage) -> ProviderResult<Vec<Bytes>> {
+        let provider = self.factory.provider()?;
+        let tx = provider.tx_ref();
+        let limit = request.response_bytes.min(SOFT_RESPONSE_LIMIT);
+
+        let mut codes = Vec::new();
+        let mut size = 0;
+        for code_hash in request.hashes.iter().take(MAX_CODE_LOOKUPS) {
+            let code = if *code_hash == KECCAK_EMPTY {
+                Bytes::new()
+            } else if let Some(code) = tx.get::<tables::Bytecodes>(*code_hash)? {
+                code.original_bytes()
+            } else {
+                // unknown codes are skipped
+                continue
+            };
+            size += code.len() as u64;
+            codes.push(code);
+
+            if size >= limit {
+                break
+            }
+        }
+
+        Ok(codes)
+    }
+
+    fn trie_nodes(&self, request: &GetTrieNodesMessage) -> ProviderResult<Vec<Bytes>> {
+        let Some(provider) = self.provider_for_root(request.root_hash)? else {
+            return Ok(Default::default())
+        };
+        let tx = provider.tx_ref();
+        let proof = Proof::new(tx);
+        let limit = request.response_bytes.min(SOFT_RESPONSE_LIMIT);
+
+        // resolve all account trie nodes at once
+        let account_paths = request
+            .paths
+            .iter()
+            .filter(|set| set.paths.len() == 1)
+            .filter_map(|set| nibbles_from_compact(&set.paths[0]))
+            .collect::<Vec<_>>();
+        let account_nodes = if account_paths.is_empty() {
+            Default::default()
+        } else {
+            proof.account_multiproof(account_paths).map_err(into_provider_err)?.1
+        };
+
+        let mut nodes = Vec::new();
+        let mut size = 0;
+        'sets: for set in &request.paths {
+            let resolved =
+                match set.paths.as_slice() {
+                    [] => continue,
+                    [path] => Vec::from([nibbles_from_compact(path)
+                        .and_then(|path| account_nodes.get(&path).cloned())]),
+                    [account, paths @ ..] => {
+                        // storage paths are prefixed by the full account hash
+                        let Some(hashed_address) = hash_from_bytes(account) else { break 'sets };
+                        let paths = paths
+                            .iter()
+                            .map(|path| nibbles_from_compact(path.as_ref()))
+                            .collect::<Vec<_>>();
+                        let (_, storage_nodes) = proof
+                            .storage_multiproof(
+                                hashed_address,
+                                paths.iter().flatten().cloned().collect(),
+                            )
+                            .map_err(into_provider_err)?;
+                        paths
+                            .into_iter()
+                            .map(|path| path.and_then(|path| storage_nodes.get(&path).cloned()))
+                            .collect()
+                    }
+                };
+
+            for node in resolved {
+                // stop at the first node that can't be resolved
+                let Some(node) = node else { break 'sets };
+                size += node.len() as u64;
+                nodes.push(node);
+                if size >= limit || nodes.len() >= MAX_TRIE_NODE_LOOKUPS {
+                    break 'sets
+                }
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    fn on_request(&self, incoming: IncomingSnapRequest) {
+        match incoming {
+            IncomingSnapRequest::GetAccountRange { peer_id, request, response } => {
+                let (accounts, proof) = self.account_range(&request).unwrap_or_else(|err| {
+                    on_error(peer_id, err);
+                    Default::default()
+                });
+                let _ = response.send(AccountRangeMessage {
+                    request_id: request.request_id,
+                    accounts,
+                    proof,
+                });
+            }
+            IncomingSnapRequest::GetStorageRanges { peer_id, request, response } => {
+                let (slots, proof) = self.storage_ranges(&request).unwrap_or_else(|err| {
+                    on_error(peer_id, err);
+                    Default::default()
+                });
+                let _ = response.send(StorageRangesMessage {
+                    request_id: request.request_id,
+                    slots,
+                    proof,
+                });
+            }
+            IncomingSnapRequest::GetByteCodes { peer_id, request, response } => {
+                let codes = self.byte_codes(&request).unwrap_or_else(|err| {
+                    on_error(peer_id, err);
+                    Default::default()
+                });
+                let _ = response.send(ByteCodesMessage { request_id: request.request_id, codes });
+            }
+            IncomingSnapRequest::GetTrieNodes { peer_id, request, response } => {
+                let nodes = self.trie_nodes(&request).unwrap_or_else(|err| {
+                    on_error(peer_id, err);
+                    Default::default()
+                });
+                let _ = response.send(TrieNodesMessage { request_id: request.request_id, nodes });
+            }
+        }
+    }
+}
+
+impl<DB> Future for SnapRequestHandler<DB>
+where
+    DB: Database + Unpin,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match this.incoming_requests.poll_next_unpin(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Ready(Some(incoming)) => this.on_request(incoming),
+            }
+        }
+    }
+}
+
+fn on_error(peer_id: PeerId, err: ProviderError) {
+    debug!(target: "net::snap", ?peer_id, %err, "Failed to serve snap request");
+}
+
+fn into_provider_err(err: impl Into<StateRootError>) -> ProviderError {
+    let err: StateRootError = err.into();
+    ProviderError::Database(err.into())
+}
+
+/// Converts a hash of the request, empty values are treated as absent.
+fn hash_from_bytes(bytes: &[u8]) -> Option<B256> {
+    (bytes.len() == B256::len_bytes()).then(|| B256::from_slice(bytes))
+}
+
+/// Decodes a compact (hex-prefix) encoded trie path.
+fn nibbles_from_compact(compact: &[u8]) -> Option<Nibbles> {
+    let Some((&first, rest)) = compact.split_first() else {
+        // the empty path refers to the root node
+        return Some(Nibbles::default())
+    };
+    let flag = first >> 4;
+    if flag > 3 {
+        return None
+    }
+
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    // odd length paths store the first nibble in the flag byte
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Some(Nibbles::from_nibbles_unchecked(nibbles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::transaction::DbTxMut;
+    use reth_eth_wire::TriePathSet;
+    use reth_primitives::{
+        keccak256, stage::StageCheckpoint, Account, Bytecode, Header, StorageEntry, U256,
+    };
+    use reth_provider::{test_utils::create_test_provider_factory, StageCheckpointWriter};
+    use reth_trie::StateRoot;
+
+    /// Inserts a state with one contract with storage and returns the state root.
+    fn insert_state<DB: Database>(factory: &ProviderFactory<DB>) -> (B256, B256) {
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let code_hash = keccak256(code.original_bytes());
+        tx.put::<tables::Bytecodes>(code_hash, code).unwrap();
+
+        let contract = keccak256(B256::with_last_byte(1));
+        for idx in 2..10u8 {
+            let account =
+                Account { nonce: idx as u64, balance: U256::from(idx), bytecode_hash: None };
+            tx.put::<tables::HashedAccount>(keccak256(B256::with_last_byte(idx)), account).unwrap();
+        }
+        let account = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) };
+        tx.put::<tables::HashedAccount>(contract, account).unwrap();
+        for slot in 1..5u8 {
+            tx.put::<tables::HashedStorage>(
+                contract,
+                StorageEntry {
+                    key: keccak256(B256::with_last_byte(slot)),
+                    value: U256::from(slot),
+                },
+            )
+            .unwrap();
+        }
+
+        let (root, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+
+        tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() }).unwrap();
+        for stage in [
+            StageId::AccountHashing,
+            StageId::StorageHashing,
+            StageId::MerkleExecute,
+            StageId::Finish,
+        ] {
+            provider.save_stage_checkpoint(stage, StageCheckpoint::new(0)).unwrap();
+        }
+        provider.commit().unwrap();
+
+        (root, contract)
+    }
+
+    #[test]
+    fn compact_paths() {
+        assert_eq!(nibbles_from_compact(&[]), Some(Nibbles::default()));
+        assert_eq!(nibbles_from_compact(&[0x00]), Some(Nibbles::default()));
+        assert_eq!(nibbles_from_compact(&[0x11, 0x23]), Some(Nibbles::from_nibbles([1, 2, 3])));
+        assert_eq!(nibbles_from_compact(&[0x00, 0x12]), Some(Nibbles::from_nibbles([1, 2])));
+        assert_eq!(nibbles_from_compact(&[0x3f]), Some(Nibbles::from_nibbles([0xf])));
+        assert_eq!(nibbles_from_compact(&[0x40]), None);
+    }
+
+    #[test]
+    fn serve_account_range() {
+        let factory = create_test_provider_factory();
+        let (root, _) = insert_state(&factory);
+        let handler = SnapRequestHandler::with_protocol(factory).0;
+
+        let request = GetAccountRangeMessage {
+            request_id: 1,
+            root_hash: root,
+            starting_hash: B256::ZERO,
+            limit_hash: B256::repeat_byte(0xff),
+            response_bytes: SOFT_RESPONSE_LIMIT,
+        };
+        let (accounts, proof) = handler.account_range(&request).unwrap();
+        assert_eq!(accounts.len(), 9);
+        assert!(accounts.windows(2).all(|pair| pair[0].hash < pair[1].hash));
+        assert_eq!(keccak256(&proof[0]), root);
+
+        // the response is capped by the limit hash
+        let request = GetAccountRangeMessage { limit_hash: accounts[3].hash, ..request };
+        let (limited, _) = handler.account_range(&request).unwrap();
+        assert_eq!(limited, accounts[..4]);
+
+        // unknown roots are not served
+        let request = GetAccountRangeMessage { root_hash: B256::random(), ..request };
+        assert_eq!(handler.account_range(&request).unwrap(), Default::default());
+    }
+
+    #[test]
+    fn no_root_served_while_pipeline_runs() {
+        let factory = create_test_provider_factory();
+        let (root, _) = insert_state(&factory);
+
+        // the merkle stage committed the trie of a later block before the pipeline finished
+        let provider = factory.provider_rw().unwrap();
+        provider.save_stage_checkpoint(StageId::MerkleExecute, StageCheckpoint::new(1)).unwrap();
+        provider.commit().unwrap();
+        let handler = SnapRequestHandler::with_protocol(factory).0;
+
+        let request = GetAccountRangeMessage {
+            request_id: 1,
+            root_hash: root,
+            starting_hash: B256::ZERO,
+            limit_hash: B256::repeat_byte(0xff),
+            response_bytes: SOFT_RESPONSE_LIMIT,
+        };
+        assert_eq!(handler.account_range(&request).unwrap(), Default::default());
+    }
+
+    #[test]
+    fn serve_storage_ranges_and_codes() {
+        let factory = create_test_provider_factory();
+        let (root, contract) = insert_state(&factory);
+        let handler = SnapRequestHandler::with_protocol(factory).0;
+
+        let request = GetStorageRangesMessage {
+            request_id: 1,
+            root_hash: root,
+            account_hashes: vec![contract],
+            starting_hash: Bytes::new(),
+            limit_hash: Bytes::new(),
+            response_bytes: SOFT_RESPONSE_LIMIT,
+        };
+        let (slots, proof) = handler.storage_ranges(&request).unwrap();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].len(), 4);
+        // complete ranges are not proven
+        assert!(proof.is_empty());
+
+        // ranges with an origin are proven
+        let request =
+            GetStorageRangesMessage { starting_hash: slots[0][1].hash.to_vec().into(), ..request };
+        let (partial, proof) = handler.storage_ranges(&request).unwrap();
+        assert_eq!(partial[0], slots[0][1..]);
+        assert!(!proof.is_empty());
+
+        let code_hash = keccak256([0x60, 0x00]);
+        let request = GetByteCodesMessage {
+            request_id: 1,
+            hashes: vec![code_hash, B256::random(), KECCAK_EMPTY],
+            response_bytes: SOFT_RESPONSE_LIMIT,
+        };
+        let codes = handler.byte_codes(&request).unwrap();
+        assert_eq!(codes, vec![Bytes::from_static(&[0x60, 0x00]), Bytes::new()]);
+    }
+
+    #[test]
+    fn serve_trie_nodes() {
+        let factory = create_test_provider_factory();
+        let (root, contract) = insert_state(&factory);
+        let handler = SnapRequestHandler::with_protocol(factory).0;
+
+        let request = GetTrieNodesMessage {
+            request_id: 1,
+            root_hash: root,
+            paths: vec![
+                TriePathSet { paths: vec![Bytes::from_static(&[0x00])] },
+                TriePathSet { paths: vec![contract.to_vec().into(), Bytes::from_static(&[0x00])] },
+            ],
+            response_bytes: SOFT_RESPONSE_LIMIT,
+        };
+        let nodes = handler.trie_nodes(&request).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(keccak256(&nodes[0]), root);
+
+        let provider = handler.factory.provider().unwrap();
+        let (storage_root, _) =
+            Proof::new(provider.tx_ref()).storage_multiproof(contract, Vec::new()).unwrap();
+        assert_eq!(keccak256(&nodes[1]), storage_root);
+    }
+}
@@ -177,6 +177,9 @@ where
             SessionEvent::ProtocolBreach { peer_id } => {
                 Some(SwarmEvent::ProtocolBreach { peer_id })
             }
+            SessionEvent::BandwidthExceeded { peer_id } => {
+                Some(SwarmEvent::BandwidthExceeded { peer_id })
+            }
         }
     }
 
@@ -194,6 +197,11 @@ where
                 if self.is_shutting_down() {
                     return None;
                 }
+                // in a permissioned network with the ip filter enabled, only accept connections
+                // from addresses of allow-listed nodes
+                if !self.sessions.permissions().check_incoming(remote_addr.ip()) {
+                    return None;
+                }
                 // ensure we can handle an incoming connection from this address
                 if let Err(err) =
                     self.state_mut().peers_mut().on_incoming_pending_session(remote_addr.ip())
@@ -361,6 +369,11 @@ pub(crate) enum SwarmEvent {
         /// Identifier of the remote peer.
         peer_id: PeerId,
     },
+    /// Remote peer exceeded its bandwidth limit
+    BandwidthExceeded {
+        /// Identifier of the remote peer.
+        peer_id: PeerId,
+    },
     /// The underlying tcp listener closed.
     TcpListenerClosed {
         /// Address of the closed listener.
@@ -313,9 +313,14 @@ where
         trace!(target: "net::tx", num_hashes=?hashes.len(), "Start propagating transactions");
 
         // This fetches all transaction from the pool, including the blob transactions, which are
-        // only ever sent as hashes.
+        // only ever sent as hashes. Private transactions are never propagated.
         let propagated = self.propagate_transactions(
-            self.pool.get_all(hashes).into_iter().map(PropagateTransaction::new).collect(),
+            self.pool
+                .get_all(hashes)
+                .into_iter()
+                .filter(|tx| tx.propagate)
+                .map(PropagateTransaction::new)
+                .collect(),
         );
 
         // notify pool so events get fired
@@ -430,7 +435,7 @@ where
             .pool
             .get_all(txs)
             .into_iter()
-            .filter(|tx| !tx.transaction.is_eip4844())
+            .filter(|tx| tx.propagate && !tx.transaction.is_eip4844())
             .map(PropagateTransaction::new);
 
         // Iterate through the transactions to propagate and fill the hashes and full transaction
@@ -472,8 +477,13 @@ where
                 return;
             };
 
-            let to_propagate: Vec<PropagateTransaction> =
-                self.pool.get_all(hashes).into_iter().map(PropagateTransaction::new).collect();
+            let to_propagate: Vec<PropagateTransaction> = self
+                .pool
+                .get_all(hashes)
+                .into_iter()
+                .filter(|tx| tx.propagate)
+                .map(PropagateTransaction::new)
+                .collect();
 
             let mut propagated = PropagatedTransactions::default();
 
@@ -12,7 +12,8 @@ use reth_interfaces::{
 use reth_net_common::ban_list::BanList;
 use reth_network::{
     test_utils::{enr_to_peer_id, NetworkEventStream, PeerConfig, Testnet, GETH_TIMEOUT},
-    NetworkConfigBuilder, NetworkEvent, NetworkEvents, NetworkManager, PeersConfig,
+    NetworkConfigBuilder, NetworkEvent, NetworkEvents, NetworkManager, NetworkPermissions,
+    PeersConfig,
 };
 use reth_network_api::{NetworkInfo, Peers, PeersInfo};
 use reth_primitives::{mainnet_nodes, HeadersDirection, NodeRecord, PeerId};
@@ -583,3 +584,42 @@ async fn test_disconnect_incoming_when_exceeded_incoming_connections() {
 
     net_handle.terminate().await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_permissioned_network() {
+    reth_tracing::init_test_tracing();
+    let net = Testnet::create(2).await;
+    let mut handles = net.handles();
+    let allowed = handles.next().unwrap();
+    let denied = handles.next().unwrap();
+    drop(handles);
+
+    let secret_key = SecretKey::new(&mut rand::thread_rng());
+    let permissions = NetworkPermissions::allowlist([allowed.local_node_record()]);
+    let config = NetworkConfigBuilder::new(secret_key)
+        .listener_port(0)
+        .disable_discovery()
+        .permissions(permissions)
+        .build(NoopProvider::default());
+    let network = NetworkManager::new(config).await.unwrap();
+    let handle = network.handle().clone();
+
+    allowed.add_peer(*handle.peer_id(), handle.local_addr());
+    denied.add_peer(*handle.peer_id(), handle.local_addr());
+
+    tokio::task::spawn(network);
+    let net_handle = net.spawn();
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let peers = handle.get_all_peers().await.unwrap();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].remote_id, *allowed.peer_id());
+
+    // removing the node from the allowlist disconnects it
+    assert!(handle.remove_allowed_peer(*allowed.peer_id()));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(handle.num_connected_peers(), 0);
+
+    net_handle.terminate().await;
+}
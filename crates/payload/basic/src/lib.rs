@@ -15,8 +15,9 @@ use futures_core::ready;
 use futures_util::FutureExt;
 use reth_interfaces::RethResult;
 use reth_payload_builder::{
-    database::CachedReads, error::PayloadBuilderError, BuiltPayload, KeepPayloadJobAlive,
-    PayloadBuilderAttributes, PayloadId, PayloadJob, PayloadJobGenerator,
+    database::CachedReads, error::PayloadBuilderError, BuiltPayload, Bundle, BundlePool,
+    BundleTransaction, KeepPayloadJobAlive, PayloadBuilderAttributes, PayloadId, PayloadJob,
+    PayloadJobGenerator,
 };
 use reth_primitives::{
     bytes::BytesMut,
@@ -28,7 +29,7 @@ use reth_primitives::{
     proofs,
     revm::{compat::into_reth_log, env::tx_env_with_recovered},
     Block, BlockNumberOrTag, Bytes, ChainSpec, Header, IntoRecoveredTransaction, Receipt, Receipts,
-    SealedBlock, Withdrawal, B256, EMPTY_OMMER_ROOT_HASH, U256,
+    SealedBlock, TransactionSignedEcRecovered, Withdrawal, B256, EMPTY_OMMER_ROOT_HASH, U256,
 };
 use reth_provider::{
     BlockReaderIdExt, BlockSource, BundleStateWithReceipts, ProviderError, StateProviderFactory,
@@ -40,8 +41,11 @@ use reth_revm::{
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::TransactionPool;
 use revm::{
-    db::states::bundle_state::BundleRetention,
-    primitives::{BlockEnv, CfgEnv, EVMError, Env, InvalidTransaction, ResultAndState},
+    db::{states::bundle_state::BundleRetention, CacheDB, DatabaseRef},
+    primitives::{
+        BlockEnv, CfgEnv, EVMError, Env, ExecutionResult, InvalidTransaction, ResultAndState,
+        State as EvmState,
+    },
     Database, DatabaseCommit, State,
 };
 use std::{
@@ -88,6 +92,8 @@ pub struct BasicPayloadJobGenerator<Client, Pool, Tasks, Builder = EthereumPaylo
     ///
     /// See [PayloadBuilder]
     builder: Builder,
+    /// The bundles to include at the top of built payloads, if any.
+    bundle_pool: Option<BundlePool>,
 }
 
 // === impl BasicPayloadJobGenerator ===
@@ -130,9 +136,16 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
             config,
             chain_spec,
             builder,
+            bundle_pool: None,
         }
     }
 
+    /// Configures the pool of bundles the jobs try to include at the top of their payloads.
+    pub fn with_bundle_pool(mut self, bundle_pool: BundlePool) -> Self {
+        self.bundle_pool = Some(bundle_pool);
+        self
+    }
+
     /// Returns the maximum duration a job should be allowed to run.
     ///
     /// This adheres to the following specification:
@@ -215,6 +228,7 @@ where
             payload_task_guard: self.payload_task_guard.clone(),
             metrics: Default::default(),
             builder: self.builder.clone(),
+            bundle_pool: self.bundle_pool.clone(),
         })
     }
 }
@@ -352,6 +366,8 @@ pub struct BasicPayloadJob<Client, Pool, Tasks, Builder> {
     ///
     /// See [PayloadBuilder]
     builder: Builder,
+    /// The bundles to include at the top of the payload, if any.
+    bundle_pool: Option<BundlePool>,
 }
 
 impl<Client, Pool, Tasks, Builder> Future for BasicPayloadJob<Client, Pool, Tasks, Builder>
@@ -388,6 +404,16 @@ where
                 this.metrics.inc_initiated_payload_builds();
                 let cached_reads = this.cached_reads.take().unwrap_or_default();
                 let builder = this.builder.clone();
+                let bundles = this
+                    .bundle_pool
+                    .as_ref()
+                    .map(|bundle_pool| {
+                        bundle_pool.bundles_at(
+                            payload_config.parent_block.number + 1,
+                            payload_config.attributes.timestamp,
+                        )
+                    })
+                    .unwrap_or_default();
                 this.executor.spawn_blocking(Box::pin(async move {
                     // acquire the permit for executing the task
                     let _permit = guard.0.acquire().await;
@@ -398,6 +424,7 @@ where
                         config: payload_config,
                         cancel,
                         best_payload,
+                        bundles,
                     };
                     let result = builder.try_build(args);
                     let _ = tx.send(result);
@@ -505,6 +532,7 @@ where
                         config: self.config.clone(),
                         cancel: Cancelled::default(),
                         best_payload: None,
+                        bundles: Vec::new(),
                     };
                     if let Ok(BuildOutcome::Better { payload, cached_reads }) =
                         self.builder.try_build(args)
@@ -720,7 +748,8 @@ pub enum BuildOutcome {
 ///
 /// This struct encapsulates the essential components and configuration required for the payload
 /// building process. It holds references to the Ethereum client, transaction pool, cached reads,
-/// payload configuration, cancellation status, and the best payload achieved so far.
+/// payload configuration, cancellation status, the best payload achieved so far, and the bundles
+/// to include at the top of the payload.
 #[derive(Debug)]
 pub struct BuildArguments<Pool, Client> {
     client: Client,
@@ -729,6 +758,7 @@ pub struct BuildArguments<Pool, Client> {
     config: PayloadConfig,
     cancel: Cancelled,
     best_payload: Option<Arc<BuiltPayload>>,
+    bundles: Vec<Bundle>,
 }
 
 impl<Pool, Client> BuildArguments<Pool, Client> {
@@ -741,7 +771,13 @@ impl<Pool, Client> BuildArguments<Pool, Client> {
         cancel: Cancelled,
         best_payload: Option<Arc<BuiltPayload>>,
     ) -> Self {
-        Self { client, pool, cached_reads, config, cancel, best_payload }
+        Self { client, pool, cached_reads, config, cancel, best_payload, bundles: Vec::new() }
+    }
+
+    /// Sets the bundles to include at the top of the payload, in order of priority.
+    pub fn with_bundles(mut self, bundles: Vec<Bundle>) -> Self {
+        self.bundles = bundles;
+        self
     }
 }
 
@@ -799,7 +835,8 @@ where
     Client: StateProviderFactory,
     Pool: TransactionPool,
 {
-    let BuildArguments { client, pool, mut cached_reads, config, cancel, best_payload } = args;
+    let BuildArguments { client, pool, mut cached_reads, config, cancel, best_payload, bundles } =
+        args;
 
     let state_provider = client.state_by_block_hash(config.parent_block.hash)?;
     let state = StateProviderDatabase::new(&state_provider);
@@ -839,6 +876,76 @@ where
     )?;
 
     let mut receipts = Vec::new();
+
+    // include the bundles at the top of the block. Every bundle is first executed on top of the
+    // bundles included before it, and only applied to the block if it can be included as a whole
+    if !bundles.is_empty() {
+        let mut bundle_db = CacheDB::new(state.clone());
+        pre_block_beacon_root_contract_call(
+            &mut bundle_db,
+            &chain_spec,
+            block_number,
+            &initialized_cfg,
+            &initialized_block_env,
+            &attributes,
+        )?;
+
+        for bundle in &bundles {
+            // check if the job was cancelled, if so we can exit early
+            if cancel.is_cancelled() {
+                return Ok(BuildOutcome::Cancelled)
+            }
+
+            let bundle_gas_limit =
+                bundle.transactions.iter().map(|tx| tx.transaction.gas_limit()).sum::<u64>();
+            if cumulative_gas_used + bundle_gas_limit > block_gas_limit {
+                trace!(target: "payload_builder", bundle=?bundle.hash, "skipping bundle that does not fit into the block");
+                continue
+            }
+
+            // the sidecars of blob transactions are only available for transactions in the pool
+            if bundle.transactions.iter().any(|tx| tx.transaction.is_eip4844()) {
+                trace!(target: "payload_builder", bundle=?bundle.hash, "skipping bundle with blob transactions");
+                continue
+            }
+
+            let Some((next_bundle_db, executed)) =
+                execute_bundle(&bundle_db, bundle, &initialized_cfg, &initialized_block_env)?
+            else {
+                continue
+            };
+            bundle_db = next_bundle_db;
+
+            for ExecutedBundleTransaction { transaction, result, state } in executed {
+                // commit changes
+                db.commit(state);
+
+                let gas_used = result.gas_used();
+                cumulative_gas_used += gas_used;
+
+                receipts.push(Some(Receipt {
+                    tx_type: transaction.tx_type(),
+                    success: result.is_success(),
+                    cumulative_gas_used,
+                    logs: result.logs().into_iter().map(into_reth_log).collect(),
+                    #[cfg(feature = "optimism")]
+                    deposit_nonce: None,
+                    #[cfg(feature = "optimism")]
+                    deposit_receipt_version: None,
+                }));
+
+                let miner_fee = transaction
+                    .effective_tip_per_gas(Some(base_fee))
+                    .expect("fee is always valid; execution succeeded");
+                total_fees += U256::from(miner_fee) * U256::from(gas_used);
+
+                executed_txs.push(transaction.into_signed());
+            }
+        }
+    }
+
+    // pool transactions that were already included as part of a bundle are skipped because their
+    // nonce is too low
     while let Some(pool_tx) = best_txs.next() {
         // ensure we still have capacity for this transaction
         if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
@@ -1232,6 +1339,82 @@ where
     .map_err(|err| PayloadBuilderError::Internal(err.into()))
 }
 
+/// A bundle transaction that was executed on top of the bundles included before it.
+struct ExecutedBundleTransaction {
+    /// The executed transaction.
+    transaction: TransactionSignedEcRecovered,
+    /// The outcome of the execution.
+    result: ExecutionResult,
+    /// The state changes of the execution.
+    state: EvmState,
+}
+
+/// Executes the transactions of the bundle in order on top of `db`.
+///
+/// Returns the state with the bundle applied together with the executed transactions, or `None`
+/// if the bundle can't be included: because one of its transactions is invalid, a transaction
+/// that is not allowed to revert reverts, or the bundle does not pay the coinbase.
+fn execute_bundle<DB>(
+    db: &CacheDB<DB>,
+    bundle: &Bundle,
+    initialized_cfg: &CfgEnv,
+    initialized_block_env: &BlockEnv,
+) -> Result<Option<(CacheDB<DB>, Vec<ExecutedBundleTransaction>)>, PayloadBuilderError>
+where
+    DB: DatabaseRef<Error = ProviderError> + Clone,
+{
+    let coinbase = initialized_block_env.coinbase;
+    let balance_before =
+        DatabaseRef::basic_ref(db, coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+
+    let env = Env {
+        cfg: initialized_cfg.clone(),
+        block: initialized_block_env.clone(),
+        ..Default::default()
+    };
+    let mut evm = revm::EVM::with_env(env);
+    evm.database(db.clone());
+
+    let mut executed = Vec::with_capacity(bundle.transactions.len());
+    for BundleTransaction { transaction, can_revert } in &bundle.transactions {
+        evm.env.tx = tx_env_with_recovered(transaction);
+        let ResultAndState { result, state } = match evm.transact() {
+            Ok(res) => res,
+            Err(EVMError::Transaction(err)) => {
+                trace!(target: "payload_builder", ?err, bundle=?bundle.hash, tx=?transaction.hash, "skipping bundle with invalid transaction");
+                return Ok(None)
+            }
+            Err(err) => {
+                // this is an error that we should treat as fatal for this attempt
+                return Err(PayloadBuilderError::EvmExecutionError(err))
+            }
+        };
+
+        if !result.is_success() && !can_revert {
+            trace!(target: "payload_builder", bundle=?bundle.hash, tx=?transaction.hash, "skipping bundle with reverted transaction");
+            return Ok(None)
+        }
+
+        // need to apply the state changes of this transaction before executing the next one
+        evm.db.as_mut().expect("is set").commit(state.clone());
+        executed.push(ExecutedBundleTransaction {
+            transaction: transaction.clone(),
+            result,
+            state,
+        });
+    }
+
+    let db = evm.db.take().expect("is set");
+    let balance_after =
+        DatabaseRef::basic_ref(&db, coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+    if balance_after <= balance_before {
+        trace!(target: "payload_builder", bundle=?bundle.hash, "skipping unprofitable bundle");
+        return Ok(None)
+    }
+
+    Ok(Some((db, executed)))
+}
+
 /// Checks if the new payload is better than the current best.
 ///
 /// This compares the total fees of the blocks, higher is better.
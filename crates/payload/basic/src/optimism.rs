@@ -20,7 +20,9 @@ where
     Client: StateProviderFactory,
     Pool: TransactionPool,
 {
-    let BuildArguments { client, pool, mut cached_reads, config, cancel, best_payload } = args;
+    // bundles are not supported on optimism, transactions are ordered by the sequencer
+    let BuildArguments { client, pool, mut cached_reads, config, cancel, best_payload, bundles: _ } =
+        args;
 
     let state_provider = client.state_by_block_hash(config.parent_block.hash)?;
     let state = StateProviderDatabase::new(&state_provider);
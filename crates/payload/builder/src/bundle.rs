@@ -0,0 +1,342 @@
+//! A local pool of transaction bundles that payload builders can draw from.
+
+use crate::error::BundlePoolError;
+use reth_primitives::{Address, TransactionSignedEcRecovered, B256};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, RwLock},
+};
+
+/// The default maximum number of bundles a [BundlePool] holds.
+pub const DEFAULT_MAX_BUNDLES: usize = 10_000;
+
+/// A transaction of a [Bundle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleTransaction {
+    /// The signed transaction with its recovered signer.
+    pub transaction: TransactionSignedEcRecovered,
+    /// Whether the transaction is allowed to revert without invalidating the bundle.
+    pub can_revert: bool,
+}
+
+/// A bundle of transactions that must be included in a block in the given order, or not at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    /// The hash that identifies the bundle.
+    pub hash: B256,
+    /// The transactions of the bundle, in execution order.
+    pub transactions: Vec<BundleTransaction>,
+    /// The first block the bundle is valid for.
+    pub block_number: u64,
+    /// The last block the bundle is valid for.
+    pub max_block_number: u64,
+    /// The earliest block timestamp the bundle is valid for.
+    pub min_timestamp: Option<u64>,
+    /// The latest block timestamp the bundle is valid for.
+    pub max_timestamp: Option<u64>,
+    /// The identifier that can be used to replace or cancel the bundle.
+    pub replacement_uuid: Option<String>,
+    /// The address that signed the submission of the bundle, if it was signed.
+    ///
+    /// A bundle can only be replaced by a bundle with the same signer.
+    pub signer: Option<Address>,
+}
+
+impl Bundle {
+    /// Returns `true` if the bundle can be included in the block with the given number.
+    pub fn is_valid_for_block(&self, block_number: u64) -> bool {
+        (self.block_number..=self.max_block_number).contains(&block_number)
+    }
+
+    /// Returns `true` if the bundle can be included in the block with the given number and
+    /// timestamp.
+    pub fn is_valid_at(&self, block_number: u64, timestamp: u64) -> bool {
+        self.is_valid_for_block(block_number) &&
+            self.min_timestamp.map_or(true, |min| timestamp >= min) &&
+            self.max_timestamp.map_or(true, |max| timestamp <= max)
+    }
+}
+
+/// A thread-safe pool of [Bundle]s, keyed by their hash and indexed by the blocks they target.
+///
+/// The pool is cheap to clone: all clones share the same bundles, so the RPC handlers that accept
+/// bundles and the payload builder that includes them can each hold their own handle.
+#[derive(Debug, Clone)]
+pub struct BundlePool {
+    inner: Arc<RwLock<BundlePoolInner>>,
+    /// The maximum number of bundles in the pool.
+    max_bundles: usize,
+}
+
+impl BundlePool {
+    /// Creates an empty [BundlePool] that holds at most [DEFAULT_MAX_BUNDLES] bundles.
+    pub fn new() -> Self {
+        Self::with_max_bundles(DEFAULT_MAX_BUNDLES)
+    }
+
+    /// Creates an empty [BundlePool] that holds at most `max_bundles` bundles.
+    pub fn with_max_bundles(max_bundles: usize) -> Self {
+        Self { inner: Default::default(), max_bundles }
+    }
+
+    /// Inserts the bundle into the pool, returning the bundle it replaced, if any.
+    ///
+    /// A bundle replaces the bundle with the same hash, or the bundle that was submitted with the
+    /// same replacement uuid, if both have the same signer.
+    ///
+    /// Returns an error if the bundle would replace a bundle of another signer, or if it would
+    /// exceed the maximum number of bundles in the pool.
+    pub fn insert(&self, bundle: Bundle) -> Result<Option<Bundle>, BundlePoolError> {
+        let mut inner = self.inner.write().expect("not poisoned");
+        let replaced_hash = bundle
+            .replacement_uuid
+            .as_ref()
+            .and_then(|uuid| inner.by_uuid.get(uuid).copied())
+            .filter(|hash| *hash != bundle.hash);
+        for hash in replaced_hash.iter().chain(Some(&bundle.hash)) {
+            if let Some(existing) = inner.bundles.get(hash) {
+                if existing.signer != bundle.signer {
+                    return Err(BundlePoolError::SignerMismatch {
+                        hash: *hash,
+                        signer: existing.signer,
+                    })
+                }
+            }
+        }
+        if replaced_hash.is_none() &&
+            !inner.bundles.contains_key(&bundle.hash) &&
+            inner.bundles.len() >= self.max_bundles
+        {
+            return Err(BundlePoolError::PoolFull(self.max_bundles))
+        }
+
+        let replaced = replaced_hash.and_then(|hash| inner.remove(&hash));
+        Ok(inner.insert(bundle).or(replaced))
+    }
+
+    /// Removes the bundle with the given hash from the pool.
+    pub fn remove(&self, hash: &B256) -> Option<Bundle> {
+        self.inner.write().expect("not poisoned").remove(hash)
+    }
+
+    /// Removes the bundle that was submitted with the given replacement uuid from the pool.
+    pub fn remove_by_uuid(&self, uuid: &str) -> Option<Bundle> {
+        let mut inner = self.inner.write().expect("not poisoned");
+        let hash = inner.by_uuid.get(uuid).copied()?;
+        inner.remove(&hash)
+    }
+
+    /// Returns the bundle with the given hash, if it's in the pool.
+    pub fn get(&self, hash: &B256) -> Option<Bundle> {
+        self.inner.read().expect("not poisoned").bundles.get(hash).cloned()
+    }
+
+    /// Returns the bundle that was submitted with the given replacement uuid, if it's in the pool.
+    pub fn get_by_uuid(&self, uuid: &str) -> Option<Bundle> {
+        let inner = self.inner.read().expect("not poisoned");
+        inner.by_uuid.get(uuid).and_then(|hash| inner.bundles.get(hash)).cloned()
+    }
+
+    /// Returns the number of bundles in the pool.
+    pub fn len(&self) -> usize {
+        self.inner.read().expect("not poisoned").bundles.len()
+    }
+
+    /// Returns `true` if the pool contains no bundles.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns all bundles that can be included in the block with the given number, ordered by
+    /// the first block they are valid for.
+    pub fn bundles_for_block(&self, block_number: u64) -> Vec<Bundle> {
+        let inner = self.inner.read().expect("not poisoned");
+        inner
+            .by_block
+            .range(..=block_number)
+            .flat_map(|(_, hashes)| hashes.iter().filter_map(|hash| inner.bundles.get(hash)))
+            .filter(|bundle| bundle.is_valid_for_block(block_number))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns all bundles that can be included in the block with the given number and
+    /// timestamp, ordered by the first block they are valid for.
+    pub fn bundles_at(&self, block_number: u64, timestamp: u64) -> Vec<Bundle> {
+        let mut bundles = self.bundles_for_block(block_number);
+        bundles.retain(|bundle| bundle.is_valid_at(block_number, timestamp));
+        bundles
+    }
+
+    /// Removes all bundles that can't be included in the block with the given number or any later
+    /// block.
+    ///
+    /// Returns the number of removed bundles.
+    pub fn remove_expired(&self, block_number: u64) -> usize {
+        let mut inner = self.inner.write().expect("not poisoned");
+        let expired = inner
+            .bundles
+            .values()
+            .filter(|bundle| bundle.max_block_number < block_number)
+            .map(|bundle| bundle.hash)
+            .collect::<Vec<_>>();
+        for hash in &expired {
+            inner.remove(hash);
+        }
+        expired.len()
+    }
+}
+
+impl Default for BundlePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The bundles of a [BundlePool] and the indices over them.
+#[derive(Debug, Default)]
+struct BundlePoolInner {
+    /// All bundles, keyed by their hash.
+    bundles: HashMap<B256, Bundle>,
+    /// The hashes of the bundles, keyed by the first block they are valid for.
+    by_block: BTreeMap<u64, BTreeSet<B256>>,
+    /// The hashes of the bundles that were submitted with a replacement uuid.
+    by_uuid: HashMap<String, B256>,
+}
+
+impl BundlePoolInner {
+    fn insert(&mut self, bundle: Bundle) -> Option<Bundle> {
+        let replaced = self.remove(&bundle.hash);
+        self.by_block.entry(bundle.block_number).or_default().insert(bundle.hash);
+        if let Some(uuid) = &bundle.replacement_uuid {
+            self.by_uuid.insert(uuid.clone(), bundle.hash);
+        }
+        self.bundles.insert(bundle.hash, bundle);
+        replaced
+    }
+
+    fn remove(&mut self, hash: &B256) -> Option<Bundle> {
+        let bundle = self.bundles.remove(hash)?;
+        if let Some(hashes) = self.by_block.get_mut(&bundle.block_number) {
+            hashes.remove(hash);
+            if hashes.is_empty() {
+                self.by_block.remove(&bundle.block_number);
+            }
+        }
+        if let Some(uuid) = &bundle.replacement_uuid {
+            if self.by_uuid.get(uuid) == Some(hash) {
+                self.by_uuid.remove(uuid);
+            }
+        }
+        Some(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(hash: u8, block_number: u64, max_block_number: u64) -> Bundle {
+        Bundle {
+            hash: B256::with_last_byte(hash),
+            transactions: Vec::new(),
+            block_number,
+            max_block_number,
+            min_timestamp: None,
+            max_timestamp: None,
+            replacement_uuid: None,
+            signer: None,
+        }
+    }
+
+    #[test]
+    fn bundles_for_target_block() {
+        let pool = BundlePool::new();
+        pool.insert(bundle(1, 10, 10)).unwrap();
+        pool.insert(bundle(2, 8, 12)).unwrap();
+        pool.insert(bundle(3, 11, 11)).unwrap();
+
+        let hashes = |bundles: Vec<Bundle>| bundles.into_iter().map(|b| b.hash).collect::<Vec<_>>();
+        assert_eq!(
+            hashes(pool.bundles_for_block(10)),
+            vec![bundle(2, 8, 12).hash, bundle(1, 10, 10).hash]
+        );
+        assert_eq!(hashes(pool.bundles_for_block(13)), Vec::<B256>::new());
+
+        assert_eq!(pool.remove_expired(11), 1);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.bundles_for_block(10).iter().all(|b| b.hash != bundle(1, 10, 10).hash));
+    }
+
+    #[test]
+    fn bundles_within_timestamps() {
+        let pool = BundlePool::new();
+        let mut b = bundle(1, 10, 10);
+        b.min_timestamp = Some(100);
+        b.max_timestamp = Some(200);
+        pool.insert(b).unwrap();
+
+        assert!(pool.bundles_at(10, 99).is_empty());
+        assert_eq!(pool.bundles_at(10, 100).len(), 1);
+        assert_eq!(pool.bundles_at(10, 200).len(), 1);
+        assert!(pool.bundles_at(10, 201).is_empty());
+    }
+
+    #[test]
+    fn replace_and_cancel_by_uuid() {
+        let pool = BundlePool::new();
+        let mut first = bundle(1, 10, 10);
+        first.replacement_uuid = Some("uuid".to_string());
+        let mut second = bundle(2, 11, 11);
+        second.replacement_uuid = Some("uuid".to_string());
+
+        assert!(pool.insert(first.clone()).unwrap().is_none());
+        assert_eq!(pool.insert(second.clone()).unwrap(), Some(first.clone()));
+        assert_eq!(pool.len(), 1);
+        assert!(pool.get(&first.hash).is_none());
+        assert!(pool.bundles_for_block(10).is_empty());
+
+        assert_eq!(pool.remove_by_uuid("uuid"), Some(second));
+        assert!(pool.is_empty());
+        assert!(pool.remove_by_uuid("uuid").is_none());
+    }
+    #[test]
+    fn replace_only_bundles_of_same_signer() {
+        let pool = BundlePool::new();
+        let mut first = bundle(1, 10, 10);
+        first.replacement_uuid = Some("uuid".to_string());
+        first.signer = Some(Address::with_last_byte(1));
+        pool.insert(first.clone()).unwrap();
+
+        let mut other = bundle(2, 10, 10);
+        other.replacement_uuid = Some("uuid".to_string());
+        other.signer = Some(Address::with_last_byte(2));
+        assert_eq!(
+            pool.insert(other.clone()),
+            Err(BundlePoolError::SignerMismatch { hash: first.hash, signer: first.signer })
+        );
+        let mut resubmitted = first.clone();
+        resubmitted.signer = None;
+        assert!(pool.insert(resubmitted).is_err());
+        assert_eq!(pool.get_by_uuid("uuid"), Some(first.clone()));
+
+        other.signer = first.signer;
+        assert_eq!(pool.insert(other.clone()).unwrap(), Some(first));
+        assert_eq!(pool.get_by_uuid("uuid"), Some(other));
+    }
+
+    #[test]
+    fn rejects_bundles_when_full() {
+        let pool = BundlePool::with_max_bundles(2);
+        pool.insert(bundle(1, 10, 10)).unwrap();
+        pool.insert(bundle(2, 10, 10)).unwrap();
+        assert_eq!(pool.insert(bundle(3, 10, 10)), Err(BundlePoolError::PoolFull(2)));
+
+        // replacing a bundle doesn't grow the pool
+        assert!(pool.insert(bundle(2, 10, 11)).unwrap().is_some());
+
+        assert_eq!(pool.remove_expired(11), 1);
+        pool.insert(bundle(3, 11, 11)).unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+}
@@ -1,7 +1,7 @@
 //! Error types emitted by types or implementations of this crate.
 
 use reth_interfaces::{provider::ProviderError, RethError};
-use reth_primitives::{revm_primitives::EVMError, B256};
+use reth_primitives::{revm_primitives::EVMError, Address, B256};
 use reth_transaction_pool::BlobStoreError;
 use tokio::sync::oneshot;
 
@@ -38,6 +38,22 @@ impl From<ProviderError> for PayloadBuilderError {
     }
 }
 
+/// Errors of the [BundlePool](crate::BundlePool).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundlePoolError {
+    /// Thrown if a bundle would replace a bundle that was submitted by another signer.
+    #[error("bundle {hash} was submitted by {signer:?}")]
+    SignerMismatch {
+        /// The hash of the bundle that would be replaced.
+        hash: B256,
+        /// The signer of the bundle that would be replaced.
+        signer: Option<Address>,
+    },
+    /// Thrown if the pool already holds the maximum number of bundles.
+    #[error("bundle pool is full: {0} bundles")]
+    PoolFull(usize),
+}
+
 /// Optimism specific payload building errors.
 #[cfg(feature = "optimism")]
 #[derive(Debug, thiserror::Error)]
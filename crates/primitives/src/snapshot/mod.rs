@@ -24,6 +24,12 @@ pub struct HighestSnapshots {
     /// Highest snapshotted block of transactions, inclusive.
     /// If [`None`], no snapshot is available.
     pub transactions: Option<BlockNumber>,
+    /// Highest snapshotted block of account change sets, inclusive.
+    /// If [`None`], no snapshot is available.
+    pub account_changesets: Option<BlockNumber>,
+    /// Highest snapshotted block of storage change sets, inclusive.
+    /// If [`None`], no snapshot is available.
+    pub storage_changesets: Option<BlockNumber>,
 }
 
 impl HighestSnapshots {
@@ -33,6 +39,8 @@ impl HighestSnapshots {
             SnapshotSegment::Headers => self.headers,
             SnapshotSegment::Transactions => self.transactions,
             SnapshotSegment::Receipts => self.receipts,
+            SnapshotSegment::AccountChangeSets => self.account_changesets,
+            SnapshotSegment::StorageChangeSets => self.storage_changesets,
         }
     }
 
@@ -42,6 +50,8 @@ impl HighestSnapshots {
             SnapshotSegment::Headers => &mut self.headers,
             SnapshotSegment::Transactions => &mut self.transactions,
             SnapshotSegment::Receipts => &mut self.receipts,
+            SnapshotSegment::AccountChangeSets => &mut self.account_changesets,
+            SnapshotSegment::StorageChangeSets => &mut self.storage_changesets,
         }
     }
 }
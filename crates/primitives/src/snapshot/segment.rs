@@ -35,6 +35,12 @@ pub enum SnapshotSegment {
     #[strum(serialize = "receipts")]
     /// Snapshot segment responsible for the `Receipts` table.
     Receipts,
+    #[strum(serialize = "account-change-sets")]
+    /// Snapshot segment responsible for the `AccountChangeSet` table.
+    AccountChangeSets,
+    #[strum(serialize = "storage-change-sets")]
+    /// Snapshot segment responsible for the `StorageChangeSet` table.
+    StorageChangeSets,
 }
 
 impl SnapshotSegment {
@@ -52,6 +58,11 @@ impl SnapshotSegment {
             SnapshotSegment::Headers => default_config,
             SnapshotSegment::Transactions => default_config,
             SnapshotSegment::Receipts => default_config,
+            // Change sets are looked up with a binary search over their sorted rows, so they don't
+            // need filters.
+            SnapshotSegment::AccountChangeSets | SnapshotSegment::StorageChangeSets => {
+                SegmentConfig { filters: Filters::WithoutFilters, ..default_config }
+            }
         }
     }
 
@@ -180,10 +191,14 @@ impl SegmentHeader {
     }
 
     /// Returns the row offset which depends on whether the segment is block or transaction based.
+    ///
+    /// Change set segments have a row per changed account or storage slot, so their rows are
+    /// addressed directly.
     pub fn start(&self) -> u64 {
         match self.segment {
             SnapshotSegment::Headers => self.block_start(),
             SnapshotSegment::Transactions | SnapshotSegment::Receipts => self.tx_start(),
+            SnapshotSegment::AccountChangeSets | SnapshotSegment::StorageChangeSets => 0,
         }
     }
 }
@@ -219,6 +234,13 @@ mod tests {
                 "snapshot_transactions_1123233_11223233_1123233_2123233",
                 None,
             ),
+            (
+                SnapshotSegment::StorageChangeSets,
+                500_000..=999_999,
+                1_000..=2_000,
+                "snapshot_storage-change-sets_500000_999999_1000_2000",
+                None,
+            ),
             (
                 SnapshotSegment::Headers,
                 2..=30,
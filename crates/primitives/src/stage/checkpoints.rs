@@ -100,6 +100,42 @@ impl Compact for MerkleCheckpoint {
     }
 }
 
+/// Saves the progress of SnapSync stage.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SnapSyncCheckpoint {
+    /// The block whose state is downloaded.
+    pub pivot_block: BlockNumber,
+    /// The state root of the pivot block.
+    pub state_root: B256,
+    /// The next account hash to request account ranges from.
+    pub next_account: B256,
+    /// Whether all account ranges were downloaded.
+    pub accounts_complete: bool,
+}
+
+/// The downloads of the SnapSync stage that were pending when its progress was saved.
+#[main_codec]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SnapSyncTasks {
+    /// The accounts whose storage is still to be downloaded.
+    pub storages: Vec<SnapStorageTask>,
+    /// The bytecodes that are still to be downloaded.
+    pub codes: Vec<B256>,
+}
+
+/// The download of the storage of an account by the SnapSync stage.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SnapStorageTask {
+    /// The hashed address of the account.
+    pub account: B256,
+    /// The storage root of the account in the pivot state.
+    pub root: B256,
+    /// The hashed slot to continue the download from.
+    pub start: B256,
+}
+
 /// Saves the progress of AccountHashing stage.
 #[main_codec]
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
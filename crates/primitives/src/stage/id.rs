@@ -16,6 +16,12 @@ pub enum StageId {
     TransactionLookup,
     IndexStorageHistory,
     IndexAccountHistory,
+    /// Optional stage, not part of [`StageId::ALL`].
+    IndexAddressAppearances,
+    /// Optional stage, not part of [`StageId::ALL`].
+    IndexLogs,
+    /// Optional stage, not part of [`StageId::ALL`].
+    SnapSync,
     Finish,
     Other(&'static str),
 }
@@ -53,6 +59,9 @@ impl StageId {
             StageId::TransactionLookup => "TransactionLookup",
             StageId::IndexAccountHistory => "IndexAccountHistory",
             StageId::IndexStorageHistory => "IndexStorageHistory",
+            StageId::IndexAddressAppearances => "IndexAddressAppearances",
+            StageId::IndexLogs => "IndexLogs",
+            StageId::SnapSync => "SnapSync",
             StageId::Finish => "Finish",
             StageId::Other(s) => s,
         }
@@ -92,6 +101,9 @@ mod tests {
         assert_eq!(StageId::MerkleExecute.to_string(), "MerkleExecute");
         assert_eq!(StageId::IndexAccountHistory.to_string(), "IndexAccountHistory");
         assert_eq!(StageId::IndexStorageHistory.to_string(), "IndexStorageHistory");
+        assert_eq!(StageId::IndexAddressAppearances.to_string(), "IndexAddressAppearances");
+        assert_eq!(StageId::IndexLogs.to_string(), "IndexLogs");
+        assert_eq!(StageId::SnapSync.to_string(), "SnapSync");
         assert_eq!(StageId::TransactionLookup.to_string(), "TransactionLookup");
         assert_eq!(StageId::Finish.to_string(), "Finish");
 
@@ -88,7 +88,7 @@ impl<DB: Database> Pruner<DB> {
         let mut done = true;
         let mut stats = BTreeMap::new();
 
-        // TODO(alexey): prune snapshotted segments of data (headers, transactions)
+        // TODO(alexey): prune snapshotted segments of data (receipts)
         let highest_snapshots = *self.highest_snapshots_tracker.borrow();
 
         // Multiply `self.delete_limit` (number of rows to delete per block) by number of blocks
@@ -215,6 +215,48 @@ impl<DB: Database> Pruner<DB> {
                     (PruneProgress::from_done(output.done), output.pruned),
                 );
             }
+
+            let changesets: [(Option<BlockNumber>, fn(PruneMode) -> Arc<dyn Segment<DB>>); 2] = [
+                (snapshots.account_changesets, |mode| {
+                    Arc::new(segments::AccountChangeSets::new(mode))
+                }),
+                (snapshots.storage_changesets, |mode| {
+                    Arc::new(segments::StorageChangeSets::new(mode))
+                }),
+            ];
+            for (to_block, segment) in changesets {
+                let (Some(to_block), true) = (to_block, delete_limit > 0) else { continue };
+
+                let prune_mode = PruneMode::Before(to_block + 1);
+                let segment = segment(prune_mode);
+                trace!(
+                    target: "pruner",
+                    prune_segment = ?segment.segment(),
+                    %to_block,
+                    ?prune_mode,
+                    "Got target block to prune"
+                );
+
+                let segment_start = Instant::now();
+                let previous_checkpoint = provider.get_prune_checkpoint(segment.segment())?;
+                let output = segment
+                    .prune(&provider, PruneInput { previous_checkpoint, to_block, delete_limit })?;
+                if let Some(checkpoint) = output.checkpoint {
+                    segment
+                        .save_checkpoint(&provider, checkpoint.as_prune_checkpoint(prune_mode))?;
+                }
+                self.metrics
+                    .get_prune_segment_metrics(segment.segment())
+                    .duration_seconds
+                    .record(segment_start.elapsed());
+
+                done = done && output.done;
+                delete_limit = delete_limit.saturating_sub(output.pruned);
+                stats.insert(
+                    segment.segment(),
+                    (PruneProgress::from_done(output.done), output.pruned),
+                );
+            }
         }
 
         provider.commit()?;
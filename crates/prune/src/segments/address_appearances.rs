@@ -0,0 +1,109 @@
+use crate::{
+    segments::{
+        history::prune_history_indices, PruneInput, PruneOutput, PruneOutputCheckpoint, Segment,
+    },
+    PrunerError,
+};
+use reth_db::{database::Database, models::ShardedKey, tables};
+use reth_primitives::{PruneMode, PruneSegment};
+use reth_provider::DatabaseProviderRW;
+use tracing::{instrument, trace};
+
+#[derive(Debug)]
+pub struct AddressAppearances {
+    mode: PruneMode,
+}
+
+impl AddressAppearances {
+    pub fn new(mode: PruneMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl<DB: Database> Segment<DB> for AddressAppearances {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::AddressAppearances
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        let range = match input.get_next_block_range() {
+            Some(range) => range,
+            None => {
+                trace!(target: "pruner", "No address appearances to prune");
+                return Ok(PruneOutput::done())
+            }
+        };
+        let range_end = *range.end();
+
+        // Unlike account and storage history, there are no changesets backing the index, so the
+        // whole range is pruned at once.
+        let (processed, pruned) = prune_history_indices::<DB, tables::AddressAppearances, _>(
+            provider,
+            range_end,
+            |a, b| a.key == b.key,
+            |key| ShardedKey::last(key.key),
+        )?;
+        trace!(target: "pruner", %processed, %pruned, "Pruned address appearances");
+
+        Ok(PruneOutput {
+            done: true,
+            pruned,
+            checkpoint: Some(PruneOutputCheckpoint {
+                block_number: Some(range_end),
+                tx_number: None,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{AddressAppearances, PruneInput, PruneOutput, Segment};
+    use assert_matches::assert_matches;
+    use reth_db::tables;
+    use reth_primitives::{Address, PruneMode};
+    use reth_provider::HistoryWriter;
+    use reth_stages::test_utils::TestStageDB;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn prune() {
+        let db = TestStageDB::default();
+        let first = Address::with_last_byte(1);
+        let second = Address::with_last_byte(2);
+
+        let provider = db.factory.provider_rw().unwrap();
+        provider
+            .insert_address_appearance_index(BTreeMap::from([
+                (first, vec![1, 2, 3]),
+                (second, vec![3, 7, 10]),
+            ]))
+            .unwrap();
+        provider.commit().unwrap();
+
+        let input = PruneInput { previous_checkpoint: None, to_block: 3, delete_limit: 10 };
+        let segment = AddressAppearances::new(PruneMode::Before(4));
+
+        let provider = db.factory.provider_rw().unwrap();
+        let result = segment.prune(&provider, input).unwrap();
+        assert_matches!(result, PruneOutput { done: true, pruned: 1, checkpoint: Some(_) });
+        provider.commit().unwrap();
+
+        let table = db
+            .table::<tables::AddressAppearances>()
+            .unwrap()
+            .into_iter()
+            .map(|(key, blocks)| (key.key, blocks.iter(0).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(table, vec![(second, vec![7, 10])]);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    segments::{PruneInput, PruneOutput, PruneOutputCheckpoint},
+    PrunerError,
+};
+use reth_db::{database::Database, table::Table};
+use reth_primitives::BlockNumber;
+use reth_provider::DatabaseProviderRW;
+use std::{
+    fmt::Debug,
+    ops::{RangeBounds, RangeInclusive},
+};
+use tracing::trace;
+
+/// Prunes the change sets of the next block range of the input from the change set table `T`,
+/// leaving the history indices intact.
+///
+/// `keys` converts the block range into the range of table keys, and `block_number` returns the
+/// block number of a table key.
+pub(crate) fn prune_changesets<DB, T, R>(
+    provider: &DatabaseProviderRW<DB>,
+    input: PruneInput,
+    keys: impl FnOnce(RangeInclusive<BlockNumber>) -> R,
+    block_number: impl Fn(&T::Key) -> BlockNumber,
+) -> Result<PruneOutput, PrunerError>
+where
+    DB: Database,
+    T: Table,
+    R: RangeBounds<T::Key> + Clone + Debug,
+{
+    let range = match input.get_next_block_range() {
+        Some(range) => range,
+        None => {
+            trace!(target: "pruner", table = T::NAME, "No changesets to prune");
+            return Ok(PruneOutput::done())
+        }
+    };
+    let range_end = *range.end();
+
+    let mut last_pruned_block = None;
+    let (pruned, done) = provider.prune_table_with_range::<T>(
+        keys(range),
+        input.delete_limit,
+        |_| false,
+        |row| last_pruned_block = Some(block_number(&row.0)),
+    )?;
+    trace!(target: "pruner", table = T::NAME, %pruned, %done, "Pruned changesets");
+
+    let last_pruned_block = last_pruned_block
+        // If there's more changesets to prune, set the checkpoint block number to previous, so we
+        // could finish pruning its changesets on the next run.
+        .map(|block_number| if done { block_number } else { block_number.saturating_sub(1) })
+        .unwrap_or(range_end);
+
+    Ok(PruneOutput {
+        done,
+        pruned,
+        checkpoint: Some(PruneOutputCheckpoint {
+            block_number: Some(last_pruned_block),
+            tx_number: None,
+        }),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use reth_interfaces::test_utils::{
+        generators,
+        generators::{random_block_range, random_changeset_range, random_eoa_account_range},
+    };
+    use reth_primitives::B256;
+    use reth_stages::test_utils::TestStageDB;
+    use std::collections::BTreeMap;
+
+    /// Returns a database with 101 blocks and their account and storage change sets and history.
+    pub(crate) fn db_with_changesets() -> TestStageDB {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=100, B256::ZERO, 0..1);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+
+        let accounts =
+            random_eoa_account_range(&mut rng, 0..2).into_iter().collect::<BTreeMap<_, _>>();
+
+        let (changesets, _) = random_changeset_range(
+            &mut rng,
+            blocks.iter(),
+            accounts.into_iter().map(|(addr, acc)| (addr, (acc, Vec::new()))),
+            2..3,
+            1..2,
+        );
+        db.insert_changesets(changesets.clone(), None).expect("insert changesets");
+        db.insert_history(changesets, None).expect("insert history");
+
+        db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prune_changesets, test_utils::db_with_changesets};
+    use crate::segments::{AccountChangeSets, PruneInput, PruneOutput, Segment, StorageChangeSets};
+    use assert_matches::assert_matches;
+    use reth_db::tables;
+    use reth_primitives::{PruneCheckpoint, PruneMode};
+    use reth_provider::{
+        providers::SnapshotProvider, AccountReader, HistoricalStateProviderRef, StateProvider,
+    };
+    use reth_snapshot::segments::{self as snapshot_segments, Segment as _};
+
+    #[test]
+    fn prune_in_batches() {
+        let db = db_with_changesets();
+        let original_changesets = db.table::<tables::AccountChangeSet>().unwrap();
+
+        let to_block = 50;
+        let provider = db.factory.provider_rw().unwrap();
+        let mut previous_checkpoint = None;
+        let mut pruned_total = 0;
+        loop {
+            let input = PruneInput { previous_checkpoint, to_block, delete_limit: 10 };
+            let output = prune_changesets::<_, tables::AccountChangeSet, _>(
+                &provider,
+                input,
+                |range| range,
+                |block| *block,
+            )
+            .unwrap();
+            pruned_total += output.pruned;
+
+            let checkpoint = output.checkpoint.unwrap().block_number.unwrap();
+            if output.done {
+                assert_eq!(checkpoint, to_block);
+                break
+            }
+            // unfinished blocks are pruned again on the next run
+            assert_matches!(output, PruneOutput { pruned: 10, .. });
+            assert!(checkpoint < to_block);
+            previous_checkpoint = Some(PruneCheckpoint {
+                block_number: Some(checkpoint),
+                tx_number: None,
+                prune_mode: PruneMode::Before(to_block + 1),
+            });
+        }
+        provider.commit().expect("commit");
+
+        assert_eq!(
+            pruned_total,
+            original_changesets.iter().filter(|(block, _)| *block <= to_block).count()
+        );
+        assert_eq!(
+            db.table::<tables::AccountChangeSet>().unwrap(),
+            original_changesets
+                .into_iter()
+                .filter(|(block, _)| *block > to_block)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn read_pruned_changesets_from_snapshots() {
+        let db = db_with_changesets();
+        let original_account_changesets = db.table::<tables::AccountChangeSet>().unwrap();
+        let original_storage_changesets = db.table::<tables::StorageChangeSet>().unwrap();
+
+        // Snapshot the change sets of the first blocks
+        let to_block = 49;
+        let snapshots_dir = tempfile::TempDir::new().unwrap();
+        let provider = db.factory.provider().unwrap();
+        snapshot_segments::AccountChangeSets::default()
+            .snapshot(&provider, snapshots_dir.path(), 0..=to_block)
+            .expect("snapshot account change sets");
+        snapshot_segments::StorageChangeSets::default()
+            .snapshot(&provider, snapshots_dir.path(), 0..=to_block)
+            .expect("snapshot storage change sets");
+        drop(provider);
+
+        // Prune the snapshotted change sets from the database
+        let prune_mode = PruneMode::Before(to_block + 1);
+        let input = PruneInput { previous_checkpoint: None, to_block, delete_limit: usize::MAX };
+        let provider = db.factory.provider_rw().unwrap();
+        AccountChangeSets::new(prune_mode).prune(&provider, input).unwrap();
+        StorageChangeSets::new(prune_mode).prune(&provider, input).unwrap();
+        provider.commit().expect("commit");
+        assert!(db
+            .table::<tables::AccountChangeSet>()
+            .unwrap()
+            .iter()
+            .all(|(block, _)| *block > to_block));
+        assert!(db
+            .table::<tables::StorageChangeSet>()
+            .unwrap()
+            .iter()
+            .all(|(key, _)| key.block_number() > to_block));
+
+        // Every pruned change set is read from the snapshots
+        let snapshot_provider = SnapshotProvider::new(snapshots_dir.path()).unwrap();
+        let snapshotted_account_changesets = original_account_changesets
+            .iter()
+            .filter(|(block, _)| *block <= to_block)
+            .cloned()
+            .collect::<Vec<_>>();
+        let snapshotted_storage_changesets = original_storage_changesets
+            .iter()
+            .filter(|(key, _)| key.block_number() <= to_block)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(!snapshotted_account_changesets.is_empty());
+        assert!(!snapshotted_storage_changesets.is_empty());
+
+        for (block, account) in &snapshotted_account_changesets {
+            assert_eq!(
+                snapshot_provider.account_changeset(*block, account.address).unwrap().as_ref(),
+                Some(account)
+            );
+        }
+        for (key, entry) in &snapshotted_storage_changesets {
+            assert_eq!(
+                snapshot_provider
+                    .storage_changeset(key.block_number(), key.address(), entry.key)
+                    .unwrap()
+                    .as_ref(),
+                Some(entry)
+            );
+        }
+        assert_eq!(
+            snapshot_provider.account_changesets(0..to_block + 1).unwrap(),
+            snapshotted_account_changesets
+        );
+        assert_eq!(
+            snapshot_provider.storage_changesets(0..to_block + 1).unwrap(),
+            snapshotted_storage_changesets
+        );
+
+        // The historical state before every pruned change is read from the snapshots through the
+        // history indices, which are left intact
+        let provider = db.factory.provider().unwrap();
+        for (block, account) in &snapshotted_account_changesets {
+            let state = HistoricalStateProviderRef::new(provider.tx_ref(), *block)
+                .with_snapshot_provider(&snapshot_provider);
+            assert_eq!(state.basic_account(account.address).unwrap(), account.info);
+        }
+        for (key, entry) in &snapshotted_storage_changesets {
+            let state = HistoricalStateProviderRef::new(provider.tx_ref(), key.block_number())
+                .with_snapshot_provider(&snapshot_provider);
+            assert_eq!(state.storage(key.address(), entry.key).unwrap(), Some(entry.value));
+        }
+    }
+}
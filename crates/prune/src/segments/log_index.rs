@@ -0,0 +1,127 @@
+use crate::{
+    segments::{
+        history::prune_history_indices, PruneInput, PruneOutput, PruneOutputCheckpoint, Segment,
+    },
+    PrunerError,
+};
+use reth_db::{database::Database, models::ShardedKey, tables};
+use reth_primitives::{PruneMode, PruneSegment};
+use reth_provider::DatabaseProviderRW;
+use tracing::{instrument, trace};
+
+#[derive(Debug)]
+pub struct LogIndex {
+    mode: PruneMode,
+}
+
+impl LogIndex {
+    pub fn new(mode: PruneMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl<DB: Database> Segment<DB> for LogIndex {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::LogIndex
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        let range = match input.get_next_block_range() {
+            Some(range) => range,
+            None => {
+                trace!(target: "pruner", "No log index to prune");
+                return Ok(PruneOutput::done())
+            }
+        };
+        let range_end = *range.end();
+
+        // Like address appearances, the index isn't backed by changesets, so the whole range is
+        // pruned at once.
+        let (addresses_processed, addresses_pruned) =
+            prune_history_indices::<DB, tables::LogAddressIndex, _>(
+                provider,
+                range_end,
+                |a, b| a.key == b.key,
+                |key| ShardedKey::last(key.key),
+            )?;
+        let (topics_processed, topics_pruned) =
+            prune_history_indices::<DB, tables::LogTopicIndex, _>(
+                provider,
+                range_end,
+                |a, b| a.key == b.key,
+                |key| ShardedKey::last(key.key),
+            )?;
+        let processed = addresses_processed + topics_processed;
+        let pruned = addresses_pruned + topics_pruned;
+        trace!(target: "pruner", %processed, %pruned, "Pruned log index");
+
+        Ok(PruneOutput {
+            done: true,
+            pruned,
+            checkpoint: Some(PruneOutputCheckpoint {
+                block_number: Some(range_end),
+                tx_number: None,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{LogIndex, PruneInput, PruneOutput, Segment};
+    use assert_matches::assert_matches;
+    use reth_db::tables;
+    use reth_primitives::{Address, PruneMode, B256};
+    use reth_provider::{HistoryWriter, LogIndexEntries};
+    use reth_stages::test_utils::TestStageDB;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn prune() {
+        let db = TestStageDB::default();
+        let address = Address::with_last_byte(1);
+        let first_topic = B256::with_last_byte(1);
+        let second_topic = B256::with_last_byte(2);
+
+        let provider = db.factory.provider_rw().unwrap();
+        provider
+            .insert_log_index(LogIndexEntries {
+                addresses: BTreeMap::from([(address, vec![1, 2, 7])]),
+                topics: BTreeMap::from([(first_topic, vec![1, 2]), (second_topic, vec![2, 7])]),
+            })
+            .unwrap();
+        provider.commit().unwrap();
+
+        let input = PruneInput { previous_checkpoint: None, to_block: 3, delete_limit: 10 };
+        let segment = LogIndex::new(PruneMode::Before(4));
+
+        let provider = db.factory.provider_rw().unwrap();
+        let result = segment.prune(&provider, input).unwrap();
+        assert_matches!(result, PruneOutput { done: true, pruned: 1, checkpoint: Some(_) });
+        provider.commit().unwrap();
+
+        let addresses = db
+            .table::<tables::LogAddressIndex>()
+            .unwrap()
+            .into_iter()
+            .map(|(key, blocks)| (key.key, blocks.iter(0).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, vec![(address, vec![7])]);
+        let topics = db
+            .table::<tables::LogTopicIndex>()
+            .unwrap()
+            .into_iter()
+            .map(|(key, blocks)| (key.key, blocks.iter(0).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(topics, vec![(second_topic, vec![7])]);
+    }
+}
@@ -1,21 +1,30 @@
+mod account_changesets;
 mod account_history;
+mod address_appearances;
+mod changesets;
 mod headers;
 mod history;
+mod log_index;
 mod receipts;
 mod receipts_by_logs;
 mod sender_recovery;
 mod set;
+mod storage_changesets;
 mod storage_history;
 mod transaction_lookup;
 mod transactions;
 
+pub use account_changesets::AccountChangeSets;
 pub use account_history::AccountHistory;
+pub use address_appearances::AddressAppearances;
 pub use headers::Headers;
+pub use log_index::LogIndex;
 pub use receipts::Receipts;
 pub use receipts_by_logs::ReceiptsByLogs;
 pub use sender_recovery::SenderRecovery;
 pub use set::SegmentSet;
 use std::fmt::Debug;
+pub use storage_changesets::StorageChangeSets;
 pub use storage_history::StorageHistory;
 pub use transaction_lookup::TransactionLookup;
 pub use transactions::Transactions;
@@ -0,0 +1,102 @@
+use crate::{
+    segments::{changesets::prune_changesets, PruneInput, PruneOutput, Segment},
+    PrunerError,
+};
+use reth_db::{database::Database, models::BlockNumberAddress, tables};
+use reth_primitives::{PruneMode, PruneSegment};
+use reth_provider::DatabaseProviderRW;
+use tracing::instrument;
+
+/// Prunes the `StorageChangeSet` table, leaving the `StorageHistory` table intact. Used for the
+/// change sets which were moved to snapshots.
+#[derive(Debug)]
+pub struct StorageChangeSets {
+    mode: PruneMode,
+}
+
+impl StorageChangeSets {
+    pub fn new(mode: PruneMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl<DB: Database> Segment<DB> for StorageChangeSets {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::StorageChangeSets
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        prune_changesets::<DB, tables::StorageChangeSet, _>(
+            provider,
+            input,
+            BlockNumberAddress::range,
+            |key| key.block_number(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{
+        changesets::test_utils::db_with_changesets, PruneInput, PruneOutput, Segment,
+        StorageChangeSets,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::tables;
+    use reth_primitives::{PruneCheckpoint, PruneMode, PruneSegment};
+    use reth_provider::PruneCheckpointReader;
+
+    #[test]
+    fn prune() {
+        let db = db_with_changesets();
+        let original_changesets = db.table::<tables::StorageChangeSet>().unwrap();
+        let original_other_changesets = db.table::<tables::AccountChangeSet>().unwrap();
+        let original_shards = db.table::<tables::StorageHistory>().unwrap();
+
+        let to_block = 50;
+        let prune_mode = PruneMode::Before(to_block + 1);
+        let input = PruneInput { previous_checkpoint: None, to_block, delete_limit: usize::MAX };
+        let segment = StorageChangeSets::new(prune_mode);
+
+        let provider = db.factory.provider_rw().unwrap();
+        let result = segment.prune(&provider, input).unwrap();
+        let expected_pruned =
+            original_changesets.iter().filter(|(key, _)| key.block_number() <= to_block).count();
+        assert_matches!(
+            result,
+            PruneOutput { done: true, pruned, checkpoint: Some(_) } if pruned == expected_pruned
+        );
+        segment
+            .save_checkpoint(&provider, result.checkpoint.unwrap().as_prune_checkpoint(prune_mode))
+            .unwrap();
+        provider.commit().expect("commit");
+
+        assert_eq!(
+            db.table::<tables::StorageChangeSet>().unwrap(),
+            original_changesets
+                .into_iter()
+                .filter(|(key, _)| key.block_number() > to_block)
+                .collect::<Vec<_>>()
+        );
+        // The other change sets and the history indices are left intact
+        assert_eq!(db.table::<tables::AccountChangeSet>().unwrap(), original_other_changesets);
+        assert_eq!(db.table::<tables::StorageHistory>().unwrap(), original_shards);
+        assert_eq!(
+            db.factory
+                .provider()
+                .unwrap()
+                .get_prune_checkpoint(PruneSegment::StorageChangeSets)
+                .unwrap(),
+            Some(PruneCheckpoint { block_number: Some(to_block), tx_number: None, prune_mode })
+        );
+    }
+}
@@ -24,3 +24,6 @@ pub mod stack;
 
 /// An inspector for recording traces
 pub mod tracing;
+
+/// An inspector that records ETH transfers as logs
+pub mod transfer;
@@ -5,9 +5,9 @@ use crate::tracing::{
     utils::load_account_code,
     TracingInspectorConfig,
 };
-use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_primitives::{hex, Address, Bytes, B256, U256};
 use reth_rpc_types::trace::geth::{
-    AccountChangeKind, AccountState, CallConfig, CallFrame, DefaultFrame, DiffMode,
+    AccountChangeKind, AccountState, CallConfig, CallFrame, DefaultFrame, DiffMode, FourByteFrame,
     GethDefaultTracingOptions, PreStateConfig, PreStateFrame, PreStateMode, StructLog,
 };
 use revm::{db::DatabaseRef, primitives::ResultAndState};
@@ -169,6 +169,23 @@ impl GethTraceBuilder {
         }
     }
 
+    /// Generate the result of the 4byte tracer from the recorded call traces.
+    ///
+    /// This is equivalent to the [FourByteInspector](crate::tracing::FourByteInspector), but
+    /// allows deriving the 4byte trace from an existing trace, e.g. for the `muxTracer`.
+    pub fn geth_four_byte_traces(&self) -> FourByteFrame {
+        let mut selectors = BTreeMap::new();
+        for node in self.nodes.iter() {
+            let input = &node.trace.data;
+            if node.trace.kind.is_any_create() || input.len() < 4 {
+                continue
+            }
+            let key = format!("0x{}-{}", hex::encode(&input[..4]), input.len() - 4);
+            *selectors.entry(key).or_default() += 1;
+        }
+        FourByteFrame(selectors)
+    }
+
     ///  Returns the accounts necessary for transaction execution.
     ///
     /// The prestate mode returns the accounts necessary to execute a given transaction.
@@ -1,6 +1,6 @@
 use super::walker::CallTraceNodeWalkerBF;
 use crate::tracing::{
-    types::{CallTraceNode, CallTraceStep},
+    types::{CallTraceNode, CallTraceStep, LogCallOrder},
     utils::load_account_code,
     TracingInspectorConfig,
 };
@@ -18,7 +18,9 @@ use std::collections::{HashSet, VecDeque};
 
 /// A type for creating parity style traces
 ///
-/// Note: Parity style traces always ignore calls to precompiles.
+/// Note: Calls to precompiles are ignored if the traces were recorded with
+/// [TracingInspectorConfig::exclude_precompile_calls] or
+/// [ParityTraceBuilder::without_precompile_calls] is used.
 #[derive(Clone, Debug)]
 pub struct ParityTraceBuilder {
     /// Recorded trace nodes
@@ -27,7 +29,7 @@ pub struct ParityTraceBuilder {
     spec_id: Option<SpecId>,
 
     /// How the traces were recorded
-    _config: TracingInspectorConfig,
+    config: TracingInspectorConfig,
 }
 
 impl ParityTraceBuilder {
@@ -35,9 +37,48 @@ impl ParityTraceBuilder {
     pub fn new(
         nodes: Vec<CallTraceNode>,
         spec_id: Option<SpecId>,
-        _config: TracingInspectorConfig,
+        config: TracingInspectorConfig,
     ) -> Self {
-        Self { nodes, spec_id, _config }
+        Self { nodes, spec_id, config }
+    }
+
+    /// Excludes the calls to precompiles from the traces, even if they were recorded.
+    ///
+    /// This detaches the calls to precompiles from their parents, as if the traces were recorded
+    /// with [TracingInspectorConfig::exclude_precompile_calls].
+    pub fn without_precompile_calls(mut self) -> Self {
+        if self.config.exclude_precompile_calls {
+            return self
+        }
+        self.config.exclude_precompile_calls = true;
+
+        for idx in 0..self.nodes.len() {
+            if !self.nodes[idx].is_precompile() {
+                continue
+            }
+            let Some(parent) = self.nodes[idx].parent else { continue };
+            let parent = &mut self.nodes[parent];
+            let Some(position) = parent.children.iter().position(|child| *child == idx) else {
+                continue
+            };
+            parent.children.remove(position);
+            parent.ordering.retain_mut(|order| match order {
+                LogCallOrder::Call(call) if *call == position => false,
+                LogCallOrder::Call(call) => {
+                    if *call > position {
+                        *call -= 1;
+                    }
+                    true
+                }
+                LogCallOrder::Log(_) => true,
+            });
+        }
+        self
+    }
+
+    /// Returns true if the node is a call to a precompile that is excluded from the traces.
+    fn is_excluded(&self, node: &CallTraceNode) -> bool {
+        self.config.exclude_precompile_calls && node.is_precompile()
     }
 
     /// Returns a list of all addresses that appeared as callers.
@@ -96,7 +137,7 @@ impl ParityTraceBuilder {
         }
         let mut graph = vec![];
         let mut node = &self.nodes[idx];
-        if node.is_precompile() {
+        if self.is_excluded(node) {
             return graph
         }
         while let Some(parent) = node.parent {
@@ -119,7 +160,7 @@ impl ParityTraceBuilder {
     ///
     /// This excludes nodes that represent calls to precompiles.
     fn iter_traceable_nodes(&self) -> impl Iterator<Item = &CallTraceNode> {
-        self.nodes.iter().filter(|node| !node.is_precompile())
+        self.nodes.iter().filter(|node| !self.is_excluded(node))
     }
 
     /// Returns an iterator over all recorded traces  for `trace_transaction`
@@ -273,13 +314,14 @@ impl ParityTraceBuilder {
     /// Returns an iterator over all recorded traces  for `trace_transaction`
     pub fn into_transaction_traces_iter(self) -> impl Iterator<Item = TransactionTrace> {
         let trace_addresses = self.trace_addresses();
+        let exclude_precompile_calls = self.config.exclude_precompile_calls;
         TransactionTraceIter {
             next_selfdestruct: None,
             iter: self
                 .nodes
                 .into_iter()
                 .zip(trace_addresses)
-                .filter(|(node, _)| !node.is_precompile())
+                .filter(move |(node, _)| !(exclude_precompile_calls && node.is_precompile()))
                 .map(|(node, trace_address)| (node.parity_transaction_trace(trace_address), node)),
         }
     }
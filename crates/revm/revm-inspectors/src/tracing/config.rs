@@ -1,4 +1,7 @@
-use reth_rpc_types::trace::{geth::GethDefaultTracingOptions, parity::TraceType};
+use reth_rpc_types::trace::{
+    geth::{FlatCallConfig, GethDefaultTracingOptions},
+    parity::TraceType,
+};
 use std::collections::HashSet;
 
 /// Gives guidance to the [TracingInspector](crate::tracing::TracingInspector).
@@ -97,6 +100,16 @@ impl TracingInspectorConfig {
         }
     }
 
+    /// Returns a config for the flat call tracer based on the given [FlatCallConfig].
+    ///
+    /// The flat call tracer produces parity style traces, so this is the same as
+    /// [TracingInspectorConfig::default_parity] unless precompiles should be included.
+    #[inline]
+    pub fn from_flat_call_config(config: &FlatCallConfig) -> Self {
+        Self::default_parity()
+            .set_exclude_precompile_calls(!config.include_precompiles.unwrap_or_default())
+    }
+
     /// Configure whether calls to precompiles should be ignored.
     ///
     /// If set to `true`, calls to precompiles without value transfers will be ignored.
@@ -222,4 +235,15 @@ mod tests {
         // not required for StateDiff
         assert!(!config.record_state_diff);
     }
+
+    #[test]
+    fn test_flat_call_config() {
+        let config = TracingInspectorConfig::from_flat_call_config(&FlatCallConfig::default());
+        assert_eq!(config, TracingInspectorConfig::default_parity());
+
+        let config = TracingInspectorConfig::from_flat_call_config(
+            &FlatCallConfig::default().include_precompiles(),
+        );
+        assert!(!config.exclude_precompile_calls);
+    }
 }
@@ -21,7 +21,7 @@ mod config;
 mod fourbyte;
 mod opcount;
 pub mod types;
-mod utils;
+pub(crate) mod utils;
 use self::parity::stack_push_count;
 use crate::tracing::{
     arena::PushTraceKind,
@@ -197,9 +197,10 @@ impl TracingInspector {
         mut gas_limit: u64,
         maybe_precompile: Option<bool>,
     ) {
-        // This will only be true if the inspector is configured to exclude precompiles and the call
-        // is to a precompile
-        let push_kind = if maybe_precompile.unwrap_or(false) {
+        // Calls to precompiles are only detached from the call graph if the inspector is configured
+        // to exclude them
+        let push_kind = if self.config.exclude_precompile_calls && maybe_precompile.unwrap_or(false)
+        {
             // We don't want to track precompiles
             PushTraceKind::PushOnly
         } else {
@@ -458,9 +459,9 @@ where
             inputs.transfer.value
         };
 
-        // if calls to precompiles should be excluded, check whether this is a call to a precompile
-        let maybe_precompile =
-            self.config.exclude_precompile_calls.then(|| self.is_precompile_call(data, &to, value));
+        // calls to precompiles are always marked, so they can still be excluded from the parity
+        // style traces if they were recorded
+        let maybe_precompile = Some(self.is_precompile_call(data, &to, value));
 
         self.start_trace_on_call(
             data,
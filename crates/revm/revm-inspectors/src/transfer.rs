@@ -0,0 +1,177 @@
+use crate::tracing::utils::get_create_address;
+use alloy_primitives::{address, b256, Address, Bytes, B256, U256};
+use revm::{
+    interpreter::{return_ok, CallInputs, CreateInputs, Gas, InstructionResult},
+    primitives::Log,
+    Database, EVMData, Inspector,
+};
+
+/// The address that emits the synthetic ETH transfer logs of the [TransferInspector].
+pub const TRANSFER_LOG_EMITTER: Address = address!("eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+
+/// The topic of the ERC-20 `Transfer(address,address,uint256)` event.
+pub const TRANSFER_EVENT_TOPIC: B256 =
+    b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// An [Inspector] that collects the logs of a transaction and additionally records every ETH
+/// transfer as an ERC-20 `Transfer` log emitted by [TRANSFER_LOG_EMITTER].
+///
+/// Logs and transfers of calls that revert are discarded, so the collected logs are in the same
+/// order as the logs of the transaction with the transfer logs interleaved.
+#[derive(Default, Debug)]
+pub struct TransferInspector {
+    /// All logs, including the transfer logs.
+    logs: Vec<Log>,
+    /// The number of logs recorded when the currently active calls were entered.
+    checkpoints: Vec<usize>,
+}
+
+impl TransferInspector {
+    /// Returns the collected logs.
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    /// Consumes the inspector and returns the collected logs.
+    pub fn into_logs(self) -> Vec<Log> {
+        self.logs
+    }
+
+    /// Records a transfer of `value` from `from` to `to`, if it moves any ether.
+    fn record_transfer(&mut self, from: Address, to: Address, value: U256) {
+        if value.is_zero() || from == to {
+            return
+        }
+        self.logs.push(transfer_log(from, to, value));
+    }
+
+    /// Enters a new call.
+    fn enter(&mut self) {
+        self.checkpoints.push(self.logs.len());
+    }
+
+    /// Exits the current call, discarding its logs if it did not succeed.
+    fn exit(&mut self, status: InstructionResult) {
+        let checkpoint = self.checkpoints.pop().unwrap_or_default();
+        if !matches!(status, return_ok!()) {
+            self.logs.truncate(checkpoint);
+        }
+    }
+}
+
+impl<DB> Inspector<DB> for TransferInspector
+where
+    DB: Database,
+{
+    fn log(
+        &mut self,
+        _evm_data: &mut EVMData<'_, DB>,
+        address: &Address,
+        topics: &[B256],
+        data: &Bytes,
+    ) {
+        self.logs.push(Log { address: *address, topics: topics.to_vec(), data: data.clone() });
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.enter();
+        // delegate calls don't transfer any value and the transfer of callcode is a self transfer
+        self.record_transfer(inputs.transfer.source, inputs.transfer.target, inputs.transfer.value);
+
+        (InstructionResult::Continue, Gas::new(0), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: Bytes,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.exit(ret);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.enter();
+
+        if !inputs.value.is_zero() {
+            let _ = data.journaled_state.load_account(inputs.caller, data.db);
+            let nonce = data.journaled_state.account(inputs.caller).info.nonce;
+            self.record_transfer(inputs.caller, get_create_address(inputs, nonce), inputs.value);
+        }
+
+        (InstructionResult::Continue, None, Gas::new(inputs.gas_limit), Bytes::default())
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        out: Bytes,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.exit(ret);
+        (ret, address, remaining_gas, out)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        self.record_transfer(contract, target, value);
+    }
+}
+
+/// Returns the `Transfer` log for a transfer of `value` from `from` to `to`.
+fn transfer_log(from: Address, to: Address, value: U256) -> Log {
+    Log {
+        address: TRANSFER_LOG_EMITTER,
+        topics: vec![TRANSFER_EVENT_TOPIC, from.into_word(), to.into_word()],
+        data: value.to_be_bytes_vec().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_transfer_log() {
+        assert_eq!(TRANSFER_EVENT_TOPIC, keccak256("Transfer(address,address,uint256)"));
+
+        let from = Address::with_last_byte(1);
+        let to = Address::with_last_byte(2);
+        let log = transfer_log(from, to, U256::from(1337));
+        assert_eq!(log.address, TRANSFER_LOG_EMITTER);
+        assert_eq!(log.topics[1], from.into_word());
+        assert_eq!(log.topics[2], to.into_word());
+        assert_eq!(U256::from_be_slice(&log.data), U256::from(1337));
+    }
+
+    #[test]
+    fn test_discard_reverted_transfers() {
+        let mut inspector = TransferInspector::default();
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+
+        inspector.enter();
+        inspector.record_transfer(a, b, U256::from(1));
+        inspector.record_transfer(a, a, U256::from(1));
+        inspector.enter();
+        inspector.record_transfer(b, a, U256::from(2));
+        inspector.exit(InstructionResult::Revert);
+        inspector.record_transfer(a, b, U256::ZERO);
+        inspector.exit(InstructionResult::Stop);
+
+        assert_eq!(inspector.into_logs(), vec![transfer_log(a, b, U256::from(1))]);
+    }
+}
@@ -3,12 +3,12 @@ use reth_interfaces::executor::{
     BlockExecutionError, BlockValidationError, OptimismBlockExecutionError,
 };
 use reth_primitives::{
-    revm::compat::into_reth_log, revm_primitives::ResultAndState, BlockWithSenders, Hardfork,
-    Receipt, U256,
+    revm::compat::into_reth_log, revm_primitives::ResultAndState, Address, BlockNumber,
+    BlockWithSenders, Hardfork, Receipt, U256,
 };
 use reth_provider::{BlockExecutor, BlockExecutorStats, BundleStateWithReceipts};
 use revm::DatabaseCommit;
-use std::time::Instant;
+use std::{collections::BTreeMap, time::Instant};
 use tracing::{debug, trace};
 
 impl<'a> BlockExecutor for EVMProcessor<'a> {
@@ -118,6 +118,7 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
             self.stats.execution_duration += time.elapsed();
             let time = Instant::now();
 
+            self.record_accessed_accounts(block.number, state.keys());
             self.db_mut().commit(state);
 
             self.stats.apply_state_duration += time.elapsed();
@@ -166,4 +167,12 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
     fn size_hint(&self) -> Option<usize> {
         self.evm.db.as_ref().map(|db| db.bundle_size_hint())
     }
+
+    fn set_record_accessed_accounts(&mut self, record: bool) {
+        self.accessed_accounts = record.then(BTreeMap::new);
+    }
+
+    fn take_accessed_accounts(&mut self) -> BTreeMap<Address, Vec<BlockNumber>> {
+        self.accessed_accounts.as_mut().map(std::mem::take).unwrap_or_default()
+    }
 }
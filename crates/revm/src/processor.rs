@@ -19,7 +19,7 @@ use revm::{
     primitives::ResultAndState,
     State, EVM,
 };
-use std::{sync::Arc, time::Instant};
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
 
 #[cfg(not(feature = "optimism"))]
 use reth_primitives::revm::compat::into_reth_log;
@@ -72,6 +72,9 @@ pub struct EVMProcessor<'a> {
     /// Empty implies that there is going to be addresses to include in the filter in a future
     /// block. None means there isn't any kind of configuration.
     pruning_address_filter: Option<(u64, Vec<Address>)>,
+    /// The accounts accessed by the executed transactions and the blocks in which they were
+    /// accessed, if recording is enabled.
+    pub(crate) accessed_accounts: Option<BTreeMap<Address, Vec<BlockNumber>>>,
     /// Execution stats
     pub(crate) stats: BlockExecutorStats,
 }
@@ -94,6 +97,7 @@ impl<'a> EVMProcessor<'a> {
             tip: None,
             prune_modes: PruneModes::none(),
             pruning_address_filter: None,
+            accessed_accounts: None,
             stats: BlockExecutorStats::default(),
         }
     }
@@ -127,6 +131,7 @@ impl<'a> EVMProcessor<'a> {
             tip: None,
             prune_modes: PruneModes::none(),
             pruning_address_filter: None,
+            accessed_accounts: None,
             stats: BlockExecutorStats::default(),
         }
     }
@@ -260,6 +265,21 @@ impl<'a> EVMProcessor<'a> {
         out.map_err(|e| BlockValidationError::EVM { hash, error: e.into() }.into())
     }
 
+    /// Records the accounts loaded by a transaction of the given block, if recording is enabled.
+    pub(crate) fn record_accessed_accounts<'b>(
+        &mut self,
+        block_number: BlockNumber,
+        accounts: impl IntoIterator<Item = &'b Address>,
+    ) {
+        let Some(accessed) = &mut self.accessed_accounts else { return };
+        for address in accounts {
+            let blocks = accessed.entry(*address).or_default();
+            if blocks.last() != Some(&block_number) {
+                blocks.push(block_number);
+            }
+        }
+    }
+
     /// Execute the block, verify gas usage and apply post-block state changes.
     pub(crate) fn execute_inner(
         &mut self,
@@ -446,6 +466,7 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
             self.stats.execution_duration += time.elapsed();
             let time = Instant::now();
 
+            self.record_accessed_accounts(block.number, state.keys());
             self.db_mut().commit(state);
 
             self.stats.apply_state_duration += time.elapsed();
@@ -486,6 +507,14 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
     fn size_hint(&self) -> Option<usize> {
         self.evm.db.as_ref().map(|db| db.bundle_size_hint())
     }
+
+    fn set_record_accessed_accounts(&mut self, record: bool) {
+        self.accessed_accounts = record.then(BTreeMap::new);
+    }
+
+    fn take_accessed_accounts(&mut self) -> BTreeMap<Address, Vec<BlockNumber>> {
+        self.accessed_accounts.as_mut().map(std::mem::take).unwrap_or_default()
+    }
 }
 
 impl<'a> PrunableBlockExecutor for EVMProcessor<'a> {
@@ -537,20 +566,22 @@ pub fn verify_receipt<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_rlp::Encodable;
     use reth_interfaces::provider::ProviderResult;
     use reth_primitives::{
         bytes,
         constants::{BEACON_ROOTS_ADDRESS, SYSTEM_ADDRESS},
         keccak256,
-        trie::AccountProof,
-        Account, Bytecode, Bytes, ChainSpecBuilder, ForkCondition, StorageKey, MAINNET,
+        trie::{AccountProof, HashBuilder, Nibbles, StorageProof, TrieAccount},
+        Account, Bytecode, Bytes, ChainSpecBuilder, ForkCondition, Signature, StorageKey,
+        Transaction, TransactionKind, TxLegacy, MAINNET,
     };
     use reth_provider::{
         AccountReader, BlockHashReader, BundleStateWithReceipts, StateRootProvider,
     };
     use reth_trie::updates::TrieUpdates;
     use revm::{Database, TransitionState};
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     static BEACON_ROOT_CONTRACT_CODE: Bytes = bytes!("3373fffffffffffffffffffffffffffffffffffffffe14604d57602036146024575f5ffd5b5f35801560495762001fff810690815414603c575f5ffd5b62001fff01545f5260205ff35b5f5ffd5b62001fff42064281555f359062001fff015500");
 
@@ -577,6 +608,111 @@ mod tests {
             }
             self.accounts.insert(address, (storage, account));
         }
+
+        /// Returns the accounts and storages by hashed keys with the bundle state applied on top.
+        fn hashed_state(
+            &self,
+            bundle_state: &BundleStateWithReceipts,
+        ) -> BTreeMap<B256, (Account, BTreeMap<B256, U256>)> {
+            let mut state = self
+                .accounts
+                .iter()
+                .map(|(address, (storage, account))| {
+                    let storage = storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(slot, value)| (keccak256(slot), *value))
+                        .collect();
+                    (keccak256(address), (*account, storage))
+                })
+                .collect::<BTreeMap<_, _>>();
+
+            let hashed_post_state = bundle_state.hash_state_slow();
+            for (hashed_address, account) in hashed_post_state.accounts() {
+                match account {
+                    Some(account) => state.entry(hashed_address).or_default().0 = account,
+                    None => {
+                        state.remove(&hashed_address);
+                    }
+                }
+            }
+            for (hashed_address, hashed_storage) in hashed_post_state.storages() {
+                let Some((_, storage)) = state.get_mut(hashed_address) else { continue };
+                if hashed_storage.wiped() {
+                    storage.clear();
+                }
+                for (hashed_slot, value) in hashed_storage.storage_slots() {
+                    if value.is_zero() {
+                        storage.remove(&hashed_slot);
+                    } else {
+                        storage.insert(hashed_slot, value);
+                    }
+                }
+            }
+            state
+        }
+
+        /// Computes the state root and the proofs of the given account and storage slots.
+        fn state_root_with_proof(
+            &self,
+            bundle_state: &BundleStateWithReceipts,
+            address: Address,
+            slots: &[B256],
+        ) -> (B256, AccountProof) {
+            let hashed_address = keccak256(address);
+            let target_nibbles = Nibbles::unpack(hashed_address);
+            let mut account_proof = AccountProof::new(address);
+
+            let mut hash_builder =
+                HashBuilder::default().with_proof_retainer(Vec::from([target_nibbles]));
+            for (hashed, (account, storage)) in self.hashed_state(bundle_state) {
+                let storage_root = if hashed == hashed_address {
+                    let (storage_root, storage_proofs) = storage_root_with_proofs(&storage, slots);
+                    account_proof.set_account(account, storage_root, storage_proofs);
+                    storage_root
+                } else {
+                    storage_root_with_proofs(&storage, &[]).0
+                };
+
+                let mut account_rlp = Vec::new();
+                TrieAccount::from((account, storage_root)).encode(&mut account_rlp);
+                hash_builder.add_leaf(Nibbles::unpack(hashed), &account_rlp);
+            }
+
+            let state_root = hash_builder.root();
+            account_proof.set_proof(hash_builder.take_proofs().into_values().collect());
+            (state_root, account_proof)
+        }
+    }
+
+    /// Computes the storage root and the proofs of the given slots.
+    fn storage_root_with_proofs(
+        storage: &BTreeMap<B256, U256>,
+        slots: &[B256],
+    ) -> (B256, Vec<StorageProof>) {
+        let mut proofs = slots.iter().copied().map(StorageProof::new).collect::<Vec<_>>();
+        let mut hash_builder = HashBuilder::default()
+            .with_proof_retainer(proofs.iter().map(|proof| proof.nibbles.clone()).collect());
+        for (hashed_slot, value) in storage {
+            let nibbles = Nibbles::unpack(hashed_slot);
+            if let Some(proof) = proofs.iter_mut().find(|proof| proof.nibbles == nibbles) {
+                proof.set_value(*value);
+            }
+            hash_builder.add_leaf(nibbles, alloy_rlp::encode_fixed_size(value).as_ref());
+        }
+
+        let storage_root = hash_builder.root();
+        let proof_nodes = hash_builder.take_proofs();
+        for proof in proofs.iter_mut() {
+            proof.set_proof(
+                proof_nodes
+                    .iter()
+                    .filter(|(path, _)| proof.nibbles.starts_with(path))
+                    .map(|(_, node)| node.clone())
+                    .collect(),
+            );
+        }
+        (storage_root, proofs)
     }
 
     impl AccountReader for StateProviderTest {
@@ -605,15 +741,25 @@ mod tests {
     }
 
     impl StateRootProvider for StateProviderTest {
-        fn state_root(&self, _bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
-            unimplemented!("state root computation is not supported")
+        fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+            Ok(self.state_root_with_proof(bundle_state, Address::ZERO, &[]).0)
         }
 
+        /// The in-memory state has no trie, so no trie updates are returned.
         fn state_root_with_updates(
             &self,
-            _bundle_state: &BundleStateWithReceipts,
+            bundle_state: &BundleStateWithReceipts,
         ) -> ProviderResult<(B256, TrieUpdates)> {
-            unimplemented!("state root computation is not supported")
+            Ok((self.state_root(bundle_state)?, TrieUpdates::default()))
+        }
+
+        fn state_proof(
+            &self,
+            bundle_state: &BundleStateWithReceipts,
+            address: Address,
+            keys: &[B256],
+        ) -> ProviderResult<AccountProof> {
+            Ok(self.state_root_with_proof(bundle_state, address, keys).1)
         }
     }
 
@@ -633,8 +779,8 @@ mod tests {
             Ok(self.contracts.get(&code_hash).cloned())
         }
 
-        fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
-            unimplemented!("proof generation is not supported")
+        fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+            self.state_proof(&BundleStateWithReceipts::default(), address, keys)
         }
     }
 
@@ -1008,4 +1154,110 @@ mod tests {
             .unwrap();
         assert_eq!(parent_beacon_block_root_storage, U256::from(0x69));
     }
+
+    #[test]
+    fn state_provider_test_root_and_proof() {
+        let address = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let slot = B256::with_last_byte(1);
+        let account = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+        let changed = Account { nonce: 2, ..account };
+
+        let mut db = StateProviderTest::default();
+        db.insert_account(address, account, None, HashMap::from([(slot, U256::from(5))]));
+        db.insert_account(other, account, None, HashMap::new());
+
+        let bundle_state = BundleStateWithReceipts::new_init(
+            HashMap::from([(
+                other,
+                (
+                    Some(account),
+                    Some(changed),
+                    HashMap::from([(slot, (U256::ZERO, U256::from(7)))]),
+                ),
+            )]),
+            HashMap::new(),
+            vec![],
+            Receipts::new(),
+            0,
+        );
+
+        // the bundle state is applied on top of the in-memory state
+        let expected = reth_trie::test_utils::state_root(
+            [
+                (address, (account, vec![(slot, U256::from(5))])),
+                (other, (changed, vec![(slot, U256::from(7))])),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(db.state_root(&bundle_state), Ok(expected));
+
+        let proof = db.state_proof(&bundle_state, other, &[slot]).unwrap();
+        assert_eq!(proof.info, Some(changed));
+        assert_eq!(
+            proof.storage_root,
+            reth_trie::test_utils::storage_root([(slot, U256::from(7))].into_iter())
+        );
+        assert_eq!(proof.storage_proofs[0].value, U256::from(7));
+        // the first node of the account proof is the root node
+        assert_eq!(keccak256(&proof.proof[0]), expected);
+
+        let proof = db.proof(address, &[slot]).unwrap();
+        assert_eq!(proof.info, Some(account));
+        assert_eq!(proof.storage_proofs[0].value, U256::from(5));
+    }
+
+    #[test]
+    fn records_accessed_accounts() {
+        let sender = Address::with_last_byte(0xa1);
+        let caller = Address::with_last_byte(0xa2);
+        let callee = Address::with_last_byte(0xa3);
+
+        // STATICCALL(gas, callee, 0, 0, 0, 0), which neither changes state nor emits a log
+        let mut code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+        code.extend_from_slice(callee.as_slice());
+        code.extend_from_slice(&[0x5a, 0xfa, 0x00]);
+
+        let mut db = StateProviderTest::default();
+        db.insert_account(caller, Account::default(), Some(code.into()), HashMap::new());
+
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build());
+        let mut executor = EVMProcessor::new_with_db(chain_spec, StateProviderDatabase::new(db));
+        executor.set_record_accessed_accounts(true);
+
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy {
+                gas_limit: 100_000,
+                to: TransactionKind::Call(caller),
+                ..Default::default()
+            }),
+            Signature::default(),
+        );
+        let header = Header {
+            number: 1,
+            gas_limit: 1_000_000,
+            base_fee_per_gas: Some(0),
+            ..Default::default()
+        };
+        executor
+            .execute_transactions(
+                &BlockWithSenders {
+                    block: Block {
+                        header,
+                        body: vec![transaction],
+                        ommers: vec![],
+                        withdrawals: None,
+                    },
+                    senders: vec![sender],
+                },
+                U256::ZERO,
+            )
+            .unwrap();
+
+        let accessed = executor.take_accessed_accounts();
+        for address in [sender, caller, callee] {
+            assert_eq!(accessed.get(&address), Some(&vec![1]));
+        }
+        assert!(executor.take_accessed_accounts().is_empty());
+    }
 }
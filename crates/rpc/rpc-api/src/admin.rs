@@ -1,6 +1,7 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_primitives::NodeRecord;
-use reth_rpc_types::{NodeInfo, PeerInfo};
+use reth_rpc_types::{Bans, NodeInfo, PeerInfo};
+use std::net::IpAddr;
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -28,6 +29,39 @@ pub trait AdminApi {
     #[method(name = "removeTrustedPeer")]
     fn remove_trusted_peer(&self, record: NodeRecord) -> RpcResult<bool>;
 
+    /// Adds the given node record to the allowlist of a permissioned network.
+    ///
+    /// Returns false if the network is not permissioned.
+    #[method(name = "addAllowedPeer")]
+    fn add_allowed_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+
+    /// Removes a remote node from the allowlist of a permissioned network and disconnects it.
+    ///
+    /// Returns true if the node was allow-listed.
+    #[method(name = "removeAllowedPeer")]
+    fn remove_allowed_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+
+    /// Returns all banned peers and IP addresses.
+    #[method(name = "bans")]
+    async fn bans(&self) -> RpcResult<Bans>;
+
+    /// Bans a remote node for the given number of seconds, or indefinitely, and disconnects it.
+    #[method(name = "banPeer")]
+    fn ban_peer(&self, record: NodeRecord, duration: Option<u64>) -> RpcResult<bool>;
+
+    /// Lifts the ban of a remote node.
+    #[method(name = "unbanPeer")]
+    fn unban_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+
+    /// Bans an IP address for the given number of seconds, or indefinitely, and disconnects all
+    /// remote nodes using it.
+    #[method(name = "banIp")]
+    fn ban_ip(&self, ip: IpAddr, duration: Option<u64>) -> RpcResult<bool>;
+
+    /// Lifts the ban of an IP address.
+    #[method(name = "unbanIp")]
+    fn unban_ip(&self, ip: IpAddr) -> RpcResult<bool>;
+
     /// The peers administrative property can be queried for all the information known about the
     /// connected remote nodes at the networking granularity. These include general information
     /// about the nodes themselves as participants of the devp2p P2P overlay protocol, as well as
@@ -25,6 +25,7 @@ mod eth_pubsub;
 mod mev;
 mod net;
 mod otterscan;
+mod personal;
 mod reth;
 mod rpc;
 mod trace;
@@ -48,6 +49,7 @@ pub mod servers {
         mev::MevApiServer,
         net::NetApiServer,
         otterscan::OtterscanServer,
+        personal::PersonalApiServer,
         reth::RethApiServer,
         rpc::RpcApiServer,
         trace::TraceApiServer,
@@ -74,6 +76,7 @@ pub mod clients {
         mev::MevApiClient,
         net::NetApiClient,
         otterscan::OtterscanClient,
+        personal::PersonalApiClient,
         rpc::RpcApiServer,
         trace::TraceApiClient,
         txpool::TxPoolApiClient,
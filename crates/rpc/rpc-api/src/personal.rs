@@ -0,0 +1,33 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_primitives::{Address, Bytes};
+
+/// Personal rpc interface for managing the accounts of the keystore.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "personal"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "personal"))]
+#[async_trait::async_trait]
+pub trait PersonalApi {
+    /// Returns the addresses of all accounts in the keystore.
+    #[method(name = "listAccounts")]
+    async fn list_accounts(&self) -> RpcResult<Vec<Address>>;
+
+    /// Unlocks the account with the given password for `duration` seconds.
+    ///
+    /// Defaults to 300 seconds if no duration is given, `0` unlocks the account until the node
+    /// shuts down.
+    #[method(name = "unlockAccount")]
+    async fn unlock_account(
+        &self,
+        address: Address,
+        password: String,
+        duration: Option<u64>,
+    ) -> RpcResult<bool>;
+
+    /// Locks the account, returns `false` if the account is unknown.
+    #[method(name = "lockAccount")]
+    async fn lock_account(&self, address: Address) -> RpcResult<bool>;
+
+    /// Returns an Ethereum specific signature with: sign(keccak256("\x19Ethereum Signed Message:\n"
+    /// + len(message) + message))), using the key decrypted with the given password.
+    #[method(name = "sign")]
+    async fn sign(&self, message: Bytes, address: Address, password: String) -> RpcResult<Bytes>;
+}
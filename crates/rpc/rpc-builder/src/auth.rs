@@ -12,8 +12,8 @@ use jsonrpsee::{
 };
 use reth_network_api::{NetworkInfo, Peers};
 use reth_provider::{
-    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, ReceiptProviderIdExt,
-    StateProviderFactory,
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, LogIndexReader,
+    ReceiptProviderIdExt, StateProviderFactory,
 };
 use reth_rpc::{
     eth::{
@@ -47,6 +47,7 @@ where
         + ChainSpecProvider
         + EvmEnvProvider
         + HeaderProvider
+        + LogIndexReader
         + ReceiptProviderIdExt
         + StateProviderFactory
         + Clone
@@ -72,6 +73,7 @@ where
         eth_cache.clone(),
         gas_oracle,
         EthConfig::default().rpc_gas_cap,
+        EthConfig::default().eth_proof_window,
         Box::new(executor.clone()),
         BlockingTaskPool::build().expect("failed to build tracing pool"),
         fee_history_cache,
@@ -97,6 +99,7 @@ where
         + ChainSpecProvider
         + EvmEnvProvider
         + HeaderProvider
+        + LogIndexReader
         + StateProviderFactory
         + Clone
         + Unpin
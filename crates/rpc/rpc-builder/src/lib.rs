@@ -19,8 +19,9 @@
 //! ```
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
-//!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     AccountReader, AddressAppearanceReader, BlockReaderIdExt, CanonStateSubscriptions,
+//!     ChainSpecProvider, ChangeSetReader, EvmEnvProvider, LogIndexReader, StateProviderFactory,
+//!     StateRangeReader,
 //! };
 //! use reth_rpc_builder::{
 //!     RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
@@ -37,6 +38,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + AddressAppearanceReader
+//!         + LogIndexReader
+//!         + StateRangeReader
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -70,8 +74,9 @@
 //! ```
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
-//!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     AccountReader, AddressAppearanceReader, BlockReaderIdExt, CanonStateSubscriptions,
+//!     ChainSpecProvider, ChangeSetReader, EvmEnvProvider, LogIndexReader, StateProviderFactory,
+//!     StateRangeReader,
 //! };
 //! use reth_rpc::JwtSecret;
 //! use reth_rpc_api::EngineApiServer;
@@ -93,6 +98,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + AddressAppearanceReader
+//!         + LogIndexReader
+//!         + StateRangeReader
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -152,7 +160,10 @@ use jsonrpsee::{
 };
 use serde::{Deserialize, Serialize, Serializer};
 use strum::{AsRefStr, EnumIter, EnumVariantNames, IntoStaticStr, ParseError, VariantNames};
-use tower::layer::util::{Identity, Stack};
+use tower::{
+    layer::util::{Identity, Stack},
+    util::Either,
+};
 use tower_http::cors::CorsLayer;
 use tracing::{instrument, trace};
 
@@ -161,20 +172,23 @@ use error::{RpcError, ServerKind};
 use reth_ipc::server::IpcServer;
 pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{noop::NoopNetwork, NetworkInfo, Peers};
+use reth_payload_builder::BundlePool;
 use reth_provider::{
-    AccountReader, BlockReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-    ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+    AccountReader, AddressAppearanceReader, BadBlockStore, BlockReader, BlockReaderIdExt,
+    CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, EvmEnvProvider, LogIndexReader,
+    StateProviderFactory, StateRangeReader,
 };
 use reth_rpc::{
     eth::{
         cache::{cache_new_blocks_task, EthStateCache},
         fee_history_cache_new_blocks_task,
         gas_oracle::GasPriceOracle,
-        EthBundle, FeeHistoryCache,
+        EthBundle, EthBundleRelay, FeeHistoryCache, KeystoreSigner,
     },
     AdminApi, AuthLayer, BlockingTaskGuard, BlockingTaskPool, Claims, DebugApi, EngineEthApi,
-    EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, JwtAuthValidator, JwtSecret, NetApi,
-    OtterscanApi, RPCApi, RethApi, TraceApi, TxPoolApi, Web3Api,
+    EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, JwtAuthValidator, JwtSecret, MevApi,
+    NetApi, OtterscanApi, PersonalApi, RPCApi, RateLimitConfig, RateLimitLayer, RateLimitLogger,
+    RethApi, TraceApi, TxPoolApi, ValidationApi, ValidationApiConfig, Web3Api,
 };
 use reth_rpc_api::{servers::*, EngineApiServer};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
@@ -222,6 +236,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AddressAppearanceReader
+        + LogIndexReader
+        + StateRangeReader
         + Clone
         + Unpin
         + 'static,
@@ -253,6 +270,12 @@ pub struct RpcModuleBuilder<Provider, Pool, Network, Tasks, Events> {
     executor: Tasks,
     /// Provides access to chain events, such as new blocks, required by pubsub.
     events: Events,
+    /// The pool that stores the bundles received via the `mev_` and `eth_` bundle namespaces.
+    bundle_pool: BundlePool,
+    /// The store of recently rejected blocks served by the `debug_` namespace.
+    bad_blocks: BadBlockStore,
+    /// The keystore whose accounts are used for signing and managed by the `personal_` namespace.
+    keystore: KeystoreSigner,
 }
 
 // === impl RpcBuilder ===
@@ -268,7 +291,36 @@ impl<Provider, Pool, Network, Tasks, Events>
         executor: Tasks,
         events: Events,
     ) -> Self {
-        Self { provider, pool, network, executor, events }
+        Self {
+            provider,
+            pool,
+            network,
+            executor,
+            events,
+            bundle_pool: BundlePool::new(),
+            bad_blocks: BadBlockStore::default(),
+            keystore: KeystoreSigner::default(),
+        }
+    }
+
+    /// Configure the pool that stores received bundles, so it can be shared with the payload
+    /// builder.
+    pub fn with_bundle_pool(mut self, bundle_pool: BundlePool) -> Self {
+        self.bundle_pool = bundle_pool;
+        self
+    }
+
+    /// Configure the store of rejected blocks, so it can be shared with the blockchain tree and the
+    /// consensus engine.
+    pub fn with_bad_blocks(mut self, bad_blocks: BadBlockStore) -> Self {
+        self.bad_blocks = bad_blocks;
+        self
+    }
+
+    /// Configure the keystore whose accounts can sign via the `eth_` and `personal_` namespaces.
+    pub fn with_keystore(mut self, keystore: KeystoreSigner) -> Self {
+        self.keystore = keystore;
+        self
     }
 
     /// Configure the provider instance.
@@ -276,8 +328,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
     {
-        let Self { pool, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, executor, events, bundle_pool, bad_blocks, keystore, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            executor,
+            events,
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure the transaction pool instance.
@@ -285,8 +346,18 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: TransactionPool + 'static,
     {
-        let Self { provider, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, network, executor, events, bundle_pool, bad_blocks, keystore, .. } =
+            self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            executor,
+            events,
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure a [NoopTransactionPool] instance.
@@ -297,13 +368,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_noop_pool(
         self,
     ) -> RpcModuleBuilder<Provider, NoopTransactionPool, Network, Tasks, Events> {
-        let Self { provider, executor, events, network, .. } = self;
+        let Self { provider, executor, events, network, bundle_pool, bad_blocks, keystore, .. } =
+            self;
         RpcModuleBuilder {
             provider,
             executor,
             events,
             network,
             pool: NoopTransactionPool::default(),
+            bundle_pool,
+            bad_blocks,
+            keystore,
         }
     }
 
@@ -312,8 +387,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, events, bundle_pool, bad_blocks, keystore, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            executor,
+            events,
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure a [NoopNetwork] instance.
@@ -322,8 +406,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     /// This is only intended for allow easier setup of namespaces that depend on the [EthApi] which
     /// requires a [NetworkInfo] implementation.
     pub fn with_noop_network(self) -> RpcModuleBuilder<Provider, Pool, NoopNetwork, Tasks, Events> {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, pool, executor, events, network: NoopNetwork::default() }
+        let Self { provider, pool, executor, events, bundle_pool, bad_blocks, keystore, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            pool,
+            executor,
+            events,
+            network: NoopNetwork::default(),
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure the task executor to use for additional tasks.
@@ -334,8 +427,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         T: TaskSpawner + 'static,
     {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, provider, events, bundle_pool, bad_blocks, keystore, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            executor,
+            events,
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure [TokioTaskExecutor] as the task executor to use for additional tasks.
@@ -345,8 +447,17 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_tokio_executor(
         self,
     ) -> RpcModuleBuilder<Provider, Pool, Network, TokioTaskExecutor, Events> {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, events, executor: TokioTaskExecutor::default() }
+        let Self { pool, network, provider, events, bundle_pool, bad_blocks, keystore, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            events,
+            executor: TokioTaskExecutor::default(),
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 
     /// Configure the event subscriber instance
@@ -354,8 +465,18 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         E: CanonStateSubscriptions + 'static,
     {
-        let Self { provider, pool, executor, network, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, network, bundle_pool, bad_blocks, keystore, .. } =
+            self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            executor,
+            events,
+            bundle_pool,
+            bad_blocks,
+            keystore,
+        }
     }
 }
 
@@ -368,6 +489,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AddressAppearanceReader
+        + LogIndexReader
+        + StateRangeReader
         + Clone
         + Unpin
         + 'static,
@@ -395,7 +519,8 @@ where
     {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, bundle_pool, bad_blocks, keystore } =
+            self;
 
         let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
 
@@ -407,11 +532,17 @@ where
             events,
             config.unwrap_or_default(),
         );
+        registry.set_bundle_pool(bundle_pool);
+        registry.set_bad_blocks(bad_blocks);
+        registry.set_keystore(keystore);
 
         modules.config = module_config;
         modules.http = registry.maybe_module(http.as_ref());
         modules.ws = registry.maybe_module(ws.as_ref());
         modules.ipc = registry.maybe_module(ipc.as_ref());
+        if !registry.config.allow_insecure_unlock {
+            modules.refuse_insecure_unlock();
+        }
 
         let auth_module = registry.create_auth_module(engine);
 
@@ -445,8 +576,14 @@ where
         self,
         config: RpcModuleConfig,
     ) -> RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
-        let Self { provider, pool, network, executor, events } = self;
-        RethModuleRegistry::new(provider, pool, network, executor, events, config)
+        let Self { provider, pool, network, executor, events, bundle_pool, bad_blocks, keystore } =
+            self;
+        let mut registry =
+            RethModuleRegistry::new(provider, pool, network, executor, events, config);
+        registry.set_bundle_pool(bundle_pool);
+        registry.set_bad_blocks(bad_blocks);
+        registry.set_keystore(keystore);
+        registry
     }
 
     /// Configures all [RpcModule]s specific to the given [TransportRpcModuleConfig] which can be
@@ -456,7 +593,8 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, bundle_pool, bad_blocks, keystore } =
+            self;
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
@@ -469,11 +607,17 @@ where
                 events,
                 config.unwrap_or_default(),
             );
+            registry.set_bundle_pool(bundle_pool);
+            registry.set_bad_blocks(bad_blocks);
+            registry.set_keystore(keystore);
 
             modules.config = module_config;
             modules.http = registry.maybe_module(http.as_ref());
             modules.ws = registry.maybe_module(ws.as_ref());
             modules.ipc = registry.maybe_module(ipc.as_ref());
+            if !registry.config.allow_insecure_unlock {
+                modules.refuse_insecure_unlock();
+            }
         }
 
         modules
@@ -491,6 +635,12 @@ impl Default for RpcModuleBuilder<(), (), (), (), ()> {
 pub struct RpcModuleConfig {
     /// `eth` namespace settings
     eth: EthConfig,
+    /// `flashbots` namespace settings
+    #[serde(default)]
+    flashbots: ValidationApiConfig,
+    /// Whether `personal_unlockAccount` is served over http and ws.
+    #[serde(default)]
+    allow_insecure_unlock: bool,
 }
 
 // === impl RpcModuleConfig ===
@@ -502,7 +652,7 @@ impl RpcModuleConfig {
     }
     /// Returns a new RPC module config given the eth namespace config
     pub fn new(eth: EthConfig) -> Self {
-        Self { eth }
+        Self { eth, flashbots: Default::default(), allow_insecure_unlock: false }
     }
 }
 
@@ -510,6 +660,8 @@ impl RpcModuleConfig {
 #[derive(Clone, Debug, Default)]
 pub struct RpcModuleConfigBuilder {
     eth: Option<EthConfig>,
+    flashbots: Option<ValidationApiConfig>,
+    allow_insecure_unlock: bool,
 }
 
 // === impl RpcModuleConfigBuilder ===
@@ -521,10 +673,29 @@ impl RpcModuleConfigBuilder {
         self
     }
 
+    /// Configures a custom flashbots namespace config
+    pub fn flashbots(mut self, flashbots: ValidationApiConfig) -> Self {
+        self.flashbots = Some(flashbots);
+        self
+    }
+
+    /// Allows unlocking accounts via `personal_unlockAccount` over http and ws.
+    ///
+    /// By default accounts can only be unlocked over ipc, since an unlocked account can be used by
+    /// anyone who can reach the http or ws server.
+    pub fn allow_insecure_unlock(mut self, allow: bool) -> Self {
+        self.allow_insecure_unlock = allow;
+        self
+    }
+
     /// Consumes the type and creates the [RpcModuleConfig]
     pub fn build(self) -> RpcModuleConfig {
-        let RpcModuleConfigBuilder { eth } = self;
-        RpcModuleConfig { eth: eth.unwrap_or_default() }
+        let RpcModuleConfigBuilder { eth, flashbots, allow_insecure_unlock } = self;
+        RpcModuleConfig {
+            eth: eth.unwrap_or_default(),
+            flashbots: flashbots.unwrap_or_default(),
+            allow_insecure_unlock,
+        }
     }
 }
 
@@ -654,6 +825,9 @@ impl RpcModuleSelection {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + AddressAppearanceReader
+            + LogIndexReader
+            + StateRangeReader
             + Clone
             + Unpin
             + 'static,
@@ -780,6 +954,20 @@ pub enum RethRpcModule {
     /// This is separate from [RethRpcModule::Eth] because it is a non standardized call that
     /// should be opt-in.
     EthCallBundle,
+    /// The full non-standard `eth_` bundle namespace: `eth_sendBundle`, `eth_callBundle`,
+    /// `eth_cancelBundle` and the private transaction calls.
+    ///
+    /// This includes `eth_callBundle`, so [RethRpcModule::EthCallBundle] is redundant if this is
+    /// selected.
+    EthBundle,
+    /// `mev_` module for MEV-Share bundles
+    Mev,
+    /// `flashbots_` module for validating the block submissions of builders
+    ///
+    /// This is meant to be used by relays and should be opt-in.
+    Flashbots,
+    /// `personal_` module for managing the accounts of the keystore
+    Personal,
 }
 
 // === impl RethRpcModule ===
@@ -819,6 +1007,10 @@ impl FromStr for RethRpcModule {
             "reth" => RethRpcModule::Reth,
             "ots" => RethRpcModule::Ots,
             "eth-call-bundle" | "eth_callBundle" => RethRpcModule::EthCallBundle,
+            "eth-bundle" => RethRpcModule::EthBundle,
+            "mev" => RethRpcModule::Mev,
+            "flashbots" => RethRpcModule::Flashbots,
+            "personal" => RethRpcModule::Personal,
             _ => return Err(ParseError::VariantNotFound),
         })
     }
@@ -860,6 +1052,12 @@ pub struct RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
     eth: Option<EthHandlers<Provider, Pool, Network, Events>>,
     /// to put trace calls behind semaphore
     blocking_pool_guard: BlockingTaskGuard,
+    /// Bundles received via the `mev_` namespace
+    bundle_pool: BundlePool,
+    /// Recently rejected blocks served by the `debug_` namespace
+    bad_blocks: BadBlockStore,
+    /// The keystore used for signing, managed by the `personal_` namespace
+    keystore: KeystoreSigner,
     /// Contains the [Methods] of a module
     modules: HashMap<RethRpcModule, Methods>,
 }
@@ -886,6 +1084,9 @@ impl<Provider, Pool, Network, Tasks, Events>
             executor,
             modules: Default::default(),
             blocking_pool_guard: BlockingTaskGuard::new(config.eth.max_tracing_requests),
+            bundle_pool: BundlePool::new(),
+            bad_blocks: BadBlockStore::default(),
+            keystore: KeystoreSigner::default(),
             config,
             events,
         }
@@ -911,6 +1112,49 @@ impl<Provider, Pool, Network, Tasks, Events>
         &self.provider
     }
 
+    /// Returns a reference to the pool that stores the bundles received via the `mev_` and `eth_`
+    /// bundle namespaces
+    pub fn bundle_pool(&self) -> &BundlePool {
+        &self.bundle_pool
+    }
+
+    /// Configures the pool that stores the bundles received via the `mev_` and `eth_` bundle
+    /// namespaces, so it can be shared with the payload builder.
+    pub fn set_bundle_pool(&mut self, bundle_pool: BundlePool) -> &mut Self {
+        self.bundle_pool = bundle_pool;
+        self
+    }
+
+    /// Returns a reference to the store of recently rejected blocks
+    pub fn bad_blocks(&self) -> &BadBlockStore {
+        &self.bad_blocks
+    }
+
+    /// Configures the store of recently rejected blocks, so it can be shared with the blockchain
+    /// tree and the consensus engine.
+    pub fn set_bad_blocks(&mut self, bad_blocks: BadBlockStore) -> &mut Self {
+        self.bad_blocks = bad_blocks;
+        self
+    }
+
+    /// Returns a reference to the keystore used for signing
+    pub fn keystore(&self) -> &KeystoreSigner {
+        &self.keystore
+    }
+
+    /// Configures the keystore whose accounts can sign via the `eth_` and `personal_` namespaces.
+    ///
+    /// Note: this must be configured before the `eth_` handlers are created.
+    pub fn set_keystore(&mut self, keystore: KeystoreSigner) -> &mut Self {
+        self.keystore = keystore;
+        self
+    }
+
+    /// Returns a reference to the guard that limits the number of concurrent tracing calls
+    pub fn blocking_task_guard(&self) -> &BlockingTaskGuard {
+        &self.blocking_pool_guard
+    }
+
     /// Returns all installed methods
     pub fn methods(&self) -> Vec<Methods> {
         self.modules.values().cloned().collect()
@@ -965,6 +1209,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AddressAppearanceReader
+        + LogIndexReader
+        + StateRangeReader
         + Clone
         + Unpin
         + 'static,
@@ -1119,6 +1366,7 @@ where
                             eth_api.clone(),
                             Box::new(self.executor.clone()),
                             self.blocking_pool_guard.clone(),
+                            self.bad_blocks.clone(),
                         )
                         .into_rpc()
                         .into(),
@@ -1152,17 +1400,63 @@ where
                         )
                         .into_rpc()
                         .into(),
-                        RethRpcModule::Ots => OtterscanApi::new(eth_api.clone()).into_rpc().into(),
+                        RethRpcModule::Ots => {
+                            OtterscanApi::new(self.provider.clone(), eth_api.clone())
+                                .into_rpc()
+                                .into()
+                        }
                         RethRpcModule::Reth => {
                             RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
                                 .into_rpc()
                                 .into()
                         }
                         RethRpcModule::EthCallBundle => {
+                            if namespaces.contains(&RethRpcModule::EthBundle) {
+                                // `eth_callBundle` is already served by the full bundle namespace
+                                return Methods::new()
+                            }
                             EthBundle::new(eth_api.clone(), self.blocking_pool_guard.clone())
                                 .into_rpc()
                                 .into()
                         }
+                        RethRpcModule::EthBundle => {
+                            let relay = EthBundleRelay::new(
+                                self.provider.clone(),
+                                self.pool.clone(),
+                                eth_api.clone(),
+                                self.bundle_pool.clone(),
+                                self.blocking_pool_guard.clone(),
+                            );
+                            self.executor.spawn(Box::pin(
+                                relay.clone().maintain(self.events.canonical_state_stream()),
+                            ));
+                            relay.into_rpc().into()
+                        }
+                        RethRpcModule::Mev => {
+                            let mev = MevApi::new(
+                                self.provider.clone(),
+                                eth_api.clone(),
+                                self.pool.clone(),
+                                self.bundle_pool.clone(),
+                                self.blocking_pool_guard.clone(),
+                            );
+                            self.executor.spawn(Box::pin(
+                                mev.clone().maintain(self.events.canonical_state_stream()),
+                            ));
+                            mev.into_rpc().into()
+                        }
+                        RethRpcModule::Flashbots => ValidationApi::new(
+                            self.provider.clone(),
+                            self.config.flashbots.clone(),
+                            Box::new(self.executor.clone()),
+                        )
+                        .into_rpc()
+                        .into(),
+                        RethRpcModule::Personal => {
+                            PersonalApi::new(self.keystore.clone(), Box::new(self.executor.clone()))
+                                .into_rpc()
+                                .into()
+                        }
                     })
                     .clone()
             })
@@ -1230,10 +1524,12 @@ where
                 cache.clone(),
                 gas_oracle,
                 self.config.eth.rpc_gas_cap,
+                self.config.eth.eth_proof_window,
                 executor.clone(),
                 blocking_task_pool.clone(),
                 fee_history_cache,
             );
+            api.add_keystore(self.keystore.clone());
             let filter = EthFilter::new(
                 self.provider.clone(),
                 self.pool.clone(),
@@ -1296,14 +1592,62 @@ where
         EthBundle::new(eth_api, self.blocking_pool_guard.clone())
     }
 
+    /// Instantiates [EthBundleRelay] Api and spawns the task that evicts its expired bundles and
+    /// private transactions
+    ///
+    /// # Panics
+    ///
+    /// If called outside of the tokio runtime. See also [Self::eth_api]
+    pub fn bundle_relay_api(
+        &mut self,
+    ) -> EthBundleRelay<Provider, Pool, EthApi<Provider, Pool, Network>> {
+        let eth_api = self.eth_api();
+        let relay = EthBundleRelay::new(
+            self.provider.clone(),
+            self.pool.clone(),
+            eth_api,
+            self.bundle_pool.clone(),
+            self.blocking_pool_guard.clone(),
+        );
+        self.executor.spawn(Box::pin(relay.clone().maintain(self.events.canonical_state_stream())));
+        relay
+    }
+
+    /// Instantiates [MevApi] and spawns the task that evicts expired bundles from the bundle pool
+    ///
+    /// # Panics
+    ///
+    /// If called outside of the tokio runtime. See also [Self::eth_api]
+    pub fn mev_api(&mut self) -> MevApi<Provider, EthApi<Provider, Pool, Network>, Pool> {
+        let eth_api = self.eth_api();
+        let mev = MevApi::new(
+            self.provider.clone(),
+            eth_api,
+            self.pool.clone(),
+            self.bundle_pool.clone(),
+            self.blocking_pool_guard.clone(),
+        );
+        self.executor.spawn(Box::pin(mev.clone().maintain(self.events.canonical_state_stream())));
+        mev
+    }
+
+    /// Instantiates [ValidationApi]
+    pub fn validation_api(&mut self) -> ValidationApi<Provider> {
+        ValidationApi::new(
+            self.provider.clone(),
+            self.config.flashbots.clone(),
+            Box::new(self.executor.clone()),
+        )
+    }
+
     /// Instantiates OtterscanApi
     ///
     /// # Panics
     ///
     /// If called outside of the tokio runtime. See also [Self::eth_api]
-    pub fn otterscan_api(&mut self) -> OtterscanApi<EthApi<Provider, Pool, Network>> {
+    pub fn otterscan_api(&mut self) -> OtterscanApi<Provider, EthApi<Provider, Pool, Network>> {
         let eth_api = self.eth_api();
-        OtterscanApi::new(eth_api)
+        OtterscanApi::new(self.provider.clone(), eth_api)
     }
 
     /// Instantiates DebugApi
@@ -1318,6 +1662,7 @@ where
             eth_api,
             Box::new(self.executor.clone()),
             self.blocking_pool_guard.clone(),
+            self.bad_blocks.clone(),
         )
     }
 
@@ -1335,6 +1680,11 @@ where
     pub fn reth_api(&mut self) -> RethApi<Provider> {
         RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
     }
+
+    /// Instantiates PersonalApi
+    pub fn personal_api(&mut self) -> PersonalApi {
+        PersonalApi::new(self.keystore.clone(), Box::new(self.executor.clone()))
+    }
 }
 
 /// A builder type for configuring and launching the servers that will handle RPC requests.
@@ -1368,6 +1718,10 @@ pub struct RpcServerConfig {
     ipc_endpoint: Option<Endpoint>,
     /// JWT secret for authentication
     jwt_secret: Option<JwtSecret>,
+    /// Rate limit settings of the http and ws servers
+    rate_limit: Option<RateLimitConfig>,
+    /// Guard that limits the number of concurrent tracing calls
+    tracing_guard: Option<BlockingTaskGuard>,
 }
 
 impl fmt::Debug for RpcServerConfig {
@@ -1381,6 +1735,8 @@ impl fmt::Debug for RpcServerConfig {
             .field("ipc_server_config", &self.ipc_server_config)
             .field("ipc_endpoint", &self.ipc_endpoint.as_ref().map(|endpoint| endpoint.path()))
             .field("jwt_secret", &self.jwt_secret)
+            .field("rate_limit", &self.rate_limit)
+            .field("tracing_guard", &self.tracing_guard)
             .finish()
     }
 }
@@ -1498,6 +1854,35 @@ impl RpcServerConfig {
         self
     }
 
+    /// Configures the rate limit of the http and ws servers.
+    ///
+    /// See also [RateLimitLayer]
+    pub fn with_rate_limit(mut self, config: Option<RateLimitConfig>) -> Self {
+        self.rate_limit = config;
+        self
+    }
+
+    /// Configures the guard that limits the number of concurrent tracing calls, see
+    /// [RethModuleRegistry::blocking_task_guard].
+    ///
+    /// If rate limiting is enabled, tracing calls are rejected right away while all permits are
+    /// taken.
+    pub fn with_tracing_guard(mut self, guard: BlockingTaskGuard) -> Self {
+        self.tracing_guard = Some(guard);
+        self
+    }
+
+    /// Returns the rate limit layer for a server that serves the given module.
+    fn rate_limit_layer(&self, module: Option<&RpcModule<()>>) -> Option<RateLimitLayer> {
+        let config = self.rate_limit.clone()?;
+        let methods = module.map(|module| module.method_names()).into_iter().flatten();
+        let mut layer = RateLimitLayer::new(config, methods);
+        if let Some(guard) = self.tracing_guard.clone() {
+            layer = layer.with_tracing_guard(guard);
+        }
+        Some(layer)
+    }
+
     /// Returns true if any server is configured.
     ///
     /// If no server is configured, no server will be be launched on [RpcServerConfig::start].
@@ -1574,11 +1959,13 @@ impl RpcServerConfig {
             modules.config.ensure_ws_http_identical()?;
 
             let builder = self.http_server_config.take().expect("is set; qed");
+            let rate_limit = self.rate_limit_layer(modules.http.as_ref().or(modules.ws.as_ref()));
             let (server, addr) = WsHttpServerKind::build(
                 builder,
                 http_socket_addr,
                 cors,
                 secret,
+                rate_limit.clone(),
                 ServerKind::WsHttp(http_socket_addr),
                 modules
                     .http
@@ -1593,6 +1980,7 @@ impl RpcServerConfig {
                 ws_local_addr: Some(addr),
                 server: WsHttpServers::SamePort(server),
                 jwt_secret,
+                ws_rate_limit: rate_limit,
             })
         }
 
@@ -1601,13 +1989,16 @@ impl RpcServerConfig {
 
         let mut ws_local_addr = None;
         let mut ws_server = None;
+        let mut ws_rate_limit = None;
         if let Some(builder) = self.ws_server_config.take() {
             let builder = builder.ws_only();
+            ws_rate_limit = self.rate_limit_layer(modules.ws.as_ref());
             let (server, addr) = WsHttpServerKind::build(
                 builder,
                 ws_socket_addr,
                 self.ws_cors_domains.take(),
                 self.jwt_secret.clone(),
+                ws_rate_limit.clone(),
                 ServerKind::WS(ws_socket_addr),
                 modules.ws.as_ref().map(RpcServerMetrics::new).unwrap_or_default(),
             )
@@ -1623,6 +2014,7 @@ impl RpcServerConfig {
                 http_socket_addr,
                 self.http_cors_domains.take(),
                 self.jwt_secret.clone(),
+                self.rate_limit_layer(modules.http.as_ref()),
                 ServerKind::Http(http_socket_addr),
                 modules.http.as_ref().map(RpcServerMetrics::new).unwrap_or_default(),
             )
@@ -1636,6 +2028,7 @@ impl RpcServerConfig {
             ws_local_addr,
             server: WsHttpServers::DifferentPort { http: http_server, ws: ws_server },
             jwt_secret,
+            ws_rate_limit,
         })
     }
 
@@ -1827,6 +2220,18 @@ impl TransportRpcModules {
         Ok(false)
     }
 
+    /// Replaces `personal_unlockAccount` of the http and ws modules with a handler that refuses to
+    /// unlock accounts, see [PersonalApi::insecure_unlock_refused].
+    fn refuse_insecure_unlock(&mut self) {
+        for module in [self.http.as_mut(), self.ws.as_mut()].into_iter().flatten() {
+            if module.remove_method(PersonalApi::UNLOCK_ACCOUNT_METHOD).is_some() {
+                module
+                    .merge(PersonalApi::insecure_unlock_refused())
+                    .expect("unlock method was removed");
+            }
+        }
+    }
+
     /// Merge the given Methods in all configured methods.
     ///
     /// Fails if any of the methods in other is present already.
@@ -1858,6 +2263,8 @@ struct WsHttpServer {
     server: WsHttpServers,
     /// The jwt secret.
     jwt_secret: Option<JwtSecret>,
+    /// The rate limit layer of the server that serves ws connections.
+    ws_rate_limit: Option<RateLimitLayer>,
 }
 
 /// Enum for holding the http and ws servers in all possible combinations.
@@ -1872,12 +2279,22 @@ enum WsHttpServers {
 
 impl WsHttpServers {
     /// Starts the servers and returns the handles (http, ws)
+    ///
+    /// Calls over ws connections don't pass through the middleware, so the methods of the module
+    /// served over ws are wrapped by the rate limit layer, if any.
     async fn start(
         self,
         http_module: Option<RpcModule<()>>,
         ws_module: Option<RpcModule<()>>,
+        ws_rate_limit: Option<&RateLimitLayer>,
         config: &TransportRpcModuleConfig,
     ) -> Result<(Option<ServerHandle>, Option<ServerHandle>), RpcError> {
+        let limit_ws_calls = |mut module: RpcModule<()>| {
+            if let Some(rate_limit) = ws_rate_limit {
+                rate_limit.limit_ws_calls(&mut module);
+            }
+            module
+        };
         let mut http_handle = None;
         let mut ws_handle = None;
         match self {
@@ -1887,7 +2304,7 @@ impl WsHttpServers {
                 config.ensure_ws_http_identical()?;
 
                 if let Some(module) = http_module.or(ws_module) {
-                    let handle = both.start(module).await;
+                    let handle = both.start(limit_ws_calls(module)).await;
                     http_handle = Some(handle.clone());
                     ws_handle = Some(handle);
                 }
@@ -1901,7 +2318,7 @@ impl WsHttpServers {
                 if let Some((server, module)) =
                     ws.and_then(|server| ws_module.map(|module| (server, module)))
                 {
-                    ws_handle = Some(server.start(module).await);
+                    ws_handle = Some(server.start(limit_ws_calls(module)).await);
                 }
             }
         }
@@ -1916,17 +2333,28 @@ impl Default for WsHttpServers {
     }
 }
 
+/// The optional rate limit layer, stacked innermost so requests rejected by cors or auth don't
+/// consume tokens.
+type RateLimited<L> = Stack<Either<RateLimitLayer, Identity>, L>;
+
+/// The logger of the http and ws servers, which also reports the peer addresses to the rate limit
+/// layer.
+type ServerLogger = (RpcServerMetrics, RateLimitLogger);
+
 /// Http Servers Enum
 enum WsHttpServerKind {
     /// Http server
-    Plain(Server<Identity, RpcServerMetrics>),
+    Plain(Server<RateLimited<Identity>, ServerLogger>),
     /// Http server with cors
-    WithCors(Server<Stack<CorsLayer, Identity>, RpcServerMetrics>),
+    WithCors(Server<RateLimited<Stack<CorsLayer, Identity>>, ServerLogger>),
     /// Http server with auth
-    WithAuth(Server<Stack<AuthLayer<JwtAuthValidator>, Identity>, RpcServerMetrics>),
+    WithAuth(Server<RateLimited<Stack<AuthLayer<JwtAuthValidator>, Identity>>, ServerLogger>),
     /// Http server with cors and auth
     WithCorsAuth(
-        Server<Stack<AuthLayer<JwtAuthValidator>, Stack<CorsLayer, Identity>>, RpcServerMetrics>,
+        Server<
+            RateLimited<Stack<AuthLayer<JwtAuthValidator>, Stack<CorsLayer, Identity>>>,
+            ServerLogger,
+        >,
     ),
 }
 
@@ -1951,9 +2379,11 @@ impl WsHttpServerKind {
         socket_addr: SocketAddr,
         cors_domains: Option<String>,
         jwt_secret: Option<JwtSecret>,
+        rate_limit: Option<RateLimitLayer>,
         server_kind: ServerKind,
         metrics: RpcServerMetrics,
     ) -> Result<(Self, SocketAddr), RpcError> {
+        let logger = (metrics, RateLimitLogger::default());
         if let Some(cors) = cors_domains.as_deref().map(cors::create_cors_layer) {
             let cors = cors.map_err(|err| RpcError::Custom(err.to_string()))?;
 
@@ -1961,11 +2391,12 @@ impl WsHttpServerKind {
                 // stack cors and auth layers
                 let middleware = tower::ServiceBuilder::new()
                     .layer(cors)
-                    .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())));
+                    .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())))
+                    .option_layer(rate_limit);
 
                 let server = builder
                     .set_middleware(middleware)
-                    .set_logger(metrics)
+                    .set_logger(logger)
                     .build(socket_addr)
                     .await
                     .map_err(|err| RpcError::from_jsonrpsee_error(err, server_kind))?;
@@ -1973,10 +2404,10 @@ impl WsHttpServerKind {
                 let server = WsHttpServerKind::WithCorsAuth(server);
                 Ok((server, local_addr))
             } else {
-                let middleware = tower::ServiceBuilder::new().layer(cors);
+                let middleware = tower::ServiceBuilder::new().layer(cors).option_layer(rate_limit);
                 let server = builder
                     .set_middleware(middleware)
-                    .set_logger(metrics)
+                    .set_logger(logger)
                     .build(socket_addr)
                     .await
                     .map_err(|err| RpcError::from_jsonrpsee_error(err, server_kind))?;
@@ -1987,10 +2418,11 @@ impl WsHttpServerKind {
         } else if let Some(secret) = jwt_secret {
             // jwt auth layered service
             let middleware = tower::ServiceBuilder::new()
-                .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())));
+                .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())))
+                .option_layer(rate_limit);
             let server = builder
                 .set_middleware(middleware)
-                .set_logger(metrics)
+                .set_logger(logger)
                 .build(socket_addr)
                 .await
                 .map_err(|err| {
@@ -2000,9 +2432,11 @@ impl WsHttpServerKind {
             let server = WsHttpServerKind::WithAuth(server);
             Ok((server, local_addr))
         } else {
-            // plain server without any middleware
+            // plain server without cors and auth
+            let middleware = tower::ServiceBuilder::new().option_layer(rate_limit);
             let server = builder
-                .set_logger(metrics)
+                .set_middleware(middleware)
+                .set_logger(logger)
                 .build(socket_addr)
                 .await
                 .map_err(|err| RpcError::from_jsonrpsee_error(err, server_kind))?;
@@ -2066,7 +2500,8 @@ impl RpcServer {
             jwt_secret: None,
         };
 
-        let (http, ws) = ws_http.server.start(http, ws, &config).await?;
+        let (http, ws) =
+            ws_http.server.start(http, ws, ws_http.ws_rate_limit.as_ref(), &config).await?;
         handle.http = http;
         handle.ws = ws;
 
@@ -2219,6 +2654,34 @@ mod tests {
         assert_eq!(selection, RethRpcModule::EthCallBundle);
     }
 
+    #[test]
+    fn parse_eth_bundle() {
+        let selection = "eth-bundle".parse::<RethRpcModule>().unwrap();
+        assert_eq!(selection, RethRpcModule::EthBundle);
+        assert_eq!(RethRpcModule::EthBundle.as_str(), "eth-bundle");
+    }
+
+    #[test]
+    fn parse_mev() {
+        let selection = "mev".parse::<RethRpcModule>().unwrap();
+        assert_eq!(selection, RethRpcModule::Mev);
+        assert_eq!(RethRpcModule::Mev.as_str(), "mev");
+    }
+
+    #[test]
+    fn parse_flashbots() {
+        let selection = "flashbots".parse::<RethRpcModule>().unwrap();
+        assert_eq!(selection, RethRpcModule::Flashbots);
+        assert_eq!(RethRpcModule::Flashbots.as_str(), "flashbots");
+    }
+
+    #[test]
+    fn parse_personal() {
+        let selection = "personal".parse::<RethRpcModule>().unwrap();
+        assert_eq!(selection, RethRpcModule::Personal);
+        assert_eq!(RethRpcModule::Personal.as_str(), "personal");
+    }
+
     #[test]
     fn parse_eth_call_bundle_selection() {
         let selection = "eth,admin,debug,eth-call-bundle".parse::<RpcModuleSelection>().unwrap();
@@ -1,5 +1,5 @@
 //! Standalone http tests
-use crate::utils::{launch_http, launch_http_ws, launch_ws};
+use crate::utils::{launch_http, launch_http_ws, launch_ws, launch_ws_with_provider};
 use jsonrpsee::{
     core::{
         client::{ClientT, SubscriptionClientT},
@@ -9,9 +9,10 @@ use jsonrpsee::{
     types::error::ErrorCode,
 };
 use reth_primitives::{
-    hex_literal::hex, Address, BlockId, BlockNumberOrTag, Bytes, NodeRecord, TxHash, B256, B64,
-    U256,
+    hex_literal::hex, Address, Block, BlockId, BlockNumberOrTag, Bytes, Header, NodeRecord, TxHash,
+    B256, B64, U256,
 };
+use reth_provider::test_utils::MockEthProvider;
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
     DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, TraceApiClient,
@@ -26,16 +27,19 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashSet;
 
-fn is_unimplemented(err: Error) -> bool {
+fn is_internal_error(err: Error, message: &str) -> bool {
     match err {
         Error::Call(error_obj) => {
-            error_obj.code() == ErrorCode::InternalError.code() &&
-                error_obj.message() == "unimplemented"
+            error_obj.code() == ErrorCode::InternalError.code() && error_obj.message() == message
         }
         _ => false,
     }
 }
 
+fn is_unimplemented(err: Error) -> bool {
+    is_internal_error(err, "unimplemented")
+}
+
 /// Represents a builder for creating JSON-RPC requests.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RawRpcParamsBuilder {
@@ -115,6 +119,13 @@ where
     AdminApiClient::remove_peer(client, node).await.unwrap();
     AdminApiClient::add_trusted_peer(client, node).await.unwrap();
     AdminApiClient::remove_trusted_peer(client, node).await.unwrap();
+    AdminApiClient::add_allowed_peer(client, node).await.unwrap();
+    AdminApiClient::remove_allowed_peer(client, node).await.unwrap();
+    AdminApiClient::ban_peer(client, node, Some(60)).await.unwrap();
+    AdminApiClient::unban_peer(client, node).await.unwrap();
+    AdminApiClient::ban_ip(client, node.address, None).await.unwrap();
+    AdminApiClient::unban_ip(client, node.address).await.unwrap();
+    AdminApiClient::bans(client).await.unwrap();
     AdminApiClient::node_info(client).await.unwrap();
 }
 
@@ -167,7 +178,9 @@ where
     EthApiClient::call(client, call_request.clone(), Some(block_number.into()), None, None)
         .await
         .unwrap();
+    EthApiClient::simulate_v1(client, Default::default(), None).await.unwrap_err();
     EthApiClient::syncing(client).await.unwrap();
+    EthApiClient::sign_transaction(client, transaction_request.clone()).await.unwrap_err();
     EthApiClient::send_transaction(client, transaction_request).await.unwrap_err();
     EthApiClient::hashrate(client).await.unwrap();
     EthApiClient::submit_hashrate(client, U256::default(), B256::default()).await.unwrap();
@@ -185,9 +198,6 @@ where
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(
-        EthApiClient::sign_transaction(client, call_request.clone()).await.err().unwrap()
-    ));
 }
 
 async fn test_basic_debug_calls<C>(client: &C)
@@ -200,7 +210,7 @@ where
     DebugApiClient::raw_block(client, block_id).await.unwrap();
     DebugApiClient::raw_transaction(client, B256::default()).await.unwrap();
     DebugApiClient::raw_receipts(client, block_id).await.unwrap();
-    assert!(is_unimplemented(DebugApiClient::bad_blocks(client).await.err().unwrap()));
+    DebugApiClient::bad_blocks(client).await.unwrap();
 }
 
 async fn test_basic_net_calls<C>(client: &C)
@@ -288,17 +298,19 @@ where
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(
+    assert!(is_internal_error(
         OtterscanClient::search_transactions_before(client, address, block_number, page_size,)
             .await
             .err()
-            .unwrap()
+            .unwrap(),
+        "address appearance index is not enabled"
     ));
-    assert!(is_unimplemented(
+    assert!(is_internal_error(
         OtterscanClient::search_transactions_after(client, address, block_number, page_size,)
             .await
             .err()
-            .unwrap()
+            .unwrap(),
+        "address appearance index is not enabled"
     ));
     assert!(is_unimplemented(
         OtterscanClient::get_transaction_by_sender_and_nonce(client, sender, nonce,)
@@ -401,6 +413,65 @@ async fn test_call_debug_functions_http_and_ws() {
     test_basic_debug_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_chain_invalid_range_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_ws(vec![RethRpcModule::Debug]).await;
+    let client = handle.ws_client().await.unwrap();
+
+    // the range is empty, so the subscription is rejected
+    let err = DebugApiClient::debug_trace_chain(
+        &client,
+        BlockNumberOrTag::Latest,
+        BlockNumberOrTag::Earliest,
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::Call(obj) if obj.code() == ErrorCode::InvalidParams.code()),
+        "{err:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_chain_ws() {
+    reth_tracing::init_test_tracing();
+
+    // more blocks than are traced in parallel, so the stream has to keep the block order while
+    // tracing ahead
+    let provider = MockEthProvider::default();
+    let mut parent_hash = B256::ZERO;
+    let mut hashes = Vec::new();
+    for number in 0..=10 {
+        let header = Header { number, parent_hash, ..Default::default() };
+        parent_hash = header.hash_slow();
+        hashes.push(parent_hash);
+        provider.add_block(parent_hash, Block { header, ..Default::default() });
+    }
+
+    let handle = launch_ws_with_provider(vec![RethRpcModule::Debug], provider).await;
+    let client = handle.ws_client().await.unwrap();
+
+    let mut subscription = DebugApiClient::debug_trace_chain(
+        &client,
+        BlockNumberOrTag::Number(2),
+        BlockNumberOrTag::Latest,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // every block after the start up to and including the latest block is delivered in order
+    for number in 3..=10 {
+        let trace = subscription.next().await.unwrap().unwrap();
+        assert_eq!(trace.block, U256::from(number));
+        assert_eq!(trace.hash, hashes[number]);
+        assert!(trace.traces.is_empty());
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_net_functions_http() {
     reth_tracing::init_test_tracing();
@@ -2,7 +2,7 @@ use reth_beacon_consensus::BeaconConsensusEngineHandle;
 use reth_network_api::noop::NoopNetwork;
 use reth_payload_builder::test_utils::spawn_test_payload_service;
 use reth_primitives::MAINNET;
-use reth_provider::test_utils::{NoopProvider, TestCanonStateSubscriptions};
+use reth_provider::test_utils::{MockEthProvider, NoopProvider, TestCanonStateSubscriptions};
 use reth_rpc::JwtSecret;
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerConfig, AuthServerHandle},
@@ -56,6 +56,19 @@ pub async fn launch_ws(modules: impl Into<RpcModuleSelection>) -> RpcServerHandl
         .unwrap()
 }
 
+/// Launches a new server with ws only with the given modules that reads from the given provider
+pub async fn launch_ws_with_provider(
+    modules: impl Into<RpcModuleSelection>,
+    provider: MockEthProvider,
+) -> RpcServerHandle {
+    let builder = test_rpc_builder().with_provider(provider);
+    let server = builder.build(TransportRpcModuleConfig::set_ws(modules));
+    server
+        .start_server(RpcServerConfig::ws(Default::default()).with_ws_address(test_address()))
+        .await
+        .unwrap()
+}
+
 /// Launches a new server with http and ws and with the given modules
 pub async fn launch_http_ws(modules: impl Into<RpcModuleSelection>) -> RpcServerHandle {
     let builder = test_rpc_builder();
@@ -89,6 +89,35 @@ pub struct EthProtocolInfo {
     pub genesis: B256,
 }
 
+/// Represents the `admin_bans` response: the peers and IP addresses that are currently banned.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bans {
+    /// Banned peers.
+    pub peers: Vec<BannedPeer>,
+    /// Banned IP addresses.
+    pub ips: Vec<BannedIp>,
+}
+
+/// A banned peer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    /// ID of the banned peer.
+    pub id: PeerId,
+    /// UNIX timestamp in seconds until which the peer is banned, or `None` if it is banned
+    /// indefinitely.
+    pub until: Option<u64>,
+}
+
+/// A banned IP address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedIp {
+    /// The banned IP address.
+    pub ip: IpAddr,
+    /// UNIX timestamp in seconds until which the IP address is banned, or `None` if it is banned
+    /// indefinitely.
+    pub until: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
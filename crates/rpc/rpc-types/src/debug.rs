@@ -0,0 +1,270 @@
+//! Types for the `debug` state inspection and bad block endpoints.
+//!
+//! These mirror the response types returned by geth so that tooling built against geth (state
+//! diffing, fork checks) works unchanged.
+
+use crate::RichBlock;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Response of `debug_storageRangeAt`.
+///
+/// Storage entries are keyed by the keccak256 hash of the slot, which is also the order in which
+/// the range is walked.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeResult {
+    /// The storage entries in this page, keyed by hashed slot.
+    pub storage: BTreeMap<B256, StorageRangeEntry>,
+    /// The hashed slot to continue from, `null` if the range is exhausted.
+    pub next_key: Option<B256>,
+}
+
+/// A single entry of a [StorageRangeResult].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageRangeEntry {
+    /// The unhashed slot, if known.
+    pub key: Option<B256>,
+    /// The value stored at the slot.
+    pub value: B256,
+}
+
+/// Response of `debug_accountRange` and `debug_dumpBlock`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDump {
+    /// The state root of the dumped state.
+    pub root: B256,
+    /// The dumped accounts.
+    ///
+    /// Keyed by the checksummed address, or `pre(<hashed address>)` if the preimage of the hashed
+    /// address is unknown.
+    pub accounts: BTreeMap<String, DumpAccount>,
+    /// The hashed address to continue the iteration from, if there are more accounts.
+    ///
+    /// Encoded as base64 like geth does, it can be passed back as the `start` parameter of
+    /// `debug_accountRange` as is, see [AccountRangeStart].
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "base64_bytes")]
+    pub next: Option<Bytes>,
+}
+
+impl StateDump {
+    /// Returns the key under which an account is listed in [StateDump::accounts].
+    pub fn account_key(address: Option<Address>, hashed_address: B256) -> String {
+        match address {
+            Some(address) => address.to_checksum(None),
+            None => format!("pre({hashed_address:?})"),
+        }
+    }
+}
+
+/// The `start` parameter of `debug_accountRange`, a prefix of the hashed address to start from.
+///
+/// Accepts hex encoded bytes like geth, and the base64 encoded [StateDump::next] of the previous
+/// page. Serialized as hex.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountRangeStart(pub Bytes);
+
+impl From<Bytes> for AccountRangeStart {
+    fn from(start: Bytes) -> Self {
+        Self(start)
+    }
+}
+
+impl Serialize for AccountRangeStart {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountRangeStart {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use base64::Engine;
+        use serde::de::Error;
+
+        let s = String::deserialize(d)?;
+        // base64 encoded 32 byte keys are padded, so they are never valid hex
+        if let Some(hex) = s.strip_prefix("0x") {
+            if let Ok(bytes) = alloy_primitives::hex::decode(hex) {
+                return Ok(Self(bytes.into()))
+            }
+        }
+        base64::engine::general_purpose::STANDARD
+            .decode(&s)
+            .map(|bytes| Self(bytes.into()))
+            .map_err(|_| D::Error::custom("start must be hex or base64 encoded"))
+    }
+}
+
+/// An account in a [StateDump].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpAccount {
+    /// The balance of the account, as a decimal string.
+    #[serde(with = "decimal_u256")]
+    pub balance: U256,
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The storage root of the account.
+    pub root: B256,
+    /// The hash of the account's bytecode.
+    pub code_hash: B256,
+    /// The bytecode of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The storage of the account.
+    ///
+    /// Values are encoded as hex without the `0x` prefix and without leading zero bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "storage_values")]
+    pub storage: Option<BTreeMap<B256, U256>>,
+    /// The address of the account, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The hashed address of the account.
+    #[serde(default, rename = "key", skip_serializing_if = "Option::is_none")]
+    pub hashed_address: Option<B256>,
+}
+
+/// A block that was rejected as invalid, as returned by `debug_getBadBlocks`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BadBlockArgs {
+    /// The hash of the block.
+    pub hash: B256,
+    /// The block, with full transactions.
+    pub block: RichBlock,
+    /// The rlp encoded block.
+    pub rlp: Bytes,
+    /// The validation error the block was rejected with.
+    pub error: String,
+}
+
+mod base64_bytes {
+    use alloy_primitives::Bytes;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(value: &Option<Bytes>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(bytes) => s.serialize_str(&STANDARD.encode(bytes)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Bytes>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|s| STANDARD.decode(s).map(Into::into).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+mod decimal_u256 {
+    use alloy_primitives::U256;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(value: &U256, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.to_string())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<U256, D::Error> {
+        let s = String::deserialize(d)?;
+        U256::from_str_radix(&s, 10).map_err(D::Error::custom)
+    }
+}
+
+mod storage_values {
+    use alloy_primitives::{hex, B256, U256};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub(super) fn serialize<S: Serializer>(
+        storage: &Option<BTreeMap<B256, U256>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match storage {
+            Some(storage) => s.collect_map(
+                storage
+                    .iter()
+                    .map(|(key, value)| (key, hex::encode(value.to_be_bytes_trimmed_vec()))),
+            ),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<BTreeMap<B256, U256>>, D::Error> {
+        let Some(storage) = Option::<BTreeMap<B256, String>>::deserialize(d)? else {
+            return Ok(None)
+        };
+        storage
+            .into_iter()
+            .map(|(key, value)| {
+                let value = value.strip_prefix("0x").unwrap_or(&value);
+                if value.is_empty() {
+                    return Ok((key, U256::ZERO))
+                }
+                U256::from_str_radix(value, 16).map(|value| (key, value)).map_err(D::Error::custom)
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_storage_range_result() {
+        let s = r#"{"storage":{"0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563":{"key":"0x0000000000000000000000000000000000000000000000000000000000000000","value":"0x000000000000000000000000000000000000000000000000000000000000002a"}},"nextKey":null}"#;
+        let res: StorageRangeResult = serde_json::from_str(s).unwrap();
+        assert_eq!(res.storage.len(), 1);
+        assert!(res.next_key.is_none());
+        assert_eq!(serde_json::to_string(&res).unwrap(), s);
+    }
+
+    #[test]
+    fn serde_state_dump() {
+        let s = r#"{"root":"0x0000000000000000000000000000000000000000000000000000000000000001","accounts":{"0x000000000000000000000000000000000000dEaD":{"balance":"1000000000000000000","nonce":1,"root":"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421","codeHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","storage":{"0x0000000000000000000000000000000000000000000000000000000000000000":"2a"},"address":"0x000000000000000000000000000000000000dead"}},"next":"AQ=="}"#;
+        let dump: StateDump = serde_json::from_str(s).unwrap();
+        let account = &dump.accounts["0x000000000000000000000000000000000000dEaD"];
+        assert_eq!(account.balance, U256::from(10u64.pow(18)));
+        assert_eq!(account.storage.as_ref().unwrap()[&B256::ZERO], U256::from(42));
+        assert!(account.code.is_none());
+        assert_eq!(dump.next, Some(Bytes::from_static(&[1])));
+        let json = serde_json::to_string(&dump).unwrap();
+        assert_eq!(serde_json::from_str::<StateDump>(&json).unwrap(), dump);
+    }
+
+    #[test]
+    fn account_range_start_from_next() {
+        let next = Bytes::from(B256::with_last_byte(1).to_vec());
+        let dump = StateDump { next: Some(next.clone()), ..Default::default() };
+        let json = serde_json::to_value(&dump).unwrap();
+        assert_eq!(json["next"], "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAE=");
+
+        // the next key of a page is accepted as the start of the next page
+        let start: AccountRangeStart = serde_json::from_value(json["next"].clone()).unwrap();
+        assert_eq!(start, AccountRangeStart(next.clone()));
+        let start: AccountRangeStart = serde_json::from_str(&format!("\"{next}\"")).unwrap();
+        assert_eq!(start, AccountRangeStart(next));
+        assert_eq!(
+            serde_json::from_str::<AccountRangeStart>(r#""0x""#).unwrap(),
+            AccountRangeStart::default()
+        );
+        assert!(serde_json::from_str::<AccountRangeStart>(r#""not a key""#).is_err());
+    }
+
+    #[test]
+    fn state_dump_account_key() {
+        let address: Address = "0x000000000000000000000000000000000000dead".parse().unwrap();
+        assert_eq!(
+            StateDump::account_key(Some(address), B256::ZERO),
+            "0x000000000000000000000000000000000000dEaD"
+        );
+        assert_eq!(
+            StateDump::account_key(None, B256::with_last_byte(1)),
+            "pre(0x0000000000000000000000000000000000000000000000000000000000000001)"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Types for `eth_simulateV1`
+
+use crate::{state::StateOverride, Block, BlockOverrides, CallRequest, Log};
+use alloy_primitives::{Bytes, U64};
+use serde::{Deserialize, Serialize};
+
+/// The payload of `eth_simulateV1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+    /// The blocks to simulate, in order.
+    pub block_state_calls: Vec<SimBlock>,
+    /// Whether ETH transfers should be returned as ERC-20 `Transfer` logs emitted by
+    /// `0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee`.
+    #[serde(default)]
+    pub trace_transfers: bool,
+    /// Whether the calls should be validated like regular transactions.
+    ///
+    /// If disabled, nonces are not checked and the base fee is ignored.
+    #[serde(default)]
+    pub validation: bool,
+    /// Whether the simulated blocks should contain the full transactions instead of their hashes.
+    #[serde(default)]
+    pub return_full_transactions: bool,
+}
+
+/// A block to simulate as part of a [SimulatePayload].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimBlock {
+    /// Overrides of the block's header fields.
+    ///
+    /// By default, a simulated block follows its parent with a timestamp 12 seconds later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_overrides: Option<BlockOverrides>,
+    /// State overrides that are applied before the calls of the block are executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<StateOverride>,
+    /// The calls to execute in the block, in order.
+    #[serde(default)]
+    pub calls: Vec<CallRequest>,
+}
+
+/// A block returned by `eth_simulateV1`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedBlock {
+    /// The simulated block.
+    #[serde(flatten)]
+    pub inner: Block,
+    /// The results of the calls of the block.
+    pub calls: Vec<SimCallResult>,
+}
+
+/// The result of a simulated call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimCallResult {
+    /// The output of the call.
+    pub return_data: Bytes,
+    /// The logs emitted by the call.
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    /// The gas used by the call.
+    pub gas_used: U64,
+    /// `1` if the call succeeded, `0` if it reverted or halted.
+    pub status: U64,
+    /// The error if the call did not succeed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimulateError>,
+}
+
+/// The error of a simulated call that did not succeed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulateError {
+    /// The error code.
+    pub code: i32,
+    /// The error message.
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+
+    #[test]
+    fn serde_simulate_payload() {
+        let s = r#"{
+            "blockStateCalls": [
+                {
+                    "blockOverrides": {"number": "0x10", "time": "0x64"},
+                    "stateOverrides": {
+                        "0xc000000000000000000000000000000000000000": {"balance": "0x3e8"}
+                    },
+                    "calls": [
+                        {
+                            "from": "0xc000000000000000000000000000000000000000",
+                            "to": "0xc100000000000000000000000000000000000000",
+                            "value": "0x3e8"
+                        }
+                    ]
+                },
+                {}
+            ],
+            "traceTransfers": true
+        }"#;
+        let payload: SimulatePayload = serde_json::from_str(s).unwrap();
+        assert_eq!(payload.block_state_calls.len(), 2);
+        assert!(payload.trace_transfers);
+        assert!(!payload.validation);
+        assert!(!payload.return_full_transactions);
+
+        let block = &payload.block_state_calls[0];
+        assert_eq!(block.block_overrides.as_ref().unwrap().number, Some(U256::from(16)));
+        let from: Address = "0xc000000000000000000000000000000000000000".parse().unwrap();
+        assert_eq!(block.state_overrides.as_ref().unwrap()[&from].balance, Some(U256::from(1000)));
+        assert_eq!(block.calls[0].value, Some(U256::from(1000)));
+        assert!(payload.block_state_calls[1].calls.is_empty());
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(serde_json::from_str::<SimulatePayload>(&json).unwrap(), payload);
+    }
+
+    #[test]
+    fn serde_sim_call_result() {
+        let s = r#"{"returnData":"0x","logs":[],"gasUsed":"0x5208","status":"0x0","error":{"code":3,"message":"execution reverted"}}"#;
+        let res: SimCallResult = serde_json::from_str(s).unwrap();
+        assert_eq!(res.gas_used, U64::from(21000));
+        assert_eq!(res.error.as_ref().unwrap().code, 3);
+        assert_eq!(serde_json::to_string(&res).unwrap(), s);
+    }
+}
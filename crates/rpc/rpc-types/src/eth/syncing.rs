@@ -54,6 +54,31 @@ pub struct PeerInfo {
     pub network: PeerNetworkInfo,
     /// Protocols information
     pub protocols: PeerProtocolsInfo,
+    /// Bytes exchanged with the peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bandwidth: Option<PeerBandwidthInfo>,
+}
+
+/// Bytes exchanged with a peer.
+///
+/// Messages are accounted by their encoded size before compression.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerBandwidthInfo {
+    /// Total bytes received from the peer
+    pub ingress: u64,
+    /// Total bytes sent to the peer
+    pub egress: u64,
+    /// Bytes exchanged per capability and message ID
+    pub messages: BTreeMap<String, BTreeMap<u8, MessageBandwidthInfo>>,
+}
+
+/// Bytes exchanged with a peer for a single message type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageBandwidthInfo {
+    /// Bytes received from the peer
+    pub ingress: u64,
+    /// Bytes sent to the peer
+    pub egress: u64,
 }
 
 /// Peer network information
@@ -1,5 +1,8 @@
 //! `trace_filter` types and support
-use crate::serde_helpers::num::u64_hex_or_decimal_opt;
+use crate::{
+    serde_helpers::num::u64_hex_or_decimal_opt,
+    trace::parity::{Action, TraceOutput, TransactionTrace},
+};
 use alloy_primitives::Address;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -63,19 +66,46 @@ pub struct TraceFilterMatcher {
 impl TraceFilterMatcher {
     /// Returns `true` if the given `from` and `to` addresses match this filter.
     pub fn matches(&self, from: Address, to: Option<Address>) -> bool {
+        self.matches_opt(Some(from), to)
+    }
+
+    /// Returns `true` if the given trace matches this filter.
+    ///
+    /// The addresses of a trace are derived from its action: caller and callee of a call, creator
+    /// and created contract of a create, destroyed contract and refund address of a selfdestruct.
+    /// Rewards only have a recipient, the block author.
+    pub fn matches_trace(&self, trace: &TransactionTrace) -> bool {
+        match &trace.action {
+            Action::Call(call) => self.matches(call.from, Some(call.to)),
+            Action::Create(create) => {
+                let created = match &trace.result {
+                    Some(TraceOutput::Create(output)) => Some(output.address),
+                    _ => None,
+                };
+                self.matches(create.from, created)
+            }
+            Action::Selfdestruct(selfdestruct) => {
+                self.matches(selfdestruct.address, Some(selfdestruct.refund_address))
+            }
+            Action::Reward(reward) => self.matches_opt(None, Some(reward.author)),
+        }
+    }
+
+    /// Returns all addresses this filter is looking for, if any.
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> + '_ {
+        self.from_addresses.iter().chain(self.to_addresses.iter())
+    }
+
+    fn matches_opt(&self, from: Option<Address>, to: Option<Address>) -> bool {
+        let from_matches = || from.map_or(false, |from| self.from_addresses.contains(&from));
+        let to_matches = || to.map_or(false, |to| self.to_addresses.contains(&to));
         match (self.from_addresses.is_empty(), self.to_addresses.is_empty()) {
             (true, true) => true,
-            (false, true) => self.from_addresses.contains(&from),
-            (true, false) => to.map_or(false, |to_addr| self.to_addresses.contains(&to_addr)),
+            (false, true) => from_matches(),
+            (true, false) => to_matches(),
             (false, false) => match self.mode {
-                TraceFilterMode::Union => {
-                    self.from_addresses.contains(&from) ||
-                        to.map_or(false, |to_addr| self.to_addresses.contains(&to_addr))
-                }
-                TraceFilterMode::Intersection => {
-                    self.from_addresses.contains(&from) &&
-                        to.map_or(false, |to_addr| self.to_addresses.contains(&to_addr))
-                }
+                TraceFilterMode::Union => from_matches() || to_matches(),
+                TraceFilterMode::Intersection => from_matches() && to_matches(),
             },
         }
     }
@@ -180,4 +210,50 @@ mod tests {
         assert!(!matcher.matches(test_addr_d8, Some(test_addr_d8)));
         assert!(!matcher.matches(test_addr_d8, Some(test_addr_16)));
     }
+
+    #[test]
+    fn test_filter_matcher_traces() {
+        let test_addr_d8 = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".parse().unwrap();
+        let test_addr_16 = "0x160f5f00288e9e1cc8655b327e081566e580a71d".parse().unwrap();
+        let filter_json = json!({
+            "fromBlock": "0x3",
+            "toBlock": "0x5",
+            "toAddress": [test_addr_d8],
+        });
+        let filter: TraceFilter = serde_json::from_value(filter_json).unwrap();
+        let matcher = filter.matcher();
+
+        let create: TransactionTrace = serde_json::from_value(json!({
+            "action": {
+                "from": test_addr_16,
+                "gas": "0x0",
+                "init": "0x",
+                "value": "0x0"
+            },
+            "result": {
+                "address": test_addr_d8,
+                "code": "0x",
+                "gasUsed": "0x0"
+            },
+            "subtraces": 0,
+            "traceAddress": [],
+            "type": "create"
+        }))
+        .unwrap();
+        assert!(matcher.matches_trace(&create));
+
+        let reward: TransactionTrace = serde_json::from_value(json!({
+            "action": {
+                "author": test_addr_16,
+                "rewardType": "block",
+                "value": "0x0"
+            },
+            "result": null,
+            "subtraces": 0,
+            "traceAddress": [],
+            "type": "reward"
+        }))
+        .unwrap();
+        assert!(!matcher.matches_trace(&reward));
+    }
 }
@@ -0,0 +1,58 @@
+use crate::trace::parity::LocalizedTransactionTrace;
+use serde::{Deserialize, Serialize};
+
+/// The response object for `debug_traceTransaction` with `"tracer": "flatCallTracer"`
+///
+/// These are the parity style traces of the transaction, see also
+/// <https://github.com/ethereum/go-ethereum/blob/0b1438c5cfce2ec0a7adae1d4ad9da8fd2cd8c02/eth/tracers/native/call_flat.go#L108>
+pub type FlatCallFrame = Vec<LocalizedTransactionTrace>;
+
+/// The config for the `flatCallTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlatCallConfig {
+    /// Whether calls to precompiles should be included in the traces.
+    ///
+    /// Note: errors are always reported in the parity format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_precompiles: Option<bool>,
+}
+
+impl FlatCallConfig {
+    /// Includes calls to precompiles in the traces
+    pub fn include_precompiles(mut self) -> Self {
+        self.include_precompiles = Some(true);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::geth::*;
+
+    #[test]
+    fn test_serialize_flat_call_trace() {
+        let mut opts = GethDebugTracingCallOptions::default();
+        opts.tracing_options.tracer =
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::FlatCallTracer));
+        opts.tracing_options.tracer_config =
+            serde_json::to_value(FlatCallConfig::default().include_precompiles()).unwrap().into();
+
+        assert_eq!(
+            serde_json::to_string(&opts).unwrap(),
+            r#"{"tracer":"flatCallTracer","tracerConfig":{"includePrecompiles":true}}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_flat_call_trace() {
+        let s = r#"[{"action":{"from":"0xd1220a0cf47c7b9be7a2e6ba89f429762e7b9adb","callType":"call","gas":"0x1f36d","input":"0x","to":"0x7a250d5630b4cf539739df2c5dacb4c659f2488d","value":"0x0"},"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","blockNumber":1,"result":{"gasUsed":"0x0","output":"0x"},"subtraces":0,"traceAddress":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000002","transactionPosition":0,"type":"call"}]"#;
+        let frame: FlatCallFrame = serde_json::from_str(s).unwrap();
+        assert_eq!(frame.len(), 1);
+        assert_eq!(frame[0].block_number, Some(1));
+
+        let trace: GethTrace = serde_json::from_str(s).unwrap();
+        assert_eq!(trace, GethTrace::FlatCallTracer(frame));
+    }
+}
@@ -9,7 +9,9 @@ use std::{collections::BTreeMap, time::Duration};
 // re-exports
 pub use self::{
     call::{CallConfig, CallFrame, CallLogFrame},
+    flat_call::{FlatCallConfig, FlatCallFrame},
     four_byte::FourByteFrame,
+    mux::{MuxConfig, MuxFrame},
     noop::NoopFrame,
     pre_state::{
         AccountChangeKind, AccountState, DiffMode, DiffStateKind, PreStateConfig, PreStateFrame,
@@ -18,7 +20,9 @@ pub use self::{
 };
 
 mod call;
+mod flat_call;
 mod four_byte;
+mod mux;
 mod noop;
 mod pre_state;
 
@@ -98,6 +102,61 @@ pub struct StructLog {
     pub error: Option<String>,
 }
 
+/// A struct log entry as written by geth's json logger, one line per executed opcode.
+///
+/// See [EIP-3155](https://eips.ethereum.org/EIPS/eip-3155) and <https://github.com/ethereum/go-ethereum/blob/366d2169fbc0e0f803b68c042b77b6b480836dbc/eth/tracers/logger/gen_structlog.go#L17-L34>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLoggerStep {
+    /// program counter
+    pub pc: u64,
+    /// opcode to be executed
+    pub op: u8,
+    /// remaining gas
+    #[serde(with = "crate::serde_helpers::u64_hex")]
+    pub gas: u64,
+    /// cost for executing op
+    #[serde(with = "crate::serde_helpers::u64_hex")]
+    pub gas_cost: u64,
+    /// Memory of the current call. Enabled via enableMemory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Bytes>,
+    /// Size of memory.
+    pub mem_size: u64,
+    /// EVM stack, `null` if disabled via disableStack
+    #[serde(default)]
+    pub stack: Option<Vec<U256>>,
+    /// Last call's return data. Enabled via enableReturnData
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Bytes>,
+    /// Current call depth
+    pub depth: u64,
+    /// Refund counter
+    pub refund: u64,
+    /// Name of the opcode
+    pub op_name: String,
+    /// Error message if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The last line geth's json logger writes for a transaction, after all [JsonLoggerStep]s.
+///
+/// <https://github.com/ethereum/go-ethereum/blob/366d2169fbc0e0f803b68c042b77b6b480836dbc/eth/tracers/logger/logger.go#L386-L395>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLoggerResult {
+    /// Output of the transaction
+    #[serde(serialize_with = "crate::serde_helpers::serialize_hex_string_no_prefix")]
+    pub output: Bytes,
+    /// How much gas was used.
+    #[serde(with = "crate::serde_helpers::u64_hex")]
+    pub gas_used: u64,
+    /// Error message if the transaction failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Tracing response objects
 ///
 /// Note: This deserializes untagged, so it's possible that a custom javascript tracer response
@@ -110,12 +169,16 @@ pub enum GethTrace {
     Default(DefaultFrame),
     /// The response for call tracer
     CallTracer(CallFrame),
+    /// The response for flat call tracer
+    FlatCallTracer(FlatCallFrame),
     /// The response for four byte tracer
     FourByteTracer(FourByteFrame),
     /// The response for pre-state byte tracer
     PreStateTracer(PreStateFrame),
     /// An empty json response
     NoopTracer(NoopFrame),
+    /// The response for mux tracer
+    MuxTracer(MuxFrame),
     /// Any other trace response, such as custom javascript response objects
     JS(serde_json::Value),
 }
@@ -150,10 +213,22 @@ impl From<NoopFrame> for GethTrace {
     }
 }
 
+impl From<FlatCallFrame> for GethTrace {
+    fn from(value: FlatCallFrame) -> Self {
+        GethTrace::FlatCallTracer(value)
+    }
+}
+
+impl From<MuxFrame> for GethTrace {
+    fn from(value: MuxFrame) -> Self {
+        GethTrace::MuxTracer(value)
+    }
+}
+
 /// Available built-in tracers
 ///
 /// See <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
 pub enum GethDebugBuiltInTracerType {
     /// The 4byteTracer collects the function selectors of every function executed in the lifetime
     /// of a transaction, along with the size of the supplied call data. The result is a
@@ -166,6 +241,10 @@ pub enum GethDebugBuiltInTracerType {
     /// with the top-level call at root and sub-calls as children of the higher levels.
     #[serde(rename = "callTracer")]
     CallTracer,
+    /// The flatCallTracer tracks all the call frames executed during a transaction like the
+    /// callTracer, but returns them as a flat list of parity style traces.
+    #[serde(rename = "flatCallTracer")]
+    FlatCallTracer,
     /// The prestate tracer has two modes: prestate and diff. The prestate mode returns the
     /// accounts necessary to execute a given transaction. diff mode returns the differences
     /// between the transaction's pre and post-state (i.e. what changed because the transaction
@@ -179,6 +258,11 @@ pub enum GethDebugBuiltInTracerType {
     /// This tracer is noop. It returns an empty object and is only meant for testing the setup.
     #[serde(rename = "noopTracer")]
     NoopTracer,
+    /// The muxTracer runs several built-in tracers in a single execution of the transaction. The
+    /// config is a map of the tracers to run to their config, the result is a map of the tracers
+    /// to their result.
+    #[serde(rename = "muxTracer")]
+    MuxTracer,
 }
 
 /// Available tracers
@@ -240,6 +324,22 @@ impl GethDebugTracerConfig {
         }
         self.from_value()
     }
+
+    /// Returns the [FlatCallConfig] if it is a flat call config.
+    pub fn into_flat_call_config(self) -> Result<FlatCallConfig, serde_json::Error> {
+        if self.0.is_null() {
+            return Ok(Default::default())
+        }
+        self.from_value()
+    }
+
+    /// Returns the [MuxConfig] if it is a mux config.
+    pub fn into_mux_config(self) -> Result<MuxConfig, serde_json::Error> {
+        if self.0.is_null() {
+            return Ok(Default::default())
+        }
+        self.from_value()
+    }
 }
 
 impl From<serde_json::Value> for GethDebugTracerConfig {
@@ -304,6 +404,20 @@ impl GethDebugTracingOptions {
             GethDebugTracerConfig(serde_json::to_value(config).expect("is serializable"));
         self
     }
+
+    /// Configures a [FlatCallConfig]
+    pub fn flat_call_config(mut self, config: FlatCallConfig) -> Self {
+        self.tracer_config =
+            GethDebugTracerConfig(serde_json::to_value(config).expect("is serializable"));
+        self
+    }
+
+    /// Configures a [MuxConfig]
+    pub fn mux_config(mut self, config: MuxConfig) -> Self {
+        self.tracer_config =
+            GethDebugTracerConfig(serde_json::to_value(config).expect("is serializable"));
+        self
+    }
 }
 
 /// Default tracing options for the struct looger.
@@ -560,6 +674,17 @@ mod tests {
         similar_asserts::assert_eq!(input, val);
     }
 
+    #[test]
+    fn test_serialize_json_logger_lines() {
+        let s = r#"{"pc":0,"op":96,"gas":"0x2fd8","gasCost":"0x3","memSize":0,"stack":[],"depth":1,"refund":0,"opName":"PUSH1"}"#;
+        let step: JsonLoggerStep = serde_json::from_str(s).unwrap();
+        assert_eq!(serde_json::to_string(&step).unwrap(), s);
+
+        let s = r#"{"output":"","gasUsed":"0x5208"}"#;
+        let result: JsonLoggerResult = serde_json::from_str(s).unwrap();
+        assert_eq!(serde_json::to_string(&result).unwrap(), s);
+    }
+
     #[test]
     fn test_trace_result_serde() {
         let s = r#"        {
@@ -0,0 +1,69 @@
+use crate::trace::geth::{GethDebugBuiltInTracerType, GethDebugTracerConfig, GethTrace};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The config for the `muxTracer`, the tracers to run with their config.
+///
+/// <https://github.com/ethereum/go-ethereum/blob/0b1438c5cfce2ec0a7adae1d4ad9da8fd2cd8c02/eth/tracers/native/mux.go#L38>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MuxConfig(pub HashMap<GethDebugBuiltInTracerType, Option<GethDebugTracerConfig>>);
+
+/// The response object for `debug_traceTransaction` with `"tracer": "muxTracer"`, the result of
+/// every configured tracer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MuxFrame(pub HashMap<GethDebugBuiltInTracerType, GethTrace>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::geth::*;
+
+    #[test]
+    fn test_serialize_mux_trace() {
+        let mut opts = GethDebugTracingCallOptions::default();
+        opts.tracing_options.tracer =
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::MuxTracer));
+        opts.tracing_options.tracer_config = serde_json::to_value(MuxConfig(HashMap::from([(
+            GethDebugBuiltInTracerType::CallTracer,
+            Some(serde_json::to_value(CallConfig::default().only_top_call()).unwrap().into()),
+        )])))
+        .unwrap()
+        .into();
+
+        assert_eq!(
+            serde_json::to_string(&opts).unwrap(),
+            r#"{"tracer":"muxTracer","tracerConfig":{"callTracer":{"onlyTopCall":true}}}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_mux_config() {
+        let s = r#"{"4byteTracer":null,"callTracer":{"withLog":true},"prestateTracer":{"diffMode":true}}"#;
+        let config: MuxConfig = serde_json::from_str(s).unwrap();
+        assert_eq!(config.0.len(), 3);
+        assert!(config.0[&GethDebugBuiltInTracerType::FourByteTracer].is_none());
+        let call_config = config.0[&GethDebugBuiltInTracerType::CallTracer]
+            .clone()
+            .unwrap()
+            .into_call_config()
+            .unwrap();
+        assert_eq!(call_config.with_log, Some(true));
+    }
+
+    #[test]
+    fn test_deserialize_mux_frame() {
+        let s = r#"{"4byteTracer":{"0x27dc297e-128":1},"callTracer":{"from":"0x0000000000000000000000000000000000000000","gas":"0x0","gasUsed":"0x0","input":"0x","type":"CALL"}}"#;
+        let frame: MuxFrame = serde_json::from_str(s).unwrap();
+        assert!(matches!(
+            frame.0[&GethDebugBuiltInTracerType::FourByteTracer],
+            GethTrace::FourByteTracer(_)
+        ));
+        assert!(matches!(
+            frame.0[&GethDebugBuiltInTracerType::CallTracer],
+            GethTrace::CallTracer(_)
+        ));
+
+        let trace: GethTrace = serde_json::from_str(s).unwrap();
+        assert_eq!(trace, GethTrace::MuxTracer(frame));
+    }
+}
@@ -79,6 +79,11 @@ pub enum BundleItem {
         /// If true, the transaction can revert without the bundle being considered invalid.
         can_revert: bool,
     },
+    /// A nested bundle, e.g. a backrun that envelops the bundle it targets.
+    Bundle {
+        /// The nested bundle.
+        bundle: Box<SendBundleRequest>,
+    },
 }
 
 /// Requirements for the bundle to be included in the block.
@@ -140,9 +145,9 @@ pub struct PrivacyHint {
     pub logs: bool,
     /// The function selector of the bundle's transactions should be shared.
     pub function_selector: bool,
-    /// The hash of the bundle's transactions should be shared.
-    pub hash: bool,
     /// The hash of the bundle should be shared.
+    pub hash: bool,
+    /// The hashes of the bundle's transactions should be shared.
     pub tx_hash: bool,
 }
 
@@ -378,6 +383,9 @@ impl SendBundleRequest {
 pub struct CancelBundleRequest {
     /// Bundle hash of the bundle to be canceled
     pub bundle_hash: String,
+    /// EIP-191 signature of the `bundleHash` string by the key that signed the bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Bytes>,
 }
 
 /// Request for `eth_sendPrivateTransaction`
@@ -583,6 +591,11 @@ pub struct EthSendBundle {
     /// UUID that can be used to cancel/replace this bundle
     #[serde(rename = "replacementUuid", skip_serializing_if = "Option::is_none")]
     pub replacement_uuid: Option<String>,
+    /// EIP-191 signature of the bundle hash, which binds the bundle to the signing key.
+    ///
+    /// Only signed bundles can be replaced or canceled, and only with the same key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Bytes>,
 }
 
 /// Response from the matchmaker after sending a bundle.
@@ -711,6 +724,50 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn can_deserialize_nested() {
+        let str = r#"
+        {
+            "version": "v0.1",
+            "inclusion": {
+                "block": "0x1",
+                "maxBlock": "0x5"
+            },
+            "body": [{
+                "bundle": {
+                    "version": "v0.1",
+                    "inclusion": {
+                        "block": "0x1"
+                    },
+                    "body": [{
+                        "hash": "0x4fd0bc4e4cbd66fe2d3ab4bb44a42a6f1e28e2cf7b9ef2fb0c1d1a8d4fea62bd"
+                    }]
+                }
+            }, {
+                "tx": "0x02f86b0180843b9aca00852ecc889a0082520894c87037874aed04e51c29f582394217a0a2b89d808080c080a0a463985c616dd8ee17d7ef9112af4e6e06a27b071525b42182fe7b0b5c8b4925a00af5ca177ffef2ff28449292505d41be578bebb77110dfc09361d2fb56998260",
+                "canRevert": false
+            }],
+            "validity": {
+                "refund": [{
+                    "bodyIdx": 0,
+                    "percent": 90
+                }]
+            }
+        }
+        "#;
+        let req: SendBundleRequest = serde_json::from_str(str).unwrap();
+        let BundleItem::Bundle { bundle } = &req.bundle_body[0] else {
+            panic!("expected nested bundle, got {:?}", req.bundle_body[0])
+        };
+        assert!(matches!(bundle.bundle_body[0], BundleItem::Hash { .. }));
+        assert!(matches!(req.bundle_body[1], BundleItem::Tx { .. }));
+        assert_eq!(
+            serde_json::from_value::<SendBundleRequest>(serde_json::to_value(&req).unwrap())
+                .unwrap(),
+            req
+        );
+    }
+
     #[test]
     fn can_deserialize_complex() {
         let str = r#"
@@ -66,9 +66,11 @@ pub struct BlockDetails {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OtsTransactionReceipt {
+    /// The transaction receipt.
     #[serde(flatten)]
-    receipt: TransactionReceipt,
-    timestamp: u64,
+    pub receipt: TransactionReceipt,
+    /// The timestamp of the block the transaction is included in.
+    pub timestamp: u64,
 }
 
 /// Custom struct for otterscan `getBlockTransactions` RPC response
@@ -83,10 +85,14 @@ pub struct OtsBlockTransactions {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionsWithReceipts {
-    txs: Vec<Transaction>,
-    receipts: Vec<OtsTransactionReceipt>,
-    first_page: bool,
-    last_page: bool,
+    /// The transactions, newest first.
+    pub txs: Vec<Transaction>,
+    /// The receipts of the transactions, in the same order.
+    pub receipts: Vec<OtsTransactionReceipt>,
+    /// Whether there are no newer transactions.
+    pub first_page: bool,
+    /// Whether there are no older transactions.
+    pub last_page: bool,
 }
 
 /// Custom struct for otterscan `getContractCreator` RPC responses
@@ -27,7 +27,7 @@ pub enum ValidateBuilderSubmissionEqualityError {
         /// The expected gas limit
         expected: u64,
         /// The actual gas limit
-        actual: B256,
+        actual: u64,
     },
     /// Thrown if block hash mismatches
     #[error("incorrect GasUsed {actual}, expected {expected}")]
@@ -35,6 +35,6 @@ pub enum ValidateBuilderSubmissionEqualityError {
         /// The expected gas used
         expected: u64,
         /// The actual gas used
-        actual: B256,
+        actual: u64,
     },
 }
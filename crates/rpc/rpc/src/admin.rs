@@ -4,7 +4,10 @@ use jsonrpsee::core::RpcResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::{NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo};
+use reth_rpc_types::{
+    Bans, NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+};
+use std::{net::IpAddr, time::Duration};
 
 /// `admin` API implementation.
 ///
@@ -50,6 +53,45 @@ where
         Ok(true)
     }
 
+    /// Handler for `admin_addAllowedPeer`
+    fn add_allowed_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        Ok(self.network.add_allowed_peer(record))
+    }
+
+    /// Handler for `admin_removeAllowedPeer`
+    fn remove_allowed_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        Ok(self.network.remove_allowed_peer(record.id))
+    }
+
+    /// Handler for `admin_bans`
+    async fn bans(&self) -> RpcResult<Bans> {
+        self.network.get_bans().await.to_rpc_result()
+    }
+
+    /// Handler for `admin_banPeer`
+    fn ban_peer(&self, record: NodeRecord, duration: Option<u64>) -> RpcResult<bool> {
+        self.network.ban_peer(record.id, duration.map(Duration::from_secs));
+        Ok(true)
+    }
+
+    /// Handler for `admin_unbanPeer`
+    fn unban_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        self.network.unban_peer(record.id);
+        Ok(true)
+    }
+
+    /// Handler for `admin_banIp`
+    fn ban_ip(&self, ip: IpAddr, duration: Option<u64>) -> RpcResult<bool> {
+        self.network.ban_ip(ip, duration.map(Duration::from_secs));
+        Ok(true)
+    }
+
+    /// Handler for `admin_unbanIp`
+    fn unban_ip(&self, ip: IpAddr) -> RpcResult<bool> {
+        self.network.unban_ip(ip);
+        Ok(true)
+    }
+
     async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
         let peers = self.network.get_all_peers().await.to_rpc_result()?;
         let peers = peers
@@ -73,6 +115,7 @@ where
                     }),
                     pip: None,
                 },
+                bandwidth: Some(peer.bandwidth),
             })
             .collect();
 
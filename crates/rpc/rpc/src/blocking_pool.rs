@@ -33,6 +33,11 @@ impl BlockingTaskGuard {
         self.0.acquire_owned().await
     }
 
+    /// Returns the number of tasks that can currently be started without waiting.
+    pub fn available_permits(&self) -> usize {
+        self.0.available_permits()
+    }
+
     /// See also [Semaphore::acquire_many_owned]
     pub async fn acquire_many_owned(self, n: u32) -> Result<OwnedSemaphorePermit, AcquireError> {
         self.0.acquire_many_owned(n).await
@@ -12,17 +12,27 @@ use crate::{
 };
 use alloy_rlp::{Decodable, Encodable};
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult;
+use futures::{Stream, StreamExt};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    server::SubscriptionMessage,
+    PendingSubscriptionSink,
+};
+use reth_interfaces::RethError;
 use reth_primitives::{
+    hex, keccak256, proofs,
     revm::env::tx_env_with_recovered,
     revm_primitives::{
         db::{DatabaseCommit, DatabaseRef},
         BlockEnv, CfgEnv,
     },
-    Address, Block, BlockId, BlockNumberOrTag, Bytes, TransactionSignedEcRecovered, B256,
+    Account, Address, Block, BlockId, BlockNumberOrTag, BlockWithSenders, Bytes, Receipts,
+    TransactionSignedEcRecovered, B256, KECCAK_EMPTY, U256,
 };
 use reth_provider::{
-    BlockReaderIdExt, ChainSpecProvider, HeaderProvider, StateProviderBox, TransactionVariant,
+    AccountRangeEntry, BadBlockStore, BlockHashReader, BlockIdReader, BlockReaderIdExt,
+    BundleStateWithReceipts, ChainSpecProvider, HeaderProvider, StateProviderBox, StateRangeReader,
+    TransactionVariant,
 };
 use reth_revm::{
     database::{StateProviderDatabase, SubState},
@@ -34,19 +44,48 @@ use reth_revm::{
 use reth_rpc_api::DebugApiServer;
 use reth_rpc_types::{
     trace::geth::{
-        BlockTraceResult, FourByteFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
-        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, NoopFrame, TraceResult,
+        BlockTraceResult, CallConfig, DefaultFrame, FourByteFrame, GethDebugBuiltInTracerType,
+        GethDebugTracerType, GethDebugTracingCallOptions, GethDebugTracingOptions,
+        GethDefaultTracingOptions, GethTrace, JsonLoggerResult, JsonLoggerStep, MuxConfig,
+        MuxFrame, NoopFrame, PreStateConfig, TraceResult,
     },
-    BlockError, Bundle, CallRequest, RichBlock, StateContext,
+    AccountRangeStart, BadBlockArgs, BlockError, BlockTransactionsKind, Bundle, CallRequest,
+    DumpAccount, StateContext, StateDump, StorageRangeEntry, StorageRangeResult, TransactionInfo,
 };
+use reth_rpc_types_compat::block::from_block;
 use reth_tasks::TaskSpawner;
 use revm::{
-    db::{CacheDB, EmptyDB},
+    db::{states::bundle_state::BundleRetention, AccountState, CacheDB, EmptyDB},
+    interpreter::opcode,
     primitives::Env,
+    State,
+};
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{mpsc, AcquireError, OwnedSemaphorePermit},
 };
-use std::sync::Arc;
-use tokio::sync::{mpsc, AcquireError, OwnedSemaphorePermit};
-use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The maximum number of accounts returned by `debug_accountRange`.
+const ACCOUNT_RANGE_MAX_RESULTS: usize = 256;
+
+/// The maximum number of accounts returned by `debug_dumpBlock`.
+const DUMP_BLOCK_MAX_ACCOUNTS: usize = 100_000;
+
+/// How often a new random file name is tried before giving up, same as go's `os.CreateTemp`.
+const MAX_TEMP_FILE_ATTEMPTS: usize = 10_000;
+
+/// The maximum number of storage slots returned by `debug_dumpBlock`.
+const DUMP_BLOCK_MAX_STORAGE_SLOTS: usize = 1_000_000;
+
+/// The maximum number of blocks a `debug_traceChain` subscription traces in parallel.
+const TRACE_CHAIN_MAX_PARALLEL_BLOCKS: usize = 4;
 
 /// `debug` API implementation.
 ///
@@ -64,9 +103,15 @@ impl<Provider, Eth> DebugApi<Provider, Eth> {
         eth: Eth,
         task_spawner: Box<dyn TaskSpawner>,
         blocking_task_guard: BlockingTaskGuard,
+        bad_blocks: BadBlockStore,
     ) -> Self {
-        let inner =
-            Arc::new(DebugApiInner { provider, eth_api: eth, task_spawner, blocking_task_guard });
+        let inner = Arc::new(DebugApiInner {
+            provider,
+            eth_api: eth,
+            task_spawner,
+            blocking_task_guard,
+            bad_blocks,
+        });
         Self { inner }
     }
 }
@@ -75,7 +120,7 @@ impl<Provider, Eth> DebugApi<Provider, Eth> {
 
 impl<Provider, Eth> DebugApi<Provider, Eth>
 where
-    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + 'static,
+    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + StateRangeReader + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Acquires a permit to execute a tracing call.
@@ -87,6 +132,7 @@ where
     async fn trace_block_with(
         &self,
         at: BlockId,
+        block_hash: B256,
         transactions: Vec<TransactionSignedEcRecovered>,
         cfg: CfgEnv,
         block_env: BlockEnv,
@@ -99,14 +145,24 @@ where
             .spawn_with_state_at_block(at, move |state| {
                 let mut results = Vec::with_capacity(transactions.len());
                 let mut db = CacheDB::new(StateProviderDatabase::new(state));
+                let block_number = block_env.number.saturating_to::<u64>();
+                let base_fee = block_env.basefee.saturating_to::<u64>();
 
-                let mut transactions = transactions.into_iter().peekable();
-                while let Some(tx) = transactions.next() {
+                let mut transactions = transactions.into_iter().enumerate().peekable();
+                while let Some((idx, tx)) = transactions.next() {
                     let tx_hash = tx.hash;
+                    let tx_info = TransactionInfo {
+                        hash: Some(tx_hash),
+                        index: Some(idx as u64),
+                        block_hash: Some(block_hash),
+                        block_number: Some(block_number),
+                        base_fee: Some(base_fee),
+                    };
                     let tx = tx_env_with_recovered(&tx);
                     let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
-                    let (result, state_changes) =
-                        this.trace_transaction(opts.clone(), env, at, &mut db).map_err(|err| {
+                    let (result, state_changes) = this
+                        .trace_transaction(opts.clone(), env, at, &mut db, tx_info)
+                        .map_err(|err| {
                             results.push(TraceResult::Error {
                                 error: err.to_string(),
                                 tx_hash: Some(tx_hash),
@@ -139,10 +195,21 @@ where
     ) -> EthResult<Vec<TraceResult>> {
         let block =
             Block::decode(&mut rlp_block.as_ref()).map_err(BlockError::RlpDecodeRawBlock)?;
+        self.trace_block_on_parent(block, opts).await
+    }
 
+    /// Replays the given block on top of its parent and returns the trace of each transaction.
+    ///
+    /// Unlike [Self::debug_trace_block], the block itself does not need to be known.
+    async fn trace_block_on_parent(
+        &self,
+        block: Block,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<Vec<TraceResult>> {
         let (cfg, block_env) = self.inner.eth_api.evm_env_for_raw_block(&block.header).await?;
         // we trace on top the block's parent block
         let parent = block.parent_hash;
+        let block_hash = block.header.hash_slow();
 
         // Depending on EIP-2 we need to recover the transactions differently
         let transactions =
@@ -166,7 +233,127 @@ where
                     .collect::<EthResult<Vec<_>>>()?
             };
 
-        self.trace_block_with(parent.into(), transactions, cfg, block_env, opts).await
+        self.trace_block_with(parent.into(), block_hash, transactions, cfg, block_env, opts).await
+    }
+
+    /// Returns the recently rejected blocks, most recent first.
+    pub fn debug_bad_blocks(&self) -> EthResult<Vec<BadBlockArgs>> {
+        let mut bad_blocks = Vec::new();
+        for bad in self.inner.bad_blocks.blocks() {
+            let hash = bad.block.hash;
+            let block = bad.block.clone().unseal();
+
+            let mut rlp = Vec::with_capacity(block.length());
+            block.encode(&mut rlp);
+
+            // bad blocks are not part of the chain, so the total difficulty is derived from the
+            // parent
+            let total_difficulty =
+                self.inner.provider.header_td(&block.parent_hash)?.unwrap_or_default() +
+                    block.difficulty;
+
+            // the senders of a block that was rejected because of an invalid signature can't be
+            // recovered, in which case only the transaction hashes are returned
+            let block = match block.clone().with_recovered_senders() {
+                Some(block) => {
+                    from_block(block, total_difficulty, BlockTransactionsKind::Full, Some(hash))?
+                }
+                None => from_block(
+                    BlockWithSenders { block, senders: Vec::new() },
+                    total_difficulty,
+                    BlockTransactionsKind::Hashes,
+                    Some(hash),
+                )?,
+            };
+
+            bad_blocks.push(BadBlockArgs {
+                hash,
+                block: block.into(),
+                rlp: rlp.into(),
+                error: bad.error.clone(),
+            });
+        }
+        Ok(bad_blocks)
+    }
+
+    /// Replays a block that was rejected as invalid on top of its parent and returns the trace of
+    /// each transaction.
+    pub async fn debug_trace_bad_block(
+        &self,
+        block_hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<Vec<TraceResult>> {
+        let bad = self.inner.bad_blocks.get(&block_hash).ok_or_else(|| {
+            EthApiError::InvalidParams(format!("bad block {block_hash} not found"))
+        })?;
+        self.trace_block_on_parent(bad.block.clone().unseal(), opts).await
+    }
+
+    /// Replays a block that was rejected as invalid with the default struct logger and writes the
+    /// trace of each transaction to a new file in the temp directory, one line per executed opcode
+    /// like geth's json logger ([EIP-3155](https://eips.ethereum.org/EIPS/eip-3155)).
+    ///
+    /// Like geth, the file names end with a random suffix and the files are created exclusively, so
+    /// an existing file is never overwritten. Returns the paths of the written files, or an error
+    /// if any transaction of the block could not be traced.
+    pub async fn debug_standard_trace_bad_block_to_file(
+        &self,
+        block_hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<Vec<PathBuf>> {
+        // only the struct logger config is respected, memory is always recorded for `memSize`
+        let config = opts.config;
+        let opts = GethDebugTracingOptions {
+            config: GethDefaultTracingOptions {
+                enable_memory: Some(true),
+                disable_memory: None,
+                ..config.clone()
+            },
+            ..Default::default()
+        };
+        let traces = self.debug_trace_bad_block(block_hash, opts).await?;
+
+        // encode all traces first, so no files are left behind if a transaction failed to trace
+        let mut encoded = Vec::with_capacity(traces.len());
+        for trace in traces {
+            match trace {
+                TraceResult::Success { result: GethTrace::Default(frame), tx_hash } => {
+                    let lines = json_logger_lines(frame, &config).map_err(|err| {
+                        RethError::Custom(format!("failed to encode trace: {err}"))
+                    })?;
+                    encoded.push((tx_hash.unwrap_or_default(), lines));
+                }
+                TraceResult::Success { tx_hash, .. } => {
+                    return Err(RethError::Custom(format!(
+                        "unexpected trace for transaction {}",
+                        tx_hash.unwrap_or_default()
+                    ))
+                    .into())
+                }
+                TraceResult::Error { error, tx_hash } => {
+                    return Err(RethError::Custom(format!(
+                        "failed to trace transaction {}: {error}",
+                        tx_hash.unwrap_or_default()
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir();
+        let mut files = Vec::with_capacity(encoded.len());
+        for (idx, (tx_hash, lines)) in encoded.into_iter().enumerate() {
+            let prefix = format!(
+                "block_0x{}-{idx}-0x{}-",
+                hex::encode(&block_hash[..4]),
+                hex::encode(&tx_hash[..4])
+            );
+            let path = write_new_temp_file(&dir, &prefix, &lines)
+                .await
+                .map_err(|err| RethError::Custom(format!("failed to write trace: {err}")))?;
+            files.push(path);
+        }
+        Ok(files)
     }
 
     /// Replays a block and returns the trace of each transaction.
@@ -193,6 +380,7 @@ where
 
         self.trace_block_with(
             state_at.into(),
+            block_hash,
             block.into_transactions_ecrecovered().collect(),
             cfg,
             block_env,
@@ -201,6 +389,64 @@ where
         .await
     }
 
+    /// Returns the block range `(start_exclusive, end_inclusive]` of a `debug_traceChain`
+    /// subscription.
+    fn trace_chain_range(
+        &self,
+        start_exclusive: BlockNumberOrTag,
+        end_inclusive: BlockNumberOrTag,
+    ) -> EthResult<(u64, u64)> {
+        let start = self
+            .inner
+            .provider
+            .convert_block_number(start_exclusive)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        let end = self
+            .inner
+            .provider
+            .convert_block_number(end_inclusive)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        if start >= end {
+            return Err(EthApiError::InvalidParams(format!(
+                "end block ({end}) must be greater than start block ({start})"
+            )))
+        }
+        Ok((start, end))
+    }
+
+    /// Traces all blocks after `start_exclusive` up to and including `end_inclusive`.
+    ///
+    /// Returns a stream of the [BlockTraceResult]s in block order. Up to
+    /// [TRACE_CHAIN_MAX_PARALLEL_BLOCKS] blocks are traced in parallel, each holding a tracing
+    /// permit, and blocks are only traced ahead as far as the stream is consumed.
+    pub fn debug_trace_chain(
+        &self,
+        start_exclusive: u64,
+        end_inclusive: u64,
+        opts: GethDebugTracingOptions,
+    ) -> impl Stream<Item = EthResult<BlockTraceResult>> + Send + 'static {
+        let this = self.clone();
+        let traces = (start_exclusive + 1..=end_inclusive).map(move |number| {
+            let this = this.clone();
+            let opts = opts.clone();
+            async move { this.trace_chain_block(number, opts).await }
+        });
+        futures::stream::iter(traces).buffered(TRACE_CHAIN_MAX_PARALLEL_BLOCKS)
+    }
+
+    /// Traces a single block of a `debug_traceChain` subscription.
+    async fn trace_chain_block(
+        &self,
+        number: u64,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<BlockTraceResult> {
+        let _permit = self.acquire_trace_permit().await;
+        let hash =
+            self.inner.provider.block_hash(number)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let traces = self.debug_trace_block(hash.into(), opts).await?;
+        Ok(BlockTraceResult { block: U256::from(number), hash, traces })
+    }
+
     /// Trace the transaction according to the provided options.
     ///
     /// Ref: <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
@@ -225,7 +471,7 @@ where
             .eth_api
             .spawn_with_state_at_block(state_at, move |state| {
                 // configure env for the target transaction
-                let tx = transaction.into_recovered();
+                let (tx, tx_info) = transaction.split();
 
                 let mut db = CacheDB::new(StateProviderDatabase::new(state));
                 // replay all transactions prior to the targeted transaction
@@ -238,7 +484,8 @@ where
                 )?;
 
                 let env = Env { cfg, block: block_env, tx: tx_env_with_recovered(&tx) };
-                this.trace_transaction(opts, env, state_at, &mut db).map(|(trace, _)| trace)
+                this.trace_transaction(opts, env, state_at, &mut db, tx_info)
+                    .map(|(trace, _)| trace)
             })
             .await
     }
@@ -321,6 +568,23 @@ where
                         return Ok(frame.into())
                     }
                     GethDebugBuiltInTracerType::NoopTracer => Ok(NoopFrame::default().into()),
+                    tracer @ (GethDebugBuiltInTracerType::FlatCallTracer |
+                    GethDebugBuiltInTracerType::MuxTracer) => {
+                        let opts = GethDebugTracingOptions {
+                            config,
+                            tracer: Some(tracer.into()),
+                            tracer_config,
+                            timeout: None,
+                        };
+                        let this = self.clone();
+                        self.inner
+                            .eth_api
+                            .spawn_with_call_at(call, at, overrides, move |mut db, env| {
+                                this.trace_transaction(opts, env, at, &mut db, Default::default())
+                                    .map(|(trace, _)| trace)
+                            })
+                            .await
+                    }
                 },
                 GethDebugTracerType::JsTracer(code) => {
                     let config = tracer_config.into_json();
@@ -464,6 +728,7 @@ where
                             env,
                             target_block,
                             &mut db,
+                            Default::default(),
                         )?;
 
                         // If there is more transactions, commit the database
@@ -481,6 +746,381 @@ where
             .await
     }
 
+    /// Returns the storage of the given account after executing the first `tx_idx` transactions
+    /// of the block, ordered by hashed slot and starting at `key_start`.
+    ///
+    /// The hashed state does not store the preimages of the hashed slots, so keys are only known
+    /// for slots that changed after the parent block or in the replayed transactions.
+    pub async fn debug_storage_range_at(
+        &self,
+        block_hash: B256,
+        tx_idx: usize,
+        address: Address,
+        key_start: B256,
+        max_result: u64,
+    ) -> EthResult<StorageRangeResult> {
+        let ((cfg, block_env, _), block) = futures::try_join!(
+            self.inner.eth_api.evm_env_at(block_hash.into()),
+            self.inner.eth_api.block_by_id_with_senders(block_hash.into()),
+        )?;
+        let block = block.ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+        if tx_idx > block.body.len() {
+            return Err(EthApiError::InvalidParams(format!(
+                "transaction index {tx_idx} out of range for block with {} transactions",
+                block.body.len()
+            )))
+        }
+
+        let parent_number = block.number.saturating_sub(1);
+        let transactions = block.into_transactions_ecrecovered().take(tx_idx).collect::<Vec<_>>();
+        let limit = max_result as usize;
+
+        let this = self.clone();
+        self.inner
+            .eth_api
+            .spawn_with_state_at_block(parent_number.into(), move |state| {
+                // the changes of the replayed transactions are applied on top of the storage at
+                // the end of the parent block
+                let mut db = CacheDB::new(StateProviderDatabase::new(state));
+                for tx in transactions {
+                    let tx = tx_env_with_recovered(&tx);
+                    let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
+                    let (res, _) = transact(&mut db, env)?;
+                    db.commit(res.state);
+                }
+                let (cleared, changes) = match db.accounts.remove(&address) {
+                    Some(account) => (
+                        matches!(
+                            account.account_state,
+                            AccountState::NotExisting | AccountState::StorageCleared
+                        ),
+                        account
+                            .storage
+                            .into_iter()
+                            .map(|(slot, value)| {
+                                let slot = B256::from(slot);
+                                (keccak256(slot), (slot, value))
+                            })
+                            .collect::<BTreeMap<_, _>>(),
+                    ),
+                    None => (false, BTreeMap::new()),
+                };
+
+                // page through the stored slots from `key_start`, merged with the changed slots
+                // which take precedence
+                let provider = &this.inner.provider;
+                let mut changes = changes.range(key_start..).peekable();
+                let mut page = Vec::new().into_iter().peekable();
+                let mut next_page = (!cleared).then_some(key_start);
+
+                let mut result = StorageRangeResult::default();
+                loop {
+                    if page.peek().is_none() {
+                        if let Some(start) = next_page.take() {
+                            let range = provider.storage_range(
+                                parent_number,
+                                address,
+                                start,
+                                limit.saturating_add(1),
+                            )?;
+                            next_page = range.next;
+                            page = range.slots.into_iter().peekable();
+                        }
+                    }
+
+                    let take_change = match (page.peek(), changes.peek()) {
+                        (None, None) => break,
+                        (Some(entry), Some((hashed_slot, _))) => **hashed_slot <= entry.hashed_slot,
+                        (Some(_), None) => false,
+                        (None, Some(_)) => true,
+                    };
+
+                    let (hashed_slot, slot, value) = if take_change {
+                        let (hashed_slot, (slot, value)) = changes.next().expect("is some");
+                        if page.peek().map(|entry| entry.hashed_slot) == Some(*hashed_slot) {
+                            page.next();
+                        }
+                        (*hashed_slot, Some(*slot), *value)
+                    } else {
+                        let entry = page.next().expect("is some");
+                        (entry.hashed_slot, entry.slot, entry.value)
+                    };
+
+                    // the slot was cleared by the replayed transactions
+                    if value == U256::ZERO {
+                        continue
+                    }
+
+                    if result.storage.len() == limit {
+                        result.next_key = Some(hashed_slot);
+                        break
+                    }
+                    result.storage.insert(
+                        hashed_slot,
+                        StorageRangeEntry { key: slot, value: B256::from(value) },
+                    );
+                }
+
+                Ok(result)
+            })
+            .await
+    }
+
+    /// Returns a page of the accounts at the given block, ordered by hashed address and starting
+    /// at the hashed address prefix `start`.
+    ///
+    /// The hashed state does not store the preimages of the hashed addresses, so addresses are
+    /// only known for accounts that changed after the block. If incomplete accounts are not
+    /// requested, accounts without a known address are skipped and the page can hold fewer than
+    /// `max_results` accounts.
+    pub async fn debug_account_range(
+        &self,
+        block_number: BlockNumberOrTag,
+        start: Bytes,
+        max_results: u64,
+        nocode: bool,
+        nostorage: bool,
+        incompletes: bool,
+    ) -> EthResult<StateDump> {
+        if start.len() > 32 {
+            return Err(EthApiError::InvalidParams(format!(
+                "start key of {} bytes exceeds 32 bytes",
+                start.len()
+            )))
+        }
+        let mut start_key = B256::ZERO;
+        start_key[..start.len()].copy_from_slice(&start);
+
+        let limit = match max_results as usize {
+            0 => ACCOUNT_RANGE_MAX_RESULTS,
+            limit => limit.min(ACCOUNT_RANGE_MAX_RESULTS),
+        };
+
+        let header = self
+            .inner
+            .provider
+            .header_by_number_or_tag(block_number)?
+            .ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+
+        let this = self.clone();
+        self.inner
+            .eth_api
+            .spawn_with_state_at_block(header.number.into(), move |state| {
+                let provider = &this.inner.provider;
+                let mut range = provider.account_range(header.number, start_key, limit)?;
+                if !incompletes {
+                    range.accounts.retain(|entry| entry.address.is_some());
+                }
+                let hashed_addresses =
+                    range.accounts.iter().map(|entry| entry.hashed_address).collect();
+                let mut storages = provider.hashed_storages(header.number, &hashed_addresses)?;
+
+                let mut dump = StateDump {
+                    root: header.state_root,
+                    next: range.next.map(|next| Bytes::copy_from_slice(next.as_slice())),
+                    ..Default::default()
+                };
+                for AccountRangeEntry { hashed_address, address, account } in range.accounts {
+                    let storage = storages.remove(&hashed_address).unwrap_or_default();
+                    let root = proofs::storage_root(storage.clone());
+                    let account = Self::dump_account(
+                        &state,
+                        address,
+                        hashed_address,
+                        account,
+                        root,
+                        (!nostorage).then_some(storage),
+                        nocode,
+                    )?;
+                    dump.accounts.insert(StateDump::account_key(address, hashed_address), account);
+                }
+                Ok(dump)
+            })
+            .await
+    }
+
+    /// Returns all accounts of the state at the given block, including their code and storage.
+    ///
+    /// The accounts are read in pages, and the dump fails once the state exceeds
+    /// [DUMP_BLOCK_MAX_ACCOUNTS] accounts or [DUMP_BLOCK_MAX_STORAGE_SLOTS] storage slots, larger
+    /// states can be read with `debug_accountRange` and `debug_storageRangeAt`.
+    pub async fn debug_dump_block(&self, block_id: BlockId) -> EthResult<StateDump> {
+        let header = self
+            .inner
+            .provider
+            .header_by_id(block_id)?
+            .ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+
+        let this = self.clone();
+        self.inner
+            .eth_api
+            .spawn_with_state_at_block(header.number.into(), move |state| {
+                let provider = &this.inner.provider;
+                let limit_exceeded = || {
+                    EthApiError::InvalidParams(format!(
+                        "state at block {} exceeds {DUMP_BLOCK_MAX_ACCOUNTS} accounts or \
+                         {DUMP_BLOCK_MAX_STORAGE_SLOTS} storage slots",
+                        header.number
+                    ))
+                };
+
+                let mut dump = StateDump { root: header.state_root, ..Default::default() };
+                let mut storage_slots = 0;
+                let mut next = Some(Address::ZERO);
+                while let Some(start) = next {
+                    let range =
+                        provider.plain_accounts(header.number, start, ACCOUNT_RANGE_MAX_RESULTS)?;
+                    next = range.next;
+                    if dump.accounts.len() + range.accounts.len() > DUMP_BLOCK_MAX_ACCOUNTS {
+                        return Err(limit_exceeded())
+                    }
+
+                    let addresses = range.accounts.iter().map(|(address, _)| *address).collect();
+                    let mut storages = provider.plain_storages(header.number, &addresses)?;
+                    storage_slots += storages.values().map(BTreeMap::len).sum::<usize>();
+                    if storage_slots > DUMP_BLOCK_MAX_STORAGE_SLOTS {
+                        return Err(limit_exceeded())
+                    }
+
+                    for (address, account) in range.accounts {
+                        let hashed_address = keccak256(address);
+                        let storage = storages.remove(&address).unwrap_or_default();
+                        let root = proofs::storage_root_unhashed(storage.clone());
+                        let account = Self::dump_account(
+                            &state,
+                            Some(address),
+                            hashed_address,
+                            account,
+                            root,
+                            Some(storage),
+                            false,
+                        )?;
+                        dump.accounts
+                            .insert(StateDump::account_key(Some(address), hashed_address), account);
+                    }
+                }
+                Ok(dump)
+            })
+            .await
+    }
+
+    /// Converts an account of the state into its [DumpAccount] representation.
+    fn dump_account(
+        state: &StateProviderBox,
+        address: Option<Address>,
+        hashed_address: B256,
+        account: Account,
+        root: B256,
+        storage: Option<BTreeMap<B256, U256>>,
+        nocode: bool,
+    ) -> EthResult<DumpAccount> {
+        let code_hash = account.bytecode_hash.unwrap_or(KECCAK_EMPTY);
+        let code = if nocode || code_hash == KECCAK_EMPTY {
+            None
+        } else {
+            state.bytecode_by_hash(code_hash)?.map(|code| code.original_bytes())
+        };
+
+        Ok(DumpAccount {
+            balance: account.balance,
+            nonce: account.nonce,
+            root,
+            code_hash,
+            code,
+            storage: storage.filter(|storage| !storage.is_empty()),
+            address,
+            hashed_address: Some(hashed_address),
+        })
+    }
+
+    /// Returns all accounts that changed in the blocks after `start` up to and including `end`,
+    /// or in block `start` if no `end` is given.
+    pub async fn debug_get_modified_accounts_by_number(
+        &self,
+        start: u64,
+        end: Option<u64>,
+    ) -> EthResult<Vec<Address>> {
+        let range = match end {
+            None => start..=start,
+            Some(end) if start >= end => return Err(EthApiError::InvalidBlockRange),
+            Some(end) => start + 1..=end,
+        };
+        if *range.end() > self.inner.provider.best_block_number()? {
+            return Err(EthApiError::UnknownBlockNumber)
+        }
+
+        Ok(self.inner.provider.modified_accounts(range)?.into_iter().collect())
+    }
+
+    /// Returns all accounts that changed in the blocks after `start` up to and including `end`,
+    /// or in block `start` if no `end` is given.
+    pub async fn debug_get_modified_accounts_by_hash(
+        &self,
+        start: B256,
+        end: Option<B256>,
+    ) -> EthResult<Vec<Address>> {
+        let provider = &self.inner.provider;
+        let start = provider.block_number(start)?.ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+        let end = end
+            .map(|end| provider.block_number(end)?.ok_or_else(|| EthApiError::UnknownBlockNumber))
+            .transpose()?;
+
+        self.debug_get_modified_accounts_by_number(start, end).await
+    }
+
+    /// Re-executes the given block and returns the state root after each transaction.
+    ///
+    /// The roots of blocks below the tip are computed on the parent state reverted from the latest
+    /// state, so the changesets of all later blocks need to be available.
+    pub async fn debug_intermediate_roots(&self, block_hash: B256) -> EthResult<Vec<B256>> {
+        let ((cfg, block_env, _), block) = futures::try_join!(
+            self.inner.eth_api.evm_env_at(block_hash.into()),
+            self.inner.eth_api.block_by_id_with_senders(block_hash.into()),
+        )?;
+        let block = block.ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+        let block_number = block.number;
+
+        self.inner
+            .eth_api
+            .spawn_with_state_at_block(block.parent_hash.into(), move |state| {
+                let mut db = State::builder()
+                    .with_database(StateProviderDatabase::new(&state))
+                    .with_bundle_update()
+                    .build();
+
+                let mut roots = Vec::with_capacity(block.body.len());
+                for tx in block.into_transactions_ecrecovered() {
+                    let tx = tx_env_with_recovered(&tx);
+                    let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
+                    let (res, _) = transact(&mut db, env)?;
+                    db.commit(res.state);
+
+                    // the state root of all changes so far on top of the parent state
+                    db.merge_transitions(BundleRetention::PlainState);
+                    let bundle = BundleStateWithReceipts::new(
+                        db.bundle_state.clone(),
+                        Receipts::default(),
+                        block_number,
+                    );
+                    roots.push(state.state_root(&bundle)?);
+                }
+                Ok(roots)
+            })
+            .await
+    }
+
+    /// Returns the preimage of the given hash.
+    ///
+    /// Preimages of hashed addresses and slots are not stored, only bytecode can be looked up by
+    /// its hash.
+    pub async fn debug_preimage(&self, hash: B256) -> EthResult<Bytes> {
+        let state = self.inner.eth_api.state_at(BlockNumberOrTag::Latest.into())?;
+        state
+            .bytecode_by_hash(hash)?
+            .map(|code| code.original_bytes())
+            .ok_or_else(|| EthApiError::InvalidParams("unknown preimage".to_string()))
+    }
+
     /// Executes the configured transaction with the environment on the given database.
     ///
     /// Returns the trace frame and the state that got updated after executing the transaction.
@@ -494,6 +1134,7 @@ where
         env: Env,
         at: BlockId,
         db: &mut SubState<StateProviderBox>,
+        tx_info: TransactionInfo,
     ) -> EthResult<(GethTrace, revm_primitives::State)> {
         let GethDebugTracingOptions { config, tracer, tracer_config, .. } = opts;
 
@@ -547,6 +1188,29 @@ where
                     GethDebugBuiltInTracerType::NoopTracer => {
                         Ok((NoopFrame::default().into(), Default::default()))
                     }
+                    GethDebugBuiltInTracerType::FlatCallTracer => {
+                        let flat_call_config = tracer_config
+                            .into_flat_call_config()
+                            .map_err(|_| EthApiError::InvalidTracerConfig)?;
+
+                        let mut inspector = TracingInspector::new(
+                            TracingInspectorConfig::from_flat_call_config(&flat_call_config),
+                        );
+                        let (res, _) = inspect(db, env, &mut inspector)?;
+
+                        let frame = inspector
+                            .with_transaction_gas_used(res.result.gas_used())
+                            .into_parity_builder()
+                            .into_localized_transaction_traces(tx_info);
+
+                        return Ok((frame.into(), res.state))
+                    }
+                    GethDebugBuiltInTracerType::MuxTracer => {
+                        let mux_config = tracer_config
+                            .into_mux_config()
+                            .map_err(|_| EthApiError::InvalidTracerConfig)?;
+                        trace_mux(config, mux_config, env, db, tx_info)
+                    }
                 },
                 GethDebugTracerType::JsTracer(code) => {
                     let config = tracer_config.into_json();
@@ -667,7 +1331,7 @@ where
 #[async_trait]
 impl<Provider, Eth> DebugApiServer for DebugApi<Provider, Eth>
 where
-    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + 'static,
+    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + StateRangeReader + 'static,
     Eth: EthApiSpec + 'static,
 {
     /// Handler for `debug_getRawHeader`
@@ -748,17 +1412,50 @@ where
     }
 
     /// Handler for `debug_getBadBlocks`
-    async fn bad_blocks(&self) -> RpcResult<Vec<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn bad_blocks(&self) -> RpcResult<Vec<BadBlockArgs>> {
+        Ok(DebugApi::debug_bad_blocks(self)?)
     }
 
     /// Handler for `debug_traceChain`
+    ///
+    /// Results are sent as the subscription sink has capacity. A failed block ends the
+    /// subscription with an error notification.
     async fn debug_trace_chain(
         &self,
-        _start_exclusive: BlockNumberOrTag,
-        _end_inclusive: BlockNumberOrTag,
-    ) -> RpcResult<Vec<BlockTraceResult>> {
-        Err(internal_rpc_err("unimplemented"))
+        pending: PendingSubscriptionSink,
+        start_exclusive: BlockNumberOrTag,
+        end_inclusive: BlockNumberOrTag,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> SubscriptionResult {
+        let (start, end) = match self.trace_chain_range(start_exclusive, end_inclusive) {
+            Ok(range) => range,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(())
+            }
+        };
+        let sink = pending.accept().await?;
+
+        let traces = DebugApi::debug_trace_chain(self, start, end, opts.unwrap_or_default());
+        tokio::pin!(traces);
+        loop {
+            tokio::select! {
+                _ = sink.closed() => {
+                    // connection dropped or unsubscribed
+                    break Ok(())
+                },
+                maybe_trace = traces.next() => {
+                    let Some(trace) = maybe_trace else {
+                        // all blocks traced
+                        break Ok(())
+                    };
+                    let msg = SubscriptionMessage::from_json(&trace?)?;
+                    if sink.send(msg).await.is_err() {
+                        break Ok(())
+                    }
+                }
+            }
+        }
     }
 
     /// Handler for `debug_traceBlock`
@@ -827,16 +1524,26 @@ where
         Ok(())
     }
 
+    /// Handler for `debug_accountRange`
     async fn debug_account_range(
         &self,
-        _block_number: BlockNumberOrTag,
-        _start: Bytes,
-        _max_results: u64,
-        _nocode: bool,
-        _nostorage: bool,
-        _incompletes: bool,
-    ) -> RpcResult<()> {
-        Ok(())
+        block_number: BlockNumberOrTag,
+        start: AccountRangeStart,
+        max_results: u64,
+        nocode: bool,
+        nostorage: bool,
+        incompletes: bool,
+    ) -> RpcResult<StateDump> {
+        Ok(DebugApi::debug_account_range(
+            self,
+            block_number,
+            start.0,
+            max_results,
+            nocode,
+            nostorage,
+            incompletes,
+        )
+        .await?)
     }
 
     async fn debug_block_profile(&self, _file: String, _seconds: u64) -> RpcResult<()> {
@@ -867,8 +1574,9 @@ where
         Ok(())
     }
 
-    async fn debug_dump_block(&self, _number: BlockId) -> RpcResult<()> {
-        Ok(())
+    /// Handler for `debug_dumpBlock`
+    async fn debug_dump_block(&self, number: BlockId) -> RpcResult<StateDump> {
+        Ok(DebugApi::debug_dump_block(self, number).await?)
     }
 
     async fn debug_free_os_memory(&self) -> RpcResult<()> {
@@ -891,32 +1599,36 @@ where
         Ok(())
     }
 
+    /// Handler for `debug_getModifiedAccountsByHash`
     async fn debug_get_modified_accounts_by_hash(
         &self,
-        _start_hash: B256,
-        _end_hash: B256,
-    ) -> RpcResult<()> {
-        Ok(())
+        start_hash: B256,
+        end_hash: Option<B256>,
+    ) -> RpcResult<Vec<Address>> {
+        Ok(DebugApi::debug_get_modified_accounts_by_hash(self, start_hash, end_hash).await?)
     }
 
+    /// Handler for `debug_getModifiedAccountsByNumber`
     async fn debug_get_modified_accounts_by_number(
         &self,
-        _start_number: u64,
-        _end_number: u64,
-    ) -> RpcResult<()> {
-        Ok(())
+        start_number: u64,
+        end_number: Option<u64>,
+    ) -> RpcResult<Vec<Address>> {
+        Ok(DebugApi::debug_get_modified_accounts_by_number(self, start_number, end_number).await?)
     }
 
     async fn debug_go_trace(&self, _file: String, _seconds: u64) -> RpcResult<()> {
         Ok(())
     }
 
+    /// Handler for `debug_intermediateRoots`
     async fn debug_intermediate_roots(
         &self,
-        _block_hash: B256,
+        block_hash: B256,
         _opts: Option<GethDebugTracingCallOptions>,
-    ) -> RpcResult<()> {
-        Ok(())
+    ) -> RpcResult<Vec<B256>> {
+        let _permit = self.acquire_trace_permit().await;
+        Ok(DebugApi::debug_intermediate_roots(self, block_hash).await?)
     }
 
     async fn debug_mem_stats(&self) -> RpcResult<()> {
@@ -927,8 +1639,9 @@ where
         Ok(())
     }
 
-    async fn debug_preimage(&self, _hash: B256) -> RpcResult<()> {
-        Ok(())
+    /// Handler for `debug_preimage`
+    async fn debug_preimage(&self, hash: B256) -> RpcResult<Bytes> {
+        Ok(DebugApi::debug_preimage(self, hash).await?)
     }
 
     async fn debug_print_block(&self, _number: u64) -> RpcResult<()> {
@@ -963,12 +1676,20 @@ where
         Ok(())
     }
 
+    /// Handler for `debug_standardTraceBadBlockToFile`
     async fn debug_standard_trace_bad_block_to_file(
         &self,
-        _block: BlockNumberOrTag,
-        _opts: Option<GethDebugTracingCallOptions>,
-    ) -> RpcResult<()> {
-        Ok(())
+        block_hash: B256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<Vec<String>> {
+        let _permit = self.acquire_trace_permit().await;
+        let files = DebugApi::debug_standard_trace_bad_block_to_file(
+            self,
+            block_hash,
+            opts.unwrap_or_default(),
+        )
+        .await?;
+        Ok(files.into_iter().map(|path| path.display().to_string()).collect())
     }
 
     async fn debug_standard_trace_block_to_file(
@@ -995,23 +1716,35 @@ where
         Ok(())
     }
 
+    /// Handler for `debug_storageRangeAt`
     async fn debug_storage_range_at(
         &self,
-        _block_hash: B256,
-        _tx_idx: usize,
-        _contract_address: Address,
-        _key_start: B256,
-        _max_result: u64,
-    ) -> RpcResult<()> {
-        Ok(())
+        block_hash: B256,
+        tx_idx: usize,
+        contract_address: Address,
+        key_start: B256,
+        max_result: u64,
+    ) -> RpcResult<StorageRangeResult> {
+        let _permit = self.acquire_trace_permit().await;
+        Ok(DebugApi::debug_storage_range_at(
+            self,
+            block_hash,
+            tx_idx,
+            contract_address,
+            key_start,
+            max_result,
+        )
+        .await?)
     }
 
+    /// Handler for `debug_traceBadBlock`
     async fn debug_trace_bad_block(
         &self,
-        _block_hash: B256,
-        _opts: Option<GethDebugTracingCallOptions>,
-    ) -> RpcResult<()> {
-        Ok(())
+        block_hash: B256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<Vec<TraceResult>> {
+        let _permit = self.acquire_trace_permit().await;
+        Ok(DebugApi::debug_trace_bad_block(self, block_hash, opts.unwrap_or_default()).await?)
     }
 
     async fn debug_verbosity(&self, _level: usize) -> RpcResult<()> {
@@ -1047,6 +1780,172 @@ impl<Provider, Eth> Clone for DebugApi<Provider, Eth> {
     }
 }
 
+/// Executes the transaction once and derives the result of every tracer configured for the
+/// `muxTracer` from the traces recorded by a single [TracingInspector].
+///
+/// Caution: this is blocking and should be performed on a blocking task.
+fn trace_mux(
+    config: GethDefaultTracingOptions,
+    mux_config: MuxConfig,
+    env: Env,
+    db: &mut SubState<StateProviderBox>,
+    tx_info: TransactionInfo,
+) -> EthResult<(GethTrace, revm_primitives::State)> {
+    // parse all configs upfront, so the inspector can record everything the tracers need
+    let mut inspector_config = TracingInspectorConfig::from_geth_config(&config);
+    let mut tracers = Vec::with_capacity(mux_config.0.len());
+    for (tracer, tracer_config) in mux_config.0 {
+        let tracer_config = tracer_config.unwrap_or_default();
+        let muxed = match tracer {
+            GethDebugBuiltInTracerType::FourByteTracer => MuxedTracer::FourByte,
+            GethDebugBuiltInTracerType::CallTracer => {
+                let call_config = tracer_config
+                    .into_call_config()
+                    .map_err(|_| EthApiError::InvalidTracerConfig)?;
+                if call_config.with_log.unwrap_or_default() {
+                    inspector_config = inspector_config.set_record_logs(true);
+                }
+                MuxedTracer::Call(call_config)
+            }
+            GethDebugBuiltInTracerType::FlatCallTracer => {
+                let flat_call_config = tracer_config
+                    .into_flat_call_config()
+                    .map_err(|_| EthApiError::InvalidTracerConfig)?;
+                // calls to precompiles are recorded for the other tracers and only excluded
+                // from the flat call traces
+                MuxedTracer::FlatCall(flat_call_config.include_precompiles.unwrap_or_default())
+            }
+            GethDebugBuiltInTracerType::PreStateTracer => {
+                let prestate_config = tracer_config
+                    .into_pre_state_config()
+                    .map_err(|_| EthApiError::InvalidTracerConfig)?;
+                if prestate_config.is_default_mode() {
+                    inspector_config = inspector_config.with_state_diffs();
+                }
+                MuxedTracer::PreState(prestate_config)
+            }
+            GethDebugBuiltInTracerType::NoopTracer => MuxedTracer::Noop,
+            GethDebugBuiltInTracerType::MuxTracer => return Err(EthApiError::InvalidTracerConfig),
+        };
+        tracers.push((tracer, muxed));
+    }
+
+    let mut inspector = TracingInspector::new(inspector_config);
+    let (res, _) = inspect(&mut *db, env, &mut inspector)?;
+    let gas_used = res.result.gas_used();
+
+    let mut frame = MuxFrame::default();
+    let builder = inspector.clone().into_geth_builder();
+    for (tracer, muxed) in tracers {
+        let trace = match muxed {
+            MuxedTracer::FourByte => builder.geth_four_byte_traces().into(),
+            MuxedTracer::Call(call_config) => {
+                builder.geth_call_traces(call_config, gas_used).into()
+            }
+            MuxedTracer::FlatCall(include_precompiles) => {
+                let mut builder =
+                    inspector.clone().with_transaction_gas_used(gas_used).into_parity_builder();
+                if !include_precompiles {
+                    builder = builder.without_precompile_calls();
+                }
+                builder.into_localized_transaction_traces(tx_info).into()
+            }
+            MuxedTracer::PreState(prestate_config) => {
+                builder.geth_prestate_traces(&res, prestate_config, &*db)?.into()
+            }
+            MuxedTracer::Noop => NoopFrame::default().into(),
+        };
+        frame.0.insert(tracer, trace);
+    }
+
+    Ok((frame.into(), res.state))
+}
+
+/// A tracer of the `muxTracer` with its parsed config.
+enum MuxedTracer {
+    FourByte,
+    Call(CallConfig),
+    /// Whether calls to precompiles are included.
+    FlatCall(bool),
+    PreState(PreStateConfig),
+    Noop,
+}
+
+/// Encodes a struct log trace like geth's json logger: one [JsonLoggerStep] per line, followed by
+/// the [JsonLoggerResult] of the transaction.
+///
+/// The trace must be recorded with memory enabled to fill in `memSize`, the memory itself is only
+/// included if enabled in `config`.
+fn json_logger_lines(
+    frame: DefaultFrame,
+    config: &GethDefaultTracingOptions,
+) -> serde_json::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut last_error = None;
+    for log in frame.struct_logs {
+        // the memory words are encoded by the tracer and always valid hex
+        let memory = hex::decode(log.memory.unwrap_or_default().concat()).unwrap_or_default();
+        // the struct log only has the name of the opcode, see the `Display` impl of `OpCode`
+        let op = opcode::OPCODE_JUMPMAP
+            .iter()
+            .position(|name| *name == Some(log.op.as_str()))
+            .map(|op| op as u8)
+            .or_else(|| {
+                let op = log.op.strip_prefix("UNKNOWN(0x")?.strip_suffix(')')?;
+                u8::from_str_radix(op, 16).ok()
+            })
+            .unwrap_or_default();
+        let step = JsonLoggerStep {
+            pc: log.pc,
+            op,
+            gas: log.gas,
+            gas_cost: log.gas_cost,
+            mem_size: memory.len() as u64,
+            memory: config.is_memory_enabled().then(|| memory.into()),
+            stack: log.stack,
+            return_data: log.return_data,
+            depth: log.depth,
+            refund: log.refund_counter.unwrap_or_default(),
+            op_name: log.op,
+            error: log.error,
+        };
+        serde_json::to_writer(&mut out, &step)?;
+        out.push(b'\n');
+        if step.error.is_some() {
+            last_error = step.error;
+        }
+    }
+
+    let result = JsonLoggerResult {
+        output: frame.return_value,
+        gas_used: frame.gas,
+        error: frame.failed.then(|| last_error.unwrap_or_else(|| "execution reverted".to_string())),
+    };
+    serde_json::to_writer(&mut out, &result)?;
+    out.push(b'\n');
+    Ok(out)
+}
+
+/// Writes `content` to a new file in `dir` whose name starts with `prefix` and ends with a random
+/// number, like go's `os.CreateTemp`.
+///
+/// The file is created exclusively, so an existing file or symlink is never written to.
+async fn write_new_temp_file(dir: &Path, prefix: &str, content: &[u8]) -> io::Result<PathBuf> {
+    for _ in 0..MAX_TEMP_FILE_ATTEMPTS {
+        let path = dir.join(format!("{prefix}{}", rand::random::<u32>()));
+        let mut file =
+            match tokio::fs::OpenOptions::new().write(true).create_new(true).open(&path).await {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            };
+        file.write_all(content).await?;
+        file.flush().await?;
+        return Ok(path)
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("no unused file name for {prefix}")))
+}
+
 struct DebugApiInner<Provider, Eth> {
     /// The provider that can interact with the chain.
     provider: Provider,
@@ -1056,4 +1955,151 @@ struct DebugApiInner<Provider, Eth> {
     blocking_task_guard: BlockingTaskGuard,
     /// The type that can spawn tasks which would otherwise block.
     task_spawner: Box<dyn TaskSpawner>,
+    /// Recently rejected blocks.
+    bad_blocks: BadBlockStore,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::revm_primitives::{AccountInfo, Bytecode, TransactTo};
+    use reth_provider::test_utils::NoopProvider;
+    use reth_rpc_types::trace::geth::{GethDebugTracerConfig, StructLog};
+    use std::collections::HashMap;
+
+    /// Traces a call to a contract that calls the identity precompile with the `muxTracer`.
+    fn trace_precompile_call(mux_config: MuxConfig) -> MuxFrame {
+        let contract = Address::with_last_byte(0xaa);
+        // STATICCALL(gas, 0x04, 0, 0, 0, 0)
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x5a, 0xfa, 0x00,
+        ]);
+        let state: StateProviderBox = Box::new(NoopProvider::default());
+        let mut db = CacheDB::new(StateProviderDatabase::new(state));
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code_hash: keccak256(&code),
+                code: Some(Bytecode::new_raw(code)),
+                ..Default::default()
+            },
+        );
+
+        let mut env = Env::default();
+        env.tx.caller = Address::with_last_byte(0xbb);
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+
+        let (trace, _) =
+            trace_mux(Default::default(), mux_config, env, &mut db, Default::default()).unwrap();
+        match trace {
+            GethTrace::MuxTracer(frame) => frame,
+            trace => panic!("unexpected trace: {trace:?}"),
+        }
+    }
+
+    #[test]
+    fn mux_call_and_flat_call_tracer_with_precompile_call() {
+        let frame = trace_precompile_call(MuxConfig(HashMap::from([
+            (GethDebugBuiltInTracerType::CallTracer, None),
+            (GethDebugBuiltInTracerType::FlatCallTracer, None),
+        ])));
+
+        // the call tracer always includes the call to the precompile
+        let GethTrace::CallTracer(call) = &frame.0[&GethDebugBuiltInTracerType::CallTracer] else {
+            panic!("expected call trace")
+        };
+        assert_eq!(call.calls.len(), 1);
+        assert_eq!(call.calls[0].to, Some(Address::with_last_byte(0x04)));
+
+        // the flat call tracer excludes it by default
+        let GethTrace::FlatCallTracer(traces) =
+            &frame.0[&GethDebugBuiltInTracerType::FlatCallTracer]
+        else {
+            panic!("expected flat call traces")
+        };
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].trace.subtraces, 0);
+    }
+
+    #[test]
+    fn mux_flat_call_tracer_includes_precompile_calls() {
+        let include_precompiles =
+            GethDebugTracerConfig(serde_json::json!({ "includePrecompiles": true }));
+        let frame = trace_precompile_call(MuxConfig(HashMap::from([
+            (GethDebugBuiltInTracerType::CallTracer, None),
+            (GethDebugBuiltInTracerType::FlatCallTracer, Some(include_precompiles)),
+        ])));
+
+        let GethTrace::CallTracer(call) = &frame.0[&GethDebugBuiltInTracerType::CallTracer] else {
+            panic!("expected call trace")
+        };
+        assert_eq!(call.calls.len(), 1);
+
+        let GethTrace::FlatCallTracer(traces) =
+            &frame.0[&GethDebugBuiltInTracerType::FlatCallTracer]
+        else {
+            panic!("expected flat call traces")
+        };
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].trace.subtraces, 1);
+        assert_eq!(traces[1].trace.trace_address, vec![0]);
+    }
+
+    #[test]
+    fn json_logger_lines_per_opcode() {
+        let frame = DefaultFrame {
+            failed: true,
+            gas: 21_005,
+            return_value: Bytes::default(),
+            struct_logs: vec![
+                StructLog {
+                    pc: 0,
+                    op: "PUSH1".to_string(),
+                    gas: 100,
+                    gas_cost: 3,
+                    memory: Some(vec![]),
+                    stack: Some(vec![]),
+                    depth: 1,
+                    ..Default::default()
+                },
+                StructLog {
+                    pc: 2,
+                    op: "UNKNOWN(0xEF)".to_string(),
+                    gas: 97,
+                    gas_cost: 97,
+                    memory: Some(vec!["00".repeat(32)]),
+                    stack: Some(vec![U256::from(1)]),
+                    depth: 1,
+                    error: Some("invalid opcode: opcode 0xef not defined".to_string()),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let lines = json_logger_lines(frame, &GethDefaultTracingOptions::default()).unwrap();
+        let lines = String::from_utf8(lines).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"pc":0,"op":96,"gas":"0x64","gasCost":"0x3","memSize":0,"stack":[],"depth":1,"refund":0,"opName":"PUSH1"}"#,
+                r#"{"pc":2,"op":239,"gas":"0x61","gasCost":"0x61","memSize":32,"stack":["0x1"],"depth":1,"refund":0,"opName":"UNKNOWN(0xEF)","error":"invalid opcode: opcode 0xef not defined"}"#,
+                r#"{"output":"","gasUsed":"0x520d","error":"invalid opcode: opcode 0xef not defined"}"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn write_new_temp_file_never_reuses_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_new_temp_file(dir.path(), "block_0x01-0-0x02-", b"first").await.unwrap();
+        let second =
+            write_new_temp_file(dir.path(), "block_0x01-0-0x02-", b"second").await.unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.file_name().unwrap().to_str().unwrap().starts_with("block_0x01-0-0x02-"));
+        assert_eq!(std::fs::read(first).unwrap(), b"first");
+        assert_eq!(std::fs::read(second).unwrap(), b"second");
+    }
 }
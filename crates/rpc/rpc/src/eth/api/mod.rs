@@ -9,10 +9,12 @@ use crate::eth::{
     cache::EthStateCache,
     error::{EthApiError, EthResult},
     gas_oracle::GasPriceOracle,
+    keystore::KeystoreSigner,
     signer::EthSigner,
 };
 
 use async_trait::async_trait;
+use parking_lot::RwLock;
 use reth_interfaces::RethResult;
 use reth_network_api::NetworkInfo;
 use reth_primitives::{
@@ -44,6 +46,7 @@ mod optimism;
 mod pending_block;
 mod server;
 mod sign;
+mod simulate;
 mod state;
 mod transactions;
 
@@ -110,6 +113,7 @@ where
             eth_cache,
             gas_oracle,
             gas_cap.into().into(),
+            DEFAULT_ETH_PROOF_WINDOW,
             Box::<TokioTaskExecutor>::default(),
             blocking_task_pool,
             fee_history_cache,
@@ -125,6 +129,7 @@ where
         eth_cache: EthStateCache,
         gas_oracle: GasPriceOracle<Provider>,
         gas_cap: u64,
+        eth_proof_window: u64,
         task_spawner: Box<dyn TaskSpawner>,
         blocking_task_pool: BlockingTaskPool,
         fee_history_cache: FeeHistoryCache,
@@ -145,6 +150,7 @@ where
             eth_cache,
             gas_oracle,
             gas_cap,
+            eth_proof_window,
             starting_block: U256::from(latest_block),
             task_spawner,
             pending_block: Default::default(),
@@ -192,6 +198,11 @@ where
         self.inner.gas_cap
     }
 
+    /// Returns the maximum number of blocks into the past for which `eth_getProof` is served
+    pub fn eth_proof_window(&self) -> u64 {
+        self.inner.eth_proof_window
+    }
+
     /// Returns the inner `Provider`
     pub fn provider(&self) -> &Provider {
         &self.inner.provider
@@ -211,6 +222,13 @@ where
     pub fn fee_history_cache(&self) -> &FeeHistoryCache {
         &self.inner.fee_history_cache
     }
+
+    /// Registers the accounts of the keystore for signing.
+    ///
+    /// The accounts are served via `eth_accounts` and can sign once they are unlocked.
+    pub fn add_keystore(&self, keystore: KeystoreSigner) {
+        self.inner.signers.write().push(Arc::new(keystore));
+    }
 }
 
 // === State access helpers ===
@@ -386,7 +404,7 @@ where
     }
 
     fn accounts(&self) -> Vec<Address> {
-        self.inner.signers.iter().flat_map(|s| s.accounts()).collect()
+        self.inner.signers.read().iter().flat_map(|s| s.accounts()).collect()
     }
 
     fn is_syncing(&self) -> bool {
@@ -420,6 +438,15 @@ where
 /// more complex calls.
 pub const RPC_DEFAULT_GAS_CAP: GasCap = GasCap(50_000_000);
 
+/// The default maximum number of blocks into the past for which `eth_getProof` is served.
+///
+/// By default only proofs for the latest block are served.
+pub const DEFAULT_ETH_PROOF_WINDOW: u64 = 0;
+
+/// The maximum configurable number of blocks into the past for which `eth_getProof` is served,
+/// roughly 28 days of 12 second blocks.
+pub const MAX_ETH_PROOF_WINDOW: u64 = 28 * 24 * 60 * 60 / 12;
+
 /// The wrapper type for gas limit
 #[derive(Debug, Clone, Copy)]
 pub struct GasCap(u64);
@@ -451,13 +478,15 @@ struct EthApiInner<Provider, Pool, Network> {
     /// An interface to interact with the network
     network: Network,
     /// All configured Signers
-    signers: Vec<Box<dyn EthSigner>>,
+    signers: RwLock<Vec<Arc<dyn EthSigner>>>,
     /// The async cache frontend for eth related data
     eth_cache: EthStateCache,
     /// The async gas oracle frontend for gas price suggestions
     gas_oracle: GasPriceOracle<Provider>,
     /// Maximum gas limit for `eth_call` and call tracing RPC methods.
     gas_cap: u64,
+    /// Maximum number of blocks into the past for which proofs are generated.
+    eth_proof_window: u64,
     /// The block number at which the node started
     starting_block: U256,
     /// The type that can spawn tasks which would otherwise block.
@@ -0,0 +1,790 @@
+//! Contains the implementation of `eth_simulateV1`, which executes calls in a sequence of simulated
+//! blocks.
+
+use crate::{
+    eth::{
+        error::{EthApiError, EthResult, RevertError, RpcInvalidTransactionError},
+        revm_utils::{apply_state_overrides, build_call_evm_env, inspect, transact},
+        EthTransactions,
+    },
+    EthApi,
+};
+use reth_network_api::NetworkInfo;
+use reth_primitives::{
+    constants::{BEACON_NONCE, EMPTY_WITHDRAWALS},
+    logs_bloom, proofs,
+    revm::{
+        compat::into_reth_log,
+        env::{fill_block_env_with_coinbase, fill_cfg_env},
+    },
+    Block, BlockId, BlockNumberOrTag, Bytes, ChainSpec, Header, Receipt, SealedHeader, Signature,
+    Transaction, TransactionKind, TransactionSigned, TxEip1559, TxEip2930, TxEip4844, TxLegacy,
+    B256, EMPTY_OMMER_ROOT_HASH, U256, U64,
+};
+use reth_provider::{
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory,
+};
+use reth_revm::{database::StateProviderDatabase, transfer::TransferInspector};
+use reth_rpc_types::{
+    simulate::{SimBlock, SimCallResult, SimulateError, SimulatePayload, SimulatedBlock},
+    AccessList, BlockOverrides, CallRequest, Log,
+};
+use reth_rpc_types_compat::block::from_block;
+use reth_transaction_pool::TransactionPool;
+use revm::{
+    db::{CacheDB, DatabaseRef},
+    primitives::{BlockEnv, CfgEnv, ExecutionResult, SpecId, TransactTo, TxEnv},
+    DatabaseCommit,
+};
+use std::sync::Arc;
+
+/// The maximum number of blocks, including the blocks that fill gaps between block numbers, that
+/// can be simulated with a single `eth_simulateV1` request.
+const MAX_SIMULATE_BLOCKS: u64 = 256;
+
+/// The time between two simulated blocks if the timestamp is not overridden.
+const SIMULATED_BLOCK_TIME: u64 = 12;
+
+/// The error code of a simulated call that reverted.
+const SIMULATE_REVERT_CODE: i32 = 3;
+
+/// The error code of a simulated call that halted.
+const SIMULATE_VM_ERROR_CODE: i32 = -32015;
+
+impl<Provider, Pool, Network> EthApi<Provider, Pool, Network>
+where
+    Pool: TransactionPool + Clone + 'static,
+    Provider:
+        BlockReaderIdExt + ChainSpecProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Network: NetworkInfo + Send + Sync + 'static,
+{
+    /// Simulates the blocks of the [SimulatePayload] on top of the given block (`eth_simulateV1`).
+    ///
+    /// Every simulated block is the child of the previously simulated block: state changes carry
+    /// over, gaps between the requested block numbers are filled with empty blocks and the
+    /// `BLOCKHASH` of a simulated block returns the hash of the simulated block.
+    ///
+    /// Note: the state root of the simulated blocks is not computed and always zero, because the
+    /// state overrides can't be applied to the state trie.
+    pub async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block_number: Option<BlockId>,
+    ) -> EthResult<Vec<SimulatedBlock>> {
+        let SimulatePayload {
+            block_state_calls,
+            trace_transfers,
+            validation,
+            return_full_transactions,
+        } = payload;
+        if block_state_calls.is_empty() {
+            return Err(EthApiError::InvalidParams(String::from("block state calls are empty.")))
+        }
+
+        let target_block = block_number.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let (cfg, _, at) = self.evm_env_at(target_block).await?;
+
+        let provider = self.provider();
+        let parent = provider.sealed_header_by_id(at)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let total_difficulty = match provider.header_td(&parent.hash)? {
+            Some(td) => td,
+            // the pending block is not stored yet
+            None => {
+                provider.header_td(&parent.parent_hash)?.unwrap_or_default() + parent.difficulty
+            }
+        };
+
+        let mut simulator = BlockSimulator {
+            chain_spec: provider.chain_spec(),
+            cfg,
+            max_block_number: parent.number + MAX_SIMULATE_BLOCKS,
+            parent,
+            total_difficulty,
+            gas_cap: self.inner.gas_cap,
+            trace_transfers,
+            validation,
+            return_full_transactions,
+            blocks: Vec::with_capacity(block_state_calls.len()),
+        };
+
+        self.spawn_with_state_at_block(at, move |state| {
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+            for block in block_state_calls {
+                simulator.simulate_block(&mut db, block)?;
+            }
+            Ok(simulator.blocks)
+        })
+        .await
+    }
+}
+
+/// Builds the simulated blocks of an `eth_simulateV1` request on top of each other.
+#[derive(Debug)]
+struct BlockSimulator {
+    chain_spec: Arc<ChainSpec>,
+    /// The config of the block the simulation is based on.
+    cfg: CfgEnv,
+    /// The header of the last simulated block, or of the block the simulation is based on.
+    parent: SealedHeader,
+    /// The total difficulty of the parent.
+    total_difficulty: U256,
+    /// The highest block number that can be simulated.
+    max_block_number: u64,
+    /// The gas that is left for all following calls.
+    gas_cap: u64,
+    trace_transfers: bool,
+    validation: bool,
+    return_full_transactions: bool,
+    /// All blocks simulated so far.
+    blocks: Vec<SimulatedBlock>,
+}
+
+impl BlockSimulator {
+    /// Simulates the given block, preceded by empty blocks if its number is not the next one.
+    fn simulate_block<DB>(&mut self, db: &mut CacheDB<DB>, block: SimBlock) -> EthResult<()>
+    where
+        DB: DatabaseRef,
+        EthApiError: From<<DB as DatabaseRef>::Error>,
+    {
+        let SimBlock { block_overrides, state_overrides, calls } = block;
+        let mut block_overrides = block_overrides.unwrap_or_default();
+
+        if let Some(number) = block_overrides.number.take() {
+            let number: u64 = number.try_into().map_err(|_| {
+                EthApiError::InvalidParams(String::from("block number is too large."))
+            })?;
+            if number <= self.parent.number {
+                return Err(EthApiError::InvalidParams(format!(
+                    "block number {number} is not greater than the previous block number {}.",
+                    self.parent.number
+                )))
+            }
+            if number > self.max_block_number {
+                return Err(EthApiError::InvalidParams(format!(
+                    "too many blocks, at most {MAX_SIMULATE_BLOCKS} blocks can be simulated."
+                )))
+            }
+
+            // fill the gap with empty blocks
+            while self.parent.number + 1 < number {
+                self.execute_block(db, BlockOverrides::default(), Vec::new())?;
+            }
+        } else if self.parent.number >= self.max_block_number {
+            return Err(EthApiError::InvalidParams(format!(
+                "too many blocks, at most {MAX_SIMULATE_BLOCKS} blocks can be simulated."
+            )))
+        }
+
+        if let Some(block_hashes) = block_overrides.block_hash.take() {
+            db.block_hashes
+                .extend(block_hashes.into_iter().map(|(num, hash)| (U256::from(num), hash)));
+        }
+        if let Some(state_overrides) = state_overrides {
+            apply_state_overrides(state_overrides, db)?;
+        }
+
+        self.execute_block(db, block_overrides, calls)
+    }
+
+    /// Executes the calls in a new child block of the parent and appends the sealed block.
+    fn execute_block<DB>(
+        &mut self,
+        db: &mut CacheDB<DB>,
+        block_overrides: BlockOverrides,
+        calls: Vec<CallRequest>,
+    ) -> EthResult<()>
+    where
+        DB: DatabaseRef,
+        EthApiError: From<<DB as DatabaseRef>::Error>,
+    {
+        let mut header = self.next_header(block_overrides)?;
+
+        let mut cfg = self.cfg.clone();
+        fill_cfg_env(&mut cfg, &self.chain_spec, &header, self.total_difficulty);
+        // without validation the calls are executed like `eth_call`
+        cfg.disable_base_fee = !self.validation;
+        cfg.disable_eip3607 = !self.validation;
+
+        let mut block_env = BlockEnv::default();
+        let after_merge = cfg.spec_id >= SpecId::MERGE;
+        fill_block_env_with_coinbase(&mut block_env, &header, after_merge, header.beneficiary);
+
+        let mut transactions = Vec::with_capacity(calls.len());
+        let mut senders = Vec::with_capacity(calls.len());
+        let mut receipts = Vec::with_capacity(calls.len());
+        let mut results = Vec::with_capacity(calls.len());
+        let mut cumulative_gas_used = 0u64;
+        let mut blob_gas_used = 0u64;
+
+        for call in calls {
+            let gas_left = header.gas_limit - cumulative_gas_used;
+            let gas_limit = match call.gas {
+                Some(gas) if gas > U256::from(gas_left) => {
+                    return Err(EthApiError::InvalidParams(format!(
+                        "block gas limit reached in block {}.",
+                        header.number
+                    )))
+                }
+                Some(gas) => gas.to(),
+                None => gas_left,
+            };
+
+            let is_dynamic_fee = call.max_fee_per_gas.is_some() ||
+                call.max_priority_fee_per_gas.is_some() ||
+                call.max_fee_per_blob_gas.is_some();
+            let access_list = call.access_list.clone();
+
+            let mut env = build_call_evm_env(cfg.clone(), block_env.clone(), call)?;
+            env.tx.gas_limit = gas_limit.min(self.gas_cap);
+
+            let nonce = match env.tx.nonce {
+                Some(nonce) => nonce,
+                None => db.basic_ref(env.tx.caller)?.map(|acc| acc.nonce).unwrap_or_default(),
+            };
+            // nonces are only checked if the calls are validated
+            env.tx.nonce = self.validation.then_some(nonce);
+
+            let tx =
+                simulated_transaction(&env.tx, nonce, cfg.chain_id, is_dynamic_fee, access_list);
+            let sender = env.tx.caller;
+
+            let (res, logs) = if self.trace_transfers {
+                let mut inspector = TransferInspector::default();
+                let (res, _) = inspect(&mut *db, env, &mut inspector)?;
+                (res, inspector.into_logs())
+            } else {
+                let (res, _) = transact(&mut *db, env)?;
+                let logs = res.result.logs();
+                (res, logs)
+            };
+            db.commit(res.state);
+
+            let result = res.result;
+            let gas_used = result.gas_used();
+            cumulative_gas_used += gas_used;
+            self.gas_cap = self.gas_cap.saturating_sub(gas_used);
+            if let Some(blob_tx) = tx.as_eip4844() {
+                blob_gas_used += blob_tx.blob_gas();
+            }
+
+            receipts.push(Receipt {
+                tx_type: tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                logs: result.logs().into_iter().map(into_reth_log).collect(),
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            });
+
+            let (return_data, error) = match result {
+                ExecutionResult::Success { output, .. } => (output.into_data(), None),
+                ExecutionResult::Revert { output, .. } => {
+                    let message = RevertError::new(output.clone()).to_string();
+                    (output, Some(SimulateError { code: SIMULATE_REVERT_CODE, message }))
+                }
+                ExecutionResult::Halt { reason, gas_used } => {
+                    let message = RpcInvalidTransactionError::halt(reason, gas_used).to_string();
+                    (Bytes::new(), Some(SimulateError { code: SIMULATE_VM_ERROR_CODE, message }))
+                }
+            };
+            let status = U64::from(error.is_none() as u8);
+            results.push((
+                SimCallResult {
+                    return_data,
+                    logs: Vec::new(),
+                    gas_used: U64::from(gas_used),
+                    status,
+                    error,
+                },
+                logs,
+            ));
+
+            transactions.push(tx);
+            senders.push(sender);
+        }
+
+        let receipts = receipts.into_iter().map(Receipt::with_bloom).collect::<Vec<_>>();
+        header.receipts_root = proofs::calculate_receipt_root(
+            &receipts,
+            #[cfg(feature = "optimism")]
+            self.chain_spec.as_ref(),
+            #[cfg(feature = "optimism")]
+            header.timestamp,
+        );
+        header.logs_bloom = logs_bloom(receipts.iter().flat_map(|r| r.receipt.logs.iter()));
+        header.transactions_root = proofs::calculate_transaction_root(&transactions);
+        header.gas_used = cumulative_gas_used;
+        if header.excess_blob_gas.is_some() {
+            header.blob_gas_used = Some(blob_gas_used);
+        }
+
+        let withdrawals = header.withdrawals_root.is_some().then(Vec::new);
+        let block = Block { header, body: transactions, ommers: vec![], withdrawals }.seal_slow();
+        let (block_hash, block_number) = (block.hash, block.number);
+        db.block_hashes.insert(U256::from(block_number), block_hash);
+        self.total_difficulty += block.difficulty;
+
+        // populate the logs of the calls with the context of the sealed block
+        let mut log_index = 0;
+        let calls = results
+            .into_iter()
+            .zip(block.body.iter())
+            .enumerate()
+            .map(|(tx_index, ((mut call, logs), tx))| {
+                call.logs = logs
+                    .into_iter()
+                    .map(|log| {
+                        let log = Log {
+                            address: log.address,
+                            topics: log.topics,
+                            data: log.data,
+                            block_hash: Some(block_hash),
+                            block_number: Some(U256::from(block_number)),
+                            transaction_hash: Some(tx.hash),
+                            transaction_index: Some(U256::from(tx_index)),
+                            log_index: Some(U256::from(log_index)),
+                            removed: false,
+                        };
+                        log_index += 1;
+                        log
+                    })
+                    .collect();
+                call
+            })
+            .collect();
+
+        let header = block.header.clone();
+        let inner = from_block(
+            block.unseal().with_senders(senders),
+            self.total_difficulty,
+            self.return_full_transactions.into(),
+            Some(block_hash),
+        )?;
+        self.blocks.push(SimulatedBlock { inner, calls });
+        self.parent = header;
+
+        Ok(())
+    }
+
+    /// Returns the header of the next simulated block, without the fields that depend on the
+    /// executed calls.
+    fn next_header(&self, block_overrides: BlockOverrides) -> EthResult<Header> {
+        let BlockOverrides { difficulty, time, gas_limit, coinbase, random, base_fee, .. } =
+            block_overrides;
+        let parent = &self.parent;
+
+        let timestamp =
+            time.map(|time| time.to()).unwrap_or(parent.timestamp + SIMULATED_BLOCK_TIME);
+        if timestamp <= parent.timestamp {
+            return Err(EthApiError::InvalidParams(format!(
+                "block timestamp {timestamp} is not greater than the previous block timestamp {}.",
+                parent.timestamp
+            )))
+        }
+
+        let base_fee_per_gas = match base_fee {
+            Some(base_fee) => Some(base_fee.saturating_to()),
+            None => parent.next_block_base_fee(self.chain_spec.base_fee_params(timestamp)),
+        };
+        let is_shanghai = self.chain_spec.is_shanghai_active_at_timestamp(timestamp);
+        let is_cancun = self.chain_spec.is_cancun_active_at_timestamp(timestamp);
+
+        Ok(Header {
+            parent_hash: parent.hash,
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary: coinbase.unwrap_or(parent.beneficiary),
+            state_root: B256::ZERO,
+            withdrawals_root: is_shanghai.then_some(EMPTY_WITHDRAWALS),
+            difficulty: difficulty.unwrap_or(parent.difficulty),
+            number: parent.number + 1,
+            gas_limit: gas_limit.map(|gas_limit| gas_limit.to()).unwrap_or(parent.gas_limit),
+            timestamp,
+            mix_hash: random.unwrap_or_default(),
+            nonce: BEACON_NONCE,
+            base_fee_per_gas,
+            excess_blob_gas: is_cancun
+                .then(|| parent.next_block_excess_blob_gas().unwrap_or_default()),
+            parent_beacon_block_root: is_cancun.then_some(B256::ZERO),
+            ..Default::default()
+        })
+    }
+}
+
+/// Returns the transaction of a simulated call that is included in the simulated block.
+///
+/// Simulated calls are not signed, so the transaction is included with an empty signature.
+fn simulated_transaction(
+    tx: &TxEnv,
+    nonce: u64,
+    chain_id: u64,
+    is_dynamic_fee: bool,
+    access_list: Option<AccessList>,
+) -> TransactionSigned {
+    let to = match tx.transact_to {
+        TransactTo::Call(to) => TransactionKind::Call(to),
+        TransactTo::Create(_) => TransactionKind::Create,
+    };
+    let access_list: reth_primitives::AccessList = access_list.map(Into::into).unwrap_or_default();
+    let gas_price = tx.gas_price.saturating_to();
+    let max_priority_fee_per_gas = tx.gas_priority_fee.unwrap_or_default().saturating_to();
+
+    let transaction = if !tx.blob_hashes.is_empty() {
+        Transaction::Eip4844(TxEip4844 {
+            chain_id,
+            nonce,
+            gas_limit: tx.gas_limit,
+            max_fee_per_gas: gas_price,
+            max_priority_fee_per_gas,
+            to,
+            value: tx.value.into(),
+            access_list,
+            blob_versioned_hashes: tx.blob_hashes.clone(),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas.unwrap_or_default().saturating_to(),
+            input: tx.data.clone(),
+        })
+    } else if is_dynamic_fee {
+        Transaction::Eip1559(TxEip1559 {
+            chain_id,
+            nonce,
+            gas_limit: tx.gas_limit,
+            max_fee_per_gas: gas_price,
+            max_priority_fee_per_gas,
+            to,
+            value: tx.value.into(),
+            access_list,
+            input: tx.data.clone(),
+        })
+    } else if !access_list.0.is_empty() {
+        Transaction::Eip2930(TxEip2930 {
+            chain_id,
+            nonce,
+            gas_price,
+            gas_limit: tx.gas_limit,
+            to,
+            value: tx.value.into(),
+            access_list,
+            input: tx.data.clone(),
+        })
+    } else {
+        Transaction::Legacy(TxLegacy {
+            chain_id: Some(chain_id),
+            nonce,
+            gas_price,
+            gas_limit: tx.gas_limit,
+            to,
+            value: tx.value.into(),
+            input: tx.data.clone(),
+        })
+    };
+
+    TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{bytes, Address, ChainSpecBuilder, TxType};
+    use reth_provider::test_utils::NoopProvider;
+    use reth_revm::transfer::{TRANSFER_EVENT_TOPIC, TRANSFER_LOG_EMITTER};
+    use reth_rpc_types::state::{AccountOverride, StateOverride};
+
+    const ETHER: u128 = 1_000_000_000_000_000_000;
+
+    /// Returns a simulator on top of a Cancun block with the given options.
+    fn test_simulator(validation: bool, trace_transfers: bool) -> BlockSimulator {
+        let parent = Header {
+            number: 10,
+            timestamp: 1_700_000_000,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            ..Default::default()
+        }
+        .seal_slow();
+        BlockSimulator {
+            chain_spec: Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build()),
+            cfg: CfgEnv::default(),
+            max_block_number: parent.number + MAX_SIMULATE_BLOCKS,
+            parent,
+            total_difficulty: U256::ZERO,
+            gas_cap: 50_000_000,
+            trace_transfers,
+            validation,
+            return_full_transactions: false,
+            blocks: Vec::new(),
+        }
+    }
+
+    fn empty_state() -> CacheDB<StateProviderDatabase<NoopProvider>> {
+        CacheDB::new(StateProviderDatabase::new(NoopProvider::default()))
+    }
+
+    fn fund(account: Address) -> StateOverride {
+        StateOverride::from([(
+            account,
+            AccountOverride { balance: Some(U256::from(ETHER)), ..Default::default() },
+        )])
+    }
+
+    fn transfer(from: Address, to: Address, value: u128) -> CallRequest {
+        CallRequest {
+            from: Some(from),
+            to: Some(to),
+            value: Some(U256::from(value)),
+            ..Default::default()
+        }
+    }
+
+    fn balance<DB: DatabaseRef>(db: &CacheDB<DB>, account: Address) -> U256 {
+        db.basic_ref(account).ok().flatten().map(|acc| acc.balance).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_simulated_blocks_are_chained() {
+        let (alice, bob, carol) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let mut simulator = test_simulator(false, false);
+        let mut db = empty_state();
+
+        simulator
+            .simulate_block(
+                &mut db,
+                SimBlock {
+                    block_overrides: None,
+                    state_overrides: Some(fund(alice)),
+                    calls: vec![transfer(alice, bob, ETHER / 2)],
+                },
+            )
+            .unwrap();
+        // bob can only spend the ether he received in the previous block
+        simulator
+            .simulate_block(
+                &mut db,
+                SimBlock {
+                    block_overrides: Some(BlockOverrides {
+                        number: Some(U256::from(14)),
+                        ..Default::default()
+                    }),
+                    state_overrides: None,
+                    calls: vec![transfer(bob, carol, ETHER / 4)],
+                },
+            )
+            .unwrap();
+
+        // blocks 12 and 13 fill the gap
+        let blocks = &simulator.blocks;
+        assert_eq!(blocks.len(), 4);
+        let mut parent_hash = None;
+        for (block, number) in blocks.iter().zip(11u64..) {
+            let header = &block.inner.header;
+            assert_eq!(header.number, Some(U256::from(number)));
+            assert_eq!(
+                header.timestamp,
+                U256::from(1_700_000_000 + (number - 10) * SIMULATED_BLOCK_TIME)
+            );
+            if let Some(parent_hash) = parent_hash {
+                assert_eq!(header.parent_hash, parent_hash);
+            }
+            parent_hash = header.hash;
+        }
+        assert_eq!(blocks[0].calls.len(), 1);
+        assert!(blocks[1].calls.is_empty());
+        assert!(blocks[2].calls.is_empty());
+        assert_eq!(blocks[3].calls[0].status, U64::from(1));
+
+        assert_eq!(balance(&db, alice), U256::from(ETHER / 2));
+        assert_eq!(balance(&db, bob), U256::from(ETHER / 4));
+        assert_eq!(balance(&db, carol), U256::from(ETHER / 4));
+        // the hashes of the simulated blocks are returned by `BLOCKHASH`
+        assert_eq!(db.block_hashes.get(&U256::from(14)).copied(), parent_hash);
+
+        // simulated blocks must be in order
+        let err = simulator
+            .simulate_block(
+                &mut db,
+                SimBlock {
+                    block_overrides: Some(BlockOverrides {
+                        number: Some(U256::from(14)),
+                        ..Default::default()
+                    }),
+                    state_overrides: None,
+                    calls: Vec::new(),
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)));
+    }
+
+    #[test]
+    fn test_simulate_overrides() {
+        let caller = Address::with_last_byte(1);
+        let (storage, clock) = (Address::with_last_byte(0xaa), Address::with_last_byte(0xbb));
+        let coinbase = Address::with_last_byte(0xcc);
+        let mut simulator = test_simulator(false, false);
+        let mut db = empty_state();
+
+        let state_overrides = StateOverride::from([
+            (
+                storage,
+                AccountOverride {
+                    // returns the value of slot 0
+                    code: Some(bytes!("60005460005260206000f3")),
+                    state: Some([(B256::ZERO, U256::from(42))].into()),
+                    ..Default::default()
+                },
+            ),
+            (
+                clock,
+                AccountOverride {
+                    // returns the timestamp of the block
+                    code: Some(bytes!("4260005260206000f3")),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let block_overrides = BlockOverrides {
+            time: Some(U64::from(1_800_000_000u64)),
+            gas_limit: Some(U64::from(20_000_000u64)),
+            coinbase: Some(coinbase),
+            base_fee: Some(U256::from(100)),
+            ..Default::default()
+        };
+        let call = |to| CallRequest { from: Some(caller), to: Some(to), ..Default::default() };
+        simulator
+            .simulate_block(
+                &mut db,
+                SimBlock {
+                    block_overrides: Some(block_overrides),
+                    state_overrides: Some(state_overrides),
+                    calls: vec![call(storage), call(clock)],
+                },
+            )
+            .unwrap();
+
+        let block = &simulator.blocks[0];
+        assert_eq!(block.calls[0].return_data, Bytes::from(U256::from(42).to_be_bytes_vec()));
+        assert_eq!(
+            block.calls[1].return_data,
+            Bytes::from(U256::from(1_800_000_000u64).to_be_bytes_vec())
+        );
+        let header = &block.inner.header;
+        assert_eq!(header.timestamp, U256::from(1_800_000_000u64));
+        assert_eq!(header.gas_limit, U256::from(20_000_000u64));
+        assert_eq!(header.miner, coinbase);
+        assert_eq!(header.base_fee_per_gas, Some(U256::from(100)));
+
+        // the timestamp must increase
+        let err = simulator
+            .simulate_block(
+                &mut db,
+                SimBlock {
+                    block_overrides: Some(BlockOverrides {
+                        time: Some(U64::from(1_800_000_000u64)),
+                        ..Default::default()
+                    }),
+                    state_overrides: None,
+                    calls: Vec::new(),
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)));
+    }
+
+    #[test]
+    fn test_simulate_validation() {
+        let (alice, bob) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let simulate = |validation: bool, call: CallRequest| {
+            test_simulator(validation, false).simulate_block(
+                &mut empty_state(),
+                SimBlock {
+                    block_overrides: None,
+                    state_overrides: Some(fund(alice)),
+                    calls: vec![call],
+                },
+            )
+        };
+
+        // the fee cap is below the base fee of the block
+        let call = transfer(alice, bob, 1);
+        assert!(simulate(false, call.clone()).is_ok());
+        let err = simulate(true, call).unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::InvalidTransaction(RpcInvalidTransactionError::FeeCapTooLow)
+        ));
+
+        // the nonce is ahead of the account nonce
+        let call = CallRequest {
+            max_fee_per_gas: Some(U256::from(100)),
+            nonce: Some(U64::from(5)),
+            ..transfer(alice, bob, 1)
+        };
+        assert!(simulate(false, call.clone()).is_ok());
+        let err = simulate(true, call).unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::InvalidTransaction(RpcInvalidTransactionError::NonceTooHigh)
+        ));
+
+        // a valid transaction passes validation
+        let call =
+            CallRequest { max_fee_per_gas: Some(U256::from(100)), ..transfer(alice, bob, 1) };
+        assert!(simulate(true, call).is_ok());
+    }
+
+    #[test]
+    fn test_simulate_trace_transfers() {
+        let (alice, bob) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let block = || SimBlock {
+            block_overrides: None,
+            state_overrides: Some(fund(alice)),
+            calls: vec![transfer(alice, bob, 1000), transfer(alice, bob, 0)],
+        };
+
+        let mut simulator = test_simulator(false, true);
+        simulator.simulate_block(&mut empty_state(), block()).unwrap();
+        let block_hash = simulator.blocks[0].inner.header.hash;
+        let calls = &simulator.blocks[0].calls;
+
+        assert_eq!(calls[0].logs.len(), 1);
+        let log = &calls[0].logs[0];
+        assert_eq!(log.address, TRANSFER_LOG_EMITTER);
+        assert_eq!(log.topics, vec![TRANSFER_EVENT_TOPIC, alice.into_word(), bob.into_word()]);
+        assert_eq!(log.data, Bytes::from(U256::from(1000).to_be_bytes_vec()));
+        assert_eq!(log.block_hash, block_hash);
+        assert_eq!(log.block_number, Some(U256::from(11)));
+        assert_eq!(log.transaction_index, Some(U256::ZERO));
+        assert_eq!(log.log_index, Some(U256::ZERO));
+        // transfers without value are not logged
+        assert!(calls[1].logs.is_empty());
+
+        // transfers are only logged if requested
+        let mut simulator = test_simulator(false, false);
+        simulator.simulate_block(&mut empty_state(), block()).unwrap();
+        assert!(simulator.blocks[0].calls[0].logs.is_empty());
+    }
+
+    #[test]
+    fn test_simulated_transaction_type() {
+        let tx = TxEnv {
+            transact_to: TransactTo::Call(Address::with_last_byte(1)),
+            value: U256::from(1),
+            ..Default::default()
+        };
+        let legacy = simulated_transaction(&tx, 1, 1, false, None);
+        assert_eq!(legacy.tx_type(), TxType::Legacy);
+        assert_eq!(legacy.nonce(), 1);
+        assert_eq!(legacy.to(), Some(Address::with_last_byte(1)));
+
+        let eip1559 = simulated_transaction(&tx, 1, 1, true, None);
+        assert_eq!(eip1559.tx_type(), TxType::EIP1559);
+        assert_ne!(legacy.hash(), eip1559.hash());
+
+        let access_list = AccessList(vec![Default::default()]);
+        let eip2930 = simulated_transaction(&tx, 1, 1, false, Some(access_list));
+        assert_eq!(eip2930.tx_type(), TxType::EIP2930);
+    }
+}
@@ -154,6 +154,10 @@ pub trait EthTransactions: Send + Sync {
     /// Returns the hash of the signed transaction.
     async fn send_transaction(&self, request: TransactionRequest) -> EthResult<B256>;
 
+    /// Signs transaction with a matching signer, if any, without submitting it.
+    /// Returns the EIP-2718 encoded signed transaction.
+    async fn sign_transaction(&self, request: TransactionRequest) -> EthResult<Bytes>;
+
     /// Prepares the state and env for the given [CallRequest] at the given [BlockId] and executes
     /// the closure on a new task returning the result of the closure.
     async fn spawn_with_call_at<F, R>(
@@ -512,82 +516,8 @@ where
         Ok(hash)
     }
 
-    async fn send_transaction(&self, mut request: TransactionRequest) -> EthResult<B256> {
-        let from = match request.from {
-            Some(from) => from,
-            None => return Err(SignError::NoAccount.into()),
-        };
-
-        // set nonce if not already set before
-        if request.nonce.is_none() {
-            let nonce =
-                self.get_transaction_count(from, Some(BlockId::Number(BlockNumberOrTag::Pending)))?;
-            // note: `.to()` can't panic because the nonce is constructed from a `u64`
-            request.nonce = Some(U64::from(nonce.to::<u64>()));
-        }
-
-        let chain_id = self.chain_id();
-        // TODO: we need an oracle to fetch the gas price of the current chain
-        let gas_price = request.gas_price.unwrap_or_default();
-        let max_fee_per_gas = request.max_fee_per_gas.unwrap_or_default();
-
-        let estimated_gas = self
-            .estimate_gas_at(
-                CallRequest {
-                    from: Some(from),
-                    to: request.to,
-                    gas: request.gas,
-                    gas_price: Some(U256::from(gas_price)),
-                    max_fee_per_gas: Some(U256::from(max_fee_per_gas)),
-                    value: request.value,
-                    input: request.input.clone().into(),
-                    nonce: request.nonce,
-                    chain_id: Some(chain_id),
-                    access_list: request.access_list.clone(),
-                    max_priority_fee_per_gas: Some(U256::from(max_fee_per_gas)),
-                    transaction_type: None,
-                    blob_versioned_hashes: None,
-                    max_fee_per_blob_gas: None,
-                },
-                BlockId::Number(BlockNumberOrTag::Pending),
-                None,
-            )
-            .await?;
-        let gas_limit = estimated_gas;
-
-        let transaction = match request.into_typed_request() {
-            Some(TypedTransactionRequest::Legacy(mut m)) => {
-                m.chain_id = Some(chain_id.to());
-                m.gas_limit = gas_limit;
-                m.gas_price = gas_price;
-
-                TypedTransactionRequest::Legacy(m)
-            }
-            Some(TypedTransactionRequest::EIP2930(mut m)) => {
-                m.chain_id = chain_id.to();
-                m.gas_limit = gas_limit;
-                m.gas_price = gas_price;
-
-                TypedTransactionRequest::EIP2930(m)
-            }
-            Some(TypedTransactionRequest::EIP1559(mut m)) => {
-                m.chain_id = chain_id.to();
-                m.gas_limit = gas_limit;
-                m.max_fee_per_gas = max_fee_per_gas;
-
-                TypedTransactionRequest::EIP1559(m)
-            }
-            Some(TypedTransactionRequest::EIP4844(mut m)) => {
-                m.chain_id = chain_id.to();
-                m.gas_limit = gas_limit;
-                m.max_fee_per_gas = max_fee_per_gas;
-
-                TypedTransactionRequest::EIP4844(m)
-            }
-            None => return Err(EthApiError::ConflictingFeeFieldsInRequest),
-        };
-
-        let signed_tx = self.sign_request(&from, transaction)?;
+    async fn send_transaction(&self, request: TransactionRequest) -> EthResult<B256> {
+        let signed_tx = self.fill_and_sign_request(request).await?;
 
         let recovered =
             signed_tx.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
@@ -601,6 +531,11 @@ where
         Ok(hash)
     }
 
+    async fn sign_transaction(&self, request: TransactionRequest) -> EthResult<Bytes> {
+        let signed_tx = self.fill_and_sign_request(request).await?;
+        Ok(signed_tx.envelope_encoded())
+    }
+
     async fn spawn_with_call_at<F, R>(
         &self,
         request: CallRequest,
@@ -873,6 +808,89 @@ where
         BlockReaderIdExt + ChainSpecProvider + StateProviderFactory + EvmEnvProvider + 'static,
     Network: NetworkInfo + Send + Sync + 'static,
 {
+    /// Fills the missing nonce, chain id and gas limit of the request and signs it with a matching
+    /// signer.
+    async fn fill_and_sign_request(
+        &self,
+        mut request: TransactionRequest,
+    ) -> EthResult<TransactionSigned> {
+        let from = match request.from {
+            Some(from) => from,
+            None => return Err(SignError::NoAccount.into()),
+        };
+
+        // set nonce if not already set before
+        if request.nonce.is_none() {
+            let nonce =
+                self.get_transaction_count(from, Some(BlockId::Number(BlockNumberOrTag::Pending)))?;
+            // note: `.to()` can't panic because the nonce is constructed from a `u64`
+            request.nonce = Some(U64::from(nonce.to::<u64>()));
+        }
+
+        let chain_id = self.chain_id();
+        // TODO: we need an oracle to fetch the gas price of the current chain
+        let gas_price = request.gas_price.unwrap_or_default();
+        let max_fee_per_gas = request.max_fee_per_gas.unwrap_or_default();
+
+        let estimated_gas = self
+            .estimate_gas_at(
+                CallRequest {
+                    from: Some(from),
+                    to: request.to,
+                    gas: request.gas,
+                    gas_price: Some(U256::from(gas_price)),
+                    max_fee_per_gas: Some(U256::from(max_fee_per_gas)),
+                    value: request.value,
+                    input: request.input.clone().into(),
+                    nonce: request.nonce,
+                    chain_id: Some(chain_id),
+                    access_list: request.access_list.clone(),
+                    max_priority_fee_per_gas: Some(U256::from(max_fee_per_gas)),
+                    transaction_type: None,
+                    blob_versioned_hashes: None,
+                    max_fee_per_blob_gas: None,
+                },
+                BlockId::Number(BlockNumberOrTag::Pending),
+                None,
+            )
+            .await?;
+        let gas_limit = estimated_gas;
+
+        let transaction = match request.into_typed_request() {
+            Some(TypedTransactionRequest::Legacy(mut m)) => {
+                m.chain_id = Some(chain_id.to());
+                m.gas_limit = gas_limit;
+                m.gas_price = gas_price;
+
+                TypedTransactionRequest::Legacy(m)
+            }
+            Some(TypedTransactionRequest::EIP2930(mut m)) => {
+                m.chain_id = chain_id.to();
+                m.gas_limit = gas_limit;
+                m.gas_price = gas_price;
+
+                TypedTransactionRequest::EIP2930(m)
+            }
+            Some(TypedTransactionRequest::EIP1559(mut m)) => {
+                m.chain_id = chain_id.to();
+                m.gas_limit = gas_limit;
+                m.max_fee_per_gas = max_fee_per_gas;
+
+                TypedTransactionRequest::EIP1559(m)
+            }
+            Some(TypedTransactionRequest::EIP4844(mut m)) => {
+                m.chain_id = chain_id.to();
+                m.gas_limit = gas_limit;
+                m.max_fee_per_gas = max_fee_per_gas;
+
+                TypedTransactionRequest::EIP4844(m)
+            }
+            None => return Err(EthApiError::ConflictingFeeFieldsInRequest),
+        };
+
+        self.sign_request(&from, transaction)
+    }
+
     /// Spawns the given closure on a new blocking tracing task
     async fn spawn_tracing_task_with<F, T>(&self, f: F) -> EthResult<T>
     where
@@ -1026,7 +1044,7 @@ where
         from: &Address,
         request: TypedTransactionRequest,
     ) -> EthResult<TransactionSigned> {
-        for signer in self.inner.signers.iter() {
+        for signer in self.inner.signers.read().iter() {
             if signer.is_signer_for(from) {
                 return match signer.sign_transaction(request, from) {
                     Ok(tx) => Ok(tx),
@@ -9,20 +9,35 @@ use crate::{
     },
     BlockingTaskGuard,
 };
+use futures::{Stream, StreamExt};
 use jsonrpsee::core::RpcResult;
+use reth_payload_builder::{Bundle, BundlePool, BundleTransaction};
 use reth_primitives::{
-    keccak256,
+    eip191_hash_message, keccak256,
     revm_primitives::db::{DatabaseCommit, DatabaseRef},
-    U256,
+    Address, Bytes, Signature, B256, U256,
 };
+use reth_provider::{BlockNumReader, CanonStateNotification};
 use reth_revm::database::StateProviderDatabase;
-use reth_rpc_api::EthCallBundleApiServer;
-use reth_rpc_types::{EthCallBundle, EthCallBundleResponse, EthCallBundleTransactionResult};
+use reth_rpc_api::{EthBundleApiServer, EthCallBundleApiServer};
+use reth_rpc_types::{
+    CancelBundleRequest, CancelPrivateTransactionRequest, EthBundleHash, EthCallBundle,
+    EthCallBundleResponse, EthCallBundleTransactionResult, EthSendBundle,
+    PrivateTransactionRequest,
+};
+use reth_transaction_pool::{PoolTransaction, TransactionOrigin, TransactionPool};
 use revm::{
     db::CacheDB,
     primitives::{Env, ResultAndState, TxEnv},
 };
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The number of blocks a private transaction is kept for if the request does not set a maximum
+/// block number.
+const DEFAULT_PRIVATE_TX_BLOCKS: u64 = 25;
 
 /// `Eth` bundle implementation.
 pub struct EthBundle<Eth> {
@@ -209,6 +224,352 @@ impl<Eth> Clone for EthBundle<Eth> {
     }
 }
 
+/// The __full__ `Eth` bundle implementation.
+///
+/// Bundles submitted via `eth_sendBundle` are stored in a [BundlePool] keyed by the block they
+/// target, from which the payload builder draws. Private transactions are added to the
+/// transaction pool with [TransactionOrigin::Private], so they are never gossiped to peers, and
+/// are dropped again once their maximum block number has passed, see
+/// [maintain](EthBundleRelay::maintain).
+///
+/// Bundles that are submitted with a signature are bound to the signing key: only a request signed
+/// by the same key can replace or cancel them.
+pub struct EthBundleRelay<Provider, Pool, Eth> {
+    /// All nested fields bundled together.
+    inner: Arc<EthBundleRelayInner<Provider, Pool, Eth>>,
+}
+
+impl<Provider, Pool, Eth> EthBundleRelay<Provider, Pool, Eth> {
+    /// Create a new `EthBundleRelay` instance.
+    pub fn new(
+        provider: Provider,
+        pool: Pool,
+        eth_api: Eth,
+        bundle_pool: BundlePool,
+        blocking_task_guard: BlockingTaskGuard,
+    ) -> Self {
+        let inner = EthBundleRelayInner {
+            provider,
+            pool,
+            call_bundle: EthBundle::new(eth_api, blocking_task_guard),
+            bundle_pool,
+            private_transactions: Default::default(),
+        };
+        Self { inner: Arc::new(inner) }
+    }
+
+    /// Returns the pool the bundles are stored in.
+    pub fn bundle_pool(&self) -> &BundlePool {
+        &self.inner.bundle_pool
+    }
+}
+
+impl<Provider, Pool, Eth> EthBundleRelay<Provider, Pool, Eth>
+where
+    Provider: BlockNumReader + 'static,
+    Pool: TransactionPool + 'static,
+    Eth: EthTransactions + 'static,
+{
+    /// Validates the bundle and stores it in the bundle pool for the block it targets.
+    ///
+    /// Submitting a bundle with the `replacementUuid` of a pending bundle replaces that bundle, if
+    /// both are signed by the same key.
+    pub async fn send_bundle(&self, bundle: EthSendBundle) -> EthResult<EthBundleHash> {
+        let EthSendBundle {
+            txs,
+            block_number,
+            min_timestamp,
+            max_timestamp,
+            reverting_tx_hashes,
+            replacement_uuid,
+            signature,
+        } = bundle;
+        if txs.is_empty() {
+            return Err(EthApiError::InvalidParams(
+                EthBundleError::EmptyBundleTransactions.to_string(),
+            ))
+        }
+        let block_number = block_number.to::<u64>();
+        if block_number == 0 {
+            return Err(EthApiError::InvalidParams(
+                EthBundleError::BundleMissingBlockNumber.to_string(),
+            ))
+        }
+        if let (Some(min), Some(max)) = (min_timestamp, max_timestamp) {
+            if min > max {
+                return Err(EthApiError::InvalidParams(
+                    EthBundleError::InvalidTimestampRange.to_string(),
+                ))
+            }
+        }
+
+        if replacement_uuid.is_some() && signature.is_none() {
+            return Err(EthApiError::InvalidParams(EthBundleError::UnsignedReplacement.to_string()))
+        }
+
+        let next_block = self.inner.provider.best_block_number()? + 1;
+        if block_number < next_block {
+            return Err(EthApiError::InvalidParams(EthBundleError::BundleExpired.to_string()))
+        }
+
+        let mut hash_bytes = Vec::with_capacity(32 * txs.len());
+        let mut transactions = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let transaction = recover_raw_transaction(tx)?.into_ecrecovered_transaction();
+            hash_bytes.extend_from_slice(transaction.hash().as_slice());
+            let can_revert = reverting_tx_hashes.contains(&transaction.hash());
+            transactions.push(BundleTransaction { transaction, can_revert });
+        }
+        let bundle_hash = keccak256(&hash_bytes);
+        let signer = signature
+            .map(|signature| recover_signer(bundle_hash.as_slice(), &signature))
+            .transpose()?;
+
+        self.inner.bundle_pool.remove_expired(next_block);
+        self.inner
+            .bundle_pool
+            .insert(Bundle {
+                hash: bundle_hash,
+                transactions,
+                block_number,
+                max_block_number: block_number,
+                min_timestamp,
+                max_timestamp,
+                replacement_uuid,
+                signer,
+            })
+            .map_err(|err| EthApiError::InvalidParams(err.to_string()))?;
+
+        Ok(EthBundleHash { bundle_hash })
+    }
+
+    /// Removes the bundle from the bundle pool.
+    ///
+    /// The bundle is identified either by the `replacementUuid` it was submitted with or by its
+    /// hash. The request must be signed by the key that signed the bundle, so unsigned bundles
+    /// can't be canceled and are only dropped once they expire.
+    pub fn cancel_bundle(&self, request: CancelBundleRequest) -> EthResult<()> {
+        let CancelBundleRequest { bundle_hash, signature } = request;
+        let signature = signature.ok_or_else(|| {
+            EthApiError::InvalidParams(EthBundleError::UnsignedCancellation.to_string())
+        })?;
+        let signer = recover_signer(bundle_hash.as_bytes(), &signature)?;
+
+        let bundle_pool = &self.inner.bundle_pool;
+        let Some(bundle) = bundle_pool
+            .get_by_uuid(&bundle_hash)
+            .or_else(|| bundle_hash.parse::<B256>().ok().and_then(|hash| bundle_pool.get(&hash)))
+        else {
+            return Ok(())
+        };
+        if bundle.signer != Some(signer) {
+            return Err(EthApiError::InvalidParams(EthBundleError::SignerMismatch.to_string()))
+        }
+        bundle_pool.remove(&bundle.hash);
+        Ok(())
+    }
+
+    /// Removes the bundles and private transactions that can no longer be included whenever the
+    /// canonical chain advances, until the stream of canonical state notifications ends.
+    pub async fn maintain<St>(self, mut events: St)
+    where
+        St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+    {
+        while let Some(event) = events.next().await {
+            let tip = event.tip().number;
+            self.inner.bundle_pool.remove_expired(tip + 1);
+            self.remove_expired_private_transactions(tip);
+        }
+    }
+
+    /// Adds the transaction to the pool without ever propagating it to peers.
+    ///
+    /// The transaction is removed from the pool once `maxBlockNumber` has passed, which defaults
+    /// to 25 blocks after the current block.
+    pub async fn send_private_transaction(
+        &self,
+        request: PrivateTransactionRequest,
+    ) -> EthResult<B256> {
+        let PrivateTransactionRequest { tx, max_block_number, .. } = request;
+        let best_block = self.inner.provider.best_block_number()?;
+        let max_block_number = match max_block_number {
+            Some(max_block_number) => {
+                let max_block_number = max_block_number.to::<u64>();
+                if max_block_number <= best_block {
+                    return Err(EthApiError::InvalidParams(
+                        EthBundleError::BundleExpired.to_string(),
+                    ))
+                }
+                max_block_number
+            }
+            None => best_block + DEFAULT_PRIVATE_TX_BLOCKS,
+        };
+        self.add_private_transaction(tx, best_block, max_block_number).await
+    }
+
+    /// Adds the transaction to the pool without ever propagating it to peers.
+    ///
+    /// The transaction is kept for 25 blocks after the current block.
+    pub async fn send_private_raw_transaction(&self, bytes: Bytes) -> EthResult<B256> {
+        let best_block = self.inner.provider.best_block_number()?;
+        self.add_private_transaction(bytes, best_block, best_block + DEFAULT_PRIVATE_TX_BLOCKS)
+            .await
+    }
+
+    /// Removes a private transaction that was submitted via this API from the pool.
+    ///
+    /// Returns `false` if the transaction is not a pending private transaction.
+    ///
+    /// Note: requests are not authenticated, so any caller that knows the hash of a private
+    /// transaction can cancel it.
+    pub fn cancel_private_transaction(
+        &self,
+        request: CancelPrivateTransactionRequest,
+    ) -> EthResult<bool> {
+        let CancelPrivateTransactionRequest { tx_hash } = request;
+        let tracked =
+            self.inner.private_transactions.lock().expect("not poisoned").remove(&tx_hash);
+        if tracked.is_none() {
+            return Ok(false)
+        }
+        let is_private = self
+            .inner
+            .pool
+            .get(&tx_hash)
+            .map_or(false, |tx| tx.origin == TransactionOrigin::Private);
+        if !is_private {
+            return Ok(false)
+        }
+        Ok(!self.inner.pool.remove_transactions(vec![tx_hash]).is_empty())
+    }
+
+    async fn add_private_transaction(
+        &self,
+        tx: Bytes,
+        best_block: u64,
+        max_block_number: u64,
+    ) -> EthResult<B256> {
+        self.remove_expired_private_transactions(best_block);
+
+        let recovered = recover_raw_transaction(tx)?;
+        let pool_transaction = <Pool::Transaction>::from_recovered_pooled_transaction(recovered);
+        let hash =
+            self.inner.pool.add_transaction(TransactionOrigin::Private, pool_transaction).await?;
+
+        self.inner
+            .private_transactions
+            .lock()
+            .expect("not poisoned")
+            .insert(hash, max_block_number);
+        Ok(hash)
+    }
+
+    /// Removes all private transactions that can no longer be included after the given block
+    /// from the pool.
+    fn remove_expired_private_transactions(&self, best_block: u64) {
+        let mut private_transactions =
+            self.inner.private_transactions.lock().expect("not poisoned");
+        let mut expired = Vec::new();
+        private_transactions.retain(|hash, max_block_number| {
+            let keep = *max_block_number > best_block;
+            if !keep {
+                expired.push(*hash);
+            }
+            keep
+        });
+        drop(private_transactions);
+
+        if !expired.is_empty() {
+            self.inner.pool.remove_transactions(expired);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<Provider, Pool, Eth> EthBundleApiServer for EthBundleRelay<Provider, Pool, Eth>
+where
+    Provider: BlockNumReader + 'static,
+    Pool: TransactionPool + 'static,
+    Eth: EthTransactions + 'static,
+{
+    async fn send_bundle(&self, bundle: EthSendBundle) -> RpcResult<EthBundleHash> {
+        Ok(EthBundleRelay::send_bundle(self, bundle).await?)
+    }
+
+    async fn call_bundle(&self, request: EthCallBundle) -> RpcResult<EthCallBundleResponse> {
+        Ok(self.inner.call_bundle.call_bundle(request).await?)
+    }
+
+    async fn cancel_bundle(&self, request: CancelBundleRequest) -> RpcResult<()> {
+        Ok(EthBundleRelay::cancel_bundle(self, request)?)
+    }
+
+    async fn send_private_transaction(
+        &self,
+        request: PrivateTransactionRequest,
+    ) -> RpcResult<B256> {
+        Ok(EthBundleRelay::send_private_transaction(self, request).await?)
+    }
+
+    async fn send_private_raw_transaction(&self, bytes: Bytes) -> RpcResult<B256> {
+        Ok(EthBundleRelay::send_private_raw_transaction(self, bytes).await?)
+    }
+
+    async fn cancel_private_transaction(
+        &self,
+        request: CancelPrivateTransactionRequest,
+    ) -> RpcResult<bool> {
+        Ok(EthBundleRelay::cancel_private_transaction(self, request)?)
+    }
+}
+
+/// Container type for `EthBundleRelay` internals
+#[derive(Debug)]
+struct EthBundleRelayInner<Provider, Pool, Eth> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// The transaction pool private transactions are added to.
+    pool: Pool,
+    /// Handles `eth_callBundle`.
+    call_bundle: EthBundle<Eth>,
+    /// The pool submitted bundles are stored in.
+    bundle_pool: BundlePool,
+    /// The pending private transactions, mapped to the last block they can be included in.
+    private_transactions: Mutex<HashMap<B256, u64>>,
+}
+
+impl<Provider, Pool, Eth> std::fmt::Debug for EthBundleRelay<Provider, Pool, Eth> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EthBundleRelay").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Pool, Eth> Clone for EthBundleRelay<Provider, Pool, Eth> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+/// Recovers the address that signed the [EIP-191](https://eips.ethereum.org/EIPS/eip-191) message
+/// from the 65 bytes `r || s || v` signature.
+fn recover_signer(message: &[u8], signature: &Bytes) -> EthResult<Address> {
+    let invalid = || EthApiError::InvalidParams(EthBundleError::InvalidSignature.to_string());
+    if signature.len() != 65 {
+        return Err(invalid())
+    }
+    let odd_y_parity = match signature[64] {
+        0 | 27 => false,
+        1 | 28 => true,
+        _ => return Err(invalid()),
+    };
+    let signature = Signature {
+        r: U256::from_be_slice(&signature[..32]),
+        s: U256::from_be_slice(&signature[32..64]),
+        odd_y_parity,
+    };
+    signature.recover_signer(eip191_hash_message(message)).ok_or_else(invalid)
+}
+
 /// [EthBundle] specific errors.
 #[derive(Debug, thiserror::Error)]
 pub enum EthBundleError {
@@ -218,4 +579,43 @@ pub enum EthBundleError {
     /// Thrown if the bundle does not contain a block number, or block number is 0.
     #[error("bundle missing blockNumber")]
     BundleMissingBlockNumber,
+    /// Thrown if the bundle targets a block that is already part of the chain.
+    #[error("bundle targets a past block")]
+    BundleExpired,
+    /// Thrown if the bundle's minimum timestamp is greater than its maximum timestamp.
+    #[error("bundle minTimestamp is greater than maxTimestamp")]
+    InvalidTimestampRange,
+    /// Thrown if a bundle with a replacement uuid is not signed.
+    #[error("bundle with replacementUuid must be signed")]
+    UnsignedReplacement,
+    /// Thrown if a request to cancel a bundle is not signed.
+    #[error("cancelBundle request must be signed")]
+    UnsignedCancellation,
+    /// Thrown if the signature of a request can't be recovered.
+    #[error("invalid signature")]
+    InvalidSignature,
+    /// Thrown if a bundle is canceled by a key other than the one that signed it.
+    #[error("bundle was signed by a different key")]
+    SignerMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{public_key_to_address, sign_message};
+    use secp256k1::{SecretKey, SECP256K1};
+
+    #[test]
+    fn recovers_request_signer() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let address = public_key_to_address(secret.public_key(SECP256K1));
+        let message = b"bundle-uuid";
+        let signature =
+            sign_message(B256::from_slice(secret.as_ref()), eip191_hash_message(message)).unwrap();
+
+        let bytes = Bytes::from(signature.to_bytes().to_vec());
+        assert_eq!(recover_signer(message, &bytes).unwrap(), address);
+        assert_ne!(recover_signer(b"other", &bytes).ok(), Some(address));
+        assert!(recover_signer(message, &Bytes::from(vec![0u8; 64])).is_err());
+    }
 }
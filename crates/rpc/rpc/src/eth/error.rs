@@ -43,6 +43,9 @@ pub enum EthApiError {
     UnknownBlockOrTxIndex,
     #[error("invalid block range")]
     InvalidBlockRange,
+    /// Thrown when the target block of a proof request is older than the configured proof window.
+    #[error("distance to target block exceeds maximum proof window")]
+    ExceedsMaxProofWindow,
     /// An internal error where prevrandao is not set in the evm's environment
     #[error("prevrandao not in the EVM's environment after merge")]
     PrevrandaoNotSet,
@@ -134,6 +137,7 @@ impl From<EthApiError> for ErrorObject<'static> {
             EthApiError::InvalidTransactionSignature |
             EthApiError::EmptyRawTransactionData |
             EthApiError::InvalidBlockRange |
+            EthApiError::ExceedsMaxProofWindow |
             EthApiError::ConflictingFeeFieldsInRequest |
             EthApiError::Signing(_) |
             EthApiError::BothStateAndStateDiffInOverride(_) |
@@ -664,6 +668,9 @@ pub enum SignError {
     /// Signer for requested account not found.
     #[error("unknown account")]
     NoAccount,
+    /// The account needs to be unlocked before it can sign.
+    #[error("authentication needed: password or unlock")]
+    AccountLocked,
     /// TypedData has invalid format.
     #[error("given typed data is not valid")]
     InvalidTypedData,
@@ -12,7 +12,7 @@ use core::fmt;
 use async_trait::async_trait;
 use jsonrpsee::{core::RpcResult, server::IdProvider};
 use reth_primitives::{IntoRecoveredTransaction, TxHash};
-use reth_provider::{BlockIdReader, BlockReader, EvmEnvProvider, ProviderError};
+use reth_provider::{BlockIdReader, BlockReader, EvmEnvProvider, LogIndexReader, ProviderError};
 use reth_rpc_api::EthFilterApiServer;
 use reth_rpc_types::{
     BlockNumHash, Filter, FilterBlockOption, FilterChanges, FilterId, FilteredParams, Log,
@@ -21,7 +21,7 @@ use reth_rpc_types::{
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::{NewSubpoolTransactionStream, PoolTransaction, TransactionPool};
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     iter::StepBy,
     ops::RangeInclusive,
     sync::Arc,
@@ -124,7 +124,7 @@ where
 
 impl<Provider, Pool> EthFilter<Provider, Pool>
 where
-    Provider: BlockReader + BlockIdReader + EvmEnvProvider + 'static,
+    Provider: BlockReader + BlockIdReader + EvmEnvProvider + LogIndexReader + 'static,
     Pool: TransactionPool + 'static,
     <Pool as TransactionPool>::Transaction: 'static,
 {
@@ -223,7 +223,7 @@ where
 #[async_trait]
 impl<Provider, Pool> EthFilterApiServer for EthFilter<Provider, Pool>
 where
-    Provider: BlockReader + BlockIdReader + EvmEnvProvider + 'static,
+    Provider: BlockReader + BlockIdReader + EvmEnvProvider + LogIndexReader + 'static,
     Pool: TransactionPool + 'static,
 {
     /// Handler for `eth_newFilter`
@@ -342,7 +342,7 @@ struct EthFilterInner<Provider, Pool> {
 
 impl<Provider, Pool> EthFilterInner<Provider, Pool>
 where
-    Provider: BlockReader + BlockIdReader + EvmEnvProvider + 'static,
+    Provider: BlockReader + BlockIdReader + EvmEnvProvider + LogIndexReader + 'static,
     Pool: TransactionPool + 'static,
 {
     /// Returns logs matching given filter object.
@@ -416,6 +416,24 @@ where
     ) -> Result<Vec<Log>, FilterError> {
         trace!(target: "rpc::eth::filter", from=from_block, to=to_block, ?filter, "finding logs in range");
 
+        // if the log index is maintained, jump straight to the blocks that may contain matching
+        // logs instead of checking the bloom of every header in the range
+        let addresses = filter.address.iter().copied().collect::<Vec<_>>();
+        let topics = filter
+            .topics
+            .iter()
+            .map(|topics| topics.iter().copied().collect())
+            .collect::<Vec<Vec<_>>>();
+        if let Some(blocks) =
+            self.provider.log_candidates(&addresses, &topics, from_block..=to_block)?
+        {
+            // only the candidate blocks are scanned, so they are what the limit applies to
+            if blocks.len() as u64 > self.max_blocks_per_filter.saturating_add(1) {
+                return Err(FilterError::QueryExceedsMaxBlocks(self.max_blocks_per_filter))
+            }
+            return self.get_logs_in_blocks(filter, blocks, from_block != to_block).await
+        }
+
         if to_block - from_block > self.max_blocks_per_filter {
             return Err(FilterError::QueryExceedsMaxBlocks(self.max_blocks_per_filter))
         }
@@ -474,6 +492,45 @@ where
 
         Ok(all_logs)
     }
+
+    /// Returns all logs in the given blocks that match the filter
+    ///
+    /// Returns an error if:
+    ///  - underlying database error
+    ///  - amount of matches exceeds configured limit and `is_multi_block_range` is set
+    async fn get_logs_in_blocks(
+        &self,
+        filter: &Filter,
+        blocks: BTreeSet<u64>,
+        is_multi_block_range: bool,
+    ) -> Result<Vec<Log>, FilterError> {
+        let mut all_logs = Vec::new();
+        let filter_params = FilteredParams::new(Some(filter.clone()));
+
+        for block_number in blocks {
+            let block_hash = self
+                .provider
+                .block_hash(block_number)?
+                .ok_or(ProviderError::BlockNotFound(block_number.into()))?;
+
+            if let Some(receipts) = self.eth_cache.get_receipts(block_hash).await? {
+                append_matching_block_logs(
+                    &mut all_logs,
+                    &self.provider,
+                    &filter_params,
+                    BlockNumHash::new(block_number, block_hash),
+                    &receipts,
+                    false,
+                )?;
+
+                if is_multi_block_range && all_logs.len() > self.max_logs_per_response {
+                    return Err(FilterError::QueryExceedsMaxResults(self.max_logs_per_response))
+                }
+            }
+        }
+
+        Ok(all_logs)
+    }
 }
 
 /// Config for the filter
@@ -0,0 +1,360 @@
+//! A signer backed by an encrypted keystore directory.
+
+use crate::eth::{error::SignError, signer::EthSigner};
+use alloy_dyn_abi::TypedData;
+use parking_lot::RwLock;
+use reth_primitives::{
+    eip191_hash_message, public_key_to_address, sign_message, Address, Signature,
+    TransactionSigned, B256,
+};
+use reth_rpc_types::TypedTransactionRequest;
+use reth_rpc_types_compat::transaction::to_primitive_transaction;
+use secp256k1::{SecretKey, SECP256K1};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+type Result<T> = std::result::Result<T, SignError>;
+
+/// Errors that can occur when loading or unlocking a keystore.
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    /// Failed to read the keystore directory or the password file.
+    #[error("failed to read {path}: {err}")]
+    Io {
+        /// The path that could not be read.
+        path: PathBuf,
+        /// The underlying error.
+        err: std::io::Error,
+    },
+    /// The key file of the account is not a valid keystore file.
+    #[error("invalid key file {0}")]
+    InvalidKeyFile(PathBuf),
+    /// The account is not part of the keystore.
+    #[error("unknown account {0}")]
+    UnknownAccount(Address),
+    /// The key could not be decrypted with the given password.
+    #[error("could not decrypt key with given password")]
+    InvalidPassword,
+    /// The account would stay unlocked beyond the representable time.
+    #[error("unlock duration {0:?} is too large")]
+    InvalidUnlockDuration(Duration),
+    /// Failed to sign with the decrypted key.
+    #[error(transparent)]
+    Signing(#[from] SignError),
+}
+
+/// The parts of a Web3 Secret Storage file that are read before the key is decrypted.
+#[derive(Deserialize)]
+struct KeyFileHeader {
+    /// The address of the key, hex encoded without the `0x` prefix.
+    address: Address,
+}
+
+/// An unlocked key.
+#[derive(Clone)]
+struct UnlockedKey {
+    secret: SecretKey,
+    /// When the key is locked again, `None` if it stays unlocked.
+    expires_at: Option<Instant>,
+}
+
+impl UnlockedKey {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Default)]
+struct KeystoreInner {
+    /// The key files of all accounts in the keystore.
+    key_files: HashMap<Address, PathBuf>,
+    /// The keys that are currently unlocked.
+    unlocked: RwLock<HashMap<Address, UnlockedKey>>,
+}
+
+/// A signer for the accounts of an encrypted keystore directory.
+///
+/// The keystore directory contains one Web3 Secret Storage JSON file per account, as written by
+/// geth or clef. The keys stay encrypted on disk and are only held in memory while an account is
+/// unlocked, either for the lifetime of the node via a password file, or temporarily via
+/// `personal_unlockAccount`.
+///
+/// Signing with a locked account fails with [SignError::AccountLocked].
+#[derive(Clone, Default)]
+pub struct KeystoreSigner {
+    inner: Arc<KeystoreInner>,
+}
+
+impl KeystoreSigner {
+    /// Loads all key files of the given keystore directory.
+    ///
+    /// Files that are not keystore files are skipped. All accounts are locked.
+    pub fn load(dir: impl AsRef<Path>) -> std::result::Result<Self, KeystoreError> {
+        let dir = dir.as_ref();
+        let io_err = |err| KeystoreError::Io { path: dir.to_path_buf(), err };
+
+        let mut key_files = HashMap::new();
+        for entry in std::fs::read_dir(dir).map_err(io_err)? {
+            let path = entry.map_err(io_err)?.path();
+            if !path.is_file() {
+                continue
+            }
+            let Ok(content) = std::fs::read(&path) else { continue };
+            if let Ok(header) = serde_json::from_slice::<KeyFileHeader>(&content) {
+                key_files.insert(header.address, path);
+            }
+        }
+
+        Ok(Self { inner: Arc::new(KeystoreInner { key_files, unlocked: Default::default() }) })
+    }
+
+    /// Unlocks all accounts of the keystore until the node shuts down, using the first line of the
+    /// given password file as password.
+    pub fn unlock_all_with_password_file(
+        &self,
+        password_file: impl AsRef<Path>,
+    ) -> std::result::Result<(), KeystoreError> {
+        let path = password_file.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| KeystoreError::Io { path: path.to_path_buf(), err })?;
+        let password = content.lines().next().unwrap_or_default();
+        for address in self.accounts() {
+            self.unlock(address, password, None)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the addresses of all accounts in the keystore.
+    pub fn accounts(&self) -> Vec<Address> {
+        let mut accounts = self.inner.key_files.keys().copied().collect::<Vec<_>>();
+        accounts.sort_unstable();
+        accounts
+    }
+
+    /// Returns `true` if the account is part of the keystore.
+    pub fn contains(&self, address: &Address) -> bool {
+        self.inner.key_files.contains_key(address)
+    }
+
+    /// Unlocks the account with the given password.
+    ///
+    /// The account is locked again after the given duration, or stays unlocked if `None`.
+    ///
+    /// Note: decrypting the key is intentionally expensive and should not be done on an async
+    /// task.
+    pub fn unlock(
+        &self,
+        address: Address,
+        password: &str,
+        duration: Option<Duration>,
+    ) -> std::result::Result<(), KeystoreError> {
+        let expires_at = duration
+            .map(|duration| {
+                Instant::now()
+                    .checked_add(duration)
+                    .ok_or(KeystoreError::InvalidUnlockDuration(duration))
+            })
+            .transpose()?;
+        let secret = self.decrypt(address, password)?;
+        self.inner.unlocked.write().insert(address, UnlockedKey { secret, expires_at });
+        Ok(())
+    }
+
+    /// Locks the account, returns `false` if the account is not part of the keystore.
+    pub fn lock(&self, address: &Address) -> bool {
+        self.inner.unlocked.write().remove(address);
+        self.contains(address)
+    }
+
+    /// Returns `true` if the account is currently unlocked.
+    pub fn is_unlocked(&self, address: &Address) -> bool {
+        self.unlocked_key(address).is_ok()
+    }
+
+    /// Signs the message hashed according to EIP-191 with the key decrypted with the given
+    /// password, without unlocking the account.
+    pub fn sign_with_password(
+        &self,
+        address: Address,
+        password: &str,
+        message: &[u8],
+    ) -> std::result::Result<Signature, KeystoreError> {
+        let secret = self.decrypt(address, password)?;
+        let signature =
+            sign_message(B256::from_slice(secret.as_ref()), eip191_hash_message(message))
+                .map_err(|_| SignError::CouldNotSign)?;
+        Ok(signature)
+    }
+
+    /// Decrypts the key of the account.
+    fn decrypt(
+        &self,
+        address: Address,
+        password: &str,
+    ) -> std::result::Result<SecretKey, KeystoreError> {
+        let path =
+            self.inner.key_files.get(&address).ok_or(KeystoreError::UnknownAccount(address))?;
+        let key = eth_keystore::decrypt_key(path, password).map_err(|err| match err {
+            eth_keystore::KeystoreError::MacMismatch => KeystoreError::InvalidPassword,
+            _ => KeystoreError::InvalidKeyFile(path.clone()),
+        })?;
+        let secret =
+            SecretKey::from_slice(&key).map_err(|_| KeystoreError::InvalidKeyFile(path.clone()))?;
+
+        // guard against key files with a mismatching address
+        if public_key_to_address(secret.public_key(SECP256K1)) != address {
+            return Err(KeystoreError::InvalidKeyFile(path.clone()))
+        }
+        Ok(secret)
+    }
+
+    /// Returns the key of the account if it is unlocked, locking it if it expired.
+    fn unlocked_key(&self, address: &Address) -> Result<SecretKey> {
+        if !self.contains(address) {
+            return Err(SignError::NoAccount)
+        }
+        let mut unlocked = self.inner.unlocked.write();
+        match unlocked.get(address) {
+            Some(key) if !key.is_expired(Instant::now()) => Ok(key.secret),
+            Some(_) => {
+                unlocked.remove(address);
+                Err(SignError::AccountLocked)
+            }
+            None => Err(SignError::AccountLocked),
+        }
+    }
+
+    fn sign_hash(&self, hash: B256, account: Address) -> Result<Signature> {
+        let secret = self.unlocked_key(&account)?;
+        sign_message(B256::from_slice(secret.as_ref()), hash).map_err(|_| SignError::CouldNotSign)
+    }
+}
+
+#[async_trait::async_trait]
+impl EthSigner for KeystoreSigner {
+    fn accounts(&self) -> Vec<Address> {
+        KeystoreSigner::accounts(self)
+    }
+
+    fn is_signer_for(&self, addr: &Address) -> bool {
+        self.contains(addr)
+    }
+
+    async fn sign(&self, address: Address, message: &[u8]) -> Result<Signature> {
+        self.sign_hash(eip191_hash_message(message), address)
+    }
+
+    fn sign_transaction(
+        &self,
+        request: TypedTransactionRequest,
+        address: &Address,
+    ) -> Result<TransactionSigned> {
+        let transaction =
+            to_primitive_transaction(request).ok_or(SignError::InvalidTransactionRequest)?;
+        let signature = self.sign_hash(transaction.signature_hash(), *address)?;
+        Ok(TransactionSigned::from_transaction_and_signature(transaction, signature))
+    }
+
+    fn sign_typed_data(&self, address: Address, payload: &TypedData) -> Result<Signature> {
+        let hash = payload.eip712_signing_hash().map_err(|_| SignError::InvalidTypedData)?;
+        self.sign_hash(hash, address)
+    }
+}
+
+impl std::fmt::Debug for KeystoreSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeystoreSigner").field("accounts", &self.accounts()).finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use alloy_primitives::hex;
+    use rand::thread_rng;
+
+    /// Writes a new key file to the directory and returns its address.
+    ///
+    /// `eth_keystore` omits the address field geth writes, so it is added afterwards.
+    pub(crate) fn new_key_file(dir: &Path, password: &str) -> Address {
+        let (key, name) = eth_keystore::new(dir, &mut thread_rng(), password, None).unwrap();
+        let secret = SecretKey::from_slice(&key).unwrap();
+        let address = public_key_to_address(secret.public_key(SECP256K1));
+
+        let path = dir.join(name);
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        json["address"] = hex::encode(address).into();
+        std::fs::write(path, json.to_string()).unwrap();
+        address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_utils::new_key_file, *};
+
+    #[tokio::test]
+    async fn unlock_and_sign() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = new_key_file(dir.path(), "password");
+        std::fs::write(dir.path().join("README"), "not a key file").unwrap();
+
+        let keystore = KeystoreSigner::load(dir.path()).unwrap();
+        assert_eq!(keystore.accounts(), vec![address]);
+        assert!(matches!(
+            keystore.sign(address, b"hello").await.unwrap_err(),
+            SignError::AccountLocked
+        ));
+        assert!(matches!(
+            keystore.unlock(address, "wrong", None).unwrap_err(),
+            KeystoreError::InvalidPassword
+        ));
+
+        keystore.unlock(address, "password", None).unwrap();
+        let signature = keystore.sign(address, b"hello").await.unwrap();
+        let hash = eip191_hash_message(b"hello");
+        assert_eq!(signature.recover_signer(hash), Some(address));
+        assert_eq!(keystore.sign_with_password(address, "password", b"hello").unwrap(), signature);
+
+        assert!(keystore.lock(&address));
+        assert!(!keystore.is_unlocked(&address));
+        assert!(matches!(
+            keystore.sign(Address::ZERO, b"hello").await.unwrap_err(),
+            SignError::NoAccount
+        ));
+    }
+
+    #[test]
+    fn unlock_expires() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = new_key_file(dir.path(), "password");
+        let password_file = dir.path().join("password.txt");
+        std::fs::write(&password_file, "password\n").unwrap();
+
+        let keystore = KeystoreSigner::load(dir.path()).unwrap();
+        keystore.unlock(address, "password", Some(Duration::ZERO)).unwrap();
+        assert!(!keystore.is_unlocked(&address));
+
+        keystore.unlock_all_with_password_file(&password_file).unwrap();
+        assert!(keystore.is_unlocked(&address));
+    }
+
+    #[test]
+    fn unlock_duration_overflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = new_key_file(dir.path(), "password");
+
+        let keystore = KeystoreSigner::load(dir.path()).unwrap();
+        assert!(matches!(
+            keystore.unlock(address, "password", Some(Duration::MAX)).unwrap_err(),
+            KeystoreError::InvalidUnlockDuration(Duration::MAX)
+        ));
+        assert!(!keystore.is_unlocked(&address));
+    }
+}
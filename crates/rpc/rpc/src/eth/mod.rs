@@ -7,6 +7,7 @@ pub mod error;
 mod filter;
 pub mod gas_oracle;
 mod id_provider;
+pub mod keystore;
 mod logs_utils;
 mod pubsub;
 pub mod revm_utils;
@@ -15,10 +16,12 @@ pub(crate) mod utils;
 
 pub use api::{
     fee_history::{fee_history_cache_new_blocks_task, FeeHistoryCache, FeeHistoryCacheConfig},
-    EthApi, EthApiSpec, EthTransactions, TransactionSource, RPC_DEFAULT_GAS_CAP,
+    EthApi, EthApiSpec, EthTransactions, TransactionSource, DEFAULT_ETH_PROOF_WINDOW,
+    MAX_ETH_PROOF_WINDOW, RPC_DEFAULT_GAS_CAP,
 };
 
-pub use bundle::EthBundle;
+pub use bundle::{EthBundle, EthBundleRelay};
 pub use filter::{EthFilter, EthFilterConfig};
 pub use id_provider::EthSubscriptionIdProvider;
+pub use keystore::{KeystoreError, KeystoreSigner};
 pub use pubsub::EthPubSub;
@@ -0,0 +1,927 @@
+use crate::BlockingTaskGuard;
+use futures::future::BoxFuture;
+use http::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER},
+    HeaderMap, HeaderName, Method, Request, Response, StatusCode,
+};
+use http_body::Limited;
+use hyper::Body;
+use jsonrpsee::{
+    core::server::helpers::MethodResponse,
+    server::logger::{self, HttpRequest, Logger, MethodKind, TransportProtocol},
+    types::{ErrorObject, Id, Params},
+    MethodCallback, MethodSink, Methods, SubscriptionState,
+};
+use parking_lot::Mutex;
+use reth_metrics::{metrics::Counter, Metrics};
+use serde::Deserialize;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// The JSON-RPC error code of requests that are rejected by the [RateLimitLayer].
+///
+/// See also <https://eips.ethereum.org/EIPS/eip-1474#error-codes>
+pub const RATE_LIMIT_ERROR_CODE: i32 = -32005;
+
+/// The default weights of expensive methods.
+///
+/// Patterns ending with `*` match all methods with the given prefix.
+pub const DEFAULT_METHOD_WEIGHTS: &[(&str, u32)] = &[
+    ("debug_*", 20),
+    ("trace_*", 20),
+    ("ots_*", 10),
+    ("eth_getLogs", 20),
+    ("eth_call", 5),
+    ("eth_estimateGas", 5),
+    ("eth_createAccessList", 5),
+    ("eth_callBundle", 20),
+    ("eth_simulateV1", 20),
+    ("eth_getProof", 10),
+];
+
+/// The default methods that are subject to the concurrency limit of the [BlockingTaskGuard].
+pub const DEFAULT_GUARDED_METHODS: &[&str] = &["debug_trace*", "trace_*"];
+
+/// The maximum number of clients that are tracked before idle clients are evicted.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The label of calls to methods that are not served.
+const UNKNOWN_METHOD: &str = "unknown";
+
+/// The JSON-RPC error code of requests that exceed the maximum request body size.
+const OVERSIZED_REQUEST_CODE: i32 = -32007;
+
+tokio::task_local! {
+    /// Set while the middleware serves an http request.
+    ///
+    /// Calls are executed within the request, so calls made in its scope were already checked by
+    /// the middleware.
+    static HTTP_REQUEST: ();
+}
+
+thread_local! {
+    /// The address of the peer the server is about to serve, reported by the [RateLimitLogger].
+    ///
+    /// The server calls the logger right before it serves a request or calls a method over a
+    /// websocket connection, on the same thread.
+    static PEER_ADDR: Cell<Option<IpAddr>> = Cell::new(None);
+}
+
+/// Settings of the [RateLimitLayer].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// The number of tokens a client can spend at once.
+    pub burst: u32,
+    /// The number of tokens a client is credited per second.
+    pub per_second: u32,
+    /// The cost of methods without a configured weight.
+    pub default_weight: u32,
+    /// The cost per method, keyed by method name or by a prefix pattern like `debug_*`.
+    pub method_weights: HashMap<String, u32>,
+    /// Methods that are rejected early if all permits of the tracing guard are taken, see
+    /// [RateLimitLayer::with_tracing_guard].
+    pub guarded_methods: Vec<String>,
+    /// The header that identifies clients by API key.
+    pub api_key_header: Option<HeaderName>,
+    /// Whether clients without an API key are identified by the `X-Forwarded-For` or `X-Real-IP`
+    /// header instead of the IP address of their connection.
+    ///
+    /// This should only be enabled behind a reverse proxy that sets these headers, otherwise
+    /// clients can pick their own identity.
+    pub trust_forwarded_headers: bool,
+    /// The maximum size of a request body in bytes.
+    pub max_request_body_size: u32,
+}
+
+impl RateLimitConfig {
+    /// Returns the cost of a call to the given method.
+    pub fn weight(&self, method: &str) -> u32 {
+        match_method(&self.method_weights, method).copied().unwrap_or(self.default_weight)
+    }
+
+    /// Returns `true` if the method is subject to the concurrency limit.
+    pub fn is_guarded(&self, method: &str) -> bool {
+        self.guarded_methods.iter().any(|pattern| pattern_matches(pattern, method))
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            burst: 1_000,
+            per_second: 100,
+            default_weight: 1,
+            method_weights: DEFAULT_METHOD_WEIGHTS
+                .iter()
+                .map(|(method, weight)| (method.to_string(), *weight))
+                .collect(),
+            guarded_methods: DEFAULT_GUARDED_METHODS.iter().map(|m| m.to_string()).collect(),
+            api_key_header: None,
+            trust_forwarded_headers: false,
+            max_request_body_size: 15 * 1024 * 1024,
+        }
+    }
+}
+
+/// Returns `true` if the method matches the pattern, which is either a method name or a prefix
+/// followed by `*`.
+fn pattern_matches(pattern: &str, method: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    }
+}
+
+/// Returns the value of the exact method, or of the longest matching prefix pattern.
+fn match_method<'a, T>(patterns: &'a HashMap<String, T>, method: &str) -> Option<&'a T> {
+    patterns.get(method).or_else(|| {
+        patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.ends_with('*') && pattern_matches(pattern, method))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, value)| value)
+    })
+}
+
+/// Identifies a client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    /// A client identified by API key.
+    ApiKey(String),
+    /// A client identified by its IP address.
+    Ip(IpAddr),
+    /// A client whose IP address is unknown, identified by its http connection.
+    Connection(u64),
+    /// A client whose IP address is unknown, identified by its websocket connection.
+    WsConnection(usize),
+}
+
+impl ClientKey {
+    /// Returns the API key or, if trusted, the forwarded IP address of the client.
+    ///
+    /// Returns `None` if the client is identified by the address of its connection.
+    fn from_headers(config: &RateLimitConfig, headers: &HeaderMap) -> Option<Self> {
+        if let Some(key) = config
+            .api_key_header
+            .as_ref()
+            .and_then(|header| headers.get(header))
+            .and_then(|value| value.to_str().ok())
+        {
+            return Some(ClientKey::ApiKey(key.to_string()))
+        }
+
+        if config.trust_forwarded_headers {
+            let forwarded = headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .or_else(|| headers.get("x-real-ip").and_then(|value| value.to_str().ok()));
+            if let Some(ip) = forwarded.and_then(|ip| ip.trim().parse().ok()) {
+                return Some(ClientKey::Ip(ip))
+            }
+        }
+
+        None
+    }
+}
+
+/// A token bucket that is refilled continuously.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(burst: u32, now: Instant) -> Self {
+        Self { tokens: burst as f64, updated_at: now }
+    }
+
+    /// Credits the tokens accumulated since the last update.
+    fn refill(&mut self, burst: u32, per_second: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second as f64).min(burst as f64);
+        self.updated_at = now;
+    }
+
+    /// Takes `cost` tokens from the bucket or returns how long to wait until enough tokens are
+    /// available.
+    ///
+    /// Requests that cost more than the burst size are admitted once the bucket is full, leaving
+    /// the bucket in debt.
+    fn try_take(
+        &mut self,
+        cost: u32,
+        burst: u32,
+        per_second: u32,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        self.refill(burst, per_second, now);
+        let required = cost.min(burst) as f64;
+        if self.tokens >= required {
+            self.tokens -= cost as f64;
+            return Ok(())
+        }
+        let missing = required - self.tokens;
+        Err(Duration::from_secs_f64(missing / per_second.max(1) as f64))
+    }
+}
+
+/// Rate limit metrics of a method.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc_server.rate_limit")]
+struct RateLimitMethodMetrics {
+    /// The number of admitted calls
+    admitted: Counter,
+    /// The number of calls rejected because the client exceeded its rate limit
+    rate_limited: Counter,
+    /// The number of calls rejected because all tracing permits were taken
+    concurrency_limited: Counter,
+}
+
+/// The state shared by all connections of a [RateLimitLayer].
+#[derive(Debug)]
+struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<ClientKey, TokenBucket>>,
+    metrics: HashMap<&'static str, RateLimitMethodMetrics>,
+}
+
+impl RateLimiter {
+    fn metrics(&self, method: &str) -> &RateLimitMethodMetrics {
+        self.metrics.get(method).unwrap_or_else(|| &self.metrics[UNKNOWN_METHOD])
+    }
+
+    /// Checks the calls of a request, returns the time after which the client may retry if the
+    /// request is rejected.
+    fn check(
+        &self,
+        client: ClientKey,
+        methods: &[String],
+        tracing_guard: Option<&BlockingTaskGuard>,
+    ) -> Result<(), Duration> {
+        if let Some(guard) = tracing_guard {
+            if guard.available_permits() == 0 &&
+                methods.iter().any(|method| self.config.is_guarded(method))
+            {
+                for method in methods {
+                    self.metrics(method).concurrency_limited.increment(1);
+                }
+                return Err(Duration::from_secs(1))
+            }
+        }
+
+        let cost = if methods.is_empty() {
+            self.config.default_weight
+        } else {
+            methods.iter().map(|method| self.config.weight(method)).fold(0u32, u32::saturating_add)
+        };
+
+        let RateLimitConfig { burst, per_second, .. } = self.config;
+        let now = Instant::now();
+        let res = {
+            let mut buckets = self.buckets.lock();
+            if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&client) {
+                // evict clients whose buckets are full again, they are indistinguishable from new
+                // clients
+                buckets.retain(|_, bucket| {
+                    bucket.refill(burst, per_second, now);
+                    bucket.tokens < burst as f64
+                });
+            }
+            buckets
+                .entry(client)
+                .or_insert_with(|| TokenBucket::full(burst, now))
+                .try_take(cost, burst, per_second, now)
+        };
+
+        for method in methods {
+            let metrics = self.metrics(method);
+            if res.is_ok() {
+                metrics.admitted.increment(1);
+            } else {
+                metrics.rate_limited.increment(1);
+            }
+        }
+        res
+    }
+}
+
+/// A middleware layer that limits the rate of requests per client.
+///
+/// Every client has a token bucket that holds up to [RateLimitConfig::burst] tokens and is
+/// refilled with [RateLimitConfig::per_second] tokens per second. Each call costs the weight of its
+/// method, batches cost the sum of their calls. Requests of clients that ran out of tokens are
+/// rejected with a JSON-RPC error with code [RATE_LIMIT_ERROR_CODE] and a `Retry-After` header.
+///
+/// Clients are identified by the configured API key header or, if trusted, by the IP address set by
+/// a reverse proxy. All remaining clients are identified by the IP address of their connection,
+/// which the server reports to the [RateLimitLogger]. Servers without the logger identify these
+/// clients by their connection instead.
+///
+/// Note: only the http upgrade request of websocket connections passes through the middleware,
+/// calls over websocket connections are limited by the methods wrapped with
+/// [RateLimitLayer::limit_ws_calls].
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+    tracing_guard: Option<BlockingTaskGuard>,
+    /// The id of the next http connection.
+    next_connection: Arc<AtomicU64>,
+}
+
+impl RateLimitLayer {
+    /// Creates a new layer with the given config.
+    ///
+    /// Metrics are recorded for the given served methods.
+    pub fn new(config: RateLimitConfig, methods: impl IntoIterator<Item = &'static str>) -> Self {
+        let metrics = methods
+            .into_iter()
+            .chain(std::iter::once(UNKNOWN_METHOD))
+            .map(|method| (method, RateLimitMethodMetrics::new_with_labels(&[("method", method)])))
+            .collect();
+        let limiter = RateLimiter { config, buckets: Default::default(), metrics };
+        Self {
+            limiter: Arc::new(limiter),
+            tracing_guard: None,
+            next_connection: Default::default(),
+        }
+    }
+
+    /// Rejects calls to [RateLimitConfig::guarded_methods] right away while all permits of the
+    /// given guard are taken, instead of queueing them.
+    pub fn with_tracing_guard(mut self, guard: BlockingTaskGuard) -> Self {
+        self.tracing_guard = Some(guard);
+        self
+    }
+
+    /// Wraps the given methods, so that every call over a websocket connection is checked against
+    /// the budget of that connection.
+    ///
+    /// Calls over http are checked by the middleware and are passed through.
+    pub fn limit_ws_calls(&self, methods: &mut Methods) {
+        let names = methods.method_names().collect::<Vec<_>>();
+        for name in names {
+            let Some(callback) = methods.remove_method(name) else { continue };
+            let callback = self.limit_ws_callback(name, callback);
+            methods.verify_and_insert(name, callback).expect("method was removed");
+        }
+    }
+
+    /// Returns the callback that checks calls over websocket connections before calling the
+    /// given callback.
+    fn limit_ws_callback(&self, name: &'static str, callback: MethodCallback) -> MethodCallback {
+        let limiter = self.limiter.clone();
+        let tracing_guard = self.tracing_guard.clone();
+        let check = move |conn_id: usize| {
+            if HTTP_REQUEST.try_with(|_| ()).is_ok() {
+                return Ok(())
+            }
+            let client =
+                PEER_ADDR.with(Cell::take).map_or(ClientKey::WsConnection(conn_id), ClientKey::Ip);
+            limiter.check(client, &[name.to_string()], tracing_guard.as_ref())
+        };
+
+        match callback {
+            // sync methods don't receive the connection id
+            MethodCallback::Sync(method) => MethodCallback::Async(Arc::new(
+                move |id: Id<'static>,
+                      params: Params<'static>,
+                      conn_id: usize,
+                      max_response_size: usize|
+                      -> BoxFuture<'static, MethodResponse> {
+                    let response = match check(conn_id) {
+                        Ok(()) => method(id, params, max_response_size),
+                        Err(retry_after) => ws_rate_limited_response(id, retry_after),
+                    };
+                    Box::pin(std::future::ready(response))
+                },
+            )),
+            MethodCallback::Async(method) => MethodCallback::Async(Arc::new(
+                move |id: Id<'static>,
+                      params: Params<'static>,
+                      conn_id: usize,
+                      max_response_size: usize|
+                      -> BoxFuture<'static, MethodResponse> {
+                    match check(conn_id) {
+                        Ok(()) => method(id, params, conn_id, max_response_size),
+                        Err(retry_after) => {
+                            Box::pin(std::future::ready(ws_rate_limited_response(id, retry_after)))
+                        }
+                    }
+                },
+            )),
+            MethodCallback::Subscription(method) => MethodCallback::Subscription(Arc::new(
+                move |id: Id<'_>,
+                      params: Params<'_>,
+                      sink: MethodSink,
+                      state: SubscriptionState<'_>| {
+                    match check(state.conn_id) {
+                        Ok(()) => method(id, params, sink, state),
+                        Err(retry_after) => {
+                            // subscription responses are sent over the sink
+                            let response = ws_rate_limited_response(id, retry_after);
+                            Box::pin(async move {
+                                let _ = sink.send(response.result.clone()).await;
+                                Ok(response)
+                            })
+                        }
+                    }
+                },
+            )),
+            // unsubscribing is always allowed
+            unsubscription @ MethodCallback::Unsubscription(_) => unsubscription,
+        }
+    }
+}
+
+/// A server logger that reports the address of the peer to the [RateLimitLayer].
+///
+/// Must be set as the logger of the server, or be combined with its logger, so that clients are
+/// rate limited by their IP address.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitLogger {
+    /// The address of the peer of the connection, the server clones the logger per connection.
+    peer: OnceLock<IpAddr>,
+}
+
+impl Logger for RateLimitLogger {
+    type Instant = ();
+
+    fn on_connect(&self, remote_addr: SocketAddr, _request: &HttpRequest, _t: TransportProtocol) {
+        let _ = self.peer.set(remote_addr.ip());
+        PEER_ADDR.with(|peer| peer.set(Some(remote_addr.ip())));
+    }
+
+    fn on_request(&self, _transport: TransportProtocol) -> Self::Instant {}
+
+    fn on_call(
+        &self,
+        _method_name: &str,
+        _params: Params<'_>,
+        _kind: MethodKind,
+        transport: TransportProtocol,
+    ) {
+        // calls over http are checked by the middleware
+        if matches!(transport, TransportProtocol::WebSocket) {
+            PEER_ADDR.with(|peer| peer.set(self.peer.get().copied()));
+        }
+    }
+
+    fn on_result(
+        &self,
+        _method_name: &str,
+        _success_or_error: logger::SuccessOrError,
+        _started_at: Self::Instant,
+        _transport: TransportProtocol,
+    ) {
+    }
+
+    fn on_response(
+        &self,
+        _result: &str,
+        _started_at: Self::Instant,
+        _transport: TransportProtocol,
+    ) {
+    }
+
+    fn on_disconnect(&self, _remote_addr: SocketAddr, _transport: TransportProtocol) {}
+}
+
+/// Calls the service and returns the address of the peer that the server reported to the
+/// [RateLimitLogger] while handling the call.
+///
+/// The request isn't served before the returned future is polled, and a websocket connection is
+/// only established if the upgrade response is returned.
+fn call_with_peer_addr<S: Service<Request<Body>>>(
+    service: &mut S,
+    req: Request<Body>,
+) -> (Option<IpAddr>, S::Future) {
+    PEER_ADDR.with(|peer| peer.set(None));
+    let fut = service.call(req);
+    (PEER_ADDR.with(Cell::take), fut)
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            limiter: self.limiter.clone(),
+            tracing_guard: self.tracing_guard.clone(),
+            connection: self.next_connection.fetch_add(1, Ordering::Relaxed),
+            inner,
+        }
+    }
+}
+
+/// The service of the [RateLimitLayer].
+#[derive(Debug, Clone)]
+pub struct RateLimitService<S> {
+    limiter: Arc<RateLimiter>,
+    tracing_guard: Option<BlockingTaskGuard>,
+    /// The id of the connection the service serves.
+    connection: u64,
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: From<hyper::Error> + Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // take the service that was polled ready
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+        let tracing_guard = self.tracing_guard.clone();
+        let connection = self.connection;
+
+        Box::pin(async move {
+            let client = ClientKey::from_headers(&limiter.config, req.headers());
+            let client_key = |peer: Option<IpAddr>| {
+                client.or(peer.map(ClientKey::Ip)).unwrap_or(ClientKey::Connection(connection))
+            };
+
+            if req.method() != Method::POST {
+                // websocket upgrades and other non-call requests have the default weight
+                let (peer, fut) = call_with_peer_addr(&mut inner, req);
+                if let Err(retry_after) = limiter.check(client_key(peer), &[], None) {
+                    return Ok(rate_limited_response(&Calls::default(), retry_after))
+                }
+                return fut.await
+            }
+
+            let too_large = req
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok()?.parse::<u64>().ok())
+                .map_or(false, |len| len > limiter.config.max_request_body_size as u64);
+            if too_large {
+                // rejected by the server anyway, don't buffer the body
+                return inner.call(req).await
+            }
+
+            let (parts, body) = req.into_parts();
+            let limit = limiter.config.max_request_body_size as usize;
+            let body = match hyper::body::to_bytes(Limited::new(body, limit)).await {
+                Ok(body) => body,
+                Err(err) => {
+                    return match err.downcast::<hyper::Error>() {
+                        Ok(err) => Err((*err).into()),
+                        Err(_) => Ok(oversized_request_response()),
+                    }
+                }
+            };
+            let calls = parse_calls(&body);
+
+            let (peer, fut) =
+                call_with_peer_addr(&mut inner, Request::from_parts(parts, Body::from(body)));
+            let res = limiter.check(client_key(peer), &calls.methods, tracing_guard.as_ref());
+            if let Err(retry_after) = res {
+                return Ok(rate_limited_response(&calls, retry_after))
+            }
+            HTTP_REQUEST.scope((), fut).await
+        })
+    }
+}
+
+/// The fields of a JSON-RPC call the [RateLimitLayer] needs.
+#[derive(Deserialize)]
+struct CallHeader {
+    #[serde(default)]
+    id: serde_json::Value,
+    method: String,
+}
+
+/// The calls of a request body.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Calls {
+    /// Whether the calls were sent as a batch.
+    is_batch: bool,
+    /// The ids of the calls, `null` for notifications.
+    ids: Vec<serde_json::Value>,
+    /// The called methods.
+    methods: Vec<String>,
+}
+
+/// Returns the calls of a request body.
+///
+/// Bodies that are not valid JSON-RPC calls are charged the default weight and are rejected by the
+/// server.
+fn parse_calls(body: &[u8]) -> Calls {
+    let is_batch = body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+    let calls = if is_batch {
+        serde_json::from_slice::<Vec<CallHeader>>(body).unwrap_or_default()
+    } else {
+        serde_json::from_slice::<CallHeader>(body).map(|call| vec![call]).unwrap_or_default()
+    };
+    let (ids, methods) = calls.into_iter().map(|call| (call.id, call.method)).unzip();
+    Calls { is_batch, ids, methods }
+}
+
+/// Rounds up to full seconds, as required by the `Retry-After` header.
+fn retry_after_secs(retry_after: Duration) -> u64 {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    secs.max(1)
+}
+
+/// Returns the response of a rejected request.
+///
+/// Batches are answered with an error for every call of the batch.
+fn rate_limited_response(calls: &Calls, retry_after: Duration) -> Response<Body> {
+    let retry_after = retry_after_secs(retry_after);
+    let error = |id: &serde_json::Value| {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": RATE_LIMIT_ERROR_CODE,
+                "message": "rate limit exceeded",
+                "data": { "retryAfter": retry_after },
+            },
+        })
+    };
+    let body = if calls.is_batch && !calls.ids.is_empty() {
+        serde_json::Value::Array(calls.ids.iter().map(error).collect())
+    } else {
+        error(calls.ids.first().unwrap_or(&serde_json::Value::Null))
+    };
+
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(CONTENT_TYPE, "application/json")
+        .header(RETRY_AFTER, retry_after)
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+/// Returns the response of a request whose body exceeds [RateLimitConfig::max_request_body_size].
+fn oversized_request_response() -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": OVERSIZED_REQUEST_CODE, "message": "Request is too big" },
+    });
+
+    Response::builder()
+        .status(StatusCode::PAYLOAD_TOO_LARGE)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+/// Returns the response of a call over a websocket connection that is rejected.
+fn ws_rate_limited_response(id: Id<'_>, retry_after: Duration) -> MethodResponse {
+    let data = serde_json::json!({ "retryAfter": retry_after_secs(retry_after) });
+    MethodResponse::error(
+        id,
+        ErrorObject::owned(RATE_LIMIT_ERROR_CODE, "rate limit exceeded", Some(data)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::RpcModule;
+
+    fn limiter(config: RateLimitConfig) -> RateLimiter {
+        let RateLimitLayer { limiter, .. } =
+            RateLimitLayer::new(config, ["eth_call", "debug_traceCall"]);
+        Arc::try_unwrap(limiter).unwrap()
+    }
+
+    #[test]
+    fn method_weights() {
+        let config = RateLimitConfig::default();
+        assert_eq!(config.weight("eth_blockNumber"), 1);
+        assert_eq!(config.weight("eth_getLogs"), 20);
+        assert_eq!(config.weight("debug_traceTransaction"), 20);
+
+        let mut weights = HashMap::from([("debug_*".to_string(), 10)]);
+        weights.insert("debug_trace*".to_string(), 50);
+        weights.insert("debug_traceCall".to_string(), 100);
+        let config = RateLimitConfig { method_weights: weights, ..Default::default() };
+        assert_eq!(config.weight("debug_getRawBlock"), 10);
+        assert_eq!(config.weight("debug_traceBlockByNumber"), 50);
+        assert_eq!(config.weight("debug_traceCall"), 100);
+        assert!(config.is_guarded("trace_block"));
+        assert!(!config.is_guarded("debug_getBadBlocks"));
+    }
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(10, now);
+        assert!(bucket.try_take(6, 10, 2, now).is_ok());
+        assert_eq!(bucket.try_take(6, 10, 2, now), Err(Duration::from_secs(1)));
+        assert!(bucket.try_take(6, 10, 2, now + Duration::from_secs(1)).is_ok());
+
+        // expensive calls are admitted once the bucket is full
+        let mut bucket = TokenBucket::full(10, now);
+        assert!(bucket.try_take(30, 10, 4, now).is_ok());
+        assert_eq!(bucket.try_take(1, 10, 4, now), Err(Duration::from_millis(5250)));
+    }
+
+    #[test]
+    fn limits_per_client() {
+        let config = RateLimitConfig {
+            burst: 10,
+            per_second: 1,
+            api_key_header: Some(HeaderName::from_static("x-api-key")),
+            ..Default::default()
+        };
+        let limiter = limiter(config);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "alice".parse().unwrap());
+        let alice = ClientKey::from_headers(&limiter.config, &headers).unwrap();
+        assert_eq!(alice, ClientKey::ApiKey("alice".to_string()));
+
+        let calls = vec!["eth_call".to_string(), "eth_call".to_string()];
+        assert!(limiter.check(alice.clone(), &calls, None).is_ok());
+        assert!(limiter.check(alice, &calls, None).is_err());
+
+        // forwarded headers are not trusted by default, the client is identified by its peer
+        // address
+        headers.clear();
+        headers.insert("x-forwarded-for", "10.0.0.1, 10.0.0.2".parse().unwrap());
+        assert_eq!(ClientKey::from_headers(&limiter.config, &headers), None);
+        let peer = ClientKey::Ip("10.0.0.3".parse().unwrap());
+        assert!(limiter.check(peer.clone(), &calls, None).is_ok());
+        assert!(limiter.check(peer, &calls, None).is_err());
+        assert!(limiter.check(ClientKey::Ip("10.0.0.4".parse().unwrap()), &calls, None).is_ok());
+
+        let config = RateLimitConfig { trust_forwarded_headers: true, ..Default::default() };
+        assert_eq!(
+            ClientKey::from_headers(&config, &headers),
+            Some(ClientKey::Ip("10.0.0.1".parse().unwrap()))
+        );
+    }
+
+    #[tokio::test]
+    async fn limits_http_clients_by_peer_address() {
+        let config = RateLimitConfig { burst: 1, per_second: 1, ..Default::default() };
+        let layer = RateLimitLayer::new(config, ["eth_blockNumber"]);
+        let request = || {
+            Request::post("/")
+                .body(Body::from(r#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}"#))
+                .unwrap()
+        };
+
+        // a server that reports the peer address to the logger
+        let server = |peer: &'static str| {
+            let logger = RateLimitLogger::default();
+            hyper::service::service_fn(move |_req: Request<Body>| {
+                logger.on_connect(
+                    peer.parse().unwrap(),
+                    &Default::default(),
+                    TransportProtocol::Http,
+                );
+                async { Ok::<_, hyper::Error>(Response::new(Body::empty())) }
+            })
+        };
+
+        // new connections of the same peer share its budget
+        let res = layer.layer(server("10.0.0.1:30000")).call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = layer.layer(server("10.0.0.1:30001")).call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let res = layer.layer(server("10.0.0.2:30000")).call(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_guarded_methods_without_permits() {
+        let guard = BlockingTaskGuard::new(1);
+        let limiter = limiter(RateLimitConfig::default());
+
+        let client = ClientKey::Connection(0);
+
+        let calls = vec!["debug_traceCall".to_string()];
+        assert!(limiter.check(client.clone(), &calls, Some(&guard)).is_ok());
+
+        let _permit = guard.clone().acquire_owned().await.unwrap();
+        assert_eq!(
+            limiter.check(client.clone(), &calls, Some(&guard)),
+            Err(Duration::from_secs(1))
+        );
+        let calls = vec!["eth_call".to_string()];
+        assert!(limiter.check(client, &calls, Some(&guard)).is_ok());
+    }
+
+    #[test]
+    fn parse_request_calls() {
+        let calls = parse_calls(br#"{"jsonrpc":"2.0","id":7,"method":"eth_call"}"#);
+        assert!(!calls.is_batch);
+        assert_eq!(calls.ids, vec![serde_json::Value::from(7)]);
+        assert_eq!(calls.methods, vec!["eth_call".to_string()]);
+
+        let calls = parse_calls(
+            br#" [{"jsonrpc":"2.0","id":1,"method":"eth_call"},{"jsonrpc":"2.0","method":"eth_getLogs"}]"#,
+        );
+        assert!(calls.is_batch);
+        assert_eq!(calls.ids, vec![serde_json::Value::from(1), serde_json::Value::Null]);
+        assert_eq!(calls.methods, vec!["eth_call".to_string(), "eth_getLogs".to_string()]);
+
+        assert_eq!(parse_calls(b"not json"), Calls::default());
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_body() {
+        let calls = parse_calls(br#"{"jsonrpc":"2.0","id":1,"method":"eth_call"}"#);
+        let res = rate_limited_response(&calls, Duration::from_millis(1500));
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[RETRY_AFTER], "2");
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], 1);
+        assert_eq!(body["error"]["code"], RATE_LIMIT_ERROR_CODE);
+        assert_eq!(body["error"]["data"]["retryAfter"], 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_batch_response_body() {
+        let calls = parse_calls(
+            br#"[{"jsonrpc":"2.0","id":1,"method":"eth_call"},{"jsonrpc":"2.0","id":"a","method":"eth_getLogs"}]"#,
+        );
+        let res = rate_limited_response(&calls, Duration::from_secs(1));
+
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let errors = body.as_array().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0]["id"], 1);
+        assert_eq!(errors[1]["id"], "a");
+        assert!(errors.iter().all(|error| error["error"]["code"] == RATE_LIMIT_ERROR_CODE));
+    }
+
+    #[tokio::test]
+    async fn limits_ws_calls() {
+        let config = RateLimitConfig { burst: 2, per_second: 1, ..Default::default() };
+        let layer = RateLimitLayer::new(config, ["eth_blockNumber"]);
+        let mut module = RpcModule::new(());
+        module.register_method("eth_blockNumber", |_, _| "0x1").unwrap();
+        layer.limit_ws_calls(&mut module);
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}"#;
+        for _ in 0..2 {
+            let (response, _) = module.raw_json_request(request, 1).await.unwrap();
+            assert!(response.result.contains("0x1"));
+        }
+        let (response, _) = module.raw_json_request(request, 1).await.unwrap();
+        assert!(response.result.contains(&RATE_LIMIT_ERROR_CODE.to_string()));
+
+        // the server reports the peer of the connection right before calling a method
+        let logger = RateLimitLogger::default();
+        logger.on_connect(
+            "10.0.0.1:30000".parse().unwrap(),
+            &Default::default(),
+            TransportProtocol::WebSocket,
+        );
+        let on_call = || {
+            logger.on_call(
+                "eth_blockNumber",
+                Params::new(None),
+                MethodKind::MethodCall,
+                TransportProtocol::WebSocket,
+            )
+        };
+        for _ in 0..2 {
+            on_call();
+            let (response, _) = module.raw_json_request(request, 1).await.unwrap();
+            assert!(response.result.contains("0x1"));
+        }
+        on_call();
+        let (response, _) = module.raw_json_request(request, 1).await.unwrap();
+        assert!(response.result.contains(&RATE_LIMIT_ERROR_CODE.to_string()));
+
+        // calls over http are checked by the middleware
+        let (response, _) =
+            HTTP_REQUEST.scope((), module.raw_json_request(request, 1)).await.unwrap();
+        assert!(response.result.contains("0x1"));
+    }
+}
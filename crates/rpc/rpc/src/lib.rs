@@ -31,25 +31,37 @@ mod debug;
 mod engine;
 pub mod eth;
 mod layers;
+mod mev;
 mod net;
 mod otterscan;
+mod personal;
 mod reth;
 mod rpc;
 mod trace;
 mod txpool;
+mod validation;
 mod web3;
 pub use admin::AdminApi;
 pub use blocking_pool::{BlockingTaskGuard, BlockingTaskPool};
 pub use debug::DebugApi;
 pub use engine::{EngineApi, EngineEthApi};
 pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub, EthSubscriptionIdProvider};
-pub use layers::{AuthLayer, AuthValidator, Claims, JwtAuthValidator, JwtError, JwtSecret};
+pub use layers::{
+    AuthLayer, AuthValidator, Claims, JwtAuthValidator, JwtError, JwtSecret, RateLimitConfig,
+    RateLimitLayer, RateLimitLogger, RateLimitService, DEFAULT_GUARDED_METHODS,
+    DEFAULT_METHOD_WEIGHTS, RATE_LIMIT_ERROR_CODE,
+};
+pub use mev::{MevApi, MevBundleError};
 pub use net::NetApi;
 pub use otterscan::OtterscanApi;
+pub use personal::PersonalApi;
 pub use reth::RethApi;
 pub use rpc::RPCApi;
 pub use trace::TraceApi;
 pub use txpool::TxPoolApi;
+pub use validation::{
+    ProposerPaymentError, ValidationApi, ValidationApiConfig, ValidationApiError,
+};
 pub use web3::Web3Api;
 pub mod blocking_pool;
 pub mod result;
@@ -0,0 +1,911 @@
+//! `mev` namespace handler implementation.
+
+use crate::{
+    eth::{
+        error::{EthApiError, EthResult},
+        revm_utils::{inspect, FillableTransaction},
+        utils::recover_raw_transaction,
+        EthTransactions,
+    },
+    BlockingTaskGuard,
+};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use jsonrpsee::core::RpcResult;
+use reth_payload_builder::{Bundle, BundlePool, BundleTransaction};
+use reth_primitives::{
+    keccak256,
+    revm_primitives::db::{DatabaseCommit, DatabaseRef},
+    Address, BlockId, BlockNumberOrTag, TransactionSignedEcRecovered, B256, U256, U64,
+};
+use reth_provider::{BlockNumReader, CanonStateNotification};
+use reth_revm::{
+    database::StateProviderDatabase,
+    tracing::{types::LogCallOrder, CallTraceArena, TracingInspector, TracingInspectorConfig},
+};
+use reth_rpc_api::MevApiServer;
+use reth_rpc_types::{
+    BundleItem, Log, PrivacyHint, SendBundleRequest, SendBundleResponse, SimBundleLogs,
+    SimBundleOverrides, SimBundleResponse,
+};
+use reth_transaction_pool::TransactionPool;
+use revm::{
+    db::CacheDB,
+    primitives::{Env, ResultAndState, TxEnv},
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Maximum depth of nested bundles.
+const MAX_NESTED_BUNDLE_DEPTH: usize = 5;
+
+/// Maximum number of body elements of a bundle, including those of its nested bundles.
+const MAX_BUNDLE_BODY_SIZE: usize = 50;
+
+/// Maximum number of blocks a bundle can be valid for.
+const MAX_BLOCK_RANGE: u64 = 30;
+
+/// Gas charged for every refund payout transaction.
+const PAYOUT_GAS: u64 = 21_000;
+
+/// Time a bundle simulation may take if the request does not set a timeout.
+const DEFAULT_SIM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `mev` API implementation.
+///
+/// This type provides the functionality for handling MEV-Share bundles: bundles submitted via
+/// `mev_sendBundle` are stored in a [BundlePool] that the payload builder can draw from, until
+/// they expire, see [maintain](MevApi::maintain).
+pub struct MevApi<Provider, Eth, Pool> {
+    inner: Arc<MevApiInner<Provider, Eth, Pool>>,
+}
+
+// === impl MevApi ===
+
+impl<Provider, Eth, Pool> MevApi<Provider, Eth, Pool> {
+    /// Create a new instance of the [MevApi]
+    pub fn new(
+        provider: Provider,
+        eth_api: Eth,
+        pool: Pool,
+        bundle_pool: BundlePool,
+        blocking_task_guard: BlockingTaskGuard,
+    ) -> Self {
+        let inner =
+            Arc::new(MevApiInner { provider, eth_api, pool, bundle_pool, blocking_task_guard });
+        Self { inner }
+    }
+
+    /// Returns the pool the bundles are stored in.
+    pub fn bundle_pool(&self) -> &BundlePool {
+        &self.inner.bundle_pool
+    }
+}
+
+impl<Provider, Eth, Pool> MevApi<Provider, Eth, Pool>
+where
+    Provider: BlockNumReader + 'static,
+    Eth: EthTransactions + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Validates the bundle and stores it in the bundle pool.
+    pub async fn send_bundle(&self, request: SendBundleRequest) -> EthResult<SendBundleResponse> {
+        let bundle = self.parse_bundle(request)?;
+
+        let next_block = self.inner.provider.best_block_number()? + 1;
+        if bundle.max_block_number < next_block {
+            return Err(EthApiError::InvalidParams(MevBundleError::BundleExpired.to_string()))
+        }
+
+        let mut transactions = Vec::new();
+        bundle.flatten_into(&mut transactions);
+        self.inner.bundle_pool.remove_expired(next_block);
+        self.inner
+            .bundle_pool
+            .insert(Bundle {
+                hash: bundle.hash,
+                transactions,
+                block_number: bundle.block_number,
+                max_block_number: bundle.max_block_number,
+                min_timestamp: None,
+                max_timestamp: None,
+                replacement_uuid: None,
+                signer: None,
+            })
+            .map_err(|err| EthApiError::InvalidParams(err.to_string()))?;
+
+        Ok(SendBundleResponse { bundle_hash: bundle.hash })
+    }
+
+    /// Simulates the bundle on top of the requested parent block, defaulting to the latest block.
+    ///
+    /// A bundle that fails to execute, e.g. because a transaction that must not revert reverts,
+    /// results in an unsuccessful [SimBundleResponse] rather than an error.
+    pub async fn sim_bundle(
+        &self,
+        request: SendBundleRequest,
+        overrides: SimBundleOverrides,
+    ) -> EthResult<SimBundleResponse> {
+        let bundle = self.parse_bundle(request)?;
+
+        let SimBundleOverrides {
+            parent_block,
+            block_number,
+            coinbase,
+            timestamp,
+            gas_limit,
+            base_fee,
+            timeout,
+        } = overrides;
+
+        let parent_block = parent_block.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let (cfg, mut block_env, at) = self.inner.eth_api.evm_env_at(parent_block).await?;
+        let state_block = block_env.number.to::<u64>();
+
+        // the bundle is simulated in the block following the parent block
+        block_env.number =
+            U256::from(block_number.map(|n| n.to::<u64>()).unwrap_or(state_block + 1));
+        if let Some(coinbase) = coinbase {
+            block_env.coinbase = coinbase;
+        }
+        if let Some(timestamp) = timestamp {
+            block_env.timestamp = U256::from(timestamp.to::<u64>());
+        } else {
+            block_env.timestamp += U256::from(12);
+        }
+        if let Some(gas_limit) = gas_limit {
+            block_env.gas_limit = U256::from(gas_limit.to::<u64>());
+        }
+        if let Some(base_fee) = base_fee {
+            block_env.basefee = U256::from(base_fee.to::<u64>());
+        }
+
+        let sim_block = block_env.number.to::<u64>();
+        if !(bundle.block_number..=bundle.max_block_number).contains(&sim_block) {
+            return Err(EthApiError::InvalidParams(
+                MevBundleError::BlockNotInInclusionRange(sim_block).to_string(),
+            ))
+        }
+
+        let timeout = timeout.map(|t| Duration::from_secs(t.to())).unwrap_or(DEFAULT_SIM_TIMEOUT);
+        // the blocking simulation keeps running when the timeout drops its future, so it checks
+        // this flag between the transactions of the bundle
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let sim = self.inner.eth_api.spawn_with_state_at_block(at, {
+            let cancelled = Arc::clone(&cancelled);
+            move |state| {
+                let env = Env { cfg, block: block_env, tx: TxEnv::default() };
+                let mut db = CacheDB::new(StateProviderDatabase::new(state));
+                simulate_bundle(&mut db, &env, &bundle, &cancelled)
+            }
+        });
+        let sim = match tokio::time::timeout(timeout, sim).await {
+            Ok(sim) => sim,
+            Err(_) => {
+                cancelled.store(true, Ordering::Relaxed);
+                return Err(EthApiError::ExecutionTimedOut(timeout))
+            }
+        };
+
+        let state_block = U64::from(state_block);
+        let res = match sim {
+            Ok(Ok(sim)) => SimBundleResponse {
+                success: true,
+                error: None,
+                state_block,
+                mev_gas_price: U64::from(
+                    sim.profit
+                        .checked_div(U256::from(sim.gas_used))
+                        .unwrap_or_default()
+                        .saturating_to::<u64>(),
+                ),
+                profit: U64::from(sim.profit.saturating_to::<u64>()),
+                refundable_value: U64::from(sim.refundable_value.saturating_to::<u64>()),
+                gas_used: U64::from(sim.gas_used),
+                logs: Some(sim.logs),
+            },
+            Ok(Err(err)) => SimBundleResponse {
+                success: false,
+                error: Some(err.to_string()),
+                state_block,
+                mev_gas_price: U64::ZERO,
+                profit: U64::ZERO,
+                refundable_value: U64::ZERO,
+                gas_used: U64::ZERO,
+                logs: None,
+            },
+            Err(err) => return Err(err),
+        };
+
+        Ok(res)
+    }
+
+    /// Removes the bundles that can no longer be included from the bundle pool whenever the
+    /// canonical chain advances, until the stream of canonical state notifications ends.
+    pub async fn maintain<St>(self, mut events: St)
+    where
+        St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+    {
+        while let Some(event) = events.next().await {
+            self.inner.bundle_pool.remove_expired(event.tip().number + 1);
+        }
+    }
+
+    /// Decodes the transactions of the bundle, resolves the transactions it backruns and checks
+    /// the bundle's constraints.
+    fn parse_bundle(&self, request: SendBundleRequest) -> EthResult<ParsedBundle> {
+        let mut body_size = 0;
+        self.parse_bundle_at_depth(request, 0, &mut body_size)
+            .map_err(|err| EthApiError::InvalidParams(err.to_string()))
+    }
+
+    fn parse_bundle_at_depth(
+        &self,
+        request: SendBundleRequest,
+        depth: usize,
+        body_size: &mut usize,
+    ) -> Result<ParsedBundle, MevBundleError> {
+        if depth > MAX_NESTED_BUNDLE_DEPTH {
+            return Err(MevBundleError::MaxDepthExceeded)
+        }
+        let SendBundleRequest { inclusion, bundle_body, validity, privacy, .. } = request;
+
+        if bundle_body.is_empty() {
+            return Err(MevBundleError::EmptyBundleBody)
+        }
+        *body_size += bundle_body.len();
+        if *body_size > MAX_BUNDLE_BODY_SIZE {
+            return Err(MevBundleError::BundleTooLarge)
+        }
+
+        let block_number = inclusion.block_number();
+        let max_block_number = inclusion.max_block_number().unwrap_or(block_number);
+        if max_block_number < block_number || max_block_number - block_number > MAX_BLOCK_RANGE {
+            return Err(MevBundleError::InvalidInclusion)
+        }
+
+        let mut body = Vec::with_capacity(bundle_body.len());
+        let mut hash_bytes = Vec::with_capacity(32 * bundle_body.len());
+        for item in bundle_body {
+            let item = match item {
+                BundleItem::Hash { hash } => {
+                    let tx = self
+                        .inner
+                        .pool
+                        .get(&hash)
+                        .ok_or(MevBundleError::UnmatchedTransaction(hash))?
+                        .transaction
+                        .to_recovered_transaction();
+                    ParsedBundleItem::Tx { tx, can_revert: false }
+                }
+                BundleItem::Tx { tx, can_revert } => {
+                    let tx = recover_raw_transaction(tx)
+                        .map_err(|_| MevBundleError::InvalidTransaction)?
+                        .into_ecrecovered_transaction();
+                    ParsedBundleItem::Tx { tx, can_revert }
+                }
+                BundleItem::Bundle { bundle } => {
+                    let mut nested = self.parse_bundle_at_depth(*bundle, depth + 1, body_size)?;
+                    // the nested bundle can only be included where the enclosing bundle can
+                    nested.block_number = nested.block_number.max(block_number);
+                    nested.max_block_number = nested.max_block_number.min(max_block_number);
+                    if nested.max_block_number < nested.block_number {
+                        return Err(MevBundleError::InvalidInclusion)
+                    }
+                    ParsedBundleItem::Bundle(nested)
+                }
+            };
+            hash_bytes.extend_from_slice(item.hash().as_slice());
+            body.push(item);
+        }
+
+        let validity = validity.unwrap_or_default();
+        let refunds = validity
+            .refund
+            .unwrap_or_default()
+            .into_iter()
+            .map(|refund| (refund.body_idx as usize, refund.percent))
+            .collect::<Vec<_>>();
+        let mut total_refund_percent = 0u64;
+        for (body_idx, percent) in &refunds {
+            if *body_idx >= body.len() {
+                return Err(MevBundleError::InvalidRefund)
+            }
+            total_refund_percent = total_refund_percent.saturating_add(*percent);
+        }
+        if total_refund_percent > 100 {
+            return Err(MevBundleError::InvalidRefund)
+        }
+
+        let refund_config = validity
+            .refund_config
+            .map(|config| {
+                config
+                    .into_iter()
+                    .map(|config| (config.address, config.percent))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !refund_config.is_empty() &&
+            refund_config.iter().map(|(_, percent)| *percent).sum::<u64>() != 100
+        {
+            return Err(MevBundleError::InvalidRefundConfig)
+        }
+
+        let hints = privacy.and_then(|privacy| privacy.hints);
+
+        Ok(ParsedBundle {
+            hash: keccak256(&hash_bytes),
+            block_number,
+            max_block_number,
+            body,
+            refunds,
+            refund_config,
+            hints,
+        })
+    }
+}
+
+#[async_trait]
+impl<Provider, Eth, Pool> MevApiServer for MevApi<Provider, Eth, Pool>
+where
+    Provider: BlockNumReader + 'static,
+    Eth: EthTransactions + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Handler for `mev_sendBundle`
+    async fn send_bundle(&self, request: SendBundleRequest) -> RpcResult<SendBundleResponse> {
+        Ok(MevApi::send_bundle(self, request).await?)
+    }
+
+    /// Handler for `mev_simBundle`
+    async fn sim_bundle(
+        &self,
+        bundle: SendBundleRequest,
+        sim_overrides: SimBundleOverrides,
+    ) -> RpcResult<SimBundleResponse> {
+        let _permit = self.inner.blocking_task_guard.clone().acquire_owned().await;
+        Ok(MevApi::sim_bundle(self, bundle, sim_overrides).await?)
+    }
+}
+
+impl<Provider, Eth, Pool> std::fmt::Debug for MevApi<Provider, Eth, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MevApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Eth, Pool> Clone for MevApi<Provider, Eth, Pool> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+/// Container type for `MevApi`
+struct MevApiInner<Provider, Eth, Pool> {
+    /// Access to the chain's best block.
+    provider: Provider,
+    /// Access to commonly used code of the `eth` namespace
+    eth_api: Eth,
+    /// Transactions that bundles can backrun.
+    pool: Pool,
+    /// Where accepted bundles are stored.
+    bundle_pool: BundlePool,
+    /// restrict the number of concurrent simulations.
+    blocking_task_guard: BlockingTaskGuard,
+}
+
+/// A bundle with all transactions decoded and all backrun transactions resolved.
+#[derive(Debug, Clone)]
+struct ParsedBundle {
+    /// The keccak256 of the concatenated hashes of the body elements.
+    hash: B256,
+    /// The first block the bundle is valid for.
+    block_number: u64,
+    /// The last block the bundle is valid for.
+    max_block_number: u64,
+    /// The body elements, in execution order.
+    body: Vec<ParsedBundleItem>,
+    /// `(body index, percent)` of the bundle's refundable value to pay out to body elements.
+    refunds: Vec<(usize, u64)>,
+    /// `(address, percent)` of refunds paid to this bundle if it is enveloped by another bundle.
+    refund_config: Vec<(Address, u64)>,
+    /// What may be shared about the bundle if it is enveloped by another bundle. Everything may be
+    /// shared if the bundle doesn't set any hints.
+    hints: Option<PrivacyHint>,
+}
+
+impl ParsedBundle {
+    /// Returns the parts of the bundle's simulated logs that its privacy hints allow to share with
+    /// the bundle that envelops it.
+    ///
+    /// Logs are only shared with the `logs` hint. Their emitting contract is only shared with the
+    /// `contract_address` hint and their transaction hash only with the `tx_hash` hint. Neither
+    /// calldata nor function selectors are part of the simulated logs.
+    fn shared_logs(&self, mut logs: Vec<SimBundleLogs>) -> Option<Vec<SimBundleLogs>> {
+        let Some(hints) = &self.hints else { return Some(logs) };
+        if !hints.has_logs() {
+            return None
+        }
+        redact_logs(&mut logs, hints);
+        Some(logs)
+    }
+
+    /// Appends all transactions of the bundle and its nested bundles to `out`, in execution order.
+    fn flatten_into(&self, out: &mut Vec<BundleTransaction>) {
+        for item in &self.body {
+            match item {
+                ParsedBundleItem::Tx { tx, can_revert } => {
+                    out.push(BundleTransaction { transaction: tx.clone(), can_revert: *can_revert })
+                }
+                ParsedBundleItem::Bundle(bundle) => bundle.flatten_into(out),
+            }
+        }
+    }
+}
+
+/// An element of a [ParsedBundle]'s body.
+#[derive(Debug, Clone)]
+enum ParsedBundleItem {
+    /// A single transaction.
+    Tx { tx: TransactionSignedEcRecovered, can_revert: bool },
+    /// A nested bundle.
+    Bundle(ParsedBundle),
+}
+
+impl ParsedBundleItem {
+    /// Returns the hash of the transaction or nested bundle.
+    fn hash(&self) -> B256 {
+        match self {
+            ParsedBundleItem::Tx { tx, .. } => tx.hash(),
+            ParsedBundleItem::Bundle(bundle) => bundle.hash,
+        }
+    }
+
+    /// Returns the number of payout transactions a refund to this element requires.
+    fn refund_recipients(&self) -> u64 {
+        match self {
+            ParsedBundleItem::Tx { .. } => 1,
+            ParsedBundleItem::Bundle(bundle) => bundle.refund_config.len().max(1) as u64,
+        }
+    }
+}
+
+/// The outcome of a successful bundle simulation.
+#[derive(Debug)]
+struct SimulatedBundle {
+    /// Gas used by the bundle's transactions and refund payouts.
+    gas_used: u64,
+    /// What the bundle pays to the coinbase, net of refunds.
+    profit: U256,
+    /// The part of the profit that refunds are computed from.
+    refundable_value: U256,
+    /// The logs of each body element.
+    logs: Vec<SimBundleLogs>,
+}
+
+/// Removes the fields the hints don't allow to share from the logs, including those of nested
+/// bundles.
+fn redact_logs(logs: &mut [SimBundleLogs], hints: &PrivacyHint) {
+    for logs in logs {
+        for log in logs.tx_logs.iter_mut().flatten() {
+            if !hints.has_contract_address() {
+                log.address = Address::ZERO;
+            }
+            if !hints.has_tx_hash() {
+                log.transaction_hash = None;
+            }
+        }
+        if let Some(bundle_logs) = &mut logs.bundle_logs {
+            redact_logs(bundle_logs, hints);
+        }
+    }
+}
+
+/// Executes the bundle on top of `db`, committing all state changes.
+///
+/// The simulation is aborted before the next transaction once `cancelled` is set.
+fn simulate_bundle<DB>(
+    db: &mut CacheDB<DB>,
+    env: &Env,
+    bundle: &ParsedBundle,
+    cancelled: &AtomicBool,
+) -> EthResult<Result<SimulatedBundle, MevBundleError>>
+where
+    DB: DatabaseRef,
+    EthApiError: From<<DB as DatabaseRef>::Error>,
+{
+    let coinbase = env.block.coinbase;
+    let block_number = env.block.number;
+    let mut gas_used = 0u64;
+    let mut body_profits = Vec::with_capacity(bundle.body.len());
+    let mut logs = Vec::with_capacity(bundle.body.len());
+
+    for item in &bundle.body {
+        match item {
+            ParsedBundleItem::Tx { tx, can_revert } => {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Ok(Err(MevBundleError::SimulationCancelled))
+                }
+                let coinbase_balance_before =
+                    db.basic_ref(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+
+                let mut env = env.clone();
+                tx.try_fill_tx_env(&mut env.tx)?;
+                let mut inspector = TracingInspector::new(
+                    TracingInspectorConfig::default_parity().set_record_logs(true),
+                );
+                let (ResultAndState { result, state }, _) = inspect(&mut *db, env, &mut inspector)?;
+
+                if !result.is_success() && !can_revert {
+                    return Ok(Err(MevBundleError::TransactionReverted(tx.hash())))
+                }
+                gas_used += result.gas_used();
+                db.commit(state);
+
+                let coinbase_balance_after =
+                    db.basic_ref(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+                body_profits.push(coinbase_balance_after.saturating_sub(coinbase_balance_before));
+
+                let tx_logs = receipt_logs(inspector.get_traces())
+                    .into_iter()
+                    .map(|(address, log)| Log {
+                        address,
+                        topics: log.topics().to_vec(),
+                        data: log.data,
+                        block_hash: None,
+                        block_number: Some(block_number),
+                        transaction_hash: Some(tx.hash()),
+                        transaction_index: None,
+                        log_index: None,
+                        removed: false,
+                    })
+                    .collect();
+                logs.push(SimBundleLogs { tx_logs: Some(tx_logs), bundle_logs: None });
+            }
+            ParsedBundleItem::Bundle(nested) => {
+                let sim = match simulate_bundle(db, env, nested, cancelled)? {
+                    Ok(sim) => sim,
+                    err => return Ok(err),
+                };
+                gas_used += sim.gas_used;
+                body_profits.push(sim.profit);
+                let bundle_logs = nested.shared_logs(sim.logs);
+                logs.push(SimBundleLogs { tx_logs: None, bundle_logs });
+            }
+        }
+    }
+
+    let mut profit = body_profits.iter().fold(U256::ZERO, |acc, p| acc.saturating_add(*p));
+    // the profit generated by the refunded elements themselves is not refundable
+    let refundable_value = body_profits
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !bundle.refunds.iter().any(|(body_idx, _)| body_idx == idx))
+        .fold(U256::ZERO, |acc, (_, p)| acc.saturating_add(*p));
+
+    for (body_idx, percent) in &bundle.refunds {
+        let payout_gas = PAYOUT_GAS * bundle.body[*body_idx].refund_recipients();
+        let payout_cost = env.block.basefee * U256::from(payout_gas);
+        let payout = refundable_value * U256::from(*percent) / U256::from(100);
+        if payout < payout_cost {
+            return Ok(Err(MevBundleError::NegativeRefund(*body_idx)))
+        }
+        profit = match profit.checked_sub(payout) {
+            Some(profit) => profit,
+            None => return Ok(Err(MevBundleError::NegativeProfit)),
+        };
+        gas_used += payout_gas;
+    }
+
+    Ok(Ok(SimulatedBundle { gas_used, profit, refundable_value, logs }))
+}
+
+/// Returns the logs of the traced transaction that end up in its receipt, i.e. the logs of all
+/// call frames that did not revert, in the order they were emitted, together with the address
+/// that emitted them.
+fn receipt_logs(traces: &CallTraceArena) -> Vec<(Address, alloy_primitives::Log)> {
+    fn collect(
+        traces: &CallTraceArena,
+        idx: usize,
+        out: &mut Vec<(Address, alloy_primitives::Log)>,
+    ) {
+        let node = &traces.nodes()[idx];
+        if !node.trace.success {
+            return
+        }
+        for order in &node.ordering {
+            match order {
+                LogCallOrder::Log(log) => {
+                    out.push((node.execution_address(), node.logs[*log].clone()))
+                }
+                LogCallOrder::Call(child) => collect(traces, node.children[*child], out),
+            }
+        }
+    }
+
+    let mut logs = Vec::new();
+    if !traces.nodes().is_empty() {
+        collect(traces, 0, &mut logs);
+    }
+    logs
+}
+
+/// [MevApi] specific errors.
+#[derive(Debug, thiserror::Error)]
+pub enum MevBundleError {
+    /// Thrown if the body of a bundle is empty.
+    #[error("bundle body is empty")]
+    EmptyBundleBody,
+    /// Thrown if bundles are nested too deeply.
+    #[error("bundles nested deeper than {MAX_NESTED_BUNDLE_DEPTH}")]
+    MaxDepthExceeded,
+    /// Thrown if a bundle has too many body elements.
+    #[error("bundle body exceeds {MAX_BUNDLE_BODY_SIZE} elements")]
+    BundleTooLarge,
+    /// Thrown if the inclusion range of a bundle is empty or too large.
+    #[error("invalid inclusion range")]
+    InvalidInclusion,
+    /// Thrown if a bundle can no longer be included.
+    #[error("bundle expired")]
+    BundleExpired,
+    /// Thrown if the simulated block is outside of the bundle's inclusion range.
+    #[error("block {0} not in the bundle's inclusion range")]
+    BlockNotInInclusionRange(u64),
+    /// Thrown if a backrun transaction is not in the transaction pool.
+    #[error("unmatched transaction {0}")]
+    UnmatchedTransaction(B256),
+    /// Thrown if a raw transaction can not be decoded.
+    #[error("invalid transaction")]
+    InvalidTransaction,
+    /// Thrown if a refund targets a missing body element, or refunds exceed 100 percent.
+    #[error("invalid refund")]
+    InvalidRefund,
+    /// Thrown if the refund config percents do not add up to 100.
+    #[error("invalid refund config")]
+    InvalidRefundConfig,
+    /// Thrown if a transaction that must not revert reverted.
+    #[error("transaction {0} reverted")]
+    TransactionReverted(B256),
+    /// Thrown if the refund to a body element does not cover the cost of paying it out.
+    #[error("refund to body element {0} does not cover the payout gas")]
+    NegativeRefund(usize),
+    /// Thrown if the refunds exceed what the bundle pays to the coinbase.
+    #[error("bundle profit does not cover the refunds")]
+    NegativeProfit,
+    /// Thrown if the simulation was cancelled because it timed out.
+    #[error("bundle simulation cancelled")]
+    SimulationCancelled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eth::{
+            cache::EthStateCache, gas_oracle::GasPriceOracle, FeeHistoryCache,
+            FeeHistoryCacheConfig,
+        },
+        BlockingTaskPool, EthApi,
+    };
+    use reth_interfaces::test_utils::generators::{self, sign_tx_with_key_pair};
+    use reth_network_api::noop::NoopNetwork;
+    use reth_primitives::{
+        constants::ETHEREUM_BLOCK_GAS_LIMIT, public_key_to_address, Block, Bytes, Header,
+        Transaction, TransactionKind, TransactionSigned, TxEip1559,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_rpc_types::{Privacy, ProtocolVersion};
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+    use secp256k1::KeyPair;
+
+    type TestMevApi =
+        MevApi<MockEthProvider, EthApi<MockEthProvider, TestPool, NoopNetwork>, TestPool>;
+
+    /// A contract that emits a log without topics or data.
+    const LOGGER_CODE: &[u8] = &[0x60, 0x00, 0x60, 0x00, 0xa0, 0x00];
+
+    /// A contract that always reverts.
+    const REVERTER_CODE: &[u8] = &[0x60, 0x00, 0x60, 0x00, 0xfd];
+
+    fn mev_api(provider: MockEthProvider) -> TestMevApi {
+        let cache = EthStateCache::spawn(provider.clone(), Default::default());
+        let fee_history_cache =
+            FeeHistoryCache::new(cache.clone(), FeeHistoryCacheConfig::default());
+        let eth_api = EthApi::new(
+            provider.clone(),
+            testing_pool(),
+            NoopNetwork::default(),
+            cache.clone(),
+            GasPriceOracle::new(provider.clone(), Default::default(), cache),
+            ETHEREUM_BLOCK_GAS_LIMIT,
+            BlockingTaskPool::build().expect("failed to build tracing pool"),
+            fee_history_cache,
+        );
+        MevApi::new(provider, eth_api, testing_pool(), BundlePool::new(), BlockingTaskGuard::new(1))
+    }
+
+    /// Adds a block with the given number, which becomes the best block if it's the highest.
+    fn add_block(provider: &MockEthProvider, number: u64) {
+        let block = Block { header: Header { number, ..Default::default() }, ..Default::default() };
+        provider.add_block(B256::random(), block);
+    }
+
+    /// Returns funded keys and a provider that knows the logger and reverter contracts.
+    fn funded_provider(keys: usize) -> (MockEthProvider, Vec<KeyPair>, Address, Address) {
+        let provider = MockEthProvider::default();
+        add_block(&provider, 1);
+        let keys = generators::generate_keys(&mut generators::rng(), keys);
+        for key_pair in &keys {
+            provider.add_account(
+                public_key_to_address(key_pair.public_key()),
+                ExtendedAccount::new(0, U256::from(10u64.pow(18))),
+            );
+        }
+        let (logger, reverter) = (Address::random(), Address::random());
+        for (address, code) in [(logger, LOGGER_CODE), (reverter, REVERTER_CODE)] {
+            provider.add_account(
+                address,
+                ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(code)),
+            );
+        }
+        (provider, keys, logger, reverter)
+    }
+
+    /// Returns a transaction calling `to` that pays a priority fee of one wei per gas.
+    fn signed(key_pair: KeyPair, nonce: u64, to: Address) -> TransactionSigned {
+        sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit: 100_000,
+                max_fee_per_gas: 10,
+                max_priority_fee_per_gas: 1,
+                to: TransactionKind::Call(to),
+                ..Default::default()
+            }),
+        )
+    }
+
+    fn tx(key_pair: KeyPair, nonce: u64, to: Address, can_revert: bool) -> BundleItem {
+        BundleItem::Tx { tx: signed(key_pair, nonce, to).envelope_encoded(), can_revert }
+    }
+
+    fn bundle(block: u64, max_block: Option<u64>, body: Vec<BundleItem>) -> SendBundleRequest {
+        SendBundleRequest::new(
+            U64::from(block),
+            max_block.map(U64::from),
+            ProtocolVersion::V0_1,
+            body,
+        )
+    }
+
+    #[tokio::test]
+    async fn send_bundle() {
+        let (provider, keys, _, _) = funded_provider(1);
+        let api = mev_api(provider.clone());
+
+        let SendBundleResponse { bundle_hash } = api
+            .send_bundle(bundle(2, Some(3), vec![tx(keys[0], 0, Address::random(), false)]))
+            .await
+            .unwrap();
+        let stored = api.bundle_pool().get(&bundle_hash).unwrap();
+        assert_eq!((stored.block_number, stored.max_block_number), (2, 3));
+        assert_eq!(stored.transactions.len(), 1);
+
+        // bundles for past blocks and backruns of unknown transactions are rejected
+        let expired = bundle(1, None, vec![tx(keys[0], 1, Address::random(), false)]);
+        assert!(api.send_bundle(expired).await.is_err());
+        let backrun = bundle(2, None, vec![BundleItem::Hash { hash: B256::random() }]);
+        assert!(api.send_bundle(backrun).await.is_err());
+
+        // bundles that expired are evicted when the next bundle is sent
+        add_block(&provider, 3);
+        api.send_bundle(bundle(4, None, vec![tx(keys[0], 1, Address::random(), false)]))
+            .await
+            .unwrap();
+        assert!(api.bundle_pool().get(&bundle_hash).is_none());
+        assert_eq!(api.bundle_pool().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sim_bundle() {
+        let (provider, keys, logger, reverter) = funded_provider(1);
+        let api = mev_api(provider);
+        let overrides =
+            SimBundleOverrides { coinbase: Some(Address::random()), ..Default::default() };
+
+        let res = api
+            .sim_bundle(bundle(1, None, vec![tx(keys[0], 0, logger, false)]), overrides.clone())
+            .await
+            .unwrap();
+        assert!(res.success);
+        // the coinbase only receives the priority fee of one wei per gas
+        assert_eq!(res.profit, res.gas_used);
+        assert_eq!(res.mev_gas_price, U64::from(1));
+        let logs = res.logs.unwrap();
+        assert_eq!(logs[0].tx_logs.as_ref().unwrap()[0].address, logger);
+
+        let res = api
+            .sim_bundle(bundle(1, None, vec![tx(keys[0], 0, reverter, false)]), overrides.clone())
+            .await
+            .unwrap();
+        assert!(!res.success);
+        assert!(res.error.unwrap().contains("reverted"));
+
+        let res = api
+            .sim_bundle(bundle(1, None, vec![tx(keys[0], 0, reverter, true)]), overrides.clone())
+            .await
+            .unwrap();
+        assert!(res.success);
+
+        // the simulated block must be within the inclusion range
+        assert!(api
+            .sim_bundle(bundle(5, None, vec![tx(keys[0], 0, logger, false)]), overrides)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn sim_bundle_shares_nested_logs_by_hints() {
+        let (provider, keys, logger, _) = funded_provider(2);
+        let api = mev_api(provider);
+
+        let nested_logs = |hints: Option<PrivacyHint>| {
+            let mut nested = bundle(1, None, vec![tx(keys[0], 0, logger, false)]);
+            nested.privacy = hints.map(|hints| Privacy { hints: Some(hints), builders: None });
+            let backrun = bundle(
+                1,
+                None,
+                vec![
+                    BundleItem::Bundle { bundle: Box::new(nested) },
+                    tx(keys[1], 0, Address::random(), false),
+                ],
+            );
+            let api = api.clone();
+            async move {
+                let res = api.sim_bundle(backrun, Default::default()).await.unwrap();
+                assert!(res.success);
+                res.logs.unwrap()[0].bundle_logs.clone().map(|logs| {
+                    let log = logs[0].tx_logs.as_ref().unwrap()[0].clone();
+                    (log.address, log.transaction_hash)
+                })
+            }
+        };
+        let tx_hash = signed(keys[0], 0, logger).hash();
+
+        assert_eq!(nested_logs(None).await, Some((logger, Some(tx_hash))));
+        assert_eq!(nested_logs(Some(PrivacyHint::default())).await, None);
+        assert_eq!(
+            nested_logs(Some(PrivacyHint::default().with_logs())).await,
+            Some((Address::ZERO, None))
+        );
+        let hints = PrivacyHint::default().with_logs().with_contract_address().with_tx_hash();
+        assert_eq!(nested_logs(Some(hints)).await, Some((logger, Some(tx_hash))));
+    }
+
+    #[test]
+    fn cancelled_simulation_stops() {
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let tx = signed(key_pair, 0, Address::random()).into_ecrecovered().unwrap();
+        let bundle = ParsedBundle {
+            hash: B256::ZERO,
+            block_number: 1,
+            max_block_number: 1,
+            body: vec![ParsedBundleItem::Tx { tx, can_revert: false }],
+            refunds: Vec::new(),
+            refund_config: Vec::new(),
+            hints: None,
+        };
+        let mut db = CacheDB::new(StateProviderDatabase::new(MockEthProvider::default()));
+
+        let res = simulate_bundle(&mut db, &Env::default(), &bundle, &AtomicBool::new(true));
+        assert!(matches!(res, Ok(Err(MevBundleError::SimulationCancelled))));
+    }
+}
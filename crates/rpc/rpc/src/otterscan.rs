@@ -1,33 +1,133 @@
 #![allow(dead_code, unused_variables)]
-use crate::result::internal_rpc_err;
+use crate::{
+    eth::{
+        error::{EthApiError, EthResult},
+        EthTransactions,
+    },
+    result::internal_rpc_err,
+};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
-use reth_primitives::{Address, BlockId, BlockNumberOrTag, TxHash, B256};
+use reth_primitives::{Address, BlockId, BlockNumber, BlockNumberOrTag, TxHash, B256};
+use reth_provider::{AddressAppearanceReader, BlockReaderIdExt};
+use reth_revm::tracing::TracingInspectorConfig;
 use reth_rpc_api::{EthApiServer, OtterscanServer};
 use reth_rpc_types::{
-    BlockDetails, ContractCreator, InternalOperation, OtsBlockTransactions, TraceEntry,
-    Transaction, TransactionsWithReceipts,
+    trace::filter::TraceFilter, BlockDetails, BlockTransactions, ContractCreator,
+    InternalOperation, OtsBlockTransactions, OtsTransactionReceipt, TraceEntry, Transaction,
+    TransactionsWithReceipts,
 };
+use std::ops::RangeInclusive;
 
 const API_LEVEL: u64 = 8;
 
+/// The number of blocks that are looked up in the address appearance index at once when
+/// searching for transactions.
+const SEARCH_WINDOW: u64 = 100_000;
+
 /// Otterscan Api
 #[derive(Debug)]
-pub struct OtterscanApi<Eth> {
+pub struct OtterscanApi<Provider, Eth> {
+    provider: Provider,
     eth: Eth,
 }
 
-impl<Eth> OtterscanApi<Eth> {
+impl<Provider, Eth> OtterscanApi<Provider, Eth> {
     /// Creates a new instance of `Otterscan`.
-    pub fn new(eth: Eth) -> Self {
-        Self { eth }
+    pub fn new(provider: Provider, eth: Eth) -> Self {
+        Self { provider, eth }
+    }
+}
+
+impl<Provider, Eth> OtterscanApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt + AddressAppearanceReader + 'static,
+    Eth: EthApiServer + EthTransactions + 'static,
+{
+    /// Resolves the given block number or tag.
+    fn block_number(&self, block_number: BlockNumberOrTag) -> EthResult<BlockNumber> {
+        self.provider.convert_block_number(block_number)?.ok_or(EthApiError::UnknownBlockNumber)
+    }
+
+    /// Returns the blocks within the range in which the address may appear, in ascending order.
+    ///
+    /// Requires the address appearance index.
+    fn candidate_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> EthResult<Vec<BlockNumber>> {
+        let blocks = self
+            .provider
+            .address_appearance_candidates(&[address], range)?
+            .ok_or(EthApiError::Unsupported("address appearance index is not enabled"))?;
+        Ok(blocks.into_iter().collect())
+    }
+
+    /// Returns the transactions of the block in which the address appears, newest first,
+    /// alongside their receipts.
+    ///
+    /// The address appears in a transaction if it is the sender or recipient of any of its calls,
+    /// creations or selfdestructs.
+    async fn transactions_with_address(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+    ) -> RpcResult<(Vec<Transaction>, Vec<OtsTransactionReceipt>)> {
+        let matcher = TraceFilter {
+            from_address: vec![address],
+            to_address: vec![address],
+            ..Default::default()
+        }
+        .matcher();
+        let indices = self
+            .eth
+            .trace_block_with(
+                block_number.into(),
+                TracingInspectorConfig::default_parity(),
+                move |tx_info, inspector, _, _, _| {
+                    let traces = inspector.into_parity_builder().into_transaction_traces();
+                    let appears = traces.iter().any(|trace| matcher.matches_trace(trace));
+                    Ok(tx_info.index.filter(|_| appears))
+                },
+            )
+            .await?
+            .unwrap_or_default();
+        let indices = indices.into_iter().flatten().collect::<Vec<_>>();
+        if indices.is_empty() {
+            return Ok(Default::default())
+        }
+
+        let block = EthApiServer::block_by_number(&self.eth, block_number.into(), true)
+            .await?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        let receipts = EthApiServer::block_receipts(&self.eth, block_number.into())
+            .await?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        let timestamp = block.inner.header.timestamp.to::<u64>();
+        let BlockTransactions::Full(transactions) = block.inner.transactions else {
+            return Err(EthApiError::UnknownBlockNumber.into())
+        };
+
+        let mut txs = Vec::with_capacity(indices.len());
+        let mut tx_receipts = Vec::with_capacity(indices.len());
+        for index in indices.into_iter().rev() {
+            let index = index as usize;
+            let (Some(tx), Some(receipt)) = (transactions.get(index), receipts.get(index)) else {
+                return Err(EthApiError::UnknownBlockOrTxIndex.into())
+            };
+            txs.push(tx.clone());
+            tx_receipts.push(OtsTransactionReceipt { receipt: receipt.clone(), timestamp });
+        }
+        Ok((txs, tx_receipts))
     }
 }
 
 #[async_trait]
-impl<Eth> OtterscanServer for OtterscanApi<Eth>
+impl<Provider, Eth> OtterscanServer for OtterscanApi<Provider, Eth>
 where
-    Eth: EthApiServer,
+    Provider: BlockReaderIdExt + AddressAppearanceReader + 'static,
+    Eth: EthApiServer + EthTransactions + 'static,
 {
     /// Handler for `ots_hasCode`
     async fn has_code(&self, address: Address, block_number: Option<BlockId>) -> RpcResult<bool> {
@@ -80,23 +180,91 @@ where
     }
 
     /// Handler for `searchTransactionsBefore`
+    ///
+    /// Searches backwards from the block before the given one, or from the latest block if it's
+    /// `0`. Transactions of a block are never split across pages, so a page can contain more than
+    /// `page_size` transactions.
     async fn search_transactions_before(
         &self,
         address: Address,
         block_number: BlockNumberOrTag,
         page_size: usize,
     ) -> RpcResult<TransactionsWithReceipts> {
-        Err(internal_rpc_err("unimplemented"))
+        let block_number = self.block_number(block_number)?;
+        let first_page = block_number == 0;
+        let mut end = if first_page {
+            self.provider.best_block_number().map_err(EthApiError::from)?
+        } else {
+            block_number - 1
+        };
+
+        let mut result = TransactionsWithReceipts {
+            txs: Vec::new(),
+            receipts: Vec::new(),
+            first_page,
+            last_page: true,
+        };
+        'search: loop {
+            let start = end.saturating_sub(SEARCH_WINDOW - 1);
+            for block in self.candidate_blocks(address, start..=end)?.into_iter().rev() {
+                if result.txs.len() >= page_size {
+                    result.last_page = false;
+                    break 'search
+                }
+                let (txs, receipts) = self.transactions_with_address(address, block).await?;
+                result.txs.extend(txs);
+                result.receipts.extend(receipts);
+            }
+            if start == 0 {
+                break
+            }
+            end = start - 1;
+        }
+
+        Ok(result)
     }
 
     /// Handler for `searchTransactionsAfter`
+    ///
+    /// Searches forwards from the block after the given one. Transactions of a block are never
+    /// split across pages, so a page can contain more than `page_size` transactions.
     async fn search_transactions_after(
         &self,
         address: Address,
         block_number: BlockNumberOrTag,
         page_size: usize,
     ) -> RpcResult<TransactionsWithReceipts> {
-        Err(internal_rpc_err("unimplemented"))
+        let block_number = self.block_number(block_number)?;
+        let best = self.provider.best_block_number().map_err(EthApiError::from)?;
+        let mut start = block_number + 1;
+
+        let mut blocks = Vec::new();
+        let mut found = 0;
+        let mut first_page = true;
+        'search: while start <= best {
+            let end = start.saturating_add(SEARCH_WINDOW - 1).min(best);
+            for block in self.candidate_blocks(address, start..=end)? {
+                if found >= page_size {
+                    first_page = false;
+                    break 'search
+                }
+                let (txs, receipts) = self.transactions_with_address(address, block).await?;
+                found += txs.len();
+                blocks.push((txs, receipts));
+            }
+            start = end + 1;
+        }
+
+        // transactions are returned newest first
+        let (txs, receipts) = blocks.into_iter().rev().fold(
+            (Vec::new(), Vec::new()),
+            |(mut all_txs, mut all_receipts), (txs, receipts)| {
+                all_txs.extend(txs);
+                all_receipts.extend(receipts);
+                (all_txs, all_receipts)
+            },
+        );
+        Ok(TransactionsWithReceipts { txs, receipts, first_page, last_page: block_number == 0 })
     }
 
     /// Handler for `getTransactionBySenderAndNonce`
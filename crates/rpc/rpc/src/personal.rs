@@ -0,0 +1,246 @@
+use crate::{
+    eth::{KeystoreError, KeystoreSigner},
+    result::{internal_rpc_err, invalid_params_rpc_err},
+};
+use async_trait::async_trait;
+use jsonrpsee::{core::RpcResult, types::ErrorObject, RpcModule};
+use reth_primitives::{Address, Bytes};
+use reth_rpc_api::PersonalApiServer;
+use reth_tasks::TaskSpawner;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// The duration an account is unlocked for by `personal_unlockAccount` if none is given.
+pub const DEFAULT_UNLOCK_DURATION: Duration = Duration::from_secs(300);
+
+/// `personal` API implementation.
+///
+/// This type provides the functionality for handling `personal` related requests, which manage
+/// the accounts of the node's [KeystoreSigner].
+pub struct PersonalApi {
+    /// The keystore that holds the accounts.
+    keystore: KeystoreSigner,
+    /// The type that can spawn tasks which would otherwise block.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+// === impl PersonalApi ===
+
+impl PersonalApi {
+    /// The name of the method that unlocks accounts.
+    pub const UNLOCK_ACCOUNT_METHOD: &'static str = "personal_unlockAccount";
+
+    /// Creates a new instance of `PersonalApi`.
+    pub fn new(keystore: KeystoreSigner, task_spawner: Box<dyn TaskSpawner>) -> Self {
+        Self { keystore, task_spawner }
+    }
+
+    /// Returns a module with a [Self::UNLOCK_ACCOUNT_METHOD] handler that refuses to unlock
+    /// accounts.
+    ///
+    /// An unlocked account can be used by anyone who can reach the server, so like geth, accounts
+    /// can only be unlocked over ipc unless insecure unlocking is allowed.
+    pub fn insecure_unlock_refused() -> RpcModule<()> {
+        let mut module = RpcModule::new(());
+        module
+            .register_method(Self::UNLOCK_ACCOUNT_METHOD, |_, _| {
+                Err::<bool, _>(invalid_params_rpc_err(
+                    "account unlock with HTTP access is forbidden",
+                ))
+            })
+            .expect("module is empty");
+        module
+    }
+
+    /// Executes the closure on a new blocking task.
+    ///
+    /// Decrypting a key is intentionally expensive, hence all operations that require the password
+    /// are spawned.
+    async fn on_blocking_task<F, R>(&self, f: F) -> RpcResult<R>
+    where
+        F: FnOnce(KeystoreSigner) -> Result<R, KeystoreError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let keystore = self.keystore.clone();
+        self.task_spawner.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(f(keystore));
+        }));
+        let res = rx.await.map_err(|_| internal_rpc_err("internal blocking task error"))?;
+        res.map_err(Into::into)
+    }
+}
+
+#[async_trait]
+impl PersonalApiServer for PersonalApi {
+    /// Handler for `personal_listAccounts`
+    async fn list_accounts(&self) -> RpcResult<Vec<Address>> {
+        Ok(self.keystore.accounts())
+    }
+
+    /// Handler for `personal_unlockAccount`
+    async fn unlock_account(
+        &self,
+        address: Address,
+        password: String,
+        duration: Option<u64>,
+    ) -> RpcResult<bool> {
+        let duration = match duration {
+            None => Some(DEFAULT_UNLOCK_DURATION),
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+        };
+        self.on_blocking_task(move |keystore| keystore.unlock(address, &password, duration))
+            .await?;
+        Ok(true)
+    }
+
+    /// Handler for `personal_lockAccount`
+    async fn lock_account(&self, address: Address) -> RpcResult<bool> {
+        Ok(self.keystore.lock(&address))
+    }
+
+    /// Handler for `personal_sign`
+    async fn sign(&self, message: Bytes, address: Address, password: String) -> RpcResult<Bytes> {
+        let signature = self
+            .on_blocking_task(move |keystore| {
+                keystore.sign_with_password(address, &password, &message)
+            })
+            .await?;
+        Ok(signature.to_hex_bytes())
+    }
+}
+
+impl std::fmt::Debug for PersonalApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersonalApi").finish_non_exhaustive()
+    }
+}
+
+impl From<KeystoreError> for ErrorObject<'static> {
+    fn from(err: KeystoreError) -> Self {
+        match err {
+            KeystoreError::Io { .. } | KeystoreError::InvalidKeyFile(_) => {
+                internal_rpc_err(err.to_string())
+            }
+            KeystoreError::UnknownAccount(_) |
+            KeystoreError::InvalidPassword |
+            KeystoreError::InvalidUnlockDuration(_) |
+            KeystoreError::Signing(_) => invalid_params_rpc_err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eth::{
+            cache::EthStateCache,
+            error::{EthApiError, SignError},
+            gas_oracle::GasPriceOracle,
+            keystore::test_utils::new_key_file,
+            FeeHistoryCache, FeeHistoryCacheConfig,
+        },
+        BlockingTaskPool, EthApi,
+    };
+    use reth_network_api::noop::NoopNetwork;
+    use reth_primitives::{constants::ETHEREUM_BLOCK_GAS_LIMIT, U128, U256, U64};
+    use reth_provider::test_utils::NoopProvider;
+    use reth_rpc_types::{LegacyTransactionRequest, TransactionKind, TypedTransactionRequest};
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+    /// Returns the `personal` and `eth` handlers of a new keystore with a single account.
+    fn apis(
+        dir: &std::path::Path,
+    ) -> (Address, PersonalApi, EthApi<NoopProvider, TestPool, NoopNetwork>) {
+        let address = new_key_file(dir, "password");
+        let keystore = KeystoreSigner::load(dir).unwrap();
+
+        let provider = NoopProvider::default();
+        let cache = EthStateCache::spawn(provider, Default::default());
+        let fee_history_cache =
+            FeeHistoryCache::new(cache.clone(), FeeHistoryCacheConfig::default());
+        let eth = EthApi::new(
+            provider,
+            testing_pool(),
+            NoopNetwork::default(),
+            cache.clone(),
+            GasPriceOracle::new(provider, Default::default(), cache),
+            ETHEREUM_BLOCK_GAS_LIMIT,
+            BlockingTaskPool::build().expect("failed to build tracing pool"),
+            fee_history_cache,
+        );
+        eth.add_keystore(keystore.clone());
+
+        let personal = PersonalApi::new(keystore, Box::<TokioTaskExecutor>::default());
+        (address, personal, eth)
+    }
+
+    #[tokio::test]
+    async fn unlock_account_expires() {
+        let dir = tempfile::tempdir().unwrap();
+        let (address, personal, eth) = apis(dir.path());
+        let message = Bytes::from_static(b"hello");
+
+        assert!(personal.unlock_account(address, "wrong".to_string(), None).await.is_err());
+        let err = personal
+            .unlock_account(address, "password".to_string(), Some(u64::MAX))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), jsonrpsee::types::error::INVALID_PARAMS_CODE);
+        assert!(matches!(
+            eth.sign(address, message.clone()).await,
+            Err(EthApiError::Signing(SignError::AccountLocked))
+        ));
+
+        assert!(personal.unlock_account(address, "password".to_string(), Some(1)).await.unwrap());
+        let signature = eth.sign(address, message.clone()).await.unwrap();
+        let expected =
+            personal.sign(message.clone(), address, "password".to_string()).await.unwrap();
+        assert_eq!(signature, expected);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(matches!(
+            eth.sign(address, message.clone()).await,
+            Err(EthApiError::Signing(SignError::AccountLocked))
+        ));
+
+        // a duration of zero keeps the account unlocked until it is locked
+        assert!(personal.unlock_account(address, "password".to_string(), Some(0)).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(eth.sign(address, message.clone()).await.is_ok());
+        assert!(personal.lock_account(address).await.unwrap());
+        assert!(eth.sign(address, message).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn sign_transaction_with_unlocked_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let (address, personal, eth) = apis(dir.path());
+
+        let request = TypedTransactionRequest::Legacy(LegacyTransactionRequest {
+            nonce: U64::ZERO,
+            gas_price: U128::from(1),
+            gas_limit: U256::from(21_000),
+            kind: TransactionKind::Call(Address::ZERO),
+            value: U256::from(1),
+            input: Bytes::default(),
+            chain_id: Some(1),
+        });
+        assert!(eth.sign_request(&address, request.clone()).is_err());
+
+        personal.unlock_account(address, "password".to_string(), None).await.unwrap();
+        let transaction = eth.sign_request(&address, request).unwrap();
+        assert_eq!(transaction.recover_signer(), Some(address));
+    }
+
+    #[tokio::test]
+    async fn refuses_insecure_unlock() {
+        let module = PersonalApi::insecure_unlock_refused();
+        let params = jsonrpsee::rpc_params![Address::ZERO, "password"];
+        let res = module.call::<_, bool>(PersonalApi::UNLOCK_ACCOUNT_METHOD, params).await;
+        assert!(res.is_err());
+    }
+}
@@ -14,7 +14,9 @@ use reth_primitives::{
     revm::env::tx_env_with_recovered, revm_primitives::db::DatabaseCommit, BlockId,
     BlockNumberOrTag, Bytes, SealedHeader, B256, U256,
 };
-use reth_provider::{BlockReader, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
+use reth_provider::{
+    AddressAppearanceReader, BlockReader, ChainSpecProvider, EvmEnvProvider, StateProviderFactory,
+};
 use reth_revm::{
     database::StateProviderDatabase,
     tracing::{parity::populate_state_diff, TracingInspector, TracingInspectorConfig},
@@ -23,12 +25,18 @@ use reth_rpc_api::TraceApiServer;
 use reth_rpc_types::{
     state::StateOverride,
     trace::{filter::TraceFilter, parity::*, tracerequest::TraceCallRequest},
-    BlockError, BlockOverrides, CallRequest, Index,
+    BlockOverrides, CallRequest, Index,
 };
 use revm::{db::CacheDB, primitives::Env};
 use std::{collections::HashSet, sync::Arc};
 use tokio::sync::{AcquireError, OwnedSemaphorePermit};
 
+/// The maximum number of blocks `trace_filter` traces if the request can't be paginated.
+const MAX_TRACE_FILTER_BLOCKS: u64 = 100;
+
+/// The number of blocks `trace_filter` traces concurrently.
+const TRACE_FILTER_BATCH_SIZE: usize = 10;
+
 /// `trace` API implementation.
 ///
 /// This type provides the functionality for handling `trace` related requests.
@@ -62,7 +70,12 @@ impl<Provider, Eth> TraceApi<Provider, Eth> {
 
 impl<Provider, Eth> TraceApi<Provider, Eth>
 where
-    Provider: BlockReader + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
+    Provider: BlockReader
+        + AddressAppearanceReader
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Executes the given call and returns a number of possible traces for it.
@@ -233,83 +246,88 @@ where
 
     /// Returns all transaction traces that match the given filter.
     ///
-    /// This is similar to [Self::trace_block] but only returns traces for transactions that match
-    /// the filter.
+    /// This is similar to [Self::trace_block] but only returns traces that match the filter.
+    ///
+    /// If the filter contains addresses and the address appearance index is maintained, only the
+    /// indexed blocks in which these addresses appear, and the blocks that aren't indexed, are
+    /// traced. Otherwise the range is limited to 100 blocks.
     pub async fn trace_filter(
         &self,
         filter: TraceFilter,
     ) -> EthResult<Vec<LocalizedTransactionTrace>> {
         let matcher = filter.matcher();
-        let TraceFilter { from_block, to_block, after: _after, count: _count, .. } = filter;
+        let TraceFilter { from_block, to_block, after, count, .. } = filter;
         let start = from_block.unwrap_or(0);
         let end = if let Some(to_block) = to_block {
             to_block
         } else {
             self.provider().best_block_number()?
         };
-
-        // ensure that the range is not too large, since we need to fetch all blocks in the range
-        let distance = end.saturating_sub(start);
-        if distance > 100 {
-            return Err(EthApiError::InvalidParams(
-                "Block range too large; currently limited to 100 blocks".to_string(),
-            ))
+        if start > end {
+            return Err(EthApiError::InvalidBlockRange)
         }
 
-        // fetch all blocks in that range
-        let blocks = self.provider().block_range(start..=end)?;
-
-        // find relevant blocks to trace
-        let mut target_blocks = Vec::new();
-        for block in blocks {
-            let mut transaction_indices = HashSet::new();
-            let mut highest_matching_index = 0;
-            for (tx_idx, tx) in block.body.iter().enumerate() {
-                let from = tx.recover_signer().ok_or(BlockError::InvalidSignature)?;
-                let to = tx.to();
-                if matcher.matches(from, to) {
-                    let idx = tx_idx as u64;
-                    transaction_indices.insert(idx);
-                    highest_matching_index = idx;
+        let addresses = matcher.addresses().copied().collect::<Vec<_>>();
+        let candidates = if addresses.is_empty() {
+            None
+        } else {
+            self.provider().address_appearance_candidates(&addresses, start..=end)?
+        };
+        let blocks = match candidates {
+            Some(blocks) => {
+                // without pagination all candidate blocks need to be traced
+                if count.is_none() && blocks.len() as u64 > MAX_TRACE_FILTER_BLOCKS {
+                    return Err(EthApiError::InvalidParams(format!(
+                        "Too many matching blocks; use `count` or limit to {MAX_TRACE_FILTER_BLOCKS} blocks"
+                    )))
                 }
+                blocks.into_iter().collect::<Vec<_>>()
             }
-            if !transaction_indices.is_empty() {
-                target_blocks.push((block.number, transaction_indices, highest_matching_index));
+            None => {
+                // ensure that the range is not too large, since we need to trace all blocks in
+                // the range
+                if end - start > MAX_TRACE_FILTER_BLOCKS {
+                    return Err(EthApiError::InvalidParams(format!(
+                        "Block range too large; currently limited to {MAX_TRACE_FILTER_BLOCKS} blocks"
+                    )))
+                }
+                (start..=end).collect()
             }
-        }
+        };
 
-        // trace all relevant blocks
-        let mut block_traces = Vec::with_capacity(target_blocks.len());
-        for (num, indices, highest_idx) in target_blocks {
-            let traces = self.inner.eth_api.trace_block_until(
-                num.into(),
-                Some(highest_idx),
-                TracingInspectorConfig::default_parity(),
-                move |tx_info, inspector, res, _, _| {
-                    if let Some(idx) = tx_info.index {
-                        if !indices.contains(&idx) {
-                            // only record traces for relevant transactions
-                            return Ok(None)
-                        }
-                    }
-                    let traces = inspector
-                        .with_transaction_gas_used(res.gas_used())
-                        .into_parity_builder()
-                        .into_localized_transaction_traces(tx_info);
-                    Ok(Some(traces))
-                },
-            );
-            block_traces.push(traces);
-        }
+        let after = after.unwrap_or_default() as usize;
+        let count = count.map_or(usize::MAX, |count| count as usize);
+
+        // trace blocks in batches until enough traces are collected
+        let mut all_traces = Vec::new();
+        for batch in blocks.chunks(TRACE_FILTER_BATCH_SIZE) {
+            let mut block_traces = Vec::with_capacity(batch.len());
+            for num in batch {
+                let matcher = matcher.clone();
+                let traces = self.inner.eth_api.trace_block_with(
+                    (*num).into(),
+                    TracingInspectorConfig::default_parity(),
+                    move |tx_info, inspector, res, _, _| {
+                        let mut traces = inspector
+                            .with_transaction_gas_used(res.gas_used())
+                            .into_parity_builder()
+                            .into_localized_transaction_traces(tx_info);
+                        traces.retain(|trace| matcher.matches_trace(&trace.trace));
+                        Ok(traces)
+                    },
+                );
+                block_traces.push(traces);
+            }
+
+            let block_traces = futures::future::try_join_all(block_traces).await?;
+            all_traces.extend(block_traces.into_iter().flatten().flatten().flatten());
 
-        let block_traces = futures::future::try_join_all(block_traces).await?;
-        let all_traces = block_traces
-            .into_iter()
-            .flatten()
-            .flat_map(|traces| traces.into_iter().flatten().flat_map(|traces| traces.into_iter()))
-            .collect();
+            if all_traces.len() >= after.saturating_add(count) {
+                break
+            }
+        }
 
-        Ok(all_traces)
+        Ok(all_traces.into_iter().skip(after).take(count).collect())
     }
 
     /// Returns all traces for the given transaction hash
@@ -428,7 +446,12 @@ where
 #[async_trait]
 impl<Provider, Eth> TraceApiServer for TraceApi<Provider, Eth>
 where
-    Provider: BlockReader + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
+    Provider: BlockReader
+        + AddressAppearanceReader
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Executes the given call and returns a number of possible traces for it.
@@ -503,7 +526,9 @@ where
     /// This is similar to `eth_getLogs` but for traces.
     ///
     /// # Limitations
-    /// This currently requires block filter fields, since reth does not have address indices yet.
+    /// Without the address appearance index, the range is limited to 100 blocks. The index records
+    /// every account accessed by a block, so calls that neither change state nor emit logs are
+    /// found as well.
     async fn trace_filter(&self, filter: TraceFilter) -> Result<Vec<LocalizedTransactionTrace>> {
         Ok(TraceApi::trace_filter(self, filter).await?)
     }
@@ -0,0 +1,732 @@
+//! `flashbots` namespace handler for validating the block submissions of builders.
+
+use crate::{
+    eth::error::EthApiError,
+    result::{internal_rpc_err, invalid_params_rpc_err},
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use reth_consensus_common::validation::{
+    validate_block_standalone, validate_header_regarding_parent, validate_header_standalone,
+};
+use reth_interfaces::{
+    consensus::ConsensusError,
+    executor::{BlockExecutionError, BlockValidationError},
+    provider::ProviderError,
+};
+use reth_primitives::{
+    Address, BlockWithSenders, GotExpected, GotExpectedBoxed, SealedBlock, B256, U256,
+};
+use reth_provider::{
+    BlockExecutor, BundleStateWithReceipts, ChainSpecProvider, HeaderProvider, StateProviderFactory,
+};
+use reth_revm::{database::StateProviderDatabase, processor::EVMProcessor};
+use reth_rpc_api::BlockSubmissionValidationApiServer;
+use reth_rpc_types::{
+    engine::PayloadError,
+    relay::{
+        error::ValidateBuilderSubmissionEqualityError, BidTrace, BuilderBlockValidationRequest,
+        BuilderBlockValidationRequestV2,
+    },
+    ExecutionPayload,
+};
+use reth_rpc_types_compat::engine::payload::try_into_sealed_block;
+use reth_tasks::TaskSpawner;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, future::Future, sync::Arc};
+use tokio::sync::oneshot;
+
+/// The lowest gas limit a block may have.
+const MINIMUM_GAS_LIMIT: u64 = 5000;
+
+/// Settings for the [ValidationApi].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationApiConfig {
+    /// Blocks that touch any of these addresses are rejected.
+    pub blacklist: HashSet<Address>,
+}
+
+/// `flashbots` API implementation.
+///
+/// This type provides the functionality a relay needs to validate the blocks submitted by builders
+/// before it offers them to proposers.
+pub struct ValidationApi<Provider> {
+    inner: Arc<ValidationApiInner<Provider>>,
+}
+
+// === impl ValidationApi ===
+
+impl<Provider> ValidationApi<Provider> {
+    /// Create a new instance of the [ValidationApi]
+    pub fn new(
+        provider: Provider,
+        config: ValidationApiConfig,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let inner = Arc::new(ValidationApiInner { provider, config, task_spawner });
+        Self { inner }
+    }
+
+    /// Returns the configured blacklist.
+    pub fn blacklist(&self) -> &HashSet<Address> {
+        &self.inner.config.blacklist
+    }
+}
+
+impl<Provider> ValidationApi<Provider>
+where
+    Provider: HeaderProvider + StateProviderFactory + ChainSpecProvider + 'static,
+{
+    /// Executes the future on a new blocking task.
+    async fn on_blocking_task<C, F, R>(&self, c: C) -> Result<R, ValidationApiError>
+    where
+        C: FnOnce(Self) -> F,
+        F: Future<Output = Result<R, ValidationApiError>> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let this = self.clone();
+        let f = c(this);
+        self.inner.task_spawner.spawn_blocking(Box::pin(async move {
+            let res = f.await;
+            let _ = tx.send(res);
+        }));
+        rx.await.map_err(|_| EthApiError::InternalEthError)?
+    }
+
+    /// Validates a `flashbots_validateBuilderSubmissionV1` request.
+    pub async fn validate_builder_submission_v1(
+        &self,
+        request: BuilderBlockValidationRequest,
+    ) -> Result<(), ValidationApiError> {
+        let BuilderBlockValidationRequest { request, registered_gas_limit } = request;
+        self.on_blocking_task(|this| async move {
+            this.validate_submission(
+                request.message,
+                request.execution_payload,
+                registered_gas_limit,
+                None,
+            )
+        })
+        .await
+    }
+
+    /// Validates a `flashbots_validateBuilderSubmissionV2` request.
+    pub async fn validate_builder_submission_v2(
+        &self,
+        request: BuilderBlockValidationRequestV2,
+    ) -> Result<(), ValidationApiError> {
+        let BuilderBlockValidationRequestV2 { request, registered_gas_limit, withdrawals_root } =
+            request;
+        self.on_blocking_task(|this| async move {
+            this.validate_submission(
+                request.message,
+                request.execution_payload,
+                registered_gas_limit,
+                Some(withdrawals_root),
+            )
+        })
+        .await
+    }
+
+    /// Validates the submitted block against the bid, then executes it on top of its parent and
+    /// checks the resulting state root and the payment to the proposer.
+    fn validate_submission(
+        &self,
+        message: BidTrace,
+        payload: ExecutionPayload,
+        registered_gas_limit: u64,
+        withdrawals_root: Option<B256>,
+    ) -> Result<(), ValidationApiError> {
+        let block = try_into_sealed_block(payload, None)?;
+        validate_message_against_block(&message, &block)?;
+
+        if let Some(expected) = withdrawals_root {
+            let got = block.withdrawals_root.unwrap_or_default();
+            if got != expected {
+                return Err(ValidationApiError::WithdrawalsRootMismatch(
+                    GotExpected { got, expected }.into(),
+                ))
+            }
+        }
+
+        let chain_spec = self.inner.provider.chain_spec();
+        let parent = self
+            .inner
+            .provider
+            .header(&block.parent_hash)?
+            .ok_or(ValidationApiError::MissingParentBlock(block.parent_hash))?
+            .seal(block.parent_hash);
+
+        validate_gas_limit(parent.gas_limit, registered_gas_limit, block.gas_limit)?;
+        validate_header_standalone(&block.header, &chain_spec)?;
+        validate_header_regarding_parent(&parent, &block.header, &chain_spec)?;
+        validate_block_standalone(&block, &chain_spec)?;
+
+        let block = block
+            .unseal()
+            .with_recovered_senders()
+            .ok_or(BlockExecutionError::Validation(BlockValidationError::SenderRecoveryError))?;
+        for (tx, sender) in block.body.iter().zip(block.senders.iter()) {
+            self.ensure_not_blacklisted(*sender)?;
+            if let Some(to) = tx.to() {
+                self.ensure_not_blacklisted(to)?;
+            }
+        }
+
+        let total_difficulty = self
+            .inner
+            .provider
+            .header_td(&parent.hash())?
+            .ok_or(ValidationApiError::MissingParentBlock(parent.hash()))?
+            .saturating_add(block.difficulty);
+
+        let state = self.inner.provider.state_by_block_hash(parent.hash())?;
+        let mut executor =
+            EVMProcessor::new_with_db(chain_spec.clone(), StateProviderDatabase::new(state));
+        executor.set_record_accessed_accounts(true);
+        executor.execute_and_verify_receipt(&block, total_difficulty)?;
+
+        // the transactions can reach blacklisted accounts through the contracts they call, even if
+        // they only read them
+        for address in executor.take_accessed_accounts().into_keys() {
+            self.ensure_not_blacklisted(address)?;
+        }
+        // withdrawals and fees credit accounts outside of the transactions
+        let post_state = executor.take_output_state();
+        for (address, _) in post_state.accounts_iter() {
+            self.ensure_not_blacklisted(address)?;
+        }
+
+        let state_root =
+            self.inner.provider.state_by_block_hash(parent.hash())?.state_root(&post_state)?;
+        if state_root != block.state_root {
+            return Err(ValidationApiError::StateRootMismatch(
+                GotExpected { got: state_root, expected: block.state_root }.into(),
+            ))
+        }
+
+        validate_proposer_payment(&message, &block, &post_state)
+    }
+
+    fn ensure_not_blacklisted(&self, address: Address) -> Result<(), ValidationApiError> {
+        if self.inner.config.blacklist.contains(&address) {
+            return Err(ValidationApiError::Blacklist(address))
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Provider> BlockSubmissionValidationApiServer for ValidationApi<Provider>
+where
+    Provider: HeaderProvider + StateProviderFactory + ChainSpecProvider + 'static,
+{
+    /// Handler for `flashbots_validateBuilderSubmissionV1`
+    async fn validate_builder_submission_v1(
+        &self,
+        request: BuilderBlockValidationRequest,
+    ) -> RpcResult<()> {
+        Ok(ValidationApi::validate_builder_submission_v1(self, request).await?)
+    }
+
+    /// Handler for `flashbots_validateBuilderSubmissionV2`
+    async fn validate_builder_submission_v2(
+        &self,
+        request: BuilderBlockValidationRequestV2,
+    ) -> RpcResult<()> {
+        Ok(ValidationApi::validate_builder_submission_v2(self, request).await?)
+    }
+}
+
+impl<Provider> std::fmt::Debug for ValidationApi<Provider> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidationApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider> Clone for ValidationApi<Provider> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+struct ValidationApiInner<Provider> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// Validation settings.
+    config: ValidationApiConfig,
+    /// The type that can spawn tasks which would otherwise block.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+/// Checks that the bid describes the submitted block.
+fn validate_message_against_block(
+    message: &BidTrace,
+    block: &SealedBlock,
+) -> Result<(), ValidateBuilderSubmissionEqualityError> {
+    if message.parent_hash != block.parent_hash {
+        return Err(ValidateBuilderSubmissionEqualityError::IncorrectParentHash {
+            expected: message.parent_hash,
+            actual: block.parent_hash,
+        })
+    }
+    if message.block_hash != block.hash() {
+        return Err(ValidateBuilderSubmissionEqualityError::IncorrectBlockHash {
+            expected: message.block_hash,
+            actual: block.hash(),
+        })
+    }
+    if message.gas_limit != block.gas_limit {
+        return Err(ValidateBuilderSubmissionEqualityError::IncorrectGasLimit {
+            expected: message.gas_limit,
+            actual: block.gas_limit,
+        })
+    }
+    if message.gas_used != block.gas_used {
+        return Err(ValidateBuilderSubmissionEqualityError::IncorrectGasUsed {
+            expected: message.gas_used,
+            actual: block.gas_used,
+        })
+    }
+    Ok(())
+}
+
+/// Returns the gas limit the block should have: the parent's gas limit moved towards the gas limit
+/// the proposer registered with, by at most the step allowed by the consensus rules.
+fn expected_gas_limit(parent_gas_limit: u64, registered_gas_limit: u64) -> u64 {
+    let max_delta = (parent_gas_limit / 1024).saturating_sub(1);
+    let desired = registered_gas_limit.max(MINIMUM_GAS_LIMIT);
+    if parent_gas_limit < desired {
+        parent_gas_limit.saturating_add(max_delta).min(desired)
+    } else {
+        parent_gas_limit.saturating_sub(max_delta).max(desired)
+    }
+}
+
+/// Checks the gas limit of the block against the gas limit the proposer registered with.
+fn validate_gas_limit(
+    parent_gas_limit: u64,
+    registered_gas_limit: u64,
+    gas_limit: u64,
+) -> Result<(), ValidationApiError> {
+    let expected = expected_gas_limit(parent_gas_limit, registered_gas_limit);
+    if gas_limit != expected {
+        return Err(ValidationApiError::GasLimitMismatch(GotExpected { got: gas_limit, expected }))
+    }
+    Ok(())
+}
+
+/// Checks that the proposer receives the value of the bid.
+///
+/// If the proposer's fee recipient is the block's beneficiary, its balance must increase by at
+/// least the bid value. Otherwise the last transaction of the block must be a plain transfer of the
+/// bid value from the builder to the fee recipient.
+fn validate_proposer_payment(
+    message: &BidTrace,
+    block: &BlockWithSenders,
+    post_state: &BundleStateWithReceipts,
+) -> Result<(), ValidationApiError> {
+    let fee_recipient = message.proposer_fee_recipient;
+    let balance_increase = post_state
+        .state()
+        .account(&fee_recipient)
+        .map(|account| {
+            let before = account.original_info.as_ref().map(|info| info.balance);
+            let after = account.info.as_ref().map(|info| info.balance);
+            after.unwrap_or_default().saturating_sub(before.unwrap_or_default())
+        })
+        .unwrap_or_default();
+
+    if block.beneficiary != fee_recipient {
+        let payment = block.body.last().ok_or(ProposerPaymentError::MissingPaymentTransaction)?;
+        if payment.to() != Some(fee_recipient) {
+            return Err(ProposerPaymentError::IncorrectRecipient(payment.to()).into())
+        }
+        if !payment.input().is_empty() {
+            return Err(ProposerPaymentError::NotATransfer.into())
+        }
+        let value = U256::from(payment.value());
+        if value != message.value {
+            return Err(ProposerPaymentError::IncorrectValue(GotExpected {
+                got: value,
+                expected: message.value,
+            })
+            .into())
+        }
+        let signer = block.senders.last().copied().unwrap_or_default();
+        if signer != block.beneficiary {
+            return Err(ProposerPaymentError::IncorrectSender(signer).into())
+        }
+    }
+
+    if balance_increase < message.value {
+        return Err(ProposerPaymentError::InsufficientPayment(GotExpected {
+            got: balance_increase,
+            expected: message.value,
+        })
+        .into())
+    }
+    Ok(())
+}
+
+/// Errors of the proposer payment check of the [ValidationApi].
+#[derive(Debug, thiserror::Error)]
+pub enum ProposerPaymentError {
+    /// Thrown if the block has no transaction that could pay the proposer.
+    #[error("missing proposer payment transaction")]
+    MissingPaymentTransaction,
+    /// Thrown if the payment transaction is not sent to the proposer's fee recipient.
+    #[error("proposer payment sent to {0:?}")]
+    IncorrectRecipient(Option<Address>),
+    /// Thrown if the payment transaction is not sent by the block's beneficiary.
+    #[error("proposer payment sent by {0}")]
+    IncorrectSender(Address),
+    /// Thrown if the payment transaction is not a plain value transfer.
+    #[error("proposer payment is not a plain transfer")]
+    NotATransfer,
+    /// Thrown if the payment transaction transfers a value other than the bid value.
+    #[error("proposer payment value {}, expected bid value {}", .0.got, .0.expected)]
+    IncorrectValue(GotExpected<U256>),
+    /// Thrown if the balance of the fee recipient increases by less than the bid value.
+    #[error("proposer balance increased by {}, expected at least {}", .0.got, .0.expected)]
+    InsufficientPayment(GotExpected<U256>),
+}
+
+/// [ValidationApi] specific errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationApiError {
+    /// Thrown if the payload can not be converted into a block.
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+    /// Thrown if the bid does not describe the submitted block.
+    #[error(transparent)]
+    Equality(#[from] ValidateBuilderSubmissionEqualityError),
+    /// Thrown if the withdrawals root does not match the one of the request.
+    #[error("withdrawals root mismatch: {0}")]
+    WithdrawalsRootMismatch(GotExpectedBoxed<B256>),
+    /// Thrown if the gas limit does not move towards the registered gas limit.
+    #[error("incorrect gas limit {}, expected {}", .0.got, .0.expected)]
+    GasLimitMismatch(GotExpected<u64>),
+    /// Thrown if the parent of the block is unknown.
+    #[error("parent block {0} not found")]
+    MissingParentBlock(B256),
+    /// Thrown if the block violates the consensus rules.
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    /// Thrown if the block fails to execute.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+    /// Thrown if the state root after executing the block does not match the block's state root.
+    #[error("state root mismatch: {0}")]
+    StateRootMismatch(GotExpectedBoxed<B256>),
+    /// Thrown if the block touches a blacklisted address.
+    #[error("blacklisted address {0}")]
+    Blacklist(Address),
+    /// Thrown if the proposer is not paid the bid value.
+    #[error(transparent)]
+    ProposerPayment(#[from] ProposerPaymentError),
+    /// Thrown if the block or state can not be read.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// Internal errors.
+    #[error(transparent)]
+    Eth(#[from] EthApiError),
+}
+
+impl From<ValidationApiError> for jsonrpsee::types::error::ErrorObject<'static> {
+    fn from(error: ValidationApiError) -> Self {
+        match error {
+            ValidationApiError::Provider(err) => internal_rpc_err(err.to_string()),
+            ValidationApiError::Eth(err) => err.into(),
+            err => invalid_params_rpc_err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::test_utils::generators::{self, sign_tx_with_key_pair};
+    use reth_primitives::{
+        logs_bloom, proofs, public_key_to_address, Block, Bytes, ChainSpecBuilder, Header,
+        ReceiptWithBloom, SealedHeader, Transaction, TransactionKind, TransactionSigned, TxEip1559,
+        EMPTY_OMMER_ROOT_HASH,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_rpc_types_compat::engine::payload::try_block_to_payload;
+    use reth_tasks::TokioTaskExecutor;
+    use secp256k1::KeyPair;
+
+    /// The priority fee per gas of the test transactions, paid to the block's beneficiary.
+    const TIP: u128 = 1;
+
+    /// Returns a provider that knows a post-merge parent block and funds the given accounts.
+    fn provider_with_parent(funded: &[Address]) -> (MockEthProvider, SealedHeader) {
+        let provider = MockEthProvider {
+            chain_spec: Arc::new(ChainSpecBuilder::mainnet().paris_activated().build()),
+            ..Default::default()
+        };
+        let parent = Header {
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            base_fee_per_gas: Some(7),
+            ..Default::default()
+        }
+        .seal_slow();
+        provider.add_header(parent.hash(), parent.header.clone());
+        for address in funded {
+            provider.add_account(*address, ExtendedAccount::new(0, U256::from(10u64.pow(18))));
+        }
+        (provider, parent)
+    }
+
+    fn transfer(key_pair: KeyPair, nonce: u64, to: Address, value: U256) -> TransactionSigned {
+        sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit: 100_000,
+                max_fee_per_gas: 10,
+                max_priority_fee_per_gas: TIP,
+                to: TransactionKind::Call(to),
+                value: value.into(),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// Executes the transactions on top of the parent and seals them into a block.
+    fn build_block(
+        provider: &MockEthProvider,
+        parent: &SealedHeader,
+        beneficiary: Address,
+        body: Vec<TransactionSigned>,
+        state_root: B256,
+    ) -> SealedBlock {
+        let chain_spec = provider.chain_spec();
+        let timestamp = parent.timestamp + 12;
+        let header = Header {
+            parent_hash: parent.hash(),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary,
+            state_root,
+            transactions_root: proofs::calculate_transaction_root(&body),
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp,
+            base_fee_per_gas: parent.next_block_base_fee(chain_spec.base_fee_params(timestamp)),
+            ..Default::default()
+        };
+        let mut block = Block { header, body, ommers: Vec::new(), withdrawals: None }
+            .with_recovered_senders()
+            .unwrap();
+
+        let state = provider.state_by_block_hash(parent.hash()).unwrap();
+        let mut executor =
+            EVMProcessor::new_with_db(chain_spec.clone(), StateProviderDatabase::new(state));
+        let (receipts, gas_used) = executor.execute_transactions(&block, U256::ZERO).unwrap();
+        block.header.gas_used = gas_used;
+        block.header.logs_bloom = logs_bloom(receipts.iter().flat_map(|receipt| &receipt.logs));
+        let receipts = receipts.into_iter().map(ReceiptWithBloom::from).collect::<Vec<_>>();
+        block.header.receipts_root = proofs::calculate_receipt_root(
+            &receipts,
+            #[cfg(feature = "optimism")]
+            &chain_spec,
+            #[cfg(feature = "optimism")]
+            timestamp,
+        );
+        block.block.seal_slow()
+    }
+
+    fn bid(block: &SealedBlock, proposer_fee_recipient: Address, value: U256) -> BidTrace {
+        BidTrace {
+            parent_hash: block.parent_hash,
+            block_hash: block.hash(),
+            gas_limit: block.gas_limit,
+            gas_used: block.gas_used,
+            proposer_fee_recipient,
+            value,
+            ..Default::default()
+        }
+    }
+
+    fn validate(
+        provider: MockEthProvider,
+        blacklist: HashSet<Address>,
+        message: BidTrace,
+        block: SealedBlock,
+    ) -> Result<(), ValidationApiError> {
+        let api = ValidationApi::new(
+            provider,
+            ValidationApiConfig { blacklist },
+            Box::<TokioTaskExecutor>::default(),
+        );
+        let registered_gas_limit = block.gas_limit;
+        api.validate_submission(message, try_block_to_payload(block), registered_gas_limit, None)
+    }
+
+    #[test]
+    fn beneficiary_is_fee_recipient() {
+        let mut rng = generators::rng();
+        let keys = generators::generate_keys(&mut rng, 2);
+        let (user, proposer) = (keys[0], keys[1]);
+        let [user_address, proposer_address] =
+            [user, proposer].map(|key_pair| public_key_to_address(key_pair.public_key()));
+        let (provider, parent) = provider_with_parent(&[user_address]);
+
+        let block = build_block(
+            &provider,
+            &parent,
+            proposer_address,
+            vec![transfer(user, 0, Address::random(), U256::from(1))],
+            B256::ZERO,
+        );
+        let tips = U256::from(block.gas_used) * U256::from(TIP);
+
+        validate(
+            provider.clone(),
+            HashSet::new(),
+            bid(&block, proposer_address, tips),
+            block.clone(),
+        )
+        .unwrap();
+        assert!(matches!(
+            validate(
+                provider,
+                HashSet::new(),
+                bid(&block, proposer_address, tips + U256::from(1)),
+                block
+            ),
+            Err(ValidationApiError::ProposerPayment(ProposerPaymentError::InsufficientPayment(_)))
+        ));
+    }
+
+    #[test]
+    fn payment_transaction() {
+        let mut rng = generators::rng();
+        let keys = generators::generate_keys(&mut rng, 3);
+        let (user, builder, proposer) = (keys[0], keys[1], keys[2]);
+        let [user_address, builder_address, proposer_address] =
+            [user, builder, proposer].map(|key_pair| public_key_to_address(key_pair.public_key()));
+        let (provider, parent) = provider_with_parent(&[user_address, builder_address]);
+        let value = U256::from(10u64.pow(15));
+
+        let block = build_block(
+            &provider,
+            &parent,
+            builder_address,
+            vec![
+                transfer(user, 0, Address::random(), U256::from(1)),
+                transfer(builder, 0, proposer_address, value),
+            ],
+            B256::ZERO,
+        );
+        validate(
+            provider.clone(),
+            HashSet::new(),
+            bid(&block, proposer_address, value),
+            block.clone(),
+        )
+        .unwrap();
+        assert!(matches!(
+            validate(
+                provider.clone(),
+                HashSet::new(),
+                bid(&block, proposer_address, value + U256::from(1)),
+                block
+            ),
+            Err(ValidationApiError::ProposerPayment(ProposerPaymentError::IncorrectValue(_)))
+        ));
+
+        // the payment must be sent by the beneficiary
+        let block = build_block(
+            &provider,
+            &parent,
+            builder_address,
+            vec![transfer(user, 0, proposer_address, value)],
+            B256::ZERO,
+        );
+        assert!(matches!(
+            validate(provider, HashSet::new(), bid(&block, proposer_address, value), block),
+            Err(ValidationApiError::ProposerPayment(ProposerPaymentError::IncorrectSender(sender)))
+                if sender == user_address
+        ));
+    }
+
+    #[test]
+    fn rejects_blacklisted_accounts_read_by_contracts() {
+        let mut rng = generators::rng();
+        let keys = generators::generate_keys(&mut rng, 2);
+        let (user, builder) = (keys[0], keys[1]);
+        let [user_address, builder_address] =
+            [user, builder].map(|key_pair| public_key_to_address(key_pair.public_key()));
+        let (provider, parent) = provider_with_parent(&[user_address, builder_address]);
+
+        // a contract that only reads the balance of the blacklisted account: PUSH20, BALANCE, POP
+        let (blacklisted, contract, proposer) =
+            (Address::random(), Address::random(), Address::random());
+        let code = [&[0x73][..], blacklisted.as_slice(), &[0x31, 0x50, 0x00]].concat();
+        provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from(code)),
+        );
+
+        let block = build_block(
+            &provider,
+            &parent,
+            builder_address,
+            vec![
+                transfer(user, 0, contract, U256::ZERO),
+                transfer(builder, 0, proposer, U256::ZERO),
+            ],
+            B256::ZERO,
+        );
+        let message = bid(&block, proposer, U256::ZERO);
+        validate(provider.clone(), HashSet::new(), message.clone(), block.clone()).unwrap();
+        assert!(matches!(
+            validate(provider, HashSet::from([blacklisted]), message, block),
+            Err(ValidationApiError::Blacklist(address)) if address == blacklisted
+        ));
+    }
+
+    #[test]
+    fn rejects_state_root_mismatch() {
+        let mut rng = generators::rng();
+        let user = generators::generate_keys(&mut rng, 1)[0];
+        let user_address = public_key_to_address(user.public_key());
+        let (provider, parent) = provider_with_parent(&[user_address]);
+
+        let block = build_block(
+            &provider,
+            &parent,
+            user_address,
+            vec![transfer(user, 0, Address::random(), U256::from(1))],
+            B256::with_last_byte(1),
+        );
+        assert!(matches!(
+            validate(provider, HashSet::new(), bid(&block, user_address, U256::ZERO), block),
+            Err(ValidationApiError::StateRootMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn gas_limit_moves_towards_registered() {
+        let parent = 30_000_000;
+        let max_delta = parent / 1024 - 1;
+
+        assert_eq!(expected_gas_limit(parent, parent), parent);
+        assert_eq!(expected_gas_limit(parent, 36_000_000), parent + max_delta);
+        assert_eq!(expected_gas_limit(parent, 20_000_000), parent - max_delta);
+        assert_eq!(expected_gas_limit(parent, parent + 10), parent + 10);
+        assert_eq!(expected_gas_limit(parent, parent - 10), parent - 10);
+        assert_eq!(expected_gas_limit(MINIMUM_GAS_LIMIT, 0), MINIMUM_GAS_LIMIT);
+
+        assert!(validate_gas_limit(parent, 36_000_000, parent + max_delta).is_ok());
+        assert!(matches!(
+            validate_gas_limit(parent, 36_000_000, parent),
+            Err(ValidationApiError::GasLimitMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::segments::{prepare_jar, Segment, TableColumns};
+use reth_db::{
+    codecs::CompactU64,
+    database::Database,
+    models::BlockNumberAddress,
+    table::{Compress, Encode},
+    tables,
+};
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    snapshot::{Compression, Filters, SegmentConfig},
+    BlockNumber, SnapshotSegment,
+};
+use reth_provider::DatabaseProviderRO;
+use std::{ops::RangeInclusive, path::Path};
+
+/// Snapshot segment responsible for [SnapshotSegment::AccountChangeSets] part of data.
+///
+/// Every row is an entry of the `AccountChangeSet` table alongside its block number, in the same
+/// order as in the database, so rows are sorted by block number and address.
+#[derive(Debug)]
+pub struct AccountChangeSets {
+    config: SegmentConfig,
+}
+
+impl AccountChangeSets {
+    /// Creates new instance of [AccountChangeSets] snapshot segment.
+    pub fn new(compression: Compression, filters: Filters) -> Self {
+        Self { config: SegmentConfig { compression, filters } }
+    }
+}
+
+impl Default for AccountChangeSets {
+    fn default() -> Self {
+        Self { config: SnapshotSegment::AccountChangeSets.config() }
+    }
+}
+
+impl Segment for AccountChangeSets {
+    fn segment(&self) -> SnapshotSegment {
+        SnapshotSegment::AccountChangeSets
+    }
+
+    fn snapshot<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: impl AsRef<Path>,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let columns = TableColumns::<tables::AccountChangeSet, 2>::new(
+            block_range.clone(),
+            [
+                |_, account| account.clone().compress(),
+                |block_number, _| CompactU64(*block_number).compress(),
+            ],
+            // Keys of the filters & PHF
+            self.config.filters.has_filters().then_some(|block_number, account| {
+                BlockNumberAddress((*block_number, account.address)).encode().to_vec()
+            }),
+        );
+        let row_count = columns.row_count(provider)?;
+
+        let mut jar = prepare_jar::<DB, 2>(
+            provider,
+            directory,
+            self.segment(),
+            self.config,
+            block_range,
+            row_count,
+            || columns.dataset(provider),
+        )?;
+        columns.freeze(provider, &mut jar, row_count)
+    }
+}
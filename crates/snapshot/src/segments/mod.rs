@@ -9,6 +9,12 @@ pub use headers::Headers;
 mod receipts;
 pub use receipts::Receipts;
 
+mod account_changesets;
+pub use account_changesets::AccountChangeSets;
+
+mod storage_changesets;
+pub use storage_changesets::StorageChangeSets;
+
 use reth_db::{
     cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, RawKey, RawTable,
 };
@@ -21,7 +27,7 @@ use reth_primitives::{
     BlockNumber, SnapshotSegment,
 };
 use reth_provider::{DatabaseProviderRO, TransactionsProviderExt};
-use std::{ops::RangeInclusive, path::Path};
+use std::{error::Error as StdError, ops::RangeInclusive, path::Path};
 
 pub(crate) type Rows<const COLUMNS: usize> = [Vec<Vec<u8>>; COLUMNS];
 
@@ -55,6 +61,100 @@ pub trait Segment: Default {
     }
 }
 
+/// Encodes a row of `T` into a column value or into its filter and PHF key.
+pub(crate) type RowEncoder<T> = fn(&<T as Table>::Key, &<T as Table>::Value) -> Vec<u8>;
+
+/// Rows of a table range split into the columns of a snapshot, alongside the keys of the rows
+/// for the filters and PHF.
+///
+/// Every column is streamed from its own cursor into the snapshot file, so the rows are never
+/// held in memory.
+pub(crate) struct TableColumns<T: Table, const COLUMNS: usize> {
+    /// Range of the table rows.
+    range: RangeInclusive<T::Key>,
+    /// Encoder of every column.
+    columns: [RowEncoder<T>; COLUMNS],
+    /// Encoder of the row keys, if the snapshot has filters.
+    key: Option<RowEncoder<T>>,
+}
+
+impl<T: Table, const COLUMNS: usize> TableColumns<T, COLUMNS> {
+    /// Creates the columns of the rows of `T` within `range`, encoded with `columns` and, if
+    /// given, indexed by `key`.
+    pub(crate) fn new(
+        range: RangeInclusive<T::Key>,
+        columns: [RowEncoder<T>; COLUMNS],
+        key: Option<RowEncoder<T>>,
+    ) -> Self {
+        Self { range, columns, key }
+    }
+
+    /// Returns the number of rows.
+    pub(crate) fn row_count<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+    ) -> ProviderResult<usize> {
+        let mut row_count = 0;
+        for entry in provider.tx_ref().cursor_read::<T>()?.walk_range(self.range.clone())? {
+            entry?;
+            row_count += 1;
+        }
+        Ok(row_count)
+    }
+
+    /// Returns the first rows (at most 1000) of every column to train the zstd dictionaries.
+    pub(crate) fn dataset<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+    ) -> ProviderResult<Rows<COLUMNS>> {
+        let mut dataset: Rows<COLUMNS> = std::array::from_fn(|_| Vec::new());
+        for entry in
+            provider.tx_ref().cursor_read::<T>()?.walk_range(self.range.clone())?.take(1000)
+        {
+            let (key, value) = entry?;
+            for (column, encode) in dataset.iter_mut().zip(self.columns) {
+                column.push(encode(&key, &value));
+            }
+        }
+        Ok(dataset)
+    }
+
+    /// Writes the columns to the snapshot file, indexing the rows by their keys if the snapshot
+    /// has filters.
+    pub(crate) fn freeze<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        nippy_jar: &mut NippyJar<SegmentHeader>,
+        row_count: usize,
+    ) -> ProviderResult<()> {
+        if let Some(encode) = self.key {
+            let mut cursor = provider.tx_ref().cursor_read::<T>()?;
+            let keys = cursor
+                .walk_range(self.range.clone())?
+                .map(|entry| entry.map(|(key, value)| encode(&key, &value)).map_err(|e| e.into()));
+            nippy_jar.prepare_index(keys, row_count)?;
+        }
+
+        // Creates the cursors for the columns
+        let mut cursors = self
+            .columns
+            .iter()
+            .map(|_| provider.tx_ref().cursor_read::<T>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut columns = Vec::with_capacity(COLUMNS);
+        for (cursor, encode) in cursors.iter_mut().zip(self.columns) {
+            columns.push(cursor.walk_range(self.range.clone())?.map(move |entry| {
+                entry
+                    .map(|(key, value)| encode(&key, &value))
+                    .map_err(|e| Box::new(e) as Box<dyn StdError + Send + Sync>)
+            }));
+        }
+
+        nippy_jar.freeze(columns, row_count as u64)?;
+        Ok(())
+    }
+}
+
 /// Returns a [`NippyJar`] according to the desired configuration. The `directory` parameter
 /// determines the snapshot file's save location.
 pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
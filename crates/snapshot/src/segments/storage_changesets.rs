@@ -0,0 +1,80 @@
+use crate::segments::{prepare_jar, Segment, TableColumns};
+use reth_db::{
+    codecs::CompactU64,
+    database::Database,
+    models::BlockNumberAddress,
+    table::{Compress, Encode},
+    tables,
+};
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    snapshot::{Compression, Filters, SegmentConfig},
+    Address, BlockNumber, SnapshotSegment,
+};
+use reth_provider::DatabaseProviderRO;
+use std::{ops::RangeInclusive, path::Path};
+
+/// Snapshot segment responsible for [SnapshotSegment::StorageChangeSets] part of data.
+///
+/// Every row is an entry of the `StorageChangeSet` table alongside its block number and address,
+/// in the same order as in the database, so rows are sorted by block number, address and storage
+/// key.
+#[derive(Debug)]
+pub struct StorageChangeSets {
+    config: SegmentConfig,
+}
+
+impl StorageChangeSets {
+    /// Creates new instance of [StorageChangeSets] snapshot segment.
+    pub fn new(compression: Compression, filters: Filters) -> Self {
+        Self { config: SegmentConfig { compression, filters } }
+    }
+}
+
+impl Default for StorageChangeSets {
+    fn default() -> Self {
+        Self { config: SnapshotSegment::StorageChangeSets.config() }
+    }
+}
+
+impl Segment for StorageChangeSets {
+    fn segment(&self) -> SnapshotSegment {
+        SnapshotSegment::StorageChangeSets
+    }
+
+    fn snapshot<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: impl AsRef<Path>,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let range = BlockNumberAddress((*block_range.start(), Address::ZERO))..=
+            BlockNumberAddress((*block_range.end(), Address::repeat_byte(0xff)));
+
+        let columns = TableColumns::<tables::StorageChangeSet, 3>::new(
+            range,
+            [
+                |_, entry| entry.compress(),
+                |key, _| CompactU64(key.block_number()).compress(),
+                |key, _| key.address().compress(),
+            ],
+            // Keys of the filters & PHF
+            self.config
+                .filters
+                .has_filters()
+                .then_some(|key, entry| [key.encode().as_slice(), entry.key.as_slice()].concat()),
+        );
+        let row_count = columns.row_count(provider)?;
+
+        let mut jar = prepare_jar::<DB, 3>(
+            provider,
+            directory,
+            self.segment(),
+            self.config,
+            block_range,
+            row_count,
+            || columns.dataset(provider),
+        )?;
+        columns.freeze(provider, &mut jar, row_count)
+    }
+}
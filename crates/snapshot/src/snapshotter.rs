@@ -49,12 +49,18 @@ pub struct SnapshotTargets {
     headers: Option<RangeInclusive<BlockNumber>>,
     receipts: Option<(RangeInclusive<BlockNumber>, RangeInclusive<TxNumber>)>,
     transactions: Option<(RangeInclusive<BlockNumber>, RangeInclusive<TxNumber>)>,
+    account_changesets: Option<RangeInclusive<BlockNumber>>,
+    storage_changesets: Option<RangeInclusive<BlockNumber>>,
 }
 
 impl SnapshotTargets {
     /// Returns `true` if any of the targets are [Some].
     pub fn any(&self) -> bool {
-        self.headers.is_some() || self.receipts.is_some() || self.transactions.is_some()
+        self.headers.is_some() ||
+            self.receipts.is_some() ||
+            self.transactions.is_some() ||
+            self.account_changesets.is_some() ||
+            self.storage_changesets.is_some()
     }
 
     /// Returns `true` if all targets are either [None] or multiple of `block_interval`.
@@ -63,6 +69,8 @@ impl SnapshotTargets {
             self.headers.as_ref(),
             self.receipts.as_ref().map(|(blocks, _)| blocks),
             self.transactions.as_ref().map(|(blocks, _)| blocks),
+            self.account_changesets.as_ref(),
+            self.storage_changesets.as_ref(),
         ]
         .iter()
         .all(|blocks| blocks.map_or(true, |blocks| (blocks.end() + 1) % block_interval == 0))
@@ -75,6 +83,8 @@ impl SnapshotTargets {
             (self.headers.as_ref(), snapshots.headers),
             (self.receipts.as_ref().map(|(blocks, _)| blocks), snapshots.receipts),
             (self.transactions.as_ref().map(|(blocks, _)| blocks), snapshots.transactions),
+            (self.account_changesets.as_ref(), snapshots.account_changesets),
+            (self.storage_changesets.as_ref(), snapshots.storage_changesets),
         ]
         .iter()
         .all(|(target, highest)| {
@@ -144,6 +154,12 @@ impl<DB: Database> Snapshotter<DB> {
         if let Some((block_number, _)) = &targets.transactions {
             self.highest_snapshots.transactions = Some(*block_number.end());
         }
+        if let Some(block_number) = &targets.account_changesets {
+            self.highest_snapshots.account_changesets = Some(*block_number.end());
+        }
+        if let Some(block_number) = &targets.storage_changesets {
+            self.highest_snapshots.storage_changesets = Some(*block_number.end());
+        }
     }
 
     /// Looks into the snapshot directory to find the highest snapshotted block of each segment, and
@@ -190,6 +206,10 @@ impl<DB: Database> Snapshotter<DB> {
 
         self.run_segment::<segments::Headers>(targets.headers.clone())?;
 
+        self.run_segment::<segments::AccountChangeSets>(targets.account_changesets.clone())?;
+
+        self.run_segment::<segments::StorageChangeSets>(targets.storage_changesets.clone())?;
+
         self.update_highest_snapshots_tracker()?;
 
         Ok(targets)
@@ -242,6 +262,14 @@ impl<DB: Database> Snapshotter<DB> {
             self.get_snapshot_target_block_range(to_block_number, self.highest_snapshots.receipts);
         let transactions_block_range = self
             .get_snapshot_target_block_range(to_block_number, self.highest_snapshots.transactions);
+        let account_changesets_block_range = self.get_snapshot_target_block_range(
+            to_block_number,
+            self.highest_snapshots.account_changesets,
+        );
+        let storage_changesets_block_range = self.get_snapshot_target_block_range(
+            to_block_number,
+            self.highest_snapshots.storage_changesets,
+        );
 
         // Calculate transaction ranges to snapshot
         let mut block_to_tx_number_cache = HashMap::default();
@@ -277,6 +305,18 @@ impl<DB: Database> Snapshotter<DB> {
                 .expect("finalized block should be >= last transactions snapshot")
                 .ge(&(self.block_interval as usize))
                 .then_some((transactions_block_range, transactions_tx_range)),
+            account_changesets: account_changesets_block_range
+                .size_hint()
+                .1
+                .expect("finalized block should be >= last account changesets snapshot")
+                .ge(&(self.block_interval as usize))
+                .then_some(account_changesets_block_range),
+            storage_changesets: storage_changesets_block_range
+                .size_hint()
+                .1
+                .expect("finalized block should be >= last storage changesets snapshot")
+                .ge(&(self.block_interval as usize))
+                .then_some(storage_changesets_block_range),
         })
     }
 
@@ -363,7 +403,9 @@ mod tests {
             SnapshotTargets {
                 headers: Some(0..=1),
                 receipts: Some((0..=1, 0..=3)),
-                transactions: Some((0..=1, 0..=3))
+                transactions: Some((0..=1, 0..=3)),
+                account_changesets: Some(0..=1),
+                storage_changesets: Some(0..=1),
             }
         );
         assert!(targets.is_multiple_of_block_interval(snapshotter.block_interval));
@@ -374,7 +416,13 @@ mod tests {
         // Nothing to snapshot, last snapshots state of snapshotter doesn't pass the thresholds
         assert_eq!(
             snapshotter.get_snapshot_targets(2),
-            Ok(SnapshotTargets { headers: None, receipts: None, transactions: None })
+            Ok(SnapshotTargets {
+                headers: None,
+                receipts: None,
+                transactions: None,
+                account_changesets: None,
+                storage_changesets: None,
+            })
         );
 
         // Snapshot targets has data per part up to the passed finalized block number,
@@ -385,7 +433,9 @@ mod tests {
             SnapshotTargets {
                 headers: Some(2..=3),
                 receipts: Some((2..=3, 4..=7)),
-                transactions: Some((2..=3, 4..=7))
+                transactions: Some((2..=3, 4..=7)),
+                account_changesets: Some(2..=3),
+                storage_changesets: Some(2..=3),
             }
         );
         assert!(targets.is_multiple_of_block_interval(snapshotter.block_interval));
@@ -16,7 +16,7 @@ use reth_primitives::{
         StageId,
     },
 };
-use reth_provider::{AccountExtReader, DatabaseProviderRW, HashingWriter};
+use reth_provider::{has_snap_synced_state, AccountExtReader, DatabaseProviderRW, HashingWriter};
 use std::{
     cmp::max,
     fmt::Debug,
@@ -146,8 +146,11 @@ impl<DB: Database> Stage<DB> for AccountHashingStage {
         // if there are more blocks then threshold it is faster to go over Plain state and hash all
         // account otherwise take changesets aggregate the sets and apply hashing to
         // AccountHashing table. Also, if we start from genesis, we need to hash from scratch, as
-        // genesis accounts are not in changeset.
-        if to_block - from_block > self.clean_threshold || from_block == 1 {
+        // genesis accounts are not in changeset. The plain state of a snap synced node is
+        // incomplete, so its hashed state is always updated from the changesets.
+        if (to_block - from_block > self.clean_threshold || from_block == 1) &&
+            !has_snap_synced_state(provider.tx_ref())?
+        {
             let tx = provider.tx_ref();
             let stage_checkpoint = input
                 .checkpoint
@@ -16,7 +16,7 @@ use reth_primitives::{
     },
     StorageEntry,
 };
-use reth_provider::{DatabaseProviderRW, HashingWriter, StorageReader};
+use reth_provider::{has_snap_synced_state, DatabaseProviderRW, HashingWriter, StorageReader};
 use std::{collections::BTreeMap, fmt::Debug};
 use tracing::*;
 
@@ -66,8 +66,12 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
         // if there are more blocks then threshold it is faster to go over Plain state and hash all
         // account otherwise take changesets aggregate the sets and apply hashing to
         // AccountHashing table. Also, if we start from genesis, we need to hash from scratch, as
-        // genesis accounts are not in changeset, along with their storages.
-        if to_block - from_block > self.clean_threshold || from_block == 1 {
+        // genesis accounts are not in changeset, along with their storages. The plain state of a
+        // snap synced node is incomplete, so its hashed state is always updated from the
+        // changesets.
+        if (to_block - from_block > self.clean_threshold || from_block == 1) &&
+            !has_snap_synced_state(tx)?
+        {
             let stage_checkpoint = input
                 .checkpoint
                 .and_then(|checkpoint| checkpoint.storage_hashing_stage_checkpoint());
@@ -0,0 +1,342 @@
+use super::utils::skip_unindexed_blocks;
+use crate::{BlockErrorKind, ExecInput, ExecOutput, Stage, StageError, UnwindInput, UnwindOutput};
+use reth_db::database::Database;
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    Address, BlockNumber, PruneMode, PruneSegment,
+};
+use reth_provider::{
+    AddressAppearanceReader, BlockReader, DatabaseProviderRW, ExecutorFactory, HeaderProvider,
+    HistoryWriter, ProviderError, PruneCheckpointReader, TransactionVariant,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    ops::RangeInclusive,
+};
+
+/// Stage is indexing the blocks in which every address appears, so that address-based queries
+/// like `trace_filter` don't need to scan every block. For more information on what is considered
+/// an appearance and on index sharding take a look at [`reth_db::tables::AddressAppearances`].
+///
+/// Accounts that are accessed without being changed, e.g. targets of static calls, can't be read
+/// from the block data, so the stage re-executes the blocks on top of the historical state.
+/// Blocks whose account or storage history was pruned can't be re-executed and are skipped like
+/// the pruned blocks, by moving the prune checkpoint of [`PruneSegment::AddressAppearances`].
+///
+/// This stage is optional and is not part of [`StageId::ALL`]. Blocks committed outside of the
+/// pipeline are indexed on the next pipeline run, see
+/// [`AddressAppearanceReader::address_appearances_indexed_range`].
+#[derive(Debug)]
+pub struct IndexAddressAppearancesStage<EF> {
+    /// The stage's internal executor
+    executor_factory: EF,
+    /// Number of blocks after which the control
+    /// flow will be returned to the pipeline for commit.
+    pub commit_threshold: u64,
+    /// Pruning configuration.
+    pub prune_mode: Option<PruneMode>,
+}
+
+impl<EF> IndexAddressAppearancesStage<EF> {
+    /// Create new instance of [IndexAddressAppearancesStage].
+    pub fn new(executor_factory: EF, commit_threshold: u64, prune_mode: Option<PruneMode>) -> Self {
+        Self { executor_factory, commit_threshold, prune_mode }
+    }
+
+    /// Create new instance of [IndexAddressAppearancesStage] with default commit threshold and
+    /// without pruning.
+    pub fn new_with_factory(executor_factory: EF) -> Self {
+        Self::new(executor_factory, 10_000, None)
+    }
+}
+
+impl<EF: ExecutorFactory> IndexAddressAppearancesStage<EF> {
+    /// Re-executes the given range of blocks and returns the accounts accessed by each of them,
+    /// including the ones that weren't changed.
+    ///
+    /// Blocks that were skipped because of pruned history are not re-executed.
+    fn accessed_accounts<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<BTreeMap<Address, Vec<BlockNumber>>, StageError> {
+        let lowest_indexed = provider
+            .get_prune_checkpoint(PruneSegment::AddressAppearances)?
+            .and_then(|checkpoint| checkpoint.block_number)
+            .map_or(0, |block_number| block_number + 1);
+        let start = (*range.start()).max(lowest_indexed);
+        if start > *range.end() {
+            return Ok(BTreeMap::new())
+        }
+
+        let mut executor =
+            self.executor_factory.with_state(provider.history_state_provider_ref(start)?);
+        executor.set_record_accessed_accounts(true);
+
+        for block_number in start..=*range.end() {
+            let td = provider
+                .header_td_by_number(block_number)?
+                .ok_or_else(|| ProviderError::HeaderNotFound(block_number.into()))?;
+            let block = provider
+                .block_with_senders(block_number.into(), TransactionVariant::NoHash)?
+                .ok_or_else(|| ProviderError::BlockNotFound(block_number.into()))?;
+
+            executor.execute(&block, td).map_err(|error| StageError::Block {
+                block: Box::new(block.header.clone().seal_slow()),
+                error: BlockErrorKind::Execution(error),
+            })?;
+        }
+
+        Ok(executor.take_accessed_accounts())
+    }
+
+    /// Returns all appearances in the given range of blocks, see
+    /// [`reth_db::tables::AddressAppearances`].
+    fn appearances<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<BTreeMap<Address, Vec<BlockNumber>>, StageError> {
+        let mut appearances = BTreeMap::<Address, BTreeSet<BlockNumber>>::new();
+        for (address, blocks) in provider
+            .address_appearances_with_range(range.clone())?
+            .into_iter()
+            .chain(self.accessed_accounts(provider, range)?)
+        {
+            appearances.entry(address).or_default().extend(blocks);
+        }
+
+        Ok(appearances
+            .into_iter()
+            .map(|(address, blocks)| (address, blocks.into_iter().collect()))
+            .collect())
+    }
+}
+
+impl<EF: ExecutorFactory, DB: Database> Stage<DB> for IndexAddressAppearancesStage<EF> {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        StageId::IndexAddressAppearances
+    }
+
+    /// Execute the stage.
+    fn execute(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        mut input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        // Blocks are re-executed on top of the state at their start, which is only available if
+        // the history from there on wasn't pruned.
+        skip_unindexed_blocks(
+            provider,
+            &mut input,
+            PruneSegment::AddressAppearances,
+            self.prune_mode,
+            &[PruneSegment::AccountHistory, PruneSegment::StorageHistory],
+        )?;
+
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        let (range, is_final_range) = input.next_block_range_with_threshold(self.commit_threshold);
+
+        let appearances = self.appearances(provider, range.clone())?;
+        // Insert appearances to the index
+        provider.insert_address_appearance_index(appearances)?;
+
+        Ok(ExecOutput { checkpoint: StageCheckpoint::new(*range.end()), done: is_final_range })
+    }
+
+    /// Unwind the stage.
+    fn unwind(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        let (range, unwind_progress, _) =
+            input.unwind_block_range_with_threshold(self.commit_threshold);
+
+        // The state of the unwound blocks is still available, as the execution stage is unwound
+        // after this one.
+        let appearances = self.appearances(provider, range)?;
+        provider.unwind_address_appearance_indices(&appearances)?;
+
+        Ok(UnwindOutput { checkpoint: StageCheckpoint::new(unwind_progress) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestStageDB;
+    use reth_db::{
+        models::{AccountBeforeTx, ShardedKey, StoredBlockBodyIndices},
+        tables,
+        transaction::DbTxMut,
+        BlockNumberList,
+    };
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::{
+        address, Address, BlockNumber, Header, PruneCheckpoint, B256, MAINNET, U256,
+    };
+    use reth_provider::{
+        test_utils::TestExecutorFactory, BundleStateWithReceipts, PruneCheckpointWriter,
+    };
+    use std::collections::BTreeMap;
+
+    const ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+
+    fn cast(
+        table: Vec<(ShardedKey<Address>, BlockNumberList)>,
+    ) -> BTreeMap<ShardedKey<Address>, Vec<usize>> {
+        table.into_iter().map(|(k, v)| (k, v.iter(0).collect())).collect()
+    }
+
+    fn stage() -> IndexAddressAppearancesStage<TestExecutorFactory> {
+        let executor_factory = TestExecutorFactory::new(MAINNET.clone());
+        executor_factory.extend(vec![BundleStateWithReceipts::default()]);
+        IndexAddressAppearancesStage::new_with_factory(executor_factory)
+    }
+
+    fn insert_total_difficulties(db: &TestStageDB, range: RangeInclusive<BlockNumber>) {
+        db.commit(|tx| {
+            for block in range {
+                tx.put::<tables::HeaderTD>(block, U256::ZERO.into())?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn run(db: &TestStageDB, run_to: u64) {
+        let input = ExecInput { target: Some(run_to), ..Default::default() };
+        let provider = db.factory.provider_rw().unwrap();
+        let out = stage().execute(&provider, input).unwrap();
+        assert_eq!(out, ExecOutput { checkpoint: StageCheckpoint::new(run_to), done: true });
+        provider.commit().unwrap();
+    }
+
+    fn unwind(db: &TestStageDB, unwind_from: u64, unwind_to: u64) {
+        let input = UnwindInput {
+            checkpoint: StageCheckpoint::new(unwind_from),
+            unwind_to,
+            ..Default::default()
+        };
+        let provider = db.factory.provider_rw().unwrap();
+        let out = stage().unwind(&provider, input).unwrap();
+        assert_eq!(out, UnwindOutput { checkpoint: StageCheckpoint::new(unwind_to) });
+        provider.commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn index_changed_accounts() {
+        let db = TestStageDB::default();
+        db.commit(|tx| {
+            for block in 0..=5 {
+                tx.put::<tables::CanonicalHeaders>(block, B256::with_last_byte(block as u8))?;
+                tx.put::<tables::Headers>(block, Header { number: block, ..Default::default() })?;
+                tx.put::<tables::BlockBodyIndices>(block, StoredBlockBodyIndices::default())?;
+            }
+            tx.put::<tables::AccountChangeSet>(
+                4,
+                AccountBeforeTx { address: ADDRESS, info: None },
+            )?;
+            tx.put::<tables::AccountChangeSet>(
+                5,
+                AccountBeforeTx { address: ADDRESS, info: None },
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        insert_total_difficulties(&db, 0..=5);
+
+        run(&db, 5);
+
+        let table = cast(db.table::<tables::AddressAppearances>().unwrap());
+        assert_eq!(table, BTreeMap::from([(ShardedKey::last(ADDRESS), vec![4, 5])]));
+
+        let provider = db.factory.provider().unwrap();
+        assert_eq!(provider.address_appearance_blocks(ADDRESS, 0..=4).unwrap(), vec![4]);
+        assert_eq!(provider.address_appearance_blocks(ADDRESS, 5..=10).unwrap(), vec![5]);
+        drop(provider);
+
+        unwind(&db, 5, 0);
+        assert!(db.table::<tables::AddressAppearances>().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn index_transaction_participants() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 1..=10, B256::ZERO, 1..3);
+        db.insert_blocks(blocks.iter(), None).unwrap();
+        insert_total_difficulties(&db, 1..=10);
+
+        run(&db, 10);
+
+        let mut expected = BTreeMap::<Address, Vec<BlockNumber>>::new();
+        for block in &blocks {
+            for transaction in &block.body {
+                let sender = transaction.recover_signer().unwrap();
+                let recipient =
+                    transaction.to().unwrap_or_else(|| sender.create(transaction.nonce()));
+                for address in [sender, recipient] {
+                    let entry = expected.entry(address).or_default();
+                    if entry.last() != Some(&block.number) {
+                        entry.push(block.number);
+                    }
+                }
+            }
+        }
+
+        let provider = db.factory.provider().unwrap();
+        for (address, blocks) in expected {
+            assert_eq!(provider.address_appearance_blocks(address, 0..=10).unwrap(), blocks);
+        }
+        drop(provider);
+
+        unwind(&db, 10, 0);
+        assert!(db.table::<tables::AddressAppearances>().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_blocks_with_pruned_history() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=10, B256::ZERO, 1..3);
+        db.insert_blocks(blocks.iter(), None).unwrap();
+        insert_total_difficulties(&db, 0..=10);
+        let provider = db.factory.provider_rw().unwrap();
+        provider
+            .save_prune_checkpoint(
+                PruneSegment::AccountHistory,
+                PruneCheckpoint {
+                    block_number: Some(5),
+                    tx_number: None,
+                    prune_mode: PruneMode::Before(6),
+                },
+            )
+            .unwrap();
+        provider.commit().unwrap();
+
+        run(&db, 10);
+
+        let provider = db.factory.provider().unwrap();
+        assert_eq!(provider.address_appearances_indexed_range().unwrap(), Some(6..=10));
+        for block in &blocks {
+            let sender = block.body.first().unwrap().recover_signer().unwrap();
+            let indexed = provider.address_appearance_blocks(sender, 0..=10).unwrap();
+            assert_eq!(indexed.contains(&block.number), block.number > 5);
+
+            // Skipped blocks are always candidates.
+            let candidates =
+                provider.address_appearance_candidates(&[sender], 0..=10).unwrap().unwrap();
+            assert!(candidates.contains(&block.number));
+            assert!(candidates.is_superset(&(0..=5).collect()));
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use super::utils::skip_unindexed_blocks;
+use crate::{ExecInput, ExecOutput, Stage, StageError, UnwindInput, UnwindOutput};
+use reth_db::database::Database;
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    PruneMode, PruneSegment,
+};
+use reth_provider::{DatabaseProviderRW, HistoryWriter, LogIndexReader};
+use std::fmt::Debug;
+
+/// Stage is indexing the blocks in which every log address and topic was emitted, so that
+/// `eth_getLogs` queries don't need to check the bloom of every block in the range. For more
+/// information on index sharding take a look at [`reth_db::tables::LogAddressIndex`] and
+/// [`reth_db::tables::LogTopicIndex`].
+///
+/// Blocks whose receipts were pruned are skipped like the pruned blocks, by moving the prune
+/// checkpoint of [`PruneSegment::LogIndex`].
+///
+/// This stage is optional and is not part of [`StageId::ALL`]. Once it has a checkpoint, blocks
+/// committed outside of the pipeline are indexed by the provider as well.
+#[derive(Debug)]
+pub struct IndexLogsStage {
+    /// Number of blocks after which the control
+    /// flow will be returned to the pipeline for commit.
+    pub commit_threshold: u64,
+    /// Pruning configuration.
+    pub prune_mode: Option<PruneMode>,
+}
+
+impl IndexLogsStage {
+    /// Create new instance of [IndexLogsStage].
+    pub fn new(commit_threshold: u64, prune_mode: Option<PruneMode>) -> Self {
+        Self { commit_threshold, prune_mode }
+    }
+}
+
+impl Default for IndexLogsStage {
+    fn default() -> Self {
+        Self { commit_threshold: 100_000, prune_mode: None }
+    }
+}
+
+impl<DB: Database> Stage<DB> for IndexLogsStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        StageId::IndexLogs
+    }
+
+    /// Execute the stage.
+    fn execute(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        mut input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        // Logs are read from the receipts, which are incomplete if they were pruned.
+        skip_unindexed_blocks(
+            provider,
+            &mut input,
+            PruneSegment::LogIndex,
+            self.prune_mode,
+            &[PruneSegment::Receipts],
+        )?;
+
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        let (range, is_final_range) = input.next_block_range_with_threshold(self.commit_threshold);
+
+        let entries = provider.log_index_with_range(range.clone())?;
+        // Insert log addresses and topics to the index
+        provider.insert_log_index(entries)?;
+
+        Ok(ExecOutput { checkpoint: StageCheckpoint::new(*range.end()), done: is_final_range })
+    }
+
+    /// Unwind the stage.
+    fn unwind(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        let (range, unwind_progress, _) =
+            input.unwind_block_range_with_threshold(self.commit_threshold);
+
+        provider.unwind_log_indices(range)?;
+
+        Ok(UnwindOutput { checkpoint: StageCheckpoint::new(unwind_progress) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestStageDB;
+    use reth_db::{models::StoredBlockBodyIndices, tables, transaction::DbTxMut};
+    use reth_primitives::{address, b256, Address, Log, PruneCheckpoint, Receipt, B256};
+    use reth_provider::{PruneCheckpointReader, PruneCheckpointWriter, StageCheckpointWriter};
+    use std::collections::BTreeSet;
+
+    const ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+    const TOPIC: B256 = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+    const OTHER_TOPIC: B256 =
+        b256!("0000000000000000000000000000000000000000000000000000000000000002");
+
+    fn run(db: &TestStageDB, run_to: u64) {
+        let input = ExecInput { target: Some(run_to), ..Default::default() };
+        let mut stage = IndexLogsStage::default();
+        let provider = db.factory.provider_rw().unwrap();
+        let out = stage.execute(&provider, input).unwrap();
+        assert_eq!(out, ExecOutput { checkpoint: StageCheckpoint::new(run_to), done: true });
+        provider.commit().unwrap();
+    }
+
+    fn unwind(db: &TestStageDB, unwind_from: u64, unwind_to: u64) {
+        let input = UnwindInput {
+            checkpoint: StageCheckpoint::new(unwind_from),
+            unwind_to,
+            ..Default::default()
+        };
+        let mut stage = IndexLogsStage::default();
+        let provider = db.factory.provider_rw().unwrap();
+        let out = stage.unwind(&provider, input).unwrap();
+        assert_eq!(out, UnwindOutput { checkpoint: StageCheckpoint::new(unwind_to) });
+        provider.commit().unwrap();
+    }
+
+    fn insert_receipts(db: &TestStageDB) {
+        db.commit(|tx| {
+            // One transaction per block, blocks 2 and 4 emit logs.
+            for block in 0..=5 {
+                tx.put::<tables::BlockBodyIndices>(
+                    block,
+                    StoredBlockBodyIndices { first_tx_num: block, tx_count: 1 },
+                )?;
+                let topics = match block {
+                    2 => vec![TOPIC],
+                    4 => vec![OTHER_TOPIC, TOPIC],
+                    _ => continue,
+                };
+                tx.put::<tables::Receipts>(
+                    block,
+                    Receipt {
+                        logs: vec![Log { address: ADDRESS, topics, ..Default::default() }],
+                        ..Default::default()
+                    },
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn index_logs() {
+        let db = TestStageDB::default();
+        insert_receipts(&db);
+
+        run(&db, 5);
+
+        let provider = db.factory.provider_rw().unwrap();
+        assert_eq!(provider.log_address_blocks(ADDRESS, 0..=5).unwrap(), vec![2, 4]);
+        assert_eq!(provider.log_topic_blocks(TOPIC, 3..=5).unwrap(), vec![4]);
+        assert_eq!(provider.log_topic_blocks(OTHER_TOPIC, 0..=5).unwrap(), vec![4]);
+
+        // The index isn't used until it has a checkpoint.
+        assert_eq!(provider.log_candidates(&[ADDRESS], &[], 0..=5).unwrap(), None);
+        provider.save_stage_checkpoint(StageId::IndexLogs, StageCheckpoint::new(5)).unwrap();
+        assert_eq!(
+            provider.log_candidates(&[ADDRESS], &[vec![], vec![TOPIC]], 0..=7).unwrap(),
+            Some(BTreeSet::from([2, 4, 6, 7]))
+        );
+        assert_eq!(
+            provider.log_candidates(&[], &[vec![OTHER_TOPIC]], 0..=5).unwrap(),
+            Some(BTreeSet::from([4]))
+        );
+        assert_eq!(
+            provider.log_candidates(&[Address::ZERO], &[vec![TOPIC]], 0..=5).unwrap(),
+            Some(BTreeSet::new())
+        );
+        drop(provider);
+
+        unwind(&db, 5, 2);
+        let provider = db.factory.provider().unwrap();
+        assert_eq!(provider.log_address_blocks(ADDRESS, 0..=5).unwrap(), vec![2]);
+        assert!(provider.log_topic_blocks(OTHER_TOPIC, 0..=5).unwrap().is_empty());
+        drop(provider);
+
+        unwind(&db, 2, 0);
+        assert!(db.table::<tables::LogAddressIndex>().unwrap().is_empty());
+        assert!(db.table::<tables::LogTopicIndex>().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_blocks_with_pruned_receipts() {
+        let db = TestStageDB::default();
+        insert_receipts(&db);
+        let provider = db.factory.provider_rw().unwrap();
+        provider
+            .save_prune_checkpoint(
+                PruneSegment::Receipts,
+                PruneCheckpoint {
+                    block_number: Some(2),
+                    tx_number: Some(2),
+                    prune_mode: PruneMode::Before(3),
+                },
+            )
+            .unwrap();
+        provider.commit().unwrap();
+
+        run(&db, 5);
+
+        let provider = db.factory.provider_rw().unwrap();
+        assert_eq!(provider.log_address_blocks(ADDRESS, 0..=5).unwrap(), vec![4]);
+        assert_eq!(
+            provider.get_prune_checkpoint(PruneSegment::LogIndex).unwrap().unwrap().block_number,
+            Some(2)
+        );
+
+        // Skipped blocks and blocks above the checkpoint can't be ruled out.
+        provider.save_stage_checkpoint(StageId::IndexLogs, StageCheckpoint::new(5)).unwrap();
+        assert_eq!(provider.logs_indexed_range().unwrap(), Some(3..=5));
+        assert_eq!(
+            provider.log_candidates(&[ADDRESS], &[], 0..=6).unwrap(),
+            Some(BTreeSet::from([0, 1, 2, 4, 6]))
+        );
+        assert_eq!(
+            provider.log_candidates(&[Address::ZERO], &[], 1..=3).unwrap(),
+            Some(BTreeSet::from([1, 2]))
+        );
+    }
+}
@@ -140,6 +140,11 @@ impl<DB: Database> Stage<DB> for MerkleStage {
             MerkleStage::Both { clean_threshold } => *clean_threshold,
         };
 
+        // the trie can be ahead of the execution, e.g. after snap sync
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
         let range = input.next_block_range();
         let (from_block, to_block) = range.clone().into_inner();
         let current_block_number = input.checkpoint().block_number;
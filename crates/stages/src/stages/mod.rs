@@ -12,16 +12,24 @@ mod hashing_storage;
 mod headers;
 /// Index history of account changes
 mod index_account_history;
+/// Index blocks in which addresses appear
+mod index_address_appearances;
+/// Index blocks in which logs are emitted
+mod index_logs;
 /// Index history of storage changes
 mod index_storage_history;
 /// Stage for computing state root.
 mod merkle;
 /// The sender recovery stage.
 mod sender_recovery;
+/// The snap sync stage.
+mod snap_sync;
 /// The total difficulty stage
 mod total_difficulty;
 /// The transaction lookup stage
 mod tx_lookup;
+/// Helpers shared by the stages
+mod utils;
 
 pub use bodies::*;
 pub use execution::*;
@@ -30,9 +38,12 @@ pub use hashing_account::*;
 pub use hashing_storage::*;
 pub use headers::*;
 pub use index_account_history::*;
+pub use index_address_appearances::*;
+pub use index_logs::*;
 pub use index_storage_history::*;
 pub use merkle::*;
 pub use sender_recovery::*;
+pub use snap_sync::*;
 pub use total_difficulty::*;
 pub use tx_lookup::*;
 
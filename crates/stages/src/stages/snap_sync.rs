@@ -0,0 +1,1709 @@
+use crate::{ExecInput, ExecOutput, Stage, StageError, UnwindInput, UnwindOutput};
+use alloy_rlp::{Decodable, Header};
+use futures_util::FutureExt;
+use reth_codecs::Compact;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRW},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
+use reth_eth_wire::{
+    AccountRangeMessage, ByteCodesMessage, GetAccountRangeMessage, GetByteCodesMessage,
+    GetStorageRangesMessage, GetTrieNodesMessage, StorageRangesMessage, TrieNodesMessage,
+    TriePathSet,
+};
+use reth_interfaces::p2p::{error::RequestError, snap::SnapClient};
+use reth_primitives::{
+    keccak256,
+    stage::{SnapStorageTask, SnapSyncCheckpoint, SnapSyncTasks, StageCheckpoint, StageId},
+    trie::{HashBuilder, Nibbles, TrieAccount, EMPTY_ROOT_HASH},
+    Account, Bytecode, Bytes, PeerId, PruneCheckpoint, PruneMode, PruneSegment, StorageEntry, B256,
+    KECCAK_EMPTY, U256,
+};
+use reth_provider::{
+    BlockReader, DatabaseProviderRW, HeaderProvider, ProviderError, PruneCheckpointReader,
+    PruneCheckpointWriter, StageCheckpointReader, StageCheckpointWriter,
+};
+use reth_trie::{Proof, StateRoot};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tracing::*;
+
+/// The default soft limit of the response size of a single `snap` request.
+pub const SNAP_SYNC_DEFAULT_RESPONSE_BYTES: u64 = 512 * 1024;
+
+/// The maximum number of accounts whose storage is requested at once.
+const MAX_STORAGE_ACCOUNTS: usize = 128;
+
+/// The maximum number of bytecodes requested at once.
+const MAX_CODE_REQUESTS: usize = 64;
+
+/// The maximum number of trie nodes requested at once.
+const MAX_TRIE_NODE_REQUESTS: usize = 256;
+
+/// The number of consecutive responses without any state after which the pivot is considered
+/// stale, i.e. no longer served by the peers.
+const STALE_PIVOT_THRESHOLD: usize = 8;
+
+/// The number of healing rounds after which the pivot is considered stale.
+const MAX_HEAL_ROUNDS: usize = 16;
+
+/// The delay before a failed request is retried.
+const REQUEST_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The snap sync stage downloads the hashed state and the state trie of a recent pivot block from
+/// peers that support the `snap/1` protocol, instead of hashing the state and building the trie
+/// locally.
+///
+/// The stage runs in the following steps:
+///
+/// 1. Pick the pivot `pivot_distance` blocks below the synced header tip.
+/// 2. Download the flat account and storage ranges and the bytecodes of the pivot state straight
+///    into the hashed state tables. Every range is verified against the state root of the pivot
+///    with its range proof before anything is written or deleted, peers serving invalid ranges are
+///    penalized.
+/// 3. Compute the state root through [`StateRoot`]. If it doesn't match the root of the pivot, heal
+///    the account trie with `GetTrieNodes` requests, starting at the root and descending into every
+///    node that differs from the local one, and verify again.
+/// 4. Move the checkpoints of the execution, hashing and merkle stages to the pivot, and mark the
+///    change sets and receipts of the blocks up to the pivot as pruned.
+///
+/// The progress, including the pending storage and bytecode downloads, is checkpointed after
+/// every response, so an interrupted sync continues where it left off. If the peers stop serving
+/// the pivot state, the stage moves the pivot to the newest header and keeps the state downloaded
+/// so far.
+///
+/// Until the stage is done, its checkpoint stays at zero so that the following stages don't
+/// progress. Once done, it only forwards the target.
+///
+/// # Plain state
+///
+/// `snap/1` only serves the hashed state, which can't be turned back into the plain state tables
+/// ([`PlainAccountState`][reth_db::tables::PlainAccountState] and
+/// [`PlainStorageState`][reth_db::tables::PlainStorageState]) without the preimages of the
+/// hashes. The plain state is instead built by executing the blocks after the pivot, and the
+/// state providers read the accounts and storage that weren't touched since the pivot from the
+/// hashed state. The snap sync progress is kept once the stage is done to mark the plain state as
+/// incomplete, see [`has_snap_synced_state`][reth_provider::has_snap_synced_state].
+///
+/// Storage wipes of accounts that existed at the pivot only record the slots written since the
+/// pivot in the change sets, so unwinding them doesn't restore the storage downloaded from peers.
+///
+/// Only one request is in flight at a time, as every response is verified and written in its own
+/// execution. The client retries timed out requests with other peers.
+///
+/// This stage is optional and is not part of [`StageId::ALL`].
+///
+/// # Tables
+///
+/// - [`HashedAccount`][reth_db::tables::HashedAccount]
+/// - [`HashedStorage`][reth_db::tables::HashedStorage]
+/// - [`Bytecodes`][reth_db::tables::Bytecodes]
+/// - [`AccountsTrie`][reth_db::tables::AccountsTrie]
+/// - [`StoragesTrie`][reth_db::tables::StoragesTrie]
+pub struct SnapSyncStage<C> {
+    /// The client to request the state from.
+    client: C,
+    /// The distance of the pivot to the synced header tip.
+    pivot_distance: u64,
+    /// The soft limit of the response size of a single request.
+    response_bytes: u64,
+    /// The progress of the sync, loaded on the first execution.
+    progress: Option<SnapSyncCheckpoint>,
+    /// The accounts whose storage is still to be downloaded.
+    storage_tasks: VecDeque<SnapStorageTask>,
+    /// The bytecodes that are still to be downloaded.
+    code_tasks: VecDeque<B256>,
+    /// The account trie nodes to heal, by path and expected hash.
+    heal_tasks: VecDeque<(Nibbles, B256)>,
+    /// The number of failed state root checks of the pivot.
+    heal_rounds: usize,
+    /// The number of consecutive responses without any state.
+    empty_responses: usize,
+    /// The request that is in flight.
+    inflight: Option<SnapResponseFut>,
+    /// The delay before the next request is sent.
+    retry_delay: Option<Pin<Box<tokio::time::Sleep>>>,
+    /// The response that is written on the next execution.
+    buffer: Option<SnapResponse>,
+}
+
+impl<C: SnapClient> SnapSyncStage<C> {
+    /// Create new instance of [SnapSyncStage].
+    pub fn new(client: C, pivot_distance: u64, response_bytes: u64) -> Self {
+        Self {
+            client,
+            pivot_distance,
+            response_bytes,
+            progress: None,
+            storage_tasks: VecDeque::new(),
+            code_tasks: VecDeque::new(),
+            heal_tasks: VecDeque::new(),
+            heal_rounds: 0,
+            empty_responses: 0,
+            inflight: None,
+            retry_delay: None,
+            buffer: None,
+        }
+    }
+
+    /// Gets the snap sync progress and the downloads that were pending when it was saved.
+    pub fn get_progress<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+    ) -> Result<Option<(SnapSyncCheckpoint, SnapSyncTasks)>, StageError> {
+        let buf = provider.get_stage_checkpoint_progress(StageId::SnapSync)?.unwrap_or_default();
+
+        if buf.is_empty() {
+            return Ok(None)
+        }
+
+        let (checkpoint, rest) = SnapSyncCheckpoint::from_compact(&buf, buf.len());
+        let tasks = if rest.is_empty() {
+            SnapSyncTasks::default()
+        } else {
+            SnapSyncTasks::from_compact(rest, rest.len()).0
+        };
+        Ok(Some((checkpoint, tasks)))
+    }
+
+    /// Saves the snap sync progress together with the pending storage and bytecode downloads.
+    pub fn save_progress<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        checkpoint: Option<SnapSyncCheckpoint>,
+    ) -> Result<(), StageError> {
+        let mut buf = vec![];
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.to_compact(&mut buf);
+            let tasks = SnapSyncTasks {
+                storages: self.storage_tasks.iter().copied().collect(),
+                codes: self.code_tasks.iter().copied().collect(),
+            };
+            tasks.to_compact(&mut buf);
+        }
+        Ok(provider.save_stage_checkpoint_progress(StageId::SnapSync, buf)?)
+    }
+
+    /// Resets all in-memory progress.
+    fn reset(&mut self) {
+        self.progress = None;
+        self.storage_tasks.clear();
+        self.code_tasks.clear();
+        self.heal_tasks.clear();
+        self.heal_rounds = 0;
+        self.empty_responses = 0;
+        self.inflight = None;
+        self.retry_delay = None;
+        self.buffer = None;
+    }
+
+    /// Returns `true` if the peers no longer serve the state of the pivot.
+    fn is_stale(&self) -> bool {
+        self.empty_responses >= STALE_PIVOT_THRESHOLD
+    }
+
+    /// Returns `true` if all state of the pivot was downloaded and needs to be verified.
+    fn is_download_complete(&self, progress: &SnapSyncCheckpoint) -> bool {
+        progress.accounts_complete &&
+            self.storage_tasks.is_empty() &&
+            self.code_tasks.is_empty() &&
+            self.heal_tasks.is_empty()
+    }
+
+    /// Returns the next request to send.
+    ///
+    /// Pending storage and bytecodes are downloaded before the next account range or trie nodes.
+    fn next_request(&self) -> Option<SnapResponseFut> {
+        let progress = self.progress?;
+        let root_hash = progress.state_root;
+        let response_bytes = self.response_bytes;
+
+        if let Some(first) = self.storage_tasks.front() {
+            // the remaining storage of a large account is requested on its own
+            let tasks: Vec<_> = if first.start.is_zero() {
+                self.storage_tasks
+                    .iter()
+                    .take_while(|task| task.start.is_zero())
+                    .take(MAX_STORAGE_ACCOUNTS)
+                    .copied()
+                    .collect()
+            } else {
+                vec![*first]
+            };
+            let starting_hash =
+                if first.start.is_zero() { Bytes::new() } else { first.start.to_vec().into() };
+            let request = GetStorageRangesMessage {
+                request_id: 0,
+                root_hash,
+                account_hashes: tasks.iter().map(|task| task.account).collect(),
+                starting_hash,
+                limit_hash: Bytes::new(),
+                response_bytes,
+            };
+            let fut = self.client.get_storage_ranges(request).map(|res| {
+                res.map(|res| SnapResponse::Storages {
+                    peer_id: res.peer_id(),
+                    tasks,
+                    response: res.into_data(),
+                })
+            });
+            return Some(Box::pin(fut))
+        }
+
+        if !self.code_tasks.is_empty() {
+            let hashes: Vec<_> = self.code_tasks.iter().take(MAX_CODE_REQUESTS).copied().collect();
+            let request = GetByteCodesMessage { request_id: 0, hashes, response_bytes };
+            let fut = self.client.get_byte_codes(request).map(|res| {
+                res.map(|res| SnapResponse::ByteCodes {
+                    peer_id: res.peer_id(),
+                    response: res.into_data(),
+                })
+            });
+            return Some(Box::pin(fut))
+        }
+
+        if !progress.accounts_complete {
+            let request = GetAccountRangeMessage {
+                request_id: 0,
+                root_hash,
+                starting_hash: progress.next_account,
+                limit_hash: B256::repeat_byte(0xff),
+                response_bytes,
+            };
+            let fut = self.client.get_account_range(request).map(|res| {
+                res.map(|res| SnapResponse::Accounts {
+                    peer_id: res.peer_id(),
+                    response: res.into_data(),
+                })
+            });
+            return Some(Box::pin(fut))
+        }
+
+        if !self.heal_tasks.is_empty() {
+            let paths = self
+                .heal_tasks
+                .iter()
+                .take(MAX_TRIE_NODE_REQUESTS)
+                .map(|(path, _)| TriePathSet { paths: vec![compact_path(path)] })
+                .collect();
+            let request = GetTrieNodesMessage { request_id: 0, root_hash, paths, response_bytes };
+            let fut = self.client.get_trie_nodes(request).map(|res| {
+                res.map(|res| SnapResponse::TrieNodes {
+                    peer_id: res.peer_id(),
+                    response: res.into_data(),
+                })
+            });
+            return Some(Box::pin(fut))
+        }
+
+        None
+    }
+
+    /// Records a response without any state.
+    fn on_empty_response(&mut self, peer_id: PeerId) {
+        self.empty_responses += 1;
+        debug!(target: "sync::stages::snap_sync", ?peer_id, empty_responses = self.empty_responses, "Peer did not serve the pivot state");
+    }
+
+    /// Writes the downloaded response.
+    fn on_response<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        progress: &mut SnapSyncCheckpoint,
+        response: SnapResponse,
+    ) -> Result<(), StageError> {
+        match response {
+            SnapResponse::Accounts { peer_id, response } => {
+                self.on_account_range(tx, progress, peer_id, response)
+            }
+            SnapResponse::Storages { peer_id, tasks, response } => {
+                self.on_storage_ranges(tx, peer_id, tasks, response)
+            }
+            SnapResponse::ByteCodes { peer_id, response } => {
+                self.on_byte_codes(tx, peer_id, response)
+            }
+            SnapResponse::TrieNodes { peer_id, response } => {
+                self.on_trie_nodes(tx, peer_id, response)
+            }
+        }
+    }
+
+    fn on_account_range<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        progress: &mut SnapSyncCheckpoint,
+        peer_id: PeerId,
+        response: AccountRangeMessage,
+    ) -> Result<(), StageError> {
+        if response.accounts.is_empty() && response.proof.is_empty() {
+            self.on_empty_response(peer_id);
+            return Ok(())
+        }
+
+        let mut accounts = Vec::with_capacity(response.accounts.len());
+        let mut leaves = Vec::with_capacity(response.accounts.len());
+        for data in &response.accounts {
+            let Ok((account, storage_root)) = data.account() else {
+                self.client.report_bad_message(peer_id);
+                return Ok(())
+            };
+            let leaf = alloy_rlp::encode(TrieAccount::from((account, storage_root)));
+            leaves.push((data.hash, Bytes::from(leaf)));
+            accounts.push((data.hash, account, storage_root));
+        }
+
+        let has_more = match verify_range_proof(
+            progress.state_root,
+            progress.next_account,
+            &leaves,
+            &response.proof,
+        ) {
+            // an empty range must not have any accounts after the origin
+            Some(has_more) if !has_more || !accounts.is_empty() => has_more,
+            _ => {
+                debug!(target: "sync::stages::snap_sync", ?peer_id, origin = ?progress.next_account, "Invalid account range proof");
+                self.client.report_bad_message(peer_id);
+                return Ok(())
+            }
+        };
+        self.empty_responses = 0;
+
+        // remove the accounts of the proven range that don't exist in the pivot state
+        let next =
+            accounts.last().filter(|_| has_more).and_then(|(last, _, _)| increment_hash(*last));
+        let end = match (next, accounts.last()) {
+            (Some(_), Some((last, _, _))) => *last,
+            _ => B256::repeat_byte(0xff),
+        };
+        let served: HashSet<_> = accounts.iter().map(|(hash, _, _)| *hash).collect();
+        delete_accounts_in_range(tx, progress.next_account, end, |hash| served.contains(hash))?;
+
+        for (hashed_address, account, storage_root) in accounts {
+            self.write_account(tx, hashed_address, account, storage_root, true)?;
+        }
+
+        match next {
+            Some(next) => progress.next_account = next,
+            None => progress.accounts_complete = true,
+        }
+        Ok(())
+    }
+
+    fn on_storage_ranges<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        peer_id: PeerId,
+        tasks: Vec<SnapStorageTask>,
+        response: StorageRangesMessage,
+    ) -> Result<(), StageError> {
+        if response.slots.is_empty() && response.proof.is_empty() {
+            self.on_empty_response(peer_id);
+            return Ok(())
+        }
+        if response.slots.is_empty() || response.slots.len() > tasks.len() {
+            self.client.report_bad_message(peer_id);
+            return Ok(())
+        }
+
+        // only the last served range can be incomplete and carry a proof
+        let served = response.slots.len();
+        let mut storages = Vec::with_capacity(served);
+        for (idx, (task, slots)) in tasks.into_iter().zip(response.slots).enumerate() {
+            let mut entries = Vec::with_capacity(slots.len());
+            let mut leaves = Vec::with_capacity(slots.len());
+            for slot in slots {
+                let Ok(value) = U256::decode(&mut &slot.data[..]) else {
+                    self.client.report_bad_message(peer_id);
+                    return Ok(())
+                };
+                leaves
+                    .push((slot.hash, Bytes::from(alloy_rlp::encode_fixed_size(&value).to_vec())));
+                entries.push(StorageEntry { key: slot.hash, value });
+            }
+
+            let proof = if idx + 1 == served { &response.proof[..] } else { &[] };
+            match verify_range_proof(task.root, task.start, &leaves, proof) {
+                Some(has_more) if !has_more || !entries.is_empty() => {
+                    storages.push((task, entries, has_more))
+                }
+                _ => {
+                    debug!(target: "sync::stages::snap_sync", ?peer_id, account = ?task.account, origin = ?task.start, "Invalid storage range proof");
+                    self.client.report_bad_message(peer_id);
+                    return Ok(())
+                }
+            }
+        }
+        self.empty_responses = 0;
+
+        let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>()?;
+        for (task, entries, has_more) in storages {
+            let next =
+                entries.last().filter(|_| has_more).and_then(|entry| increment_hash(entry.key));
+            for entry in entries.into_iter().filter(|entry| entry.value != U256::ZERO) {
+                cursor.upsert(task.account, entry)?;
+            }
+
+            match next {
+                Some(start) => {
+                    if let Some(pending) = self
+                        .storage_tasks
+                        .iter_mut()
+                        .find(|pending| pending.account == task.account)
+                    {
+                        pending.start = start;
+                    }
+                }
+                None => self.storage_tasks.retain(|pending| pending.account != task.account),
+            }
+        }
+        Ok(())
+    }
+
+    fn on_byte_codes<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        peer_id: PeerId,
+        response: ByteCodesMessage,
+    ) -> Result<(), StageError> {
+        let mut delivered = HashSet::with_capacity(response.codes.len());
+        for code in response.codes {
+            let code_hash = keccak256(&code);
+            if !self.code_tasks.contains(&code_hash) {
+                self.client.report_bad_message(peer_id);
+                return Ok(())
+            }
+            tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code))?;
+            delivered.insert(code_hash);
+        }
+
+        if delivered.is_empty() {
+            self.on_empty_response(peer_id);
+        } else {
+            self.empty_responses = 0;
+            self.code_tasks.retain(|code_hash| !delivered.contains(code_hash));
+        }
+        Ok(())
+    }
+
+    fn on_trie_nodes<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        peer_id: PeerId,
+        response: TrieNodesMessage,
+    ) -> Result<(), StageError> {
+        if response.nodes.is_empty() {
+            self.on_empty_response(peer_id);
+            return Ok(())
+        }
+        if response.nodes.len() > self.heal_tasks.len() {
+            self.client.report_bad_message(peer_id);
+            return Ok(())
+        }
+        self.empty_responses = 0;
+
+        let tasks: Vec<_> = self.heal_tasks.drain(..response.nodes.len()).collect();
+        let targets = tasks.iter().map(|(path, _)| path.clone()).collect();
+        let (_, local_nodes) = Proof::new(tx)
+            .account_multiproof(targets)
+            .map_err(|e| StageError::Fatal(Box::new(e)))?;
+
+        for ((path, hash), node) in tasks.into_iter().zip(response.nodes) {
+            let remote = match TrieNode::decode(&node) {
+                Ok(remote) if keccak256(&node) == hash => remote,
+                _ => {
+                    self.client.report_bad_message(peer_id);
+                    self.heal_tasks.push_back((path, hash));
+                    continue
+                }
+            };
+
+            let local = local_nodes.get(&path);
+            if local == Some(&node) {
+                // the subtrie is identical
+                continue
+            }
+            let local = local.and_then(|node| TrieNode::decode(node).ok());
+
+            match remote {
+                TrieNode::Branch(children) => {
+                    let local_children = match local {
+                        Some(TrieNode::Branch(children)) => Some(children),
+                        _ => None,
+                    };
+                    for (nibble, child) in children.into_iter().enumerate() {
+                        if local_children.map_or(false, |local| local[nibble] == child) {
+                            continue
+                        }
+                        let child_path = join_path(&path, &[nibble as u8]);
+                        match child {
+                            Some(child_hash) => self.heal_tasks.push_back((child_path, child_hash)),
+                            None => delete_accounts_with_prefix(tx, &child_path, |_| false)?,
+                        }
+                    }
+                }
+                TrieNode::Extension(key, child_hash) => {
+                    let child_path = join_path(&path, &key);
+                    delete_accounts_with_prefix(tx, &path, |nibbles| {
+                        nibbles.starts_with(&child_path[..])
+                    })?;
+                    self.heal_tasks.push_back((child_path, child_hash));
+                }
+                TrieNode::Leaf(key, value) => {
+                    let full_path = join_path(&path, &key);
+                    let Ok((account, storage_root)) = decode_trie_account(&value) else {
+                        self.client.report_bad_message(peer_id);
+                        continue
+                    };
+                    if full_path.len() != 64 {
+                        self.client.report_bad_message(peer_id);
+                        continue
+                    }
+                    let hashed_address = B256::from_slice(&full_path.pack());
+                    delete_accounts_with_prefix(tx, &path, |nibbles| nibbles == &full_path)?;
+
+                    // only re-download the storage if it differs
+                    let local_storage_root = Proof::new(tx)
+                        .storage_root(hashed_address)
+                        .map_err(|e| StageError::Fatal(Box::new(e)))?;
+                    let refetch_storage = local_storage_root != storage_root;
+                    self.write_account(tx, hashed_address, account, storage_root, refetch_storage)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the account and schedules the download of its storage and bytecode.
+    fn write_account<TX: DbTx + DbTxMut>(
+        &mut self,
+        tx: &TX,
+        hashed_address: B256,
+        account: Account,
+        storage_root: B256,
+        refetch_storage: bool,
+    ) -> Result<(), StageError> {
+        tx.put::<tables::HashedAccount>(hashed_address, account)?;
+
+        if refetch_storage {
+            clear_storage(tx, hashed_address)?;
+            self.storage_tasks.retain(|task| task.account != hashed_address);
+            if storage_root != EMPTY_ROOT_HASH {
+                self.storage_tasks.push_back(SnapStorageTask {
+                    account: hashed_address,
+                    root: storage_root,
+                    start: B256::ZERO,
+                });
+            }
+        }
+
+        if let Some(code_hash) = account.bytecode_hash.filter(|hash| *hash != KECCAK_EMPTY) {
+            if !self.code_tasks.contains(&code_hash) &&
+                tx.get::<tables::Bytecodes>(code_hash)?.is_none()
+            {
+                self.code_tasks.push_back(code_hash);
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the pivot to the newest header if the peers no longer serve the pivot state.
+    fn repivot<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: ExecInput,
+        mut progress: SnapSyncCheckpoint,
+    ) -> Result<ExecOutput, StageError> {
+        let pivot_block = input.target().saturating_sub(self.pivot_distance);
+        if pivot_block <= progress.pivot_block {
+            // let the pipeline sync newer headers first
+            info!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, "Pivot state is no longer served, waiting for a newer pivot");
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        let header = provider
+            .header_by_number(pivot_block)?
+            .ok_or_else(|| ProviderError::HeaderNotFound(pivot_block.into()))?;
+        info!(target: "sync::stages::snap_sync", previous = progress.pivot_block, pivot_block, state_root = ?header.state_root, "Moving snap sync pivot");
+
+        // the pending storage ranges are proven against the storage roots of the old pivot, the
+        // accounts are rescheduled with their new storage roots when the account trie is healed
+        self.storage_tasks.clear();
+        self.inflight = None;
+        self.buffer = None;
+
+        progress.pivot_block = pivot_block;
+        progress.state_root = header.state_root;
+        self.save_progress(provider, Some(progress))?;
+        self.progress = Some(progress);
+
+        // the state is verified again against the new root
+        self.heal_tasks.clear();
+        self.heal_rounds = 0;
+        self.empty_responses = 0;
+
+        Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+    }
+}
+
+impl<DB: Database, C: SnapClient + 'static> Stage<DB> for SnapSyncStage<C> {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        StageId::SnapSync
+    }
+
+    fn poll_execute_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+        input: ExecInput,
+    ) -> Poll<Result<(), StageError>> {
+        if input.target_reached() ||
+            self.buffer.is_some() ||
+            self.progress.is_none() ||
+            self.is_stale()
+        {
+            return Poll::Ready(Ok(()))
+        }
+
+        loop {
+            if let Some(delay) = &mut self.retry_delay {
+                ready!(delay.as_mut().poll(cx));
+                self.retry_delay = None;
+            }
+
+            if self.inflight.is_none() {
+                // nothing left to download, the state is verified on execution
+                let Some(request) = self.next_request() else { return Poll::Ready(Ok(())) };
+                self.inflight = Some(request);
+            }
+
+            let inflight = self.inflight.as_mut().expect("request is in flight");
+            let result = ready!(inflight.poll_unpin(cx));
+            self.inflight = None;
+            match result {
+                Ok(response) => {
+                    self.buffer = Some(response);
+                    return Poll::Ready(Ok(()))
+                }
+                Err(err) => {
+                    debug!(target: "sync::stages::snap_sync", %err, "Snap request failed, retrying");
+                    self.retry_delay = Some(Box::pin(tokio::time::sleep(REQUEST_RETRY_DELAY)));
+                }
+            }
+        }
+    }
+
+    /// Download the state of the pivot block.
+    fn execute(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        // the state was synced before, only forward the target
+        if input.checkpoint().block_number > 0 {
+            return Ok(ExecOutput::done(StageCheckpoint::new(input.target())))
+        }
+
+        let mut progress = match self.progress {
+            Some(progress) => progress,
+            None => {
+                let progress = match self.get_progress(provider)? {
+                    Some((progress, tasks)) => {
+                        info!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, next_account = ?progress.next_account, storages = tasks.storages.len(), codes = tasks.codes.len(), "Continuing snap sync");
+                        self.storage_tasks = tasks.storages.into();
+                        self.code_tasks = tasks.codes.into();
+                        progress
+                    }
+                    None => {
+                        let executed = provider
+                            .get_stage_checkpoint(StageId::Execution)?
+                            .unwrap_or_default()
+                            .block_number;
+                        let pivot_block = input.target().saturating_sub(self.pivot_distance);
+                        if executed > 0 || pivot_block == 0 {
+                            info!(target: "sync::stages::snap_sync", executed, pivot_block, "Skipping snap sync");
+                            return Ok(ExecOutput::done(StageCheckpoint::new(input.target())))
+                        }
+
+                        let header = provider
+                            .header_by_number(pivot_block)?
+                            .ok_or_else(|| ProviderError::HeaderNotFound(pivot_block.into()))?;
+                        info!(target: "sync::stages::snap_sync", pivot_block, state_root = ?header.state_root, "Starting snap sync");
+
+                        let progress = SnapSyncCheckpoint {
+                            pivot_block,
+                            state_root: header.state_root,
+                            next_account: B256::ZERO,
+                            accounts_complete: false,
+                        };
+                        self.save_progress(provider, Some(progress))?;
+                        progress
+                    }
+                };
+                self.progress = Some(progress);
+                return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+            }
+        };
+
+        if self.is_stale() {
+            return self.repivot(provider, input, progress)
+        }
+
+        if let Some(response) = self.buffer.take() {
+            self.on_response(provider.tx_ref(), &mut progress, response)?;
+            self.save_progress(provider, Some(progress))?;
+            self.progress = Some(progress);
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        }
+
+        if !self.is_download_complete(&progress) {
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        }
+
+        // all state of the pivot was downloaded, verify it
+        let tx = provider.tx_ref();
+        let missing_codes = missing_bytecodes(tx)?;
+        if !missing_codes.is_empty() {
+            debug!(target: "sync::stages::snap_sync", missing = missing_codes.len(), "Downloading missing bytecodes");
+            self.code_tasks.extend(missing_codes);
+            self.save_progress(provider, Some(progress))?;
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        }
+
+        tx.clear::<tables::AccountsTrie>()?;
+        tx.clear::<tables::StoragesTrie>()?;
+        let (root, updates) = StateRoot::from_tx(tx)
+            .root_with_updates()
+            .map_err(|e| StageError::Fatal(Box::new(e)))?;
+        updates.flush(tx)?;
+
+        if root != progress.state_root {
+            self.heal_rounds += 1;
+            if self.heal_rounds > MAX_HEAL_ROUNDS {
+                warn!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, "Failed to heal the pivot state");
+                self.empty_responses = STALE_PIVOT_THRESHOLD;
+                return self.repivot(provider, input, progress)
+            }
+
+            debug!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, got = ?root, expected = ?progress.state_root, "State root mismatch, healing the trie");
+            self.heal_tasks.push_back((Nibbles::default(), progress.state_root));
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        }
+
+        // the hashed state and the trie are complete at the pivot, the blocks after it are executed
+        // on top of it
+        for stage_id in [
+            StageId::Execution,
+            StageId::AccountHashing,
+            StageId::StorageHashing,
+            StageId::MerkleExecute,
+        ] {
+            provider.save_stage_checkpoint(stage_id, StageCheckpoint::new(progress.pivot_block))?;
+        }
+
+        // the blocks up to the pivot are never executed, so their change sets and receipts are
+        // treated as pruned
+        let last_tx_number =
+            provider.block_body_indices(progress.pivot_block)?.map(|indices| indices.last_tx_num());
+        for segment in
+            [PruneSegment::AccountHistory, PruneSegment::StorageHistory, PruneSegment::Receipts]
+        {
+            let pruned = provider
+                .get_prune_checkpoint(segment)?
+                .and_then(|checkpoint| checkpoint.block_number)
+                .is_some_and(|block_number| block_number >= progress.pivot_block);
+            if !pruned {
+                let checkpoint = PruneCheckpoint {
+                    block_number: Some(progress.pivot_block),
+                    tx_number: (segment == PruneSegment::Receipts)
+                        .then_some(last_tx_number)
+                        .flatten(),
+                    prune_mode: PruneMode::Before(progress.pivot_block + 1),
+                };
+                provider.save_prune_checkpoint(segment, checkpoint)?;
+            }
+        }
+
+        // the progress is kept to mark the plain state as incomplete
+        self.save_progress(provider, Some(progress))?;
+        self.reset();
+
+        info!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, state_root = ?root, "Snap sync finished");
+        Ok(ExecOutput::done(StageCheckpoint::new(input.target())))
+    }
+
+    /// Unwind the stage.
+    fn unwind(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        if let Some((progress, _)) = self.get_progress(provider)? {
+            if input.unwind_to < progress.pivot_block {
+                warn!(target: "sync::stages::snap_sync", pivot_block = progress.pivot_block, unwind_to = input.unwind_to, "Unwinding below the snap sync pivot, picking a new pivot");
+                self.save_progress(provider, None)?;
+                self.reset();
+            }
+        }
+
+        Ok(UnwindOutput { checkpoint: StageCheckpoint::new(input.unwind_to) })
+    }
+}
+
+impl<C> Debug for SnapSyncStage<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapSyncStage")
+            .field("pivot_distance", &self.pivot_distance)
+            .field("response_bytes", &self.response_bytes)
+            .field("progress", &self.progress)
+            .field("storage_tasks", &self.storage_tasks.len())
+            .field("code_tasks", &self.code_tasks.len())
+            .field("heal_tasks", &self.heal_tasks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A downloaded response.
+#[derive(Debug)]
+enum SnapResponse {
+    Accounts { peer_id: PeerId, response: AccountRangeMessage },
+    Storages { peer_id: PeerId, tasks: Vec<SnapStorageTask>, response: StorageRangesMessage },
+    ByteCodes { peer_id: PeerId, response: ByteCodesMessage },
+    TrieNodes { peer_id: PeerId, response: TrieNodesMessage },
+}
+
+/// The future of a request in flight.
+type SnapResponseFut =
+    Pin<Box<dyn Future<Output = Result<SnapResponse, RequestError>> + Send + Sync>>;
+
+/// A node of the account trie with the hashes of its children.
+#[derive(Debug, PartialEq, Eq)]
+enum TrieNode {
+    Branch([Option<B256>; 16]),
+    Extension(Nibbles, B256),
+    Leaf(Nibbles, Bytes),
+}
+
+impl TrieNode {
+    /// Decodes a RLP encoded trie node.
+    fn decode(mut buf: &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(&mut buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+        if buf.len() < header.payload_length {
+            return Err(alloy_rlp::Error::InputTooShort)
+        }
+
+        let mut payload = &buf[..header.payload_length];
+        let mut items = Vec::with_capacity(17);
+        while !payload.is_empty() {
+            items.push(decode_string(&mut payload)?);
+        }
+
+        match items.len() {
+            17 => {
+                let mut children = [None; 16];
+                for (child, item) in children.iter_mut().zip(items) {
+                    *child = match item.len() {
+                        0 => None,
+                        32 => Some(B256::from_slice(item)),
+                        _ => return Err(alloy_rlp::Error::Custom("unexpected child reference")),
+                    };
+                }
+                Ok(Self::Branch(children))
+            }
+            2 => {
+                let (key, is_leaf) = decode_compact_path(items[0])?;
+                if is_leaf {
+                    Ok(Self::Leaf(key, Bytes::copy_from_slice(items[1])))
+                } else if items[1].len() == 32 {
+                    Ok(Self::Extension(key, B256::from_slice(items[1])))
+                } else {
+                    Err(alloy_rlp::Error::Custom("unexpected child reference"))
+                }
+            }
+            _ => Err(alloy_rlp::Error::Custom("invalid trie node")),
+        }
+    }
+}
+
+/// Decodes the payload of a RLP string.
+fn decode_string<'a>(buf: &mut &'a [u8]) -> alloy_rlp::Result<&'a [u8]> {
+    let header = Header::decode(buf)?;
+    if header.list {
+        return Err(alloy_rlp::Error::UnexpectedList)
+    }
+    if buf.len() < header.payload_length {
+        return Err(alloy_rlp::Error::InputTooShort)
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+    Ok(payload)
+}
+
+/// Decodes a compact (hex-prefix) encoded path and whether it belongs to a leaf.
+fn decode_compact_path(compact: &[u8]) -> alloy_rlp::Result<(Nibbles, bool)> {
+    let (&first, rest) = compact.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(alloy_rlp::Error::Custom("invalid path flag"))
+    }
+
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Ok((Nibbles::from_nibbles_unchecked(nibbles), flag & 2 == 2))
+}
+
+/// Encodes the path of an account trie node in compact (hex-prefix) encoding.
+fn compact_path(path: &[u8]) -> Bytes {
+    let mut compact = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        compact.push(0x10 | path[0]);
+        &path[1..]
+    } else {
+        compact.push(0x00);
+        path
+    };
+    compact.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    compact.into()
+}
+
+/// Decodes a RLP encoded account of the state trie.
+fn decode_trie_account(mut buf: &[u8]) -> alloy_rlp::Result<(Account, B256)> {
+    let header = Header::decode(&mut buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString)
+    }
+    let nonce = u64::decode(&mut buf)?;
+    let balance = U256::decode(&mut buf)?;
+    let storage_root = B256::decode(&mut buf)?;
+    let code_hash = B256::decode(&mut buf)?;
+    let bytecode_hash = (code_hash != KECCAK_EMPTY).then_some(code_hash);
+    Ok((Account { nonce, balance, bytecode_hash }, storage_root))
+}
+
+/// A subtrie or leaf next to a proven range.
+#[derive(Debug)]
+enum BoundaryNode {
+    /// The hash of a subtrie.
+    Hash(B256),
+    /// The value of a leaf.
+    Leaf(Bytes),
+}
+
+/// Verifies a range of trie leaves, sorted by key and starting at the origin, against the root.
+///
+/// The proof contains the trie nodes on the paths to the origin and to the last leaf of the range.
+/// The root is rebuilt from the subtries and leaves left and right of these paths, which the proof
+/// nodes reference, and the leaves of the range in between. A range without a proof therefore has
+/// to contain all leaves of the trie.
+///
+/// Returns whether the trie has more leaves after the range, or `None` if the range doesn't match
+/// the root.
+fn verify_range_proof(
+    root: B256,
+    origin: B256,
+    leaves: &[(B256, Bytes)],
+    proof: &[Bytes],
+) -> Option<bool> {
+    let sorted = leaves.windows(2).all(|pair| pair[0].0 < pair[1].0);
+    if !sorted || leaves.first().map_or(false, |(key, _)| *key < origin) {
+        return None
+    }
+
+    let nodes: HashMap<_, _> = proof.iter().map(|node| (keccak256(node), node)).collect();
+    let origin = Nibbles::unpack(origin);
+    let last = leaves.last().map_or_else(|| origin.clone(), |(key, _)| Nibbles::unpack(key));
+
+    let mut boundary = BTreeMap::new();
+    collect_boundary(&nodes, root, &origin, Ordering::Less, &mut boundary)?;
+    let mut right = BTreeMap::new();
+    collect_boundary(&nodes, root, &last, Ordering::Greater, &mut right)?;
+    let has_more = !right.is_empty();
+
+    boundary.extend(right);
+    boundary.extend(
+        leaves.iter().map(|(key, value)| (Nibbles::unpack(key), BoundaryNode::Leaf(value.clone()))),
+    );
+
+    let mut hash_builder = HashBuilder::default();
+    for (path, node) in boundary {
+        match node {
+            BoundaryNode::Hash(hash) => hash_builder.add_branch(path, hash, false),
+            BoundaryNode::Leaf(value) => hash_builder.add_leaf(path, &value),
+        }
+    }
+    (hash_builder.root() == root).then_some(has_more)
+}
+
+/// Walks the proof nodes from the root along the path to the bound and collects the subtries and
+/// leaves that are entirely on the given side of it.
+///
+/// The walk stops at the first node that is not part of the proof. Returns `None` if a proof node
+/// is invalid.
+fn collect_boundary(
+    nodes: &HashMap<B256, &Bytes>,
+    root: B256,
+    bound: &[u8],
+    side: Ordering,
+    boundary: &mut BTreeMap<Nibbles, BoundaryNode>,
+) -> Option<()> {
+    let mut path = Nibbles::default();
+    let mut hash = root;
+    while let Some(node) = nodes.get(&hash) {
+        match TrieNode::decode(node).ok()? {
+            TrieNode::Branch(children) => {
+                let next = *bound.get(path.len())?;
+                for (nibble, child) in children.into_iter().enumerate() {
+                    if let Some(child) = child.filter(|_| (nibble as u8).cmp(&next) == side) {
+                        boundary
+                            .insert(join_path(&path, &[nibble as u8]), BoundaryNode::Hash(child));
+                    }
+                }
+                let Some(child) = children[next as usize] else { break };
+                path = join_path(&path, &[next]);
+                hash = child;
+            }
+            TrieNode::Extension(key, child) => {
+                let child_path = join_path(&path, &key);
+                if child_path.len() >= bound.len() {
+                    return None
+                }
+                match child_path[..].cmp(&bound[..child_path.len()]) {
+                    Ordering::Equal => {
+                        path = child_path;
+                        hash = child;
+                    }
+                    ordering => {
+                        if ordering == side {
+                            boundary.insert(child_path, BoundaryNode::Hash(child));
+                        }
+                        break
+                    }
+                }
+            }
+            TrieNode::Leaf(key, value) => {
+                let full_path = join_path(&path, &key);
+                if full_path.len() != bound.len() {
+                    return None
+                }
+                if full_path[..].cmp(bound) == side {
+                    boundary.insert(full_path, BoundaryNode::Leaf(value));
+                }
+                break
+            }
+        }
+    }
+    Some(())
+}
+
+/// Joins two paths of nibbles.
+fn join_path(path: &[u8], suffix: &[u8]) -> Nibbles {
+    let mut joined = Vec::with_capacity(path.len() + suffix.len());
+    joined.extend_from_slice(path);
+    joined.extend_from_slice(suffix);
+    Nibbles::from_nibbles_unchecked(joined)
+}
+
+/// Returns the hash that follows the given one, if any.
+fn increment_hash(hash: B256) -> Option<B256> {
+    U256::from_be_bytes(hash.0).checked_add(U256::from(1)).map(B256::from)
+}
+
+/// Returns the bytecodes of the hashed accounts that are missing.
+fn missing_bytecodes<TX: DbTx>(tx: &TX) -> Result<Vec<B256>, DatabaseError> {
+    let mut missing = HashSet::new();
+    let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+    for entry in cursor.walk(None)? {
+        let (_, account) = entry?;
+        if let Some(code_hash) = account.bytecode_hash.filter(|hash| *hash != KECCAK_EMPTY) {
+            if !missing.contains(&code_hash) && tx.get::<tables::Bytecodes>(code_hash)?.is_none() {
+                missing.insert(code_hash);
+            }
+        }
+    }
+    Ok(missing.into_iter().collect())
+}
+
+/// Deletes the storage of the account.
+fn clear_storage<TX: DbTxMut>(tx: &TX, hashed_address: B256) -> Result<(), DatabaseError> {
+    let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>()?;
+    if cursor.seek_exact(hashed_address)?.is_some() {
+        cursor.delete_current_duplicates()?;
+    }
+    Ok(())
+}
+
+/// Deletes the accounts and their storage in the given range, except for the kept ones.
+fn delete_accounts_in_range<TX: DbTx + DbTxMut>(
+    tx: &TX,
+    start: B256,
+    end: B256,
+    keep: impl Fn(&B256) -> bool,
+) -> Result<(), DatabaseError> {
+    let mut stale = Vec::new();
+    let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+    for entry in cursor.walk_range(start..=end)? {
+        let (hashed_address, _) = entry?;
+        if !keep(&hashed_address) {
+            stale.push(hashed_address);
+        }
+    }
+
+    for hashed_address in stale {
+        tx.delete::<tables::HashedAccount>(hashed_address, None)?;
+        clear_storage(tx, hashed_address)?;
+    }
+    Ok(())
+}
+
+/// Deletes the accounts and their storage below the given trie path, except for the kept ones.
+fn delete_accounts_with_prefix<TX: DbTx + DbTxMut>(
+    tx: &TX,
+    prefix: &[u8],
+    keep: impl Fn(&Nibbles) -> bool,
+) -> Result<(), DatabaseError> {
+    let bound = |fill: u8| {
+        let mut nibbles = prefix.to_vec();
+        nibbles.resize(64, fill);
+        B256::from_slice(&Nibbles::from_nibbles_unchecked(nibbles).pack())
+    };
+    delete_accounts_in_range(tx, bound(0x0), bound(0xf), |hashed_address| {
+        keep(&Nibbles::unpack(hashed_address))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::TestStageDB, StageExt};
+    use alloy_rlp::Encodable;
+    use reth_db::{cursor::DbDupCursorRO, test_utils::TempDatabase, DatabaseEnv};
+    use reth_interfaces::{
+        p2p::{download::DownloadClient, error::PeerRequestResult, snap::SnapFut},
+        test_utils::generators::{self, random_header},
+    };
+    use reth_primitives::{Address, WithPeerId};
+    use reth_provider::{
+        has_snap_synced_state, AccountReader, LatestStateProviderRef, ProviderFactory,
+        StateProvider,
+    };
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// Serves the state of a database. Account ranges contain at most two accounts.
+    #[derive(Debug, Clone)]
+    struct TestSnapClient {
+        factory: ProviderFactory<Arc<TempDatabase<DatabaseEnv>>>,
+        /// Accounts that are served with a different value in account ranges.
+        outdated: HashMap<B256, Account>,
+        bad_messages: Arc<AtomicUsize>,
+    }
+
+    impl TestSnapClient {
+        fn respond<T: Send + Sync + 'static>(data: T) -> SnapFut<T> {
+            let res: PeerRequestResult<T> = Ok(WithPeerId::new(PeerId::ZERO, data));
+            Box::pin(futures_util::future::ready(res))
+        }
+    }
+
+    impl DownloadClient for TestSnapClient {
+        fn report_bad_message(&self, _peer_id: PeerId) {
+            self.bad_messages.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn num_connected_peers(&self) -> usize {
+            1
+        }
+    }
+
+    impl SnapClient for TestSnapClient {
+        fn get_account_range(
+            &self,
+            request: GetAccountRangeMessage,
+        ) -> SnapFut<AccountRangeMessage> {
+            let provider = self.factory.provider().unwrap();
+            let tx = provider.tx_ref();
+            let proof = Proof::new(tx);
+            let mut cursor = tx.cursor_read::<tables::HashedAccount>().unwrap();
+            let accounts: Vec<_> = cursor
+                .walk(Some(request.starting_hash))
+                .unwrap()
+                .take(2)
+                .map(|entry| {
+                    let (hashed_address, account) = entry.unwrap();
+                    let account = self.outdated.get(&hashed_address).copied().unwrap_or(account);
+                    let storage_root = proof.storage_root(hashed_address).unwrap();
+                    reth_eth_wire::AccountData::new(hashed_address, account, storage_root)
+                })
+                .collect();
+
+            let mut targets = vec![Nibbles::unpack(request.starting_hash)];
+            targets.extend(accounts.last().map(|account| Nibbles::unpack(account.hash)));
+            let (_, nodes) = proof.account_multiproof(targets).unwrap();
+            Self::respond(AccountRangeMessage {
+                request_id: request.request_id,
+                accounts,
+                proof: nodes.into_values().collect(),
+            })
+        }
+
+        fn get_storage_ranges(
+            &self,
+            request: GetStorageRangesMessage,
+        ) -> SnapFut<StorageRangesMessage> {
+            let provider = self.factory.provider().unwrap();
+            let mut cursor = provider.tx_ref().cursor_dup_read::<tables::HashedStorage>().unwrap();
+            let slots = request
+                .account_hashes
+                .iter()
+                .map(|hashed_address| {
+                    cursor
+                        .walk_dup(Some(*hashed_address), None)
+                        .unwrap()
+                        .map(|entry| {
+                            let (_, entry) = entry.unwrap();
+                            let data = alloy_rlp::encode(entry.value).into();
+                            reth_eth_wire::StorageData { hash: entry.key, data }
+                        })
+                        .collect()
+                })
+                .collect();
+            Self::respond(StorageRangesMessage {
+                request_id: request.request_id,
+                slots,
+                proof: vec![],
+            })
+        }
+
+        fn get_byte_codes(&self, request: GetByteCodesMessage) -> SnapFut<ByteCodesMessage> {
+            let provider = self.factory.provider().unwrap();
+            let codes = request
+                .hashes
+                .iter()
+                .filter_map(|hash| provider.tx_ref().get::<tables::Bytecodes>(*hash).unwrap())
+                .map(|code| code.original_bytes())
+                .collect();
+            Self::respond(ByteCodesMessage { request_id: request.request_id, codes })
+        }
+
+        fn get_trie_nodes(&self, request: GetTrieNodesMessage) -> SnapFut<TrieNodesMessage> {
+            let provider = self.factory.provider().unwrap();
+            let paths: Vec<_> = request
+                .paths
+                .iter()
+                .map(|set| decode_compact_path(&set.paths[0]).unwrap().0)
+                .collect();
+            let (_, nodes) =
+                Proof::new(provider.tx_ref()).account_multiproof(paths.clone()).unwrap();
+            let nodes = paths.iter().map(|path| nodes[path].clone()).collect();
+            Self::respond(TrieNodesMessage { request_id: request.request_id, nodes })
+        }
+    }
+
+    /// Populates the source database and returns its state root.
+    fn seed_source(source: &TestStageDB) -> B256 {
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55]);
+        let code_hash = keccak256(&code);
+        source
+            .commit(|tx| Ok(tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code))?))
+            .unwrap();
+
+        let accounts = (0..7u64).map(|idx| {
+            let account = Account {
+                nonce: idx,
+                balance: U256::from(idx * 1_000),
+                bytecode_hash: (idx % 3 == 0).then_some(code_hash),
+            };
+            let storage = (0..idx * 2).map(|slot| StorageEntry {
+                key: B256::with_last_byte(slot as u8),
+                value: U256::from(slot + 1),
+            });
+            (Address::with_last_byte(idx as u8), (account, storage.collect::<Vec<_>>()))
+        });
+        source.insert_accounts_and_storages(accounts).unwrap();
+
+        source.query(|tx| Ok(StateRoot::from_tx(tx).root().unwrap())).unwrap()
+    }
+
+    /// Runs a single execution of the stage.
+    async fn step(
+        db: &TestStageDB,
+        stage: &mut SnapSyncStage<TestSnapClient>,
+        input: ExecInput,
+    ) -> ExecOutput {
+        stage.execute_ready(input).await.unwrap();
+        let provider = db.factory.provider_rw().unwrap();
+        let output = stage.execute(&provider, input).unwrap();
+        provider.commit().unwrap();
+        output
+    }
+
+    /// Runs the stage to completion.
+    async fn run_stage(
+        db: &TestStageDB,
+        stage: &mut SnapSyncStage<TestSnapClient>,
+        target: u64,
+    ) -> ExecOutput {
+        let mut input = ExecInput { target: Some(target), checkpoint: None };
+        loop {
+            let output = step(db, stage, input).await;
+            input.checkpoint = Some(output.checkpoint);
+            if output.done {
+                return output
+            }
+        }
+    }
+
+    fn insert_pivot(db: &TestStageDB, number: u64, state_root: B256) {
+        let mut rng = generators::rng();
+        let mut header = random_header(&mut rng, number, None).unseal();
+        header.state_root = state_root;
+        db.insert_headers(std::iter::once(&header.seal_slow())).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sync_pivot_state() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+
+        let db = TestStageDB::default();
+        insert_pivot(&db, 10, state_root);
+
+        let client = TestSnapClient {
+            factory: source.factory.clone(),
+            outdated: HashMap::new(),
+            bad_messages: Default::default(),
+        };
+        let mut stage = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+
+        let output = run_stage(&db, &mut stage, 10).await;
+        assert_eq!(output, ExecOutput::done(StageCheckpoint::new(10)));
+        assert_eq!(client.bad_messages.load(Ordering::Relaxed), 0);
+
+        assert_eq!(
+            db.table::<tables::HashedAccount>().unwrap(),
+            source.table::<tables::HashedAccount>().unwrap()
+        );
+        assert_eq!(
+            db.table::<tables::HashedStorage>().unwrap(),
+            source.table::<tables::HashedStorage>().unwrap()
+        );
+        assert_eq!(
+            db.table::<tables::Bytecodes>().unwrap(),
+            source.table::<tables::Bytecodes>().unwrap()
+        );
+
+        // the blocks after the pivot are executed on top of the downloaded state
+        let provider = db.factory.provider_rw().unwrap();
+        for stage_id in [
+            StageId::Execution,
+            StageId::AccountHashing,
+            StageId::StorageHashing,
+            StageId::MerkleExecute,
+        ] {
+            assert_eq!(
+                provider.get_stage_checkpoint(stage_id).unwrap(),
+                Some(StageCheckpoint::new(10))
+            );
+        }
+        assert_eq!(provider.get_stage_checkpoint(StageId::IndexAccountHistory).unwrap(), None);
+        for segment in
+            [PruneSegment::AccountHistory, PruneSegment::StorageHistory, PruneSegment::Receipts]
+        {
+            assert_eq!(
+                provider.get_prune_checkpoint(segment).unwrap().and_then(|c| c.block_number),
+                Some(10)
+            );
+        }
+
+        // the pivot is kept and the state that isn't in the plain state is read from the hashed
+        // state
+        let (progress, tasks) = stage.get_progress(&provider).unwrap().unwrap();
+        assert_eq!((progress.pivot_block, progress.state_root), (10, state_root));
+        assert_eq!(tasks, SnapSyncTasks::default());
+        assert!(has_snap_synced_state(provider.tx_ref()).unwrap());
+
+        let source_provider = source.factory.provider().unwrap();
+        let source_state = LatestStateProviderRef::new(source_provider.tx_ref());
+        let state = LatestStateProviderRef::new(provider.tx_ref());
+        for idx in 0..7u8 {
+            let address = Address::with_last_byte(idx);
+            assert_eq!(state.basic_account(address), source_state.basic_account(address));
+            let slot = B256::with_last_byte(idx);
+            assert_eq!(state.storage(address, slot), source_state.storage(address, slot));
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_pending_downloads() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+
+        let db = TestStageDB::default();
+        insert_pivot(&db, 10, state_root);
+
+        let client = TestSnapClient {
+            factory: source.factory.clone(),
+            outdated: HashMap::new(),
+            bad_messages: Default::default(),
+        };
+        let mut stage = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+
+        // download account ranges until storage or bytecodes are pending
+        let input = ExecInput { target: Some(10), checkpoint: None };
+        while stage.storage_tasks.is_empty() && stage.code_tasks.is_empty() {
+            assert!(!step(&db, &mut stage, input).await.done);
+        }
+
+        // the pending downloads are restored after a restart
+        let mut restarted = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+        assert!(!step(&db, &mut restarted, input).await.done);
+        assert_eq!(restarted.progress, stage.progress);
+        assert_eq!(restarted.storage_tasks, stage.storage_tasks);
+        assert_eq!(restarted.code_tasks, stage.code_tasks);
+
+        let output = run_stage(&db, &mut restarted, 10).await;
+        assert_eq!(output, ExecOutput::done(StageCheckpoint::new(10)));
+        assert_eq!(client.bad_messages.load(Ordering::Relaxed), 0);
+
+        assert_eq!(
+            db.table::<tables::HashedStorage>().unwrap(),
+            source.table::<tables::HashedStorage>().unwrap()
+        );
+        assert_eq!(
+            db.table::<tables::Bytecodes>().unwrap(),
+            source.table::<tables::Bytecodes>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn repivot_with_pending_storage() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+
+        let db = TestStageDB::default();
+        insert_pivot(&db, 10, state_root);
+
+        let client = TestSnapClient {
+            factory: source.factory.clone(),
+            outdated: HashMap::new(),
+            bad_messages: Default::default(),
+        };
+        let mut stage = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+
+        // download account ranges until storage is pending
+        let input = ExecInput { target: Some(10), checkpoint: None };
+        while stage.storage_tasks.is_empty() {
+            assert!(!step(&db, &mut stage, input).await.done);
+        }
+
+        // the storage of the pending account changes at the next pivot, which the peers serve
+        // from now on
+        let pending = stage.storage_tasks[0].account;
+        source
+            .commit(|tx| {
+                Ok(tx.put::<tables::HashedStorage>(
+                    pending,
+                    StorageEntry { key: B256::repeat_byte(0xaa), value: U256::from(1) },
+                )?)
+            })
+            .unwrap();
+        let new_root = source.query(|tx| Ok(StateRoot::from_tx(tx).root().unwrap())).unwrap();
+        assert_ne!(new_root, state_root);
+        insert_pivot(&db, 20, new_root);
+        stage.empty_responses = STALE_PIVOT_THRESHOLD;
+
+        let output = run_stage(&db, &mut stage, 20).await;
+        assert_eq!(output, ExecOutput::done(StageCheckpoint::new(20)));
+        assert_eq!(client.bad_messages.load(Ordering::Relaxed), 0);
+
+        let provider = db.factory.provider_rw().unwrap();
+        let (progress, _) = stage.get_progress(&provider).unwrap().unwrap();
+        assert_eq!((progress.pivot_block, progress.state_root), (20, new_root));
+        assert_eq!(
+            db.table::<tables::HashedAccount>().unwrap(),
+            source.table::<tables::HashedAccount>().unwrap()
+        );
+        assert_eq!(
+            db.table::<tables::HashedStorage>().unwrap(),
+            source.table::<tables::HashedStorage>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_account_range() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+
+        let db = TestStageDB::default();
+        insert_pivot(&db, 10, state_root);
+
+        // the first account is served with a value that doesn't match the proof
+        let (outdated_address, outdated) = source.table::<tables::HashedAccount>().unwrap()[0];
+        let client = TestSnapClient {
+            factory: source.factory.clone(),
+            outdated: HashMap::from([(
+                outdated_address,
+                Account { balance: outdated.balance + U256::from(1), ..outdated },
+            )]),
+            bad_messages: Default::default(),
+        };
+        let mut stage = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+
+        // the first execution only picks the pivot
+        let input = ExecInput { target: Some(10), checkpoint: None };
+        for _ in 0..3 {
+            assert!(!step(&db, &mut stage, input).await.done);
+        }
+
+        assert_eq!(client.bad_messages.load(Ordering::Relaxed), 2);
+        assert!(db.table::<tables::HashedAccount>().unwrap().is_empty());
+        assert_eq!(stage.progress.map(|progress| progress.next_account), Some(B256::ZERO));
+    }
+
+    #[tokio::test]
+    async fn heal_missing_storage() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+
+        let db = TestStageDB::default();
+        insert_pivot(&db, 10, state_root);
+
+        // the accounts were downloaded without their storage, and an account that no longer
+        // exists is present locally
+        let mut accounts = source.table::<tables::HashedAccount>().unwrap();
+        accounts.push((B256::repeat_byte(0xfe), Account::default()));
+        db.commit(|tx| {
+            for (hashed_address, account) in accounts {
+                tx.put::<tables::HashedAccount>(hashed_address, account)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let client = TestSnapClient {
+            factory: source.factory.clone(),
+            outdated: HashMap::new(),
+            bad_messages: Default::default(),
+        };
+        let mut stage = SnapSyncStage::new(client.clone(), 0, SNAP_SYNC_DEFAULT_RESPONSE_BYTES);
+        let provider = db.factory.provider_rw().unwrap();
+        let progress = SnapSyncCheckpoint {
+            pivot_block: 10,
+            state_root,
+            next_account: B256::ZERO,
+            accounts_complete: true,
+        };
+        stage.save_progress(&provider, Some(progress)).unwrap();
+        provider.commit().unwrap();
+
+        let output = run_stage(&db, &mut stage, 10).await;
+        assert_eq!(output, ExecOutput::done(StageCheckpoint::new(10)));
+        assert_eq!(client.bad_messages.load(Ordering::Relaxed), 0);
+
+        assert_eq!(
+            db.table::<tables::HashedAccount>().unwrap(),
+            source.table::<tables::HashedAccount>().unwrap()
+        );
+        assert_eq!(
+            db.table::<tables::HashedStorage>().unwrap(),
+            source.table::<tables::HashedStorage>().unwrap()
+        );
+    }
+
+    #[test]
+    fn verify_account_range_proofs() {
+        let source = TestStageDB::default();
+        let state_root = seed_source(&source);
+        let accounts = source.table::<tables::HashedAccount>().unwrap();
+
+        source
+            .query(|tx| {
+                let proof = Proof::new(tx);
+                let leaves: Vec<_> = accounts
+                    .iter()
+                    .map(|(hashed_address, account)| {
+                        let storage_root = proof.storage_root(*hashed_address).unwrap();
+                        let leaf = alloy_rlp::encode(TrieAccount::from((*account, storage_root)));
+                        (*hashed_address, Bytes::from(leaf))
+                    })
+                    .collect();
+
+                // a range in the middle of the trie
+                let range = &leaves[2..5];
+                let targets = vec![Nibbles::unpack(range[0].0), Nibbles::unpack(range[2].0)];
+                let (_, nodes) = proof.account_multiproof(targets).unwrap();
+                let nodes: Vec<_> = nodes.into_values().collect();
+                assert_eq!(verify_range_proof(state_root, range[0].0, range, &nodes), Some(true));
+
+                // a withheld account is detected
+                let withheld = [range[0].clone(), range[2].clone()];
+                assert_eq!(verify_range_proof(state_root, range[0].0, &withheld, &nodes), None);
+
+                // a range without a proof has to contain the whole trie
+                assert_eq!(verify_range_proof(state_root, B256::ZERO, &leaves, &[]), Some(false));
+                assert_eq!(verify_range_proof(state_root, B256::ZERO, &leaves[..6], &[]), None);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn decode_trie_nodes() {
+        let path = Nibbles::from_nibbles_unchecked([0x1, 0x2, 0x3]);
+        let (decoded, is_leaf) = decode_compact_path(&compact_path(&path)).unwrap();
+        assert_eq!(decoded, path);
+        assert!(!is_leaf);
+
+        let mut branch = Vec::new();
+        let children: Vec<&[u8]> =
+            (0..17).map(|idx| if idx == 4 { &[0xab; 32][..] } else { &[][..] }).collect();
+        let payload_length: usize = children.iter().map(|child| child.length()).sum();
+        Header { list: true, payload_length }.encode(&mut branch);
+        children.iter().for_each(|child| child.encode(&mut branch));
+
+        let mut expected = [None; 16];
+        expected[4] = Some(B256::repeat_byte(0xab));
+        assert_eq!(TrieNode::decode(&branch), Ok(TrieNode::Branch(expected)));
+
+        let mut leaf = Vec::new();
+        let key: &[u8] = &[0x20, 0x12];
+        let value: &[u8] = &[0xc0];
+        Header { list: true, payload_length: key.length() + value.length() }.encode(&mut leaf);
+        key.encode(&mut leaf);
+        value.encode(&mut leaf);
+        assert_eq!(
+            TrieNode::decode(&leaf),
+            Ok(TrieNode::Leaf(
+                Nibbles::from_nibbles_unchecked([0x1, 0x2]),
+                Bytes::from_static(&[0xc0])
+            ))
+        );
+    }
+}
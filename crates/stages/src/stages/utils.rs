@@ -0,0 +1,60 @@
+use crate::{ExecInput, StageError};
+use reth_db::database::Database;
+use reth_primitives::{stage::StageCheckpoint, PruneCheckpoint, PruneMode, PruneSegment};
+use reth_provider::{DatabaseProviderRW, PruneCheckpointReader, PruneCheckpointWriter};
+
+/// Moves the checkpoint of an optional index stage past the blocks that won't be indexed, and
+/// records them in the prune checkpoint of the index `segment`. Readers only trust the index above
+/// that checkpoint, see e.g. [`reth_provider::LogIndexReader::logs_indexed_range`].
+///
+/// Blocks are skipped if they are pruned according to the stage's `prune_mode`, or if the data the
+/// index is built from was pruned for any of the `source_segments`.
+pub(crate) fn skip_unindexed_blocks<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    input: &mut ExecInput,
+    segment: PruneSegment,
+    prune_mode: Option<PruneMode>,
+    source_segments: &[PruneSegment],
+) -> Result<(), StageError> {
+    let mut skip_to = None;
+    if let Some((target_prunable_block, prune_mode)) = prune_mode
+        .map(|mode| mode.prune_target_block(input.target(), segment))
+        .transpose()?
+        .flatten()
+    {
+        skip_to = Some((target_prunable_block, prune_mode));
+    }
+    for source_segment in source_segments {
+        let pruned_block = provider
+            .get_prune_checkpoint(*source_segment)?
+            .and_then(|checkpoint| checkpoint.block_number)
+            .map(|block_number| block_number.min(input.target()));
+        if let Some(pruned_block) = pruned_block {
+            if skip_to.map_or(true, |(block_number, _)| pruned_block > block_number) {
+                skip_to = Some((pruned_block, PruneMode::Before(pruned_block + 1)));
+            }
+        }
+    }
+
+    let Some((skip_to, prune_mode)) = skip_to else { return Ok(()) };
+    if skip_to <= input.checkpoint().block_number {
+        return Ok(())
+    }
+    input.checkpoint = Some(StageCheckpoint::new(skip_to));
+
+    // Index segments are pruned by deleting all entries up to the target block, regardless of the
+    // previous prune checkpoint, so moving it forward doesn't leave any blocks unpruned.
+    let prune_checkpoint = provider.get_prune_checkpoint(segment)?;
+    if prune_checkpoint.and_then(|checkpoint| checkpoint.block_number) < Some(skip_to) {
+        provider.save_prune_checkpoint(
+            segment,
+            PruneCheckpoint {
+                block_number: Some(skip_to),
+                tx_number: None,
+                prune_mode: prune_checkpoint.map_or(prune_mode, |checkpoint| checkpoint.prune_mode),
+            },
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,98 +1,218 @@
 //! Mock database
+//!
+//! An in-memory [Database] that mirrors the semantics of the MDBX backend, so it can be used in
+//! place of a database in a temporary directory in tests.
 use crate::{
-    common::{IterPairResult, PairResult, ValueOnlyResult},
+    common::{PairResult, ValueOnlyResult},
     cursor::{
         DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
         ReverseWalker, Walker,
     },
     database::Database,
-    table::{DupSort, Table, TableImporter},
+    table::{Compress, Decode, Decompress, DupSort, Encode, Table, TableImporter},
+    tables::{TableType, Tables},
     transaction::{DbTx, DbTxMut},
     DatabaseError,
 };
 use core::ops::Bound;
-use std::{collections::BTreeMap, ops::RangeBounds};
+use parking_lot::{Condvar, Mutex, RwLock};
+use reth_interfaces::db::{DatabaseWriteError, DatabaseWriteOperation};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+    ops::RangeBounds,
+    str::FromStr,
+    sync::Arc,
+};
 
-/// Mock database used for testing with inner BTreeMap structure
-/// TODO
+/// Error code of MDBX when inserting a key that already exists.
+const KEY_EXIST: i32 = -30799;
+/// Error code of MDBX when a key or value can't be found.
+const NOT_FOUND: i32 = -30798;
+/// Error code of MDBX when appending a key or value out of order.
+const KEY_MISMATCH: i32 = -30418;
+/// Error code of MDBX when writing in a read-only transaction (`EACCES`).
+const ACCESS_DENIED: i32 = 13;
+
+/// An encoded key and compressed value.
+type Entry = (Vec<u8>, Vec<u8>);
+
+/// The entries of a table, ordered by key and then by value like in MDBX.
+///
+/// Tables that are not [DupSort] have at most one entry per key.
+type Entries = BTreeSet<Entry>;
+
+/// The entries of all tables, by table name.
+type Snapshot = BTreeMap<&'static str, Arc<Entries>>;
+
+/// In-memory database used for testing.
+///
+/// Read-only transactions see the data committed when they were opened. Like in MDBX, there can
+/// only be one read-write transaction at a time, opening another one blocks until the first one is
+/// committed or dropped.
+///
+/// Clones share the same data.
 #[derive(Clone, Debug, Default)]
 pub struct DatabaseMock {
-    /// Main data. TODO (Make it table aware)
-    pub data: BTreeMap<Vec<u8>, Vec<u8>>,
+    inner: Arc<DatabaseMockInner>,
+}
+
+#[derive(Debug, Default)]
+struct DatabaseMockInner {
+    /// The committed data.
+    committed: RwLock<Snapshot>,
+    /// Whether a read-write transaction is open.
+    writing: Mutex<bool>,
+    /// Notified when a read-write transaction is closed.
+    writer_closed: Condvar,
 }
 
 impl Database for DatabaseMock {
     type TX = TxMock;
     type TXMut = TxMock;
     fn tx(&self) -> Result<Self::TX, DatabaseError> {
-        Ok(TxMock::default())
+        Ok(TxMock::new(self.inner.committed.read().clone(), None))
     }
 
     fn tx_mut(&self) -> Result<Self::TXMut, DatabaseError> {
-        Ok(TxMock::default())
+        let mut writing = self.inner.writing.lock();
+        while *writing {
+            self.inner.writer_closed.wait(&mut writing);
+        }
+        *writing = true;
+        drop(writing);
+
+        let writer = WriterGuard(self.inner.clone());
+        Ok(TxMock::new(self.inner.committed.read().clone(), Some(writer)))
+    }
+}
+
+/// Marks the read-write transaction of a [DatabaseMock] as closed when dropped.
+#[derive(Debug)]
+struct WriterGuard(Arc<DatabaseMockInner>);
+
+impl Drop for WriterGuard {
+    fn drop(&mut self) {
+        *self.0.writing.lock() = false;
+        self.0.writer_closed.notify_one();
     }
 }
 
-/// Mock read only tx
-#[derive(Debug, Clone, Default)]
+/// Mock transaction, read-only or read-write.
+#[derive(Debug)]
 pub struct TxMock {
-    /// Table representation
-    _table: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The data as seen by this transaction, shared with its cursors.
+    data: Arc<Mutex<Snapshot>>,
+    /// Set if this is a read-write transaction.
+    writer: Option<WriterGuard>,
+}
+
+impl TxMock {
+    fn new(data: Snapshot, writer: Option<WriterGuard>) -> Self {
+        Self { data: Arc::new(Mutex::new(data)), writer }
+    }
+
+    fn new_cursor<T: Table>(&self) -> CursorMock<T> {
+        CursorMock {
+            data: self.data.clone(),
+            writable: self.writer.is_some(),
+            position: None,
+            _table: PhantomData,
+        }
+    }
+
+    fn ensure_writable<T: Table>(
+        &self,
+        operation: DatabaseWriteOperation,
+        key: &[u8],
+    ) -> Result<(), DatabaseError> {
+        ensure_writable::<T>(self.writer.is_some(), operation, key)
+    }
 }
 
 impl DbTx for TxMock {
-    type Cursor<T: Table> = CursorMock;
-    type DupCursor<T: DupSort> = CursorMock;
+    type Cursor<T: Table> = CursorMock<T>;
+    type DupCursor<T: DupSort> = CursorMock<T>;
 
-    fn get<T: Table>(&self, _key: T::Key) -> Result<Option<T::Value>, DatabaseError> {
-        Ok(None)
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, DatabaseError> {
+        let key = encode_key::<T>(key);
+        let data = self.data.lock();
+        let entry = data.get(T::NAME).and_then(|entries| first_of_key(entries, &key));
+        entry.map(|(_, value)| T::Value::decompress(value)).transpose()
     }
 
     fn commit(self) -> Result<bool, DatabaseError> {
+        if let Some(writer) = &self.writer {
+            *writer.0.committed.write() = self.data.lock().clone();
+        }
         Ok(true)
     }
 
     fn abort(self) {}
 
     fn cursor_read<T: Table>(&self) -> Result<Self::Cursor<T>, DatabaseError> {
-        Ok(CursorMock { _cursor: 0 })
+        Ok(self.new_cursor())
     }
 
     fn cursor_dup_read<T: DupSort>(&self) -> Result<Self::DupCursor<T>, DatabaseError> {
-        Ok(CursorMock { _cursor: 0 })
+        Ok(self.new_cursor())
     }
 
     fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
-        Ok(self._table.len())
+        Ok(self.data.lock().get(T::NAME).map_or(0, |entries| entries.len()))
     }
 }
 
 impl DbTxMut for TxMock {
-    type CursorMut<T: Table> = CursorMock;
-    type DupCursorMut<T: DupSort> = CursorMock;
+    type CursorMut<T: Table> = CursorMock<T>;
+    type DupCursorMut<T: DupSort> = CursorMock<T>;
+
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let key = encode_key::<T>(key);
+        self.ensure_writable::<T>(DatabaseWriteOperation::Put, &key)?;
 
-    fn put<T: Table>(&self, _key: T::Key, _value: T::Value) -> Result<(), DatabaseError> {
+        let mut data = self.data.lock();
+        upsert::<T>(table_mut::<T>(&mut data), (key, compress_value::<T>(value)));
         Ok(())
     }
 
     fn delete<T: Table>(
         &self,
-        _key: T::Key,
-        _value: Option<T::Value>,
+        key: T::Key,
+        value: Option<T::Value>,
     ) -> Result<bool, DatabaseError> {
-        Ok(true)
+        if self.writer.is_none() {
+            return Err(DatabaseError::Delete(ACCESS_DENIED))
+        }
+
+        let key = encode_key::<T>(key);
+        let mut data = self.data.lock();
+        let entries = table_mut::<T>(&mut data);
+        let deleted = if let Some(value) = value {
+            entries.remove(&(key, compress_value::<T>(value)))
+        } else {
+            let len = entries.len();
+            entries.retain(|(k, _)| *k != key);
+            entries.len() != len
+        };
+        Ok(deleted)
     }
 
     fn clear<T: Table>(&self) -> Result<(), DatabaseError> {
+        if self.writer.is_none() {
+            return Err(DatabaseError::Delete(ACCESS_DENIED))
+        }
+
+        self.data.lock().remove(T::NAME);
         Ok(())
     }
 
     fn cursor_write<T: Table>(&self) -> Result<Self::CursorMut<T>, DatabaseError> {
-        Ok(CursorMock { _cursor: 0 })
+        Ok(self.new_cursor())
     }
 
     fn cursor_dup_write<T: DupSort>(&self) -> Result<Self::DupCursorMut<T>, DatabaseError> {
-        Ok(CursorMock { _cursor: 0 })
+        Ok(self.new_cursor())
     }
 }
 
@@ -100,43 +220,117 @@ impl TableImporter for TxMock {}
 
 /// Cursor that iterates over table
 #[derive(Debug)]
-pub struct CursorMock {
-    _cursor: u32,
+pub struct CursorMock<T: Table> {
+    /// The data of the transaction the cursor belongs to.
+    data: Arc<Mutex<Snapshot>>,
+    /// Whether the cursor belongs to a read-write transaction.
+    writable: bool,
+    /// The entry the cursor points to.
+    ///
+    /// The entry no longer exists if it was deleted, in which case the cursor effectively points
+    /// to the entry after it.
+    position: Option<Entry>,
+    _table: PhantomData<T>,
+}
+
+impl<T: Table> CursorMock<T> {
+    /// Runs the closure on the entries of the table.
+    fn read<R>(&self, f: impl FnOnce(&Entries, Option<&Entry>) -> R) -> R {
+        let data = self.data.lock();
+        let empty = Entries::new();
+        f(data.get(T::NAME).map_or(&empty, |entries| entries.as_ref()), self.position.as_ref())
+    }
+
+    /// Moves the cursor to the entry found by the closure, if any, and returns it.
+    fn move_to(
+        &mut self,
+        find: impl FnOnce(&Entries, Option<&Entry>) -> Option<Entry>,
+    ) -> PairResult<T> {
+        let entry = self.read(find);
+        if entry.is_some() {
+            self.position = entry.clone();
+        }
+        entry.map(decode_entry::<T>).transpose()
+    }
+
+    /// Moves the cursor to the first entry of the key with a value greater than or equal to the
+    /// subkey.
+    fn seek_key_subkey(&mut self, key: Vec<u8>, subkey: Vec<u8>) -> PairResult<T> {
+        self.move_to(|entries, _| {
+            at_or_after(entries, &(key.clone(), subkey)).filter(|(k, _)| *k == key)
+        })
+    }
+
+    /// Inserts the entry and moves the cursor to it.
+    fn write(&mut self, entry: Entry) {
+        let mut data = self.data.lock();
+        upsert::<T>(table_mut::<T>(&mut data), entry.clone());
+        self.position = Some(entry);
+    }
+
+    /// Removes the entries matching the predicate, if the cursor points to an entry.
+    fn delete(&mut self, matches: impl Fn(&Entry, &Entry) -> bool) -> Result<(), DatabaseError> {
+        if !self.writable {
+            return Err(DatabaseError::Delete(ACCESS_DENIED))
+        }
+
+        let Some(current) = self.read(|entries, position| at_or_after(entries, position?)) else {
+            return Ok(())
+        };
+        let mut data = self.data.lock();
+        table_mut::<T>(&mut data).retain(|entry| !matches(entry, &current));
+        drop(data);
+
+        self.position = Some(current);
+        Ok(())
+    }
 }
 
-impl<T: Table> DbCursorRO<T> for CursorMock {
+impl<T: Table> DbCursorRO<T> for CursorMock<T> {
     fn first(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, _| entries.first().cloned())
     }
 
-    fn seek_exact(&mut self, _key: T::Key) -> PairResult<T> {
-        Ok(None)
+    fn seek_exact(&mut self, key: T::Key) -> PairResult<T> {
+        let key = encode_key::<T>(key);
+        let entry = self.move_to(|entries, _| at_or_after(entries, &(key.clone(), Vec::new())))?;
+        Ok(entry.filter(|_| self.position.as_ref().is_some_and(|(k, _)| *k == key)))
     }
 
-    fn seek(&mut self, _key: T::Key) -> PairResult<T> {
-        Ok(None)
+    fn seek(&mut self, key: T::Key) -> PairResult<T> {
+        let key = encode_key::<T>(key);
+        self.move_to(|entries, _| at_or_after(entries, &(key, Vec::new())))
     }
 
     fn next(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, position| match position {
+            Some(position) => after(entries, position),
+            None => entries.first().cloned(),
+        })
     }
 
     fn prev(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, position| match position {
+            Some(position) => entries.range(..position.clone()).next_back().cloned(),
+            None => entries.last().cloned(),
+        })
     }
 
     fn last(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, _| entries.last().cloned())
     }
 
     fn current(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.read(|entries, position| at_or_after(entries, position?))
+            .map(decode_entry::<T>)
+            .transpose()
     }
 
     fn walk(&mut self, start_key: Option<T::Key>) -> Result<Walker<'_, T, Self>, DatabaseError> {
-        let start: IterPairResult<T> = match start_key {
-            Some(key) => <CursorMock as DbCursorRO<T>>::seek(self, key).transpose(),
-            None => <CursorMock as DbCursorRO<T>>::first(self).transpose(),
+        let start = if let Some(start_key) = start_key {
+            self.seek(start_key).transpose()
+        } else {
+            self.first().transpose()
         };
 
         Ok(Walker::new(self, start))
@@ -146,102 +340,464 @@ impl<T: Table> DbCursorRO<T> for CursorMock {
         &mut self,
         range: impl RangeBounds<T::Key>,
     ) -> Result<RangeWalker<'_, T, Self>, DatabaseError> {
-        let start_key = match range.start_bound() {
-            Bound::Included(key) | Bound::Excluded(key) => Some((*key).clone()),
-            Bound::Unbounded => None,
-        };
-
-        let end_key = match range.end_bound() {
-            Bound::Included(key) | Bound::Excluded(key) => Bound::Included((*key).clone()),
-            Bound::Unbounded => Bound::Unbounded,
-        };
-
-        let start: IterPairResult<T> = match start_key {
-            Some(key) => <CursorMock as DbCursorRO<T>>::seek(self, key).transpose(),
-            None => <CursorMock as DbCursorRO<T>>::first(self).transpose(),
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) => self.seek(key),
+            Bound::Excluded(_key) => {
+                unreachable!("Rust doesn't allow for Bound::Excluded in starting bounds");
+            }
+            Bound::Unbounded => self.first(),
         };
-
-        Ok(RangeWalker::new(self, start, end_key))
+        Ok(RangeWalker::new(self, start.transpose(), range.end_bound().cloned()))
     }
 
     fn walk_back(
         &mut self,
         start_key: Option<T::Key>,
     ) -> Result<ReverseWalker<'_, T, Self>, DatabaseError> {
-        let start: IterPairResult<T> = match start_key {
-            Some(key) => <CursorMock as DbCursorRO<T>>::seek(self, key).transpose(),
-            None => <CursorMock as DbCursorRO<T>>::last(self).transpose(),
-        };
+        let start =
+            if let Some(start_key) = start_key { self.seek(start_key) } else { self.last() }
+                .transpose();
+
         Ok(ReverseWalker::new(self, start))
     }
 }
 
-impl<T: DupSort> DbDupCursorRO<T> for CursorMock {
+impl<T: DupSort> DbDupCursorRO<T> for CursorMock<T> {
     fn next_dup(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, position| {
+            let position = position?;
+            after(entries, position).filter(|(key, _)| *key == position.0)
+        })
     }
 
     fn next_no_dup(&mut self) -> PairResult<T> {
-        Ok(None)
+        self.move_to(|entries, position| match position {
+            Some((key, _)) => {
+                // the smallest key greater than the current one
+                let mut next_key = key.clone();
+                next_key.push(0);
+                at_or_after(entries, &(next_key, Vec::new()))
+            }
+            None => entries.first().cloned(),
+        })
     }
 
     fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
-        Ok(None)
+        Ok(self.next_dup()?.map(|(_, value)| value))
     }
 
     fn seek_by_key_subkey(
         &mut self,
-        _key: <T as Table>::Key,
-        _subkey: <T as DupSort>::SubKey,
+        key: <T as Table>::Key,
+        subkey: <T as DupSort>::SubKey,
     ) -> ValueOnlyResult<T> {
-        Ok(None)
+        let entry = self.seek_key_subkey(encode_key::<T>(key), subkey.encode().into())?;
+        Ok(entry.map(|(_, value)| value))
     }
 
     fn walk_dup(
         &mut self,
-        _key: Option<<T>::Key>,
-        _subkey: Option<<T as DupSort>::SubKey>,
+        key: Option<T::Key>,
+        subkey: Option<T::SubKey>,
     ) -> Result<DupWalker<'_, T, Self>, DatabaseError> {
-        Ok(DupWalker { cursor: self, start: None })
+        let start = match (key, subkey) {
+            (Some(key), Some(subkey)) => {
+                self.seek_key_subkey(encode_key::<T>(key), subkey.encode().into()).transpose()
+            }
+            (Some(key), None) => {
+                let key = encode_key::<T>(key);
+                self.move_to(|entries, _| first_of_key(entries, &key).cloned()).transpose()
+            }
+            (None, Some(subkey)) => {
+                if let Some((key, _)) = self.first()? {
+                    self.seek_key_subkey(encode_key::<T>(key), subkey.encode().into()).transpose()
+                } else {
+                    Some(Err(DatabaseError::Read(NOT_FOUND)))
+                }
+            }
+            (None, None) => self.first().transpose(),
+        };
+
+        Ok(DupWalker { cursor: self, start })
     }
 }
 
-impl<T: Table> DbCursorRW<T> for CursorMock {
+impl<T: Table> DbCursorRW<T> for CursorMock<T> {
+    /// Like in MDBX, `upsert` adds a value to the existing ones of a [DupSort] table.
     fn upsert(
         &mut self,
-        _key: <T as Table>::Key,
-        _value: <T as Table>::Value,
+        key: <T as Table>::Key,
+        value: <T as Table>::Value,
     ) -> Result<(), DatabaseError> {
+        let key = encode_key::<T>(key);
+        ensure_writable::<T>(self.writable, DatabaseWriteOperation::CursorUpsert, &key)?;
+
+        self.write((key, compress_value::<T>(value)));
         Ok(())
     }
 
     fn insert(
         &mut self,
-        _key: <T as Table>::Key,
-        _value: <T as Table>::Value,
+        key: <T as Table>::Key,
+        value: <T as Table>::Value,
     ) -> Result<(), DatabaseError> {
+        let key = encode_key::<T>(key);
+        let operation = DatabaseWriteOperation::CursorInsert;
+        ensure_writable::<T>(self.writable, operation, &key)?;
+
+        if let Some(existing) = self.read(|entries, _| first_of_key(entries, &key).cloned()) {
+            self.position = Some(existing);
+            return Err(write_error::<T>(KEY_EXIST, operation, key))
+        }
+
+        self.write((key, compress_value::<T>(value)));
         Ok(())
     }
 
     fn append(
         &mut self,
-        _key: <T as Table>::Key,
-        _value: <T as Table>::Value,
+        key: <T as Table>::Key,
+        value: <T as Table>::Value,
     ) -> Result<(), DatabaseError> {
+        let key = encode_key::<T>(key);
+        let operation = DatabaseWriteOperation::CursorAppend;
+        ensure_writable::<T>(self.writable, operation, &key)?;
+
+        // the values of the last key of a dupsort table can still be added to
+        let is_dupsort = is_dupsort::<T>();
+        let last = self.read(|entries, _| entries.last().cloned());
+        if let Some(last) = last {
+            if key < last.0 || (key == last.0 && !is_dupsort) {
+                self.position = Some(last);
+                return Err(write_error::<T>(KEY_MISMATCH, operation, key))
+            }
+        }
+
+        self.write((key, compress_value::<T>(value)));
         Ok(())
     }
 
     fn delete_current(&mut self) -> Result<(), DatabaseError> {
-        Ok(())
+        self.delete(|entry, current| entry == current)
     }
 }
 
-impl<T: DupSort> DbDupCursorRW<T> for CursorMock {
+impl<T: DupSort> DbDupCursorRW<T> for CursorMock<T> {
     fn delete_current_duplicates(&mut self) -> Result<(), DatabaseError> {
+        self.delete(|entry, current| entry.0 == current.0)
+    }
+
+    fn append_dup(&mut self, key: <T>::Key, value: <T>::Value) -> Result<(), DatabaseError> {
+        let key = encode_key::<T>(key);
+        let operation = DatabaseWriteOperation::CursorAppendDup;
+        ensure_writable::<T>(self.writable, operation, &key)?;
+
+        let value = compress_value::<T>(value);
+        let last_dup = self.read(|entries, _| {
+            let mut next_key = key.clone();
+            next_key.push(0);
+            entries.range(..(next_key, Vec::new())).next_back().filter(|(k, _)| *k == key).cloned()
+        });
+        if let Some(last_dup) = last_dup {
+            if value <= last_dup.1 {
+                self.position = Some(last_dup);
+                return Err(write_error::<T>(KEY_MISMATCH, operation, key))
+            }
+        }
+
+        self.write((key, value));
         Ok(())
     }
+}
+
+/// Returns `true` if the table is a [DupSort] table.
+fn is_dupsort<T: Table>() -> bool {
+    Tables::from_str(T::NAME).is_ok_and(|table| table.table_type() == TableType::DupSort)
+}
+
+/// Returns the entries of the table for writing, copying them if they are shared with other
+/// transactions.
+fn table_mut<T: Table>(data: &mut Snapshot) -> &mut Entries {
+    Arc::make_mut(data.entry(T::NAME).or_default())
+}
 
-    fn append_dup(&mut self, _key: <T>::Key, _value: <T>::Value) -> Result<(), DatabaseError> {
+/// Inserts the entry, replacing the existing value of its key unless the table is [DupSort].
+fn upsert<T: Table>(entries: &mut Entries, entry: Entry) {
+    if !is_dupsort::<T>() {
+        if let Some(existing) = first_of_key(entries, &entry.0).cloned() {
+            entries.remove(&existing);
+        }
+    }
+    entries.insert(entry);
+}
+
+/// Returns the first entry of the key.
+fn first_of_key<'a>(entries: &'a Entries, key: &[u8]) -> Option<&'a Entry> {
+    entries.range((key.to_vec(), Vec::new())..).next().filter(|(k, _)| k == key)
+}
+
+/// Returns the first entry greater than or equal to the given one.
+fn at_or_after(entries: &Entries, entry: &Entry) -> Option<Entry> {
+    entries.range(entry.clone()..).next().cloned()
+}
+
+/// Returns the first entry greater than the given one.
+fn after(entries: &Entries, entry: &Entry) -> Option<Entry> {
+    entries.range((Bound::Excluded(entry.clone()), Bound::Unbounded)).next().cloned()
+}
+
+fn encode_key<T: Table>(key: T::Key) -> Vec<u8> {
+    key.encode().into()
+}
+
+fn compress_value<T: Table>(value: T::Value) -> Vec<u8> {
+    value.compress().as_ref().to_vec()
+}
+
+fn decode_entry<T: Table>((key, value): Entry) -> Result<(T::Key, T::Value), DatabaseError> {
+    Ok((T::Key::decode(key)?, T::Value::decompress(value)?))
+}
+
+fn ensure_writable<T: Table>(
+    writable: bool,
+    operation: DatabaseWriteOperation,
+    key: &[u8],
+) -> Result<(), DatabaseError> {
+    if writable {
         Ok(())
+    } else {
+        Err(write_error::<T>(ACCESS_DENIED, operation, key.to_vec()))
+    }
+}
+
+fn write_error<T: Table>(
+    code: i32,
+    operation: DatabaseWriteOperation,
+    key: Vec<u8>,
+) -> DatabaseError {
+    DatabaseWriteError { code, operation, table_name: T::NAME, key }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::AccountBeforeTx,
+        tables::{AccountChangeSet, CanonicalHeaders, PlainStorageState},
+    };
+    use reth_primitives::{Address, StorageEntry, B256, U256};
+
+    fn with_headers(keys: impl IntoIterator<Item = u64>) -> DatabaseMock {
+        let db = DatabaseMock::default();
+        let tx = db.tx_mut().unwrap();
+        for key in keys {
+            tx.put::<CanonicalHeaders>(key, B256::with_last_byte(key as u8)).unwrap();
+        }
+        tx.commit().unwrap();
+        db
+    }
+
+    fn storage_entry(slot: u8, value: u64) -> StorageEntry {
+        StorageEntry { key: B256::with_last_byte(slot), value: U256::from(value) }
+    }
+
+    #[test]
+    fn put_get_delete() {
+        let db = with_headers([1]);
+
+        let tx = db.tx_mut().unwrap();
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(Some(B256::with_last_byte(1))));
+        tx.put::<CanonicalHeaders>(1, B256::ZERO).unwrap();
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(Some(B256::ZERO)));
+        assert_eq!(tx.entries::<CanonicalHeaders>(), Ok(1));
+
+        assert_eq!(tx.delete::<CanonicalHeaders>(1, Some(B256::with_last_byte(1))), Ok(false));
+        assert_eq!(tx.delete::<CanonicalHeaders>(1, None), Ok(true));
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(None));
+        tx.commit().unwrap();
+
+        assert_eq!(db.tx().unwrap().entries::<CanonicalHeaders>(), Ok(0));
+    }
+
+    #[test]
+    fn snapshot_isolation() {
+        let db = with_headers([1]);
+
+        let before = db.tx().unwrap();
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(2, B256::ZERO).unwrap();
+        assert_eq!(before.get::<CanonicalHeaders>(2), Ok(None));
+
+        // uncommitted changes are discarded
+        drop(tx);
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(2), Ok(None));
+
+        let tx = db.tx_mut().unwrap();
+        tx.clear::<CanonicalHeaders>().unwrap();
+        tx.commit().unwrap();
+        assert_eq!(before.get::<CanonicalHeaders>(1), Ok(Some(B256::with_last_byte(1))));
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(1), Ok(None));
+    }
+
+    #[test]
+    fn read_only() {
+        let db = DatabaseMock::default();
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            tx.put::<CanonicalHeaders>(1, B256::ZERO),
+            Err(write_error::<CanonicalHeaders>(
+                ACCESS_DENIED,
+                DatabaseWriteOperation::Put,
+                1u64.encode().into()
+            ))
+        );
+        assert!(tx.cursor_write::<CanonicalHeaders>().unwrap().upsert(1, B256::ZERO).is_err());
+        assert_eq!(tx.clear::<CanonicalHeaders>(), Err(DatabaseError::Delete(ACCESS_DENIED)));
+    }
+
+    #[test]
+    fn cursor_navigation() {
+        let db = with_headers([0, 1, 3]);
+        let tx = db.tx().unwrap();
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        assert_eq!(cursor.current(), Ok(None));
+
+        assert_eq!(cursor.seek_exact(2), Ok(None));
+        assert_eq!(cursor.current(), Ok(Some((3, B256::with_last_byte(3)))));
+        assert_eq!(cursor.prev(), Ok(Some((1, B256::with_last_byte(1)))));
+        assert_eq!(cursor.next(), Ok(Some((3, B256::with_last_byte(3)))));
+        assert_eq!(cursor.next(), Ok(None));
+        assert_eq!(cursor.first(), Ok(Some((0, B256::ZERO))));
+        assert_eq!(cursor.last(), Ok(Some((3, B256::with_last_byte(3)))));
+        assert_eq!(cursor.seek(4), Ok(None));
+
+        let keys = |walker: Result<Vec<_>, _>| -> Vec<u64> {
+            walker.unwrap().into_iter().map(|(key, _)| key).collect()
+        };
+        assert_eq!(keys(cursor.walk(Some(1)).unwrap().collect()), vec![1, 3]);
+        assert_eq!(keys(cursor.walk_range(1..3).unwrap().collect()), vec![1]);
+        assert_eq!(keys(cursor.walk_range(..=3).unwrap().collect()), vec![0, 1, 3]);
+        assert_eq!(keys(cursor.walk_range(2..).unwrap().collect()), vec![3]);
+        assert_eq!(keys(cursor.walk_back(Some(1)).unwrap().collect()), vec![1, 0]);
+        assert_eq!(keys(cursor.walk_back(None).unwrap().collect()), vec![3, 1, 0]);
+    }
+
+    #[test]
+    fn cursor_writes() {
+        let db = with_headers([0, 1, 3]);
+        let tx = db.tx_mut().unwrap();
+        let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+
+        assert_eq!(cursor.insert(2, B256::ZERO), Ok(()));
+        assert_eq!(cursor.current(), Ok(Some((2, B256::ZERO))));
+        assert_eq!(
+            cursor.insert(1, B256::ZERO),
+            Err(write_error::<CanonicalHeaders>(
+                KEY_EXIST,
+                DatabaseWriteOperation::CursorInsert,
+                1u64.encode().into()
+            ))
+        );
+        assert_eq!(cursor.current(), Ok(Some((1, B256::with_last_byte(1)))));
+
+        assert_eq!(
+            cursor.append(3, B256::ZERO),
+            Err(write_error::<CanonicalHeaders>(
+                KEY_MISMATCH,
+                DatabaseWriteOperation::CursorAppend,
+                3u64.encode().into()
+            ))
+        );
+        assert_eq!(cursor.append(4, B256::ZERO), Ok(()));
+        assert_eq!(cursor.upsert(4, B256::with_last_byte(4)), Ok(()));
+
+        // deleting while walking continues with the next entry
+        let mut walker = cursor.walk(Some(1)).unwrap();
+        assert_eq!(walker.next(), Some(Ok((1, B256::with_last_byte(1)))));
+        walker.delete_current().unwrap();
+        assert_eq!(walker.next(), Some(Ok((2, B256::ZERO))));
+        walker.delete_current().unwrap();
+        assert_eq!(walker.next(), Some(Ok((3, B256::with_last_byte(3)))));
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let entries = tx
+            .cursor_read::<CanonicalHeaders>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![(0, B256::ZERO), (3, B256::with_last_byte(3)), (4, B256::with_last_byte(4))]
+        );
+    }
+
+    #[test]
+    fn dupsort() {
+        let db = DatabaseMock::default();
+        let (address1, address2) = (Address::with_last_byte(1), Address::with_last_byte(2));
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<PlainStorageState>(address1, storage_entry(2, 20)).unwrap();
+        tx.put::<PlainStorageState>(address1, storage_entry(1, 10)).unwrap();
+        tx.put::<PlainStorageState>(address2, storage_entry(1, 30)).unwrap();
+
+        let mut cursor = tx.cursor_dup_write::<PlainStorageState>().unwrap();
+        assert_eq!(cursor.seek_by_key_subkey(address1, B256::ZERO), Ok(Some(storage_entry(1, 10))));
+        assert_eq!(cursor.next_dup_val(), Ok(Some(storage_entry(2, 20))));
+        assert_eq!(cursor.next_dup(), Ok(None));
+        assert_eq!(cursor.next_no_dup(), Ok(Some((address2, storage_entry(1, 30)))));
+        assert_eq!(cursor.seek_by_key_subkey(address2, B256::with_last_byte(2)), Ok(None));
+
+        // upsert adds a value, insert fails if the key exists
+        cursor.upsert(address1, storage_entry(1, 11)).unwrap();
+        assert!(cursor.insert(address1, storage_entry(3, 30)).is_err());
+        let values = cursor
+            .walk_dup(Some(address1), None)
+            .unwrap()
+            .map(|entry| entry.map(|(_, value)| value))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![storage_entry(1, 10), storage_entry(1, 11), storage_entry(2, 20)]);
+
+        let walker = cursor.walk_dup(None, Some(B256::with_last_byte(2))).unwrap();
+        assert_eq!(walker.count(), 1);
+
+        cursor.seek_exact(address1).unwrap();
+        cursor.delete_current_duplicates().unwrap();
+        assert_eq!(tx.entries::<PlainStorageState>(), Ok(1));
+        assert_eq!(tx.delete::<PlainStorageState>(address2, Some(storage_entry(1, 30))), Ok(true));
+        assert_eq!(cursor.first(), Ok(None));
+        assert_eq!(
+            cursor.walk_dup(None, Some(B256::ZERO)).unwrap().next(),
+            Some(Err(DatabaseError::Read(NOT_FOUND)))
+        );
+    }
+
+    #[test]
+    fn dupsort_append() {
+        let db = DatabaseMock::default();
+        let tx = db.tx_mut().unwrap();
+        let mut cursor = tx.cursor_dup_write::<AccountChangeSet>().unwrap();
+        let change =
+            |address| AccountBeforeTx { address: Address::with_last_byte(address), info: None };
+
+        for address in [0, 1, 3] {
+            cursor.append(2, change(address)).unwrap();
+        }
+        assert_eq!(
+            cursor.append_dup(2, change(2)),
+            Err(write_error::<AccountChangeSet>(
+                KEY_MISMATCH,
+                DatabaseWriteOperation::CursorAppendDup,
+                2u64.encode().into()
+            ))
+        );
+        assert!(cursor.append(1, change(2)).is_err());
+        assert_eq!(cursor.append(2, change(2)), Ok(()));
+        assert_eq!(cursor.append_dup(2, change(4)), Ok(()));
+        assert_eq!(tx.entries::<AccountChangeSet>(), Ok(5));
     }
 }
@@ -23,6 +23,11 @@ pub mod tx;
 const GIGABYTE: usize = 1024 * 1024 * 1024;
 const TERABYTE: usize = GIGABYTE * 1024;
 
+/// Number of additional tables that can be opened, on top of [Tables::ALL].
+///
+/// Migrations may need to open tables that are no longer part of [Tables], e.g. to rename them.
+const MAX_LEGACY_TABLES: usize = 16;
+
 /// MDBX allows up to 32767 readers (`MDBX_READERS_LIMIT`), but we limit it to slightly below that
 const DEFAULT_MAX_READERS: u64 = 32_000;
 
@@ -158,7 +163,7 @@ impl DatabaseEnv {
             }
         };
 
-        inner_env.set_max_dbs(Tables::ALL.len());
+        inner_env.set_max_dbs(Tables::ALL.len() + MAX_LEGACY_TABLES);
         inner_env.set_geometry(Geometry {
             // Maximum database size of 4 terabytes
             size: Some(0..(4 * TERABYTE)),
@@ -69,6 +69,8 @@ pub mod abstraction;
 
 mod implementation;
 mod metrics;
+#[cfg(feature = "mdbx")]
+pub mod migration;
 pub mod snapshot;
 pub mod tables;
 mod utils;
@@ -93,26 +95,39 @@ use eyre::WrapErr;
 use reth_interfaces::db::LogLevel;
 use std::path::Path;
 
-/// Opens up an existing database or creates a new one at the specified path. Creates tables if
-/// necessary. Read/Write mode.
+/// Opens up an existing database or creates a new one at the specified path. Migrates the database
+/// to the latest version and creates tables if necessary. Read/Write mode.
 pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnv> {
-    use crate::version::{check_db_version_file, create_db_version_file, DatabaseVersionError};
+    use crate::version::{
+        create_db_version_file, get_db_version, DatabaseVersionError, DB_VERSION,
+    };
 
     let rpath = path.as_ref();
+    let mut migrate_from = None;
     if is_database_empty(rpath) {
         std::fs::create_dir_all(rpath)
             .wrap_err_with(|| format!("Could not create database directory {}", rpath.display()))?;
         create_db_version_file(rpath)?;
     } else {
-        match check_db_version_file(rpath) {
-            Ok(_) => (),
+        match get_db_version(rpath) {
+            Ok(DB_VERSION) => (),
+            Ok(version) => migrate_from = Some(version),
             Err(DatabaseVersionError::MissingFile) => create_db_version_file(rpath)?,
             Err(err) => return Err(err.into()),
         }
     }
     #[cfg(feature = "mdbx")]
     {
+        let migrator = migration::Migrator::default();
+        if let Some(version) = migrate_from {
+            // fail before opening the database if it can't be migrated
+            migrator.pending(version)?;
+        }
+
         let db = DatabaseEnv::open(rpath, DatabaseEnvKind::RW, log_level)?;
+        if let Some(version) = migrate_from {
+            migrator.migrate(rpath, &db, version)?;
+        }
         db.create_tables()?;
         Ok(db)
     }
@@ -0,0 +1,399 @@
+//! Database schema migrations.
+//!
+//! Every change to the database schema that is incompatible with existing databases bumps
+//! [DB_VERSION] and adds a [Migration] to [MIGRATIONS] that upgrades a database from the previous
+//! version. [init_db](crate::init_db) applies all migrations between the local database version
+//! and [DB_VERSION] in order.
+//!
+//! Each migration runs in its own read-write transaction, which also records it in the
+//! [DatabaseMigrations] table, so it's either applied entirely or not at all. The database version
+//! file is updated after every migration, so an interrupted upgrade resumes with the first
+//! migration that wasn't applied yet.
+
+use crate::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    implementation::mdbx::tx::Tx,
+    table::Table,
+    tables::{DatabaseMigrations, RawTable, RawValue, TableType, Tables},
+    transaction::{DbTx, DbTxMut},
+    version::{write_db_version_file, DatabaseVersionError, DB_VERSION},
+    DatabaseError,
+};
+use reth_interfaces::db::{DatabaseWriteError, DatabaseWriteOperation};
+use reth_libmdbx::{DatabaseFlags, WriteFlags, RW};
+use reth_tracing::tracing::{debug, info};
+use std::{
+    fmt::Debug,
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// All migrations, ordered by the database version they upgrade to.
+pub static MIGRATIONS: &[&dyn Migration] = &[];
+
+/// A migration that upgrades the database schema from the previous version.
+pub trait Migration: Debug + Send + Sync {
+    /// The database version this migration upgrades to.
+    fn version(&self) -> u64;
+
+    /// A short description of the migration.
+    fn description(&self) -> &str;
+
+    /// Applies the migration.
+    ///
+    /// Only the tables of the previous version are guaranteed to exist, new tables have to be
+    /// created with [create_table].
+    fn migrate(&self, tx: &Tx<RW>) -> Result<(), DatabaseError>;
+}
+
+/// Upgrades databases to a target version by applying [Migration]s in order.
+#[derive(Debug, Clone, Copy)]
+pub struct Migrator<'a> {
+    /// The migrations, ordered by version.
+    migrations: &'a [&'a dyn Migration],
+    /// The version databases are upgraded to.
+    target: u64,
+}
+
+impl Default for Migrator<'static> {
+    fn default() -> Self {
+        Self::new(MIGRATIONS, DB_VERSION)
+    }
+}
+
+impl<'a> Migrator<'a> {
+    /// Creates a new migrator from migrations ordered by the version they upgrade to.
+    pub const fn new(migrations: &'a [&'a dyn Migration], target: u64) -> Self {
+        Self { migrations, target }
+    }
+
+    /// Returns the version databases are upgraded to.
+    pub const fn target(&self) -> u64 {
+        self.target
+    }
+
+    /// Returns the migrations that upgrade a database of the given version to the target version.
+    ///
+    /// Returns [DatabaseVersionError::VersionMismatch] if the database can't be upgraded, because
+    /// it's newer than the target version or a migration is missing.
+    pub fn pending(&self, version: u64) -> Result<&'a [&'a dyn Migration], DatabaseVersionError> {
+        if version > self.target {
+            return Err(DatabaseVersionError::VersionMismatch { version })
+        }
+
+        let position = |version: u64| {
+            self.migrations
+                .iter()
+                .position(|migration| migration.version() > version)
+                .unwrap_or(self.migrations.len())
+        };
+        let pending = &self.migrations[position(version)..position(self.target)];
+
+        // every version up to the target must be reached by exactly one migration
+        if !pending.iter().map(|migration| migration.version()).eq(version + 1..=self.target) {
+            return Err(DatabaseVersionError::VersionMismatch { version })
+        }
+
+        Ok(pending)
+    }
+
+    /// Upgrades the database at the given path from the given version to the target version.
+    ///
+    /// Migrations that were already applied are skipped, e.g. if the node crashed before the
+    /// version file was updated.
+    pub fn migrate<DB>(&self, db_path: &Path, db: &DB, version: u64) -> eyre::Result<()>
+    where
+        DB: Database<TXMut = Tx<RW>>,
+    {
+        for migration in self.pending(version)? {
+            let tx = db.tx_mut()?;
+            create_table::<DatabaseMigrations>(&tx)?;
+
+            if tx.get::<DatabaseMigrations>(migration.version())?.is_some() {
+                debug!(
+                    target: "storage::db::migration",
+                    version = migration.version(),
+                    "Database migration was already applied"
+                );
+                tx.abort();
+            } else {
+                info!(
+                    target: "storage::db::migration",
+                    version = migration.version(),
+                    description = migration.description(),
+                    "Applying database migration"
+                );
+                migration.migrate(&tx)?;
+
+                let applied_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+                tx.put::<DatabaseMigrations>(migration.version(), applied_at)?;
+                tx.commit()?;
+            }
+
+            write_db_version_file(db_path, migration.version())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the given table, if necessary.
+pub fn create_table<T: Table>(tx: &Tx<RW>) -> Result<(), DatabaseError> {
+    tx.inner
+        .create_db(Some(T::NAME), table_flags::<T>())
+        .map_err(|e| DatabaseError::CreateTable(e.into()))?;
+
+    Ok(())
+}
+
+/// Moves all entries of the table with the given name to the given table, and drops the former.
+///
+/// Returns `false` if there's no table with the given name.
+pub fn rename_table<T: Table>(tx: &Tx<RW>, from: &str) -> Result<bool, DatabaseError> {
+    let old = match tx.inner.open_db(Some(from)) {
+        Ok(db) => db,
+        Err(reth_libmdbx::Error::NotFound) => return Ok(false),
+        Err(e) => return Err(DatabaseError::Read(e.into())),
+    };
+    let new = tx
+        .inner
+        .create_db(Some(T::NAME), table_flags::<T>())
+        .map_err(|e| DatabaseError::CreateTable(e.into()))?;
+
+    let mut cursor = tx.inner.cursor(&old).map_err(|e| DatabaseError::InitCursor(e.into()))?;
+    for entry in cursor.iter_start::<Vec<u8>, Vec<u8>>() {
+        let (key, value) = entry.map_err(|e| DatabaseError::Read(e.into()))?;
+        tx.inner.put(new.dbi(), &key, value, WriteFlags::UPSERT).map_err(|e| {
+            DatabaseWriteError {
+                code: e.into(),
+                operation: DatabaseWriteOperation::Put,
+                table_name: T::NAME,
+                key,
+            }
+        })?;
+    }
+    drop(cursor);
+
+    // SAFETY: the only cursor over the old table was dropped above, and its handle is not
+    // shared with anything else.
+    unsafe { tx.inner.drop_db(old) }.map_err(|e| DatabaseError::Delete(e.into()))?;
+
+    Ok(true)
+}
+
+/// Re-encodes all values of the given table, e.g. after the `Compact` encoding of its value type
+/// changed.
+///
+/// The closure decodes a value in the previous encoding, which is then stored in the current
+/// encoding. Returns the number of re-encoded values.
+///
+/// This must not be used for [DupSort](crate::table::DupSort) tables, whose values are sorted by
+/// their encoding and can't be replaced in place.
+pub fn reencode_table<T: Table>(
+    tx: &Tx<RW>,
+    mut decode: impl FnMut(&[u8]) -> Result<T::Value, DatabaseError>,
+) -> Result<usize, DatabaseError> {
+    let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+    let mut reencoded = 0;
+
+    let mut entry = cursor.first()?;
+    while let Some((key, value)) = entry {
+        let value = decode(value.raw_value())?;
+        cursor.upsert(key, RawValue::new(value))?;
+        reencoded += 1;
+        entry = cursor.next()?;
+    }
+
+    Ok(reencoded)
+}
+
+/// Returns the flags the given table is created with.
+fn table_flags<T: Table>() -> DatabaseFlags {
+    let table = Tables::from_str(T::NAME).expect("Requested table should be part of `Tables`.");
+    match table.table_type() {
+        TableType::Table => DatabaseFlags::default(),
+        TableType::DupSort => DatabaseFlags::DUP_SORT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        table::Decompress,
+        tables::{CanonicalHeaders, RawKey},
+        test_utils::create_test_rw_db,
+        version::get_db_version,
+    };
+    use assert_matches::assert_matches;
+    use reth_primitives::B256;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Writes a canonical header hash for its version.
+    #[derive(Debug)]
+    struct TestMigration {
+        version: u64,
+        fail: bool,
+        applied: AtomicUsize,
+    }
+
+    impl TestMigration {
+        fn new(version: u64) -> Self {
+            Self { version, fail: false, applied: AtomicUsize::new(0) }
+        }
+
+        fn failing(version: u64) -> Self {
+            Self { fail: true, ..Self::new(version) }
+        }
+
+        fn applied(&self) -> usize {
+            self.applied.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Migration for TestMigration {
+        fn version(&self) -> u64 {
+            self.version
+        }
+
+        fn description(&self) -> &str {
+            "test migration"
+        }
+
+        fn migrate(&self, tx: &Tx<RW>) -> Result<(), DatabaseError> {
+            self.applied.fetch_add(1, Ordering::Relaxed);
+            tx.put::<CanonicalHeaders>(self.version, B256::with_last_byte(self.version as u8))?;
+            if self.fail {
+                return Err(DatabaseError::Decode)
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pending_migrations() {
+        let (v2, v3) = (TestMigration::new(2), TestMigration::new(3));
+        let migrations: [&dyn Migration; 2] = [&v2, &v3];
+        let migrator = Migrator::new(&migrations, 3);
+
+        assert_eq!(migrator.pending(1).unwrap().len(), 2);
+        assert_eq!(migrator.pending(2).unwrap()[0].version(), 3);
+        assert!(migrator.pending(3).unwrap().is_empty());
+        assert_matches!(
+            migrator.pending(0),
+            Err(DatabaseVersionError::VersionMismatch { version: 0 })
+        );
+        assert_matches!(
+            migrator.pending(4),
+            Err(DatabaseVersionError::VersionMismatch { version: 4 })
+        );
+
+        // a gap in the migrations can't be bridged
+        let migrations: [&dyn Migration; 1] = [&v3];
+        let migrator = Migrator::new(&migrations, 3);
+        assert_matches!(
+            migrator.pending(1),
+            Err(DatabaseVersionError::VersionMismatch { version: 1 })
+        );
+
+        // the default migrations are complete
+        assert!(Migrator::default().pending(DB_VERSION).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_and_resume() {
+        let db = create_test_rw_db();
+        let (v2, v3) = (TestMigration::new(2), TestMigration::new(3));
+        let migrations: [&dyn Migration; 2] = [&v2, &v3];
+        let migrator = Migrator::new(&migrations, 3);
+
+        migrator.migrate(db.path(), db.db(), 1).unwrap();
+        assert_eq!((v2.applied(), v3.applied()), (1, 1));
+        assert_eq!(get_db_version(db.path()).unwrap(), 3);
+
+        let tx = db.tx().unwrap();
+        assert!(tx.get::<CanonicalHeaders>(2).unwrap().is_some());
+        assert!(tx.get::<CanonicalHeaders>(3).unwrap().is_some());
+        assert!(tx.get::<DatabaseMigrations>(3).unwrap().is_some());
+        tx.commit().unwrap();
+
+        // the node crashed before the version file was updated
+        write_db_version_file(db.path(), 2).unwrap();
+        migrator.migrate(db.path(), db.db(), 2).unwrap();
+        assert_eq!(v3.applied(), 1);
+        assert_eq!(get_db_version(db.path()).unwrap(), 3);
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        let db = create_test_rw_db();
+        let (v2, v3) = (TestMigration::new(2), TestMigration::failing(3));
+        let migrations: [&dyn Migration; 2] = [&v2, &v3];
+        let migrator = Migrator::new(&migrations, 3);
+
+        assert!(migrator.migrate(db.path(), db.db(), 1).is_err());
+        assert_eq!(get_db_version(db.path()).unwrap(), 2);
+
+        let tx = db.tx().unwrap();
+        assert!(tx.get::<CanonicalHeaders>(2).unwrap().is_some());
+        assert!(tx.get::<CanonicalHeaders>(3).unwrap().is_none());
+        assert!(tx.get::<DatabaseMigrations>(3).unwrap().is_none());
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn rename() {
+        let db = create_test_rw_db();
+        let hash = B256::with_last_byte(1);
+
+        let tx = db.tx_mut().unwrap();
+        let legacy =
+            tx.inner.create_db(Some("LegacyCanonicalHeaders"), Default::default()).unwrap();
+        tx.inner.put(legacy.dbi(), 1u64.to_be_bytes(), hash, WriteFlags::UPSERT).unwrap();
+
+        assert!(rename_table::<CanonicalHeaders>(&tx, "LegacyCanonicalHeaders").unwrap());
+        assert_eq!(tx.get::<CanonicalHeaders>(1).unwrap(), Some(hash));
+        assert_matches!(
+            tx.inner.open_db(Some("LegacyCanonicalHeaders")),
+            Err(reth_libmdbx::Error::NotFound)
+        );
+        assert!(!rename_table::<CanonicalHeaders>(&tx, "LegacyCanonicalHeaders").unwrap());
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn reencode() {
+        let db = create_test_rw_db();
+        let hashes = [B256::with_last_byte(1), B256::with_last_byte(2)];
+
+        // the previous encoding stored the hashes reversed
+        let tx = db.tx_mut().unwrap();
+        for (number, hash) in hashes.iter().enumerate() {
+            let mut reversed = hash.0;
+            reversed.reverse();
+            tx.put::<RawTable<CanonicalHeaders>>(
+                RawKey::new(number as u64),
+                RawValue::decompress(reversed).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let reencoded = reencode_table::<CanonicalHeaders>(&tx, |value| {
+            let mut hash = B256::from_slice(value);
+            hash.0.reverse();
+            Ok(hash)
+        })
+        .unwrap();
+        assert_eq!(reencoded, hashes.len());
+
+        for (number, hash) in hashes.iter().enumerate() {
+            assert_eq!(tx.get::<CanonicalHeaders>(number as u64).unwrap(), Some(*hash));
+        }
+        tx.commit().unwrap();
+    }
+}
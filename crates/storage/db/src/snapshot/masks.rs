@@ -1,11 +1,12 @@
-use super::{ReceiptMask, TransactionMask};
+use super::{AccountChangeSetMask, ReceiptMask, StorageChangeSetMask, TransactionMask};
 use crate::{
     add_snapshot_mask,
-    snapshot::mask::{ColumnSelectorOne, ColumnSelectorTwo, HeaderMask},
+    codecs::CompactU64,
+    snapshot::mask::{ColumnSelectorOne, ColumnSelectorThree, ColumnSelectorTwo, HeaderMask},
     table::Table,
-    CanonicalHeaders, HeaderTD, Receipts, Transactions,
+    AccountChangeSet, CanonicalHeaders, HeaderTD, Receipts, StorageChangeSet, Transactions,
 };
-use reth_primitives::{BlockHash, Header};
+use reth_primitives::{Address, BlockHash, Header};
 
 // HEADER MASKS
 
@@ -26,3 +27,17 @@ add_snapshot_mask!(ReceiptMask, <Receipts as Table>::Value, 0b1);
 
 // TRANSACTION MASKS
 add_snapshot_mask!(TransactionMask, <Transactions as Table>::Value, 0b1);
+
+// ACCOUNT CHANGESET MASKS
+add_snapshot_mask!(AccountChangeSetMask, <AccountChangeSet as Table>::Value, 0b01);
+add_snapshot_mask!(AccountChangeSetMask, <AccountChangeSet as Table>::Value, CompactU64, 0b11);
+
+// STORAGE CHANGESET MASKS
+add_snapshot_mask!(StorageChangeSetMask, <StorageChangeSet as Table>::Value, 0b001);
+add_snapshot_mask!(
+    StorageChangeSetMask,
+    <StorageChangeSet as Table>::Value,
+    CompactU64,
+    Address,
+    0b111
+);
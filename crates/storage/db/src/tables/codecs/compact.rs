@@ -46,6 +46,7 @@ impl_compression_for_compact!(
     AccountBeforeTx,
     TransactionSignedNoHash,
     CompactU256,
+    CompactU64,
     StageCheckpoint,
     PruneCheckpoint
 );
@@ -1,7 +1,7 @@
 //! Integrates different codecs into table::Encode and table::Decode
 
 mod compact;
-pub use compact::CompactU256;
+pub use compact::{CompactU256, CompactU64};
 
 pub mod fuzz;
 
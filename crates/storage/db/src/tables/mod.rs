@@ -53,7 +53,7 @@ pub enum TableType {
 }
 
 /// Number of tables that should be present inside database.
-pub const NUM_TABLES: usize = 28;
+pub const NUM_TABLES: usize = 32;
 
 /// The general purpose of this is to use with a combination of Tables enum,
 /// by implementing a `TableViewer` trait you can operate on db tables in an abstract way.
@@ -219,7 +219,11 @@ tables!([
             SyncStageProgress,
             PruneCheckpoints,
             ConsensusNumber,
-            ConsensusContent
+            ConsensusContent,
+            AddressAppearances,
+            LogAddressIndex,
+            LogTopicIndex,
+            DatabaseMigrations
         ]
     ),
     (
@@ -465,6 +469,50 @@ table!(
     ( ConsensusContent ) BlockHash | ConsensusBytes
 );
 
+table!(
+    /// Stores pointers to the blocks in which an address appears.
+    ///
+    /// An address appears in a block if it is the sender or the recipient of one of its
+    /// transactions, a contract created by it, the emitter of one of its logs, an account whose
+    /// state was changed by it, or an account that was accessed while executing it (which covers
+    /// every call target in traces).
+    ///
+    /// Only the blocks between the prune checkpoint of the `AddressAppearances` segment and the
+    /// checkpoint of the `IndexAddressAppearances` stage are completely indexed.
+    ///
+    /// Sharded the same way as [`AccountHistory`]: the last shard of an address has `u64::MAX` as
+    /// its highest block number.
+    ///
+    /// This table is optional and only populated if the `IndexAddressAppearances` stage is enabled.
+    ( AddressAppearances ) ShardedKey<Address> | BlockNumberList
+);
+
+table!(
+    /// Stores pointers to the blocks that contain logs emitted by an address.
+    ///
+    /// Sharded the same way as [`AccountHistory`]: the last shard of an address has `u64::MAX` as
+    /// its highest block number.
+    ///
+    /// This table is optional and only populated if the `IndexLogs` stage is enabled.
+    ( LogAddressIndex ) ShardedKey<Address> | BlockNumberList
+);
+
+table!(
+    /// Stores pointers to the blocks that contain logs with a topic, regardless of its position.
+    ///
+    /// Sharded the same way as [`AccountHistory`]: the last shard of a topic has `u64::MAX` as its
+    /// highest block number.
+    ///
+    /// This table is optional and only populated if the `IndexLogs` stage is enabled.
+    ( LogTopicIndex ) ShardedKey<B256> | BlockNumberList
+);
+
+table!(
+    /// Stores the database migrations that were applied, keyed by the database version they
+    /// upgrade to, along with the UNIX timestamp at which they were applied.
+    ( DatabaseMigrations ) u64 | u64
+);
+
 /// Alias Types
 
 /// List with transaction numbers.
@@ -501,6 +549,10 @@ mod tests {
         (TableType::Table, PruneCheckpoints::NAME),
         (TableType::Table, ConsensusNumber::NAME),
         (TableType::Table, ConsensusContent::NAME),
+        (TableType::Table, AddressAppearances::NAME),
+        (TableType::Table, LogAddressIndex::NAME),
+        (TableType::Table, LogTopicIndex::NAME),
+        (TableType::Table, DatabaseMigrations::NAME),
         (TableType::DupSort, PlainStorageState::NAME),
         (TableType::DupSort, AccountChangeSet::NAME),
         (TableType::DupSort, StorageChangeSet::NAME),
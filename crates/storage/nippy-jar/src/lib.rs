@@ -209,6 +209,11 @@ impl<H: NippyJarHeader> NippyJar<H> {
         &self.user_header
     }
 
+    /// Returns the number of rows in the jar.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
     /// Returns the size of inclusion filter
     pub fn filter_size(&self) -> usize {
         self.size()
@@ -0,0 +1,119 @@
+//! A bounded store of recently rejected blocks.
+
+use parking_lot::RwLock;
+use reth_primitives::{SealedBlock, B256};
+use std::{collections::VecDeque, sync::Arc};
+
+/// The default number of blocks kept by a [BadBlockStore].
+pub const DEFAULT_MAX_BAD_BLOCKS: usize = 10;
+
+/// A block that was rejected as invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadBlock {
+    /// The rejected block.
+    pub block: SealedBlock,
+    /// The validation error the block was rejected with.
+    pub error: String,
+}
+
+/// Keeps the full bodies of the most recently rejected blocks.
+///
+/// The store is shared between the components that reject blocks, like the blockchain tree and
+/// the consensus engines, and the RPC that serves them. Once the store is full, the oldest block is
+/// evicted.
+///
+/// Note: bad blocks are only kept in memory and do not survive a restart.
+#[derive(Debug, Clone)]
+pub struct BadBlockStore {
+    /// The bad blocks, most recent first.
+    blocks: Arc<RwLock<VecDeque<Arc<BadBlock>>>>,
+    /// The maximum number of blocks to keep.
+    max_blocks: usize,
+}
+
+impl BadBlockStore {
+    /// Creates a new store that keeps at most `max_blocks` blocks.
+    pub fn new(max_blocks: usize) -> Self {
+        Self { blocks: Default::default(), max_blocks }
+    }
+
+    /// Records a rejected block with the error it was rejected with.
+    ///
+    /// Blocks that are already known are ignored.
+    pub fn insert(&self, block: SealedBlock, error: impl ToString) {
+        if self.max_blocks == 0 {
+            return
+        }
+
+        let mut blocks = self.blocks.write();
+        if blocks.iter().any(|bad| bad.block.hash == block.hash) {
+            return
+        }
+        blocks.push_front(Arc::new(BadBlock { block, error: error.to_string() }));
+        blocks.truncate(self.max_blocks);
+    }
+
+    /// Returns the bad block with the given hash, if known.
+    pub fn get(&self, hash: &B256) -> Option<Arc<BadBlock>> {
+        self.blocks.read().iter().find(|bad| bad.block.hash == *hash).cloned()
+    }
+
+    /// Returns all bad blocks, most recent first.
+    pub fn blocks(&self) -> Vec<Arc<BadBlock>> {
+        self.blocks.read().iter().cloned().collect()
+    }
+
+    /// Returns the number of bad blocks in the store.
+    pub fn len(&self) -> usize {
+        self.blocks.read().len()
+    }
+
+    /// Returns `true` if there are no bad blocks in the store.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.read().is_empty()
+    }
+}
+
+impl Default for BadBlockStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BAD_BLOCKS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::test_utils::generators::{self, random_block};
+
+    #[test]
+    fn keeps_most_recent_blocks() {
+        let mut rng = generators::rng();
+        let store = BadBlockStore::new(2);
+        let blocks = (0..3)
+            .map(|number| random_block(&mut rng, number, None, None, None))
+            .collect::<Vec<_>>();
+
+        for block in &blocks {
+            store.insert(block.clone(), "invalid");
+        }
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&blocks[0].hash).is_none());
+
+        let hashes = store.blocks().iter().map(|bad| bad.block.hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![blocks[2].hash, blocks[1].hash]);
+    }
+
+    #[test]
+    fn ignores_known_blocks() {
+        let mut rng = generators::rng();
+        let store = BadBlockStore::default();
+        let block = random_block(&mut rng, 1, None, None, None);
+
+        store.insert(block.clone(), "first");
+        store.insert(block.clone(), "second");
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&block.hash).unwrap().error, "first");
+
+        BadBlockStore::new(0).insert(block, "ignored");
+    }
+}
@@ -8,11 +8,13 @@ use reth_interfaces::db::DatabaseError;
 use reth_primitives::{
     keccak256, logs_bloom,
     revm::compat::{into_reth_acc, into_revm_acc},
+    trie::AccountProof,
     Account, Address, BlockNumber, Bloom, Bytecode, Log, Receipt, Receipts, StorageEntry, B256,
     U256,
 };
 use reth_trie::{
     hashed_cursor::{HashedPostState, HashedPostStateCursorFactory, HashedStorage},
+    proof::Proof,
     updates::TrieUpdates,
     StateRoot, StateRootError,
 };
@@ -224,6 +226,18 @@ impl BundleStateWithReceipts {
         self.state_root_calculator(tx, &hashed_post_state).root_with_updates()
     }
 
+    /// Generates the account and storage proofs for this [BundleState] on top of the current
+    /// state. See [Self::state_root_slow] for more info.
+    pub fn account_proof_slow<TX: DbTx>(
+        &self,
+        tx: &TX,
+        address: Address,
+        slots: &[B256],
+    ) -> Result<AccountProof, StateRootError> {
+        let hashed_post_state = self.hash_state_slow();
+        Proof::overlay(tx, &hashed_post_state).account_proof(address, slots)
+    }
+
     /// Transform block number to the index of block.
     fn block_number_to_index(&self, block_number: BlockNumber) -> Option<usize> {
         if self.first_block > block_number {
@@ -411,7 +425,10 @@ impl BundleStateWithReceipts {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{test_utils::create_test_provider_factory, AccountReader, BundleStateWithReceipts};
+    use crate::{
+        test_utils::create_test_provider_factory, AccountReader, BundleStateWithReceipts,
+        LatestStateProviderRef, StageCheckpointWriter, StateProvider,
+    };
     use reth_db::{
         cursor::{DbCursorRO, DbDupCursorRO},
         database::Database,
@@ -421,7 +438,8 @@ mod tests {
         transaction::DbTx,
     };
     use reth_primitives::{
-        revm::compat::into_reth_acc, Address, Receipt, Receipts, StorageEntry, B256, U256,
+        revm::compat::into_reth_acc, stage::StageId, Address, Receipt, Receipts, StorageEntry,
+        B256, U256,
     };
     use reth_trie::test_utils::state_root;
     use revm::{
@@ -429,7 +447,7 @@ mod tests {
             states::{
                 bundle_state::{BundleRetention, OriginalValuesKnown},
                 changes::PlainStorageRevert,
-                PlainStorageChangeset,
+                PlainStorageChangeset, StateChangeset,
             },
             BundleState, EmptyDB,
         },
@@ -1245,16 +1263,17 @@ mod tests {
         let mut state = State::builder().with_bundle_update().build();
 
         let assert_state_root = |state: &State<EmptyDB>, expected: &PreState, msg| {
-            assert_eq!(
-                BundleStateWithReceipts::new(state.bundle_state.clone(), Receipts::default(), 0)
-                    .state_root_slow(&tx)
-                    .unwrap(),
-                state_root(expected.clone().into_iter().map(|(address, (account, storage))| (
-                    address,
-                    (account, storage.into_iter())
-                ))),
-                "{msg}"
-            );
+            let bundle_state =
+                BundleStateWithReceipts::new(state.bundle_state.clone(), Receipts::default(), 0);
+            let expected_root =
+                state_root(expected.clone().into_iter().map(|(address, (account, storage))| {
+                    (address, (account, storage.into_iter()))
+                }));
+            assert_eq!(bundle_state.state_root_slow(&tx).unwrap(), expected_root, "{msg}");
+
+            // the first node of the account proof is the root node
+            let proof = bundle_state.account_proof_slow(&tx, Address::ZERO, &[]).unwrap();
+            assert_eq!(keccak256(&proof.proof[0]), expected_root, "{msg}");
         };
 
         // database only state root is correct
@@ -1409,4 +1428,64 @@ mod tests {
         // account2 got inserted
         assert_eq!(end_state.state.get(&address2).unwrap().info, Some(account2));
     }
+
+    #[test]
+    fn write_to_db_snap_synced_state() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+
+        let address_a = Address::with_last_byte(1);
+        let address_b = Address::with_last_byte(2);
+        let address_c = Address::with_last_byte(3);
+        let account = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+        let slot_1 = B256::with_last_byte(1);
+        let slot_2 = B256::with_last_byte(2);
+
+        // the state of the pivot is only present in the hashed state
+        provider.tx_ref().put::<tables::HashedAccount>(keccak256(address_a), account).unwrap();
+        for address in [address_b, address_c] {
+            for slot in [slot_1, slot_2] {
+                provider
+                    .tx_ref()
+                    .put::<tables::HashedStorage>(
+                        keccak256(address),
+                        StorageEntry { key: keccak256(slot), value: U256::from(5) },
+                    )
+                    .unwrap();
+            }
+        }
+
+        // the hashed state is only read if the state was snap synced
+        let state = LatestStateProviderRef::new(provider.tx_ref());
+        assert_eq!(state.basic_account(address_a), Ok(None));
+        assert_eq!(state.storage(address_b, slot_1), Ok(None));
+
+        provider.save_stage_checkpoint_progress(StageId::SnapSync, vec![1]).unwrap();
+        let state = LatestStateProviderRef::new(provider.tx_ref());
+        assert_eq!(state.basic_account(address_a), Ok(Some(account)));
+        assert_eq!(state.storage(address_b, slot_1), Ok(Some(U256::from(5))));
+
+        // the account is deleted, a slot is zeroed and the other storage is wiped
+        StateChanges(StateChangeset {
+            accounts: vec![(address_a, None)],
+            storage: vec![
+                PlainStorageChangeset {
+                    address: address_b,
+                    wipe_storage: false,
+                    storage: vec![(U256::from(1), U256::ZERO)],
+                },
+                PlainStorageChangeset { address: address_c, wipe_storage: true, storage: vec![] },
+            ],
+            contracts: vec![],
+        })
+        .write_to_db(provider.tx_ref())
+        .unwrap();
+
+        let state = LatestStateProviderRef::new(provider.tx_ref());
+        assert_eq!(state.basic_account(address_a), Ok(None));
+        assert_eq!(state.storage(address_b, slot_1), Ok(None));
+        assert_eq!(state.storage(address_b, slot_2), Ok(Some(U256::from(5))));
+        assert_eq!(state.storage(address_c, slot_1), Ok(None));
+        assert_eq!(state.storage(address_c, slot_2), Ok(None));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::has_snap_synced_state;
 use rayon::slice::ParallelSliceMut;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
@@ -5,7 +6,7 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
 };
 use reth_interfaces::db::DatabaseError;
-use reth_primitives::{revm::compat::into_reth_acc, Bytecode, StorageEntry, U256};
+use reth_primitives::{keccak256, revm::compat::into_reth_acc, Bytecode, StorageEntry, U256};
 use revm::db::states::{PlainStorageChangeset, StateChangeset};
 
 /// A change to the state of the world.
@@ -20,7 +21,13 @@ impl From<StateChangeset> for StateChanges {
 
 impl StateChanges {
     /// Write the bundle state to the database.
+    ///
+    /// If the state was snap synced, deleted accounts, wiped storages and zeroed storage slots
+    /// are deleted from the hashed state as well, as they are read from there when missing from
+    /// the plain state.
     pub fn write_to_db<TX: DbTxMut + DbTx>(mut self, tx: &TX) -> Result<(), DatabaseError> {
+        let snap_synced = has_snap_synced_state(tx)?;
+
         // sort all entries so they can be written to database in more performant way.
         // and take smaller memory footprint.
         self.0.accounts.par_sort_by_key(|a| a.0);
@@ -30,15 +37,23 @@ impl StateChanges {
         // Write new account state
         tracing::trace!(target: "provider::bundle_state", len = self.0.accounts.len(), "Writing new account state");
         let mut accounts_cursor = tx.cursor_write::<tables::PlainAccountState>()?;
+        let mut hashed_accounts_cursor = tx.cursor_write::<tables::HashedAccount>()?;
         // write account to database.
         for (address, account) in self.0.accounts.into_iter() {
             if let Some(account) = account {
                 tracing::trace!(target: "provider::bundle_state", ?address, "Updating plain state account");
                 accounts_cursor.upsert(address, into_reth_acc(account))?;
-            } else if accounts_cursor.seek_exact(address)?.is_some() {
+                continue
+            }
+
+            if accounts_cursor.seek_exact(address)?.is_some() {
                 tracing::trace!(target: "provider::bundle_state", ?address, "Deleting plain state account");
                 accounts_cursor.delete_current()?;
             }
+            if snap_synced && hashed_accounts_cursor.seek_exact(keccak256(address))?.is_some() {
+                tracing::trace!(target: "provider::bundle_state", ?address, "Deleting hashed state account");
+                hashed_accounts_cursor.delete_current()?;
+            }
         }
 
         // Write bytecode
@@ -51,11 +66,19 @@ impl StateChanges {
         // Write new storage state and wipe storage if needed.
         tracing::trace!(target: "provider::bundle_state", len = self.0.storage.len(), "Writing new storage state");
         let mut storages_cursor = tx.cursor_dup_write::<tables::PlainStorageState>()?;
+        let mut hashed_storages_cursor = tx.cursor_dup_write::<tables::HashedStorage>()?;
         for PlainStorageChangeset { address, wipe_storage, storage } in self.0.storage.into_iter() {
+            let hashed_address = keccak256(address);
             // Wiping of storage.
             if wipe_storage && storages_cursor.seek_exact(address)?.is_some() {
                 storages_cursor.delete_current_duplicates()?;
             }
+            if snap_synced &&
+                wipe_storage &&
+                hashed_storages_cursor.seek_exact(hashed_address)?.is_some()
+            {
+                hashed_storages_cursor.delete_current_duplicates()?;
+            }
             // cast storages to B256.
             let mut storage = storage
                 .into_iter()
@@ -74,6 +97,15 @@ impl StateChanges {
 
                 if entry.value != U256::ZERO {
                     storages_cursor.upsert(address, entry)?;
+                } else if snap_synced {
+                    let hashed_slot = keccak256(entry.key);
+                    if let Some(db_entry) =
+                        hashed_storages_cursor.seek_by_key_subkey(hashed_address, hashed_slot)?
+                    {
+                        if db_entry.key == hashed_slot {
+                            hashed_storages_cursor.delete_current()?;
+                        }
+                    }
                 }
             }
         }
@@ -2,7 +2,7 @@ use crate::{
     bundle_state::BundleStateWithReceipts, AccountReader, BlockHashReader, BundleStateDataProvider,
     StateProvider, StateRootProvider,
 };
-use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{trie::AccountProof, Account, Address, BlockNumber, Bytecode, B256};
 use reth_trie::updates::TrieUpdates;
 
@@ -38,10 +38,17 @@ impl<SP: StateProvider, BSDP: BundleStateDataProvider> BlockHashReader
 
     fn canonical_hashes_range(
         &self,
-        _start: BlockNumber,
-        _end: BlockNumber,
+        start: BlockNumber,
+        end: BlockNumber,
     ) -> ProviderResult<Vec<B256>> {
-        unimplemented!()
+        let mut hashes = Vec::new();
+        for block_number in start..end {
+            match self.block_hash(block_number)? {
+                Some(block_hash) => hashes.push(block_hash),
+                None => break,
+            }
+        }
+        Ok(hashes)
     }
 }
 
@@ -74,6 +81,17 @@ impl<SP: StateProvider, BSDP: BundleStateDataProvider> StateRootProvider
         state.extend(bundle_state.clone());
         self.state_provider.state_root_with_updates(&state)
     }
+
+    fn state_proof(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+        address: Address,
+        keys: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        let mut state = self.bundle_state_data_provider.state().clone();
+        state.extend(bundle_state.clone());
+        self.state_provider.state_proof(&state, address, keys)
+    }
 }
 
 impl<SP: StateProvider, BSDP: BundleStateDataProvider> StateProvider
@@ -102,7 +120,154 @@ impl<SP: StateProvider, BSDP: BundleStateDataProvider> StateProvider
         self.state_provider.bytecode_by_hash(code_hash)
     }
 
-    fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
-        Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.state_provider.state_proof(self.bundle_state_data_provider.state(), address, keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::create_test_provider_factory, ProviderFactory};
+    use reth_db::{database::Database, tables, transaction::DbTxMut};
+    use reth_primitives::{keccak256, BlockNumHash, Receipts, StorageEntry, U256};
+    use reth_trie::{proof::Proof, StateRoot};
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    /// The accounts and their storage.
+    type State = BTreeMap<Address, (Account, BTreeMap<B256, U256>)>;
+
+    /// The state of a pending block on top of the database.
+    struct PendingState(BundleStateWithReceipts);
+
+    impl BundleStateDataProvider for PendingState {
+        fn state(&self) -> &BundleStateWithReceipts {
+            &self.0
+        }
+
+        fn block_hash(&self, _block_number: BlockNumber) -> Option<B256> {
+            None
+        }
+
+        fn canonical_fork(&self) -> BlockNumHash {
+            BlockNumHash { number: 0, hash: B256::ZERO }
+        }
+    }
+
+    /// Writes the plain and hashed state and its tries to the database.
+    fn insert_state<DB: Database>(factory: &ProviderFactory<DB>, state: &State) {
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+        for (address, (account, storage)) in state {
+            tx.put::<tables::PlainAccountState>(*address, *account).unwrap();
+            tx.put::<tables::HashedAccount>(keccak256(address), *account).unwrap();
+            for (key, value) in storage {
+                tx.put::<tables::PlainStorageState>(
+                    *address,
+                    StorageEntry { key: *key, value: *value },
+                )
+                .unwrap();
+                tx.put::<tables::HashedStorage>(
+                    keccak256(address),
+                    StorageEntry { key: keccak256(key), value: *value },
+                )
+                .unwrap();
+            }
+        }
+        let (_, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+        provider.commit().unwrap();
+    }
+
+    /// Computes the state root and the proof from scratch in a database that only contains the
+    /// state.
+    fn root_and_proof(state: &State, address: Address, keys: &[B256]) -> (B256, AccountProof) {
+        let factory = create_test_provider_factory();
+        insert_state(&factory, state);
+        let provider = factory.provider().unwrap();
+        let tx = provider.tx_ref();
+        let root = StateRoot::from_tx(tx).root().unwrap();
+        (root, Proof::new(tx).account_proof(address, keys).unwrap())
+    }
+
+    /// Returns the bundle state that changes the state `from` to the state `to`.
+    fn bundle_state(from: &State, to: &State, block: BlockNumber) -> BundleStateWithReceipts {
+        let addresses = from.keys().chain(to.keys()).collect::<BTreeSet<_>>();
+        let state_init = addresses
+            .into_iter()
+            .map(|address| {
+                let (original, present) = (from.get(address), to.get(address));
+                let value = |state: Option<&(Account, BTreeMap<B256, U256>)>, key: &B256| {
+                    state.and_then(|(_, storage)| storage.get(key)).copied().unwrap_or_default()
+                };
+                let storage = original
+                    .into_iter()
+                    .chain(present)
+                    .flat_map(|(_, storage)| storage.keys())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(|key| (*key, (value(original, key), value(present, key))))
+                    .collect();
+                (*address, (original.map(|(acc, _)| *acc), present.map(|(acc, _)| *acc), storage))
+            })
+            .collect();
+        BundleStateWithReceipts::new_init(
+            state_init,
+            HashMap::new(),
+            vec![],
+            Receipts::new(),
+            block,
+        )
+    }
+
+    #[test]
+    fn state_root_and_proof_of_pending_state() {
+        let (alice, bob, carol) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let slot = B256::with_last_byte;
+        let account =
+            |nonce, balance| Account { nonce, balance: U256::from(balance), bytecode_hash: None };
+
+        let db_state = State::from([
+            (
+                alice,
+                (
+                    account(1, 100),
+                    BTreeMap::from([(slot(1), U256::from(1)), (slot(2), U256::from(2))]),
+                ),
+            ),
+            (bob, (account(0, 50), BTreeMap::new())),
+        ]);
+        // the pending block changes and deletes slots and creates an account
+        let mut pending_state = db_state.clone();
+        pending_state.insert(alice, (account(2, 90), BTreeMap::from([(slot(1), U256::from(10))])));
+        pending_state.insert(carol, (account(0, 10), BTreeMap::from([(slot(3), U256::from(3))])));
+        // a block on top of the pending block
+        let mut next_state = pending_state.clone();
+        next_state.insert(bob, (account(1, 40), BTreeMap::from([(slot(4), U256::from(4))])));
+
+        let factory = create_test_provider_factory();
+        insert_state(&factory, &db_state);
+        // the pending state as returned by `StateProviderFactory::pending`
+        let provider = BundleStateProvider::new(
+            factory.latest().unwrap(),
+            PendingState(bundle_state(&db_state, &pending_state, 1)),
+        );
+
+        let keys = [slot(1), slot(2), slot(3), slot(4)];
+        let (pending_root, _) = root_and_proof(&pending_state, alice, &[]);
+        assert_ne!(pending_root, root_and_proof(&db_state, alice, &[]).0);
+        for address in [alice, bob, carol, Address::with_last_byte(4)] {
+            let (_, expected) = root_and_proof(&pending_state, address, &keys);
+            assert_eq!(provider.proof(address, &keys).unwrap(), expected);
+        }
+        assert_eq!(provider.state_root(&BundleStateWithReceipts::default()).unwrap(), pending_root);
+
+        // the state root and proofs of a block on top of the pending block
+        let next = bundle_state(&pending_state, &next_state, 2);
+        let (next_root, expected) = root_and_proof(&next_state, bob, &keys);
+        assert_eq!(provider.state_root(&next).unwrap(), next_root);
+        assert_eq!(provider.state_root_with_updates(&next).unwrap().0, next_root);
+        assert_eq!(provider.state_proof(&next, bob, &keys).unwrap(), expected);
     }
 }
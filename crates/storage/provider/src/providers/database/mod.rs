@@ -3,11 +3,12 @@ use crate::{
         state::{historical::HistoricalStateProvider, latest::LatestStateProvider},
         SnapshotProvider,
     },
-    traits::{BlockSource, ReceiptProvider},
-    BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, ConsensusNumberReader,
-    EvmEnvProvider, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode,
-    ProviderError, PruneCheckpointReader, StageCheckpointReader, StateProviderBox,
-    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    traits::{AccountRange, BlockSource, PlainAccountRange, ReceiptProvider, StorageRange},
+    AddressAppearanceReader, BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider,
+    ConsensusNumberReader, EvmEnvProvider, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider,
+    HeaderSyncMode, LogIndexEntries, LogIndexReader, ProviderError, PruneCheckpointReader,
+    StageCheckpointReader, StateProviderBox, StateRangeReader, TransactionVariant,
+    TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{
     database::Database,
@@ -19,13 +20,14 @@ use reth_interfaces::{db::LogLevel, provider::ProviderResult, RethError, RethRes
 use reth_primitives::{
     snapshot::HighestSnapshots,
     stage::{StageCheckpoint, StageId},
-    Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, ChainInfo,
-    ChainSpec, Header, PruneCheckpoint, PruneSegment, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
-    Withdrawal, B256, U256,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
+    ChainInfo, ChainSpec, Header, PruneCheckpoint, PruneSegment, Receipt, SealedBlock,
+    SealedBlockWithSenders, SealedHeader, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256, U256,
 };
 use revm::primitives::{BlockEnv, CfgEnv};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     ops::{RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
     sync::Arc,
@@ -158,6 +160,9 @@ impl<DB: Database> ProviderFactory<DB> {
             provider.get_prune_checkpoint(PruneSegment::StorageHistory)?;
 
         let mut state_provider = HistoricalStateProvider::new(provider.into_tx(), block_number);
+        if let Some(snapshot_provider) = &self.snapshot_provider {
+            state_provider = state_provider.with_snapshot_provider(snapshot_provider.clone());
+        }
 
         // If we pruned account or storage history, we can't return state on every historical block.
         // Instead, we should cap it at the latest prune checkpoint for corresponding prune segment.
@@ -501,6 +506,128 @@ impl<DB: Database> PruneCheckpointReader for ProviderFactory<DB> {
     }
 }
 
+impl<DB: Database> AddressAppearanceReader for ProviderFactory<DB> {
+    fn address_appearances_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<BlockNumber>>> {
+        self.provider()?.address_appearances_with_range(range)
+    }
+
+    fn address_appearance_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.provider()?.address_appearance_blocks(address, range)
+    }
+
+    fn address_appearances_indexed_range(
+        &self,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.provider()?.address_appearances_indexed_range()
+    }
+
+    fn address_appearance_candidates(
+        &self,
+        addresses: &[Address],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        self.provider()?.address_appearance_candidates(addresses, range)
+    }
+}
+
+impl<DB: Database> StateRangeReader for ProviderFactory<DB> {
+    fn account_range(
+        &self,
+        block_number: BlockNumber,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<AccountRange> {
+        self.provider()?.account_range(block_number, start, limit)
+    }
+
+    fn plain_accounts(
+        &self,
+        block_number: BlockNumber,
+        start: Address,
+        limit: usize,
+    ) -> ProviderResult<PlainAccountRange> {
+        self.provider()?.plain_accounts(block_number, start, limit)
+    }
+
+    fn plain_storages(
+        &self,
+        block_number: BlockNumber,
+        addresses: &BTreeSet<Address>,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>> {
+        self.provider()?.plain_storages(block_number, addresses)
+    }
+
+    fn storage_range(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<StorageRange> {
+        self.provider()?.storage_range(block_number, address, start, limit)
+    }
+
+    fn hashed_storages(
+        &self,
+        block_number: BlockNumber,
+        hashed_addresses: &BTreeSet<B256>,
+    ) -> ProviderResult<BTreeMap<B256, BTreeMap<B256, U256>>> {
+        self.provider()?.hashed_storages(block_number, hashed_addresses)
+    }
+
+    fn modified_accounts(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeSet<Address>> {
+        self.provider()?.modified_accounts(range)
+    }
+}
+
+impl<DB: Database> LogIndexReader for ProviderFactory<DB> {
+    fn log_index_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<LogIndexEntries> {
+        self.provider()?.log_index_with_range(range)
+    }
+
+    fn log_address_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.provider()?.log_address_blocks(address, range)
+    }
+
+    fn log_topic_blocks(
+        &self,
+        topic: B256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.provider()?.log_topic_blocks(topic, range)
+    }
+
+    fn logs_indexed_range(&self) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.provider()?.logs_indexed_range()
+    }
+
+    fn log_candidates(
+        &self,
+        addresses: &[Address],
+        topics: &[Vec<B256>],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        self.provider()?.log_candidates(addresses, topics, range)
+    }
+}
+
 impl<DB: Database> ConsensusNumberReader for ProviderFactory<DB> {
     fn last_consensus_number(&self) -> ProviderResult<BlockNumber> {
         self.provider()?.last_consensus_number()
@@ -520,12 +647,20 @@ mod tests {
     use super::ProviderFactory;
     use crate::{
         test_utils::create_test_provider_factory, BlockHashReader, BlockNumReader, BlockWriter,
-        HeaderSyncGapProvider, HeaderSyncMode, TransactionsProvider,
+        HeaderSyncGapProvider, HeaderSyncMode, StateRangeReader, StorageRangeEntry,
+        TransactionsProvider,
     };
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
     use rand::Rng;
-    use reth_db::{tables, test_utils::ERROR_TEMPDIR, transaction::DbTxMut, DatabaseEnv};
+    use reth_db::{
+        mock::DatabaseMock,
+        models::{AccountBeforeTx, BlockNumberAddress},
+        tables,
+        test_utils::ERROR_TEMPDIR,
+        transaction::DbTxMut,
+        DatabaseEnv,
+    };
     use reth_interfaces::{
         provider::ProviderError,
         test_utils::{
@@ -535,9 +670,10 @@ mod tests {
         RethError,
     };
     use reth_primitives::{
-        hex_literal::hex, ChainSpecBuilder, PruneMode, PruneModes, SealedBlock, TxNumber, B256,
+        hex_literal::hex, keccak256, Account, Address, ChainSpecBuilder, PruneMode, PruneModes,
+        SealedBlock, StorageEntry, TxNumber, B256, MAINNET, U256,
     };
-    use std::{ops::RangeInclusive, sync::Arc};
+    use std::{collections::BTreeSet, ops::RangeInclusive, sync::Arc};
     use tokio::sync::watch;
 
     #[test]
@@ -556,6 +692,113 @@ mod tests {
         assert_eq!(chain_info.best_hash, B256::ZERO);
     }
 
+    #[test]
+    fn state_range_at_past_block() {
+        let factory = create_test_provider_factory();
+        let existing = Address::with_last_byte(1);
+        let created = Address::with_last_byte(2);
+        let (slot1, slot2) = (B256::with_last_byte(1), B256::with_last_byte(2));
+
+        // `existing` is modified and `created` is created in block 1
+        let provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_ref();
+        for (address, nonce) in [(existing, 2), (created, 1)] {
+            let account = Account { nonce, ..Default::default() };
+            tx.put::<tables::PlainAccountState>(address, account).unwrap();
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+        }
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx {
+                address: existing,
+                info: Some(Account { nonce: 1, ..Default::default() }),
+            },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address: created, info: None })
+            .unwrap();
+        for (key, value) in [(slot1, 7), (slot2, 9)] {
+            tx.put::<tables::PlainStorageState>(
+                existing,
+                StorageEntry { key, value: U256::from(value) },
+            )
+            .unwrap();
+            tx.put::<tables::HashedStorage>(
+                keccak256(existing),
+                StorageEntry { key: keccak256(key), value: U256::from(value) },
+            )
+            .unwrap();
+        }
+        for (key, value) in [(slot1, 5), (slot2, 0)] {
+            tx.put::<tables::StorageChangeSet>(
+                BlockNumberAddress((1, existing)),
+                StorageEntry { key, value: U256::from(value) },
+            )
+            .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let range = provider.plain_accounts(0, Address::ZERO, 10).unwrap();
+        assert_eq!(range.accounts, vec![(existing, Account { nonce: 1, ..Default::default() })]);
+        assert_eq!(range.next, None);
+
+        let range = provider.plain_accounts(1, Address::ZERO, 1).unwrap();
+        assert_eq!(range.accounts.len(), 1);
+        assert_eq!(range.next, Some(created));
+        let range = provider.plain_accounts(1, created, 1).unwrap();
+        assert_eq!(range.accounts[0].0, created);
+        assert_eq!(range.next, None);
+
+        let storage = provider.plain_storage(0, existing).unwrap();
+        assert_eq!(storage.into_iter().collect::<Vec<_>>(), vec![(slot1, U256::from(5))]);
+        let storages = provider.plain_storages(1, &BTreeSet::from([existing, created])).unwrap();
+        assert_eq!(storages[&existing].len(), 2);
+        assert!(storages[&created].is_empty());
+
+        let hashed_address = keccak256(existing);
+        let storages = provider.hashed_storages(0, &BTreeSet::from([hashed_address])).unwrap();
+        assert_eq!(
+            storages[&hashed_address].clone().into_iter().collect::<Vec<_>>(),
+            vec![(keccak256(slot1), U256::from(5))]
+        );
+
+        let range = provider.storage_range(0, existing, B256::ZERO, 10).unwrap();
+        assert_eq!(
+            range.slots,
+            vec![StorageRangeEntry {
+                hashed_slot: keccak256(slot1),
+                slot: Some(slot1),
+                value: U256::from(5)
+            }]
+        );
+        assert_eq!(range.next, None);
+
+        let mut hashed_slots = [keccak256(slot1), keccak256(slot2)];
+        hashed_slots.sort();
+        let range = provider.storage_range(1, existing, B256::ZERO, 1).unwrap();
+        assert_eq!(range.slots.len(), 1);
+        assert_eq!(range.slots[0].hashed_slot, hashed_slots[0]);
+        assert_eq!(range.slots[0].slot, None);
+        assert_eq!(range.next, Some(hashed_slots[1]));
+        let range = provider.storage_range(1, existing, hashed_slots[1], 1).unwrap();
+        assert_eq!(range.slots[0].hashed_slot, hashed_slots[1]);
+        assert_eq!(range.next, None);
+        assert_eq!(provider.storage_range(1, created, B256::ZERO, 10).unwrap(), Default::default());
+
+        let range = provider.account_range(0, B256::ZERO, 10).unwrap();
+        assert_eq!(range.accounts.len(), 1);
+        assert_eq!(range.accounts[0].address, Some(existing));
+        assert_eq!(range.accounts[0].account.nonce, 1);
+        assert_eq!(range.next, None);
+
+        let range = provider.account_range(1, B256::ZERO, 1).unwrap();
+        assert_eq!(range.accounts.len(), 1);
+        assert!(range.next.is_some());
+
+        assert_eq!(provider.modified_accounts(1..=1).unwrap(), BTreeSet::from([existing, created]));
+    }
+
     #[test]
     fn provider_flow() {
         let factory = create_test_provider_factory();
@@ -583,6 +826,26 @@ mod tests {
         provider.block_hash(0).unwrap();
     }
 
+    #[test]
+    fn provider_factory_with_database_mock() {
+        let factory = ProviderFactory::new(DatabaseMock::default(), MAINNET.clone());
+
+        let mut rng = generators::rng();
+        let block = random_block(&mut rng, 0, None, Some(3), None);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_matches!(
+            provider_rw.insert_block(block.clone().try_seal_with_senders().unwrap(), None),
+            Ok(_)
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_hash(0).unwrap(), Some(block.hash()));
+        assert_eq!(provider.transaction_id(block.body[2].hash).unwrap(), Some(2));
+        assert_eq!(provider.transactions_by_tx_range(0..).unwrap().len(), 3);
+    }
+
     #[test]
     fn insert_block_with_prune_modes() {
         let factory = create_test_provider_factory();
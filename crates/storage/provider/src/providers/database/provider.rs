@@ -1,16 +1,23 @@
 use crate::{
     bundle_state::{BundleStateInit, BundleStateWithReceipts, HashedStateChanges, RevertsInit},
-    providers::{database::metrics, SnapshotProvider},
+    providers::{
+        database::metrics,
+        snapshot::{account_changesets_with_snapshots, storage_changesets_with_snapshots},
+        state::{historical::LowestAvailableBlocks, snap::HashedStateFallback},
+        HistoricalStateProviderRef, SnapshotProvider,
+    },
     to_range,
     traits::{
-        AccountExtReader, BlockSource, ChangeSetReader, ReceiptProvider, StageCheckpointWriter,
+        AccountExtReader, AccountRange, AccountRangeEntry, BlockSource, ChangeSetReader,
+        PlainAccountRange, ReceiptProvider, StageCheckpointWriter, StorageRange, StorageRangeEntry,
     },
-    AccountReader, BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
-    Chain, ConsensusNumberReader, ConsensusNumberWriter, EvmEnvProvider, HashingWriter,
-    HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode, HistoryWriter,
-    OriginalValuesKnown, ProviderError, PruneCheckpointReader, PruneCheckpointWriter,
-    StageCheckpointReader, StorageReader, TransactionVariant, TransactionsProvider,
-    TransactionsProviderExt, WithdrawalsProvider,
+    AccountReader, AddressAppearanceReader, BlockExecutionWriter, BlockHashReader, BlockNumReader,
+    BlockReader, BlockWriter, Chain, ConsensusNumberReader, ConsensusNumberWriter, EvmEnvProvider,
+    HashingWriter, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode,
+    HistoryWriter, LogIndexEntries, LogIndexReader, OriginalValuesKnown, ProviderError,
+    PruneCheckpointReader, PruneCheckpointWriter, StageCheckpointReader, StateRangeReader,
+    StorageReader, TransactionVariant, TransactionsProvider, TransactionsProviderExt,
+    WithdrawalsProvider,
 };
 use ahash::{AHashMap, AHashSet};
 use itertools::{izip, Itertools};
@@ -44,8 +51,9 @@ use reth_primitives::{
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
     ChainInfo, ChainSpec, GotExpected, Hardfork, Head, Header, PruneCheckpoint, PruneModes,
     PruneSegment, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, SnapshotSegment,
-    StorageEntry, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256, U256,
+    StorageEntry, TransactionKind, TransactionMeta, TransactionSigned,
+    TransactionSignedEcRecovered, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256,
+    U256,
 };
 use reth_trie::{
     hashed_cursor::HashedPostState, prefix_set::PrefixSetMut, updates::TrieUpdates, StateRoot,
@@ -162,6 +170,58 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         }
         Ok(items)
     }
+
+    /// Returns the range of blocks that is completely covered by an optional block index, or
+    /// `None` if the index is not maintained.
+    ///
+    /// The index is built up to the checkpoint of its stage, starting right after the prune
+    /// checkpoint of its segment, which also covers the blocks the stage skipped.
+    fn sharded_index_range(
+        &self,
+        stage_id: StageId,
+        segment: PruneSegment,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        let Some(checkpoint) = self.get_stage_checkpoint(stage_id)? else { return Ok(None) };
+        let lowest_indexed = self
+            .get_prune_checkpoint(segment)?
+            .and_then(|checkpoint| checkpoint.block_number)
+            .map_or(0, |block_number| block_number + 1);
+
+        Ok(Some(lowest_indexed..=checkpoint.block_number))
+    }
+
+    /// Returns the blocks within the given range that are recorded for the key in a block index
+    /// sharded by [`ShardedKey`], in ascending order.
+    fn sharded_index_blocks<T, K>(
+        &self,
+        key: K,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>>
+    where
+        T: Table<Key = ShardedKey<K>, Value = BlockNumberList>,
+        K: PartialEq + Clone,
+    {
+        let mut blocks = Vec::new();
+        let mut cursor = self.tx.cursor_read::<T>()?;
+        // The first shard that can contain the start of the range is the one with the lowest
+        // highest block number that is greater or equal to it.
+        let mut shard = cursor.seek(ShardedKey::new(key.clone(), *range.start()))?;
+        while let Some((sharded_key, list)) = shard {
+            if sharded_key.key != key {
+                break
+            }
+            for block_number in list.iter(0).map(|i| i as BlockNumber) {
+                if block_number > *range.end() {
+                    return Ok(blocks)
+                }
+                if block_number >= *range.start() {
+                    blocks.push(block_number);
+                }
+            }
+            shard = cursor.next()?;
+        }
+        Ok(blocks)
+    }
 }
 
 /// For a given key, unwind all history shards that are below the given block number.
@@ -239,6 +299,36 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         &self.tx
     }
 
+    /// Returns a state provider for the state at the start of the given block.
+    ///
+    /// Snapshotted change sets are read from the [SnapshotProvider], if one is set, and reads of
+    /// pruned account or storage history fail.
+    pub fn history_state_provider_ref(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<HistoricalStateProviderRef<'_, TX>> {
+        let lowest_available_block = |segment| -> ProviderResult<_> {
+            Ok(self
+                .get_prune_checkpoint(segment)?
+                .and_then(|checkpoint| checkpoint.block_number)
+                .map(|block_number| block_number + 1))
+        };
+        let lowest_available_blocks = LowestAvailableBlocks {
+            account_history_block_number: lowest_available_block(PruneSegment::AccountHistory)?,
+            storage_history_block_number: lowest_available_block(PruneSegment::StorageHistory)?,
+        };
+
+        let mut state_provider = HistoricalStateProviderRef::new_with_lowest_available_blocks(
+            &self.tx,
+            block_number,
+            lowest_available_blocks,
+        );
+        if let Some(snapshot_provider) = &self.snapshot_provider {
+            state_provider = state_provider.with_snapshot_provider(snapshot_provider);
+        }
+        Ok(state_provider)
+    }
+
     /// Return full table as Vec
     pub fn table<T: Table>(&self) -> Result<Vec<KeyValue<T>>, DatabaseError>
     where
@@ -278,7 +368,11 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         if let Some(snapshot_provider) = &self.snapshot_provider {
             // If there is, check the maximum block or transaction number of the segment.
             if let Some(snapshot_upper_bound) = match segment {
-                SnapshotSegment::Headers => snapshot_provider.get_highest_snapshot_block(segment),
+                SnapshotSegment::Headers |
+                SnapshotSegment::AccountChangeSets |
+                SnapshotSegment::StorageChangeSets => {
+                    snapshot_provider.get_highest_snapshot_block(segment)
+                }
                 SnapshotSegment::Transactions | SnapshotSegment::Receipts => {
                     snapshot_provider.get_highest_snapshot_tx(segment)
                 }
@@ -325,7 +419,9 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         if let Some(provider) = &self.snapshot_provider {
             // If there is, check the maximum block or transaction number of the segment.
             let snapshot_upper_bound = match segment {
-                SnapshotSegment::Headers => provider.get_highest_snapshot_block(segment),
+                SnapshotSegment::Headers |
+                SnapshotSegment::AccountChangeSets |
+                SnapshotSegment::StorageChangeSets => provider.get_highest_snapshot_block(segment),
                 SnapshotSegment::Transactions | SnapshotSegment::Receipts => {
                     provider.get_highest_snapshot_tx(segment)
                 }
@@ -938,8 +1034,12 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
     {
         for (partial_key, indices) in index_updates {
             let last_shard = self.take_shard::<T>(sharded_key_factory(partial_key, u64::MAX))?;
+            // Indices at or above the first new one can only be left over from a partial unwind,
+            // see `unwind_address_appearance_index`, and are replaced.
+            let first_index = indices.first().copied().unwrap_or(u64::MAX);
             // chunk indices and insert them in shards of N size.
-            let indices = last_shard.iter().chain(indices.iter());
+            let indices =
+                last_shard.iter().filter(|index| **index < first_index).chain(indices.iter());
             let chunks = indices
                 .chunks(sharded_key::NUM_OF_INDICES_IN_SHARD)
                 .into_iter()
@@ -962,11 +1062,106 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
         }
         Ok(())
     }
+
+    /// Unwinds a block index sharded by [`ShardedKey`], removing all blocks starting from the
+    /// lowest one recorded for each key.
+    fn unwind_sharded_index<T, K>(&self, keys: &BTreeMap<K, Vec<BlockNumber>>) -> ProviderResult<()>
+    where
+        T: Table<Key = ShardedKey<K>, Value = BlockNumberList>,
+        K: PartialEq + Clone,
+    {
+        let mut cursor = self.tx.cursor_write::<T>()?;
+        for (key, blocks) in keys {
+            let rem_index = *blocks.first().expect("indexed blocks are never empty");
+            let partial_shard = unwind_history_shards::<_, T, _>(
+                &mut cursor,
+                ShardedKey::last(key.clone()),
+                rem_index,
+                |sharded_key| sharded_key.key == *key,
+            )?;
+
+            // Check the last returned partial shard.
+            // If it's not empty, the shard needs to be reinserted.
+            if !partial_shard.is_empty() {
+                cursor.insert(
+                    ShardedKey::last(key.clone()),
+                    BlockNumberList::new_pre_sorted(partial_shard),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Unwinds the address appearance index for the given range of blocks, if it's maintained and
+    /// has indexed any of them.
+    ///
+    /// Accounts that were only accessed during execution can't be recovered from the block data,
+    /// so their entries for the range are left in place. They are above the new checkpoint, hence
+    /// not read until the `IndexAddressAppearances` stage indexes the range again, after which the
+    /// ones that aren't replaced only add candidate blocks to address queries.
+    fn unwind_address_appearance_index(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let Some(checkpoint) = self.get_stage_checkpoint(StageId::IndexAddressAppearances)? else {
+            return Ok(())
+        };
+        if checkpoint.block_number < *range.start() {
+            return Ok(())
+        }
+
+        let indexed_end = checkpoint.block_number.min(*range.end());
+        let appearances = self.address_appearances_with_range(*range.start()..=indexed_end)?;
+        self.unwind_address_appearance_indices(&appearances)?;
+        self.save_stage_checkpoint(
+            StageId::IndexAddressAppearances,
+            StageCheckpoint::new(range.start().saturating_sub(1)),
+        )
+    }
+
+    /// Extends the log index with the given range of blocks.
+    ///
+    /// The index is only extended if it's maintained, i.e. the `IndexLogs` stage has a checkpoint,
+    /// and that checkpoint is right before the range. Otherwise, the stage catches up on the next
+    /// pipeline run.
+    fn update_log_index(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
+        let Some(checkpoint) = self.get_stage_checkpoint(StageId::IndexLogs)? else {
+            return Ok(())
+        };
+        if checkpoint.block_number + 1 != *range.start() {
+            return Ok(())
+        }
+
+        let entries = self.log_index_with_range(range.clone())?;
+        self.insert_log_index(entries)?;
+        self.save_stage_checkpoint(StageId::IndexLogs, StageCheckpoint::new(*range.end()))
+    }
+
+    /// Unwinds the log index for the given range of blocks, if it's maintained and has indexed any
+    /// of them.
+    fn unwind_log_index(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
+        let Some(checkpoint) = self.get_stage_checkpoint(StageId::IndexLogs)? else {
+            return Ok(())
+        };
+        if checkpoint.block_number < *range.start() {
+            return Ok(())
+        }
+
+        let indexed_end = checkpoint.block_number.min(*range.end());
+        self.unwind_log_indices(*range.start()..=indexed_end)?;
+        self.save_stage_checkpoint(
+            StageId::IndexLogs,
+            StageCheckpoint::new(range.start().saturating_sub(1)),
+        )
+    }
 }
 
 impl<TX: DbTx> AccountReader for DatabaseProvider<TX> {
     fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
-        Ok(self.tx.get::<tables::PlainAccountState>(address)?)
+        if let Some(account) = self.tx.get::<tables::PlainAccountState>(address)? {
+            return Ok(Some(account))
+        }
+        Ok(HashedStateFallback::default().account(&self.tx, address)?)
     }
 }
 
@@ -975,13 +1170,11 @@ impl<TX: DbTx> AccountExtReader for DatabaseProvider<TX> {
         &self,
         range: impl RangeBounds<BlockNumber>,
     ) -> ProviderResult<BTreeSet<Address>> {
-        self.tx
-            .cursor_read::<tables::AccountChangeSet>()?
-            .walk_range(range)?
-            .map(|entry| {
-                entry.map(|(_, account_before)| account_before.address).map_err(Into::into)
-            })
-            .collect()
+        Ok(self
+            .account_changesets(range)?
+            .into_iter()
+            .map(|(_, account_before)| account_before.address)
+            .collect())
     }
 
     fn basic_accounts(
@@ -999,35 +1192,460 @@ impl<TX: DbTx> AccountExtReader for DatabaseProvider<TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<Address, Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
-
-        let account_transitions = changeset_cursor.walk_range(range)?.try_fold(
-            BTreeMap::new(),
-            |mut accounts: BTreeMap<Address, Vec<u64>>, entry| -> ProviderResult<_> {
-                let (index, account) = entry?;
-                accounts.entry(account.address).or_default().push(index);
-                Ok(accounts)
-            },
-        )?;
+        let mut account_transitions = BTreeMap::<Address, Vec<u64>>::new();
+        for (index, account) in self.account_changesets(range)? {
+            account_transitions.entry(account.address).or_default().push(index);
+        }
 
         Ok(account_transitions)
     }
 }
 
+impl<TX: DbTx> AddressAppearanceReader for DatabaseProvider<TX> {
+    fn address_appearances_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<BlockNumber>>> {
+        let mut appearances = BTreeMap::<Address, BTreeSet<BlockNumber>>::new();
+
+        // Senders, recipients, created contracts and log emitters of the transactions.
+        let mut transactions_cursor = self.tx.cursor_read::<tables::Transactions>()?;
+        let mut senders_cursor = self.tx.cursor_read::<tables::TxSenders>()?;
+        let mut receipts_cursor = self.tx.cursor_read::<tables::Receipts>()?;
+        for entry in self.tx.cursor_read::<tables::BlockBodyIndices>()?.walk_range(range.clone())? {
+            let (block_number, body) = entry?;
+            for tx_number in body.tx_num_range() {
+                let (_, transaction) = transactions_cursor
+                    .seek_exact(tx_number)?
+                    .ok_or(ProviderError::TransactionNotFound(tx_number.into()))?;
+                let sender = match senders_cursor.seek_exact(tx_number)? {
+                    Some((_, sender)) => sender,
+                    None => {
+                        transaction.recover_signer().ok_or(ProviderError::SenderRecoveryError)?
+                    }
+                };
+                appearances.entry(sender).or_default().insert(block_number);
+
+                let recipient = match transaction.kind() {
+                    TransactionKind::Call(to) => *to,
+                    TransactionKind::Create => sender.create(transaction.nonce()),
+                };
+                appearances.entry(recipient).or_default().insert(block_number);
+
+                if let Some((_, receipt)) = receipts_cursor.seek_exact(tx_number)? {
+                    for log in receipt.logs {
+                        appearances.entry(log.address).or_default().insert(block_number);
+                    }
+                }
+            }
+        }
+
+        // Accounts touched by internal calls and creations, block rewards and withdrawals.
+        for (address, blocks) in self.changed_accounts_and_blocks_with_range(range.clone())? {
+            appearances.entry(address).or_default().extend(blocks);
+        }
+        for ((address, _), blocks) in self.changed_storages_and_blocks_with_range(range)? {
+            appearances.entry(address).or_default().extend(blocks);
+        }
+
+        Ok(appearances
+            .into_iter()
+            .map(|(address, blocks)| (address, blocks.into_iter().collect()))
+            .collect())
+    }
+
+    fn address_appearance_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.sharded_index_blocks::<tables::AddressAppearances, _>(address, range)
+    }
+
+    fn address_appearances_indexed_range(
+        &self,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.sharded_index_range(StageId::IndexAddressAppearances, PruneSegment::AddressAppearances)
+    }
+}
+
+impl<TX: DbTx> LogIndexReader for DatabaseProvider<TX> {
+    fn log_index_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<LogIndexEntries> {
+        let mut addresses = BTreeMap::<Address, BTreeSet<BlockNumber>>::new();
+        let mut topics = BTreeMap::<B256, BTreeSet<BlockNumber>>::new();
+
+        let mut receipts_cursor = self.tx.cursor_read::<tables::Receipts>()?;
+        for entry in self.tx.cursor_read::<tables::BlockBodyIndices>()?.walk_range(range)? {
+            let (block_number, body) = entry?;
+            if body.tx_count == 0 {
+                continue
+            }
+            for receipt in receipts_cursor.walk_range(body.tx_num_range())? {
+                let (_, receipt) = receipt?;
+                for log in receipt.logs {
+                    addresses.entry(log.address).or_default().insert(block_number);
+                    for topic in log.topics {
+                        topics.entry(topic).or_default().insert(block_number);
+                    }
+                }
+            }
+        }
+
+        Ok(LogIndexEntries {
+            addresses: addresses
+                .into_iter()
+                .map(|(address, blocks)| (address, blocks.into_iter().collect()))
+                .collect(),
+            topics: topics
+                .into_iter()
+                .map(|(topic, blocks)| (topic, blocks.into_iter().collect()))
+                .collect(),
+        })
+    }
+
+    fn log_address_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.sharded_index_blocks::<tables::LogAddressIndex, _>(address, range)
+    }
+
+    fn log_topic_blocks(
+        &self,
+        topic: B256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.sharded_index_blocks::<tables::LogTopicIndex, _>(topic, range)
+    }
+
+    fn logs_indexed_range(&self) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.sharded_index_range(StageId::IndexLogs, PruneSegment::LogIndex)
+    }
+}
+
+impl<TX: DbTx> DatabaseProvider<TX> {
+    /// Returns the account changesets of the block range, including the ones moved to snapshots.
+    fn account_changesets(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        account_changesets_with_snapshots(&self.tx, self.snapshot_provider.as_deref(), range)
+    }
+
+    /// Returns the storage changesets of the block range, including the ones moved to snapshots.
+    fn storage_changesets(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        storage_changesets_with_snapshots(&self.tx, self.snapshot_provider.as_deref(), range)
+    }
+
+    /// Returns an error if the changesets needed to revert the state to the end of the given block
+    /// were pruned.
+    fn ensure_state_revertible_to(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        for segment in [PruneSegment::AccountHistory, PruneSegment::StorageHistory] {
+            let pruned_until =
+                self.get_prune_checkpoint(segment)?.and_then(|checkpoint| checkpoint.block_number);
+            if pruned_until.map_or(false, |pruned_until| pruned_until > block_number) {
+                return Err(ProviderError::StateAtBlockPruned(block_number))
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the accounts that changed after the given block, with their info at the end of the
+    /// block.
+    fn account_reverts(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<BTreeMap<Address, Option<Account>>> {
+        let mut reverts = BTreeMap::new();
+        for (_, AccountBeforeTx { address, info }) in self.account_changesets(block_number + 1..)? {
+            // the first change after the block holds the value at the end of the block
+            reverts.entry(address).or_insert(info);
+        }
+        Ok(reverts)
+    }
+
+    /// Returns the storage slots of the matching accounts that changed after the given block, with
+    /// their value at the end of the block, keyed by address.
+    fn storage_reverts(
+        &self,
+        block_number: BlockNumber,
+        mut matches: impl FnMut(Address) -> bool,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>> {
+        let mut reverts = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for (index, StorageEntry { key, value }) in self.storage_changesets(block_number + 1..)? {
+            let address = index.address();
+            if matches(address) {
+                // the first change after the block holds the value at the end of the block
+                reverts.entry(address).or_default().entry(key).or_insert(value);
+            }
+        }
+        Ok(reverts)
+    }
+
+    /// Walks the accounts of the table from `start`, merged with the reverted accounts which take
+    /// precedence, until `limit` accounts that existed at the end of the block are collected.
+    ///
+    /// Returns the collected accounts and the key of the first account after them, if any.
+    fn walk_accounts_with_reverts<T>(
+        &self,
+        start: T::Key,
+        reverts: &BTreeMap<T::Key, Option<Account>>,
+        limit: usize,
+    ) -> ProviderResult<(Vec<(T::Key, Account)>, Option<T::Key>)>
+    where
+        T: Table<Value = Account>,
+        T::Key: Copy,
+    {
+        let mut reverts = reverts.range(start..).peekable();
+        let mut cursor = self.tx.cursor_read::<T>()?;
+        let mut current = cursor.seek(start)?;
+
+        let mut accounts = Vec::new();
+        loop {
+            let take_revert = match (current.as_ref(), reverts.peek()) {
+                (None, None) => break,
+                (Some((key, _)), Some((revert_key, _))) => *revert_key <= key,
+                (Some(_), None) => false,
+                (None, Some(_)) => true,
+            };
+
+            let (key, account) = if take_revert {
+                let (key, info) = reverts.next().expect("is some");
+                if current.as_ref().map(|(current_key, _)| current_key) == Some(key) {
+                    current = cursor.next()?;
+                }
+                (*key, *info)
+            } else {
+                let (key, account) = current.take().expect("is some");
+                current = cursor.next()?;
+                (key, Some(account))
+            };
+
+            // the account did not exist at the end of the block
+            let Some(account) = account else { continue };
+
+            if accounts.len() == limit {
+                return Ok((accounts, Some(key)))
+            }
+            accounts.push((key, account));
+        }
+
+        Ok((accounts, None))
+    }
+}
+
+impl<TX: DbTx> StateRangeReader for DatabaseProvider<TX> {
+    fn account_range(
+        &self,
+        block_number: BlockNumber,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<AccountRange> {
+        self.ensure_state_revertible_to(block_number)?;
+
+        let mut addresses = HashMap::new();
+        let mut reverts = BTreeMap::new();
+        for (address, info) in self.account_reverts(block_number)? {
+            let hashed_address = keccak256(address);
+            addresses.insert(hashed_address, address);
+            reverts.insert(hashed_address, info);
+        }
+
+        let (accounts, next) =
+            self.walk_accounts_with_reverts::<tables::HashedAccount>(start, &reverts, limit)?;
+        let accounts = accounts
+            .into_iter()
+            .map(|(hashed_address, account)| AccountRangeEntry {
+                hashed_address,
+                address: addresses.get(&hashed_address).copied(),
+                account,
+            })
+            .collect();
+        Ok(AccountRange { accounts, next })
+    }
+
+    fn plain_accounts(
+        &self,
+        block_number: BlockNumber,
+        start: Address,
+        limit: usize,
+    ) -> ProviderResult<PlainAccountRange> {
+        self.ensure_state_revertible_to(block_number)?;
+
+        let reverts = self.account_reverts(block_number)?;
+        let (accounts, next) =
+            self.walk_accounts_with_reverts::<tables::PlainAccountState>(start, &reverts, limit)?;
+        Ok(PlainAccountRange { accounts, next })
+    }
+
+    fn plain_storages(
+        &self,
+        block_number: BlockNumber,
+        addresses: &BTreeSet<Address>,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>> {
+        self.ensure_state_revertible_to(block_number)?;
+
+        let mut cursor = self.tx.cursor_dup_read::<tables::PlainStorageState>()?;
+        let mut storages = BTreeMap::new();
+        for address in addresses {
+            let mut storage = BTreeMap::new();
+            for entry in cursor.walk_dup(Some(*address), None)? {
+                let (_, StorageEntry { key, value }) = entry?;
+                storage.insert(key, value);
+            }
+            storages.insert(*address, storage);
+        }
+
+        let reverts = self.storage_reverts(block_number, |changed| addresses.contains(&changed))?;
+        for (address, slots) in reverts {
+            storages.entry(address).or_default().extend(slots);
+        }
+        for storage in storages.values_mut() {
+            storage.retain(|_, value| *value != U256::ZERO);
+        }
+        Ok(storages)
+    }
+
+    fn storage_range(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<StorageRange> {
+        self.ensure_state_revertible_to(block_number)?;
+
+        let mut preimages = HashMap::new();
+        let mut reverts = BTreeMap::new();
+        for (slot, value) in self
+            .storage_reverts(block_number, |changed| changed == address)?
+            .into_values()
+            .flatten()
+        {
+            let hashed_slot = keccak256(slot);
+            preimages.insert(hashed_slot, slot);
+            reverts.insert(hashed_slot, value);
+        }
+
+        // walk the hashed storage from `start`, merged with the reverted slots which take
+        // precedence
+        let mut reverts = reverts.range(start..).peekable();
+        let mut cursor = self.tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut current = cursor.seek_by_key_subkey(keccak256(address), start)?;
+
+        let mut slots = Vec::new();
+        loop {
+            let take_revert = match (current.as_ref(), reverts.peek()) {
+                (None, None) => break,
+                (Some(entry), Some((revert_slot, _))) => **revert_slot <= entry.key,
+                (Some(_), None) => false,
+                (None, Some(_)) => true,
+            };
+
+            let (hashed_slot, value) = if take_revert {
+                let (hashed_slot, value) = reverts.next().expect("is some");
+                if current.as_ref().map(|entry| entry.key) == Some(*hashed_slot) {
+                    current = cursor.next_dup_val()?;
+                }
+                (*hashed_slot, *value)
+            } else {
+                let entry = current.take().expect("is some");
+                current = cursor.next_dup_val()?;
+                (entry.key, entry.value)
+            };
+
+            // the slot was empty at the end of the block
+            if value == U256::ZERO {
+                continue
+            }
+
+            if slots.len() == limit {
+                return Ok(StorageRange { slots, next: Some(hashed_slot) })
+            }
+            slots.push(StorageRangeEntry {
+                hashed_slot,
+                slot: preimages.get(&hashed_slot).copied(),
+                value,
+            });
+        }
+
+        Ok(StorageRange { slots, next: None })
+    }
+
+    fn hashed_storages(
+        &self,
+        block_number: BlockNumber,
+        hashed_addresses: &BTreeSet<B256>,
+    ) -> ProviderResult<BTreeMap<B256, BTreeMap<B256, U256>>> {
+        self.ensure_state_revertible_to(block_number)?;
+
+        let mut cursor = self.tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut storages = BTreeMap::new();
+        for hashed_address in hashed_addresses {
+            let mut storage = BTreeMap::new();
+            for entry in cursor.walk_dup(Some(*hashed_address), None)? {
+                let (_, StorageEntry { key, value }) = entry?;
+                storage.insert(key, value);
+            }
+            storages.insert(*hashed_address, storage);
+        }
+
+        let reverts = self.storage_reverts(block_number, |changed| {
+            hashed_addresses.contains(&keccak256(changed))
+        })?;
+        for (address, slots) in reverts {
+            storages
+                .entry(keccak256(address))
+                .or_default()
+                .extend(slots.into_iter().map(|(slot, value)| (keccak256(slot), value)));
+        }
+        for storage in storages.values_mut() {
+            storage.retain(|_, value| *value != U256::ZERO);
+        }
+        Ok(storages)
+    }
+
+    fn modified_accounts(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeSet<Address>> {
+        let mut accounts = self.changed_accounts_with_range(range.clone())?;
+        accounts.extend(self.changed_storages_with_range(range)?.into_keys());
+        Ok(accounts)
+    }
+}
+
 impl<TX: DbTx> ChangeSetReader for DatabaseProvider<TX> {
     fn account_block_changeset(
         &self,
         block_number: BlockNumber,
     ) -> ProviderResult<Vec<AccountBeforeTx>> {
-        let range = block_number..=block_number;
-        self.tx
-            .cursor_read::<tables::AccountChangeSet>()?
-            .walk_range(range)?
-            .map(|result| -> ProviderResult<_> {
-                let (_, account_before) = result?;
-                Ok(account_before)
-            })
-            .collect()
+        let changeset = self.get_with_snapshot(
+            SnapshotSegment::AccountChangeSets,
+            block_number,
+            |snapshot| snapshot.account_block_changeset(block_number).map(Some),
+            || {
+                let range = block_number..=block_number;
+                self.tx
+                    .cursor_read::<tables::AccountChangeSet>()?
+                    .walk_range(range)?
+                    .map(|result| -> ProviderResult<_> {
+                        let (_, account_before) = result?;
+                        Ok(account_before)
+                    })
+                    .collect::<ProviderResult<Vec<_>>>()
+                    .map(Some)
+            },
+        )?;
+
+        Ok(changeset.unwrap_or_default())
     }
 }
 
@@ -1869,36 +2487,26 @@ impl<TX: DbTx> StorageReader for DatabaseProvider<TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<Address, BTreeSet<B256>>> {
-        self.tx
-            .cursor_read::<tables::StorageChangeSet>()?
-            .walk_range(BlockNumberAddress::range(range))?
-            // fold all storages and save its old state so we can remove it from HashedStorage
-            // it is needed as it is dup table.
-            .try_fold(BTreeMap::new(), |mut accounts: BTreeMap<Address, BTreeSet<B256>>, entry| {
-                let (BlockNumberAddress((_, address)), storage_entry) = entry?;
-                accounts.entry(address).or_default().insert(storage_entry.key);
-                Ok(accounts)
-            })
+        // fold all storages and save its old state so we can remove it from HashedStorage
+        // it is needed as it is dup table.
+        let mut accounts = BTreeMap::<Address, BTreeSet<B256>>::new();
+        for (BlockNumberAddress((_, address)), storage_entry) in self.storage_changesets(range)? {
+            accounts.entry(address).or_default().insert(storage_entry.key);
+        }
+        Ok(accounts)
     }
 
     fn changed_storages_and_blocks_with_range(
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<(Address, B256), Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::StorageChangeSet>()?;
-
-        let storage_changeset_lists =
-            changeset_cursor.walk_range(BlockNumberAddress::range(range))?.try_fold(
-                BTreeMap::new(),
-                |mut storages: BTreeMap<(Address, B256), Vec<u64>>, entry| -> ProviderResult<_> {
-                    let (index, storage) = entry?;
-                    storages
-                        .entry((index.address(), storage.key))
-                        .or_default()
-                        .push(index.block_number());
-                    Ok(storages)
-                },
-            )?;
+        let mut storage_changeset_lists = BTreeMap::<(Address, B256), Vec<u64>>::new();
+        for (index, storage) in self.storage_changesets(range)? {
+            storage_changeset_lists
+                .entry((index.address(), storage.key))
+                .or_default()
+                .push(index.block_number());
+        }
 
         Ok(storage_changeset_lists)
     }
@@ -2216,6 +2824,40 @@ impl<TX: DbTxMut + DbTx> HistoryWriter for DatabaseProvider<TX> {
         let changesets = last_indices.len();
         Ok(changesets)
     }
+
+    fn unwind_address_appearance_indices(
+        &self,
+        appearances: &BTreeMap<Address, Vec<BlockNumber>>,
+    ) -> ProviderResult<usize> {
+        self.unwind_sharded_index::<tables::AddressAppearances, _>(appearances)?;
+
+        Ok(appearances.values().map(Vec::len).sum())
+    }
+
+    fn insert_address_appearance_index(
+        &self,
+        appearances: BTreeMap<Address, Vec<u64>>,
+    ) -> ProviderResult<()> {
+        self.append_history_index::<_, tables::AddressAppearances>(appearances, ShardedKey::new)
+    }
+
+    fn unwind_log_indices(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<usize> {
+        // Like address appearances, the indexed addresses and topics are recovered from the
+        // receipts of the range.
+        let entries = self.log_index_with_range(range)?;
+        self.unwind_sharded_index::<tables::LogAddressIndex, _>(&entries.addresses)?;
+        self.unwind_sharded_index::<tables::LogTopicIndex, _>(&entries.topics)?;
+
+        Ok(entries.len())
+    }
+
+    fn insert_log_index(&self, entries: LogIndexEntries) -> ProviderResult<()> {
+        self.append_history_index::<_, tables::LogAddressIndex>(
+            entries.addresses,
+            ShardedKey::new,
+        )?;
+        self.append_history_index::<_, tables::LogTopicIndex>(entries.topics, ShardedKey::new)
+    }
 }
 
 impl<TX: DbTxMut + DbTx> BlockExecutionWriter for DatabaseProvider<TX> {
@@ -2261,6 +2903,12 @@ impl<TX: DbTxMut + DbTx> BlockExecutionWriter for DatabaseProvider<TX> {
             // Unwind storage history indices.
             self.unwind_storage_history_indices(storage_range)?;
 
+            // Unwind address appearance indices.
+            self.unwind_address_appearance_index(range.clone())?;
+
+            // Unwind log indices.
+            self.unwind_log_index(range.clone())?;
+
             // Calculate the reverted merkle root.
             // This is the same as `StateRoot::incremental_root_with_updates`, only the prefix sets
             // are pre-loaded.
@@ -2487,6 +3135,7 @@ impl<TX: DbTxMut + DbTx> BlockWriter for DatabaseProvider<TX> {
         durations_recorder.record_relative(metrics::Action::InsertHashes);
 
         self.update_history_indices(first_number..=last_block_number)?;
+        self.update_log_index(first_number..=last_block_number)?;
         durations_recorder.record_relative(metrics::Action::InsertHistoryIndices);
 
         // Update pipeline progress
@@ -1,10 +1,11 @@
 use crate::{
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    BlockchainTreePendingStateProvider, BundleStateDataProvider, CanonChainTracker,
-    CanonStateNotifications, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, HeaderProvider, ProviderError, PruneCheckpointReader, ReceiptProvider,
+    AccountRange, AccountReader, AddressAppearanceReader, BlockHashReader, BlockIdReader,
+    BlockNumReader, BlockReader, BlockReaderIdExt, BlockchainTreePendingStateProvider,
+    BundleStateDataProvider, CanonChainTracker, CanonStateNotifications, CanonStateSubscriptions,
+    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, LogIndexEntries,
+    LogIndexReader, PlainAccountRange, ProviderError, PruneCheckpointReader, ReceiptProvider,
     ReceiptProviderIdExt, StageCheckpointReader, StateProviderBox, StateProviderFactory,
-    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    StateRangeReader, StorageRange, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{database::Database, models::StoredBlockBodyIndices};
 use reth_interfaces::{
@@ -22,7 +23,7 @@ use reth_primitives::{
 };
 use revm::primitives::{BlockEnv, CfgEnv};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     ops::{RangeBounds, RangeInclusive},
     sync::Arc,
     time::Instant,
@@ -32,6 +33,7 @@ use tracing::trace;
 pub use state::{
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    snap::has_snap_synced_state,
 };
 
 mod bundle_state_provider;
@@ -505,6 +507,140 @@ where
     }
 }
 
+impl<DB, Tree> AddressAppearanceReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn address_appearances_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<BlockNumber>>> {
+        self.database.provider()?.address_appearances_with_range(range)
+    }
+
+    fn address_appearance_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.database.provider()?.address_appearance_blocks(address, range)
+    }
+
+    fn address_appearances_indexed_range(
+        &self,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.database.provider()?.address_appearances_indexed_range()
+    }
+
+    fn address_appearance_candidates(
+        &self,
+        addresses: &[Address],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        self.database.provider()?.address_appearance_candidates(addresses, range)
+    }
+}
+
+impl<DB, Tree> StateRangeReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn account_range(
+        &self,
+        block_number: BlockNumber,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<AccountRange> {
+        self.database.provider()?.account_range(block_number, start, limit)
+    }
+
+    fn plain_accounts(
+        &self,
+        block_number: BlockNumber,
+        start: Address,
+        limit: usize,
+    ) -> ProviderResult<PlainAccountRange> {
+        self.database.provider()?.plain_accounts(block_number, start, limit)
+    }
+
+    fn plain_storages(
+        &self,
+        block_number: BlockNumber,
+        addresses: &BTreeSet<Address>,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>> {
+        self.database.provider()?.plain_storages(block_number, addresses)
+    }
+
+    fn storage_range(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<StorageRange> {
+        self.database.provider()?.storage_range(block_number, address, start, limit)
+    }
+
+    fn hashed_storages(
+        &self,
+        block_number: BlockNumber,
+        hashed_addresses: &BTreeSet<B256>,
+    ) -> ProviderResult<BTreeMap<B256, BTreeMap<B256, U256>>> {
+        self.database.provider()?.hashed_storages(block_number, hashed_addresses)
+    }
+
+    fn modified_accounts(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeSet<Address>> {
+        self.database.provider()?.modified_accounts(range)
+    }
+}
+
+impl<DB, Tree> LogIndexReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn log_index_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<LogIndexEntries> {
+        self.database.provider()?.log_index_with_range(range)
+    }
+
+    fn log_address_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.database.provider()?.log_address_blocks(address, range)
+    }
+
+    fn log_topic_blocks(
+        &self,
+        topic: B256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.database.provider()?.log_topic_blocks(topic, range)
+    }
+
+    fn logs_indexed_range(&self) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        self.database.provider()?.logs_indexed_range()
+    }
+
+    fn log_candidates(
+        &self,
+        addresses: &[Address],
+        topics: &[Vec<B256>],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        self.database.provider()?.log_candidates(addresses, topics, range)
+    }
+}
+
 impl<DB, Tree> ChainSpecProvider for BlockchainProvider<DB, Tree>
 where
     DB: Send + Sync,
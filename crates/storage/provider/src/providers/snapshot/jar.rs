@@ -1,18 +1,23 @@
 use super::LoadedJarRef;
 use crate::{
-    to_range, BlockHashReader, BlockNumReader, HeaderProvider, ReceiptProvider,
+    to_range, BlockHashReader, BlockNumReader, ChangeSetReader, HeaderProvider, ReceiptProvider,
     TransactionsProvider,
 };
 use reth_db::{
-    codecs::CompactU256,
-    snapshot::{HeaderMask, ReceiptMask, SnapshotCursor, TransactionMask},
+    codecs::{CompactU256, CompactU64},
+    models::{AccountBeforeTx, BlockNumberAddress},
+    snapshot::{
+        AccountChangeSetMask, HeaderMask, ReceiptMask, SnapshotCursor, StorageChangeSetMask,
+        TransactionMask,
+    },
 };
 use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_primitives::{
     Address, BlockHash, BlockHashOrNumber, BlockNumber, ChainInfo, Header, Receipt, SealedHeader,
-    TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, B256, U256,
+    StorageEntry, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
+    B256, U256,
 };
-use std::ops::{Deref, RangeBounds};
+use std::ops::{Deref, Range, RangeBounds};
 
 /// Provider over a specific `NippyJar` and range.
 #[derive(Debug)]
@@ -50,6 +55,135 @@ impl<'a> SnapshotJarProvider<'a> {
         self.auxiliar_jar = Some(Box::new(auxiliar_jar));
         self
     }
+
+    /// Returns the state of `address` before `block_number`, if the account was changed in that
+    /// block.
+    pub fn account_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+    ) -> ProviderResult<Option<AccountBeforeTx>> {
+        let mut cursor = self.cursor()?;
+        let row = lower_bound(&mut cursor, &(block_number, address), account_changeset_key)?;
+
+        Ok(cursor
+            .get_two::<AccountChangeSetMask<AccountBeforeTx, CompactU64>>(row.into())?
+            .filter(|(account, block)| block.0 == block_number && account.address == address)
+            .map(|(account, _)| account))
+    }
+
+    /// Returns the value of the storage slot `key` of `address` before `block_number`, if the slot
+    /// was changed in that block.
+    pub fn storage_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        key: B256,
+    ) -> ProviderResult<Option<StorageEntry>> {
+        let mut cursor = self.cursor()?;
+        let row = lower_bound(&mut cursor, &(block_number, address, key), storage_changeset_key)?;
+
+        Ok(cursor
+            .get_three::<StorageChangeSetMask<StorageEntry, CompactU64, Address>>(row.into())?
+            .filter(|(entry, block, entry_address)| {
+                block.0 == block_number && entry_address == &address && entry.key == key
+            })
+            .map(|(entry, _, _)| entry))
+    }
+
+    /// Returns the account change sets of the blocks in the range which are in this snapshot, in
+    /// block order.
+    pub fn account_changesets(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        let mut cursor = self.cursor()?;
+        let mut row =
+            lower_bound(&mut cursor, &(range.start, Address::ZERO), account_changeset_key)?;
+
+        let mut changesets = Vec::new();
+        while let Some((account, block)) =
+            cursor.get_two::<AccountChangeSetMask<AccountBeforeTx, CompactU64>>(row.into())?
+        {
+            if block.0 >= range.end {
+                break
+            }
+            changesets.push((block.0, account));
+            row += 1;
+        }
+
+        Ok(changesets)
+    }
+
+    /// Returns the storage change sets of the blocks in the range which are in this snapshot, in
+    /// block order.
+    pub fn storage_changesets(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        let mut cursor = self.cursor()?;
+        let mut row = lower_bound(
+            &mut cursor,
+            &(range.start, Address::ZERO, B256::ZERO),
+            storage_changeset_key,
+        )?;
+
+        let mut changesets = Vec::new();
+        while let Some((entry, block, address)) =
+            cursor
+                .get_three::<StorageChangeSetMask<StorageEntry, CompactU64, Address>>(row.into())?
+        {
+            if block.0 >= range.end {
+                break
+            }
+            changesets.push((BlockNumberAddress((block.0, address)), entry));
+            row += 1;
+        }
+
+        Ok(changesets)
+    }
+}
+
+/// Returns the number of the first row of a change set segment whose key is not lower than `key`,
+/// or the number of rows if there's none.
+///
+/// Change set rows are sorted by their key, so the row is found with a binary search.
+fn lower_bound<K: Ord>(
+    cursor: &mut SnapshotCursor<'_>,
+    key: &K,
+    row_key: impl Fn(&mut SnapshotCursor<'_>, u64) -> ProviderResult<Option<K>>,
+) -> ProviderResult<u64> {
+    let (mut low, mut high) = (0, cursor.jar().rows() as u64);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match row_key(cursor, middle)? {
+            Some(middle_key) if &middle_key < key => low = middle + 1,
+            _ => high = middle,
+        }
+    }
+
+    Ok(low)
+}
+
+/// Returns the key of an account change set row: its block number and address.
+fn account_changeset_key(
+    cursor: &mut SnapshotCursor<'_>,
+    row: u64,
+) -> ProviderResult<Option<(BlockNumber, Address)>> {
+    Ok(cursor
+        .get_two::<AccountChangeSetMask<AccountBeforeTx, CompactU64>>(row.into())?
+        .map(|(account, block)| (block.0, account.address)))
+}
+
+/// Returns the key of a storage change set row: its block number, address and storage key.
+fn storage_changeset_key(
+    cursor: &mut SnapshotCursor<'_>,
+    row: u64,
+) -> ProviderResult<Option<(BlockNumber, Address, B256)>> {
+    Ok(cursor
+        .get_three::<StorageChangeSetMask<StorageEntry, CompactU64, Address>>(row.into())?
+        .map(|(entry, block, address)| (block.0, address, entry.key)))
 }
 
 impl<'a> HeaderProvider for SnapshotJarProvider<'a> {
@@ -303,3 +437,16 @@ impl<'a> ReceiptProvider for SnapshotJarProvider<'a> {
         Ok(receipts)
     }
 }
+
+impl<'a> ChangeSetReader for SnapshotJarProvider<'a> {
+    fn account_block_changeset(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Vec<AccountBeforeTx>> {
+        Ok(self
+            .account_changesets(block_number..block_number + 1)?
+            .into_iter()
+            .map(|(_, account)| account)
+            .collect())
+    }
+}
@@ -1,14 +1,14 @@
 use super::{LoadedJar, SnapshotJarProvider};
 use crate::{
-    to_range, BlockHashReader, BlockNumReader, BlockReader, BlockSource, HeaderProvider,
-    ReceiptProvider, TransactionVariant, TransactionsProvider, TransactionsProviderExt,
-    WithdrawalsProvider,
+    to_range, BlockHashReader, BlockNumReader, BlockReader, BlockSource, ChangeSetReader,
+    HeaderProvider, ReceiptProvider, TransactionVariant, TransactionsProvider,
+    TransactionsProviderExt, WithdrawalsProvider,
 };
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use reth_db::{
     codecs::CompactU256,
-    models::StoredBlockBodyIndices,
+    models::{AccountBeforeTx, BlockNumberAddress, StoredBlockBodyIndices},
     snapshot::{iter_snapshots, HeaderMask, ReceiptMask, SnapshotCursor, TransactionMask},
 };
 use reth_interfaces::provider::{ProviderError, ProviderResult};
@@ -16,8 +16,8 @@ use reth_nippy_jar::NippyJar;
 use reth_primitives::{
     snapshot::HighestSnapshots, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber,
     BlockWithSenders, ChainInfo, Header, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, SnapshotSegment, TransactionMeta, TransactionSigned, TransactionSignedNoHash,
-    TxHash, TxNumber, Withdrawal, B256, U256,
+    SealedHeader, SnapshotSegment, StorageEntry, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256, U256,
 };
 use std::{
     collections::{hash_map::Entry, BTreeMap, HashMap},
@@ -327,7 +327,11 @@ impl SnapshotProvider {
         P: FnMut(&T) -> bool,
     {
         let get_provider = |start: u64| match segment {
-            SnapshotSegment::Headers => self.get_segment_provider_from_block(segment, start, None),
+            SnapshotSegment::Headers |
+            SnapshotSegment::AccountChangeSets |
+            SnapshotSegment::StorageChangeSets => {
+                self.get_segment_provider_from_block(segment, start, None)
+            }
             SnapshotSegment::Transactions | SnapshotSegment::Receipts => {
                 self.get_segment_provider_from_transaction(segment, start, None)
             }
@@ -359,6 +363,75 @@ impl SnapshotProvider {
 
         Ok(result)
     }
+
+    /// Returns the state of `address` before `block_number` from the account change sets
+    /// snapshots, if the account was changed in that block.
+    pub fn account_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+    ) -> ProviderResult<Option<AccountBeforeTx>> {
+        self.get_segment_provider_from_block(
+            SnapshotSegment::AccountChangeSets,
+            block_number,
+            None,
+        )?
+        .account_changeset(block_number, address)
+    }
+
+    /// Returns the value of the storage slot `key` of `address` before `block_number` from the
+    /// storage change sets snapshots, if the slot was changed in that block.
+    pub fn storage_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        key: B256,
+    ) -> ProviderResult<Option<StorageEntry>> {
+        self.get_segment_provider_from_block(
+            SnapshotSegment::StorageChangeSets,
+            block_number,
+            None,
+        )?
+        .storage_changeset(block_number, address, key)
+    }
+
+    /// Returns the account change sets of the blocks in the range from the account change sets
+    /// snapshots, in block order.
+    pub fn account_changesets(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        self.fetch_changesets(SnapshotSegment::AccountChangeSets, range, |provider, range| {
+            provider.account_changesets(range)
+        })
+    }
+
+    /// Returns the storage change sets of the blocks in the range from the storage change sets
+    /// snapshots, in block order.
+    pub fn storage_changesets(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        self.fetch_changesets(SnapshotSegment::StorageChangeSets, range, |provider, range| {
+            provider.storage_changesets(range)
+        })
+    }
+
+    /// Fetches the change sets of a block range across multiple snapshot files of the segment.
+    fn fetch_changesets<T>(
+        &self,
+        segment: SnapshotSegment,
+        mut range: Range<BlockNumber>,
+        get_fn: impl Fn(&SnapshotJarProvider<'_>, Range<BlockNumber>) -> ProviderResult<Vec<T>>,
+    ) -> ProviderResult<Vec<T>> {
+        let mut changesets = Vec::new();
+        while range.start < range.end {
+            let provider = self.get_segment_provider_from_block(segment, range.start, None)?;
+            changesets.extend(get_fn(&provider, range.clone())?);
+            range.start = provider.user_header().block_end() + 1;
+        }
+        Ok(changesets)
+    }
 }
 
 impl HeaderProvider for SnapshotProvider {
@@ -590,6 +663,20 @@ impl TransactionsProvider for SnapshotProvider {
     }
 }
 
+impl ChangeSetReader for SnapshotProvider {
+    fn account_block_changeset(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Vec<AccountBeforeTx>> {
+        self.get_segment_provider_from_block(
+            SnapshotSegment::AccountChangeSets,
+            block_number,
+            None,
+        )?
+        .account_block_changeset(block_number)
+    }
+}
+
 /* Cannot be successfully implemented but must exist for trait requirements */
 
 impl BlockNumReader for SnapshotProvider {
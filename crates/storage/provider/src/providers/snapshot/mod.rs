@@ -4,10 +4,22 @@ pub use manager::SnapshotProvider;
 mod jar;
 pub use jar::SnapshotJarProvider;
 
+use crate::to_range;
+use reth_db::{
+    cursor::DbCursorRO,
+    models::{AccountBeforeTx, BlockNumberAddress},
+    tables,
+    transaction::DbTx,
+};
 use reth_interfaces::provider::ProviderResult;
 use reth_nippy_jar::NippyJar;
-use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment};
-use std::{ops::Deref, sync::Arc};
+use reth_primitives::{
+    snapshot::SegmentHeader, Address, BlockNumber, SnapshotSegment, StorageEntry,
+};
+use std::{
+    ops::{Deref, Range, RangeBounds},
+    sync::Arc,
+};
 
 /// Alias type for each specific `NippyJar`.
 type LoadedJarRef<'a> = dashmap::mapref::one::Ref<'a, (u64, SnapshotSegment), LoadedJar>;
@@ -38,6 +50,77 @@ impl Deref for LoadedJar {
     }
 }
 
+/// Returns the account change sets of the block range in block order.
+///
+/// The blocks which were moved to snapshots are read from the snapshot provider, the rest from
+/// the database.
+pub(crate) fn account_changesets_with_snapshots<TX: DbTx>(
+    tx: &TX,
+    snapshot_provider: Option<&SnapshotProvider>,
+    range: impl RangeBounds<BlockNumber>,
+) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+    let (mut changesets, range) = snapshotted_changesets(
+        snapshot_provider,
+        SnapshotSegment::AccountChangeSets,
+        to_range(range),
+        |provider, range| provider.account_changesets(range),
+    )?;
+
+    if range.start < range.end {
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(range)? {
+            changesets.push(entry?);
+        }
+    }
+    Ok(changesets)
+}
+
+/// Returns the storage change sets of the block range in block order.
+///
+/// The blocks which were moved to snapshots are read from the snapshot provider, the rest from
+/// the database.
+pub(crate) fn storage_changesets_with_snapshots<TX: DbTx>(
+    tx: &TX,
+    snapshot_provider: Option<&SnapshotProvider>,
+    range: impl RangeBounds<BlockNumber>,
+) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+    let (mut changesets, range) = snapshotted_changesets(
+        snapshot_provider,
+        SnapshotSegment::StorageChangeSets,
+        to_range(range),
+        |provider, range| provider.storage_changesets(range),
+    )?;
+
+    if range.start < range.end {
+        let range = BlockNumberAddress((range.start, Address::ZERO))..
+            BlockNumberAddress((range.end, Address::ZERO));
+        for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk_range(range)? {
+            changesets.push(entry?);
+        }
+    }
+    Ok(changesets)
+}
+
+/// Reads the change sets of the block range which were moved to snapshots of the segment, and
+/// returns them with the rest of the range that is still in the database.
+fn snapshotted_changesets<T>(
+    snapshot_provider: Option<&SnapshotProvider>,
+    segment: SnapshotSegment,
+    mut range: Range<BlockNumber>,
+    fetch: impl FnOnce(&SnapshotProvider, Range<BlockNumber>) -> ProviderResult<Vec<T>>,
+) -> ProviderResult<(Vec<T>, Range<BlockNumber>)> {
+    let mut changesets = Vec::new();
+    if let Some(provider) = snapshot_provider {
+        if let Some(highest_block) = provider.get_highest_snapshot_block(segment) {
+            if range.start <= highest_block {
+                let end = range.end.min(highest_block + 1);
+                changesets = fetch(provider, range.start..end)?;
+                range.start = end;
+            }
+        }
+    }
+    Ok((changesets, range))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1,10 +1,15 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, AccountReader, BlockHashReader,
-    BundleStateWithReceipts, ProviderError, StateProvider, StateRootProvider,
+    providers::{
+        snapshot::{account_changesets_with_snapshots, storage_changesets_with_snapshots},
+        state::{macros::delegate_provider_impls, snap::HashedStateFallback},
+        SnapshotProvider,
+    },
+    AccountReader, BlockHashReader, BundleStateWithReceipts, ProviderError, StateProvider,
+    StateRootProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
-    models::{storage_sharded_key::StorageShardedKey, ShardedKey},
+    models::{storage_sharded_key::StorageShardedKey, AccountBeforeTx, ShardedKey},
     table::Table,
     tables,
     transaction::DbTx,
@@ -12,9 +17,11 @@ use reth_db::{
 };
 use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{
-    trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
+    stage::StageId, trie::AccountProof, Account, Address, BlockNumber, Bytecode, SnapshotSegment,
+    StorageEntry, StorageKey, StorageValue, B256,
 };
-use reth_trie::updates::TrieUpdates;
+use reth_trie::{hashed_cursor::HashedPostState, proof::Proof, updates::TrieUpdates};
+use std::sync::Arc;
 
 /// State provider for a given block number which takes a tx reference.
 ///
@@ -27,6 +34,11 @@ use reth_trie::updates::TrieUpdates;
 /// - [tables::StorageHistory]
 /// - [tables::AccountChangeSet]
 /// - [tables::StorageChangeSet]
+///
+/// Change sets which were already moved to snapshots are read from the [SnapshotProvider], if one
+/// is set. Accounts and storage slots that are missing from the plain state are read from the
+/// hashed state if the state was snap synced, see
+/// [`has_snap_synced_state`](crate::has_snap_synced_state).
 #[derive(Debug)]
 pub struct HistoricalStateProviderRef<'b, TX: DbTx> {
     /// Transaction
@@ -35,6 +47,10 @@ pub struct HistoricalStateProviderRef<'b, TX: DbTx> {
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// Snapshot provider of the snapshotted change sets.
+    snapshot_provider: Option<&'b SnapshotProvider>,
+    /// Reads of the state that is missing from the plain state.
+    hashed_state: HashedStateFallback,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -48,7 +64,13 @@ pub enum HistoryInfo {
 impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
     /// Create new StateProvider for historical block number
     pub fn new(tx: &'b TX, block_number: BlockNumber) -> Self {
-        Self { tx, block_number, lowest_available_blocks: Default::default() }
+        Self {
+            tx,
+            block_number,
+            lowest_available_blocks: Default::default(),
+            snapshot_provider: None,
+            hashed_state: Default::default(),
+        }
     }
 
     /// Create new StateProvider for historical block number and lowest block numbers at which
@@ -58,7 +80,72 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         block_number: BlockNumber,
         lowest_available_blocks: LowestAvailableBlocks,
     ) -> Self {
-        Self { tx, block_number, lowest_available_blocks }
+        Self {
+            tx,
+            block_number,
+            lowest_available_blocks,
+            snapshot_provider: None,
+            hashed_state: Default::default(),
+        }
+    }
+
+    /// Reads the change sets which were moved to snapshots from the provided [SnapshotProvider].
+    pub fn with_snapshot_provider(mut self, snapshot_provider: &'b SnapshotProvider) -> Self {
+        self.snapshot_provider = Some(snapshot_provider);
+        self
+    }
+
+    /// Returns the [SnapshotProvider] if the change sets of the block are snapshotted for the
+    /// segment.
+    fn snapshotted_changesets(
+        &self,
+        segment: SnapshotSegment,
+        block_number: BlockNumber,
+    ) -> Option<&'b SnapshotProvider> {
+        self.snapshot_provider.filter(|provider| {
+            provider
+                .get_highest_snapshot_block(segment)
+                .is_some_and(|highest_block| highest_block >= block_number)
+        })
+    }
+
+    /// Lookup the state of an account before the block in the account change sets.
+    fn account_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+    ) -> ProviderResult<Option<AccountBeforeTx>> {
+        if let Some(provider) =
+            self.snapshotted_changesets(SnapshotSegment::AccountChangeSets, block_number)
+        {
+            return provider.account_changeset(block_number, address)
+        }
+
+        Ok(self
+            .tx
+            .cursor_dup_read::<tables::AccountChangeSet>()?
+            .seek_by_key_subkey(block_number, address)?
+            .filter(|acc| acc.address == address))
+    }
+
+    /// Lookup the value of a storage slot before the block in the storage change sets.
+    fn storage_changeset(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageEntry>> {
+        if let Some(provider) =
+            self.snapshotted_changesets(SnapshotSegment::StorageChangeSets, block_number)
+        {
+            return provider.storage_changeset(block_number, address, storage_key)
+        }
+
+        Ok(self
+            .tx
+            .cursor_dup_read::<tables::StorageChangeSet>()?
+            .seek_by_key_subkey((block_number, address).into(), storage_key)?
+            .filter(|entry| entry.key == storage_key))
     }
 
     /// Lookup an account in the AccountHistory table
@@ -95,6 +182,50 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         )
     }
 
+    /// Collects the reverts from the changesets of all blocks after and including the block
+    /// number, which revert the hashed state and tries of the last block processed by the
+    /// [`MerkleExecute`][StageId::MerkleExecute] stage to the state at this block.
+    ///
+    /// Blocks above the merkle checkpoint are not included in the tries yet, e.g. while the
+    /// pipeline is syncing, so their change sets must not be reverted.
+    fn revert_state(&self) -> ProviderResult<HashedPostState> {
+        if !self.lowest_available_blocks.is_account_history_available(self.block_number) ||
+            !self.lowest_available_blocks.is_storage_history_available(self.block_number)
+        {
+            return Err(ProviderError::StateAtBlockPruned(self.block_number))
+        }
+
+        // The state at the block number is the state after the previous block, so it can only be
+        // computed if the merkle stage has processed at least the previous block.
+        let tip = self
+            .tx
+            .get::<tables::SyncStage>(StageId::MerkleExecute.to_string())?
+            .map(|checkpoint| checkpoint.block_number)
+            .filter(|tip| tip + 1 >= self.block_number)
+            .ok_or(ProviderError::StateRootNotAvailableForHistoricalBlock)?;
+        let range = self.block_number..=tip;
+
+        // The change sets which were already moved to snapshots are read from there.
+        let account_changes =
+            account_changesets_with_snapshots(self.tx, self.snapshot_provider, range.clone())?;
+        let storage_changes =
+            storage_changesets_with_snapshots(self.tx, self.snapshot_provider, range)?;
+        HashedPostState::from_reverts(
+            account_changes.into_iter().map(|(_, account)| Ok(account)),
+            storage_changes.into_iter().map(|(index, storage)| Ok((index.address(), storage))),
+        )
+    }
+
+    /// Returns the reverts to the block number with the bundle state applied on top.
+    fn hashed_state_with_bundle(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<HashedPostState> {
+        let mut hashed_state = self.revert_state()?;
+        hashed_state.extend(bundle_state.hash_state_slow());
+        Ok(hashed_state.sorted())
+    }
+
     fn history_info<T, K>(
         &self,
         key: K,
@@ -158,17 +289,17 @@ impl<'b, TX: DbTx> AccountReader for HistoricalStateProviderRef<'b, TX> {
         match self.account_history_lookup(address)? {
             HistoryInfo::NotYetWritten => Ok(None),
             HistoryInfo::InChangeset(changeset_block_number) => Ok(self
-                .tx
-                .cursor_dup_read::<tables::AccountChangeSet>()?
-                .seek_by_key_subkey(changeset_block_number, address)?
-                .filter(|acc| acc.address == address)
+                .account_changeset(changeset_block_number, address)?
                 .ok_or(ProviderError::AccountChangesetNotFound {
                     block_number: changeset_block_number,
                     address,
                 })?
                 .info),
             HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState => {
-                Ok(self.tx.get::<tables::PlainAccountState>(address)?)
+                if let Some(account) = self.tx.get::<tables::PlainAccountState>(address)? {
+                    return Ok(Some(account))
+                }
+                Ok(self.hashed_state.account(self.tx, address)?)
             }
         }
     }
@@ -199,16 +330,34 @@ impl<'b, TX: DbTx> BlockHashReader for HistoricalStateProviderRef<'b, TX> {
 }
 
 impl<'b, TX: DbTx> StateRootProvider for HistoricalStateProviderRef<'b, TX> {
-    fn state_root(&self, _bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
-        Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        let hashed_state = self.hashed_state_with_bundle(bundle_state)?;
+        bundle_state
+            .state_root_calculator(self.tx, &hashed_state)
+            .root()
+            .map_err(|err| ProviderError::Database(err.into()))
     }
 
+    /// Trie updates are computed against the trie of the latest block, so they can't be
+    /// returned for a historical block.
     fn state_root_with_updates(
         &self,
         _bundle_state: &BundleStateWithReceipts,
     ) -> ProviderResult<(B256, TrieUpdates)> {
         Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
     }
+
+    fn state_proof(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+        address: Address,
+        keys: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        let hashed_state = self.hashed_state_with_bundle(bundle_state)?;
+        Proof::overlay(self.tx, &hashed_state)
+            .account_proof(address, keys)
+            .map_err(|err| ProviderError::Database(err.into()))
+    }
 }
 
 impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
@@ -221,10 +370,7 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
         match self.storage_history_lookup(address, storage_key)? {
             HistoryInfo::NotYetWritten => Ok(None),
             HistoryInfo::InChangeset(changeset_block_number) => Ok(Some(
-                self.tx
-                    .cursor_dup_read::<tables::StorageChangeSet>()?
-                    .seek_by_key_subkey((changeset_block_number, address).into(), storage_key)?
-                    .filter(|entry| entry.key == storage_key)
+                self.storage_changeset(changeset_block_number, address, storage_key)?
                     .ok_or_else(|| ProviderError::StorageChangesetNotFound {
                         block_number: changeset_block_number,
                         address,
@@ -232,13 +378,19 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
                     })?
                     .value,
             )),
-            HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState => Ok(self
-                .tx
-                .cursor_dup_read::<tables::PlainStorageState>()?
-                .seek_by_key_subkey(address, storage_key)?
-                .filter(|entry| entry.key == storage_key)
-                .map(|entry| entry.value)
-                .or(Some(StorageValue::ZERO))),
+            HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState => {
+                let plain = self
+                    .tx
+                    .cursor_dup_read::<tables::PlainStorageState>()?
+                    .seek_by_key_subkey(address, storage_key)?
+                    .filter(|entry| entry.key == storage_key)
+                    .map(|entry| entry.value);
+                let value = match plain {
+                    Some(value) => Some(value),
+                    None => self.hashed_state.storage(self.tx, address, storage_key)?,
+                };
+                Ok(value.or(Some(StorageValue::ZERO)))
+            }
         }
     }
 
@@ -248,8 +400,11 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
     }
 
     /// Get account and storage proofs.
-    fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
-        Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        let revert_state = self.revert_state()?;
+        Proof::overlay(self.tx, &revert_state)
+            .account_proof(address, keys)
+            .map_err(|err| ProviderError::Database(err.into()))
     }
 }
 
@@ -263,12 +418,28 @@ pub struct HistoricalStateProvider<TX: DbTx> {
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// Snapshot provider of the snapshotted change sets.
+    snapshot_provider: Option<Arc<SnapshotProvider>>,
+    /// Reads of the state that is missing from the plain state.
+    hashed_state: HashedStateFallback,
 }
 
 impl<TX: DbTx> HistoricalStateProvider<TX> {
     /// Create new StateProvider for historical block number
     pub fn new(tx: TX, block_number: BlockNumber) -> Self {
-        Self { tx, block_number, lowest_available_blocks: Default::default() }
+        Self {
+            tx,
+            block_number,
+            lowest_available_blocks: Default::default(),
+            snapshot_provider: None,
+            hashed_state: Default::default(),
+        }
+    }
+
+    /// Reads the change sets which were moved to snapshots from the provided [SnapshotProvider].
+    pub fn with_snapshot_provider(mut self, snapshot_provider: Arc<SnapshotProvider>) -> Self {
+        self.snapshot_provider = Some(snapshot_provider);
+        self
     }
 
     /// Set the lowest block number at which the account history is available.
@@ -292,11 +463,17 @@ impl<TX: DbTx> HistoricalStateProvider<TX> {
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> HistoricalStateProviderRef<'_, TX> {
-        HistoricalStateProviderRef::new_with_lowest_available_blocks(
+        let mut provider = HistoricalStateProviderRef::new_with_lowest_available_blocks(
             &self.tx,
             self.block_number,
             self.lowest_available_blocks,
-        )
+        );
+        provider.hashed_state = self.hashed_state.clone();
+
+        match &self.snapshot_provider {
+            Some(snapshot_provider) => provider.with_snapshot_provider(snapshot_provider),
+            None => provider,
+        }
     }
 }
 
@@ -335,7 +512,8 @@ impl LowestAvailableBlocks {
 mod tests {
     use crate::{
         providers::state::historical::{HistoryInfo, LowestAvailableBlocks},
-        AccountReader, HistoricalStateProvider, HistoricalStateProviderRef, StateProvider,
+        AccountReader, BundleStateWithReceipts, HistoricalStateProvider,
+        HistoricalStateProviderRef, StateProvider, StateRootProvider,
     };
     use reth_db::{
         database::Database,
@@ -346,7 +524,15 @@ mod tests {
         BlockNumberList,
     };
     use reth_interfaces::provider::ProviderError;
-    use reth_primitives::{address, b256, Account, Address, StorageEntry, B256, U256};
+    use reth_primitives::{
+        address, b256,
+        constants::EMPTY_ROOT_HASH,
+        keccak256,
+        proofs::state_root_unhashed,
+        stage::{StageCheckpoint, StageId},
+        Account, Address, StorageEntry, B256, U256,
+    };
+    use reth_trie::{proof::Proof, StateRoot};
 
     const ADDRESS: Address = address!("0000000000000000000000000000000000000001");
     const HIGHER_ADDRESS: Address = address!("0000000000000000000000000000000000000005");
@@ -580,6 +766,10 @@ mod tests {
             provider.storage_history_lookup(ADDRESS, STORAGE),
             Err(ProviderError::StateAtBlockPruned(provider.block_number))
         );
+        assert_eq!(
+            provider.proof(ADDRESS, &[STORAGE]),
+            Err(ProviderError::StateAtBlockPruned(provider.block_number))
+        );
 
         // provider block_number == lowest available block number,
         // i.e. state at provider block is available
@@ -613,4 +803,135 @@ mod tests {
             Ok(HistoryInfo::MaybeInPlainState)
         );
     }
+
+    #[test]
+    fn history_provider_state_root() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+
+        // `ADDRESS` is modified and `HIGHER_ADDRESS` is created in block 1, the tip
+        let acc_at0 = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: None };
+        let acc_plain = Account { nonce: 2, balance: U256::ZERO, bytecode_hash: None };
+        for address in [ADDRESS, HIGHER_ADDRESS] {
+            tx.put::<tables::PlainAccountState>(address, acc_plain).unwrap();
+            tx.put::<tables::HashedAccount>(keccak256(address), acc_plain).unwrap();
+        }
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx { address: ADDRESS, info: Some(acc_at0) },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx { address: HIGHER_ADDRESS, info: None },
+        )
+        .unwrap();
+        tx.put::<tables::SyncStage>(StageId::MerkleExecute.to_string(), StageCheckpoint::new(1))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+
+        // the state root at the end of block 0 is computed on the reverted state
+        let bundle = BundleStateWithReceipts::default();
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 1).state_root(&bundle),
+            Ok(state_root_unhashed([(ADDRESS, (acc_at0, EMPTY_ROOT_HASH))]))
+        );
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 2).state_root(&bundle),
+            Ok(state_root_unhashed([
+                (ADDRESS, (acc_plain, EMPTY_ROOT_HASH)),
+                (HIGHER_ADDRESS, (acc_plain, EMPTY_ROOT_HASH)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn history_provider_proof() {
+        let db = create_test_rw_db();
+        let hashed_slot = keccak256(STORAGE);
+        let acc_at0 = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+        let acc_at1 = Account { nonce: 2, balance: U256::from(5), bytecode_hash: None };
+
+        // block 0 creates `ADDRESS` with a storage slot, its proof is computed at the tip
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::PlainAccountState>(ADDRESS, acc_at0).unwrap();
+        tx.put::<tables::HashedAccount>(keccak256(ADDRESS), acc_at0).unwrap();
+        tx.put::<tables::PlainStorageState>(
+            ADDRESS,
+            StorageEntry { key: STORAGE, value: U256::from(1) },
+        )
+        .unwrap();
+        tx.put::<tables::HashedStorage>(
+            keccak256(ADDRESS),
+            StorageEntry { key: hashed_slot, value: U256::from(1) },
+        )
+        .unwrap();
+        let (_, updates) = StateRoot::from_tx(&tx).root_with_updates().unwrap();
+        updates.flush(&tx).unwrap();
+        tx.put::<tables::SyncStage>(StageId::MerkleExecute.to_string(), StageCheckpoint::new(0))
+            .unwrap();
+        let expected = Proof::new(&tx).account_proof(ADDRESS, &[STORAGE]).unwrap();
+        let expected_higher = Proof::new(&tx).account_proof(HIGHER_ADDRESS, &[]).unwrap();
+        tx.commit().unwrap();
+
+        // block 1 modifies `ADDRESS` and its slot and creates `HIGHER_ADDRESS`
+        let tx = db.tx_mut().unwrap();
+        for address in [ADDRESS, HIGHER_ADDRESS] {
+            tx.put::<tables::PlainAccountState>(address, acc_at1).unwrap();
+            tx.put::<tables::HashedAccount>(keccak256(address), acc_at1).unwrap();
+        }
+        tx.delete::<tables::PlainStorageState>(ADDRESS, None).unwrap();
+        tx.put::<tables::PlainStorageState>(
+            ADDRESS,
+            StorageEntry { key: STORAGE, value: U256::from(2) },
+        )
+        .unwrap();
+        tx.delete::<tables::HashedStorage>(keccak256(ADDRESS), None).unwrap();
+        tx.put::<tables::HashedStorage>(
+            keccak256(ADDRESS),
+            StorageEntry { key: hashed_slot, value: U256::from(2) },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx { address: ADDRESS, info: Some(acc_at0) },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx { address: HIGHER_ADDRESS, info: None },
+        )
+        .unwrap();
+        tx.put::<tables::StorageChangeSet>(
+            (1, ADDRESS).into(),
+            StorageEntry { key: STORAGE, value: U256::from(1) },
+        )
+        .unwrap();
+        let (_, updates) = StateRoot::incremental_root_with_updates(&tx, 1..=1).unwrap();
+        updates.flush(&tx).unwrap();
+        tx.put::<tables::SyncStage>(StageId::MerkleExecute.to_string(), StageCheckpoint::new(1))
+            .unwrap();
+        // headers above the merkle checkpoint are ignored
+        for number in 0..=5 {
+            tx.put::<tables::CanonicalHeaders>(number, B256::random()).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let provider = HistoricalStateProviderRef::new(&tx, 1);
+        assert_eq!(provider.proof(ADDRESS, &[STORAGE]), Ok(expected));
+        assert_eq!(provider.proof(HIGHER_ADDRESS, &[]), Ok(expected_higher));
+        assert_ne!(
+            HistoricalStateProviderRef::new(&tx, 2).proof(ADDRESS, &[STORAGE]),
+            provider.proof(ADDRESS, &[STORAGE])
+        );
+
+        // the state above the merkle checkpoint is not in the tries yet
+        assert_eq!(
+            HistoricalStateProviderRef::new(&tx, 3).proof(ADDRESS, &[STORAGE]),
+            Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, AccountReader, BlockHashReader,
-    BundleStateWithReceipts, StateProvider, StateRootProvider,
+    providers::state::{macros::delegate_provider_impls, snap::HashedStateFallback},
+    AccountReader, BlockHashReader, BundleStateWithReceipts, StateProvider, StateRootProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
@@ -14,23 +14,31 @@ use reth_primitives::{
 use reth_trie::{proof::Proof, updates::TrieUpdates};
 
 /// State provider over latest state that takes tx reference.
+///
+/// Accounts and storage slots that are missing from the plain state are read from the hashed
+/// state if the state was snap synced, see [`has_snap_synced_state`](crate::has_snap_synced_state).
 #[derive(Debug)]
 pub struct LatestStateProviderRef<'b, TX: DbTx> {
     /// database transaction
     db: &'b TX,
+    /// Reads of the state that is missing from the plain state.
+    hashed_state: HashedStateFallback,
 }
 
 impl<'b, TX: DbTx> LatestStateProviderRef<'b, TX> {
     /// Create new state provider
     pub fn new(db: &'b TX) -> Self {
-        Self { db }
+        Self { db, hashed_state: HashedStateFallback::default() }
     }
 }
 
 impl<'b, TX: DbTx> AccountReader for LatestStateProviderRef<'b, TX> {
     /// Get basic account information.
     fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
-        self.db.get::<tables::PlainAccountState>(address).map_err(Into::into)
+        if let Some(account) = self.db.get::<tables::PlainAccountState>(address)? {
+            return Ok(Some(account))
+        }
+        Ok(self.hashed_state.account(self.db, address)?)
     }
 }
 
@@ -71,6 +79,17 @@ impl<'b, TX: DbTx> StateRootProvider for LatestStateProviderRef<'b, TX> {
             .state_root_slow_with_updates(self.db)
             .map_err(|err| ProviderError::Database(err.into()))
     }
+
+    fn state_proof(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+        address: Address,
+        slots: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        bundle_state
+            .account_proof_slow(self.db, address, slots)
+            .map_err(|err| ProviderError::Database(err.into()))
+    }
 }
 
 impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
@@ -86,7 +105,7 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
                 return Ok(Some(entry.value))
             }
         }
-        Ok(None)
+        Ok(self.hashed_state.storage(self.db, account, storage_key)?)
     }
 
     /// Get account code by its hash
@@ -106,18 +125,20 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
 pub struct LatestStateProvider<TX: DbTx> {
     /// database transaction
     db: TX,
+    /// Reads of the state that is missing from the plain state.
+    hashed_state: HashedStateFallback,
 }
 
 impl<TX: DbTx> LatestStateProvider<TX> {
     /// Create new state provider
     pub fn new(db: TX) -> Self {
-        Self { db }
+        Self { db, hashed_state: HashedStateFallback::default() }
     }
 
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> LatestStateProviderRef<'_, TX> {
-        LatestStateProviderRef::new(&self.db)
+        LatestStateProviderRef { db: &self.db, hashed_state: self.hashed_state.clone() }
     }
 }
 
@@ -0,0 +1,65 @@
+//! Reads of the state that was downloaded by the [`SnapSync`][StageId::SnapSync] stage.
+
+use reth_db::{cursor::DbDupCursorRO, tables, transaction::DbTx, DatabaseError};
+use reth_primitives::{keccak256, stage::StageId, Account, Address, StorageKey, StorageValue};
+use std::sync::{Arc, OnceLock};
+
+/// Returns `true` if the state was downloaded by the [`SnapSync`][StageId::SnapSync] stage.
+///
+/// The stage only downloads the hashed state, so the plain state only contains the accounts and
+/// storage slots that were written by the blocks executed after the snap sync pivot. All other
+/// accounts and storage slots are read from the hashed state, and deletions from the plain state
+/// are applied to the hashed state as well, so that it never returns stale values.
+pub fn has_snap_synced_state<TX: DbTx>(tx: &TX) -> Result<bool, DatabaseError> {
+    Ok(tx
+        .get::<tables::SyncStageProgress>(StageId::SnapSync.to_string())?
+        .is_some_and(|progress| !progress.is_empty()))
+}
+
+/// Reads accounts and storage slots that are missing from the plain state from the hashed state,
+/// if the state was downloaded by the [`SnapSync`][StageId::SnapSync] stage.
+///
+/// Whether the state was snap synced is only looked up on the first miss of the plain state.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct HashedStateFallback(Arc<OnceLock<bool>>);
+
+impl HashedStateFallback {
+    /// Returns `true` if the plain state falls back to the hashed state.
+    fn is_enabled<TX: DbTx>(&self, tx: &TX) -> Result<bool, DatabaseError> {
+        if let Some(enabled) = self.0.get() {
+            return Ok(*enabled)
+        }
+        let enabled = has_snap_synced_state(tx)?;
+        Ok(*self.0.get_or_init(|| enabled))
+    }
+
+    /// Get an account that is missing from the plain state.
+    pub(crate) fn account<TX: DbTx>(
+        &self,
+        tx: &TX,
+        address: Address,
+    ) -> Result<Option<Account>, DatabaseError> {
+        if !self.is_enabled(tx)? {
+            return Ok(None)
+        }
+        tx.get::<tables::HashedAccount>(keccak256(address))
+    }
+
+    /// Get a storage slot that is missing from the plain state.
+    pub(crate) fn storage<TX: DbTx>(
+        &self,
+        tx: &TX,
+        address: Address,
+        storage_key: StorageKey,
+    ) -> Result<Option<StorageValue>, DatabaseError> {
+        if !self.is_enabled(tx)? {
+            return Ok(None)
+        }
+        let hashed_slot = keccak256(storage_key);
+        Ok(tx
+            .cursor_dup_read::<tables::HashedStorage>()?
+            .seek_by_key_subkey(keccak256(address), hashed_slot)?
+            .filter(|entry| entry.key == hashed_slot)
+            .map(|entry| entry.value))
+    }
+}
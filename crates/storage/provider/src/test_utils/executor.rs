@@ -4,8 +4,10 @@ use crate::{
 };
 use parking_lot::Mutex;
 use reth_interfaces::executor::BlockExecutionError;
-use reth_primitives::{BlockNumber, BlockWithSenders, ChainSpec, PruneModes, Receipt, U256};
-use std::sync::Arc;
+use reth_primitives::{
+    Address, BlockNumber, BlockWithSenders, ChainSpec, PruneModes, Receipt, U256,
+};
+use std::{collections::BTreeMap, sync::Arc};
 /// Test executor with mocked result.
 #[derive(Debug)]
 pub struct TestExecutor(pub Option<BundleStateWithReceipts>);
@@ -52,6 +54,12 @@ impl BlockExecutor for TestExecutor {
     fn size_hint(&self) -> Option<usize> {
         None
     }
+
+    fn set_record_accessed_accounts(&mut self, _record: bool) {}
+
+    fn take_accessed_accounts(&mut self) -> BTreeMap<Address, Vec<BlockNumber>> {
+        BTreeMap::new()
+    }
 }
 
 impl PrunableBlockExecutor for TestExecutor {
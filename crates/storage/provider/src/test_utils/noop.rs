@@ -1,11 +1,12 @@
 use crate::{
     bundle_state::BundleStateWithReceipts,
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
-    ReceiptProviderIdExt, StageCheckpointReader, StateProvider, StateProviderBox,
-    StateProviderFactory, StateRootProvider, TransactionVariant, TransactionsProvider,
-    WithdrawalsProvider,
+    AccountRange, AccountReader, AddressAppearanceReader, BlockHashReader, BlockIdReader,
+    BlockNumReader, BlockReader, BlockReaderIdExt, ChainSpecProvider, ChangeSetReader,
+    EvmEnvProvider, HeaderProvider, LogIndexEntries, LogIndexReader, PlainAccountRange,
+    PruneCheckpointReader, ReceiptProviderIdExt, StageCheckpointReader, StateProvider,
+    StateProviderBox, StateProviderFactory, StateRangeReader, StateRootProvider, StorageRange,
+    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::models::{AccountBeforeTx, StoredBlockBodyIndices};
 use reth_interfaces::provider::ProviderResult;
@@ -20,6 +21,7 @@ use reth_primitives::{
 use reth_trie::updates::TrieUpdates;
 use revm::primitives::{BlockEnv, CfgEnv};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     ops::{RangeBounds, RangeInclusive},
     sync::Arc,
 };
@@ -282,6 +284,111 @@ impl ChangeSetReader for NoopProvider {
     }
 }
 
+impl AddressAppearanceReader for NoopProvider {
+    fn address_appearances_with_range(
+        &self,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<BlockNumber>>> {
+        Ok(BTreeMap::default())
+    }
+
+    fn address_appearance_blocks(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
+
+    fn address_appearances_indexed_range(
+        &self,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        Ok(None)
+    }
+}
+
+impl StateRangeReader for NoopProvider {
+    fn account_range(
+        &self,
+        _block_number: BlockNumber,
+        _start: B256,
+        _limit: usize,
+    ) -> ProviderResult<AccountRange> {
+        Ok(AccountRange::default())
+    }
+
+    fn plain_accounts(
+        &self,
+        _block_number: BlockNumber,
+        _start: Address,
+        _limit: usize,
+    ) -> ProviderResult<PlainAccountRange> {
+        Ok(PlainAccountRange::default())
+    }
+
+    fn plain_storages(
+        &self,
+        _block_number: BlockNumber,
+        _addresses: &BTreeSet<Address>,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>> {
+        Ok(BTreeMap::default())
+    }
+
+    fn storage_range(
+        &self,
+        _block_number: BlockNumber,
+        _address: Address,
+        _start: B256,
+        _limit: usize,
+    ) -> ProviderResult<StorageRange> {
+        Ok(StorageRange::default())
+    }
+
+    fn hashed_storages(
+        &self,
+        _block_number: BlockNumber,
+        _hashed_addresses: &BTreeSet<B256>,
+    ) -> ProviderResult<BTreeMap<B256, BTreeMap<B256, U256>>> {
+        Ok(BTreeMap::default())
+    }
+
+    fn modified_accounts(
+        &self,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeSet<Address>> {
+        Ok(BTreeSet::default())
+    }
+}
+
+impl LogIndexReader for NoopProvider {
+    fn log_index_with_range(
+        &self,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<LogIndexEntries> {
+        Ok(LogIndexEntries::default())
+    }
+
+    fn log_address_blocks(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
+
+    fn log_topic_blocks(
+        &self,
+        _topic: B256,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
+
+    fn logs_indexed_range(&self) -> ProviderResult<Option<RangeInclusive<BlockNumber>>> {
+        Ok(None)
+    }
+}
+
 impl StateRootProvider for NoopProvider {
     fn state_root(&self, _state: &BundleStateWithReceipts) -> ProviderResult<B256> {
         Ok(B256::default())
@@ -293,6 +400,15 @@ impl StateRootProvider for NoopProvider {
     ) -> ProviderResult<(B256, TrieUpdates)> {
         Ok((B256::default(), TrieUpdates::default()))
     }
+
+    fn state_proof(
+        &self,
+        _bundle_state: &BundleStateWithReceipts,
+        _address: Address,
+        _keys: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        Ok(AccountProof::default())
+    }
 }
 
 impl StateProvider for NoopProvider {
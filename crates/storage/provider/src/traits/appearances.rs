@@ -0,0 +1,73 @@
+use auto_impl::auto_impl;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{Address, BlockNumber};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
+
+/// Address appearance reader
+#[auto_impl(&, Arc, Box)]
+pub trait AddressAppearanceReader: Send + Sync {
+    /// Iterate over the blocks in the given range and return all addresses that appear in them
+    /// alongside each specific set of blocks.
+    ///
+    /// See [`reth_db::tables::AddressAppearances`] for what is considered an appearance.
+    ///
+    /// NOTE: Get inclusive range of blocks.
+    fn address_appearances_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<BlockNumber>>>;
+
+    /// Returns the indexed blocks within the given range in which the address appears, in
+    /// ascending order.
+    ///
+    /// The result is only complete within
+    /// [address_appearances_indexed_range](Self::address_appearances_indexed_range), see
+    /// [address_appearance_candidates](Self::address_appearance_candidates).
+    fn address_appearance_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>>;
+
+    /// Returns the range of blocks that is completely covered by the address appearance index, or
+    /// `None` if the index is not maintained.
+    ///
+    /// Blocks below the range were pruned or skipped by the `IndexAddressAppearances` stage, and
+    /// blocks above it were not indexed yet. The range is empty if no block is indexed.
+    fn address_appearances_indexed_range(
+        &self,
+    ) -> ProviderResult<Option<RangeInclusive<BlockNumber>>>;
+
+    /// Returns the blocks within the given range in which any of the addresses may appear, in
+    /// ascending order, or `None` if the address appearance index is not maintained.
+    ///
+    /// Blocks outside of the indexed range can't be ruled out and are always included.
+    fn address_appearance_candidates(
+        &self,
+        addresses: &[Address],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        let Some(indexed) = self.address_appearances_indexed_range()? else { return Ok(None) };
+
+        let mut blocks = BTreeSet::new();
+        let indexed_start = (*range.start()).max(*indexed.start());
+        let indexed_end = (*range.end()).min(*indexed.end());
+        if indexed_start <= indexed_end {
+            for address in addresses {
+                blocks
+                    .extend(self.address_appearance_blocks(*address, indexed_start..=indexed_end)?);
+            }
+        }
+        if range.start() < indexed.start() {
+            blocks.extend(*range.start()..=(*range.end()).min(indexed.start() - 1));
+        }
+        if range.end() > indexed.end() {
+            blocks.extend((indexed.end() + 1).max(*range.start())..=*range.end());
+        }
+
+        Ok(Some(blocks))
+    }
+}
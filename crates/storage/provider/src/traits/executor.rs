@@ -2,8 +2,10 @@
 
 use crate::{bundle_state::BundleStateWithReceipts, StateProvider};
 use reth_interfaces::executor::BlockExecutionError;
-use reth_primitives::{BlockNumber, BlockWithSenders, ChainSpec, PruneModes, Receipt, U256};
-use std::time::Duration;
+use reth_primitives::{
+    Address, BlockNumber, BlockWithSenders, ChainSpec, PruneModes, Receipt, U256,
+};
+use std::{collections::BTreeMap, time::Duration};
 use tracing::debug;
 
 /// Executor factory that would create the EVM with particular state provider.
@@ -64,6 +66,18 @@ pub trait BlockExecutor {
 
     /// Returns the size hint of current in-memory changes.
     fn size_hint(&self) -> Option<usize>;
+
+    /// Enables or disables recording of the accounts that are accessed by the executed
+    /// transactions, see [take_accessed_accounts](BlockExecutor::take_accessed_accounts).
+    fn set_record_accessed_accounts(&mut self, record: bool);
+
+    /// Returns the accounts accessed by the transactions executed since the last call, alongside
+    /// the blocks in which they were accessed, in ascending order.
+    ///
+    /// Every account that is loaded during the execution of a transaction is included: its sender
+    /// and recipient, as well as the targets of all (static, delegate) calls, creations and
+    /// selfdestructs, even if their state didn't change. Empty if recording is disabled.
+    fn take_accessed_accounts(&mut self) -> BTreeMap<Address, Vec<BlockNumber>>;
 }
 
 /// A [BlockExecutor] capable of in-memory pruning of the data that will be written to the database.
@@ -1,3 +1,4 @@
+use crate::LogIndexEntries;
 use auto_impl::auto_impl;
 use reth_db::models::BlockNumberAddress;
 use reth_interfaces::provider::ProviderResult;
@@ -40,4 +41,27 @@ pub trait HistoryWriter: Send + Sync {
 
     /// Read account/storage changesets and update account/storage history indices.
     fn update_history_indices(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<()>;
+
+    /// Unwind and clear address appearance indices, starting from the lowest block of each
+    /// address.
+    ///
+    /// Returns number of appearances walked.
+    fn unwind_address_appearance_indices(
+        &self,
+        appearances: &BTreeMap<Address, Vec<BlockNumber>>,
+    ) -> ProviderResult<usize>;
+
+    /// Insert address appearance index to database. Used inside IndexAddressAppearances stage
+    fn insert_address_appearance_index(
+        &self,
+        appearances: BTreeMap<Address, Vec<u64>>,
+    ) -> ProviderResult<()>;
+
+    /// Unwind and clear log address and topic indices.
+    ///
+    /// Returns number of index entries walked.
+    fn unwind_log_indices(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<usize>;
+
+    /// Insert log address and topic index to database. Used inside IndexLogs stage
+    fn insert_log_index(&self, entries: LogIndexEntries) -> ProviderResult<()>;
 }
@@ -0,0 +1,121 @@
+use auto_impl::auto_impl;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{Address, BlockNumber, B256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
+
+/// Log emitters and topics found in a range of blocks, alongside each specific set of blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogIndexEntries {
+    /// Addresses of the contracts that emitted logs.
+    pub addresses: BTreeMap<Address, Vec<BlockNumber>>,
+    /// Topics of the emitted logs, regardless of their position.
+    pub topics: BTreeMap<B256, Vec<BlockNumber>>,
+}
+
+impl LogIndexEntries {
+    /// Returns `true` if no logs were found.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.topics.is_empty()
+    }
+
+    /// Returns the total number of indexed (key, block) pairs.
+    pub fn len(&self) -> usize {
+        self.addresses.values().chain(self.topics.values()).map(Vec::len).sum()
+    }
+}
+
+/// Log index reader
+#[auto_impl(&, Arc, Box)]
+pub trait LogIndexReader: Send + Sync {
+    /// Iterate over the receipts of the blocks in the given range and return all log addresses and
+    /// topics alongside the blocks in which they were emitted.
+    ///
+    /// NOTE: Get inclusive range of blocks.
+    fn log_index_with_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<LogIndexEntries>;
+
+    /// Returns the indexed blocks within the given range in which the address emitted a log, in
+    /// ascending order.
+    fn log_address_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>>;
+
+    /// Returns the indexed blocks within the given range that contain a log with the topic at any
+    /// position, in ascending order.
+    fn log_topic_blocks(
+        &self,
+        topic: B256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>>;
+
+    /// Returns the range of blocks that is completely covered by the log index, or `None` if the
+    /// index is not maintained.
+    ///
+    /// Blocks below the range were pruned or skipped by the `IndexLogs` stage, and blocks above it
+    /// were not indexed yet. The range is empty if no block is indexed.
+    fn logs_indexed_range(&self) -> ProviderResult<Option<RangeInclusive<BlockNumber>>>;
+
+    /// Returns the blocks within the given range that may contain logs matching the filter, in
+    /// ascending order, or `None` if the log index is not maintained.
+    ///
+    /// A log matches if it was emitted by any of the `addresses`, and for every position in
+    /// `topics`, has any of the listed topics. Empty lists match everything. Since topics are
+    /// indexed regardless of their position, the returned blocks are a superset of the matching
+    /// ones.
+    ///
+    /// Blocks outside of the indexed range can't be ruled out and are always included.
+    fn log_candidates(
+        &self,
+        addresses: &[Address],
+        topics: &[Vec<B256>],
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Option<BTreeSet<BlockNumber>>> {
+        let Some(indexed_range) = self.logs_indexed_range()? else { return Ok(None) };
+
+        let mut blocks = BTreeSet::new();
+        let indexed_start = (*range.start()).max(*indexed_range.start());
+        let indexed_end = (*range.end()).min(*indexed_range.end());
+        if indexed_start <= indexed_end {
+            let indexed = indexed_start..=indexed_end;
+
+            let mut candidates: Option<BTreeSet<BlockNumber>> = None;
+            if !addresses.is_empty() {
+                let mut matching = BTreeSet::new();
+                for address in addresses {
+                    matching.extend(self.log_address_blocks(*address, indexed.clone())?);
+                }
+                candidates = Some(matching);
+            }
+            for position in topics.iter().filter(|position| !position.is_empty()) {
+                let mut matching = BTreeSet::new();
+                for topic in position {
+                    matching.extend(self.log_topic_blocks(*topic, indexed.clone())?);
+                }
+                candidates = Some(match candidates {
+                    Some(candidates) => candidates.intersection(&matching).copied().collect(),
+                    None => matching,
+                });
+            }
+
+            match candidates {
+                Some(candidates) => blocks.extend(candidates),
+                None => blocks.extend(indexed),
+            }
+        }
+        if range.start() < indexed_range.start() {
+            blocks.extend(*range.start()..=(*range.end()).min(indexed_range.start() - 1));
+        }
+        if range.end() > indexed_range.end() {
+            blocks.extend((indexed_range.end() + 1).max(*range.start())..=*range.end());
+        }
+
+        Ok(Some(blocks))
+    }
+}
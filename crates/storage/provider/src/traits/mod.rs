@@ -66,6 +66,18 @@ pub use hashing::HashingWriter;
 mod history;
 pub use history::HistoryWriter;
 
+mod appearances;
+pub use appearances::AddressAppearanceReader;
+
+mod logs;
+pub use logs::{LogIndexEntries, LogIndexReader};
+
+mod state_range;
+pub use state_range::{
+    AccountRange, AccountRangeEntry, PlainAccountRange, StateRangeReader, StorageRange,
+    StorageRangeEntry,
+};
+
 mod prune_checkpoint;
 pub use prune_checkpoint::{PruneCheckpointReader, PruneCheckpointWriter};
 
@@ -243,4 +243,12 @@ pub trait StateRootProvider: Send + Sync {
         &self,
         bundle_state: &BundleStateWithReceipts,
     ) -> ProviderResult<(B256, TrieUpdates)>;
+
+    /// Returns the account and storage proofs of the BundleState on top of the current state.
+    fn state_proof(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+        address: Address,
+        keys: &[B256],
+    ) -> ProviderResult<AccountProof>;
 }
@@ -0,0 +1,135 @@
+use auto_impl::auto_impl;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{Account, Address, BlockNumber, B256, U256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
+
+/// An account of an [AccountRange].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountRangeEntry {
+    /// The keccak256 hash of the address.
+    pub hashed_address: B256,
+    /// The address of the account, if its preimage is known.
+    pub address: Option<Address>,
+    /// The account.
+    pub account: Account,
+}
+
+/// A page of accounts, ordered by their hashed address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountRange {
+    /// The accounts of the page.
+    pub accounts: Vec<AccountRangeEntry>,
+    /// The hashed address of the first account after the page, if any.
+    pub next: Option<B256>,
+}
+
+/// A storage slot of a [StorageRange].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageRangeEntry {
+    /// The keccak256 hash of the slot.
+    pub hashed_slot: B256,
+    /// The slot, if its preimage is known.
+    pub slot: Option<B256>,
+    /// The value of the slot.
+    pub value: U256,
+}
+
+/// A page of the storage of an account, ordered by hashed slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageRange {
+    /// The slots of the page.
+    pub slots: Vec<StorageRangeEntry>,
+    /// The hashed slot of the first slot after the page, if any.
+    pub next: Option<B256>,
+}
+
+/// A page of accounts, ordered by their address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlainAccountRange {
+    /// The accounts of the page.
+    pub accounts: Vec<(Address, Account)>,
+    /// The address of the first account after the page, if any.
+    pub next: Option<Address>,
+}
+
+/// Reads ranges of the state as it was at the end of a block.
+///
+/// The state of a past block is the latest state with all changes of later blocks reverted, so the
+/// changesets of all blocks after the requested block need to be available.
+#[auto_impl(&, Arc, Box)]
+pub trait StateRangeReader: Send + Sync {
+    /// Returns up to `limit` accounts at the end of the given block, ordered by hashed address and
+    /// starting at the hashed address `start`.
+    ///
+    /// Addresses are only known for accounts that changed after the block, since the hashed state
+    /// does not store preimages.
+    fn account_range(
+        &self,
+        block_number: BlockNumber,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<AccountRange>;
+
+    /// Returns up to `limit` accounts at the end of the given block, ordered by address and
+    /// starting at the address `start`.
+    fn plain_accounts(
+        &self,
+        block_number: BlockNumber,
+        start: Address,
+        limit: usize,
+    ) -> ProviderResult<PlainAccountRange>;
+
+    /// Returns the non-zero storage of the given accounts at the end of the given block, keyed by
+    /// address and slot.
+    ///
+    /// The changesets after the block are scanned once for all accounts.
+    fn plain_storages(
+        &self,
+        block_number: BlockNumber,
+        addresses: &BTreeSet<Address>,
+    ) -> ProviderResult<BTreeMap<Address, BTreeMap<B256, U256>>>;
+
+    /// Returns the non-zero storage of the account at the end of the given block, keyed by slot.
+    fn plain_storage(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+    ) -> ProviderResult<BTreeMap<B256, U256>> {
+        let mut storages = self.plain_storages(block_number, &BTreeSet::from([address]))?;
+        Ok(storages.remove(&address).unwrap_or_default())
+    }
+
+    /// Returns up to `limit` non-zero storage slots of the account at the end of the given block,
+    /// ordered by hashed slot and starting at the hashed slot `start`.
+    ///
+    /// Slots are only known for storage that changed after the block, since the hashed state does
+    /// not store preimages.
+    fn storage_range(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        start: B256,
+        limit: usize,
+    ) -> ProviderResult<StorageRange>;
+
+    /// Returns the non-zero storage of the accounts with the given hashed addresses at the end of
+    /// the given block, keyed by hashed address and hashed slot.
+    ///
+    /// The changesets after the block are scanned once for all accounts.
+    fn hashed_storages(
+        &self,
+        block_number: BlockNumber,
+        hashed_addresses: &BTreeSet<B256>,
+    ) -> ProviderResult<BTreeMap<B256, BTreeMap<B256, U256>>>;
+
+    /// Returns all accounts whose info or storage changed in the given range of blocks.
+    ///
+    /// NOTE: Get inclusive range of blocks.
+    fn modified_accounts(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<BTreeSet<Address>>;
+}
@@ -304,6 +304,8 @@ where
     }
 
     /// Returns converted [PooledTransactionsElement] for the given transaction hashes.
+    ///
+    /// Transactions that must not be propagated, like private transactions, are skipped.
     pub(crate) fn get_pooled_transaction_elements(
         &self,
         tx_hashes: Vec<TxHash>,
@@ -312,7 +314,7 @@ where
         let transactions = self.get_all(tx_hashes);
         let mut elements = Vec::with_capacity(transactions.len());
         let mut size = 0;
-        for transaction in transactions {
+        for transaction in transactions.into_iter().filter(|tx| tx.propagate) {
             let tx = transaction.to_recovered_transaction().into_signed();
             let pooled = if tx.is_eip4844() {
                 if let Some(blob) = self.get_blob_transaction(tx) {
@@ -211,6 +211,9 @@ pub trait TransactionPool: Send + Sync + Clone {
     ///
     /// If the transaction is a blob transaction, the sidecar will be included.
     ///
+    /// Transactions that are not allowed to be propagated, see [TransactionOrigin::Private], are
+    /// not returned.
+    ///
     /// Consumer: P2P
     fn get_pooled_transaction_elements(
         &self,
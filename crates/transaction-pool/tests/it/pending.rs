@@ -1,7 +1,7 @@
 use assert_matches::assert_matches;
 use reth_transaction_pool::{
     test_utils::{testing_pool, MockTransactionFactory},
-    TransactionOrigin, TransactionPool,
+    GetPooledTransactionLimit, TransactionOrigin, TransactionPool,
 };
 
 #[tokio::test(flavor = "multi_thread")]
@@ -23,3 +23,31 @@ async fn txpool_new_pending_txs() {
     assert_matches!(added_result, Ok(hash) if hash == transaction.transaction.get_hash());
     assert_matches!(best_txns.next(), Some(tx) if tx.transaction.get_hash() == transaction.transaction.get_hash());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_private_txs_not_pooled_elements() {
+    let txpool = testing_pool();
+    let mut mock_tx_factory = MockTransactionFactory::default();
+    let private = mock_tx_factory.create_eip1559();
+    let external = mock_tx_factory.create_eip1559();
+
+    let private_hash = txpool
+        .add_transaction(TransactionOrigin::Private, private.transaction.clone())
+        .await
+        .unwrap();
+    let external_hash = txpool
+        .add_transaction(TransactionOrigin::External, external.transaction.clone())
+        .await
+        .unwrap();
+
+    // private transactions are pending, but never handed out to peers
+    assert!(txpool.get(&private_hash).is_some());
+    assert_eq!(txpool.get_private_transactions().len(), 1);
+    let elements = txpool.get_pooled_transaction_elements(
+        vec![private_hash, external_hash],
+        GetPooledTransactionLimit::None,
+    );
+    assert_eq!(elements.len(), 1);
+    assert_eq!(*elements[0].hash(), external_hash);
+    assert_eq!(txpool.pooled_transaction_hashes(), vec![external_hash]);
+}
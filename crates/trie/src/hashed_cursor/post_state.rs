@@ -3,10 +3,15 @@ use crate::prefix_set::{PrefixSet, PrefixSetMut};
 use ahash::{AHashMap, AHashSet};
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
+    models::{AccountBeforeTx, BlockNumberAddress},
     tables,
     transaction::DbTx,
+    DatabaseError,
 };
-use reth_primitives::{trie::Nibbles, Account, StorageEntry, B256, U256};
+use reth_primitives::{
+    keccak256, trie::Nibbles, Account, Address, BlockNumber, StorageEntry, B256, U256,
+};
+use std::{collections::hash_map::Entry, ops::RangeInclusive};
 
 /// The post state account storage with hashed slots.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -64,6 +69,27 @@ impl HashedStorage {
     pub fn insert_zero_valued_slot(&mut self, slot: B256) {
         self.zero_valued_slots.insert(slot);
     }
+
+    /// Extends the storage with the slots of another storage, which take precedence. If the other
+    /// storage was wiped, it replaces this storage.
+    pub fn extend(&mut self, other: Self) {
+        if other.wiped {
+            *self = other;
+            return
+        }
+
+        let mut slots = self.storage_slots().collect::<AHashMap<_, _>>();
+        slots.extend(other.storage_slots());
+
+        *self = Self::new(self.wiped);
+        for (slot, value) in slots {
+            if value == U256::ZERO {
+                self.insert_zero_valued_slot(slot);
+            } else {
+                self.insert_non_zero_valued_storage(slot, value);
+            }
+        }
+    }
 }
 
 /// The post state with hashed addresses as keys.
@@ -91,6 +117,76 @@ impl Default for HashedPostState {
 }
 
 impl HashedPostState {
+    /// Initializes [HashedPostState] from the reverts of the given block range.
+    ///
+    /// Records the state of every account and storage slot before its first change within the
+    /// range. If the range ends at the tip, the result is an overlay that reverts the hashed state
+    /// to the start of the first block of the range.
+    pub fn from_revert_range<TX: DbTx>(
+        tx: &TX,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<Self, DatabaseError> {
+        let mut account_changeset_cursor = tx.cursor_read::<tables::AccountChangeSet>()?;
+        let mut storage_changeset_cursor = tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+        Self::from_reverts(
+            account_changeset_cursor
+                .walk_range(range.clone())?
+                .map(|entry| entry.map(|(_, account)| account)),
+            storage_changeset_cursor
+                .walk_range(BlockNumberAddress::range(range))?
+                .map(|entry| entry.map(|(index, storage)| (index.address(), storage))),
+        )
+    }
+
+    /// Initializes [HashedPostState] from the account and storage changes of a block range, in
+    /// block order.
+    ///
+    /// Same as [HashedPostState::from_revert_range], for change sets which are not read from the
+    /// database tables, e.g. because they were moved to snapshots.
+    pub fn from_reverts<E>(
+        account_changes: impl IntoIterator<Item = Result<AccountBeforeTx, E>>,
+        storage_changes: impl IntoIterator<Item = Result<(Address, StorageEntry), E>>,
+    ) -> Result<Self, E> {
+        // Record the value of every account before its first change.
+        let mut accounts = AHashMap::<Address, Option<Account>>::default();
+        for change in account_changes {
+            let AccountBeforeTx { address, info } = change?;
+            accounts.entry(address).or_insert(info);
+        }
+
+        // Record the value of every storage slot before its first change.
+        let mut storages = AHashMap::<Address, AHashMap<B256, U256>>::default();
+        for change in storage_changes {
+            let (address, StorageEntry { key, value }) = change?;
+            storages.entry(address).or_default().entry(key).or_insert(value);
+        }
+
+        let mut this = Self::default();
+        for (address, account) in accounts {
+            let hashed_address = keccak256(address);
+            match account {
+                Some(account) => this.insert_account(hashed_address, account),
+                None => this.insert_destroyed_account(hashed_address),
+            }
+        }
+        for (address, slots) in storages {
+            // slots written after the range start have their previous value recorded, so the
+            // storage is never wiped
+            let mut hashed_storage = HashedStorage::new(false);
+            for (slot, value) in slots {
+                let hashed_slot = keccak256(slot);
+                if value == U256::ZERO {
+                    hashed_storage.insert_zero_valued_slot(hashed_slot);
+                } else {
+                    hashed_storage.insert_non_zero_valued_storage(hashed_slot, value);
+                }
+            }
+            this.insert_hashed_storage(keccak256(address), hashed_storage);
+        }
+
+        Ok(this.sorted())
+    }
+
     /// Sort and return self.
     pub fn sorted(mut self) -> Self {
         self.sort();
@@ -138,6 +234,32 @@ impl HashedPostState {
         self.storages.insert(hashed_address, hashed_storage);
     }
 
+    /// Extends the post state with the accounts and storages of another post state, which take
+    /// precedence.
+    pub fn extend(&mut self, other: Self) {
+        let mut accounts = self.accounts().collect::<AHashMap<_, _>>();
+        accounts.extend(other.accounts());
+
+        self.accounts.clear();
+        self.destroyed_accounts.clear();
+        for (hashed_address, account) in accounts {
+            match account {
+                Some(account) => self.insert_account(hashed_address, account),
+                None => self.insert_destroyed_account(hashed_address),
+            }
+        }
+
+        for (hashed_address, hashed_storage) in other.storages {
+            match self.storages.entry(hashed_address) {
+                Entry::Occupied(mut entry) => entry.get_mut().extend(hashed_storage),
+                Entry::Vacant(entry) => {
+                    entry.insert(hashed_storage);
+                }
+            }
+        }
+        self.sorted = false;
+    }
+
     /// Returns all destroyed accounts.
     pub fn destroyed_accounts(&self) -> AHashSet<B256> {
         self.destroyed_accounts.clone()
@@ -147,6 +269,16 @@ impl HashedPostState {
     /// The prefix sets contain the hashed account and storage keys that have been changed in the
     /// post state.
     pub fn construct_prefix_sets(&self) -> (PrefixSet, AHashMap<B256, PrefixSet>) {
+        let (account_prefix_set, storage_prefix_set) = self.construct_prefix_sets_mut();
+        (
+            account_prefix_set.freeze(),
+            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
+        )
+    }
+
+    /// Construct the mutable (PrefixSetMut)[PrefixSetMut] from hashed post state, see
+    /// [HashedPostState::construct_prefix_sets].
+    pub fn construct_prefix_sets_mut(&self) -> (PrefixSetMut, AHashMap<B256, PrefixSetMut>) {
         // Initialize prefix sets.
         let mut account_prefix_set = PrefixSetMut::default();
         let mut storage_prefix_set: AHashMap<B256, PrefixSetMut> = AHashMap::default();
@@ -172,10 +304,7 @@ impl HashedPostState {
             }
         }
 
-        (
-            account_prefix_set.freeze(),
-            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
-        )
+        (account_prefix_set, storage_prefix_set)
     }
 }
 
@@ -1,37 +1,91 @@
 use crate::{
-    hashed_cursor::{HashedCursorFactory, HashedStorageCursor},
+    hashed_cursor::{
+        HashedCursorFactory, HashedPostState, HashedPostStateCursorFactory, HashedStorageCursor,
+    },
     node_iter::{AccountNode, AccountNodeIter, StorageNode, StorageNodeIter},
     prefix_set::PrefixSetMut,
     trie_cursor::{DatabaseAccountTrieCursor, DatabaseStorageTrieCursor},
     walker::TrieWalker,
     StateRootError, StorageRootError,
 };
+use ahash::AHashMap;
 use alloy_rlp::{BufMut, Encodable};
 use reth_db::{tables, transaction::DbTx};
 use reth_primitives::{
     constants::EMPTY_ROOT_HASH,
     keccak256,
     trie::{AccountProof, HashBuilder, Nibbles, StorageProof, TrieAccount},
-    Address, B256,
+    Address, Bytes, B256,
 };
+use std::collections::BTreeMap;
 
 /// A struct for generating merkle proofs.
 ///
 /// Proof generator adds the target address and slots to the prefix set, enables the proof retainer
 /// on the hash builder and follows the same algorithm as the state root calculator.
 /// See `StateRoot::root` for more info.
+///
+/// Proofs for a hashed state overlay, e.g. a
+/// [HashedPostState](crate::hashed_cursor::HashedPostState) that reverts the state to a past
+/// block, require the changed prefixes of the overlay, so that the trie nodes stored for these
+/// prefixes are recomputed.
 #[derive(Debug)]
 pub struct Proof<'a, TX, H> {
     /// A reference to the database transaction.
     tx: &'a TX,
     /// The factory for hashed cursors.
     hashed_cursor_factory: H,
+    /// A set of account prefixes that have changed.
+    changed_account_prefixes: PrefixSetMut,
+    /// A map containing storage changes with the hashed address as key and a set of storage key
+    /// prefixes as the value.
+    changed_storage_prefixes: AHashMap<B256, PrefixSetMut>,
 }
 
 impl<'a, TX> Proof<'a, TX, &'a TX> {
     /// Create a new [Proof] instance.
     pub fn new(tx: &'a TX) -> Self {
-        Self { tx, hashed_cursor_factory: tx }
+        Self {
+            tx,
+            hashed_cursor_factory: tx,
+            changed_account_prefixes: PrefixSetMut::default(),
+            changed_storage_prefixes: AHashMap::default(),
+        }
+    }
+}
+
+impl<'a, 'b, TX> Proof<'a, TX, HashedPostStateCursorFactory<'a, 'b, TX>> {
+    /// Create a new [Proof] instance for the hashed post state on top of the database state.
+    pub fn overlay(tx: &'a TX, hashed_post_state: &'b HashedPostState) -> Self {
+        let (account_prefixes, storage_prefixes) = hashed_post_state.construct_prefix_sets_mut();
+        Proof::new(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, hashed_post_state))
+            .with_changed_account_prefixes(account_prefixes)
+            .with_changed_storage_prefixes(storage_prefixes)
+    }
+}
+
+impl<'a, TX, H> Proof<'a, TX, H> {
+    /// Set the hashed cursor factory.
+    pub fn with_hashed_cursor_factory<HF>(self, hashed_cursor_factory: HF) -> Proof<'a, TX, HF> {
+        Proof {
+            tx: self.tx,
+            hashed_cursor_factory,
+            changed_account_prefixes: self.changed_account_prefixes,
+            changed_storage_prefixes: self.changed_storage_prefixes,
+        }
+    }
+
+    /// Set the changed account prefixes.
+    pub fn with_changed_account_prefixes(mut self, prefixes: PrefixSetMut) -> Self {
+        self.changed_account_prefixes = prefixes;
+        self
+    }
+
+    /// Set the changed storage prefixes.
+    pub fn with_changed_storage_prefixes(mut self, prefixes: AHashMap<B256, PrefixSetMut>) -> Self {
+        self.changed_storage_prefixes = prefixes;
+        self
     }
 }
 
@@ -55,7 +109,7 @@ where
             DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
 
         // Create the walker.
-        let mut prefix_set = PrefixSetMut::default();
+        let mut prefix_set = self.changed_account_prefixes.clone();
         prefix_set.insert(target_nibbles.clone());
         let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
 
@@ -97,6 +151,93 @@ where
         Ok(account_proof)
     }
 
+    /// Compute the state root and retain the account trie nodes on the paths to the targets.
+    ///
+    /// Targets are either full hashed account keys or partial paths of trie nodes. The retained
+    /// nodes are keyed by their path.
+    pub fn account_multiproof(
+        &self,
+        targets: Vec<Nibbles>,
+    ) -> Result<(B256, BTreeMap<Nibbles, Bytes>), StateRootError> {
+        let hashed_account_cursor = self.hashed_cursor_factory.hashed_account_cursor()?;
+        let trie_cursor =
+            DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
+
+        let mut prefix_set = self.changed_account_prefixes.clone();
+        for target in &targets {
+            prefix_set.insert(target.clone());
+        }
+        let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
+
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets);
+        let mut account_rlp = Vec::with_capacity(128);
+        let mut account_node_iter = AccountNodeIter::new(walker, hashed_account_cursor);
+        while let Some(account_node) = account_node_iter.try_next()? {
+            match account_node {
+                AccountNode::Branch(node) => {
+                    hash_builder.add_branch(node.key, node.value, node.children_are_in_trie);
+                }
+                AccountNode::Leaf(hashed_address, account) => {
+                    let storage_root = self.storage_root(hashed_address)?;
+                    account_rlp.clear();
+                    let account = TrieAccount::from((account, storage_root));
+                    account.encode(&mut account_rlp as &mut dyn BufMut);
+                    hash_builder.add_leaf(Nibbles::unpack(hashed_address), &account_rlp);
+                }
+            }
+        }
+
+        let root = hash_builder.root();
+        Ok((root, hash_builder.take_proofs()))
+    }
+
+    /// Compute the storage root and retain the storage trie nodes on the paths to the targets.
+    ///
+    /// See [Self::account_multiproof] for the targets and the retained nodes.
+    pub fn storage_multiproof(
+        &self,
+        hashed_address: B256,
+        targets: Vec<Nibbles>,
+    ) -> Result<(B256, BTreeMap<Nibbles, Bytes>), StorageRootError> {
+        let mut hashed_storage_cursor = self.hashed_cursor_factory.hashed_storage_cursor()?;
+
+        // short circuit on empty storage
+        if hashed_storage_cursor.is_storage_empty(hashed_address)? {
+            return Ok((EMPTY_ROOT_HASH, BTreeMap::new()))
+        }
+
+        let mut prefix_set =
+            self.changed_storage_prefixes.get(&hashed_address).cloned().unwrap_or_default();
+        for target in &targets {
+            prefix_set.insert(target.clone());
+        }
+        let trie_cursor = DatabaseStorageTrieCursor::new(
+            self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
+            hashed_address,
+        );
+        let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
+
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets);
+        let mut storage_node_iter =
+            StorageNodeIter::new(walker, hashed_storage_cursor, hashed_address);
+        while let Some(node) = storage_node_iter.try_next()? {
+            match node {
+                StorageNode::Branch(node) => {
+                    hash_builder.add_branch(node.key, node.value, node.children_are_in_trie);
+                }
+                StorageNode::Leaf(hashed_slot, value) => {
+                    hash_builder.add_leaf(
+                        Nibbles::unpack(hashed_slot),
+                        alloy_rlp::encode_fixed_size(&value).as_ref(),
+                    );
+                }
+            }
+        }
+
+        let root = hash_builder.root();
+        Ok((root, hash_builder.take_proofs()))
+    }
+
     /// Compute storage root.
     pub fn storage_root(&self, hashed_address: B256) -> Result<B256, StorageRootError> {
         let (storage_root, _) = self.storage_root_with_proofs(hashed_address, &[])?;
@@ -119,7 +260,12 @@ where
         }
 
         let target_nibbles = proofs.iter().map(|p| p.nibbles.clone()).collect::<Vec<_>>();
-        let prefix_set = PrefixSetMut::from(target_nibbles.clone()).freeze();
+        let mut prefix_set =
+            self.changed_storage_prefixes.get(&hashed_address).cloned().unwrap_or_default();
+        for nibbles in &target_nibbles {
+            prefix_set.insert(nibbles.clone());
+        }
+        let prefix_set = prefix_set.freeze();
         let trie_cursor = DatabaseStorageTrieCursor::new(
             self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
             hashed_address,
@@ -164,9 +310,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StateRoot;
+    use crate::{hashed_cursor::HashedStorage, StateRoot};
     use once_cell::sync::Lazy;
-    use reth_db::database::Database;
+    use reth_db::{database::Database, transaction::DbTxMut};
     use reth_interfaces::RethResult;
     use reth_primitives::{Account, Bytes, Chain, ChainSpec, StorageEntry, HOLESKY, MAINNET, U256};
     use reth_provider::{test_utils::create_test_provider_factory, HashingWriter, ProviderFactory};
@@ -289,6 +435,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn testspec_multiproof() {
+        // Create test database and insert genesis accounts.
+        let factory = create_test_provider_factory();
+        insert_genesis(&factory, TEST_SPEC.clone()).unwrap();
+
+        let provider = factory.provider().unwrap();
+        let expected_root = StateRoot::from_tx(provider.tx_ref()).root().unwrap();
+        let proof = Proof::new(provider.tx_ref());
+
+        let address = Address::from_str("0x33f0fc440b8477fcfbe9d0bf8649e7dea9baedb2").unwrap();
+        let target = Nibbles::unpack(keccak256(address));
+        let (root, nodes) =
+            proof.account_multiproof(Vec::from([Nibbles::default(), target])).unwrap();
+        assert_eq!(root, expected_root);
+
+        // the root node is retained at the empty path
+        assert_eq!(keccak256(&nodes[&Nibbles::default()]), root);
+
+        // nodes on the path to the account match the account proof
+        let account_proof = proof.account_proof(address, &[]).unwrap();
+        assert_eq!(nodes.into_values().collect::<Vec<_>>(), account_proof.proof);
+    }
+
     #[test]
     fn testspec_empty_storage_proof() {
         // Create test database and insert genesis accounts.
@@ -438,4 +608,70 @@ mod tests {
         let account_proof = Proof::new(provider.tx_ref()).account_proof(target, &slots).unwrap();
         pretty_assertions::assert_eq!(account_proof, expected);
     }
+
+    #[test]
+    fn overlay_proof_matches_reverted_state() {
+        // Create test database and insert genesis accounts.
+        let factory = create_test_provider_factory();
+        insert_genesis(&factory, TEST_SPEC.clone()).unwrap();
+
+        let target = Address::from_str("0x2031f89b3ea8014eb51a78c316e42af3e0d7695f").unwrap();
+        let changed = Address::from_str("0x62b0dd4aab2b1a0a04e279e2b828791a10755528").unwrap();
+        let created = Address::from_str("0x1000000000000000000000000000000000000000").unwrap();
+        let slot = B256::with_last_byte(1);
+
+        let provider = factory.provider().unwrap();
+        let expected = Proof::new(provider.tx_ref()).account_proof(target, &[slot]).unwrap();
+        let original_target = expected.info.unwrap();
+        let original_changed =
+            provider.tx_ref().get::<tables::HashedAccount>(keccak256(changed)).unwrap().unwrap();
+        drop(provider);
+
+        // Modify the state and recompute the trie from scratch.
+        let provider = factory.provider_rw().unwrap();
+        provider
+            .insert_account_for_hashing([
+                (target, Some(Account { nonce: 1, ..original_target })),
+                (changed, Some(Account { balance: U256::from(1), ..original_changed })),
+                (created, Some(Account { balance: U256::from(10), ..Default::default() })),
+            ])
+            .unwrap();
+        provider
+            .insert_storage_for_hashing([(
+                target,
+                [StorageEntry { key: slot, value: U256::from(5) }],
+            )])
+            .unwrap();
+        provider.tx_ref().clear::<tables::AccountsTrie>().unwrap();
+        provider.tx_ref().clear::<tables::StoragesTrie>().unwrap();
+        let (_, updates) = StateRoot::from_tx(provider.tx_ref()).root_with_updates().unwrap();
+        updates.flush(provider.tx_ref()).unwrap();
+        provider.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let modified = Proof::new(provider.tx_ref()).account_proof(target, &[slot]).unwrap();
+        assert_ne!(modified, expected);
+
+        // Revert the changes with a hashed state overlay.
+        let mut revert = HashedPostState::default();
+        revert.insert_account(keccak256(target), original_target);
+        revert.insert_account(keccak256(changed), original_changed);
+        revert.insert_destroyed_account(keccak256(created));
+        let mut storage = HashedStorage::new(false);
+        storage.insert_zero_valued_slot(keccak256(slot));
+        revert.insert_hashed_storage(keccak256(target), storage);
+        let revert = revert.sorted();
+
+        let (account_prefixes, storage_prefixes) = revert.construct_prefix_sets_mut();
+        let account_proof = Proof::new(provider.tx_ref())
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(
+                provider.tx_ref(),
+                &revert,
+            ))
+            .with_changed_account_prefixes(account_prefixes)
+            .with_changed_storage_prefixes(storage_prefixes)
+            .account_proof(target, &[slot])
+            .unwrap();
+        pretty_assertions::assert_eq!(account_proof, expected);
+    }
 }